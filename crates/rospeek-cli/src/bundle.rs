@@ -0,0 +1,167 @@
+use std::{
+    fs::{self, File},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use rospeek_core::{OpenOptions, RosPeekResult};
+use rospeek_gui::create_reader_with_options;
+
+use crate::{
+    command::DumpCompression,
+    compress::CompressedFile,
+    manifest::{MANIFEST_SUFFIX, bag_files, bag_name, build_manifest, manifest_bag, manifest_path},
+    schema::{SchemaExport, export_schemas},
+};
+
+/// Extension of bundle files.
+pub(crate) const BUNDLE_EXTENSION: &str = ".tar.zst";
+
+/// Directory of a bundle holding the exported message definitions.
+const SCHEMAS_DIR: &str = "schemas";
+
+/// Bundle extracted to be opened, with the message definitions packaged along with its bag.
+pub(crate) struct OpenedBundle {
+    /// Path to the extracted bag file or directory.
+    pub(crate) bag: PathBuf,
+    /// Directory of the exported message definitions, to be searched with `--msg-path`.
+    pub(crate) schemas: PathBuf,
+}
+
+/// Returns whether a path is a bundle written by [`write_bundle`].
+pub(crate) fn is_bundle(path: &Path) -> bool {
+    path.is_file() && path.to_string_lossy().ends_with(BUNDLE_EXTENSION)
+}
+
+/// Returns the default path of the bundle of a bag, named after the bag.
+pub(crate) fn bundle_path(bag: &Path) -> RosPeekResult<PathBuf> {
+    Ok(PathBuf::from(format!(
+        "{}{BUNDLE_EXTENSION}",
+        bag_name(bag)?
+    )))
+}
+
+/// Packages a bag into a zstd-compressed tarball along with the definitions of its message types
+/// and its manifest, so that it can be opened where its packages are not installed.
+///
+/// The tarball holds the bag as `<BAGNAME>`, its manifest as `<BAGNAME>.manifest.json` and the
+/// definitions as `schemas/<package>/msg/<Name>.idl`.
+///
+/// # Arguments
+/// * `bag` - Path to the bag file or directory.
+/// * `output` - Path to the bundle, such as `bundle.tar.zst`.
+/// * `options` - How to open the bag.
+///
+/// # Returns
+/// The exported message definitions.
+pub(crate) fn write_bundle(
+    bag: &Path,
+    output: &Path,
+    options: &OpenOptions,
+) -> RosPeekResult<SchemaExport> {
+    let manifest = build_manifest(bag, options)?;
+    let Some(name) = manifest["bag"].as_str() else {
+        anyhow::bail!("Invalid bag path: {}", bag.display());
+    };
+
+    let schemas = std::env::temp_dir().join(format!("rospeek-bundle-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&schemas);
+    let export = {
+        let reader = create_reader_with_options(bag, options)?;
+        export_schemas(reader.as_ref(), &schemas)?
+    };
+
+    let written = write_tarball(bag, name, &manifest, &schemas, output);
+    let _ = fs::remove_dir_all(&schemas);
+    written.with_context(|| format!("Failed to write {}", output.display()))?;
+
+    Ok(export)
+}
+
+/// Writes the files of a bag, its manifest and the exported message definitions into a bundle.
+fn write_tarball(
+    bag: &Path,
+    name: &str,
+    manifest: &serde_json::Value,
+    schemas: &Path,
+    output: &Path,
+) -> RosPeekResult<()> {
+    let file = CompressedFile::create(output, Some(DumpCompression::Zstd), None)?;
+    let mut builder = tar::Builder::new(file);
+    for (file_name, path) in bag_files(bag)? {
+        let entry = match bag.is_dir() {
+            true => format!("{name}/{file_name}"),
+            false => name.to_string(),
+        };
+        builder.append_path_with_name(&path, entry)?;
+    }
+
+    let data = serde_json::to_vec_pretty(manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs()),
+    );
+    header.set_cksum();
+    builder.append_data(&mut header, manifest_path(bag)?, data.as_slice())?;
+
+    if schemas.is_dir() {
+        builder.append_dir_all(SCHEMAS_DIR, schemas)?;
+    }
+    builder.into_inner()?.finish()
+}
+
+/// Extracts a bundle written by [`write_bundle`] to open its bag.
+///
+/// Bundles are extracted once into the temporary directory, and reused as long as the bundle
+/// file is not modified.
+pub(crate) fn open_bundle(path: &Path) -> RosPeekResult<OpenedBundle> {
+    let dir = extract_dir(path)?;
+    if !dir.is_dir() {
+        let partial = PathBuf::from(format!("{}.partial-{}", dir.display(), std::process::id()));
+        let _ = fs::remove_dir_all(&partial);
+        let decoder = zstd::Decoder::new(File::open(path)?)?;
+        tar::Archive::new(decoder)
+            .unpack(&partial)
+            .with_context(|| format!("Failed to extract {}", path.display()))?;
+        // another process may have extracted the same bundle meanwhile
+        if fs::rename(&partial, &dir).is_err() {
+            let _ = fs::remove_dir_all(&partial);
+        }
+    }
+
+    let manifest = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .find(|path| path.to_string_lossy().ends_with(MANIFEST_SUFFIX))
+        .with_context(|| format!("Bundle has no manifest: {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_reader(File::open(&manifest)?)?;
+    Ok(OpenedBundle {
+        bag: manifest_bag(&manifest, &value)?,
+        schemas: dir.join(SCHEMAS_DIR),
+    })
+}
+
+/// Returns the directory a bundle is extracted into, named after the bundle and the time it was
+/// modified.
+fn extract_dir(path: &Path) -> RosPeekResult<PathBuf> {
+    let path = path.canonicalize()?;
+    let metadata = path.metadata()?;
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    metadata.modified()?.hash(&mut hasher);
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stem = name.trim_end_matches(BUNDLE_EXTENSION);
+    Ok(std::env::temp_dir()
+        .join("rospeek-bundles")
+        .join(format!("{stem}-{:016x}", hasher.finish())))
+}
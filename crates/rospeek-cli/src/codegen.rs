@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::bail;
+use rospeek_core::{FieldType, MessageField, MessageSchema, Primitive, RosPeekResult, TypeName};
+
+use crate::schema::{SchemaResolver, is_builtin};
+
+/// Serde supports fixed-size arrays only up to this length, longer ones become `Vec`.
+const MAX_SERDE_ARRAY: usize = 32;
+
+/// Generates serde-compatible Rust structs for a message type and all of its nested types.
+///
+/// The structs deserialize from the JSON values produced by the CDR decoder.
+///
+/// # Arguments
+/// * `resolver` - The resolver to look up message definitions with.
+/// * `type_name` - Name of ROS message type, such as `foo_msgs/msg/Foo`.
+///
+/// # Returns
+/// The generated Rust source code.
+pub(crate) fn generate_structs(
+    resolver: &SchemaResolver,
+    type_name: &str,
+) -> RosPeekResult<String> {
+    // dependencies first so that the root type comes last
+    let mut schemas = Vec::new();
+    collect_schemas(resolver, type_name, &mut schemas)?;
+
+    // plain message names are used unless two packages define the same one
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+    for schema in schemas.iter() {
+        *occurrences
+            .entry(message_name(&schema.type_name))
+            .or_default() += 1;
+    }
+    let names: BTreeMap<&str, String> = schemas
+        .iter()
+        .map(|schema| {
+            let name = message_name(&schema.type_name);
+            let ident = if occurrences[name] > 1 {
+                let package = TypeName::parse(&schema.type_name).map_or("", |t| t.package);
+                format!("{}{name}", to_pascal_case(package))
+            } else {
+                name.to_string()
+            };
+            (schema.type_name.as_str(), ident)
+        })
+        .collect();
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        "// Generated by `rospeek codegen` from {type_name}. Do not edit.\n\n"
+    ));
+    out.push_str("use serde::{Deserialize, Serialize};\n");
+    for schema in schemas.iter() {
+        out.push_str(&format!("\n/// `{}`\n", schema.type_name));
+        out.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+        out.push_str(&format!(
+            "pub struct {} {{\n",
+            names[schema.type_name.as_str()]
+        ));
+        for field in schema.fields.iter() {
+            out.push_str(&field_decl(field, &names));
+        }
+        out.push_str("}\n");
+    }
+    Ok(out)
+}
+
+fn collect_schemas(
+    resolver: &SchemaResolver,
+    type_name: &str,
+    schemas: &mut Vec<MessageSchema>,
+) -> RosPeekResult<()> {
+    if schemas.iter().any(|s| s.type_name == type_name) {
+        return Ok(());
+    }
+    let Some((schema, _)) = resolver.resolve(type_name) else {
+        bail!("Message definition not found for {type_name}");
+    };
+    for field in schema.fields.iter() {
+        if !is_builtin(field.type_name()) {
+            collect_schemas(resolver, field.type_name(), schemas)?;
+        }
+    }
+    schemas.push(schema);
+    Ok(())
+}
+
+fn field_decl(field: &MessageField, names: &BTreeMap<&str, String>) -> String {
+    let element = rust_type(field.type_name(), names);
+    let ty = match field.field_type {
+        FieldType::Object(_) => element,
+        FieldType::Array(_, n) if n <= MAX_SERDE_ARRAY => format!("[{element}; {n}]"),
+        FieldType::Sequence(_) | FieldType::Array(..) => format!("Vec<{element}>"),
+    };
+
+    match field.name.as_str() {
+        // these keywords cannot be raw identifiers
+        "self" | "super" | "crate" | "Self" => format!(
+            "    #[serde(rename = \"{0}\")]\n    pub {0}_: {ty},\n",
+            field.name
+        ),
+        name if is_keyword(name) => format!("    pub r#{name}: {ty},\n"),
+        name => format!("    pub {name}: {ty},\n"),
+    }
+}
+
+fn rust_type(type_name: &str, names: &BTreeMap<&str, String>) -> String {
+    if type_name == "string" {
+        return "String".to_string();
+    }
+    match Primitive::from_type_name(type_name) {
+        Some(primitive) => match primitive {
+            Primitive::Bool => "bool",
+            Primitive::Octet | Primitive::Uint8 => "u8",
+            Primitive::Char => "char",
+            Primitive::Int8 => "i8",
+            Primitive::Int16 => "i16",
+            Primitive::Uint16 => "u16",
+            Primitive::Int32 => "i32",
+            Primitive::Uint32 => "u32",
+            Primitive::Int64 => "i64",
+            Primitive::Uint64 => "u64",
+            Primitive::Float => "f32",
+            Primitive::Double => "f64",
+        }
+        .to_string(),
+        None => names[type_name].clone(),
+    }
+}
+
+/// Returns the message name of a type name, such as `Foo` for `foo_msgs/msg/Foo`.
+fn message_name(type_name: &str) -> &str {
+    TypeName::parse(type_name).map_or(type_name, |t| t.name)
+}
+
+fn to_pascal_case(s: &str) -> String {
+    s.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn is_keyword(s: &str) -> bool {
+    matches!(
+        s,
+        "as" | "async"
+            | "await"
+            | "break"
+            | "const"
+            | "continue"
+            | "dyn"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "fn"
+            | "for"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "pub"
+            | "ref"
+            | "return"
+            | "static"
+            | "struct"
+            | "trait"
+            | "true"
+            | "type"
+            | "unsafe"
+            | "use"
+            | "where"
+            | "while"
+            | "abstract"
+            | "become"
+            | "box"
+            | "do"
+            | "final"
+            | "gen"
+            | "macro"
+            | "override"
+            | "priv"
+            | "try"
+            | "typeof"
+            | "unsized"
+            | "virtual"
+            | "yield"
+    )
+}
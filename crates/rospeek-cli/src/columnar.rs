@@ -0,0 +1,102 @@
+use std::path::Path;
+
+use datafusion::arrow::{array::RecordBatch, datatypes::SchemaRef};
+use parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, GzipLevel, ZstdLevel},
+    file::properties::WriterProperties,
+};
+use rospeek_core::{RosPeekResult, TIMESTAMP_COLUMN, VALUE_COLUMN, write_arrow_ipc};
+use rospeek_gui::OutputFile;
+
+use crate::command::DumpCompression;
+
+/// Keeps the columns of the given fields, each selecting the column of its path and the columns
+/// nested under it, such as `header.stamp` selecting `header.stamp.sec` and
+/// `header.stamp.nanosec`.
+///
+/// [`TIMESTAMP_COLUMN`] is always kept, as is [`VALUE_COLUMN`] of messages stored as JSON.
+///
+/// # Arguments
+/// * `schema` - The schema of the batches.
+/// * `batches` - The batches to select the columns of.
+/// * `fields` - Dot-separated paths of the fields to keep, or empty to keep every column.
+pub(crate) fn select_columns(
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    fields: &[String],
+) -> RosPeekResult<(SchemaRef, Vec<RecordBatch>)> {
+    if fields.is_empty() || schema.column_with_name(VALUE_COLUMN).is_some() {
+        return Ok((schema, batches));
+    }
+    let mut indices = vec![schema.index_of(TIMESTAMP_COLUMN)?];
+    for field in fields {
+        let prefix = format!("{field}.");
+        let start = indices.len();
+        indices.extend(
+            schema
+                .fields()
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.name() == field || f.name().starts_with(&prefix))
+                .map(|(i, _)| i),
+        );
+        if indices.len() == start {
+            anyhow::bail!("Field not found: {field}");
+        }
+    }
+    let batches = batches
+        .iter()
+        .map(|batch| batch.project(&indices))
+        .collect::<Result<_, _>>()?;
+    Ok((schema.project(&indices)?.into(), batches))
+}
+
+/// Writes record batches into a Parquet file, keeping the types of their columns so that
+/// integers are not read back as floats.
+///
+/// # Arguments
+/// * `path` - The path to the file.
+/// * `schema` - The schema of the batches.
+/// * `batches` - The batches, written in order.
+/// * `compression` - Codec compressing the pages of the file, `None` to write them as is.
+/// * `recipients` - age public keys to encrypt the file for, `None` to write it as is.
+pub(crate) fn write_parquet(
+    path: &Path,
+    schema: SchemaRef,
+    batches: &[RecordBatch],
+    compression: Option<DumpCompression>,
+    recipients: Option<&[String]>,
+) -> RosPeekResult<()> {
+    let compression = match compression {
+        None => Compression::UNCOMPRESSED,
+        Some(DumpCompression::Gzip) => Compression::GZIP(GzipLevel::default()),
+        Some(DumpCompression::Zstd) => Compression::ZSTD(ZstdLevel::default()),
+    };
+    let properties = WriterProperties::builder()
+        .set_compression(compression)
+        .build();
+    let file = OutputFile::create(path, recipients)?;
+    let mut writer = ArrowWriter::try_new(file, schema, Some(properties))?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.into_inner()?.finish()
+}
+
+/// Writes record batches into an uncompressed Arrow IPC file, which readers can memory-map.
+///
+/// # Arguments
+/// * `path` - The path to the file.
+/// * `schema` - The schema of the batches.
+/// * `batches` - The batches, written in order.
+/// * `recipients` - age public keys to encrypt the file for, `None` to write it as is.
+pub(crate) fn write_arrow(
+    path: &Path,
+    schema: &SchemaRef,
+    batches: &[RecordBatch],
+    recipients: Option<&[String]>,
+) -> RosPeekResult<()> {
+    let file = OutputFile::create(path, recipients)?;
+    write_arrow_ipc(file, schema, batches)?.finish()
+}
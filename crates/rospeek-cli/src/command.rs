@@ -1,14 +1,77 @@
-use std::path::PathBuf;
+use std::{ffi::OsString, path::PathBuf};
 
 use clap::{Subcommand, ValueEnum};
+use rospeek_core::{EventRule, StorageType};
 
-/// Output file format for the dump command.
+/// Output format for the schema command.
 #[derive(Debug, Clone, ValueEnum)]
+pub(crate) enum SchemaFormat {
+    /// Indented field tree
+    Text,
+    /// JSON Schema of the decoded JSON
+    JsonSchema,
+}
+
+/// Output file format for the dump command.
+#[derive(Debug, Clone, Copy, ValueEnum)]
 pub(crate) enum DumpFormat {
     /// JSON format
     Json,
     /// CSV format
     Csv,
+    /// Parquet format, with a typed column for each field
+    Parquet,
+    /// Arrow IPC file format, also known as Feather, with a typed column for each field
+    Arrow,
+}
+
+impl DumpFormat {
+    /// Returns the extension of the output files, such as `json`.
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            DumpFormat::Json => "json",
+            DumpFormat::Csv => "csv",
+            DumpFormat::Parquet => "parquet",
+            DumpFormat::Arrow => "arrow",
+        }
+    }
+}
+
+/// Compression of the output file of the dump command.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum DumpCompression {
+    /// gzip (.gz)
+    Gzip,
+    /// Zstandard (.zst)
+    Zstd,
+}
+
+impl DumpCompression {
+    /// Returns the extension appended to the name of compressed files, such as `.zst`.
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            DumpCompression::Gzip => ".gz",
+            DumpCompression::Zstd => ".zst",
+        }
+    }
+}
+
+/// Storage to read bag files as, overriding the detected one.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum StorageFormat {
+    /// SQLite3 (.db3)
+    Db3,
+    /// MCAP (.mcap)
+    Mcap,
+}
+
+impl From<StorageFormat> for StorageType {
+    fn from(format: StorageFormat) -> Self {
+        match format {
+            StorageFormat::Db3 => StorageType::Sqlite3,
+            StorageFormat::Mcap => StorageType::Mcap,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -17,6 +80,40 @@ pub(crate) enum Command {
     Info {
         #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
         bag: PathBuf,
+
+        #[arg(
+            long,
+            help = "Read the storage files of a bag directory and cross-check them against its metadata.yaml"
+        )]
+        verify: bool,
+
+        #[arg(
+            long,
+            help = "Show the compressed and uncompressed sizes of the chunks and topics of MCAP bags"
+        )]
+        detailed: bool,
+
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "Number of namespace levels topics are grouped by (e.g. 1 for /sensing, 2 for /sensing/lidar)"
+        )]
+        depth: usize,
+    },
+
+    /// List topic names, e.g. for shell completions
+    Topics {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(long, help = "Print the message type after each topic name")]
+        types: bool,
+
+        #[arg(
+            long,
+            help = "Read the storage files of a bag directory and cross-check them against its metadata.yaml"
+        )]
+        verify: bool,
     },
 
     /// Show the first N messages of a topic
@@ -32,6 +129,15 @@ pub(crate) enum Command {
 
         #[arg(long, help = "Number of messages to skip after filtering")]
         offset: Option<usize>,
+
+        #[arg(
+            long,
+            value_name = "START..END",
+            value_parser = parse_index_range,
+            conflicts_with_all = ["count", "offset"],
+            help = "Indexes of the messages to show, END being exclusive and optional (e.g. 100..200)"
+        )]
+        range: Option<(usize, Option<usize>)>,
     },
 
     /// Decode CDR-encoded messages and dump them in the specified format
@@ -51,6 +157,29 @@ pub(crate) enum Command {
         )]
         format: DumpFormat,
 
+        #[arg(
+            long,
+            value_enum,
+            help = "Compress the output while writing it, e.g. into <TOPIC>.json.zst, or the pages of Parquet files"
+        )]
+        compress: Option<DumpCompression>,
+
+        #[arg(
+            long,
+            value_name = "SIZE",
+            value_parser = parse_size,
+            help = "Start a new numbered output file past this size before compression (e.g. 1GB)"
+        )]
+        split_size: Option<u64>,
+
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = parse_duration,
+            help = "Start a new numbered output file every this long of messages (e.g. 60s, 5m)"
+        )]
+        split_duration: Option<u64>,
+
         #[arg(long, help = "Timestamp in nanoseconds since which to read messages")]
         since: Option<u64>,
 
@@ -62,8 +191,808 @@ pub(crate) enum Command {
 
         #[arg(long, help = "Number of messages to skip after filtering")]
         offset: Option<usize>,
+
+        #[arg(
+            long,
+            help = "Interpret --since/--until as simulated time published on /clock"
+        )]
+        clock: bool,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Comma-separated field paths to decode (e.g. header.stamp.sec,pose.position.x)"
+        )]
+        fields: Vec<String>,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Lua script whose transform(topic, message) returns the record to dump, or nil to skip it"
+        )]
+        script: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Show decode throughput, pending messages and busy workers on stderr while dumping"
+        )]
+        top: bool,
+
+        #[arg(
+            long,
+            help = "Write integers beyond 2^53, such as nanosecond stamps, as strings to keep their precision"
+        )]
+        large_ints_as_strings: bool,
+
+        #[arg(
+            long,
+            help = "Add roll_deg, pitch_deg and yaw_deg next to the quaternions of orientation and rotation fields"
+        )]
+        euler: bool,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write a JSON report of the messages which fail to decode"
+        )]
+        report: Option<PathBuf>,
+
+        #[arg(
+            long,
+            requires = "recipients",
+            help = "Encrypt the output with age into <OUTPUT>.age"
+        )]
+        encrypt: bool,
+
+        #[arg(
+            long = "recipient",
+            value_name = "AGE_PUBLIC_KEY",
+            help = "age public key (age1...) which can decrypt the output, can be repeated"
+        )]
+        recipients: Vec<String>,
+
+        #[arg(
+            long,
+            help = "Estimate the number of messages and output size without dumping, e.g. to check disk space"
+        )]
+        dry_run: bool,
+    },
+
+    /// Export selected topics and time range into a new bag file, converting between the
+    /// sqlite3 and MCAP storage formats by the extension of the output
+    #[command(visible_aliases = ["filter", "convert"])]
+    Export {
+        #[arg(
+            value_name = "BAGFILE",
+            help = "Path to the [.db3, .mcap] bag file or bag directory"
+        )]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Path to the output bag file, whose extension [.db3, .mcap] selects its storage format"
+        )]
+        output: PathBuf,
+
+        #[arg(
+            short,
+            long = "topic",
+            visible_alias = "topics",
+            help = "Topic name to export, can be repeated (default: all topics)"
+        )]
+        topics: Vec<String>,
+
+        #[arg(
+            short = 'x',
+            long,
+            help = "Topic name to drop, can be repeated, applied after --topic"
+        )]
+        exclude: Vec<String>,
+
+        #[arg(long, help = "Timestamp in nanoseconds since which to export messages")]
+        since: Option<u64>,
+
+        #[arg(long, help = "Timestamp in nanoseconds until which to export messages")]
+        until: Option<u64>,
+
+        #[arg(
+            long,
+            help = "Interpret --since/--until as simulated time published on /clock"
+        )]
+        clock: bool,
+
+        #[arg(
+            long,
+            help = "Drop exact duplicates (same timestamp and payload) of messages"
+        )]
+        dedup: bool,
+
+        #[arg(
+            long,
+            help = "Re-sort messages by timestamp instead of keeping the recorded order"
+        )]
+        sort_by_time: bool,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Lua script whose transform(topic, message) returns nil for messages to drop"
+        )]
+        script: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "PRESET",
+            help = "Anonymize messages with a built-in preset (available: gdpr-basic)"
+        )]
+        redact: Option<String>,
+
+        #[arg(
+            long,
+            help = "Resume an interrupted export into the same .db3 output with the same options, from its checkpoint"
+        )]
+        resume: bool,
+
+        #[arg(
+            long,
+            requires = "recipients",
+            conflicts_with = "resume",
+            help = "Encrypt the output with age into <OUTPUT>.age"
+        )]
+        encrypt: bool,
+
+        #[arg(
+            long = "recipient",
+            value_name = "AGE_PUBLIC_KEY",
+            help = "age public key (age1...) which can decrypt the output, can be repeated"
+        )]
+        recipients: Vec<String>,
+
+        #[arg(
+            long,
+            help = "Estimate the number of messages and output size of each topic without exporting"
+        )]
+        dry_run: bool,
+    },
+
+    /// Check topics for recording glitches such as duplicated messages
+    Check {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long = "topic",
+            help = "Topic name to check, can be repeated (default: all topics)"
+        )]
+        topics: Vec<String>,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Also decode every message, writing a JSON report of those which fail"
+        )]
+        report: Option<PathBuf>,
+    },
+
+    /// Decode a topic and compare it against a golden dump, with a tolerance on numbers
+    CompareDump {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            value_name = "GOLDEN",
+            help = "Path to the golden dump, a JSON array written by dump or JSON Lines"
+        )]
+        golden: PathBuf,
+
+        #[arg(short, long, help = "Topic name to decode (e.g. /tf)")]
+        topic: String,
+
+        #[arg(
+            long,
+            default_value_t = 0.0,
+            help = "Largest absolute difference between numbers considered equal"
+        )]
+        tolerance: f64,
+
+        #[arg(
+            long,
+            help = "Compare integers beyond 2^53 as strings, for dumps written with the same flag"
+        )]
+        large_ints_as_strings: bool,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write a JSON report of every difference"
+        )]
+        report: Option<PathBuf>,
+    },
+
+    /// Extract labeled time intervals where conditions on fields hold, e.g. as dataset annotations
+    Events {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            long = "rule",
+            required = true,
+            value_name = "RULE",
+            help = "Labeled condition, can be repeated (e.g. \"brake: /vehicle/brake.data > 0.8\")"
+        )]
+        rules: Vec<EventRule>,
+
+        #[arg(
+            long,
+            default_value_t = 0.0,
+            help = "Margin by which a value must fall back past the threshold to end an event"
+        )]
+        hysteresis: f64,
+
+        #[arg(
+            long,
+            default_value_t = 0.0,
+            help = "Minimum duration of an event in seconds, shorter ones being dropped"
+        )]
+        min_duration: f64,
+
+        #[arg(short, long, help = "Path to the output JSON file (default: stdout)")]
+        output: Option<PathBuf>,
+    },
+
+    /// Export the messages of topics nearest to each message of a trigger topic as aligned samples
+    Samples {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(short, long, help = "Path to the output directory")]
+        output: PathBuf,
+
+        #[arg(
+            long,
+            help = "Topic whose messages set the timestamps of the samples (e.g. /lidar/points)"
+        )]
+        trigger: String,
+
+        #[arg(
+            short,
+            long = "topic",
+            required = true,
+            help = "Topic name to export, can be repeated (e.g. the trigger, cameras and pose)"
+        )]
+        topics: Vec<String>,
+
+        #[arg(long, default_value_t = 1, help = "Take every N-th trigger message")]
+        stride: usize,
+
+        #[arg(
+            long,
+            help = "Maximum time difference in seconds between a trigger and an exported message"
+        )]
+        max_offset: Option<f64>,
+
+        #[arg(
+            long,
+            help = "Keep samples where some topics have no message within the maximum offset"
+        )]
+        keep_incomplete: bool,
+
+        #[arg(
+            long,
+            help = "Undistort images with the camera_info topic of their namespace (plumb_bob, equidistant)"
+        )]
+        undistort: bool,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Point fields to keep in point clouds, dropping the others and padding (e.g. x,y,z,intensity)"
+        )]
+        fields: Vec<String>,
+
+        #[arg(
+            long,
+            value_name = "LEAF_SIZE",
+            help = "Downsample point clouds keeping a point per voxel of this size in meters"
+        )]
+        voxel_size: Option<f64>,
+    },
+
+    /// Export lidar frames with the camera images and pose of each into a KITTI or nuScenes-like dataset
+    Dataset {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(short, long, help = "Path to the output directory")]
+        output: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Path to the YAML file mapping topics into the dataset"
+        )]
+        config: PathBuf,
+    },
+
+    /// Check a bag against expected topics, rates, gaps and coverage, e.g. to gate recordings in CI
+    Assert {
+        #[arg(
+            value_name = "BAGFILE",
+            help = "Path to the [.db3, .mcap] bag file or bag directory"
+        )]
+        bag: PathBuf,
+
+        #[arg(long, value_name = "FILE", help = "Path to the YAML rules file")]
+        rules: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write a JSON report of the outcome of every rule"
+        )]
+        report: Option<PathBuf>,
+    },
+
+    /// Diagnose the ROS 2 environment used to resolve and decode message types
+    Doctor {
+        #[arg(
+            value_name = "BAGFILE",
+            help = "Path to the [.db3, .mcap] bag file whose topics are also checked"
+        )]
+        bag: Option<PathBuf>,
+    },
+
+    /// Show the frequency and bandwidth of topics
+    Bandwidth {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long = "topic",
+            help = "Topic name to show, can be repeated (default: all topics)"
+        )]
+        topics: Vec<String>,
+    },
+
+    /// Show the PointField layout of a PointCloud2 topic, including its padding
+    Fields {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Topic name of sensor_msgs/msg/PointCloud2 messages"
+        )]
+        topic: String,
+    },
+
+    /// Show the distribution of message sizes of topics
+    Sizes {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long = "topic",
+            help = "Topic name to show, can be repeated (default: all topics)"
+        )]
+        topics: Vec<String>,
+    },
+
+    /// List message types with the topics using them and whether their schemas resolve
+    Types {
+        #[arg(
+            value_name = "BAGFILE",
+            help = "Path to the [.db3, .mcap] bag file or bag directory"
+        )]
+        bag: PathBuf,
+    },
+
+    /// Print the resolved message definition of a topic or a message type
+    Schema {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            required_unless_present = "type_name",
+            conflicts_with = "type_name",
+            help = "Topic name to print the message definition of (e.g. /tf)"
+        )]
+        topic: Option<String>,
+
+        #[arg(
+            long = "type",
+            help = "Message type to print the definition of (e.g. geometry_msgs/msg/TransformStamped)"
+        )]
+        type_name: Option<String>,
+
+        #[arg(
+            short,
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Output format"
+        )]
+        format: SchemaFormat,
+    },
+
+    /// Export message definitions to decode bags on machines without ROS
+    Schemas {
+        #[command(subcommand)]
+        command: SchemasCommand,
+    },
+
+    /// Generate serde-compatible Rust structs from the message definition of a topic
+    Codegen {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(short, long, help = "Topic name to generate structs for (e.g. /tf)")]
+        topic: String,
+
+        #[arg(long, help = "Path to the output .rs file (default: stdout)")]
+        out: Option<PathBuf>,
+    },
+
+    /// List attachments and metadata records of an MCAP file
+    Attachments {
+        #[arg(value_name = "BAGFILE", help = "Path to the .mcap bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "DIR",
+            num_args = 0..=1,
+            default_missing_value = ".",
+            help = "Extract attachments into a directory (default: current directory)"
+        )]
+        extract: Option<PathBuf>,
+    },
+
+    /// Follow a bag which is still being recorded and print new messages
+    Watch {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long = "topic",
+            help = "Topic name to follow, can be repeated (default: all topics)"
+        )]
+        topics: Vec<String>,
+
+        #[arg(
+            long,
+            default_value_t = 1000,
+            help = "Interval in milliseconds between checks for new messages"
+        )]
+        interval: u64,
+
+        #[arg(
+            long,
+            help = "Also print the messages recorded before watching started"
+        )]
+        from_start: bool,
+
+        #[arg(long, help = "Print decoded messages as JSON lines")]
+        decode: bool,
+
+        #[arg(
+            long,
+            help = "Write integers beyond 2^53, such as nanosecond stamps, as strings to keep their precision"
+        )]
+        large_ints_as_strings: bool,
+    },
+
+    /// Subscribe to live topics through zenoh-bridge-ros2dds and print decoded messages
+    Live {
+        #[arg(
+            short,
+            long = "topic",
+            required = true,
+            help = "Topic name to subscribe to, can be repeated"
+        )]
+        topics: Vec<String>,
+
+        #[arg(
+            long = "type",
+            value_name = "TYPE",
+            help = "Message type of the topics, such as sensor_msgs/msg/Imu (default: discovered from the bridge)"
+        )]
+        type_name: Option<String>,
+
+        #[arg(
+            long,
+            help = "Write integers beyond 2^53, such as nanosecond stamps, as strings to keep their precision"
+        )]
+        large_ints_as_strings: bool,
+    },
+
+    /// Write a manifest with file checksums, topics and time range to share along with a bag
+    Manifest {
+        #[arg(
+            value_name = "BAGFILE",
+            help = "Path to the [.db3, .mcap] bag file or bag directory"
+        )]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Output manifest path (default: <BAGNAME>.manifest.json)"
+        )]
+        output: Option<PathBuf>,
+    },
+
+    /// Package a bag with the definitions of its types and its manifest into one archive
+    Bundle {
+        #[arg(
+            value_name = "BAGFILE",
+            help = "Path to the [.db3, .mcap] bag file or bag directory"
+        )]
+        bag: PathBuf,
+
+        #[arg(short, long, help = "Output bundle path (default: <BAGNAME>.tar.zst)")]
+        output: Option<PathBuf>,
+    },
+
+    /// Check a copy of a bag against its manifest
+    VerifyManifest {
+        #[arg(value_name = "MANIFEST", help = "Path to the manifest JSON file")]
+        manifest: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            help = "Path to the bag to check (default: the bag named in the manifest, next to it)"
+        )]
+        bag: Option<PathBuf>,
+    },
+
+    /// Render a QA report of a bag from a template
+    Report {
+        #[arg(
+            value_name = "BAGFILE",
+            help = "Path to the [.db3, .mcap] bag file or bag directory"
+        )]
+        bag: PathBuf,
+
+        #[arg(
+            long,
+            help = "Path to the Tera template, HTML ones being escaped (default: built-in Markdown report)"
+        )]
+        template: Option<PathBuf>,
+
+        #[arg(short, long, help = "Path to the output file (default: stdout)")]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            default_value_t = 3.0,
+            help = "Report intervals longer than this many times the median interval of a topic as gaps"
+        )]
+        gap_factor: f64,
+    },
+
+    /// Run an SQL query over the topics of a bag, each topic being a table named by the topic
+    Sql {
+        #[arg(value_name = "BAGFILE", help = "Path to the [.db3, .mcap] bag file")]
+        bag: PathBuf,
+
+        #[arg(
+            value_name = "QUERY",
+            help = "SQL query (e.g. SELECT avg(\"twist.twist.linear.x\") FROM '/odom')"
+        )]
+        query: String,
+    },
+
+    /// Index bags into a catalog and search it
+    Catalog {
+        #[command(subcommand)]
+        command: CatalogCommand,
     },
 
     /// Spawn GUI application
     App,
+
+    /// Run `rospeek-<SUBCOMMAND>` found on PATH, passing the remaining arguments
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+/// Parses a `START..END` range of message indexes, where `END` may be omitted.
+fn parse_index_range(s: &str) -> Result<(usize, Option<usize>), String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected START..END, got `{s}`"))?;
+    let start = start.parse().map_err(|e| format!("invalid start: {e}"))?;
+    let end = match end {
+        "" => None,
+        end => Some(end.parse().map_err(|e| format!("invalid end: {e}"))?),
+    };
+    if end.is_some_and(|end| end < start) {
+        return Err(format!("end of `{s}` is before its start"));
+    }
+    Ok((start, end))
+}
+
+/// Parses a size in bytes with an optional unit such as `500MB` or `1GiB`, in powers of 1024.
+fn parse_size(s: &str) -> Result<u64, String> {
+    let digits = s.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let scale: u64 = match s[digits.len()..].to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        unit => return Err(format!("unknown size unit `{unit}`")),
+    };
+    let value: f64 = digits
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid size `{s}`: {e}"))?;
+    if value <= 0.0 {
+        return Err(format!("size `{s}` must be positive"));
+    }
+    Ok((value * scale as f64) as u64)
+}
+
+/// Parses a duration into nanoseconds with an optional unit among `ms`, `s`, `m` and `h`,
+/// seconds by default.
+fn parse_duration(s: &str) -> Result<u64, String> {
+    let digits = s.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let scale = match &s[digits.len()..] {
+        "ms" => 1e6,
+        "" | "s" => 1e9,
+        "m" => 60e9,
+        "h" => 3600e9,
+        unit => return Err(format!("unknown duration unit `{unit}`")),
+    };
+    let value: f64 = digits
+        .trim()
+        .parse()
+        .map_err(|e| format!("invalid duration `{s}`: {e}"))?;
+    if value <= 0.0 {
+        return Err(format!("duration `{s}` must be positive"));
+    }
+    Ok((value * scale) as u64)
+}
+
+impl Command {
+    /// Returns the bag the command reads, if any, so that bundles can be replaced by their bag.
+    pub(crate) fn bag_mut(&mut self) -> Option<&mut PathBuf> {
+        match self {
+            Command::Info { bag, .. }
+            | Command::Topics { bag, .. }
+            | Command::Show { bag, .. }
+            | Command::Dump { bag, .. }
+            | Command::Export { bag, .. }
+            | Command::Check { bag, .. }
+            | Command::CompareDump { bag, .. }
+            | Command::Assert { bag, .. }
+            | Command::Events { bag, .. }
+            | Command::Samples { bag, .. }
+            | Command::Dataset { bag, .. }
+            | Command::Bandwidth { bag, .. }
+            | Command::Fields { bag, .. }
+            | Command::Sizes { bag, .. }
+            | Command::Types { bag, .. }
+            | Command::Schema { bag, .. }
+            | Command::Codegen { bag, .. }
+            | Command::Attachments { bag, .. }
+            | Command::Watch { bag, .. }
+            | Command::Manifest { bag, .. }
+            | Command::Bundle { bag, .. }
+            | Command::Report { bag, .. }
+            | Command::Sql { bag, .. } => Some(bag),
+            Command::Schemas {
+                command: SchemasCommand::Export { bag, .. },
+            } => Some(bag),
+            Command::Doctor { bag } | Command::VerifyManifest { bag, .. } => bag.as_mut(),
+            Command::Catalog { .. }
+            | Command::Live { .. }
+            | Command::App
+            | Command::External(_) => None,
+        }
+    }
+
+    /// Returns the topic names given to the command, so that aliases can be resolved in place.
+    pub(crate) fn topics_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Command::Show { topic, .. }
+            | Command::Dump { topic, .. }
+            | Command::CompareDump { topic, .. }
+            | Command::Fields { topic, .. }
+            | Command::Codegen { topic, .. } => vec![topic],
+            Command::Schema { topic, .. } => topic.iter_mut().collect(),
+            Command::Export {
+                topics, exclude, ..
+            } => topics.iter_mut().chain(exclude.iter_mut()).collect(),
+            Command::Check { topics, .. }
+            | Command::Bandwidth { topics, .. }
+            | Command::Sizes { topics, .. }
+            | Command::Watch { topics, .. }
+            | Command::Live { topics, .. } => topics.iter_mut().collect(),
+            Command::Events { rules, .. } => rules.iter_mut().map(|rule| &mut rule.topic).collect(),
+            Command::Samples {
+                trigger, topics, ..
+            } => std::iter::once(trigger).chain(topics.iter_mut()).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Subcommands of the schemas command.
+#[derive(Subcommand)]
+pub(crate) enum SchemasCommand {
+    /// Write the IDL definition of every type of a bag into a directory tree for --msg-path
+    Export {
+        #[arg(
+            value_name = "BAGFILE",
+            help = "Path to the [.db3, .mcap] bag file or bag directory"
+        )]
+        bag: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = "schemas",
+            help = "Directory to write <package>/msg/<Name>.idl files into"
+        )]
+        output: PathBuf,
+    },
+}
+
+/// Subcommands of the catalog command.
+#[derive(Subcommand)]
+pub(crate) enum CatalogCommand {
+    /// Index every bag under a directory tree
+    Add {
+        #[arg(value_name = "DIR", help = "Directory scanned for bags recursively")]
+        dir: PathBuf,
+
+        #[arg(
+            long,
+            help = "Path to the catalog (default: $ROSPEEK_CATALOG or ~/.local/share/rospeek/catalog.db3)"
+        )]
+        catalog: Option<PathBuf>,
+    },
+
+    /// Find indexed bags by topic, type and start time
+    Search {
+        #[arg(
+            short,
+            long,
+            help = "Topic the bag must contain, with * and ? wildcards (e.g. /sensing/radar/*)"
+        )]
+        topic: Option<String>,
+
+        #[arg(
+            long = "type",
+            help = "Message type the bag must contain, with * and ? wildcards (e.g. sensor_msgs/msg/*)"
+        )]
+        type_name: Option<String>,
+
+        #[arg(
+            long,
+            help = "Only bags starting at or after this UTC time (e.g. 2024-01-01 or 2024-01-01T12:00:00)"
+        )]
+        after: Option<String>,
+
+        #[arg(long, help = "Only bags starting before this UTC time")]
+        before: Option<String>,
+
+        #[arg(
+            long,
+            help = "Path to the catalog (default: $ROSPEEK_CATALOG or ~/.local/share/rospeek/catalog.db3)"
+        )]
+        catalog: Option<PathBuf>,
+    },
 }
@@ -0,0 +1,74 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use flate2::write::GzEncoder;
+use rospeek_core::RosPeekResult;
+use rospeek_gui::OutputFile;
+
+use crate::command::DumpCompression;
+
+/// Compression level of zstd, its default which compresses about as fast as the dump is written.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Output file compressing what is written to it on the fly, so that large dumps never hit the
+/// disk uncompressed, and encrypting it after compression if requested.
+pub(crate) enum CompressedFile {
+    Plain(OutputFile),
+    Gzip(GzEncoder<OutputFile>),
+    Zstd(zstd::Encoder<'static, OutputFile>),
+}
+
+impl CompressedFile {
+    /// Creates a file, compressed with `compression` if any.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the file, including the extension of the compression.
+    /// * `compression` - The compression of the content, `None` to write it as is.
+    /// * `recipients` - age public keys to encrypt the file for, `None` to write it as is.
+    pub(crate) fn create(
+        path: &Path,
+        compression: Option<DumpCompression>,
+        recipients: Option<&[String]>,
+    ) -> RosPeekResult<Self> {
+        let file = OutputFile::create(path, recipients)?;
+        Ok(match compression {
+            None => CompressedFile::Plain(file),
+            Some(DumpCompression::Gzip) => {
+                CompressedFile::Gzip(GzEncoder::new(file, flate2::Compression::default()))
+            }
+            Some(DumpCompression::Zstd) => {
+                CompressedFile::Zstd(zstd::Encoder::new(file, ZSTD_LEVEL)?)
+            }
+        })
+    }
+
+    /// Writes the end of the compressed and encrypted streams and flushes the file.
+    pub(crate) fn finish(self) -> RosPeekResult<()> {
+        let file = match self {
+            CompressedFile::Plain(file) => file,
+            CompressedFile::Gzip(encoder) => encoder.finish()?,
+            CompressedFile::Zstd(encoder) => encoder.finish()?,
+        };
+        file.finish()
+    }
+}
+
+impl Write for CompressedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            CompressedFile::Plain(file) => file.write(buf),
+            CompressedFile::Gzip(encoder) => encoder.write(buf),
+            CompressedFile::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            CompressedFile::Plain(file) => file.flush(),
+            CompressedFile::Gzip(encoder) => encoder.flush(),
+            CompressedFile::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
@@ -0,0 +1,574 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Cursor, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use image::{DynamicImage, ImageReader};
+use rospeek_core::{
+    BagReader, CameraInfo, DatasetConfig, DatasetLayout, OpenOptions, PointCloud, Pose, RawMessage,
+    RosPeekResult, StorageType, TriggerSample, UndistortMap, sample_at_triggers,
+};
+use rospeek_gui::{
+    create_reader_with_options,
+    images::{ImageMessage, undistort},
+};
+use rospeek_mcap::McapReader;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+/// Name of the directory of the nuScenes tables.
+const NUSCENES_VERSION: &str = "v1.0-rospeek";
+
+/// Counts of a dataset export.
+pub(crate) struct DatasetSummary {
+    pub(crate) written: usize,
+    /// Samples left out because some topics had no message close enough to the lidar frame.
+    pub(crate) skipped: usize,
+}
+
+/// Writes the samples of a dataset in a layout.
+trait DatasetWriter {
+    /// Writes a sample whose messages are ordered as [`DatasetConfig::topics`].
+    fn write(&mut self, index: usize, sample: &TriggerSample) -> RosPeekResult<()>;
+
+    /// Writes the files describing the whole dataset.
+    fn finish(&mut self) -> RosPeekResult<()>;
+}
+
+/// Exports a bag into a dataset folder structure, with a sample at each lidar frame.
+///
+/// # Arguments
+/// * `bag` - Path to the bag.
+/// * `options` - Options to open the bag with.
+/// * `config` - Mapping of the topics into the dataset.
+/// * `output` - Path to the output directory.
+pub(crate) fn export_dataset(
+    bag: &Path,
+    options: &OpenOptions,
+    config: &DatasetConfig,
+    output: &Path,
+) -> RosPeekResult<DatasetSummary> {
+    let reader = create_reader_with_options(bag, options)?;
+    let calibrations = config
+        .cameras
+        .iter()
+        .map(|camera| {
+            calibration(
+                reader.as_ref(),
+                bag,
+                options,
+                camera.camera_info.as_deref(),
+                camera.calibration.as_deref(),
+            )
+            .with_context(|| format!("Failed to read the calibration of {}", camera.name))
+        })
+        .collect::<RosPeekResult<Vec<_>>>()?;
+    let maps = config
+        .cameras
+        .iter()
+        .zip(calibrations.iter())
+        .map(|(camera, info)| match config.undistort {
+            true => info
+                .undistort_map()
+                .map(Some)
+                .with_context(|| format!("Failed to undistort {}", camera.name)),
+            false => Ok(None),
+        })
+        .collect::<RosPeekResult<Vec<_>>>()?;
+    let topics = config.topics();
+    let type_names = topics
+        .iter()
+        .map(|topic| type_name(reader.as_ref(), topic))
+        .collect::<RosPeekResult<Vec<_>>>()?;
+
+    let mut writer: Box<dyn DatasetWriter> = match config.layout {
+        DatasetLayout::Kitti => Box::new(KittiWriter::new(
+            config,
+            &calibrations,
+            maps,
+            type_names,
+            output,
+        )?),
+        DatasetLayout::Nuscenes => {
+            let log = bag
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Box::new(NuscenesWriter::new(
+                config,
+                &calibrations,
+                maps,
+                type_names,
+                log,
+                output,
+            )?)
+        }
+    };
+
+    let mut written = 0;
+    let mut skipped = 0;
+    sample_at_triggers(
+        reader.as_ref(),
+        &config.lidar.topic,
+        &topics,
+        &config.sample_options(),
+        |sample| {
+            if !sample.is_complete() {
+                skipped += 1;
+                return Ok(());
+            }
+            writer
+                .write(written, &sample)
+                .with_context(|| format!("Failed to write the sample at {}", sample.timestamp))?;
+            written += 1;
+            Ok(())
+        },
+    )?;
+    writer.finish()?;
+    Ok(DatasetSummary { written, skipped })
+}
+
+/// Decodes the image of a camera, undistorting it with a map.
+fn camera_image(image: &ImageMessage, map: Option<&UndistortMap>) -> RosPeekResult<DynamicImage> {
+    let image = image.decode()?;
+    match map {
+        Some(map) => undistort(&image, map),
+        None => Ok(image),
+    }
+}
+
+/// Reads the calibration of a camera from the first message of its camera info topic, or from a
+/// calibration file attached to an MCAP bag.
+fn calibration(
+    reader: &dyn BagReader,
+    bag: &Path,
+    options: &OpenOptions,
+    camera_info: Option<&str>,
+    attachment: Option<&str>,
+) -> RosPeekResult<CameraInfo> {
+    if let Some(topic) = camera_info {
+        let message = reader
+            .read_messages_by_index(topic, 0, 1)?
+            .pop()
+            .with_context(|| format!("No message in {topic}"))?;
+        return CameraInfo::from_cdr(&message.data);
+    }
+    let name = attachment.context("Missing camera_info topic or calibration attachment")?;
+    if options.storage.or_else(|| StorageType::detect(bag)) != Some(StorageType::Mcap) {
+        anyhow::bail!("Attachments are only supported for MCAP files");
+    }
+    let attachment = McapReader::open(bag)?
+        .attachments()?
+        .into_iter()
+        .find(|a| a.name == name)
+        .with_context(|| format!("Attachment not found: {name}"))?;
+    CameraInfo::from_calibration_yaml(std::str::from_utf8(&attachment.data)?)
+}
+
+fn type_name(reader: &dyn BagReader, topic: &str) -> RosPeekResult<String> {
+    reader
+        .topics()?
+        .into_iter()
+        .find(|t| t.name == topic)
+        .map(|t| t.type_name)
+        .with_context(|| format!("Topic not found: {topic}"))
+}
+
+/// Returns the `x`, `y`, `z` and `intensity` of each point, followed by the `ring` if requested,
+/// missing fields reading as zero.
+fn point_values(cloud: &PointCloud, with_ring: bool) -> RosPeekResult<Vec<u8>> {
+    let field = |name: &str| cloud.field(name);
+    let (Some(x), Some(y), Some(z)) = (field("x"), field("y"), field("z")) else {
+        anyhow::bail!("Point cloud has no x, y and z fields");
+    };
+    let mut fields = vec![Some(x), Some(y), Some(z), field("intensity")];
+    if with_ring {
+        fields.push(field("ring"));
+    }
+
+    let mut bytes = Vec::with_capacity(cloud.len() * fields.len() * 4);
+    for index in 0..cloud.len() {
+        for field in fields.iter() {
+            let value = field.map_or(0.0, |field| cloud.value(index, field)) as f32;
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decodes a lidar frame, downsampling it with a voxel grid of a leaf size.
+fn lidar_cloud(data: &[u8], voxel_size: Option<f64>) -> RosPeekResult<PointCloud> {
+    let cloud = PointCloud::from_cdr(data)?;
+    match voxel_size {
+        Some(leaf_size) => cloud.voxel_downsample(leaf_size),
+        None => Ok(cloud),
+    }
+}
+
+fn create_file(path: &Path) -> RosPeekResult<BufWriter<File>> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    Ok(BufWriter::new(file))
+}
+
+fn format_row(values: &[f64]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Writes numbered frames per sensor directory, as the KITTI object and raw datasets:
+///
+/// ```text
+/// velodyne/000000.bin   float32 x, y, z and intensity of each point
+/// image_2/000000.png
+/// calib/000000.txt      P0.. of the cameras, R0_rect and Tr_velo_to_cam
+/// timestamps.txt        lidar timestamp in nanoseconds of each frame
+/// poses.txt             row-major 3x4 pose of each frame, with a pose topic
+/// ```
+struct KittiWriter {
+    output: PathBuf,
+    lidar: String,
+    cameras: Vec<String>,
+    voxel_size: Option<f64>,
+    /// Undistortion of each camera, if the dataset is undistorted.
+    maps: Vec<Option<UndistortMap>>,
+    type_names: Vec<String>,
+    calib: String,
+    timestamps: BufWriter<File>,
+    poses: Option<BufWriter<File>>,
+}
+
+impl KittiWriter {
+    fn new(
+        config: &DatasetConfig,
+        calibrations: &[CameraInfo],
+        maps: Vec<Option<UndistortMap>>,
+        type_names: Vec<String>,
+        output: &Path,
+    ) -> RosPeekResult<Self> {
+        let lidar = config.lidar_name().to_string();
+        let cameras: Vec<String> = config.cameras.iter().map(|c| c.name.clone()).collect();
+        for dir in cameras
+            .iter()
+            .map(String::as_str)
+            .chain([lidar.as_str(), "calib"])
+        {
+            std::fs::create_dir_all(output.join(dir))?;
+        }
+
+        let mut calib = String::new();
+        for (i, info) in calibrations.iter().enumerate() {
+            calib += &format!("P{i}: {}\n", format_row(&info.p));
+        }
+        if let Some(info) = calibrations.first() {
+            calib += &format!("R0_rect: {}\n", format_row(&info.r));
+        }
+        if let Some(transform) = &config.lidar_to_camera {
+            calib += &format!("Tr_velo_to_cam: {}\n", format_row(transform));
+        }
+
+        Ok(Self {
+            output: output.to_path_buf(),
+            lidar,
+            cameras,
+            voxel_size: config.voxel_size,
+            maps,
+            type_names,
+            calib,
+            timestamps: create_file(&output.join("timestamps.txt"))?,
+            poses: config
+                .pose
+                .as_ref()
+                .map(|_| create_file(&output.join("poses.txt")))
+                .transpose()?,
+        })
+    }
+}
+
+impl DatasetWriter for KittiWriter {
+    fn write(&mut self, index: usize, sample: &TriggerSample) -> RosPeekResult<()> {
+        let messages: Vec<&RawMessage> = sample.messages.iter().flatten().collect();
+        let name = format!("{index:06}");
+
+        let cloud = lidar_cloud(&messages[0].data, self.voxel_size)?;
+        let path = self.output.join(&self.lidar).join(format!("{name}.bin"));
+        std::fs::write(path, point_values(&cloud, false)?)?;
+
+        for (i, camera) in self.cameras.iter().enumerate() {
+            let image = ImageMessage::from_cdr(&self.type_names[i + 1], &messages[i + 1].data)?;
+            let path = self.output.join(camera).join(format!("{name}.png"));
+            camera_image(&image, self.maps[i].as_ref())?.save(path)?;
+        }
+
+        let path = self.output.join("calib").join(format!("{name}.txt"));
+        std::fs::write(path, &self.calib)?;
+        writeln!(self.timestamps, "{}", sample.timestamp)?;
+
+        if let Some(poses) = self.poses.as_mut() {
+            let pose = Pose::from_cdr(
+                self.type_names.last().map_or("", String::as_str),
+                &messages[messages.len() - 1].data,
+            )?;
+            let rotation = pose.rotation_matrix();
+            let matrix: Vec<f64> = (0..3)
+                .flat_map(|row| {
+                    let [a, b, c] = rotation[row];
+                    [a, b, c, pose.position[row]]
+                })
+                .collect();
+            writeln!(poses, "{}", format_row(&matrix))?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> RosPeekResult<()> {
+        self.timestamps.flush()?;
+        if let Some(poses) = self.poses.as_mut() {
+            poses.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes sensor data under `samples/<channel>` described by the JSON tables of nuScenes, so
+/// that the dataset can be loaded by the nuScenes devkit without annotations.
+struct NuscenesWriter {
+    output: PathBuf,
+    log: String,
+    /// Channels of the lidar and the cameras.
+    channels: Vec<String>,
+    voxel_size: Option<f64>,
+    /// Undistortion of each camera, if the dataset is undistorted.
+    maps: Vec<Option<UndistortMap>>,
+    type_names: Vec<String>,
+    has_pose: bool,
+    samples: Vec<Value>,
+    sample_data: Vec<Value>,
+    ego_poses: Vec<Value>,
+    calibrated_sensors: Vec<Value>,
+    sensors: Vec<Value>,
+}
+
+impl NuscenesWriter {
+    fn new(
+        config: &DatasetConfig,
+        calibrations: &[CameraInfo],
+        maps: Vec<Option<UndistortMap>>,
+        type_names: Vec<String>,
+        log: String,
+        output: &Path,
+    ) -> RosPeekResult<Self> {
+        let channels: Vec<String> = std::iter::once(config.lidar_name().to_string())
+            .chain(config.cameras.iter().map(|c| c.name.clone()))
+            .collect();
+        for channel in channels.iter() {
+            std::fs::create_dir_all(output.join("samples").join(channel))?;
+        }
+        std::fs::create_dir_all(output.join(NUSCENES_VERSION))?;
+
+        let mut sensors = Vec::new();
+        let mut calibrated_sensors = Vec::new();
+        for (i, channel) in channels.iter().enumerate() {
+            let (modality, intrinsic) = match i {
+                0 => ("lidar", json!([])),
+                i => {
+                    let intrinsic = match config.undistort {
+                        // undistorted images are described by the projection matrix
+                        true => {
+                            let p = calibrations[i - 1].p;
+                            json!([&p[0..3], &p[4..7], &p[8..11]])
+                        }
+                        false => {
+                            let k = calibrations[i - 1].k;
+                            json!([&k[0..3], &k[3..6], &k[6..9]])
+                        }
+                    };
+                    ("camera", intrinsic)
+                }
+            };
+            sensors.push(json!({
+                "token": token(&["sensor", channel]),
+                "channel": channel,
+                "modality": modality,
+            }));
+            // extrinsics are not recorded in bags, so sensors sit at the ego origin
+            calibrated_sensors.push(json!({
+                "token": token(&["calibrated_sensor", channel]),
+                "sensor_token": token(&["sensor", channel]),
+                "translation": [0.0, 0.0, 0.0],
+                "rotation": [1.0, 0.0, 0.0, 0.0],
+                "camera_intrinsic": intrinsic,
+            }));
+        }
+
+        Ok(Self {
+            output: output.to_path_buf(),
+            log,
+            channels,
+            voxel_size: config.voxel_size,
+            maps,
+            type_names,
+            has_pose: config.pose.is_some(),
+            samples: Vec::new(),
+            sample_data: Vec::new(),
+            ego_poses: Vec::new(),
+            calibrated_sensors,
+            sensors,
+        })
+    }
+
+    fn write_table(&self, name: &str, rows: &[Value]) -> RosPeekResult<()> {
+        let path = self
+            .output
+            .join(NUSCENES_VERSION)
+            .join(format!("{name}.json"));
+        serde_json::to_writer_pretty(create_file(&path)?, rows)?;
+        Ok(())
+    }
+}
+
+impl DatasetWriter for NuscenesWriter {
+    fn write(&mut self, index: usize, sample: &TriggerSample) -> RosPeekResult<()> {
+        let messages: Vec<&RawMessage> = sample.messages.iter().flatten().collect();
+        let index_str = index.to_string();
+        let sample_token = token(&["sample", &index_str]);
+        let ego_pose_token = token(&["ego_pose", &index_str]);
+
+        let pose = match self.has_pose {
+            true => Pose::from_cdr(
+                self.type_names.last().map_or("", String::as_str),
+                &messages[messages.len() - 1].data,
+            )?,
+            false => Pose::default(),
+        };
+        let [x, y, z, w] = pose.orientation;
+        self.ego_poses.push(json!({
+            "token": ego_pose_token,
+            "timestamp": sample.timestamp / 1000,
+            "translation": pose.position,
+            "rotation": [w, x, y, z],
+        }));
+
+        for (i, channel) in self.channels.iter().enumerate() {
+            let message = messages[i];
+            let stem = format!("{}__{channel}__{}", self.log, message.timestamp / 1000);
+            let (filename, format, width, height) = if i == 0 {
+                let cloud = lidar_cloud(&message.data, self.voxel_size)?;
+                let filename = format!("samples/{channel}/{stem}.pcd.bin");
+                std::fs::write(self.output.join(&filename), point_values(&cloud, true)?)?;
+                (filename, "pcd", 0, 0)
+            } else {
+                let filename = format!("samples/{channel}/{stem}.jpg");
+                let path = self.output.join(&filename);
+                let (width, height) =
+                    match ImageMessage::from_cdr(&self.type_names[i], &message.data)? {
+                        // JPEG images are kept as recorded rather than encoded again
+                        ImageMessage::Compressed { format, data }
+                            if format.contains("jpeg") && self.maps[i - 1].is_none() =>
+                        {
+                            std::fs::write(&path, data)?;
+                            ImageReader::new(Cursor::new(data))
+                                .with_guessed_format()?
+                                .into_dimensions()?
+                        }
+                        image => {
+                            let image = camera_image(&image, self.maps[i - 1].as_ref())?.to_rgb8();
+                            image.save(&path)?;
+                            image.dimensions()
+                        }
+                    };
+                (filename, "jpg", width, height)
+            };
+
+            self.sample_data.push(json!({
+                "token": token(&["sample_data", channel, &index_str]),
+                "sample_token": sample_token,
+                "ego_pose_token": ego_pose_token,
+                "calibrated_sensor_token": token(&["calibrated_sensor", channel]),
+                "timestamp": message.timestamp / 1000,
+                "fileformat": format,
+                "is_key_frame": true,
+                "height": height,
+                "width": width,
+                "filename": filename,
+                "prev": neighbor(index.checked_sub(1), |i| token(&["sample_data", channel, &i])),
+                "next": neighbor(Some(index + 1), |i| token(&["sample_data", channel, &i])),
+            }));
+        }
+
+        self.samples.push(json!({
+            "token": sample_token,
+            "timestamp": sample.timestamp / 1000,
+            "scene_token": token(&["scene"]),
+            "prev": neighbor(index.checked_sub(1), |i| token(&["sample", &i])),
+            "next": neighbor(Some(index + 1), |i| token(&["sample", &i])),
+        }));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> RosPeekResult<()> {
+        // the last records were linked to samples which do not exist
+        if let Some(last) = self.samples.last_mut() {
+            last["next"] = json!("");
+        }
+        let channels = self.channels.len();
+        for row in self.sample_data.iter_mut().rev().take(channels) {
+            row["next"] = json!("");
+        }
+
+        let first = self.samples.first().map(|s| s["token"].clone());
+        let last = self.samples.last().map(|s| s["token"].clone());
+        let scene = json!({
+            "token": token(&["scene"]),
+            "log_token": token(&["log"]),
+            "nbr_samples": self.samples.len(),
+            "first_sample_token": first.unwrap_or_default(),
+            "last_sample_token": last.unwrap_or_default(),
+            "name": self.log,
+            "description": "",
+        });
+        let log = json!({
+            "token": token(&["log"]),
+            "logfile": self.log,
+            "vehicle": "",
+            "date_captured": "",
+            "location": "",
+        });
+
+        self.write_table("scene", &[scene])?;
+        self.write_table("log", &[log])?;
+        self.write_table("sample", &self.samples)?;
+        self.write_table("sample_data", &self.sample_data)?;
+        self.write_table("ego_pose", &self.ego_poses)?;
+        self.write_table("sensor", &self.sensors)?;
+        self.write_table("calibrated_sensor", &self.calibrated_sensors)?;
+        // the devkit loads every table, so the annotation ones are written empty
+        for name in [
+            "category",
+            "attribute",
+            "visibility",
+            "instance",
+            "sample_annotation",
+            "map",
+        ] {
+            self.write_table(name, &[])?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the token of a neighboring record by its index, empty without one.
+fn neighbor(index: Option<usize>, token: impl Fn(String) -> String) -> String {
+    index.map(|i| token(i.to_string())).unwrap_or_default()
+}
+
+/// Returns a token derived from the parts of a record name, stable across exports.
+fn token(parts: &[&str]) -> String {
+    let hash = Sha256::digest(parts.join("/").as_bytes());
+    hash[..16].iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+use rospeek_core::{
+    BagReader, CdrDecoder, CdrEncoder, DecodePlan, Endianness, MessageSchema, OpenOptions,
+    RosPeekResult, TypeName, unsupported_encapsulation,
+};
+use rospeek_gui::create_reader_with_options;
+use serde_json::json;
+
+/// Type decoded to check that schemas resolve, whose dependencies are installed with ROS 2.
+const SAMPLE_TYPE: &str = "std_msgs/msg/Header";
+
+/// IDL types which cannot be decoded yet.
+const UNSUPPORTED_TYPES: &[&str] = &["wstring", "wchar"];
+
+/// Number of examples listed in a diagnosis.
+const MAX_EXAMPLES: usize = 5;
+
+/// Severity of a diagnosis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Status::Ok => write!(f, "OK"),
+            Status::Warn => write!(f, "WARN"),
+            Status::Fail => write!(f, "NG"),
+        }
+    }
+}
+
+/// Result of a check of the environment.
+#[derive(Debug, Clone)]
+pub(crate) struct Diagnosis {
+    pub(crate) name: &'static str,
+    pub(crate) status: Status,
+    pub(crate) detail: String,
+    /// Suggested fix if the check did not pass.
+    pub(crate) fix: Option<String>,
+}
+
+impl Diagnosis {
+    fn ok(name: &'static str, detail: String) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail,
+            fix: None,
+        }
+    }
+
+    fn problem(name: &'static str, status: Status, detail: String, fix: &str) -> Self {
+        Self {
+            name,
+            status,
+            detail,
+            fix: Some(fix.to_string()),
+        }
+    }
+}
+
+/// Checks that message schemas can be resolved and decoded, and optionally that a bag can be
+/// decoded in this environment.
+///
+/// # Arguments
+/// * `bag` - Optional path to a bag whose topics are checked.
+/// * `options` - How to open the bag.
+pub(crate) fn run_doctor(
+    bag: Option<&Path>,
+    options: &OpenOptions,
+) -> RosPeekResult<Vec<Diagnosis>> {
+    let prefixes: Vec<PathBuf> = std::env::var("AMENT_PREFIX_PATH")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    let idls = find_idls(&prefixes);
+
+    let mut diagnoses = vec![
+        check_prefixes(&prefixes),
+        check_packages(&idls),
+        check_sample_decode(),
+        check_unsupported_types(&idls),
+    ];
+    if let Some(bag) = bag {
+        let reader = create_reader_with_options(bag, options)?;
+        diagnoses.extend(check_bag(reader.as_ref())?);
+    }
+    Ok(diagnoses)
+}
+
+fn check_prefixes(prefixes: &[PathBuf]) -> Diagnosis {
+    const NAME: &str = "AMENT_PREFIX_PATH";
+    if prefixes.is_empty() {
+        return Diagnosis::problem(
+            NAME,
+            Status::Fail,
+            "not set".to_string(),
+            "Source ROS 2 and your workspace, e.g. `source /opt/ros/$ROS_DISTRO/setup.bash`",
+        );
+    }
+    let missing: Vec<_> = prefixes.iter().filter(|p| !p.is_dir()).collect();
+    if !missing.is_empty() {
+        return Diagnosis::problem(
+            NAME,
+            Status::Warn,
+            format!(
+                "{} of {} prefixes do not exist: {}",
+                missing.len(),
+                prefixes.len(),
+                join_examples(missing.iter().map(|p| p.display()))
+            ),
+            "Rebuild or re-source the workspaces which were moved or deleted",
+        );
+    }
+    Diagnosis::ok(NAME, format!("{} prefixes", prefixes.len()))
+}
+
+fn check_packages(idls: &[(String, PathBuf)]) -> Diagnosis {
+    const NAME: &str = "Message packages";
+    let packages: BTreeSet<_> = idls
+        .iter()
+        .filter_map(|(type_name, _)| TypeName::parse(type_name).map(|t| t.package))
+        .collect();
+    if packages.is_empty() {
+        return Diagnosis::problem(
+            NAME,
+            Status::Fail,
+            "no .idl message definitions found".to_string(),
+            "Install message packages, e.g. `ros-$ROS_DISTRO-common-interfaces`, and build custom ones with colcon",
+        );
+    }
+    Diagnosis::ok(
+        NAME,
+        format!("{} packages with {} types", packages.len(), idls.len()),
+    )
+}
+
+fn check_sample_decode() -> Diagnosis {
+    const NAME: &str = "Sample decode";
+    let value = json!({"stamp": {"sec": 1, "nanosec": 2}, "frame_id": "map"});
+    let result = MessageSchema::try_from(SAMPLE_TYPE).and_then(|schema| {
+        let data = CdrEncoder::new(Endianness::Little).encode(&schema, &value)?;
+        let plan = DecodePlan::compile(&schema)?;
+        CdrDecoder::new(&data).decode_plan(&plan)
+    });
+    match result {
+        Ok(decoded) if decoded == value => Diagnosis::ok(NAME, format!("{SAMPLE_TYPE} decoded")),
+        Ok(decoded) => Diagnosis::problem(
+            NAME,
+            Status::Fail,
+            format!("{SAMPLE_TYPE} decoded as {decoded}"),
+            "Report this as a bug along with the IDL files of the type",
+        ),
+        Err(e) => Diagnosis::problem(
+            NAME,
+            Status::Fail,
+            format!("{SAMPLE_TYPE}: {e}"),
+            "Install `ros-$ROS_DISTRO-std-msgs` and source ROS 2",
+        ),
+    }
+}
+
+fn check_unsupported_types(idls: &[(String, PathBuf)]) -> Diagnosis {
+    const NAME: &str = "Type support";
+    let unsupported: Vec<&str> = idls
+        .iter()
+        .filter(|(_, path)| {
+            std::fs::read_to_string(path).is_ok_and(|idl| {
+                idl.lines().any(|line| {
+                    line.split(|c: char| !c.is_alphanumeric() && c != '_')
+                        .any(|token| UNSUPPORTED_TYPES.contains(&token))
+                })
+            })
+        })
+        .map(|(type_name, _)| type_name.as_str())
+        .collect();
+    if unsupported.is_empty() {
+        return Diagnosis::ok(NAME, "all installed types are supported".to_string());
+    }
+    Diagnosis::problem(
+        NAME,
+        Status::Warn,
+        format!(
+            "{} types use {}, which cannot be decoded: {}",
+            unsupported.len(),
+            UNSUPPORTED_TYPES.join("/"),
+            join_examples(unsupported.iter())
+        ),
+        "Dump these topics with `show` or force the Bytes view with a `bytes` rule in the config",
+    )
+}
+
+/// Checks that the types of every topic of a bag resolve and that messages are plain CDR.
+fn check_bag(reader: &dyn BagReader) -> RosPeekResult<Vec<Diagnosis>> {
+    let mut unresolved = BTreeSet::new();
+    let mut unsupported = Vec::new();
+    for topic in reader.topics()? {
+        if topic.serialization_format != "cdr" {
+            unsupported.push(format!("{} ({})", topic.name, topic.serialization_format));
+            continue;
+        }
+        if MessageSchema::try_from(topic.type_name.as_str()).is_err()
+            && reader.embedded_schema(&topic.type_name)?.is_none()
+        {
+            unresolved.insert(topic.type_name.clone());
+        }
+        let first = reader.read_messages_range(&topic.name, None, None, Some(1), None)?;
+        if let Some(message) = first.first()
+            && let Some(kind) = unsupported_encapsulation(&message.data)
+        {
+            unsupported.push(format!("{} ({kind})", topic.name));
+        }
+    }
+
+    let resolved = if unresolved.is_empty() {
+        Diagnosis::ok("Bag schemas", "all topic types resolve".to_string())
+    } else {
+        Diagnosis::problem(
+            "Bag schemas",
+            Status::Fail,
+            format!(
+                "{} types not found: {}",
+                unresolved.len(),
+                join_examples(unresolved.iter())
+            ),
+            "Source the workspace which builds these packages, or record with MCAP to embed schemas",
+        )
+    };
+    let encoding = if unsupported.is_empty() {
+        Diagnosis::ok("Bag encoding", "all topics are plain CDR".to_string())
+    } else {
+        Diagnosis::problem(
+            "Bag encoding",
+            Status::Fail,
+            format!(
+                "{} topics cannot be decoded: {}",
+                unsupported.len(),
+                join_examples(unsupported.iter())
+            ),
+            "Record with a middleware using plain CDR, such as the default Fast DDS or Cyclone DDS settings",
+        )
+    };
+    Ok(vec![resolved, encoding])
+}
+
+/// Lists the message definitions installed under the prefixes, paired with their type names.
+fn find_idls(prefixes: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut idls = Vec::new();
+    for share in prefixes.iter().map(|p| p.join("share")) {
+        let Ok(packages) = std::fs::read_dir(&share) else {
+            continue;
+        };
+        for package in packages.flatten() {
+            let Ok(files) = std::fs::read_dir(package.path().join("msg")) else {
+                continue;
+            };
+            let package = package.file_name().to_string_lossy().into_owned();
+            for path in files.flatten().map(|f| f.path()) {
+                if path.extension().is_some_and(|ext| ext == "idl")
+                    && let Some(name) = path.file_stem()
+                {
+                    let type_name = format!("{package}/msg/{}", name.to_string_lossy());
+                    idls.push((type_name, path));
+                }
+            }
+        }
+    }
+    idls.sort();
+    idls
+}
+
+/// Joins the first items into a comma-separated list, noting how many were left out.
+fn join_examples<T: Display>(items: impl Iterator<Item = T>) -> String {
+    let items: Vec<String> = items.map(|item| item.to_string()).collect();
+    let mut joined = items
+        .iter()
+        .take(MAX_EXAMPLES)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if items.len() > MAX_EXAMPLES {
+        joined.push_str(&format!(", ... ({} more)", items.len() - MAX_EXAMPLES));
+    }
+    joined
+}
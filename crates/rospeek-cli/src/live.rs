@@ -0,0 +1,119 @@
+use std::{
+    collections::HashMap,
+    sync::mpsc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rospeek_core::{DecodePlan, MessageSchema, RawMessage, RosPeekResult};
+use zenoh::{Session, Wait};
+
+use crate::watch::print_decoded;
+
+/// Key expression of the liveliness tokens declared by zenoh-bridge-ros2dds for the publishers
+/// it routes, followed by `/<TOPIC>/<TYPE>/<QOS>` where `/` in names are escaped into `§`.
+const PUBLISHER_LIVELINESS: &str = "@/*/@ros2_lv/MP";
+
+/// Subscribes to live topics routed by zenoh-bridge-ros2dds and prints their messages as JSON
+/// lines, like `ros2 topic echo`, until interrupted.
+///
+/// Messages are stamped with the time they are received.
+///
+/// # Arguments
+/// * `topics` - Topic names to subscribe to, such as `/imu`.
+/// * `type_name` - Message type of the topics, or `None` to discover the type of each topic from
+///   the publishers announced by the bridge.
+/// * `large_ints_as_strings` - Whether to print integers beyond `2^53` as strings.
+pub(crate) fn live_topics(
+    topics: &[String],
+    type_name: Option<&str>,
+    large_ints_as_strings: bool,
+) -> RosPeekResult<()> {
+    let session = zenoh::open(zenoh::Config::default())
+        .wait()
+        .map_err(|e| anyhow::anyhow!("Failed to open a zenoh session: {e}"))?;
+
+    let mut plans: HashMap<String, Option<DecodePlan>> = HashMap::new();
+    let mut resolved = Vec::new();
+    let mut subscribers = Vec::new();
+    let (sender, receiver) = mpsc::channel();
+    for (index, topic) in topics.iter().enumerate() {
+        // the bridge routes `/foo/bar` as `foo/bar`
+        let key_expr = topic.trim_start_matches('/').to_string();
+        let type_name = match type_name {
+            Some(type_name) => type_name.to_string(),
+            None => discover_type(&session, &key_expr)?.ok_or_else(|| {
+                anyhow::anyhow!("No publisher of {topic} found, pass its message type with --type")
+            })?,
+        };
+        plans.entry(type_name.clone()).or_insert_with(|| {
+            MessageSchema::try_from(type_name.as_str())
+                .and_then(|schema| DecodePlan::compile(&schema))
+                .ok()
+        });
+        eprintln!("Subscribed to {topic} [{type_name}]");
+        resolved.push((topic.clone(), type_name));
+
+        let sender = sender.clone();
+        let subscriber = session
+            .declare_subscriber(key_expr)
+            .callback(move |sample| {
+                let message = RawMessage {
+                    timestamp: now_ns(),
+                    topic_id: index as u16,
+                    data: sample.payload().to_bytes().into_owned(),
+                };
+                // the receiver only stops with the process
+                let _ = sender.send(message);
+            })
+            .wait()
+            .map_err(|e| anyhow::anyhow!("Failed to subscribe to {topic}: {e}"))?;
+        subscribers.push(subscriber);
+    }
+    drop(sender);
+
+    for message in receiver {
+        let (topic, type_name) = &resolved[message.topic_id as usize];
+        print_decoded(
+            topic,
+            type_name,
+            &message,
+            plans[type_name].as_ref(),
+            large_ints_as_strings,
+        );
+    }
+    Ok(())
+}
+
+/// Returns the message type of the first publisher of a key expression announced by the bridge.
+fn discover_type(session: &Session, key_expr: &str) -> RosPeekResult<Option<String>> {
+    let replies = session
+        .liveliness()
+        .get(format!(
+            "{PUBLISHER_LIVELINESS}/{}/**",
+            key_expr.replace('/', "§")
+        ))
+        .wait()
+        .map_err(|e| anyhow::anyhow!("Failed to query publishers of {key_expr}: {e}"))?;
+    while let Ok(reply) = replies.recv() {
+        // @/<ZENOH_ID>/@ros2_lv/MP/<TOPIC>/<TYPE>/<QOS>
+        let type_name = reply.result().ok().and_then(|sample| {
+            sample
+                .key_expr()
+                .as_str()
+                .split('/')
+                .nth(5)
+                .map(str::to_string)
+        });
+        if let Some(type_name) = type_name {
+            return Ok(Some(type_name.replace('§', "/")));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the current time in nanoseconds since the UNIX epoch.
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
@@ -1,62 +1,287 @@
+mod bundle;
+mod codegen;
+mod columnar;
 mod command;
+mod compress;
+mod dataset;
+mod doctor;
+mod live;
+mod manifest;
+mod plugin;
+mod samples;
+mod schema;
+mod script;
+mod split;
+mod sql;
+mod template;
+mod top;
+mod watch;
 
 use clap::Parser;
-use rospeek_core::{RosPeekResult, try_decode_csv, try_decode_json};
-use rospeek_gui::{create_reader, spawn_app};
-use std::{collections::BTreeMap, fs::File};
+use rospeek_core::{
+    AssertRules, BagMetadata, BagReader, CLOCK_TOPIC, ClockMap, Config, DatasetConfig,
+    DecoderRegistry, EstimateFormat, EventOptions, ExportCheckpoint, ExportEstimate, ExportOptions,
+    FieldUnits, IncompleteSign, METADATA_FILE, OpenOptions, POINT_CLOUD_TYPE, PointCloud, Redactor,
+    RosPeekResult, SampleOptions, StorageType, TimeBasis, Topic, TopicAliases, add_euler_angles,
+    assert_bag, check_bag, collect_decode_failures, compare_dumps, decode_to_table,
+    estimate_export, export_bag_with, extract_events, find_ros_idl_path, flatten_json, iso_to_ns,
+    lint_qos, load_dump, ns_to_iso, save_events, set_decode_jobs, set_msg_paths, size_histograms,
+    stringify_large_integers, topic_stats, try_decode_json, try_decode_timestamped,
+};
+use rospeek_gui::{
+    append_writer,
+    backend::STDIN,
+    catalog::{Catalog, CatalogQuery, default_catalog_path},
+    create_reader_with_options, create_summary_reader, create_writer, encrypt_file, encrypted_path,
+    encryption::ENCRYPTED_EXTENSION,
+    private_dir, spawn_app,
+};
+use rospeek_mcap::McapReader;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use crate::command::{Command, DumpFormat};
+use crate::{
+    bundle::{bundle_path, is_bundle, open_bundle, write_bundle},
+    codegen::generate_structs,
+    columnar::{select_columns, write_arrow, write_parquet},
+    command::{CatalogCommand, Command, DumpFormat, SchemaFormat, SchemasCommand, StorageFormat},
+    dataset::export_dataset,
+    doctor::{Status, run_doctor},
+    live::live_topics,
+    manifest::{build_manifest, manifest_bag, manifest_path, verify_manifest},
+    plugin::run_plugin,
+    samples::{SampleExportOptions, export_samples},
+    schema::{SchemaResolver, export_schemas, json_schema, print_schema},
+    script::LuaScript,
+    split::{DumpWriter, SplitLimits},
+    sql::run_sql,
+    template::render_report,
+    top::spawn_top,
+    watch::watch_bag,
+};
+
+/// Width in characters of the longest bar of the sizes histogram.
+const HISTOGRAM_WIDTH: u64 = 40;
+
+/// Number of differences compare-dump prints, all of them being written with `--report`.
+const MAX_PRINTED_DIFFERENCES: usize = 20;
+
+/// Ratio of the estimated output to the recorded size of a topic past which a dry run flags it.
+const EXPANSION_WARNING: f64 = 4.0;
 
 #[derive(Parser)]
 #[command(name = "rospeek", about = "Peek into rosbag files", long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Command,
+
+    #[arg(
+        short,
+        long,
+        global = true,
+        help = "Number of threads decoding messages in parallel (default: number of CPUs)"
+    )]
+    jobs: Option<usize>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Path to the YAML config file (default: $ROSPEEK_CONFIG or ~/.config/rospeek/config.yaml)"
+    )]
+    config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        help = "Storage to read bag files as, e.g. for misnamed files (default: detected from their contents)"
+    )]
+    storage: Option<StorageFormat>,
+
+    #[arg(
+        long = "msg-path",
+        global = true,
+        value_name = "DIR",
+        help = "Directory of <package>/msg/<Name>.idl files searched before installed packages, can be repeated"
+    )]
+    msg_paths: Vec<PathBuf>,
 }
 
 fn main() -> RosPeekResult<()> {
     let cli = Cli::parse();
+    if let Some(jobs) = cli.jobs {
+        set_decode_jobs(jobs)?;
+    }
+    let config = match &cli.config {
+        Some(path) => Config::load(path)?,
+        None => Config::discover()?,
+    };
+    DecoderRegistry::global().set_overrides(config.overrides);
+    FieldUnits::set_global(config.units);
 
-    match cli.command {
-        Command::Info { bag } => {
-            let reader = create_reader(bag)?;
+    // bundles are opened as their bag, with the message definitions packaged along
+    let mut command = cli.command;
+    let mut msg_paths = cli.msg_paths;
+    if let Some(bag) = command.bag_mut()
+        && is_bundle(bag)
+    {
+        let bundle = open_bundle(bag)?;
+        *bag = bundle.bag;
+        msg_paths.push(bundle.schemas);
+    }
+    if !msg_paths.is_empty() {
+        set_msg_paths(msg_paths.clone());
+    }
 
-            println!("{}", reader.stats());
+    // topics are accepted by their alias too
+    let aliases = match command.bag_mut() {
+        Some(bag) => config.aliases.with_sidecar(bag)?,
+        None => config.aliases,
+    };
+    for topic in command.topics_mut() {
+        *topic = aliases.resolve(topic).to_string();
+    }
+    TopicAliases::set_global(aliases.clone());
+    let options = OpenOptions {
+        storage: cli.storage.map(StorageType::from),
+        ..Default::default()
+    };
+
+    match command {
+        Command::Info {
+            bag,
+            verify,
+            detailed,
+            depth,
+        } => {
+            let split = bag.is_dir();
+            let metadata = BagMetadata::load(&bag)?;
+            let (topics, reader) = match metadata {
+                // answer from metadata.yaml alone, without opening the storage files
+                Some(metadata) if !verify => {
+                    println!("{}", metadata.stats(&bag)?);
+                    println!("Time basis:       - (read from {METADATA_FILE}, pass --verify)");
+                    print_incomplete_signs(&metadata.incomplete_signs(&bag, None)?);
+                    (metadata.topics(), None)
+                }
+                metadata => {
+                    // only /clock messages are read, to detect the time basis
+                    let reader = create_summary_reader(&bag, &options, &[CLOCK_TOPIC])?;
+                    println!("{}", reader.stats()?);
+                    println!("Time basis:       {}", TimeBasis::detect(reader.as_ref())?);
+                    let mut signs = reader.incomplete_signs();
+                    if let Some(metadata) = &metadata {
+                        signs.extend(metadata.incomplete_signs(&bag, Some(reader.stats()?))?);
+                    }
+                    print_incomplete_signs(&signs);
+                    if let Some(metadata) = metadata {
+                        print_metadata_differences(&metadata, reader.as_ref())?;
+                    }
+                    (reader.topics()?, Some(reader))
+                }
+            };
+            // sizes are only known from the storage files
+            let sizes = match &reader {
+                Some(reader) => reader.topic_sizes()?,
+                None => None,
+            };
 
             println!("Topic Information:");
             // group topics by namespace
             let mut grouped: BTreeMap<String, Vec<_>> = BTreeMap::new();
-            for topic in reader.topics()? {
-                let key = topic
-                    .name
-                    .split("/")
-                    .find(|s| !s.is_empty())
-                    .map(|s| format!("/{s}"))
-                    .unwrap_or_else(|| "/".to_string());
-                grouped.entry(key).or_default().push(topic);
-            }
-            for (_, mut topics) in grouped {
+            for topic in topics {
+                grouped
+                    .entry(topic.namespace_at(depth).to_string())
+                    .or_default()
+                    .push(topic);
+            }
+            for (namespace, mut topics) in grouped {
+                let bytes = sizes.as_ref().map(|sizes| {
+                    topics
+                        .iter()
+                        .filter_map(|t| sizes.get(&t.name))
+                        .sum::<u64>()
+                });
+                println!(
+                    "  Namespace: {namespace} | Topics: {} | Count: {} | Size: {}",
+                    topics.len(),
+                    topics.iter().map(|t| t.count).sum::<u64>(),
+                    bytes.map_or("-".to_string(), |bytes| format_bytes(bytes as f64))
+                );
                 // sort topics by topic name
                 topics.sort_by(|a, b| a.name.cmp(&b.name));
                 for topic in topics {
                     println!(
                         "   - Topic: {} | Type: {} | Count: {} | Serialization Format: {}",
-                        topic.name, topic.type_name, topic.count, topic.serialization_format
+                        aliases.label(&topic.name),
+                        topic.type_name,
+                        topic.count,
+                        topic.serialization_format
                     );
                 }
             }
+
+            // only the files of a split bag can disagree on the type of a topic
+            if split && let Some(reader) = reader {
+                print_type_changes(reader.as_ref())?;
+            }
+
+            if detailed {
+                print_compression(&bag, &options)?;
+            }
+        }
+        Command::Topics { bag, types, verify } => {
+            let topics = match BagMetadata::load(&bag)? {
+                Some(metadata) if !verify => metadata.topics(),
+                metadata => {
+                    let reader = create_summary_reader(&bag, &options, &[])?;
+                    if let Some(metadata) = metadata {
+                        print_metadata_differences(&metadata, reader.as_ref())?;
+                    }
+                    reader.topics()?
+                }
+            };
+            let mut topics: Vec<_> = topics.into_iter().map(|t| (t.name, t.type_name)).collect();
+            topics.sort();
+            for (name, type_name) in topics {
+                if types {
+                    println!("{name}\t{type_name}");
+                } else {
+                    println!("{name}");
+                }
+            }
         }
         Command::Show {
             bag,
             topic,
             count,
             offset,
+            range,
         } => {
-            let reader = create_reader(bag)?;
+            let reader = create_reader_with_options(bag, &options)?;
 
-            let messages = reader.read_messages_range(&topic, None, None, count, offset)?;
+            let (first, messages) = match range {
+                Some((start, end)) => {
+                    let count = end.map_or(usize::MAX, |end| end - start);
+                    (start, reader.read_messages_by_index(&topic, start, count)?)
+                }
+                None => (
+                    0,
+                    reader.read_messages_range(&topic, None, None, count, offset)?,
+                ),
+            };
             messages.iter().enumerate().for_each(|(i, msg)| {
-                println!("[{}] t = {} ns, {} bytes", i, msg.timestamp, msg.data.len())
+                println!(
+                    "[{}] t = {} ns, {} bytes",
+                    first + i,
+                    msg.timestamp,
+                    msg.data.len()
+                )
             });
         }
         Command::Dump {
@@ -67,36 +292,1230 @@ fn main() -> RosPeekResult<()> {
             until,
             limit,
             offset,
+            clock,
+            fields,
+            dry_run: true,
+            ..
+        } => {
+            let reader = create_reader_with_options(&bag, &options)?;
+            let (since, until) = if clock {
+                to_bag_range(reader.as_ref(), since, until)?
+            } else {
+                (since, until)
+            };
+            let format = match format {
+                DumpFormat::Json => EstimateFormat::Json,
+                DumpFormat::Csv => EstimateFormat::Csv,
+                // typed columns take about as many bytes as the CDR they are decoded from
+                DumpFormat::Parquet | DumpFormat::Arrow => EstimateFormat::Bag,
+            };
+            let mut estimate = estimate_export(
+                reader.as_ref(),
+                std::slice::from_ref(&topic),
+                since,
+                until,
+                format,
+                &fields,
+            )?;
+            estimate
+                .topics
+                .iter_mut()
+                .for_each(|topic| topic.page(offset, limit));
+            print_estimate(&estimate, &aliases);
+        }
+        Command::Dump {
+            bag,
+            topic,
+            format,
+            compress,
+            split_size,
+            split_duration,
+            since,
+            until,
+            limit,
+            offset,
+            clock,
+            fields,
+            script,
+            top,
+            large_ints_as_strings,
+            euler,
+            report,
+            encrypt,
+            recipients,
+            dry_run: false,
         } => {
+            if let DumpFormat::Parquet | DumpFormat::Arrow = format {
+                if script.is_some() {
+                    anyhow::bail!(
+                        "--script is not supported with --format {}",
+                        format.extension()
+                    );
+                }
+                if split_size.is_some() || split_duration.is_some() {
+                    anyhow::bail!(
+                        "--split-size and --split-duration are not supported with --format {}",
+                        format.extension()
+                    );
+                }
+                if euler {
+                    anyhow::bail!(
+                        "--euler is not supported with --format {}",
+                        format.extension()
+                    );
+                }
+            }
+            if let (DumpFormat::Arrow, Some(_)) = (format, compress) {
+                // buffers must stay uncompressed for readers to memory-map the file
+                anyhow::bail!("--compress is not supported with --format arrow");
+            }
+            let script = script.map(|path| LuaScript::load(&path)).transpose()?;
+            if top {
+                spawn_top(Duration::from_secs(1));
+            }
             println!(">> Start decoding: {topic}");
-            let reader = create_reader(bag)?;
+            let reader = create_reader_with_options(&bag, &options)?;
+            let (since, until) = if clock {
+                to_bag_range(reader.as_ref(), since, until)?
+            } else {
+                (since, until)
+            };
             println!("✨Successfully opened bag, starting to decode messages");
             println!(">> Start dumping results into {format:?}");
-            let filename = match format {
-                DumpFormat::Json => {
-                    let filename = topic.trim_start_matches('/').replace('/', ".") + ".json";
-                    let writer = File::create(&filename)?;
-                    let values = try_decode_json(reader, &topic, since, until, limit, offset)?;
-                    serde_json::to_writer_pretty(writer, &values)?;
-                    filename
-                }
-                DumpFormat::Csv => {
-                    let filename = topic.trim_start_matches('/').replace('/', ".") + ".csv";
-                    let writer = File::create(&filename)?;
-                    let mut csv_writer = csv::WriterBuilder::new().from_writer(writer);
-                    let (columns, values) =
-                        try_decode_csv(reader, &topic, since, until, limit, offset)?;
-                    csv_writer.write_record(columns)?;
-                    for value in values {
-                        csv_writer.write_record(value)?
+            let split = SplitLimits {
+                max_bytes: split_size,
+                max_duration_ns: split_duration,
+            };
+            let recipients = encrypt.then_some(recipients);
+            let dumped = (|| -> RosPeekResult<Vec<String>> {
+                let stem = topic.trim_start_matches('/').replace('/', ".");
+                if let DumpFormat::Parquet | DumpFormat::Arrow = format {
+                    let (schema, batches) =
+                        decode_to_table(reader, &topic, since, until, limit, offset)?;
+                    let (schema, batches) = select_columns(schema, batches, &fields)?;
+                    let file = match &recipients {
+                        Some(_) => format!("{stem}.{}.{ENCRYPTED_EXTENSION}", format.extension()),
+                        None => format!("{stem}.{}", format.extension()),
+                    };
+                    let recipients = recipients.as_deref();
+                    match format {
+                        DumpFormat::Arrow => {
+                            write_arrow(Path::new(&file), &schema, &batches, recipients)?
+                        }
+                        _ => {
+                            write_parquet(Path::new(&file), schema, &batches, compress, recipients)?
+                        }
+                    }
+                    return Ok(vec![file]);
+                }
+                let mut records =
+                    try_decode_timestamped(reader, &topic, &fields, since, until, limit, offset)?;
+                if euler {
+                    records
+                        .iter_mut()
+                        .for_each(|(_, value)| add_euler_angles(value));
+                }
+                if let Some(script) = &script {
+                    records = script.transform_all(&topic, records)?;
+                }
+                if large_ints_as_strings {
+                    records
+                        .iter_mut()
+                        .for_each(|(_, value)| stringify_large_integers(value));
+                }
+                let mut writer = DumpWriter::new(stem, format, compress, split);
+                if let Some(recipients) = &recipients {
+                    writer.encrypt(recipients.clone());
+                }
+                match format {
+                    DumpFormat::Json => {
+                        for (timestamp, value) in records.iter() {
+                            writer.write_json(*timestamp, value)?;
+                        }
+                    }
+                    DumpFormat::Csv if script.is_none() && !fields.is_empty() => {
+                        writer.set_header(fields.clone());
+                        for (timestamp, value) in records.iter() {
+                            writer.write_csv(
+                                *timestamp,
+                                fields.iter().map(|f| value[f.as_str()].to_string()),
+                            )?;
+                        }
+                    }
+                    DumpFormat::Csv => {
+                        let (columns, rows) = flatten_rows(&records)?;
+                        writer.set_header(columns);
+                        for (timestamp, row) in rows {
+                            writer.write_csv(timestamp, row)?;
+                        }
+                    }
+                    DumpFormat::Parquet | DumpFormat::Arrow => {
+                        unreachable!("columnar files are written from record batches")
                     }
-                    filename
                 }
+                writer.finish()
+            })();
+            if let (Err(_), Some(path)) = (&dumped, &report) {
+                let reader = create_reader_with_options(&bag, &options)?;
+                let topics = [topic.clone()];
+                let report = collect_decode_failures(reader.as_ref(), &topics, since, until)?;
+                report.write(path)?;
+                println!(
+                    ">> Found {} undecodable messages, see: {}",
+                    report.failures.len(),
+                    path.display()
+                );
+            }
+            let files = dumped?;
+            match files.as_slice() {
+                [file] => println!("✨Success to save {format:?} to: {file}"),
+                [.., manifest] => println!(
+                    "✨Success to save {format:?} into {} files listed in: {manifest}",
+                    files.len() - 1
+                ),
+                [] => {}
+            }
+        }
+        Command::Export {
+            bag,
+            topics,
+            exclude,
+            since,
+            until,
+            clock,
+            redact,
+            dry_run: true,
+            ..
+        } => {
+            let reader = create_reader_with_options(bag, &options)?;
+            let (since, until) = if clock {
+                to_bag_range(reader.as_ref(), since, until)?
+            } else {
+                (since, until)
             };
-            println!("✨Success to save {format:?} to: {filename}");
+            let topics = select_topics(reader.as_ref(), &topics, &exclude)?;
+            let topics = match redact {
+                Some(preset) => {
+                    Redactor::new(preset.parse()?).select_topics(reader.as_ref(), &topics)?
+                }
+                None => topics,
+            };
+            let estimate = estimate_export(
+                reader.as_ref(),
+                &topics,
+                since,
+                until,
+                EstimateFormat::Bag,
+                &[],
+            )?;
+            print_estimate(&estimate, &aliases);
         }
+        Command::Export {
+            bag,
+            output,
+            topics,
+            exclude,
+            since,
+            until,
+            clock,
+            dedup,
+            sort_by_time,
+            script,
+            redact,
+            resume,
+            encrypt,
+            recipients,
+            dry_run: false,
+        } => {
+            let script = script.map(|path| LuaScript::load(&path)).transpose()?;
+            let mut redactor = redact
+                .map(|preset| preset.parse().map(Redactor::new))
+                .transpose()?;
+            let reader = create_reader_with_options(bag, &options)?;
+            let (since, until) = if clock {
+                to_bag_range(reader.as_ref(), since, until)?
+            } else {
+                (since, until)
+            };
+            let topics = select_topics(reader.as_ref(), &topics, &exclude)?;
+            let topics = match redactor.as_mut() {
+                Some(redactor) => redactor.select_topics(reader.as_ref(), &topics)?,
+                None => topics,
+            };
+            // an encrypted bag is staged in a private directory next to the output, as the
+            // writers need to seek, and is removed along with it once encrypted
+            let staging = if encrypt {
+                let parent = output.parent().filter(|p| !p.as_os_str().is_empty());
+                Some(private_dir(parent)?)
+            } else {
+                None
+            };
+            let target = match &staging {
+                Some(dir) => dir.path().join(output.file_name().unwrap_or_default()),
+                None => output.clone(),
+            };
+            let checkpoint = ExportCheckpoint::path(&output);
+            let (mut writer, resume) = if resume {
+                let progress = ExportCheckpoint::load(&checkpoint)?;
+                (append_writer(&output)?, Some(progress))
+            } else {
+                (create_writer(&target)?, None)
+            };
+            let export_options = ExportOptions {
+                topics,
+                since,
+                until,
+                dedup,
+                sort_by_time,
+                // an interrupted encrypted export leaves nothing behind to resume
+                checkpoint: staging.is_none().then_some(checkpoint),
+                resume,
+            };
+            let redact_stage = redactor.as_mut();
+            let summary = export_bag_with(
+                reader.as_ref(),
+                writer.as_mut(),
+                &export_options,
+                |pipeline| {
+                    // redact first, so that scripts only see anonymized messages
+                    let pipeline = match redact_stage {
+                        Some(redactor) => pipeline.stage(redactor),
+                        None => pipeline,
+                    };
+                    match script {
+                        Some(script) => pipeline.decode().stage(script),
+                        None => pipeline,
+                    }
+                },
+            )?;
+            // the writer is finalized by the export, so the file is complete once it is dropped
+            drop(writer);
+            let output = if encrypt {
+                let encrypted = encrypted_path(&output);
+                encrypt_file(&target, &encrypted, &recipients)?;
+                encrypted
+            } else {
+                output
+            };
+            for (topic, count) in summary.counts.iter() {
+                let mut line = format!("   - Topic: {topic} | Count: {count}");
+                if let Some(duplicates) = summary.duplicates.get(topic) {
+                    line.push_str(&format!(" | Dropped Duplicates: {duplicates}"));
+                }
+                if let Some(reordered) = summary.reordered.get(topic) {
+                    line.push_str(&format!(" | Reordered: {reordered}"));
+                }
+                for (stage, dropped) in &summary.dropped_by_stage {
+                    if let Some(dropped) = dropped.get(topic).filter(|&&n| n > 0) {
+                        line.push_str(&format!(" | Dropped by {stage}: {dropped}"));
+                    }
+                }
+                if let Some(resumed) = summary.resumed.get(topic) {
+                    line.push_str(&format!(" | Already Exported: {resumed}"));
+                }
+                println!("{line}");
+            }
+            if let Some(redactor) = &redactor {
+                print_redaction_report(redactor);
+            }
+            // MCAP viewers such as Foxglove decode messages with the definitions in the file
+            if StorageType::detect(&target) == Some(StorageType::Mcap) {
+                print_missing_definitions(reader.as_ref(), &summary.counts)?;
+            }
+            println!(
+                "✨Success to export {} messages to: {}",
+                summary.total(),
+                output.display()
+            );
+        }
+        Command::Types { bag } => {
+            let reader = create_reader_with_options(&bag, &options)?;
+            let mut usage: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for topic in reader.topics()? {
+                usage.entry(topic.type_name).or_default().push(topic.name);
+            }
+
+            println!("Type Information:");
+            let mut missing = 0;
+            for (type_name, mut topics) in usage {
+                topics.sort();
+                let mut sources = Vec::new();
+                if let Some(path) = find_ros_idl_path(&type_name) {
+                    sources.push(format!("local ({})", path.display()));
+                }
+                if let Some(schema) = reader.embedded_schema(&type_name)? {
+                    sources.push(format!("embedded ({})", schema.encoding));
+                }
+                if sources.is_empty() {
+                    missing += 1;
+                    sources.push("missing".to_string());
+                }
+                println!(
+                    "   - Type: {type_name} | Schema: {} | Topics: {}",
+                    sources.join(", "),
+                    topics.join(", ")
+                );
+            }
+            if missing > 0 {
+                eprintln!(
+                    "⚠ {missing} types have no schema, so their messages cannot be decoded. Source \
+                     the workspace which builds their packages, or record with MCAP to embed schemas."
+                );
+            }
+        }
+        Command::Schema {
+            bag,
+            topic,
+            type_name,
+            format,
+        } => {
+            let reader = create_reader_with_options(bag, &options)?;
+            let type_name = match (&topic, type_name) {
+                (Some(topic), _) => reader
+                    .topics()?
+                    .into_iter()
+                    .find(|t| &t.name == topic)
+                    .map(|t| t.type_name)
+                    .ok_or_else(|| anyhow::anyhow!("Topic not found: {topic}"))?,
+                (None, Some(type_name)) => type_name,
+                (None, None) => anyhow::bail!("Either --topic or --type is required"),
+            };
+            match format {
+                SchemaFormat::Text => print_schema(
+                    reader.as_ref(),
+                    topic.as_deref().unwrap_or_default(),
+                    &type_name,
+                )?,
+                SchemaFormat::JsonSchema => {
+                    let resolver = SchemaResolver::new(reader.as_ref(), &type_name)?;
+                    let schema = json_schema(&resolver, &type_name)?;
+                    println!("{}", serde_json::to_string_pretty(&schema)?);
+                }
+            }
+        }
+        Command::Schemas {
+            command: SchemasCommand::Export { bag, output },
+        } => {
+            let reader = create_reader_with_options(&bag, &options)?;
+            let export = export_schemas(reader.as_ref(), &output)?;
+            for (type_name, origin) in export.exported.iter() {
+                println!("   - Type: {type_name} | Source: {origin}");
+            }
+            if !export.missing.is_empty() {
+                eprintln!(
+                    "⚠ {} types were not exported, their definitions being neither installed nor \
+                     embedded as ros2idl: {}",
+                    export.missing.len(),
+                    export
+                        .missing
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            println!(
+                "✨Exported {} message definitions to: {}",
+                export.exported.len(),
+                output.display()
+            );
+        }
+        Command::Codegen { bag, topic, out } => {
+            let reader = create_reader_with_options(bag, &options)?;
+            let type_name = reader
+                .topics()?
+                .into_iter()
+                .find(|t| t.name == topic)
+                .map(|t| t.type_name)
+                .ok_or_else(|| anyhow::anyhow!("Topic not found: {topic}"))?;
+            let resolver = SchemaResolver::new(reader.as_ref(), &type_name)?;
+            let code = generate_structs(&resolver, &type_name)?;
+            match out {
+                Some(out) => {
+                    std::fs::write(&out, code)?;
+                    println!("✨Success to generate {type_name} to: {}", out.display());
+                }
+                None => print!("{code}"),
+            }
+        }
+        Command::Attachments { bag, extract } => {
+            if options.storage.or_else(|| StorageType::detect(&bag)) != Some(StorageType::Mcap) {
+                anyhow::bail!("Attachments are only supported for MCAP files");
+            }
+            let reader = McapReader::open(&bag)?;
+
+            println!("Metadata:");
+            for metadata in reader.metadata()? {
+                println!("   - Name: {}", metadata.name);
+                for (key, value) in metadata.entries.iter() {
+                    println!("       {key}: {value}");
+                }
+            }
+
+            println!("Attachments:");
+            for attachment in reader.attachments()? {
+                println!(
+                    "   - Name: {} | Media Type: {} | Size: {} bytes | Log Time: {}",
+                    attachment.name,
+                    attachment.media_type,
+                    attachment.data.len(),
+                    ns_to_iso(attachment.log_time)
+                );
+                if let Some(dir) = &extract {
+                    // only keep the file name so that attachments cannot escape the directory
+                    let Some(filename) = Path::new(&attachment.name).file_name() else {
+                        continue;
+                    };
+                    std::fs::create_dir_all(dir)?;
+                    let path = dir.join(filename);
+                    std::fs::write(&path, &attachment.data)?;
+                    println!("✨Success to extract attachment to: {}", path.display());
+                }
+            }
+        }
+        Command::Check {
+            bag,
+            topics,
+            report,
+        } => {
+            let reader = create_reader_with_options(bag, &options)?;
+            let results = check_bag(reader.as_ref(), &topics)?;
+            for result in results.iter() {
+                println!(
+                    "   - Topic: {} | Count: {} | Duplicates: {} | Out of Order: {} | {}",
+                    result.name,
+                    result.messages,
+                    result.duplicates,
+                    result.out_of_order,
+                    if result.is_ok() { "OK" } else { "NG" }
+                );
+            }
+            let mut failed: BTreeSet<_> = results
+                .iter()
+                .filter(|r| !r.is_ok())
+                .map(|r| r.name.clone())
+                .collect();
+            let qos_topics: Vec<Topic> = reader
+                .topics()?
+                .into_iter()
+                .filter(|t| topics.is_empty() || topics.contains(&t.name))
+                .collect();
+            for finding in lint_qos(&qos_topics) {
+                println!(
+                    "   - QoS: {} | {} ({}) | Recommended: {}",
+                    finding.topic, finding.problem, finding.rule, finding.recommendation
+                );
+                failed.insert(finding.topic);
+            }
+            if let Some(path) = report {
+                let report = collect_decode_failures(reader.as_ref(), &topics, None, None)?;
+                report.write(&path)?;
+                println!(
+                    "   - Undecodable messages: {} | Report: {}",
+                    report.failures.len(),
+                    path.display()
+                );
+                failed.extend(report.failures.into_iter().map(|f| f.topic));
+            }
+            if !failed.is_empty() {
+                anyhow::bail!("Found problems in {} topics", failed.len());
+            }
+            println!("✨No problems found");
+        }
+        Command::CompareDump {
+            bag,
+            golden,
+            topic,
+            tolerance,
+            large_ints_as_strings,
+            report,
+        } => {
+            let expected = load_dump(&golden)?;
+            let reader = create_reader_with_options(&bag, &options)?;
+            let mut actual = try_decode_json(reader, &topic, None, None, None, None)?;
+            if large_ints_as_strings {
+                actual.iter_mut().for_each(stringify_large_integers);
+            }
+            let differences = compare_dumps(&expected, &actual, tolerance);
+            for difference in differences.iter().take(MAX_PRINTED_DIFFERENCES) {
+                let show = |value: &Option<serde_json::Value>| {
+                    value.as_ref().map_or("-".to_string(), |v| v.to_string())
+                };
+                println!(
+                    "   - Message #{} {}: expected {} | actual {}",
+                    difference.index,
+                    if difference.path.is_empty() {
+                        "(whole message)"
+                    } else {
+                        difference.path.as_str()
+                    },
+                    show(&difference.expected),
+                    show(&difference.actual)
+                );
+            }
+            if differences.len() > MAX_PRINTED_DIFFERENCES {
+                println!(
+                    "   ... and {} more",
+                    differences.len() - MAX_PRINTED_DIFFERENCES
+                );
+            }
+            if let Some(path) = report {
+                serde_json::to_writer_pretty(File::create(&path)?, &differences)?;
+            }
+            if !differences.is_empty() {
+                anyhow::bail!(
+                    "Found {} differences between {} messages of {topic} and {} messages of {}",
+                    differences.len(),
+                    actual.len(),
+                    expected.len(),
+                    golden.display()
+                );
+            }
+            println!(
+                "✨{} messages of {topic} match {}",
+                actual.len(),
+                golden.display()
+            );
+        }
+        Command::Assert { bag, rules, report } => {
+            let rules = AssertRules::load(&rules)?;
+            let reader = create_reader_with_options(&bag, &options)?;
+            let results = assert_bag(reader.as_ref(), &rules)?;
+            for result in results.results.iter() {
+                println!(
+                    "   - {} {}: {} (expected {}) | {}",
+                    result.topic.as_deref().unwrap_or("Bag"),
+                    result.rule,
+                    result.actual,
+                    result.expected,
+                    if result.passed { "OK" } else { "NG" }
+                );
+            }
+            if let Some(path) = report {
+                results.write(&path)?;
+            }
+            let failed = results.failures().count();
+            if failed > 0 {
+                anyhow::bail!("{failed} of {} assertions failed", results.results.len());
+            }
+            println!("✨All {} assertions passed", results.results.len());
+        }
+        Command::Events {
+            bag,
+            rules,
+            hysteresis,
+            min_duration,
+            output,
+        } => {
+            let reader = create_reader_with_options(&bag, &options)?;
+            let events = extract_events(
+                reader.as_ref(),
+                &rules,
+                &EventOptions {
+                    hysteresis,
+                    min_duration_ns: (min_duration * 1e9) as u64,
+                },
+            )?;
+            match output {
+                Some(output) => {
+                    save_events(&events, &output)?;
+                    println!(
+                        "✨Extracted {} events into {}",
+                        events.len(),
+                        output.display()
+                    );
+                }
+                None => println!("{}", serde_json::to_string_pretty(&events)?),
+            }
+        }
+        Command::Samples {
+            bag,
+            output,
+            trigger,
+            topics,
+            stride,
+            max_offset,
+            keep_incomplete,
+            undistort,
+            fields,
+            voxel_size,
+        } => {
+            let reader = create_reader_with_options(&bag, &options)?;
+            let sample_options = SampleOptions {
+                stride,
+                max_offset_ns: max_offset.map(|sec| (sec * 1e9) as u64),
+            };
+            let summary = export_samples(
+                reader.as_ref(),
+                &trigger,
+                &topics,
+                &sample_options,
+                &SampleExportOptions {
+                    keep_incomplete,
+                    undistort,
+                    point_fields: fields,
+                    voxel_size,
+                },
+                &output,
+            )?;
+            println!(
+                "✨Exported {} samples into {}",
+                summary.written,
+                output.display()
+            );
+            if summary.skipped > 0 {
+                println!(
+                    "   {} incomplete samples were skipped, see --max-offset and --keep-incomplete",
+                    summary.skipped
+                );
+            }
+        }
+        Command::Dataset {
+            bag,
+            output,
+            config,
+        } => {
+            let config = DatasetConfig::load(&config)?;
+            let summary = export_dataset(&bag, &options, &config, &output)?;
+            println!(
+                "✨Exported {} samples into {}",
+                summary.written,
+                output.display()
+            );
+            if summary.skipped > 0 {
+                println!(
+                    "   {} incomplete samples were skipped, see max_offset",
+                    summary.skipped
+                );
+            }
+        }
+        Command::Catalog { command } => match command {
+            CatalogCommand::Add { dir, catalog } => {
+                let path = catalog.map_or_else(default_catalog_path, Ok)?;
+                let mut catalog = Catalog::open(&path)?;
+                let results = catalog.add_dir(&dir)?;
+                let mut failed = 0;
+                for (bag, result) in results.iter() {
+                    match result {
+                        Ok(topics) => println!("   - {} | {topics} topics | OK", bag.display()),
+                        Err(e) => {
+                            failed += 1;
+                            println!("   - {} | {e} | NG", bag.display());
+                        }
+                    }
+                }
+                println!(
+                    "✨Indexed {} bags into {}",
+                    results.len() - failed,
+                    path.display()
+                );
+                if failed > 0 {
+                    anyhow::bail!("Failed to index {failed} bags");
+                }
+            }
+            CatalogCommand::Search {
+                topic,
+                type_name,
+                after,
+                before,
+                catalog,
+            } => {
+                let path = catalog.map_or_else(default_catalog_path, Ok)?;
+                let catalog = Catalog::open(&path)?;
+                let query = CatalogQuery {
+                    topic,
+                    type_name,
+                    after: after.as_deref().map(iso_to_ns).transpose()?,
+                    before: before.as_deref().map(iso_to_ns).transpose()?,
+                };
+                let entries = catalog.search(&query)?;
+                for entry in entries.iter() {
+                    println!(
+                        "   - {} | {} | {} | {:.3} GiB | {:.3} s | {} messages",
+                        entry.path,
+                        entry.storage_type,
+                        ns_to_iso(entry.start_ns),
+                        entry.size_gib,
+                        entry.duration_sec,
+                        entry.message_count
+                    );
+                }
+                println!("✨Found {} bags", entries.len());
+            }
+        },
+        Command::Doctor { bag } => {
+            let diagnoses = run_doctor(bag.as_deref(), &options)?;
+            for diagnosis in diagnoses.iter() {
+                println!(
+                    "   - {}: {} | {}",
+                    diagnosis.name, diagnosis.detail, diagnosis.status
+                );
+                if let Some(fix) = &diagnosis.fix {
+                    println!("       Fix: {fix}");
+                }
+            }
+            let failed = diagnoses
+                .iter()
+                .filter(|d| d.status == Status::Fail)
+                .count();
+            if failed > 0 {
+                anyhow::bail!("Found {failed} problems");
+            }
+            println!("✨No problems found");
+        }
+        Command::Bandwidth { bag, topics } => {
+            let reader = create_reader_with_options(bag, &options)?;
+            let results = topic_stats(reader.as_ref(), &topics)?;
+            for (name, stats) in results.iter() {
+                println!(
+                    "   - Topic: {} | Count: {} | Rate: {} | Mean Size: {} | Bandwidth: {}",
+                    aliases.label(name),
+                    stats.count,
+                    stats
+                        .frequency_hz()
+                        .map_or("-".to_string(), |hz| format!("{hz:.2} Hz")),
+                    stats.mean_size().map_or("-".to_string(), format_bytes),
+                    stats
+                        .bandwidth_bps()
+                        .map_or("-".to_string(), |bps| format!("{}/s", format_bytes(bps)))
+                );
+            }
+        }
+        Command::Fields { bag, topic } => {
+            let reader = create_reader_with_options(bag, &options)?;
+            let type_name = reader
+                .topics()?
+                .into_iter()
+                .find(|t| t.name == topic)
+                .map(|t| t.type_name)
+                .ok_or_else(|| anyhow::anyhow!("Topic not found: {topic}"))?;
+            if type_name != POINT_CLOUD_TYPE {
+                anyhow::bail!("{topic} is {type_name}, not {POINT_CLOUD_TYPE}");
+            }
+            let message = reader
+                .read_messages_by_index(&topic, 0, 1)?
+                .pop()
+                .ok_or_else(|| anyhow::anyhow!("No message in {topic}"))?;
+            let cloud = PointCloud::from_cdr(&message.data)?;
+            println!(
+                "Topic: {} | Points: {} ({}x{}) | Point Step: {} | Padding: {} | Big Endian: {}",
+                aliases.label(&topic),
+                cloud.len(),
+                cloud.width,
+                cloud.height,
+                cloud.point_step,
+                cloud.padding(),
+                cloud.is_bigendian
+            );
+            let mut fields = cloud.fields.clone();
+            fields.sort_by_key(|f| f.offset);
+            for field in fields.iter() {
+                println!(
+                    "   - Field: {:<12} | Offset: {:>4} | Type: {:<7} | Count: {}",
+                    field.name,
+                    field.offset,
+                    field.datatype.name(),
+                    field.count
+                );
+            }
+        }
+        Command::Sizes { bag, topics } => {
+            let reader = create_reader_with_options(bag, &options)?;
+            for (name, histogram) in size_histograms(reader.as_ref(), &topics)?.iter() {
+                println!(
+                    "   - Topic: {} | Count: {}",
+                    aliases.label(name),
+                    histogram.total()
+                );
+                let buckets = histogram.buckets();
+                let max = buckets.iter().map(|b| b.count).max().unwrap_or(1);
+                for bucket in buckets.iter() {
+                    println!(
+                        "       {:>10} - {:<10} | {:>8} | {}",
+                        format_bytes(bucket.min_size as f64),
+                        format_bytes(bucket.max_size as f64),
+                        bucket.count,
+                        "#".repeat((bucket.count * HISTOGRAM_WIDTH).div_ceil(max) as usize)
+                    );
+                }
+            }
+        }
+        Command::Watch {
+            bag,
+            topics,
+            interval,
+            from_start,
+            decode,
+            large_ints_as_strings,
+        } => watch_bag(
+            &bag,
+            &topics,
+            Duration::from_millis(interval),
+            from_start,
+            decode,
+            large_ints_as_strings,
+            &options,
+        )?,
+        Command::Live {
+            topics,
+            type_name,
+            large_ints_as_strings,
+        } => live_topics(&topics, type_name.as_deref(), large_ints_as_strings)?,
+        Command::Manifest { bag, output } => {
+            let manifest = build_manifest(&bag, &options)?;
+            let output = match output {
+                Some(output) => output,
+                None => manifest_path(&bag)?,
+            };
+            serde_json::to_writer_pretty(File::create(&output)?, &manifest)?;
+            println!(
+                "✨Wrote manifest of {} files and {} topics into {}",
+                manifest["files"].as_array().map_or(0, Vec::len),
+                manifest["topics"].as_array().map_or(0, Vec::len),
+                output.display()
+            );
+        }
+        Command::Bundle { bag, output } => {
+            let output = match output {
+                Some(output) => output,
+                None => bundle_path(&bag)?,
+            };
+            let export = write_bundle(&bag, &output, &options)?;
+            if !export.missing.is_empty() {
+                eprintln!(
+                    "⚠ {} types were not bundled, their definitions being neither installed nor \
+                     embedded as ros2idl: {}",
+                    export.missing.len(),
+                    export
+                        .missing
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            println!(
+                "✨Bundled {} with {} message definitions into {}",
+                bag.display(),
+                export.exported.len(),
+                output.display()
+            );
+        }
+        Command::VerifyManifest { manifest, bag } => {
+            let value: serde_json::Value = serde_json::from_reader(File::open(&manifest)?)?;
+            let bag = match bag {
+                Some(bag) => bag,
+                None => manifest_bag(&manifest, &value)?,
+            };
+            let problems = verify_manifest(&value, &bag, &options)?;
+            if !problems.is_empty() {
+                for problem in problems.iter() {
+                    println!("   - {problem}");
+                }
+                anyhow::bail!(
+                    "{} does not match {} ({} problems)",
+                    bag.display(),
+                    manifest.display(),
+                    problems.len()
+                );
+            }
+            println!("✨{} matches {}", bag.display(), manifest.display());
+        }
+        Command::Report {
+            bag,
+            template,
+            output,
+            gap_factor,
+        } => {
+            let reader = create_reader_with_options(&bag, &options)?;
+            let report = render_report(reader.as_ref(), template.as_deref(), gap_factor)?;
+            match output {
+                Some(output) => {
+                    std::fs::write(&output, report)?;
+                    println!(
+                        "✨Wrote report of {} into {}",
+                        bag.display(),
+                        output.display()
+                    );
+                }
+                None => print!("{report}"),
+            }
+        }
+        Command::Sql { bag, query } => run_sql(&bag, &options, &query)?,
         Command::App => spawn_app()?,
+        Command::External(args) => {
+            let status = run_plugin(
+                &args,
+                cli.jobs,
+                cli.config.as_deref(),
+                cli.storage,
+                &msg_paths,
+            )?;
+            if !status.success() {
+                std::process::exit(status.code().unwrap_or(1));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints what a redaction preset modified during an export.
+fn print_redaction_report(redactor: &Redactor) {
+    let report = &redactor.report;
+    println!("Redaction ({}):", redactor.preset());
+    if report.is_empty() {
+        println!("   - Nothing to redact");
+    }
+    for (topic, count) in report.dropped_topics.iter() {
+        println!("   - Topic: {topic} | Dropped Topic: {count} messages");
+    }
+    for (topic, count) in report.coordinates.iter() {
+        println!("   - Topic: {topic} | Truncated Coordinates: {count} messages");
+    }
+    for (topic, count) in report.strings.iter() {
+        println!("   - Topic: {topic} | Masked VINs: {count} messages");
+    }
+}
+
+/// Fails if the metadata.yaml of a bag directory disagrees with the contents of its files,
+/// reporting each difference on stderr.
+fn print_metadata_differences(metadata: &BagMetadata, reader: &dyn BagReader) -> RosPeekResult<()> {
+    let differences = metadata.verify(reader)?;
+    for difference in differences.iter() {
+        eprintln!("   - {difference}");
+    }
+    if !differences.is_empty() {
+        anyhow::bail!(
+            "Found {} differences between {METADATA_FILE} and the bag files",
+            differences.len()
+        );
+    }
+    eprintln!("✨{METADATA_FILE} matches the bag files");
+    Ok(())
+}
+
+/// Warns that the recording of a bag is incomplete, so that its duration and counts may be short.
+fn print_incomplete_signs(signs: &[IncompleteSign]) {
+    if signs.is_empty() {
+        return;
+    }
+    eprintln!("⚠ The recording looks incomplete, the bag may lack its last messages:");
+    for sign in signs {
+        eprintln!("   - {sign}");
+    }
+}
+
+/// Prints the topics whose type changes between the files of a bag.
+fn print_type_changes(reader: &dyn BagReader) -> RosPeekResult<()> {
+    let mut header = false;
+    for topic in reader.topics()? {
+        let versions = reader.type_versions(&topic)?;
+        if versions.len() < 2 {
+            continue;
+        }
+        if !header {
+            println!("Type Changes:");
+            header = true;
+        }
+        println!("   - Topic: {}", topic.name);
+        for version in versions {
+            println!(
+                "       {} | Count: {} | Since: {} | Files: {}",
+                version.type_name,
+                version.count,
+                ns_to_iso(version.start_ns),
+                version.files.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the topics of a bag to keep, which are the given ones or else every topic, except the
+/// excluded ones.
+///
+/// Fails if a topic is not in the bag, so that a typo does not silently keep or drop a topic, or
+/// if no topic is left.
+fn select_topics(
+    reader: &dyn BagReader,
+    topics: &[String],
+    exclude: &[String],
+) -> RosPeekResult<Vec<String>> {
+    let names: Vec<String> = reader.topics()?.into_iter().map(|t| t.name).collect();
+    if let Some(missing) = topics.iter().chain(exclude).find(|t| !names.contains(t)) {
+        anyhow::bail!("Topic not found: {missing}");
+    }
+    let mut selected = if topics.is_empty() {
+        names
+    } else {
+        topics.to_vec()
+    };
+    selected.retain(|t| !exclude.contains(t));
+    if selected.is_empty() {
+        anyhow::bail!("Every topic is excluded");
+    }
+    Ok(selected)
+}
+
+/// Warns about the message types of the exported topics which have no definition to embed into
+/// an MCAP output, being neither installed nor embedded in the bag.
+fn print_missing_definitions(
+    reader: &dyn BagReader,
+    exported: &BTreeMap<String, u64>,
+) -> RosPeekResult<()> {
+    let mut missing = BTreeSet::new();
+    for topic in reader.topics()? {
+        if exported.contains_key(&topic.name)
+            && find_ros_idl_path(&topic.type_name).is_none()
+            && reader.embedded_schema(&topic.type_name)?.is_none()
+        {
+            missing.insert(topic.type_name);
+        }
+    }
+    if !missing.is_empty() {
+        eprintln!(
+            "⚠ {} types have no message definition in the output, being neither \
+             installed nor embedded in the input: {}",
+            missing.len(),
+            missing.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Converts a time range in simulated time into bag timestamps using the `/clock` topic.
+fn to_bag_range(
+    reader: &dyn BagReader,
+    since: Option<u64>,
+    until: Option<u64>,
+) -> RosPeekResult<(Option<u64>, Option<u64>)> {
+    let Some(clock) = ClockMap::from_reader(reader)? else {
+        anyhow::bail!("No {CLOCK_TOPIC} topic found to convert simulated time");
+    };
+    Ok((
+        since.map(|t| clock.to_bag(t)),
+        until.map(|t| clock.to_bag(t)),
+    ))
+}
+
+/// Prints the estimated output of an export topic by topic, flagging topics which grow much
+/// larger once decoded.
+fn print_estimate(estimate: &ExportEstimate, aliases: &TopicAliases) {
+    println!("Estimate (nothing written, sizes before compression):");
+    for topic in estimate.topics.iter() {
+        let mut line = format!(
+            "   - Topic: {} | Messages: {} | Size: {}",
+            aliases.label(&topic.name),
+            topic.messages,
+            format_bytes(topic.output_bytes as f64)
+        );
+        if let Some(expansion) = topic
+            .expansion()
+            .filter(|expansion| *expansion >= EXPANSION_WARNING)
+        {
+            line.push_str(&format!(" | ⚠ {expansion:.1}x its recorded size"));
+        }
+        println!("{line}");
+    }
+    println!(
+        "   - Total: {} messages | {}",
+        estimate.total_messages(),
+        format_bytes(estimate.total_bytes() as f64)
+    );
+}
+
+/// Formats a number of bytes with a binary unit prefix.
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Prints the compressed and uncompressed sizes of the chunks and topics of an MCAP bag.
+fn print_compression(bag: &Path, options: &OpenOptions) -> RosPeekResult<()> {
+    println!("Compression:");
+    if bag == Path::new(STDIN) {
+        println!("   - Not reported for bags read from the standard input");
+        return Ok(());
     }
+    if options.storage.or_else(|| StorageType::detect(bag)) != Some(StorageType::Mcap) {
+        println!("   - Only MCAP files are compressed in chunks");
+        return Ok(());
+    }
+    let Some(report) = McapReader::open(bag)?.compression_report()? else {
+        println!("   - No chunk index, the file may still be being written");
+        return Ok(());
+    };
+    let format_ratio = |ratio: Option<f64>| ratio.map_or("-".to_string(), |r| format!("{r:.2}x"));
 
+    for (compression, (compressed, uncompressed, count)) in report.by_compression() {
+        println!(
+            "   - Chunks: {count} | Compression: {} | Compressed: {} | Uncompressed: {} | Ratio: {}",
+            if compression.is_empty() {
+                "none"
+            } else {
+                compression
+            },
+            format_bytes(compressed as f64),
+            format_bytes(uncompressed as f64),
+            format_ratio((compressed > 0).then(|| uncompressed as f64 / compressed as f64))
+        );
+    }
+    let sizes: Vec<u64> = report.chunks.iter().map(|c| c.uncompressed_size).collect();
+    if let (Some(min), Some(max)) = (sizes.iter().min(), sizes.iter().max()) {
+        println!(
+            "   - Chunk Size (uncompressed): min {} | mean {} | max {} | Messages per Chunk: {:.1}",
+            format_bytes(*min as f64),
+            format_bytes(sizes.iter().sum::<u64>() as f64 / sizes.len() as f64),
+            format_bytes(*max as f64),
+            report.chunks.iter().map(|c| c.message_count).sum::<u64>() as f64 / sizes.len() as f64
+        );
+    }
+    for channel in report.channels.iter() {
+        println!(
+            "   - Topic: {} | Uncompressed: {} | Compressed (estimated): {} | Ratio: {}",
+            channel.topic,
+            format_bytes(channel.message_bytes as f64),
+            format_bytes(channel.compressed_bytes),
+            format_ratio(channel.ratio())
+        );
+    }
+    println!("   - Total Ratio: {}", format_ratio(report.ratio()));
     Ok(())
 }
+
+/// Flattens nested fields of timestamped values into CSV columns and rows.
+///
+/// Values which are not objects are written into a `value` column.
+fn flatten_rows(
+    records: &[(u64, serde_json::Value)],
+) -> RosPeekResult<(Vec<String>, Vec<(u64, Vec<String>)>)> {
+    let mut flattened = Vec::with_capacity(records.len());
+    let mut columns = BTreeSet::new();
+    for (timestamp, value) in records {
+        let row = match value {
+            serde_json::Value::Object(object) => flatten_json(object)?,
+            other => serde_json::Map::from_iter([("value".to_string(), other.clone())]),
+        };
+        columns.extend(row.keys().cloned());
+        flattened.push((*timestamp, row));
+    }
+
+    let rows = flattened
+        .into_iter()
+        .map(|(timestamp, row)| {
+            let values = columns
+                .iter()
+                .map(|col| row.get(col).map(|v| v.to_string()).unwrap_or_default())
+                .collect();
+            (timestamp, values)
+        })
+        .collect();
+    Ok((columns.into_iter().collect(), rows))
+}
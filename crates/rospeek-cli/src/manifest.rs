@@ -0,0 +1,225 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, bail};
+use rospeek_core::{BagReader, OpenOptions, RosPeekResult};
+use rospeek_gui::create_reader_with_options;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+/// Version of the manifest layout, bumped when fields change incompatibly.
+const MANIFEST_VERSION: u64 = 1;
+
+/// Suffix of the default manifest file name.
+pub(crate) const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// Builds a manifest describing a bag, to be shared along with it.
+///
+/// The manifest records the size and SHA-256 hash of every file of the bag, along with its
+/// topics, message counts and time range.
+///
+/// # Arguments
+/// * `bag` - Path to the bag file or directory.
+/// * `options` - How to open the bag.
+pub(crate) fn build_manifest(bag: &Path, options: &OpenOptions) -> RosPeekResult<Value> {
+    let reader = create_reader_with_options(bag, options)?;
+    let stats = reader.stats()?;
+
+    let files = bag_files(bag)?
+        .iter()
+        .map(|(name, path)| file_entry(name, path))
+        .collect::<RosPeekResult<Vec<_>>>()?;
+
+    Ok(json!({
+        "manifest_version": MANIFEST_VERSION,
+        "tool": {
+            "name": "rospeek",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "bag": bag_name(bag)?,
+        "storage_type": stats.storage_type.to_string(),
+        "message_count": stats.message_count,
+        "start_ns": stats.start_ns,
+        "end_ns": stats.end_ns,
+        "start_time": stats.start_time,
+        "end_time": stats.end_time,
+        "duration_sec": stats.duration_sec,
+        "files": files,
+        "topics": topic_entries(reader.as_ref())?,
+    }))
+}
+
+/// Checks a copy of a bag against its manifest.
+///
+/// # Arguments
+/// * `manifest` - The manifest built by [`build_manifest`].
+/// * `bag` - Path to the copy of the bag file or directory.
+/// * `options` - How to open the bag.
+///
+/// # Returns
+/// Descriptions of the differences found, empty if the copy matches the manifest.
+pub(crate) fn verify_manifest(
+    manifest: &Value,
+    bag: &Path,
+    options: &OpenOptions,
+) -> RosPeekResult<Vec<String>> {
+    let version = manifest["manifest_version"].as_u64().unwrap_or_default();
+    if version != MANIFEST_VERSION {
+        bail!("Unsupported manifest version: {version}");
+    }
+
+    let mut problems = Vec::new();
+    let actual = bag_files(bag)?;
+    let expected = manifest["files"].as_array().cloned().unwrap_or_default();
+    for entry in expected.iter() {
+        let name = entry["path"].as_str().unwrap_or_default();
+        let Some((_, path)) = actual.iter().find(|(n, _)| n == name) else {
+            problems.push(format!("Missing file: {name}"));
+            continue;
+        };
+        let size = path.metadata()?.len();
+        if Some(size) != entry["size"].as_u64() {
+            problems.push(format!(
+                "Size mismatch for {name}: expected {}, found {size}",
+                entry["size"]
+            ));
+            continue;
+        }
+        let hash = sha256_file(path)?;
+        if Some(hash.as_str()) != entry["sha256"].as_str() {
+            problems.push(format!("Checksum mismatch for {name}"));
+        }
+    }
+    for (name, _) in actual.iter() {
+        if !expected
+            .iter()
+            .any(|e| e["path"].as_str() == Some(name.as_str()))
+        {
+            problems.push(format!("Unexpected file: {name}"));
+        }
+    }
+
+    // the files may hash correctly but still fail to open, e.g. with a different storage plugin
+    let reader = create_reader_with_options(bag, options)?;
+    let topics = topic_entries(reader.as_ref())?;
+    let expected = manifest["topics"].as_array().cloned().unwrap_or_default();
+    for entry in expected.iter() {
+        let name = entry["name"].as_str().unwrap_or_default();
+        match topics.iter().find(|t| t["name"] == entry["name"]) {
+            None => problems.push(format!("Missing topic: {name}")),
+            Some(topic) if topic["count"] != entry["count"] => problems.push(format!(
+                "Message count mismatch for {name}: expected {}, found {}",
+                entry["count"], topic["count"]
+            )),
+            Some(topic) if topic["type"] != entry["type"] => problems.push(format!(
+                "Type mismatch for {name}: expected {}, found {}",
+                entry["type"], topic["type"]
+            )),
+            Some(_) => {}
+        }
+    }
+    for topic in topics.iter() {
+        if !expected.iter().any(|e| e["name"] == topic["name"]) {
+            problems.push(format!("Unexpected topic: {}", topic["name"]));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Resolves the bag a manifest describes, which is expected next to the manifest by default.
+pub(crate) fn manifest_bag(manifest_path: &Path, manifest: &Value) -> RosPeekResult<PathBuf> {
+    let Some(name) = manifest["bag"].as_str() else {
+        bail!(
+            "Manifest does not name its bag: {}",
+            manifest_path.display()
+        );
+    };
+    let dir = manifest_path.parent().unwrap_or(Path::new(""));
+    Ok(dir.join(name))
+}
+
+/// Returns the default path of the manifest of a bag, named after the bag.
+pub(crate) fn manifest_path(bag: &Path) -> RosPeekResult<PathBuf> {
+    Ok(PathBuf::from(format!(
+        "{}{MANIFEST_SUFFIX}",
+        bag_name(bag)?
+    )))
+}
+
+pub(crate) fn bag_name(bag: &Path) -> RosPeekResult<String> {
+    bag.canonicalize()?
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .with_context(|| format!("Invalid bag path: {}", bag.display()))
+}
+
+/// Returns the files of a bag paired with their paths relative to the bag directory.
+///
+/// A bag file is its only file, while every regular file of a bag directory is included,
+/// such as `metadata.yaml`, except manifests written into it.
+pub(crate) fn bag_files(bag: &Path) -> RosPeekResult<Vec<(String, PathBuf)>> {
+    if !bag.is_dir() {
+        return Ok(vec![(bag_name(bag)?, bag.to_path_buf())]);
+    }
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(bag)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if path.is_file() && !name.ends_with(MANIFEST_SUFFIX) {
+            files.push((name, path));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn file_entry(name: &str, path: &Path) -> RosPeekResult<Value> {
+    Ok(json!({
+        "path": name,
+        "size": path.metadata()?.len(),
+        "sha256": sha256_file(path)?,
+    }))
+}
+
+fn topic_entries(reader: &dyn BagReader) -> RosPeekResult<Vec<Value>> {
+    let mut topics = reader.topics()?;
+    topics.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(topics
+        .into_iter()
+        .map(|topic| {
+            json!({
+                "name": topic.name,
+                "type": topic.type_name,
+                "count": topic.count,
+                "serialization_format": topic.serialization_format,
+            })
+        })
+        .collect())
+}
+
+/// Hashes a file in chunks, returning the digest as lowercase hex.
+fn sha256_file(path: &Path) -> RosPeekResult<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 20];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
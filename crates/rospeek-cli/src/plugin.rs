@@ -0,0 +1,88 @@
+use std::{
+    ffi::OsString,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+};
+
+use clap::ValueEnum;
+use rospeek_core::RosPeekResult;
+
+use crate::command::StorageFormat;
+
+/// Prefix of the binaries run for unknown subcommands, such as `rospeek-lanes` for `rospeek lanes`.
+const PLUGIN_PREFIX: &str = "rospeek-";
+
+/// Runs an external subcommand, cargo-style, from a `rospeek-<subcommand>` binary on `PATH`.
+///
+/// The arguments following the subcommand are passed through as is. Global flags are passed as
+/// environment variables, which plugins are expected to honor:
+/// * `ROSPEEK_CONFIG` - Path to the config file given with `--config`.
+/// * `ROSPEEK_JOBS` - Number of decoding threads given with `--jobs`.
+/// * `ROSPEEK_STORAGE` - Storage given with `--storage`, either `db3` or `mcap`.
+/// * `ROSPEEK_MSG_PATH` - Directories given with `--msg-path`, joined like `PATH`.
+/// * `ROSPEEK_BAG` - The first argument naming an existing bag file or bag directory.
+///
+/// # Arguments
+/// * `args` - The subcommand followed by its arguments.
+/// * `jobs` - Number of threads decoding messages given with `--jobs`, if any.
+/// * `config` - Path to the config file given with `--config`, if any.
+/// * `storage` - Storage to read bag files as given with `--storage`, if any.
+/// * `msg_paths` - Directories searched for message definitions given with `--msg-path`.
+///
+/// # Returns
+/// The exit status of the plugin.
+pub(crate) fn run_plugin(
+    args: &[OsString],
+    jobs: Option<usize>,
+    config: Option<&Path>,
+    storage: Option<StorageFormat>,
+    msg_paths: &[PathBuf],
+) -> RosPeekResult<ExitStatus> {
+    let Some((subcommand, args)) = args.split_first() else {
+        anyhow::bail!("No subcommand given");
+    };
+    let mut binary = OsString::from(PLUGIN_PREFIX);
+    binary.push(subcommand);
+
+    let mut command = std::process::Command::new(&binary);
+    command.args(args);
+    if let Some(config) = config {
+        command.env("ROSPEEK_CONFIG", config);
+    }
+    if let Some(jobs) = jobs {
+        command.env("ROSPEEK_JOBS", jobs.to_string());
+    }
+    if let Some(storage) = storage.and_then(|storage| storage.to_possible_value()) {
+        command.env("ROSPEEK_STORAGE", storage.get_name());
+    }
+    if !msg_paths.is_empty() {
+        let joined = std::env::join_paths(msg_paths)
+            .map_err(|e| anyhow::anyhow!("Failed to pass message paths to the plugin: {e}"))?;
+        command.env("ROSPEEK_MSG_PATH", joined);
+    }
+    if let Some(bag) = args
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_dir() || (path.is_file() && is_bag_file(path)))
+    {
+        command.env("ROSPEEK_BAG", bag);
+    }
+
+    command.status().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => anyhow::anyhow!(
+            "Unknown subcommand: {}; no {} binary was found on PATH",
+            subcommand.to_string_lossy(),
+            binary.to_string_lossy()
+        ),
+        _ => anyhow::anyhow!("Failed to run {}: {e}", binary.to_string_lossy()),
+    })
+}
+
+/// Returns whether a file is a bag file by its extension.
+fn is_bag_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("db3" | "mcap" | "age")
+    )
+}
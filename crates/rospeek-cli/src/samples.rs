@@ -0,0 +1,233 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use rospeek_core::{
+    BagReader, CameraInfo, CdrDecoder, MessageSchema, POINT_CLOUD_TYPE, PointCloud, RawMessage,
+    RosPeekResult, SampleOptions, UndistortMap, find_camera_info_topic, sample_at_triggers,
+};
+use rospeek_gui::images::{ImageMessage, is_image_type, undistort};
+use serde_json::{Value, json};
+
+/// Name of the file listing the samples at the root of the output directory.
+const INDEX_FILE: &str = "samples.json";
+
+/// Counts of an export of samples.
+pub(crate) struct SampleSummary {
+    pub(crate) written: usize,
+    /// Samples left out because some topics had no message close enough to the trigger.
+    pub(crate) skipped: usize,
+}
+
+/// Options of an export of samples besides the sampling itself.
+pub(crate) struct SampleExportOptions {
+    /// Whether to keep samples where some topics have no message.
+    pub(crate) keep_incomplete: bool,
+    /// Whether to undistort images with the camera info topic of their namespace.
+    pub(crate) undistort: bool,
+    /// Fields kept in point clouds in this order, all of them when empty.
+    pub(crate) point_fields: Vec<String>,
+    /// Leaf size of the voxel grid downsampling point clouds.
+    pub(crate) voxel_size: Option<f64>,
+}
+
+/// Writes the messages of a topic into its directory, one file per sample.
+struct TopicFiles {
+    topic: String,
+    type_name: String,
+    /// Directory of the topic relative to the output directory.
+    dir: PathBuf,
+    schema: Option<MessageSchema>,
+    /// Undistortion of the images by the camera info of the topic.
+    map: Option<UndistortMap>,
+    point_fields: Vec<String>,
+    voxel_size: Option<f64>,
+}
+
+impl TopicFiles {
+    fn new(
+        reader: &dyn BagReader,
+        topic: &str,
+        options: &SampleExportOptions,
+    ) -> RosPeekResult<Self> {
+        let topics = reader.topics()?;
+        let type_name = topics
+            .iter()
+            .find(|t| t.name == topic)
+            .map(|t| t.type_name.clone())
+            .with_context(|| format!("Topic not found: {topic}"))?;
+        // images and point clouds are read without their schema
+        let schema = match is_image_type(&type_name) || type_name == POINT_CLOUD_TYPE {
+            true => None,
+            false => Some(MessageSchema::try_from(type_name.as_str())?),
+        };
+        let map = match options.undistort && is_image_type(&type_name) {
+            true => {
+                let info_topic = find_camera_info_topic(topic, &topics)
+                    .with_context(|| format!("No camera info topic found for {topic}"))?;
+                let message = reader
+                    .read_messages_by_index(&info_topic.name, 0, 1)?
+                    .pop()
+                    .with_context(|| format!("No message in {}", info_topic.name))?;
+                let map = CameraInfo::from_cdr(&message.data)?
+                    .undistort_map()
+                    .with_context(|| format!("Failed to undistort {topic}"))?;
+                Some(map)
+            }
+            false => None,
+        };
+        Ok(Self {
+            topic: topic.to_string(),
+            type_name,
+            dir: PathBuf::from(topic.trim_start_matches('/')),
+            schema,
+            map,
+            point_fields: options.point_fields.clone(),
+            voxel_size: options.voxel_size,
+        })
+    }
+
+    /// Writes a message as an image, a PCD file or JSON depending on its type.
+    ///
+    /// # Returns
+    /// The path of the written file relative to the output directory.
+    fn write(&self, output: &Path, index: usize, message: &RawMessage) -> RosPeekResult<PathBuf> {
+        let stem = self.dir.join(format!("{index:06}"));
+        let path = if let Some(schema) = &self.schema {
+            let value = CdrDecoder::from_schema(schema)
+                .reset(&message.data)
+                .decode(schema)?;
+            let path = stem.with_extension("json");
+            serde_json::to_writer_pretty(create_file(&output.join(&path))?, &value)?;
+            path
+        } else if self.type_name == POINT_CLOUD_TYPE {
+            let path = stem.with_extension("pcd");
+            let mut cloud = PointCloud::from_cdr(&message.data)?;
+            if let Some(leaf_size) = self.voxel_size {
+                cloud = cloud.voxel_downsample(leaf_size)?;
+            }
+            if !self.point_fields.is_empty() {
+                cloud = cloud.select_fields(&self.point_fields)?;
+            }
+            cloud.write_pcd(create_file(&output.join(&path))?)?;
+            path
+        } else if let Some(map) = &self.map {
+            let image = ImageMessage::from_cdr(&self.type_name, &message.data)?.decode()?;
+            let path = stem.with_extension("png");
+            undistort(&image, map)?.save(output.join(&path))?;
+            path
+        } else {
+            match ImageMessage::from_cdr(&self.type_name, &message.data)? {
+                // compressed images are kept as recorded rather than encoded again
+                ImageMessage::Compressed { format, data } => {
+                    let path = stem.with_extension(compressed_extension(format));
+                    std::fs::write(output.join(&path), data)?;
+                    path
+                }
+                ImageMessage::Raw(image) => {
+                    let path = stem.with_extension("png");
+                    image.to_image()?.save(output.join(&path))?;
+                    path
+                }
+            }
+        };
+        Ok(path)
+    }
+}
+
+/// Exports the messages of topics nearest to each message of a trigger topic into a directory,
+/// such as the camera images and pose at every lidar frame.
+///
+/// Each topic gets a directory named by the topic, holding one file per sample named by the
+/// sample number: images as PNG or as recorded when compressed, point clouds as binary PCD and
+/// other messages as JSON. Undistorted images are written as PNG. A `samples.json` index lists
+/// the timestamp and files of each sample.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `trigger` - Name of the trigger topic.
+/// * `topics` - Names of the topics to export, which may include the trigger topic.
+/// * `sampling` - Stride and maximum time offset of the sampling.
+/// * `options` - How the samples are kept and written.
+/// * `output` - Path to the output directory.
+pub(crate) fn export_samples(
+    reader: &dyn BagReader,
+    trigger: &str,
+    topics: &[String],
+    sampling: &SampleOptions,
+    options: &SampleExportOptions,
+    output: &Path,
+) -> RosPeekResult<SampleSummary> {
+    let files = topics
+        .iter()
+        .map(|topic| TopicFiles::new(reader, topic, options))
+        .collect::<RosPeekResult<Vec<_>>>()?;
+    for topic in files.iter() {
+        std::fs::create_dir_all(output.join(&topic.dir))
+            .with_context(|| format!("Failed to create {}", output.join(&topic.dir).display()))?;
+    }
+
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    sample_at_triggers(reader, trigger, topics, sampling, |sample| {
+        if !options.keep_incomplete && !sample.is_complete() {
+            skipped += 1;
+            return Ok(());
+        }
+        let index = entries.len();
+        let mut messages = serde_json::Map::new();
+        for (topic, message) in files.iter().zip(sample.messages.iter()) {
+            let entry = match message {
+                Some(message) => {
+                    let path = topic.write(output, index, message).with_context(|| {
+                        format!("Failed to write {} at {}", topic.topic, message.timestamp)
+                    })?;
+                    json!({
+                        "file": path.to_string_lossy(),
+                        "timestamp": message.timestamp,
+                    })
+                }
+                None => Value::Null,
+            };
+            messages.insert(topic.topic.clone(), entry);
+        }
+        entries.push(json!({
+            "index": index,
+            "trigger_index": sample.index,
+            "timestamp": sample.timestamp,
+            "messages": messages,
+        }));
+        Ok(())
+    })?;
+
+    let index = json!({
+        "trigger": trigger,
+        "topics": topics,
+        "samples": entries,
+    });
+    serde_json::to_writer_pretty(create_file(&output.join(INDEX_FILE))?, &index)?;
+    Ok(SampleSummary {
+        written: entries.len(),
+        skipped,
+    })
+}
+
+fn create_file(path: &Path) -> RosPeekResult<BufWriter<File>> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    Ok(BufWriter::new(file))
+}
+
+/// Returns the file extension of a `CompressedImage` format, such as `rgb8; jpeg compressed bgr8`.
+fn compressed_extension(format: &str) -> &'static str {
+    if format.contains("png") {
+        "png"
+    } else if format.contains("jpeg") || format.contains("jpg") {
+        "jpg"
+    } else {
+        "bin"
+    }
+}
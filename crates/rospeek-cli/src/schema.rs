@@ -0,0 +1,333 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::Path,
+};
+
+use anyhow::bail;
+use rospeek_core::{
+    BagReader, FieldType, FieldUnits, MessageField, MessageSchema, Primitive, RosPeekResult,
+    TypeName, find_ros_idl_path, parse_idl_str, parse_idl_to_schema, split_ros2idl,
+};
+use serde_json::json;
+
+/// Resolves message definitions from installed IDL files, falling back to the ones embedded in the bag.
+pub(crate) struct SchemaResolver {
+    embedded: HashMap<String, String>,
+}
+
+impl SchemaResolver {
+    /// Creates a resolver with the definitions embedded in the bag for a type and its dependencies.
+    pub(crate) fn new(reader: &dyn BagReader, type_name: &str) -> RosPeekResult<Self> {
+        let embedded = match reader.embedded_schema(type_name)? {
+            Some(schema) if schema.encoding == "ros2idl" => {
+                split_ros2idl(type_name, &String::from_utf8_lossy(&schema.data))
+            }
+            _ => HashMap::new(),
+        };
+        Ok(Self { embedded })
+    }
+
+    /// Returns the schema of a type along with a description of where it was resolved from.
+    pub(crate) fn resolve(&self, type_name: &str) -> Option<(MessageSchema, String)> {
+        if let Some(path) = find_ros_idl_path(type_name)
+            && let Ok(schema) = parse_idl_to_schema(&path, type_name)
+        {
+            return Some((schema, path.display().to_string()));
+        }
+        if let Some(idl) = self.embedded.get(type_name) {
+            return Some((
+                parse_idl_str(idl, type_name),
+                "embedded in bag (ros2idl)".to_string(),
+            ));
+        }
+        builtin_time_schema(type_name).map(|schema| (schema, "built-in".to_string()))
+    }
+
+    /// Returns the IDL definition of a type along with a description of where it was found.
+    ///
+    /// Unlike [`SchemaResolver::resolve`], the definition embedded in the bag comes first, as it
+    /// is the one the messages were recorded with.
+    pub(crate) fn resolve_idl(&self, type_name: &str) -> Option<(String, String)> {
+        if let Some(idl) = self.embedded.get(type_name) {
+            return Some((idl.clone(), "embedded in bag (ros2idl)".to_string()));
+        }
+        let path = find_ros_idl_path(type_name)?;
+        let idl = std::fs::read_to_string(&path).ok()?;
+        Some((idl, path.display().to_string()))
+    }
+}
+
+/// Message definitions written by [`export_schemas`].
+pub(crate) struct SchemaExport {
+    /// Exported types with where their definitions were found.
+    pub(crate) exported: BTreeMap<String, String>,
+    /// Types whose definitions were not found.
+    pub(crate) missing: BTreeSet<String>,
+}
+
+/// Writes the IDL definitions of the types of every topic of a bag and of their nested types
+/// into `<DIR>/<package>/msg/<Name>.idl`, so that the bag can be decoded with `--msg-path <DIR>`
+/// where the packages are not installed.
+///
+/// # Arguments
+/// * `reader` - The bag reader to look up types and embedded definitions from.
+/// * `dir` - Directory to write the definitions into.
+pub(crate) fn export_schemas(reader: &dyn BagReader, dir: &Path) -> RosPeekResult<SchemaExport> {
+    let roots: BTreeSet<String> = reader.topics()?.into_iter().map(|t| t.type_name).collect();
+
+    let mut exported = BTreeMap::new();
+    let mut missing = BTreeSet::new();
+    for root in roots {
+        // definitions embedded along with a type may be the only ones of its nested types
+        let resolver = SchemaResolver::new(reader, &root)?;
+        let mut pending = vec![root];
+        while let Some(type_name) = pending.pop() {
+            if exported.contains_key(&type_name) {
+                continue;
+            }
+            let resolved = resolver
+                .resolve_idl(&type_name)
+                .zip(TypeName::parse(&type_name));
+            let Some(((idl, origin), name)) = resolved else {
+                // Time and Duration are decoded without their IDL
+                if builtin_time_schema(&type_name).is_none() {
+                    missing.insert(type_name);
+                }
+                continue;
+            };
+            let path = dir
+                .join(name.package)
+                .join(name.kind)
+                .join(format!("{}.idl", name.name));
+            std::fs::create_dir_all(dir.join(name.package).join(name.kind))?;
+            std::fs::write(&path, &idl)?;
+
+            pending.extend(
+                parse_idl_str(&idl, &type_name)
+                    .fields
+                    .iter()
+                    .map(|field| field.type_name().to_string())
+                    .filter(|nested| !is_builtin(nested)),
+            );
+            exported.insert(type_name, origin);
+        }
+    }
+    missing.retain(|type_name| !exported.contains_key(type_name));
+
+    Ok(SchemaExport { exported, missing })
+}
+
+/// Returns the schema of `Time` and `Duration`, which the decoder handles without their IDL.
+fn builtin_time_schema(type_name: &str) -> Option<MessageSchema> {
+    match type_name {
+        "builtin_interfaces/msg/Time" | "builtin_interfaces/msg/Duration" => Some(MessageSchema {
+            type_name: type_name.to_string(),
+            fields: vec![
+                MessageField {
+                    name: "sec".into(),
+                    field_type: FieldType::Object("int32".into()),
+                },
+                MessageField {
+                    name: "nanosec".into(),
+                    field_type: FieldType::Object("uint32".into()),
+                },
+            ],
+        }),
+        _ => None,
+    }
+}
+
+/// Returns whether a type is decoded as a single value rather than a nested structure.
+pub(crate) fn is_builtin(type_name: &str) -> bool {
+    Primitive::from_type_name(type_name).is_some() || type_name == "string"
+}
+
+/// Returns the type of a field as written in IDL, such as `sequence<double>` or `int32[3]`.
+pub(crate) fn type_decl(field_type: &FieldType) -> String {
+    match field_type {
+        FieldType::Object(t) => t.clone(),
+        FieldType::Sequence(t) => format!("sequence<{t}>"),
+        FieldType::Array(t, n) => format!("{t}[{n}]"),
+    }
+}
+
+/// Returns whether a type is a number, which units apply to.
+fn is_numeric(type_name: &str) -> bool {
+    Primitive::from_type_name(type_name)
+        .is_some_and(|p| !matches!(p, Primitive::Bool | Primitive::Char))
+}
+
+/// Prints the resolved definition of a message type with nested types expanded, labelling
+/// fields with their units.
+///
+/// # Arguments
+/// * `reader` - The bag reader to look up embedded definitions from.
+/// * `topic` - Name of the topic whose units are looked up, or an empty string if there is none.
+/// * `type_name` - Name of ROS message type, such as `foo_msgs/msg/Foo`.
+pub(crate) fn print_schema(
+    reader: &dyn BagReader,
+    topic: &str,
+    type_name: &str,
+) -> RosPeekResult<()> {
+    let resolver = SchemaResolver::new(reader, type_name)?;
+
+    let Some((schema, origin)) = resolver.resolve(type_name) else {
+        // definitions in other encodings can still be shown as they are
+        if let Some(embedded) = reader.embedded_schema(type_name)? {
+            println!("Type: {type_name}");
+            println!("Source: embedded in bag ({})", embedded.encoding);
+            println!("{}", String::from_utf8_lossy(&embedded.data));
+            return Ok(());
+        }
+        bail!("Message definition not found for {type_name}");
+    };
+
+    println!("Type: {type_name}");
+    println!("Source: {origin}");
+    println!("Fields:");
+    let units = FieldUnits::global();
+    let unit = |path: &str| units.unit(topic, type_name, path).map(str::to_string);
+    print_fields(
+        &resolver,
+        &schema,
+        "",
+        &unit,
+        &mut vec![type_name.to_string()],
+    );
+    Ok(())
+}
+
+fn print_fields(
+    resolver: &SchemaResolver,
+    schema: &MessageSchema,
+    prefix: &str,
+    unit: &dyn Fn(&str) -> Option<String>,
+    parents: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(parents.len());
+    for field in schema.fields.iter() {
+        let type_name = field.type_name();
+        let decl = type_decl(&field.field_type);
+        let path = format!("{prefix}{}", field.name);
+        if is_builtin(type_name) || parents.iter().any(|p| p == type_name) {
+            match unit(&path).filter(|_| is_numeric(type_name)) {
+                Some(unit) => println!("{indent}{}: {decl}  ({unit})", field.name),
+                None => println!("{indent}{}: {decl}", field.name),
+            }
+            continue;
+        }
+
+        match resolver.resolve(type_name) {
+            Some((nested, origin)) => {
+                println!("{indent}{}: {decl}  [{origin}]", field.name);
+                parents.push(type_name.to_string());
+                print_fields(resolver, &nested, &format!("{path}."), unit, parents);
+                parents.pop();
+            }
+            None => println!("{indent}{}: {decl}  [definition not found]", field.name),
+        }
+    }
+}
+
+/// Builds a JSON Schema document describing the decoded JSON of a message type.
+///
+/// Nested types are described once in `$defs` and referenced with `$ref`.
+///
+/// # Arguments
+/// * `resolver` - The resolver to look up message definitions with.
+/// * `type_name` - Name of ROS message type, such as `foo_msgs/msg/Foo`.
+pub(crate) fn json_schema(
+    resolver: &SchemaResolver,
+    type_name: &str,
+) -> RosPeekResult<serde_json::Value> {
+    let Some((schema, _)) = resolver.resolve(type_name) else {
+        bail!("Message definition not found for {type_name}");
+    };
+
+    let mut defs = serde_json::Map::new();
+    let mut root = object_schema(resolver, &schema, &mut defs)?;
+    if let serde_json::Value::Object(root) = &mut root {
+        root.insert(
+            "$schema".to_string(),
+            json!("https://json-schema.org/draft/2020-12/schema"),
+        );
+        if !defs.is_empty() {
+            root.insert("$defs".to_string(), serde_json::Value::Object(defs));
+        }
+    }
+    Ok(root)
+}
+
+fn object_schema(
+    resolver: &SchemaResolver,
+    schema: &MessageSchema,
+    defs: &mut serde_json::Map<String, serde_json::Value>,
+) -> RosPeekResult<serde_json::Value> {
+    let mut properties = serde_json::Map::new();
+    for field in schema.fields.iter() {
+        let element = element_schema(resolver, field.type_name(), defs)?;
+        let value = match field.field_type {
+            FieldType::Object(_) => element,
+            FieldType::Sequence(_) => json!({"type": "array", "items": element}),
+            FieldType::Array(_, n) => {
+                json!({"type": "array", "items": element, "minItems": n, "maxItems": n})
+            }
+        };
+        properties.insert(field.name.clone(), value);
+    }
+
+    let required: Vec<_> = schema.fields.iter().map(|f| f.name.clone()).collect();
+    Ok(json!({
+        "title": schema.type_name,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    }))
+}
+
+fn element_schema(
+    resolver: &SchemaResolver,
+    type_name: &str,
+    defs: &mut serde_json::Map<String, serde_json::Value>,
+) -> RosPeekResult<serde_json::Value> {
+    if type_name == "string" {
+        return Ok(json!({"type": "string"}));
+    }
+    if let Some(primitive) = Primitive::from_type_name(type_name) {
+        return Ok(match primitive {
+            Primitive::Bool => json!({"type": "boolean"}),
+            Primitive::Char => json!({"type": "string", "minLength": 1, "maxLength": 1}),
+            // non-finite values are serialized as null
+            Primitive::Float | Primitive::Double => json!({"type": ["number", "null"]}),
+            Primitive::Octet | Primitive::Uint8 => integer_schema(u8::MIN, u8::MAX),
+            Primitive::Int8 => integer_schema(i8::MIN, i8::MAX),
+            Primitive::Int16 => integer_schema(i16::MIN, i16::MAX),
+            Primitive::Uint16 => integer_schema(u16::MIN, u16::MAX),
+            Primitive::Int32 => integer_schema(i32::MIN, i32::MAX),
+            Primitive::Uint32 => integer_schema(u32::MIN, u32::MAX),
+            Primitive::Int64 => integer_schema(i64::MIN, i64::MAX),
+            Primitive::Uint64 => integer_schema(u64::MIN, u64::MAX),
+        });
+    }
+
+    // `/` would need escaping in JSON pointers
+    let key = type_name.replace('/', ".");
+    if !defs.contains_key(&key) {
+        let Some((schema, _)) = resolver.resolve(type_name) else {
+            bail!("Message definition not found for {type_name}");
+        };
+        // reserve the key first so that recursive types terminate
+        defs.insert(key.clone(), json!({}));
+        let value = object_schema(resolver, &schema, defs)?;
+        defs.insert(key.clone(), value);
+    }
+    Ok(json!({"$ref": format!("#/$defs/{key}")}))
+}
+
+fn integer_schema(
+    minimum: impl Into<serde_json::Value>,
+    maximum: impl Into<serde_json::Value>,
+) -> serde_json::Value {
+    json!({"type": "integer", "minimum": minimum.into(), "maximum": maximum.into()})
+}
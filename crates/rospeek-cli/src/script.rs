@@ -0,0 +1,267 @@
+use std::{collections::HashMap, path::Path};
+
+use mlua::{Function, Lua, LuaSerdeExt};
+use rospeek_core::{CdrEncoder, Endianness, MessageSchema, Record, RosPeekResult, Stage};
+use serde_json::Value;
+
+/// User-supplied Lua script transforming decoded messages.
+///
+/// The script defines a global `transform(topic, message)` function, which receives the topic
+/// name and the decoded message as a table, and returns the transformed message or `nil` to drop it.
+pub(crate) struct LuaScript {
+    name: String,
+    lua: Lua,
+    transform: Function,
+    /// Schemas of the types whose messages were modified, to encode them back.
+    schemas: HashMap<String, MessageSchema>,
+}
+
+impl LuaScript {
+    /// Loads a script and looks up its `transform` function.
+    pub(crate) fn load(path: &Path) -> RosPeekResult<Self> {
+        let name = path.display().to_string();
+        let source = std::fs::read_to_string(path)?;
+
+        let lua = Lua::new();
+        lua.load(source.as_str())
+            .set_name(name.as_str())
+            .exec()
+            .map_err(|e| anyhow::anyhow!("Failed to load script {name}: {e}"))?;
+        let transform = lua.globals().get::<Function>("transform").map_err(|_| {
+            anyhow::anyhow!("Script {name} does not define transform(topic, message)")
+        })?;
+
+        Ok(Self {
+            name,
+            lua,
+            transform,
+            schemas: HashMap::new(),
+        })
+    }
+
+    /// Transforms a decoded message.
+    ///
+    /// # Returns
+    /// The transformed message, or `None` if the script dropped it.
+    pub(crate) fn transform(&self, topic: &str, value: Value) -> RosPeekResult<Option<Value>> {
+        let lua = &self.lua;
+        let result = lua
+            .to_value(&value)
+            .and_then(|message| self.transform.call::<mlua::Value>((topic, message)))
+            .and_then(|result| {
+                if result.is_nil() {
+                    Ok(None)
+                } else {
+                    lua.from_value::<Value>(result).map(Some)
+                }
+            });
+        result.map_err(|e| anyhow::anyhow!("Script {} failed on {topic}: {e}", self.name))
+    }
+
+    /// Transforms decoded messages of a topic along with their timestamps, leaving out dropped
+    /// ones.
+    pub(crate) fn transform_all(
+        &self,
+        topic: &str,
+        records: Vec<(u64, Value)>,
+    ) -> RosPeekResult<Vec<(u64, Value)>> {
+        let mut transformed = Vec::with_capacity(records.len());
+        for (timestamp, value) in records {
+            if let Some(value) = self.transform(topic, value)? {
+                transformed.push((timestamp, value));
+            }
+        }
+        Ok(transformed)
+    }
+}
+
+/// Drops the records for which the script returns `nil`, and encodes the messages it modified
+/// back into CDR with the byte order they were recorded with.
+///
+/// Unmodified messages are exported as recorded. A modified message must keep every field of
+/// its type, since it is encoded with the schema of the topic.
+impl Stage for LuaScript {
+    fn process(&mut self, mut record: Record) -> RosPeekResult<Option<Record>> {
+        let value = record.value.take().unwrap_or(Value::Null);
+        let Some(transformed) = self.transform(&record.topic.name, value.clone())? else {
+            return Ok(None);
+        };
+        if transformed != value {
+            let type_name = &record.topic.type_name;
+            if !self.schemas.contains_key(type_name) {
+                let schema = MessageSchema::try_from(type_name.as_str())?;
+                self.schemas.insert(type_name.clone(), schema);
+            }
+            let endianness = Endianness::from(record.message.data.as_slice());
+            record.message.data = CdrEncoder::new(endianness)
+                .encode(&self.schemas[type_name], &transformed)
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Script {} returned a message of {} which cannot be encoded as {type_name}: {e}",
+                        self.name,
+                        record.topic.name
+                    )
+                })?;
+        }
+        record.value = Some(transformed);
+        Ok(Some(record))
+    }
+
+    fn name(&self) -> &str {
+        "Script"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use rospeek_core::{CdrDecoder, RawMessage, Topic, parse_idl_str, set_msg_paths};
+    use serde_json::json;
+
+    use super::*;
+
+    const IDL: &str = "module foo_msgs {\n  module msg {\n    struct Foo {\n      uint8 id;\n      double x;\n    };\n  };\n};";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rospeek-script-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+        dir
+    }
+
+    fn load(dir: &Path, source: &str) -> RosPeekResult<LuaScript> {
+        let path = dir.join("script.lua");
+        std::fs::write(&path, source).expect("Failed to write script");
+        LuaScript::load(&path)
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = temp_dir("load");
+        assert!(
+            load(
+                &dir,
+                "function transform(topic, message) return message end"
+            )
+            .is_ok()
+        );
+        assert!(load(&dir, "function other(topic, message) return message end").is_err());
+        assert!(load(&dir, "function transform(topic, message) return").is_err());
+        std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+    }
+
+    #[test]
+    fn test_transform() {
+        let dir = temp_dir("transform");
+        let script = load(
+            &dir,
+            r#"
+            function transform(topic, message)
+              if message.id == 0 then return nil end
+              message.x = message.x * 2
+              message.topic = topic
+              return message
+            end
+            "#,
+        )
+        .expect("Failed to load script");
+        let transformed = script
+            .transform("/foo", json!({"id": 1, "x": 1.5}))
+            .expect("Failed to transform");
+        assert_eq!(
+            transformed,
+            Some(json!({"id": 1, "x": 3.0, "topic": "/foo"}))
+        );
+        let dropped = script
+            .transform("/foo", json!({"id": 0, "x": 1.5}))
+            .expect("Failed to transform");
+        assert_eq!(dropped, None);
+
+        let records = vec![
+            (1, json!({"id": 0, "x": 1.0})),
+            (2, json!({"id": 2, "x": 1.0})),
+        ];
+        let records = script
+            .transform_all("/foo", records)
+            .expect("Failed to transform");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].0, 2);
+        std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+    }
+
+    #[test]
+    fn test_stage_encodes_modified_messages() {
+        let dir = temp_dir("stage");
+        let msg_dir = dir.join("msgs");
+        std::fs::create_dir_all(msg_dir.join("foo_msgs/msg")).expect("Failed to create msg dir");
+        std::fs::write(msg_dir.join("foo_msgs/msg/Foo.idl"), IDL).expect("Failed to write IDL");
+        set_msg_paths(vec![msg_dir]);
+
+        let schema = parse_idl_str(IDL, "foo_msgs/msg/Foo");
+        let topic = Arc::new(Topic {
+            id: 1,
+            name: "/foo".to_string(),
+            type_name: "foo_msgs/msg/Foo".to_string(),
+            count: 1,
+            serialization_format: "cdr".to_string(),
+            offered_qos_profiles: None,
+        });
+        let record = |id: u8| {
+            let value = json!({"id": id, "x": 1.5});
+            let data = CdrEncoder::new(Endianness::Big)
+                .encode(&schema, &value)
+                .expect("Failed to encode");
+            Record {
+                topic: topic.clone(),
+                message: RawMessage {
+                    timestamp: 1,
+                    topic_id: 1,
+                    data,
+                },
+                value: Some(value),
+            }
+        };
+
+        let mut script = load(
+            &dir,
+            r#"
+            function transform(topic, message)
+              if message.id == 1 then message.x = message.x * 2 end
+              return message
+            end
+            "#,
+        )
+        .expect("Failed to load script");
+        let modified = script
+            .process(record(1))
+            .expect("Failed to process")
+            .expect("Record dropped");
+        assert_eq!(
+            Endianness::from(modified.message.data.as_slice()),
+            Endianness::Big
+        );
+        let decoded = CdrDecoder::new(&modified.message.data)
+            .decode(&schema)
+            .expect("Failed to decode");
+        assert_eq!(decoded, json!({"id": 1, "x": 3.0}));
+
+        let unmodified = record(2);
+        let data = unmodified.message.data.clone();
+        let kept = script
+            .process(unmodified)
+            .expect("Failed to process")
+            .expect("Record dropped");
+        assert_eq!(kept.message.data, data);
+
+        // a message missing a field of its type cannot be encoded back
+        let mut script = load(
+            &dir,
+            "function transform(topic, message) message.x = nil return message end",
+        )
+        .expect("Failed to load script");
+        assert!(script.process(record(1)).is_err());
+        std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+    }
+}
@@ -0,0 +1,322 @@
+use std::{io::Write, path::Path};
+
+use rospeek_core::RosPeekResult;
+use rospeek_gui::{OutputFile, encryption::ENCRYPTED_EXTENSION};
+use serde_json::{Value, json};
+
+use crate::{
+    command::{DumpCompression, DumpFormat},
+    compress::CompressedFile,
+};
+
+/// Limits on each file of a dump, past which the next record starts a new file.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct SplitLimits {
+    /// Maximum size of a file in bytes, before compression.
+    pub(crate) max_bytes: Option<u64>,
+    /// Maximum time in nanoseconds between the first and the last message of a file.
+    pub(crate) max_duration_ns: Option<u64>,
+}
+
+impl SplitLimits {
+    fn is_unlimited(&self) -> bool {
+        self.max_bytes.is_none() && self.max_duration_ns.is_none()
+    }
+}
+
+/// File of a split dump, as listed in its manifest.
+#[derive(Debug)]
+struct PartEntry {
+    file: String,
+    messages: usize,
+    /// Size in bytes before compression.
+    bytes: u64,
+    first_timestamp: Option<u64>,
+    last_timestamp: Option<u64>,
+}
+
+impl PartEntry {
+    fn to_json(&self) -> Value {
+        json!({
+            "file": self.file,
+            "messages": self.messages,
+            "bytes": self.bytes,
+            "first_timestamp": self.first_timestamp,
+            "last_timestamp": self.last_timestamp,
+        })
+    }
+}
+
+/// Output file counting the bytes written into it before compression.
+struct CountedFile {
+    file: CompressedFile,
+    bytes: u64,
+}
+
+impl Write for CountedFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    /// Flushes nothing, so that each CSV row can be flushed into the count without flushing the
+    /// compression, the file being flushed once finished.
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// File being written, with the entry describing it.
+struct Part {
+    writer: PartWriter,
+    entry: PartEntry,
+}
+
+enum PartWriter {
+    Json(CountedFile),
+    Csv(Box<csv::Writer<CountedFile>>),
+}
+
+impl Part {
+    fn bytes(&self) -> u64 {
+        match &self.writer {
+            PartWriter::Json(file) => file.bytes,
+            PartWriter::Csv(writer) => writer.get_ref().bytes,
+        }
+    }
+
+    fn write_json(&mut self, bytes: &[u8]) -> RosPeekResult<()> {
+        match &mut self.writer {
+            PartWriter::Json(file) => file.write_all(bytes)?,
+            PartWriter::Csv(_) => anyhow::bail!("Cannot write JSON into a CSV file"),
+        }
+        Ok(())
+    }
+
+    fn write_csv<I, S>(&mut self, row: I) -> RosPeekResult<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<[u8]>,
+    {
+        match &mut self.writer {
+            PartWriter::Csv(writer) => {
+                writer.write_record(row)?;
+                writer.flush()?;
+            }
+            PartWriter::Json(_) => anyhow::bail!("Cannot write CSV into a JSON file"),
+        }
+        Ok(())
+    }
+
+    /// Writes the end of the file and returns its entry.
+    fn finish(mut self) -> RosPeekResult<PartEntry> {
+        let file = match self.writer {
+            PartWriter::Json(mut file) => {
+                match self.entry.messages {
+                    0 => file.write_all(b"]")?,
+                    _ => file.write_all(b"\n]")?,
+                }
+                file
+            }
+            PartWriter::Csv(writer) => (*writer).into_inner().map_err(|e| e.into_error())?,
+        };
+        self.entry.bytes = file.bytes;
+        file.file.finish()?;
+        Ok(self.entry)
+    }
+}
+
+/// Writes the records of a dump into a single file named by the topic, such as `foo.bar.csv`, or
+/// with limits into numbered files such as `foo.bar.000.csv` listed by `foo.bar.manifest.json`.
+///
+/// Each file is complete by itself: JSON files hold an array of their records and CSV files
+/// start with the header.
+pub(crate) struct DumpWriter {
+    /// Name of the files without their extension, such as `foo.bar`.
+    stem: String,
+    format: DumpFormat,
+    compression: Option<DumpCompression>,
+    limits: SplitLimits,
+    /// age public keys the files are encrypted for, if any.
+    recipients: Option<Vec<String>>,
+    /// Columns of the CSV files.
+    header: Vec<String>,
+    current: Option<Part>,
+    parts: Vec<PartEntry>,
+}
+
+impl DumpWriter {
+    /// Creates a writer, which creates no file until the first record or [`DumpWriter::finish`].
+    ///
+    /// # Arguments
+    /// * `stem` - Name of the files without their extension, such as `foo.bar`.
+    /// * `format` - Format of the records.
+    /// * `compression` - Compression of the files, if any.
+    /// * `limits` - Limits on each file, unlimited to write a single file.
+    pub(crate) fn new(
+        stem: String,
+        format: DumpFormat,
+        compression: Option<DumpCompression>,
+        limits: SplitLimits,
+    ) -> Self {
+        Self {
+            stem,
+            format,
+            compression,
+            limits,
+            recipients: None,
+            header: Vec::new(),
+            current: None,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Encrypts the files and the manifest with age as they are written, naming them with
+    /// [`ENCRYPTED_EXTENSION`] appended, such as `foo.bar.csv.age`.
+    pub(crate) fn encrypt(&mut self, recipients: Vec<String>) {
+        self.recipients = Some(recipients);
+    }
+
+    /// Sets the columns written at the start of each CSV file.
+    pub(crate) fn set_header(&mut self, header: Vec<String>) {
+        self.header = header;
+    }
+
+    /// Writes a record into the current file of a JSON dump, starting a new file first if the
+    /// current one reached a limit.
+    pub(crate) fn write_json(&mut self, timestamp: u64, value: &Value) -> RosPeekResult<()> {
+        // items of an array pretty-printed by serde_json are indented by two spaces
+        let item = serde_json::to_string_pretty(value)?.replace('\n', "\n  ");
+        let part = self.part_for(timestamp)?;
+        let separator = match part.entry.messages {
+            0 => "\n  ",
+            _ => ",\n  ",
+        };
+        part.write_json(separator.as_bytes())?;
+        part.write_json(item.as_bytes())?;
+        part.entry.messages += 1;
+        Ok(())
+    }
+
+    /// Writes a row into the current file of a CSV dump, starting a new file first if the
+    /// current one reached a limit.
+    pub(crate) fn write_csv<I, S>(&mut self, timestamp: u64, row: I) -> RosPeekResult<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<[u8]>,
+    {
+        let part = self.part_for(timestamp)?;
+        part.write_csv(row)?;
+        part.entry.messages += 1;
+        Ok(())
+    }
+
+    /// Closes the last file and writes the manifest of a split dump.
+    ///
+    /// # Returns
+    /// The names of the written files, the manifest being the last one of a split dump.
+    pub(crate) fn finish(mut self) -> RosPeekResult<Vec<String>> {
+        if self.current.is_none() && self.parts.is_empty() {
+            // an empty dump still gets its file
+            self.open_part()?;
+        }
+        self.close_part()?;
+
+        let mut files: Vec<String> = self.parts.iter().map(|p| p.file.clone()).collect();
+        if !self.limits.is_unlimited() {
+            let manifest = format!("{}.manifest.json{}", self.stem, self.encrypted_suffix());
+            let mut writer = OutputFile::create(Path::new(&manifest), self.recipients.as_deref())?;
+            let parts: Vec<Value> = self.parts.iter().map(PartEntry::to_json).collect();
+            serde_json::to_writer_pretty(&mut writer, &json!({ "files": parts }))?;
+            writer.finish()?;
+            files.push(manifest);
+        }
+        Ok(files)
+    }
+
+    /// Returns the file to write a record of a timestamp into, rolling over to a new file if the
+    /// current one reached a limit.
+    fn part_for(&mut self, timestamp: u64) -> RosPeekResult<&mut Part> {
+        let full = self.current.as_ref().is_some_and(|part| {
+            let entry = &part.entry;
+            let too_large = self.limits.max_bytes.is_some_and(|max| part.bytes() >= max);
+            let too_long = self.limits.max_duration_ns.is_some_and(|max| {
+                entry
+                    .first_timestamp
+                    .is_some_and(|first| timestamp.saturating_sub(first) >= max)
+            });
+            entry.messages > 0 && (too_large || too_long)
+        });
+        if full {
+            self.close_part()?;
+        }
+        if self.current.is_none() {
+            self.open_part()?;
+        }
+        let part = self.current.as_mut().expect("a part is open");
+        part.entry.first_timestamp.get_or_insert(timestamp);
+        part.entry.last_timestamp = Some(timestamp);
+        Ok(part)
+    }
+
+    fn open_part(&mut self) -> RosPeekResult<()> {
+        let extension = self.format.extension();
+        let compression = self.compression.map_or("", |c| c.extension());
+        let encrypted = self.encrypted_suffix();
+        let name = match self.limits.is_unlimited() {
+            true => format!("{}.{extension}{compression}{encrypted}", self.stem),
+            false => format!(
+                "{}.{:03}.{extension}{compression}{encrypted}",
+                self.stem,
+                self.parts.len()
+            ),
+        };
+        let file = CompressedFile::create(
+            Path::new(&name),
+            self.compression,
+            self.recipients.as_deref(),
+        )?;
+        let mut file = CountedFile { file, bytes: 0 };
+        let writer = match self.format {
+            DumpFormat::Json => {
+                file.write_all(b"[")?;
+                PartWriter::Json(file)
+            }
+            DumpFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new().from_writer(file);
+                writer.write_record(&self.header)?;
+                PartWriter::Csv(Box::new(writer))
+            }
+            DumpFormat::Parquet | DumpFormat::Arrow => {
+                anyhow::bail!("Cannot write columnar files record by record")
+            }
+        };
+        self.current = Some(Part {
+            writer,
+            entry: PartEntry {
+                file: name,
+                messages: 0,
+                bytes: 0,
+                first_timestamp: None,
+                last_timestamp: None,
+            },
+        });
+        Ok(())
+    }
+
+    /// Returns the suffix of the names of encrypted files, empty when not encrypting.
+    fn encrypted_suffix(&self) -> String {
+        match self.recipients {
+            Some(_) => format!(".{ENCRYPTED_EXTENSION}"),
+            None => String::new(),
+        }
+    }
+
+    fn close_part(&mut self) -> RosPeekResult<()> {
+        if let Some(part) = self.current.take() {
+            self.parts.push(part.finish()?);
+        }
+        Ok(())
+    }
+}
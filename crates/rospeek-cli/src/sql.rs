@@ -0,0 +1,116 @@
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use datafusion::{
+    catalog::{SchemaProvider, TableProvider},
+    datasource::MemTable,
+    error::{DataFusionError, Result as DataFusionResult},
+    prelude::SessionContext,
+};
+use rospeek_core::{
+    MessageSchema, OpenOptions, RosPeekResult, decode_to_record_batch, record_batch_schema,
+};
+use rospeek_gui::create_reader_with_options;
+
+/// Name of the catalog and schema whose tables are looked up for unqualified names.
+const CATALOG: &str = "datafusion";
+const SCHEMA: &str = "public";
+
+/// Exposes the topics of a bag as tables, named by topic.
+///
+/// Topics are decoded only once a query refers to them, so that querying one topic of a large
+/// bag does not decode the others.
+#[derive(Debug)]
+struct TopicSchemaProvider {
+    bag: PathBuf,
+    options: OpenOptions,
+    /// Topic names paired with their message types
+    topics: Vec<(String, String)>,
+}
+
+impl TopicSchemaProvider {
+    fn new(bag: &Path, options: &OpenOptions) -> RosPeekResult<Self> {
+        let reader = create_reader_with_options(bag, options)?;
+        let topics = reader
+            .topics()?
+            .into_iter()
+            .map(|t| (t.name, t.type_name))
+            .collect();
+        Ok(Self {
+            bag: bag.to_path_buf(),
+            options: *options,
+            topics,
+        })
+    }
+
+    /// Decodes a topic into an in-memory table.
+    fn decode(&self, topic: &str, type_name: &str) -> RosPeekResult<MemTable> {
+        let reader = create_reader_with_options(&self.bag, &self.options)?;
+        let batches = decode_to_record_batch(reader, topic, None, None, None, None)?;
+        let schema = match batches.first() {
+            Some(batch) => batch.schema(),
+            None => record_batch_schema(&MessageSchema::try_from(type_name)?)?,
+        };
+        if batches.iter().any(|batch| batch.schema() != schema) {
+            anyhow::bail!(
+                "The message type of {topic} changes within the bag, so it cannot be queried as one table"
+            );
+        }
+        Ok(MemTable::try_new(schema, vec![batches])?)
+    }
+}
+
+#[async_trait]
+impl SchemaProvider for TopicSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        self.topics.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    async fn table(&self, name: &str) -> DataFusionResult<Option<Arc<dyn TableProvider>>> {
+        let Some((topic, type_name)) = self.topics.iter().find(|(topic, _)| topic == name) else {
+            return Ok(None);
+        };
+        let table = self
+            .decode(topic, type_name)
+            .map_err(|e| DataFusionError::External(e.into()))?;
+        Ok(Some(Arc::new(table)))
+    }
+
+    fn table_exist(&self, name: &str) -> bool {
+        self.topics.iter().any(|(topic, _)| topic == name)
+    }
+}
+
+/// Runs an SQL query over the topics of a bag and prints the result as a table.
+///
+/// Each topic is a table named by the topic, such as `'/odom'`, with a `timestamp` column of
+/// UNIX epoch nanoseconds and a column per field, named by its dot-separated path.
+///
+/// # Arguments
+/// * `bag` - Path to the bag.
+/// * `options` - Options to open the bag with.
+/// * `query` - SQL query, such as `SELECT avg("twist.twist.linear.x") FROM '/odom'`.
+pub(crate) fn run_sql(bag: &Path, options: &OpenOptions, query: &str) -> RosPeekResult<()> {
+    let provider = TopicSchemaProvider::new(bag, options)?;
+
+    let ctx = SessionContext::new();
+    ctx.catalog(CATALOG)
+        .ok_or_else(|| anyhow::anyhow!("Catalog not found: {CATALOG}"))?
+        .register_schema(SCHEMA, Arc::new(provider))?;
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        ctx.sql(query).await?.show().await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,161 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::Context as _;
+use rospeek_core::{
+    BagReader, MessageOrder, Pipeline, Record, RosPeekResult, Sink, Topic, TopicStats,
+    count_in_bins, find_gaps, ns_to_iso,
+};
+use serde_json::{Value, json};
+use tera::{Context, Tera};
+
+/// Markdown template used without `--template`.
+const DEFAULT_TEMPLATE: &str = include_str!("../templates/report.md.tera");
+
+/// Number of bars of the rate plots.
+const PLOT_BINS: usize = 60;
+
+/// Size of the rate plots in pixels.
+const PLOT_WIDTH: usize = 480;
+const PLOT_HEIGHT: usize = 80;
+
+/// Pipeline sink keeping the timestamps and statistics of each topic.
+#[derive(Default)]
+struct TimelineSink {
+    topics: BTreeMap<String, (Topic, TopicStats, Vec<u64>)>,
+}
+
+impl Sink for TimelineSink {
+    fn add_topic(&mut self, topic: &Topic) -> RosPeekResult<()> {
+        self.topics.insert(
+            topic.name.clone(),
+            (topic.clone(), TopicStats::default(), Vec::new()),
+        );
+        Ok(())
+    }
+
+    fn write(&mut self, record: Record) -> RosPeekResult<()> {
+        if let Some((_, stats, timestamps)) = self.topics.get_mut(&record.topic.name) {
+            stats.accumulate(&record.message);
+            timestamps.push(record.message.timestamp);
+        }
+        Ok(())
+    }
+}
+
+/// Renders a QA report of a bag from a Tera template.
+///
+/// Templates see `bag` with the statistics of the bag, `topics` with a row per topic including
+/// its `gaps` and a `rate_svg` plot, and `gaps` with the gaps of every topic. HTML templates,
+/// such as `report.html.tera`, are escaped, so plots are embedded with
+/// `{{ topic.rate_svg | safe }}`.
+///
+/// # Arguments
+/// * `reader` - The reader of the bag.
+/// * `template` - Path to the template, or `None` for the built-in Markdown report.
+/// * `gap_factor` - How many times the median interval of a topic an interval must exceed to
+///   be reported as a gap.
+pub(crate) fn render_report(
+    reader: &dyn BagReader,
+    template: Option<&Path>,
+    gap_factor: f64,
+) -> RosPeekResult<String> {
+    let (source, autoescape) = match template {
+        Some(path) => (
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read template {}", path.display()))?,
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().contains(".html")),
+        ),
+        None => (DEFAULT_TEMPLATE.to_string(), false),
+    };
+
+    let context = Context::from_value(report_context(reader, gap_factor)?)?;
+    Tera::one_off(&source, &context, autoescape).map_err(|e| {
+        // the cause tells which variable or filter is wrong
+        let cause = std::error::Error::source(&e).map_or(String::new(), |s| format!(": {s}"));
+        anyhow::anyhow!("Failed to render template: {e}{cause}")
+    })
+}
+
+/// Collects the values a report template is rendered with.
+fn report_context(reader: &dyn BagReader, gap_factor: f64) -> RosPeekResult<Value> {
+    let mut sink = TimelineSink::default();
+    Pipeline::new(reader)
+        .order(MessageOrder::Topic)
+        .sink(&mut sink)?;
+
+    let stats = reader.stats()?;
+    let mut all_gaps = Vec::new();
+    let topics: Vec<Value> = sink
+        .topics
+        .into_values()
+        .map(|(topic, topic_stats, mut timestamps)| {
+            timestamps.sort_unstable();
+            let gaps: Vec<Value> = find_gaps(&timestamps, gap_factor)
+                .into_iter()
+                .map(|gap| {
+                    json!({
+                        "topic": topic.name,
+                        "start_time": ns_to_iso(gap.start_ns),
+                        "end_time": ns_to_iso(gap.end_ns),
+                        "duration_sec": gap.duration_sec(),
+                    })
+                })
+                .collect();
+            all_gaps.extend(gaps.iter().cloned());
+            let counts = count_in_bins(&timestamps, stats.start_ns, stats.end_ns, PLOT_BINS);
+            json!({
+                "name": topic.name,
+                "type": topic.type_name,
+                "count": topic_stats.count,
+                "frequency_hz": topic_stats.frequency_hz(),
+                "mean_size": topic_stats.mean_size(),
+                "bandwidth_bps": topic_stats.bandwidth_bps(),
+                "gaps": gaps,
+                "rate_svg": rate_svg(&counts),
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "tool": {
+            "name": "rospeek",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "bag": {
+            "path": stats.path,
+            "storage_type": stats.storage_type.to_string(),
+            "size_gib": stats.size_bytes,
+            "message_count": stats.message_count,
+            "duration_sec": stats.duration_sec,
+            "start_time": stats.start_time,
+            "end_time": stats.end_time,
+        },
+        "gap_factor": gap_factor,
+        "topics": topics,
+        "gaps": all_gaps,
+    }))
+}
+
+/// Draws message counts over time as an SVG bar chart.
+fn rate_svg(counts: &[u64]) -> String {
+    let max = counts.iter().copied().max().unwrap_or_default().max(1) as f64;
+    let width = PLOT_WIDTH as f64 / counts.len().max(1) as f64;
+    let bars: String = counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(i, count)| {
+            let height = *count as f64 / max * PLOT_HEIGHT as f64;
+            format!(
+                r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{height:.1}" fill="#4c78a8"/>"##,
+                i as f64 * width,
+                PLOT_HEIGHT as f64 - height,
+                width.max(1.0) - 0.5,
+            )
+        })
+        .collect();
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{PLOT_WIDTH}" height="{PLOT_HEIGHT}" viewBox="0 0 {PLOT_WIDTH} {PLOT_HEIGHT}"><rect width="100%" height="100%" fill="#f5f5f5"/>{bars}</svg>"##
+    )
+}
@@ -0,0 +1,55 @@
+use std::{
+    io::{IsTerminal, Write},
+    thread,
+    time::Duration,
+};
+
+use rospeek_core::{DecodeMonitor, DecodeSnapshot, decode_workers};
+
+use crate::format_bytes;
+
+/// Shows the decode throughput of each topic on stderr while decoding runs.
+///
+/// The view is redrawn in place on terminals, and appended otherwise so that it can be logged.
+///
+/// # Arguments
+/// * `interval` - Time between refreshes.
+pub(crate) fn spawn_top(interval: Duration) {
+    let monitor = DecodeMonitor::global();
+    monitor.enable();
+    thread::spawn(move || {
+        let terminal = std::io::stderr().is_terminal();
+        loop {
+            thread::sleep(interval);
+            let view = render(&monitor.snapshots(), decode_workers());
+            let mut stderr = std::io::stderr().lock();
+            if terminal {
+                // move to the top-left corner and clear the screen
+                let _ = write!(stderr, "\x1b[H\x1b[2J");
+            }
+            let _ = writeln!(stderr, "{view}");
+        }
+    });
+}
+
+fn render(snapshots: &[DecodeSnapshot], workers: usize) -> String {
+    let mut lines = vec![format!(
+        "{:<40} {:>10} {:>10} {:>10} {:>12} {:>12}",
+        "TOPIC", "DECODED", "PENDING", "MSG/S", "BYTES/S", "WORKERS"
+    )];
+    for snapshot in snapshots {
+        lines.push(format!(
+            "{:<40} {:>10} {:>10} {:>10.0} {:>12} {:>12}",
+            snapshot.topic,
+            snapshot.decoded,
+            snapshot.pending(),
+            snapshot.messages_per_sec(),
+            format_bytes(snapshot.bytes_per_sec()),
+            format!("{:.1}/{workers}", snapshot.busy_workers()),
+        ));
+    }
+    if snapshots.is_empty() {
+        lines.push("(waiting for decoding to start)".to_string());
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,257 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use anyhow::bail;
+use rospeek_core::{
+    CdrDecoder, DecodePlan, DecoderRegistry, MessageSchema, OpenOptions, RawMessage, RosPeekResult,
+    StorageType, decode_with_rule, ns_to_iso, stringify_large_integers,
+};
+use rospeek_db3::Db3Reader;
+use rospeek_gui::backend::UnsupportedFormat;
+use rospeek_mcap::McapFollower;
+
+/// Follows a bag which is still being recorded, printing new messages as they are written.
+///
+/// Every file of the bag is kept open across polls and read on from where the previous poll
+/// stopped, the last message ID of a `.db3` file or the byte offset of an `.mcap` file, and the
+/// files a recorder splits the bag into are picked up as they appear.
+///
+/// # Arguments
+/// * `bag` - Path to the bag file or directory.
+/// * `topics` - Topic names to follow, or all topics if empty.
+/// * `interval` - Time to wait between polls.
+/// * `from_start` - Whether to print the messages recorded before watching started.
+/// * `decode` - Whether to print decoded messages as JSON lines.
+/// * `large_ints_as_strings` - Whether to print integers beyond `2^53` as strings.
+/// * `options` - How to open the bag, of which only the storage is used.
+pub(crate) fn watch_bag(
+    bag: &Path,
+    topics: &[String],
+    interval: Duration,
+    from_start: bool,
+    decode: bool,
+    large_ints_as_strings: bool,
+    options: &OpenOptions,
+) -> RosPeekResult<()> {
+    let mut follower = BagFollower {
+        bag: bag.to_path_buf(),
+        storage: options.storage,
+        files: Vec::new(),
+    };
+    let mut plans: HashMap<String, Option<DecodePlan>> = HashMap::new();
+    let mut first = true;
+    let mut failing = false;
+
+    loop {
+        // the recorder may hold a lock or be in the middle of a write, so retry on the next poll
+        let mut messages = Vec::new();
+        let polled = follower.poll(topics, first && !from_start, &mut messages);
+        messages.sort_by_key(|(_, _, m)| m.timestamp);
+        for (topic, type_name, message) in messages {
+            if decode {
+                let plan = plans.entry(type_name.clone()).or_insert_with(|| {
+                    MessageSchema::try_from(type_name.as_str())
+                        .and_then(|schema| DecodePlan::compile(&schema))
+                        .ok()
+                });
+                print_decoded(
+                    &topic,
+                    &type_name,
+                    &message,
+                    plan.as_ref(),
+                    large_ints_as_strings,
+                );
+            } else {
+                println!(
+                    "[{topic}] t = {} ns ({}), {} bytes",
+                    message.timestamp,
+                    ns_to_iso(message.timestamp),
+                    message.data.len()
+                );
+            }
+        }
+        match polled {
+            Ok(()) => failing = false,
+            Err(e) if first => return Err(e),
+            Err(e) => {
+                if !failing {
+                    eprintln!("Failed to read {}, retrying: {e}", bag.display());
+                }
+                failing = true;
+            }
+        }
+        first = false;
+        thread::sleep(interval);
+    }
+}
+
+/// Followers of the files of a bag, in the order the recorder split the bag into them.
+struct BagFollower {
+    bag: PathBuf,
+    storage: Option<StorageType>,
+    files: Vec<(PathBuf, FileFollower)>,
+}
+
+impl BagFollower {
+    /// Collects the messages written since the previous poll as topic name, type name and
+    /// message, opening the files which appeared since then.
+    ///
+    /// With `skip_existing`, the messages already in the files opened by this poll are skipped.
+    /// The messages read before an error are collected all the same.
+    fn poll(
+        &mut self,
+        topics: &[String],
+        skip_existing: bool,
+        messages: &mut Vec<(String, String, RawMessage)>,
+    ) -> RosPeekResult<()> {
+        for path in self.paths()? {
+            if self.files.iter().all(|(opened, _)| *opened != path) {
+                let file = FileFollower::open(&path, self.storage, skip_existing)?;
+                self.files.push((path, file));
+            }
+        }
+        for (_, file) in &mut self.files {
+            file.poll(topics, messages)?;
+        }
+        Ok(())
+    }
+
+    /// Lists the files of the bag, sorted by path.
+    fn paths(&self) -> RosPeekResult<Vec<PathBuf>> {
+        if !self.bag.is_dir() {
+            return Ok(vec![self.bag.clone()]);
+        }
+        let mut paths = std::fs::read_dir(&self.bag)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| {
+            StorageType::detect(path)
+                .is_some_and(|storage| self.storage.is_none_or(|forced| forced == storage))
+        });
+        paths.sort();
+        if paths.is_empty() && self.files.is_empty() {
+            bail!("No bag files found in {}", self.bag.display());
+        }
+        Ok(paths)
+    }
+}
+
+/// Follower of a single file of a bag.
+enum FileFollower {
+    Db3 {
+        reader: Db3Reader,
+        /// ID of the last message read
+        last_id: i64,
+        /// Topic and type names by topic ID
+        topics: HashMap<u16, (String, String)>,
+    },
+    Mcap(McapFollower),
+}
+
+impl FileFollower {
+    /// Opens a file to follow, from its end with `skip_existing`.
+    fn open(path: &Path, storage: Option<StorageType>, skip_existing: bool) -> RosPeekResult<Self> {
+        let storage = storage
+            .or_else(|| StorageType::detect(path))
+            .ok_or_else(|| UnsupportedFormat::new(path))?;
+        match storage {
+            StorageType::Sqlite3 => {
+                let reader = Db3Reader::open(path)?;
+                let last_id = match skip_existing {
+                    true => reader.last_message_id()?,
+                    false => 0,
+                };
+                Ok(FileFollower::Db3 {
+                    reader,
+                    last_id,
+                    topics: HashMap::new(),
+                })
+            }
+            StorageType::Mcap => {
+                let mut follower = McapFollower::open(path)?;
+                if skip_existing {
+                    follower.poll()?;
+                }
+                Ok(FileFollower::Mcap(follower))
+            }
+        }
+    }
+
+    /// Collects the messages of `topics`, or all topics if empty, written since the previous
+    /// poll.
+    fn poll(
+        &mut self,
+        topics: &[String],
+        messages: &mut Vec<(String, String, RawMessage)>,
+    ) -> RosPeekResult<()> {
+        let wanted = |topic: &str| topics.is_empty() || topics.iter().any(|t| t == topic);
+        match self {
+            FileFollower::Db3 {
+                reader,
+                last_id,
+                topics,
+            } => {
+                let new = reader.read_messages_after_id(*last_id)?;
+                // topics are registered before their first message is written
+                if new.iter().any(|(_, m)| !topics.contains_key(&m.topic_id)) {
+                    *topics = reader
+                        .topics()?
+                        .into_iter()
+                        .map(|topic| (topic.id, (topic.name, topic.type_name)))
+                        .collect();
+                }
+                for (id, message) in new {
+                    *last_id = id;
+                    if let Some((name, type_name)) = topics.get(&message.topic_id)
+                        && wanted(name)
+                    {
+                        messages.push((name.clone(), type_name.clone(), message));
+                    }
+                }
+            }
+            FileFollower::Mcap(follower) => {
+                for message in follower.poll()? {
+                    if let Some((name, type_name)) = follower.channel(message.topic_id)
+                        && wanted(name)
+                    {
+                        messages.push((name.to_string(), type_name.to_string(), message));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints a message as a JSON line, falling back to its size if it cannot be decoded.
+///
+/// The rule of the topic in the [`DecoderRegistry`], if any, is honored.
+pub(crate) fn print_decoded(
+    topic: &str,
+    type_name: &str,
+    message: &RawMessage,
+    plan: Option<&DecodePlan>,
+    large_ints_as_strings: bool,
+) {
+    let rule = DecoderRegistry::global().rule_for(topic, type_name);
+    let decoded = decode_with_rule(
+        rule.as_ref(),
+        &mut CdrDecoder::new(&[]),
+        plan,
+        &message.data,
+    )
+    .ok();
+    let mut line = serde_json::json!({
+        "topic": topic,
+        "timestamp": message.timestamp,
+        "message": decoded.unwrap_or_else(|| serde_json::json!({ "bytes": message.data.len() })),
+    });
+    if large_ints_as_strings {
+        stringify_large_integers(&mut line);
+    }
+    println!("{line}");
+}
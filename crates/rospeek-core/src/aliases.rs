@@ -0,0 +1,134 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{OnceLock, RwLock},
+};
+
+use serde::Deserialize;
+
+use crate::RosPeekResult;
+
+/// Name of the sidecar file of a bag directory listing aliases of its topics.
+pub const ALIASES_FILE: &str = "aliases.yaml";
+
+/// Friendly names of topics, shown next to cryptic topic names and accepted in their place.
+///
+/// Aliases come from the `aliases` section of the config file and from the sidecar file of a bag,
+/// `aliases.yaml` in a bag directory or `<name>.aliases.yaml` next to a bag file, as a map from
+/// topic name to alias.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::TopicAliases;
+///
+/// let aliases = TopicAliases::from_yaml(
+///     "/sensing/radar/front_center/objects_raw: front_radar",
+/// )
+/// .unwrap();
+/// assert_eq!(aliases.resolve("front_radar"), "/sensing/radar/front_center/objects_raw");
+/// assert_eq!(aliases.resolve("/tf"), "/tf");
+/// assert_eq!(
+///     aliases.label("/sensing/radar/front_center/objects_raw"),
+///     "front_radar (/sensing/radar/front_center/objects_raw)"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct TopicAliases {
+    /// Alias of each topic name.
+    aliases: BTreeMap<String, String>,
+}
+
+impl TopicAliases {
+    /// Parses aliases from a YAML map of topic name to alias.
+    pub fn from_yaml(yaml: &str) -> RosPeekResult<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Returns the path of the sidecar file of a bag.
+    ///
+    /// # Arguments
+    /// * `bag` - The path to the bag file or directory.
+    pub fn sidecar_path<P: AsRef<Path>>(bag: P) -> PathBuf {
+        let bag = bag.as_ref();
+        if bag.is_dir() {
+            bag.join(ALIASES_FILE)
+        } else {
+            bag.with_extension(ALIASES_FILE)
+        }
+    }
+
+    /// Reads the sidecar file of a bag.
+    ///
+    /// # Arguments
+    /// * `bag` - The path to the bag file or directory.
+    ///
+    /// # Returns
+    /// The aliases of the sidecar file, or `None` if the bag has none.
+    pub fn load_sidecar<P: AsRef<Path>>(bag: P) -> RosPeekResult<Option<Self>> {
+        let path = Self::sidecar_path(bag);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let yaml = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read aliases {}: {e}", path.display()))?;
+        Self::from_yaml(&yaml)
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("Invalid aliases {}: {e}", path.display()))
+    }
+
+    /// Returns these aliases along with those of the sidecar file of a bag, which take
+    /// precedence.
+    ///
+    /// # Arguments
+    /// * `bag` - The path to the bag file or directory.
+    pub fn with_sidecar<P: AsRef<Path>>(&self, bag: P) -> RosPeekResult<Self> {
+        let mut aliases = self.clone();
+        if let Some(sidecar) = Self::load_sidecar(bag)? {
+            aliases.aliases.extend(sidecar.aliases);
+        }
+        Ok(aliases)
+    }
+
+    /// Returns whether there are no aliases.
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// Returns the alias of a topic, if any.
+    pub fn alias(&self, topic: &str) -> Option<&str> {
+        self.aliases.get(topic).map(String::as_str)
+    }
+
+    /// Returns the topic name an alias stands for, or the name itself if it is not an alias.
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases
+            .iter()
+            .find(|(_, alias)| *alias == name)
+            .map_or(name, |(topic, _)| topic.as_str())
+    }
+
+    /// Returns the name a topic is displayed with, i.e. `alias (topic)`, or the topic name if it
+    /// has no alias.
+    pub fn label(&self, topic: &str) -> String {
+        match self.alias(topic) {
+            Some(alias) => format!("{alias} ({topic})"),
+            None => topic.to_string(),
+        }
+    }
+
+    /// Returns the aliases of the process, usually those of the config file.
+    pub fn global() -> TopicAliases {
+        global_aliases().read().unwrap().clone()
+    }
+
+    /// Replaces the aliases of the process.
+    pub fn set_global(aliases: TopicAliases) {
+        *global_aliases().write().unwrap() = aliases;
+    }
+}
+
+fn global_aliases() -> &'static RwLock<TopicAliases> {
+    static ALIASES: OnceLock<RwLock<TopicAliases>> = OnceLock::new();
+    ALIASES.get_or_init(RwLock::default)
+}
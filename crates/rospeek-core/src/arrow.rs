@@ -0,0 +1,430 @@
+use std::{io::Write, sync::Arc};
+
+use arrow_array::{
+    ArrayRef, ArrowPrimitiveType, BooleanArray, PrimitiveArray, RecordBatch, StringArray,
+    UInt64Array,
+    builder::{BooleanBuilder, ListBuilder, PrimitiveBuilder, StringBuilder},
+    types::{
+        Float32Type, Float64Type, Int8Type, Int16Type, Int32Type, Int64Type, UInt8Type, UInt16Type,
+        UInt32Type, UInt64Type,
+    },
+};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use serde_json::Value;
+
+use crate::{BagReader, MessageSchema, Primitive, RosPeekResult, decode_runs, lookup_field};
+
+/// Name of the column holding the UNIX epoch nanoseconds of each message.
+pub const TIMESTAMP_COLUMN: &str = "timestamp";
+
+/// Name of the column holding messages whose decode rule does not decode CDR, as JSON.
+pub const VALUE_COLUMN: &str = "value";
+
+/// Type of the values stored in a column.
+#[derive(Debug, Clone, Copy)]
+enum ColumnKind {
+    Primitive(Primitive),
+    String,
+    /// Array or sequence of primitives
+    List(Primitive),
+    /// Any other value, serialized as JSON
+    Json,
+}
+
+/// A column of a record batch, holding a field of the messages.
+#[derive(Debug, Clone)]
+struct Column {
+    /// Dot-separated path of the field, such as `header.stamp.sec`
+    path: String,
+    kind: ColumnKind,
+}
+
+impl Column {
+    fn field(&self) -> Field {
+        let data_type = match self.kind {
+            ColumnKind::Primitive(primitive) => data_type(primitive),
+            ColumnKind::String | ColumnKind::Json => DataType::Utf8,
+            ColumnKind::List(primitive) => {
+                DataType::List(Arc::new(Field::new_list_field(data_type(primitive), true)))
+            }
+        };
+        Field::new(&self.path, data_type, true)
+    }
+
+    fn array(&self, values: &[Value]) -> ArrayRef {
+        let cells = values.iter().map(|value| lookup_field(value, &self.path));
+        match self.kind {
+            ColumnKind::Primitive(primitive) => primitive_array(primitive, cells),
+            ColumnKind::String => Arc::new(
+                cells
+                    .map(|cell| cell.and_then(Value::as_str))
+                    .collect::<StringArray>(),
+            ),
+            ColumnKind::List(primitive) => list_array(primitive, cells),
+            ColumnKind::Json => Arc::new(
+                cells
+                    .map(|cell| cell.map(Value::to_string))
+                    .collect::<StringArray>(),
+            ),
+        }
+    }
+}
+
+/// Derives the Arrow schema of the record batches of a message type.
+///
+/// Nested structures are flattened into columns named by their dot-separated path, arrays and
+/// sequences of primitives become list columns, and other iterables are stored as JSON strings.
+/// The first column is [`TIMESTAMP_COLUMN`].
+///
+/// # Arguments
+/// * `schema` - ROS message schema
+pub fn record_batch_schema(schema: &MessageSchema) -> RosPeekResult<SchemaRef> {
+    Ok(to_arrow_schema(&columns(schema)?))
+}
+
+/// Builds a record batch from messages already decoded into JSON.
+///
+/// Values missing from a message or not matching the type of their column become nulls, which
+/// includes floating-point values that JSON cannot hold, such as NaN.
+///
+/// # Arguments
+/// * `schema` - ROS message schema the values were decoded with
+/// * `timestamps` - UNIX epoch nanoseconds of each message
+/// * `values` - Decoded value of each message
+pub fn record_batch_from_values(
+    schema: &MessageSchema,
+    timestamps: &[u64],
+    values: &[Value],
+) -> RosPeekResult<RecordBatch> {
+    build_record_batch(&columns(schema)?, timestamps, values)
+}
+
+/// Decodes messages for a given topic into typed Arrow record batches.
+///
+/// One record batch is returned for each run of messages recorded with the same type version,
+/// since their schemas may differ. Rules of the [`crate::DecoderRegistry`] matching the topic are
+/// honored, and messages whose rule does not decode CDR are stored as JSON in [`VALUE_COLUMN`].
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topic` - The topic to decode messages for.
+/// * `since` - The start time to decode messages from.
+/// * `until` - The end time to decode messages to.
+/// * `limit` - Optional maximum number of messages to decode.
+/// * `offset` - Optional number of messages to skip after filtering.
+///
+/// # Returns
+/// Record batches of the messages, ordered by time.
+pub fn decode_to_record_batch(
+    reader: Box<dyn BagReader>,
+    topic: &str,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> RosPeekResult<Vec<RecordBatch>> {
+    decode_runs(reader.as_ref(), topic, since, until, limit, offset)?
+        .into_iter()
+        .map(|run| match run.schema {
+            Some(ref schema) => build_record_batch(&columns(schema)?, &run.timestamps, &run.values),
+            None => {
+                let schema = Schema::new(vec![
+                    Field::new(TIMESTAMP_COLUMN, DataType::UInt64, false),
+                    Field::new(VALUE_COLUMN, DataType::Utf8, true),
+                ]);
+                let values = run.values.iter().map(|value| Some(value.to_string()));
+                let arrays: Vec<ArrayRef> = vec![
+                    Arc::new(UInt64Array::from(run.timestamps)),
+                    Arc::new(values.collect::<StringArray>()),
+                ];
+                Ok(RecordBatch::try_new(Arc::new(schema), arrays)?)
+            }
+        })
+        .collect()
+}
+
+/// Decodes messages for a given topic into record batches sharing one schema, such as to write
+/// them into one file or query them as one table.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topic` - The topic to decode messages for.
+/// * `since` - The start time to decode messages from.
+/// * `until` - The end time to decode messages to.
+/// * `limit` - Optional maximum number of messages to decode.
+/// * `offset` - Optional number of messages to skip after filtering.
+///
+/// # Returns
+/// The schema, derived from the message type if no message is decoded, and the record batches.
+/// It is an error for the schema to change within the bag, as when the message type is updated
+/// between recordings merged into one bag.
+pub fn decode_to_table(
+    reader: Box<dyn BagReader>,
+    topic: &str,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> RosPeekResult<(SchemaRef, Vec<RecordBatch>)> {
+    let type_name = reader
+        .topics()?
+        .into_iter()
+        .find(|t| t.name == topic)
+        .map(|t| t.type_name)
+        .ok_or_else(|| anyhow::anyhow!("Topic not found: {topic}"))?;
+    let batches = decode_to_record_batch(reader, topic, since, until, limit, offset)?;
+    let schema = match batches.first() {
+        Some(batch) => batch.schema(),
+        None => record_batch_schema(&MessageSchema::try_from(type_name.as_ref())?)?,
+    };
+    if batches.iter().any(|batch| batch.schema() != schema) {
+        anyhow::bail!("The message type of {topic} changes within the bag");
+    }
+    Ok((schema, batches))
+}
+
+/// Writes record batches into an Arrow IPC file, also known as Feather V2, which other tools
+/// can memory-map without parsing.
+///
+/// # Arguments
+/// * `writer` - The file to write into.
+/// * `schema` - The schema of the batches.
+/// * `batches` - The batches, written in order.
+///
+/// # Returns
+/// The writer, flushed.
+pub fn write_arrow_ipc<W: Write>(
+    writer: W,
+    schema: &SchemaRef,
+    batches: &[RecordBatch],
+) -> RosPeekResult<W> {
+    let mut writer = FileWriter::try_new(writer, schema)?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.finish()?;
+    Ok(writer.into_inner()?)
+}
+
+/// Decodes messages for a given topic into an Arrow IPC file, with a typed column for each
+/// field as [`decode_to_record_batch`] derives them.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topic` - The topic to decode messages for.
+/// * `since` - The start time to decode messages from.
+/// * `until` - The end time to decode messages to.
+/// * `limit` - Optional maximum number of messages to decode.
+/// * `offset` - Optional number of messages to skip after filtering.
+/// * `writer` - The file to write into.
+pub fn try_decode_arrow<W: Write>(
+    reader: Box<dyn BagReader>,
+    topic: &str,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    writer: W,
+) -> RosPeekResult<W> {
+    let (schema, batches) = decode_to_table(reader, topic, since, until, limit, offset)?;
+    write_arrow_ipc(writer, &schema, &batches)
+}
+
+fn build_record_batch(
+    columns: &[Column],
+    timestamps: &[u64],
+    values: &[Value],
+) -> RosPeekResult<RecordBatch> {
+    if timestamps.len() != values.len() {
+        anyhow::bail!(
+            "Expected a timestamp for each of {} messages, got {}",
+            values.len(),
+            timestamps.len()
+        );
+    }
+    let mut arrays: Vec<ArrayRef> = vec![Arc::new(UInt64Array::from(timestamps.to_vec()))];
+    arrays.extend(columns.iter().map(|column| column.array(values)));
+    Ok(RecordBatch::try_new(to_arrow_schema(columns), arrays)?)
+}
+
+fn to_arrow_schema(columns: &[Column]) -> SchemaRef {
+    let mut fields = vec![Field::new(TIMESTAMP_COLUMN, DataType::UInt64, false)];
+    fields.extend(columns.iter().map(Column::field));
+    Arc::new(Schema::new(fields))
+}
+
+/// Flattens the fields of a schema into columns, looking up the IDL files of nested types.
+fn columns(schema: &MessageSchema) -> RosPeekResult<Vec<Column>> {
+    let mut columns = Vec::new();
+    collect_columns(schema, "", &mut columns)?;
+    Ok(columns)
+}
+
+fn collect_columns(
+    schema: &MessageSchema,
+    prefix: &str,
+    columns: &mut Vec<Column>,
+) -> RosPeekResult<()> {
+    for field in schema.fields.iter() {
+        let path = format!("{prefix}{}", field.name);
+        let type_name = field.type_name();
+        let kind = match Primitive::from_type_name(type_name) {
+            Some(primitive) if field.is_iterable() => ColumnKind::List(primitive),
+            Some(primitive) => ColumnKind::Primitive(primitive),
+            None if field.is_iterable() => ColumnKind::Json,
+            None if type_name == "string" => ColumnKind::String,
+            None => match type_name {
+                "builtin_interfaces/msg/Time" | "builtin_interfaces/msg/Duration" => {
+                    columns.push(Column {
+                        path: format!("{path}.sec"),
+                        kind: ColumnKind::Primitive(Primitive::Int32),
+                    });
+                    columns.push(Column {
+                        path: format!("{path}.nanosec"),
+                        kind: ColumnKind::Primitive(Primitive::Uint32),
+                    });
+                    continue;
+                }
+                _ => {
+                    let nested = MessageSchema::try_from(type_name)?;
+                    collect_columns(&nested, &format!("{path}."), columns)?;
+                    continue;
+                }
+            },
+        };
+        columns.push(Column { path, kind });
+    }
+    Ok(())
+}
+
+fn data_type(primitive: Primitive) -> DataType {
+    match primitive {
+        Primitive::Bool => DataType::Boolean,
+        Primitive::Octet | Primitive::Uint8 => DataType::UInt8,
+        Primitive::Char => DataType::Utf8,
+        Primitive::Int8 => DataType::Int8,
+        Primitive::Int16 => DataType::Int16,
+        Primitive::Uint16 => DataType::UInt16,
+        Primitive::Int32 => DataType::Int32,
+        Primitive::Uint32 => DataType::UInt32,
+        Primitive::Int64 => DataType::Int64,
+        Primitive::Uint64 => DataType::UInt64,
+        Primitive::Float => DataType::Float32,
+        Primitive::Double => DataType::Float64,
+    }
+}
+
+fn primitive_array<'a>(
+    primitive: Primitive,
+    cells: impl Iterator<Item = Option<&'a Value>>,
+) -> ArrayRef {
+    match primitive {
+        Primitive::Bool => Arc::new(
+            cells
+                .map(|cell| cell.and_then(Value::as_bool))
+                .collect::<BooleanArray>(),
+        ),
+        Primitive::Octet | Primitive::Uint8 => typed_array::<UInt8Type>(cells, as_unsigned),
+        Primitive::Char => Arc::new(
+            cells
+                .map(|cell| cell.and_then(Value::as_str))
+                .collect::<StringArray>(),
+        ),
+        Primitive::Int8 => typed_array::<Int8Type>(cells, as_signed),
+        Primitive::Int16 => typed_array::<Int16Type>(cells, as_signed),
+        Primitive::Uint16 => typed_array::<UInt16Type>(cells, as_unsigned),
+        Primitive::Int32 => typed_array::<Int32Type>(cells, as_signed),
+        Primitive::Uint32 => typed_array::<UInt32Type>(cells, as_unsigned),
+        Primitive::Int64 => typed_array::<Int64Type>(cells, as_signed),
+        Primitive::Uint64 => typed_array::<UInt64Type>(cells, as_unsigned),
+        Primitive::Float => typed_array::<Float32Type>(cells, |v| v.as_f64().map(|f| f as f32)),
+        Primitive::Double => typed_array::<Float64Type>(cells, Value::as_f64),
+    }
+}
+
+fn list_array<'a>(
+    primitive: Primitive,
+    cells: impl Iterator<Item = Option<&'a Value>>,
+) -> ArrayRef {
+    match primitive {
+        Primitive::Bool => {
+            let mut builder = ListBuilder::new(BooleanBuilder::new());
+            for cell in cells {
+                match cell.and_then(Value::as_array) {
+                    Some(items) => {
+                        for item in items {
+                            builder.values().append_option(item.as_bool());
+                        }
+                        builder.append(true);
+                    }
+                    None => builder.append(false),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        Primitive::Octet | Primitive::Uint8 => typed_list::<UInt8Type>(cells, as_unsigned),
+        Primitive::Char => {
+            let mut builder = ListBuilder::new(StringBuilder::new());
+            for cell in cells {
+                match cell.and_then(Value::as_array) {
+                    Some(items) => {
+                        for item in items {
+                            builder.values().append_option(item.as_str());
+                        }
+                        builder.append(true);
+                    }
+                    None => builder.append(false),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        Primitive::Int8 => typed_list::<Int8Type>(cells, as_signed),
+        Primitive::Int16 => typed_list::<Int16Type>(cells, as_signed),
+        Primitive::Uint16 => typed_list::<UInt16Type>(cells, as_unsigned),
+        Primitive::Int32 => typed_list::<Int32Type>(cells, as_signed),
+        Primitive::Uint32 => typed_list::<UInt32Type>(cells, as_unsigned),
+        Primitive::Int64 => typed_list::<Int64Type>(cells, as_signed),
+        Primitive::Uint64 => typed_list::<UInt64Type>(cells, as_unsigned),
+        Primitive::Float => typed_list::<Float32Type>(cells, |v| v.as_f64().map(|f| f as f32)),
+        Primitive::Double => typed_list::<Float64Type>(cells, Value::as_f64),
+    }
+}
+
+fn typed_array<'a, T: ArrowPrimitiveType>(
+    cells: impl Iterator<Item = Option<&'a Value>>,
+    convert: fn(&Value) -> Option<T::Native>,
+) -> ArrayRef {
+    Arc::new(
+        cells
+            .map(|cell| cell.and_then(convert))
+            .collect::<PrimitiveArray<T>>(),
+    )
+}
+
+fn typed_list<'a, T: ArrowPrimitiveType>(
+    cells: impl Iterator<Item = Option<&'a Value>>,
+    convert: fn(&Value) -> Option<T::Native>,
+) -> ArrayRef {
+    let mut builder = ListBuilder::new(PrimitiveBuilder::<T>::new());
+    for cell in cells {
+        match cell.and_then(Value::as_array) {
+            Some(items) => {
+                for item in items {
+                    builder.values().append_option(convert(item));
+                }
+                builder.append(true);
+            }
+            None => builder.append(false),
+        }
+    }
+    Arc::new(builder.finish())
+}
+
+fn as_unsigned<T: TryFrom<u64>>(value: &Value) -> Option<T> {
+    value.as_u64().and_then(|n| T::try_from(n).ok())
+}
+
+fn as_signed<T: TryFrom<i64>>(value: &Value) -> Option<T> {
+    value.as_i64().and_then(|n| T::try_from(n).ok())
+}
@@ -0,0 +1,309 @@
+use std::{collections::BTreeMap, fs::File, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BagReader, MessageOrder, Pipeline, Record, RosPeekResult, Sink, Topic};
+
+/// Expectations on a topic of a bag.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TopicRule {
+    /// Name of the topic, which must be recorded.
+    pub name: String,
+    /// Expected message type, such as `sensor_msgs/msg/PointCloud2`.
+    #[serde(default, rename = "type")]
+    pub type_name: Option<String>,
+    /// Minimum number of messages.
+    #[serde(default)]
+    pub min_count: Option<u64>,
+    /// Minimum average rate in Hz.
+    #[serde(default)]
+    pub min_frequency: Option<f64>,
+    /// Maximum interval between consecutive messages in seconds.
+    #[serde(default)]
+    pub max_gap: Option<f64>,
+    /// Minimum fraction of the bag duration between the first and last messages, from 0 to 1.
+    #[serde(default)]
+    pub min_coverage: Option<f64>,
+}
+
+impl TopicRule {
+    /// Checks the expectations of the rule against the messages of its topic.
+    ///
+    /// # Arguments
+    /// * `topic` - The topic, or `None` if it is not recorded.
+    /// * `timestamps` - Timestamps of the messages of the topic in nanoseconds, sorted.
+    /// * `start_ns` - Timestamp of the first message of the bag.
+    /// * `end_ns` - Timestamp of the last message of the bag.
+    ///
+    /// # Returns
+    /// The outcome of each expectation, or a single failure if the topic is not recorded.
+    pub fn evaluate(
+        &self,
+        topic: Option<&Topic>,
+        timestamps: &[u64],
+        start_ns: u64,
+        end_ns: u64,
+    ) -> Vec<AssertionResult> {
+        let name = Some(self.name.as_str());
+        let Some(topic) = topic else {
+            return vec![AssertionResult::new(
+                name,
+                "exists",
+                "recorded".to_string(),
+                "missing".to_string(),
+                false,
+            )];
+        };
+
+        let mut results = Vec::new();
+        if let Some(type_name) = &self.type_name {
+            results.push(AssertionResult::new(
+                name,
+                "type",
+                type_name.clone(),
+                topic.type_name.clone(),
+                &topic.type_name == type_name,
+            ));
+        }
+        let count = timestamps.len() as u64;
+        if let Some(min_count) = self.min_count {
+            results.push(AssertionResult::new(
+                name,
+                "min_count",
+                format!(">= {min_count}"),
+                count.to_string(),
+                count >= min_count,
+            ));
+        }
+
+        let span_ns = match (timestamps.first(), timestamps.last()) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0,
+        };
+        if let Some(min_frequency) = self.min_frequency {
+            let frequency =
+                (count > 1 && span_ns > 0).then(|| (count - 1) as f64 * 1e9 / span_ns as f64);
+            results.push(AssertionResult::new(
+                name,
+                "min_frequency",
+                format!(">= {min_frequency} Hz"),
+                frequency.map_or("-".to_string(), |hz| format!("{hz:.2} Hz")),
+                frequency.is_some_and(|hz| hz >= min_frequency),
+            ));
+        }
+        if let Some(max_gap) = self.max_gap {
+            let gap = timestamps
+                .windows(2)
+                .map(|w| w[1] - w[0])
+                .max()
+                .map(|ns| ns as f64 / 1e9);
+            results.push(AssertionResult::new(
+                name,
+                "max_gap",
+                format!("<= {max_gap} s"),
+                gap.map_or("-".to_string(), |sec| format!("{sec:.3} s")),
+                gap.is_some_and(|sec| sec <= max_gap),
+            ));
+        }
+        if let Some(min_coverage) = self.min_coverage {
+            let duration_ns = end_ns.saturating_sub(start_ns);
+            let coverage = if duration_ns == 0 {
+                if count > 0 { 1.0 } else { 0.0 }
+            } else {
+                span_ns as f64 / duration_ns as f64
+            };
+            results.push(AssertionResult::new(
+                name,
+                "min_coverage",
+                format!(">= {:.1}%", min_coverage * 100.0),
+                format!("{:.1}%", coverage * 100.0),
+                coverage >= min_coverage,
+            ));
+        }
+        results
+    }
+}
+
+/// Expectations on a bag, such as the topics a recording must contain, to gate recordings in CI.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::AssertRules;
+///
+/// let rules = AssertRules::from_yaml(
+///     r#"
+/// min_duration: 60.0
+/// topics:
+///   - name: /sensing/lidar/points
+///     type: sensor_msgs/msg/PointCloud2
+///     min_frequency: 9.5
+///     max_gap: 0.3
+///     min_coverage: 0.99
+/// "#,
+/// )
+/// .unwrap();
+/// assert_eq!(rules.topics[0].max_gap, Some(0.3));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssertRules {
+    /// Minimum duration of the bag in seconds.
+    #[serde(default)]
+    pub min_duration: Option<f64>,
+    /// Expectations on topics.
+    #[serde(default)]
+    pub topics: Vec<TopicRule>,
+}
+
+impl AssertRules {
+    /// Parses rules from YAML.
+    pub fn from_yaml(yaml: &str) -> RosPeekResult<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Reads a rules file.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the YAML rules file.
+    pub fn load<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read rules {}: {e}", path.display()))?;
+        Self::from_yaml(&yaml).map_err(|e| anyhow::anyhow!("Invalid rules {}: {e}", path.display()))
+    }
+}
+
+/// Outcome of a single expectation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssertionResult {
+    /// Name of the topic, or `None` for expectations on the whole bag.
+    pub topic: Option<String>,
+    /// Name of the rule, such as `min_frequency`.
+    pub rule: String,
+    /// Expected value.
+    pub expected: String,
+    /// Value found in the bag.
+    pub actual: String,
+    /// Whether the expectation is met.
+    pub passed: bool,
+}
+
+impl AssertionResult {
+    fn new(
+        topic: Option<&str>,
+        rule: &str,
+        expected: String,
+        actual: String,
+        passed: bool,
+    ) -> Self {
+        Self {
+            topic: topic.map(str::to_string),
+            rule: rule.to_string(),
+            expected,
+            actual,
+            passed,
+        }
+    }
+}
+
+/// Outcomes of the rules checked against a bag, written as JSON for CI.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AssertReport {
+    /// Path of the bag.
+    pub bag: String,
+    /// Outcome of each expectation, in the order of the rules.
+    pub results: Vec<AssertionResult>,
+}
+
+impl AssertReport {
+    /// Returns the expectations which are not met.
+    pub fn failures(&self) -> impl Iterator<Item = &AssertionResult> {
+        self.results.iter().filter(|r| !r.passed)
+    }
+
+    /// Returns whether every expectation is met.
+    pub fn is_ok(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Writes the report as pretty-printed JSON.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the report file.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> RosPeekResult<()> {
+        let file = File::create(path.as_ref())?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+/// Pipeline sink keeping the timestamps of each topic.
+#[derive(Default)]
+struct TimestampSink {
+    timestamps: BTreeMap<String, Vec<u64>>,
+}
+
+impl Sink for TimestampSink {
+    fn write(&mut self, record: Record) -> RosPeekResult<()> {
+        self.timestamps
+            .entry(record.topic.name.clone())
+            .or_default()
+            .push(record.message.timestamp);
+        Ok(())
+    }
+}
+
+/// Checks a bag against rules.
+///
+/// # Arguments
+/// * `reader` - The reader of the bag.
+/// * `rules` - The expectations on the bag.
+///
+/// # Returns
+/// The outcome of every expectation.
+pub fn assert_bag(reader: &dyn BagReader, rules: &AssertRules) -> RosPeekResult<AssertReport> {
+    let stats = reader.stats()?;
+    let topics = reader.topics()?;
+    let names: Vec<String> = rules
+        .topics
+        .iter()
+        .filter(|rule| topics.iter().any(|t| t.name == rule.name))
+        .map(|rule| rule.name.clone())
+        .collect();
+
+    let mut sink = TimestampSink::default();
+    if !names.is_empty() {
+        Pipeline::new(reader)
+            .topics(&names)
+            .order(MessageOrder::Topic)
+            .sink(&mut sink)?;
+    }
+
+    let mut results = Vec::new();
+    if let Some(min_duration) = rules.min_duration {
+        results.push(AssertionResult::new(
+            None,
+            "min_duration",
+            format!(">= {min_duration} s"),
+            format!("{:.3} s", stats.duration_sec),
+            stats.duration_sec >= min_duration,
+        ));
+    }
+    for timestamps in sink.timestamps.values_mut() {
+        timestamps.sort_unstable();
+    }
+    for rule in rules.topics.iter() {
+        let topic = topics.iter().find(|t| t.name == rule.name);
+        let timestamps = sink
+            .timestamps
+            .get(&rule.name)
+            .map_or(&[][..], Vec::as_slice);
+        results.extend(rule.evaluate(topic, timestamps, stats.start_ns, stats.end_ns));
+    }
+
+    Ok(AssertReport {
+        bag: stats.path.clone(),
+        results,
+    })
+}
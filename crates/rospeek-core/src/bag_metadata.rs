@@ -0,0 +1,258 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Deserialize;
+
+use crate::{BagReader, BagStats, IncompleteSign, RosPeekResult, StorageType, Topic};
+
+/// Difference in seconds between the durations of `metadata.yaml` and of the files past which
+/// the recording is considered incomplete.
+const DURATION_TOLERANCE_SEC: f64 = 1e-3;
+
+/// Name of the file rosbag2 writes into bag directories to describe their contents.
+pub const METADATA_FILE: &str = "metadata.yaml";
+
+#[derive(Debug, Deserialize)]
+struct MetadataFile {
+    rosbag2_bagfile_information: BagMetadata,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct Nanoseconds {
+    #[serde(alias = "nanoseconds_since_epoch")]
+    nanoseconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TopicMetadata {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    serialization_format: String,
+    /// QoS profiles, as a YAML string before Jazzy and as a list since.
+    #[serde(default)]
+    offered_qos_profiles: Option<serde_yaml::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TopicWithCount {
+    topic_metadata: TopicMetadata,
+    message_count: u64,
+}
+
+/// Contents of the `metadata.yaml` of a bag directory, which describes the bag without opening
+/// its storage files.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::BagMetadata;
+///
+/// let yaml = r#"
+/// rosbag2_bagfile_information:
+///   version: 5
+///   storage_identifier: sqlite3
+///   duration: {nanoseconds: 2000000000}
+///   starting_time: {nanoseconds_since_epoch: 1700000000000000000}
+///   message_count: 20
+///   topics_with_message_count:
+///     - topic_metadata: {name: /odom, type: nav_msgs/msg/Odometry, serialization_format: cdr}
+///       message_count: 20
+///   relative_file_paths: [bag_0.db3]
+/// "#;
+/// let metadata = BagMetadata::from_yaml(yaml).unwrap();
+///
+/// assert_eq!(metadata.end_ns(), 1700000002000000000);
+/// assert_eq!(metadata.topics()[0].type_name, "nav_msgs/msg/Odometry");
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct BagMetadata {
+    /// Storage plugin the bag was recorded with, such as `sqlite3` or `mcap`.
+    pub storage_identifier: String,
+    duration: Nanoseconds,
+    starting_time: Nanoseconds,
+    /// Number of messages in the bag.
+    pub message_count: u64,
+    topics_with_message_count: Vec<TopicWithCount>,
+    /// Paths of the storage files relative to the bag directory.
+    #[serde(default)]
+    pub relative_file_paths: Vec<String>,
+}
+
+impl BagMetadata {
+    /// Parses the contents of a `metadata.yaml` file.
+    ///
+    /// # Arguments
+    /// * `yaml` - The contents of the file.
+    pub fn from_yaml(yaml: &str) -> RosPeekResult<Self> {
+        let file: MetadataFile = serde_yaml::from_str(yaml)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {METADATA_FILE}: {e}"))?;
+        Ok(file.rosbag2_bagfile_information)
+    }
+
+    /// Loads the `metadata.yaml` of a bag directory.
+    ///
+    /// # Arguments
+    /// * `dir` - The path to the bag directory.
+    ///
+    /// # Returns
+    /// The metadata, or `None` if the path is not a directory with a `metadata.yaml`.
+    pub fn load<P: AsRef<Path>>(dir: P) -> RosPeekResult<Option<Self>> {
+        let path = dir.as_ref().join(METADATA_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Self::from_yaml(&std::fs::read_to_string(&path)?).map(Some)
+    }
+
+    /// Returns UNIX epoch nanoseconds of the first message.
+    pub fn start_ns(&self) -> u64 {
+        self.starting_time.nanoseconds
+    }
+
+    /// Returns UNIX epoch nanoseconds of the last message.
+    pub fn end_ns(&self) -> u64 {
+        self.starting_time.nanoseconds + self.duration.nanoseconds
+    }
+
+    /// Returns the topics of the bag with their message counts, numbered in their order.
+    pub fn topics(&self) -> Vec<Topic> {
+        self.topics_with_message_count
+            .iter()
+            .enumerate()
+            .map(|(i, topic)| Topic {
+                id: i as u16 + 1,
+                name: topic.topic_metadata.name.clone(),
+                type_name: topic.topic_metadata.type_name.clone(),
+                count: topic.message_count,
+                serialization_format: topic.topic_metadata.serialization_format.clone(),
+                offered_qos_profiles: topic.topic_metadata.offered_qos_profiles.as_ref().and_then(
+                    |profiles| match profiles {
+                        serde_yaml::Value::String(profiles) => Some(profiles.clone()),
+                        profiles => serde_yaml::to_string(profiles).ok(),
+                    },
+                ),
+            })
+            .collect()
+    }
+
+    /// Returns the statistics of the bag, with the size of its storage files.
+    ///
+    /// # Arguments
+    /// * `dir` - The path to the bag directory.
+    pub fn stats<P: AsRef<Path>>(&self, dir: P) -> RosPeekResult<BagStats> {
+        let storage_type = match self.storage_identifier.as_str() {
+            "sqlite3" => StorageType::Sqlite3,
+            "mcap" => StorageType::Mcap,
+            other => anyhow::bail!("Unsupported storage identifier: {other}"),
+        };
+        let mut stats = BagStats::new(
+            dir.as_ref(),
+            storage_type,
+            self.message_count,
+            self.start_ns(),
+            self.end_ns(),
+        );
+        stats.size_bytes = self
+            .relative_file_paths
+            .iter()
+            .map(|file| dir.as_ref().join(file))
+            .filter(|path| path.is_file())
+            .map(crate::size_gb)
+            .sum();
+        Ok(stats)
+    }
+
+    /// Returns the signs that the recording of the bag directory is incomplete: storage files
+    /// missing from the metadata, and with the statistics of the files, a duration which differs
+    /// from the metadata.
+    ///
+    /// # Arguments
+    /// * `dir` - The path to the bag directory.
+    /// * `stats` - The statistics of the storage files, if they are opened.
+    pub fn incomplete_signs<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        stats: Option<&BagStats>,
+    ) -> RosPeekResult<Vec<IncompleteSign>> {
+        let dir = dir.as_ref();
+        let listed: Vec<_> = self
+            .relative_file_paths
+            .iter()
+            .map(|file| dir.join(file))
+            .collect();
+        let mut files = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        files.retain(|path| {
+            matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("db3" | "mcap")
+            ) && !listed.contains(path)
+        });
+        files.sort();
+
+        let mut signs: Vec<_> = files
+            .into_iter()
+            .map(|path| IncompleteSign::UnlistedFile {
+                file: path.display().to_string(),
+            })
+            .collect();
+        if let Some(stats) = stats.filter(|stats| !stats.is_empty()) {
+            let metadata_sec = crate::to_duration_sec(self.start_ns(), self.end_ns());
+            if (metadata_sec - stats.duration_sec).abs() > DURATION_TOLERANCE_SEC {
+                signs.push(IncompleteSign::DurationMismatch {
+                    metadata_sec,
+                    files_sec: stats.duration_sec,
+                });
+            }
+        }
+        Ok(signs)
+    }
+
+    /// Cross-checks the metadata against the contents of the storage files.
+    ///
+    /// # Arguments
+    /// * `reader` - The reader of the bag the metadata describes.
+    ///
+    /// # Returns
+    /// A description of each difference, empty if the metadata matches.
+    pub fn verify(&self, reader: &dyn BagReader) -> RosPeekResult<Vec<String>> {
+        let mut differences = Vec::new();
+        let stats = reader.stats()?;
+        if stats.message_count != self.message_count {
+            differences.push(format!(
+                "Message count: {} in {METADATA_FILE}, {} in files",
+                self.message_count, stats.message_count
+            ));
+        }
+
+        let mut actual: BTreeMap<String, Topic> = reader
+            .topics()?
+            .into_iter()
+            .map(|t| (t.name.clone(), t))
+            .collect();
+        for expected in self.topics() {
+            let Some(topic) = actual.remove(&expected.name) else {
+                differences.push(format!("Topic {}: missing in files", expected.name));
+                continue;
+            };
+            if topic.type_name != expected.type_name {
+                differences.push(format!(
+                    "Topic {}: type {} in {METADATA_FILE}, {} in files",
+                    expected.name, expected.type_name, topic.type_name
+                ));
+            }
+            if topic.count != expected.count {
+                differences.push(format!(
+                    "Topic {}: {} messages in {METADATA_FILE}, {} in files",
+                    expected.name, expected.count, topic.count
+                ));
+            }
+        }
+        differences.extend(
+            actual
+                .into_keys()
+                .map(|name| format!("Topic {name}: missing in {METADATA_FILE}")),
+        );
+        Ok(differences)
+    }
+}
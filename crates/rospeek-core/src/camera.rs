@@ -0,0 +1,260 @@
+use serde::Deserialize;
+
+use crate::{CdrReader, RosPeekResult, Topic};
+
+/// Name of the ROS message type of camera calibrations.
+pub const CAMERA_INFO_TYPE: &str = "sensor_msgs/msg/CameraInfo";
+
+/// Calibration of a camera, as carried by `sensor_msgs/msg/CameraInfo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraInfo {
+    pub frame_id: String,
+    pub width: u32,
+    pub height: u32,
+    /// Distortion model, such as `plumb_bob` or `equidistant`.
+    pub distortion_model: String,
+    /// Distortion coefficients.
+    pub d: Vec<f64>,
+    /// Intrinsic matrix in row-major order.
+    pub k: [f64; 9],
+    /// Rectification matrix in row-major order.
+    pub r: [f64; 9],
+    /// Projection matrix in row-major order.
+    pub p: [f64; 12],
+}
+
+impl CameraInfo {
+    /// Decodes a CDR-encoded `sensor_msgs/msg/CameraInfo`.
+    ///
+    /// # Arguments
+    /// * `data` - CDR-encoded message.
+    pub fn from_cdr(data: &[u8]) -> RosPeekResult<Self> {
+        let mut reader = CdrReader::new(data);
+        // std_msgs/msg/Header
+        reader.read_i32()?;
+        reader.read_u32()?;
+        let frame_id = reader.read_str()?.to_string();
+
+        let height = reader.read_u32()?;
+        let width = reader.read_u32()?;
+        let distortion_model = reader.read_str()?.to_string();
+        let len = reader.read_u32()? as usize;
+        let d = (0..len)
+            .map(|_| reader.read_f64())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut k = [0.0; 9];
+        let mut r = [0.0; 9];
+        let mut p = [0.0; 12];
+        for value in k.iter_mut().chain(r.iter_mut()).chain(p.iter_mut()) {
+            *value = reader.read_f64()?;
+        }
+        Ok(Self {
+            frame_id,
+            width,
+            height,
+            distortion_model,
+            d,
+            k,
+            r,
+            p,
+        })
+    }
+
+    /// Parses a calibration file written by the ROS `camera_calibration` package, such as one
+    /// attached to a bag.
+    ///
+    /// The rectification matrix defaults to identity and the projection matrix to the intrinsic
+    /// matrix when the file lacks them.
+    ///
+    /// # Arguments
+    /// * `yaml` - Content of the calibration file.
+    pub fn from_calibration_yaml(yaml: &str) -> RosPeekResult<Self> {
+        let file: CalibrationFile = serde_yaml::from_str(yaml)?;
+        let k: [f64; 9] = file.camera_matrix.to_array("camera_matrix")?;
+        let r = match file.rectification_matrix {
+            Some(matrix) => matrix.to_array("rectification_matrix")?,
+            None => [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+        };
+        let p = match file.projection_matrix {
+            Some(matrix) => matrix.to_array("projection_matrix")?,
+            None => [
+                k[0], k[1], k[2], 0.0, k[3], k[4], k[5], 0.0, k[6], k[7], k[8], 0.0,
+            ],
+        };
+        Ok(Self {
+            frame_id: file.camera_name.unwrap_or_default(),
+            width: file.image_width,
+            height: file.image_height,
+            distortion_model: file.distortion_model.unwrap_or_default(),
+            d: file
+                .distortion_coefficients
+                .map(|matrix| matrix.data)
+                .unwrap_or_default(),
+            k,
+            r,
+            p,
+        })
+    }
+
+    /// Returns whether any distortion coefficient is set.
+    pub fn is_distorted(&self) -> bool {
+        self.d.iter().any(|d| *d != 0.0)
+    }
+
+    /// Computes where each pixel of the undistorted image comes from in the distorted one, as
+    /// `image_proc` rectifies images.
+    ///
+    /// The undistorted image is rectified by `r` and projected by `p`, or by `k` when the
+    /// projection matrix is unset. The `plumb_bob` and `rational_polynomial` models of OpenCV
+    /// and the `equidistant` fisheye model are supported.
+    pub fn undistort_map(&self) -> RosPeekResult<UndistortMap> {
+        let distortion = Distortion::new(&self.distortion_model, &self.d)?;
+        let (fx, cx, fy, cy) = match self.p.iter().any(|p| *p != 0.0) {
+            true => (self.p[0], self.p[2], self.p[5], self.p[6]),
+            false => (self.k[0], self.k[2], self.k[4], self.k[5]),
+        };
+        if fx == 0.0 || fy == 0.0 {
+            anyhow::bail!("Camera info has no focal length");
+        }
+        let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+        let r = match self.r.iter().all(|v| *v == 0.0) {
+            true => &identity,
+            false => &self.r,
+        };
+
+        let mut points = Vec::with_capacity((self.width * self.height) as usize);
+        for v in 0..self.height {
+            for u in 0..self.width {
+                let x = (u as f64 - cx) / fx;
+                let y = (v as f64 - cy) / fy;
+                // back through the rectification, whose inverse is its transpose
+                let xr = r[0] * x + r[3] * y + r[6];
+                let yr = r[1] * x + r[4] * y + r[7];
+                let w = r[2] * x + r[5] * y + r[8];
+                let (xd, yd) = distortion.apply(xr / w, yr / w);
+                points.push([
+                    (self.k[0] * xd + self.k[1] * yd + self.k[2]) as f32,
+                    (self.k[4] * yd + self.k[5]) as f32,
+                ]);
+            }
+        }
+        Ok(UndistortMap {
+            width: self.width,
+            height: self.height,
+            points,
+        })
+    }
+}
+
+/// Distortion model of a camera with its coefficients.
+enum Distortion {
+    /// Radial and tangential distortion of OpenCV, `k1, k2, p1, p2, k3, k4, k5, k6`.
+    PlumbBob([f64; 8]),
+    /// Fisheye distortion of OpenCV, `k1, k2, k3, k4`.
+    Equidistant([f64; 4]),
+}
+
+impl Distortion {
+    fn new(model: &str, d: &[f64]) -> RosPeekResult<Self> {
+        let coefficients = |i: usize| d.get(i).copied().unwrap_or_default();
+        match model {
+            "plumb_bob" | "rational_polynomial" => {
+                Ok(Distortion::PlumbBob(std::array::from_fn(coefficients)))
+            }
+            "equidistant" => Ok(Distortion::Equidistant(std::array::from_fn(coefficients))),
+            model => anyhow::bail!("Unsupported distortion model: {model}"),
+        }
+    }
+
+    /// Distorts a point of the normalized image plane.
+    fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        match *self {
+            Distortion::PlumbBob([k1, k2, p1, p2, k3, k4, k5, k6]) => {
+                let r2 = x * x + y * y;
+                let radial = (1.0 + r2 * (k1 + r2 * (k2 + r2 * k3)))
+                    / (1.0 + r2 * (k4 + r2 * (k5 + r2 * k6)));
+                (
+                    x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x),
+                    y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y,
+                )
+            }
+            Distortion::Equidistant([k1, k2, k3, k4]) => {
+                let r = (x * x + y * y).sqrt();
+                if r == 0.0 {
+                    return (x, y);
+                }
+                let theta = r.atan();
+                let t2 = theta * theta;
+                let distorted = theta * (1.0 + t2 * (k1 + t2 * (k2 + t2 * (k3 + t2 * k4))));
+                (x * distorted / r, y * distorted / r)
+            }
+        }
+    }
+}
+
+/// Source pixel in a distorted image of each pixel of its undistorted image.
+#[derive(Debug, Clone)]
+pub struct UndistortMap {
+    pub width: u32,
+    pub height: u32,
+    points: Vec<[f32; 2]>,
+}
+
+impl UndistortMap {
+    /// Returns the position in the distorted image of a pixel of the undistorted image.
+    ///
+    /// # Arguments
+    /// * `x` - Column of the pixel in the undistorted image.
+    /// * `y` - Row of the pixel in the undistorted image.
+    pub fn source(&self, x: u32, y: u32) -> [f32; 2] {
+        self.points[(y * self.width + x) as usize]
+    }
+}
+
+/// Finds the camera info topic of an image topic, such as `/camera/camera_info` for
+/// `/camera/image_raw/compressed`, looking up the namespaces from the innermost one.
+///
+/// # Arguments
+/// * `image_topic` - Name of the image topic.
+/// * `topics` - Topics of the bag.
+pub fn find_camera_info_topic<'a>(image_topic: &str, topics: &'a [Topic]) -> Option<&'a Topic> {
+    let mut namespace = image_topic;
+    while let Some((parent, _)) = namespace.rsplit_once('/') {
+        let candidate = format!("{parent}/camera_info");
+        if let Some(topic) = topics
+            .iter()
+            .find(|t| t.name == candidate && t.type_name == CAMERA_INFO_TYPE)
+        {
+            return Some(topic);
+        }
+        namespace = parent;
+    }
+    None
+}
+
+/// Calibration file of the ROS `camera_calibration` package.
+#[derive(Debug, Deserialize)]
+struct CalibrationFile {
+    image_width: u32,
+    image_height: u32,
+    camera_name: Option<String>,
+    camera_matrix: Matrix,
+    distortion_model: Option<String>,
+    distortion_coefficients: Option<Matrix>,
+    rectification_matrix: Option<Matrix>,
+    projection_matrix: Option<Matrix>,
+}
+
+/// Matrix of a calibration file, stored in row-major order.
+#[derive(Debug, Deserialize)]
+struct Matrix {
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    fn to_array<const N: usize>(&self, name: &str) -> RosPeekResult<[f64; N]> {
+        self.data.as_slice().try_into().map_err(|_| {
+            anyhow::anyhow!("Expected {N} values in {name}, found {}", self.data.len())
+        })
+    }
+}
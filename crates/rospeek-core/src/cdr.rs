@@ -1,34 +1,51 @@
-use std::{
-    collections::{BTreeSet, HashMap},
-    io::{Cursor, Read},
-    sync::Arc,
-};
+#[cfg(feature = "csv")]
+use std::collections::BTreeSet;
+use std::{collections::HashMap, ops::Range, sync::Arc, time::Instant};
 
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
 use serde_json::{Value, json};
 
-use crate::{BagReader, FieldType, MessageField, MessageSchema, RosPeekResult, flatten_json};
+#[cfg(feature = "csv")]
+use crate::flatten_json;
+use crate::{
+    BagReader, CdrReader, DecodeMonitor, DecodePlan, DecodeProgress, DecodeRule, DecoderRegistry,
+    FieldType, MessageField, MessageSchema, Primitive, RawMessage, RosPeekResult, TypeVersion,
+    decode_with_rule, lookup_field,
+    plan::{Element, Length, PlanOp},
+};
 
-#[derive(Debug)]
-enum Endianness {
-    Little, // Little-endian 0x01
-    Big,    // Big-endian 0x00
+/// Size of the encapsulation header preceding the payload of CDR-encoded data.
+const CDR_HEADER_SIZE: usize = 4;
+
+/// Kind of value stored in a [`FieldSpan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    /// A single primitive.
+    Primitive(Primitive),
+    /// Contiguous elements of an array or a sequence of primitives.
+    Array(Primitive, usize),
+    /// String bytes, including the null terminator if any.
+    String,
 }
 
-impl From<&[u8]> for Endianness {
-    fn from(bytes: &[u8]) -> Self {
-        match bytes.get(1).copied().unwrap_or(0x01) {
-            0x00 => Endianness::Big,
-            0x01 => Endianness::Little,
-            _ => Endianness::Little,
-        }
-    }
+/// Location of a field value in CDR-encoded data, reported by [`CdrDecoder::visit_plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpan<'n> {
+    /// Name of the field, without the names of the structures containing it.
+    pub name: &'n str,
+    /// Kind of the stored value.
+    pub kind: SpanKind,
+    /// Byte range of the value in the data, including the encapsulation header.
+    pub range: Range<usize>,
 }
 
 pub struct CdrDecoder<'a> {
-    endianness: Endianness,
-    cursor: Cursor<&'a [u8]>,
+    reader: CdrReader<'a>,
     cache: HashMap<String, Arc<MessageSchema>>,
+    skip_blobs: bool,
 }
 
 impl<'a> CdrDecoder<'a> {
@@ -38,9 +55,9 @@ impl<'a> CdrDecoder<'a> {
     /// * `data` - CDR-encoded data
     pub fn new(data: &'a [u8]) -> Self {
         Self {
-            endianness: Endianness::from(data),
-            cursor: Cursor::new(&data[4..]), // first 4bytes are header, so skip them
+            reader: CdrReader::new(data),
             cache: HashMap::new(),
+            skip_blobs: false,
         }
     }
 
@@ -52,19 +69,28 @@ impl<'a> CdrDecoder<'a> {
         let mut cache = HashMap::new();
         cache.insert(schema.type_name.clone(), Arc::new(schema.clone()));
         Self {
-            endianness: Endianness::Little,
-            cursor: Cursor::new(&[]),
+            reader: CdrReader::new(&[]),
             cache,
+            skip_blobs: false,
         }
     }
 
-    /// Resets cursor from CDR-encoded data.
+    /// Resets the reader to CDR-encoded data.
     ///
     /// # Arguments
     /// * `data` - CDR-encoded data
     pub fn reset(&mut self, data: &'a [u8]) -> &mut Self {
-        self.endianness = Endianness::from(data);
-        self.cursor = Cursor::new(&data[4..]);
+        self.reader = CdrReader::new(data);
+        self
+    }
+
+    /// Sets whether [`CdrDecoder::decode_plan`] replaces byte arrays, such as image data, with
+    /// `{"bytes": <length>}` instead of decoding them.
+    ///
+    /// # Arguments
+    /// * `skip` - Whether to skip byte arrays.
+    pub fn skip_blobs(&mut self, skip: bool) -> &mut Self {
+        self.skip_blobs = skip;
         self
     }
 
@@ -79,191 +105,439 @@ impl<'a> CdrDecoder<'a> {
             let value = if field.is_iterable() {
                 self.decode_iterable(field)?
             } else {
-                self.decode_primitive(field)?
+                self.decode_primitive(field.type_name())?
             };
             object.insert(field.name.clone(), value);
         }
         Ok(serde_json::Value::Object(object))
     }
 
-    fn decode_primitive(&mut self, field: &MessageField) -> RosPeekResult<serde_json::Value> {
-        match field.type_name() {
-            // === primitive types ===
-            // NOTE: https://design.ros2.org/articles/idl_interface_definition.html
-            // TODO(ktro2828): [wchar, wstring] is not supported yet
-            "boolean" => Ok(json!(self.decode_bool()?)),
-            "octet" => Ok(json!(self.decode_u8()?)),
-            "char" => Ok(json!(self.decode_char()?)),
-            "float" => Ok(json!(self.decode_f32()?)),
-            "double" => Ok(json!(self.decode_f64()?)),
-            "int8" => Ok(json!(self.decode_i8()?)),
-            "uint8" => Ok(json!(self.decode_u8()?)),
-            "int16" => Ok(json!(self.decode_i16()?)),
-            "uint16" => Ok(json!(self.decode_u16()?)),
-            "int32" => Ok(json!(self.decode_i32()?)),
-            "uint32" => Ok(json!(self.decode_u32()?)),
-            "int64" => Ok(json!(self.decode_i64()?)),
-            "uint64" => Ok(json!(self.decode_u64()?)),
-            "string" => Ok(json!(self.decode_string()?)),
-            // === special ROS 2 types ===
-            "builtin_interfaces/msg/Time" | "builtin_interfaces/msg/Duration" => {
-                let sec = self.decode_i32()?;
-                let nanosec = self.decode_u32()?;
-                Ok(json!({"sec": sec, "nanosec": nanosec}))
-            }
-            // === nested structures ===
-            _ => {
-                let nested_schema = self.get_schema(field.type_name())?;
-                self.decode(nested_schema.as_ref())
+    /// Performs decoding CDR-encoded data with a precompiled plan.
+    ///
+    /// This produces the same value as [`CdrDecoder::decode`] for the schema the plan was compiled from.
+    ///
+    /// # Arguments
+    /// * `plan` - Decode plan compiled from a ROS message schema
+    pub fn decode_plan(&mut self, plan: &DecodePlan) -> RosPeekResult<serde_json::Value> {
+        // objects being decoded, paired with the field name they are stored in
+        let mut stack = vec![(String::new(), serde_json::Map::new())];
+        for op in plan.ops.iter() {
+            match op {
+                PlanOp::Value { name, element } => {
+                    let value = self.decode_element(element)?;
+                    stack.last_mut().unwrap().1.insert(name.clone(), value);
+                }
+                PlanOp::Iterable {
+                    name,
+                    element,
+                    length,
+                } => {
+                    let length = match length {
+                        Length::Fixed(n) => *n,
+                        Length::Sequence => self.reader.read_u32()? as usize,
+                    };
+                    let value = match element {
+                        Element::Primitive(Primitive::Uint8 | Primitive::Octet)
+                            if self.skip_blobs =>
+                        {
+                            self.reader.skip_bytes(length)?;
+                            json!({ "bytes": length })
+                        }
+                        Element::Primitive(primitive) if self.reader.endianness().is_native() => {
+                            self.decode_native_array(*primitive, length)?
+                        }
+                        _ => {
+                            // the length comes from the data, so it is not trusted to allocate
+                            let mut items = Vec::with_capacity(length.min(self.reader.remaining()));
+                            for _ in 0..length {
+                                items.push(self.decode_element(element)?);
+                            }
+                            json!(items)
+                        }
+                    };
+                    stack.last_mut().unwrap().1.insert(name.clone(), value);
+                }
+                PlanOp::Begin { name } => stack.push((name.clone(), serde_json::Map::new())),
+                PlanOp::End => {
+                    let (name, object) = stack.pop().unwrap();
+                    stack
+                        .last_mut()
+                        .unwrap()
+                        .1
+                        .insert(name, serde_json::Value::Object(object));
+                }
             }
         }
+        Ok(serde_json::Value::Object(stack.pop().unwrap().1))
     }
 
-    fn decode_iterable(&mut self, field: &MessageField) -> RosPeekResult<serde_json::Value> {
-        let length = match field.field_type {
-            FieldType::Array(_, n) => n,
-            FieldType::Sequence(_) => self.decode_u32()? as usize,
-            _ => 0,
-        };
-
-        let mut items = Vec::with_capacity(length);
-        for _ in 0..length {
-            items.push(self.decode_primitive(field)?);
+    /// Walks the fields of a message with a precompiled plan without decoding them, reporting
+    /// where each primitive and string is stored in the data.
+    ///
+    /// This allows values to be patched in place, as long as their serialized size is kept.
+    ///
+    /// # Arguments
+    /// * `plan` - Decode plan compiled from a ROS message schema
+    /// * `visit` - Called with the span of every primitive, array of primitives and string
+    pub fn visit_plan(
+        &mut self,
+        plan: &DecodePlan,
+        visit: &mut dyn FnMut(FieldSpan<'_>),
+    ) -> RosPeekResult<()> {
+        for op in plan.ops.iter() {
+            match op {
+                PlanOp::Value { name, element } => self.visit_element(name, element, visit)?,
+                PlanOp::Iterable {
+                    name,
+                    element,
+                    length,
+                } => {
+                    let length = match length {
+                        Length::Fixed(n) => *n,
+                        Length::Sequence => self.reader.read_u32()? as usize,
+                    };
+                    match element {
+                        // arrays of primitives are contiguous, so they are reported as a whole
+                        Element::Primitive(primitive) if length > 0 => {
+                            self.reader.align_to(primitive.size())?;
+                            let start = self.reader.position();
+                            self.reader.skip_bytes(primitive.size() * length)?;
+                            visit(FieldSpan {
+                                name,
+                                kind: SpanKind::Array(*primitive, length),
+                                range: self.span(start),
+                            });
+                        }
+                        _ => {
+                            for _ in 0..length {
+                                self.visit_element(name, element, visit)?;
+                            }
+                        }
+                    }
+                }
+                PlanOp::Begin { .. } | PlanOp::End => {}
+            }
         }
-        Ok(json!(items))
+        Ok(())
     }
 
-    fn get_schema(&mut self, type_name: &str) -> RosPeekResult<Arc<MessageSchema>> {
-        if !self.cache.contains_key(type_name) {
-            let schema = Arc::new(MessageSchema::try_from(type_name)?);
-            self.cache.insert(type_name.to_string(), schema);
+    fn visit_element(
+        &mut self,
+        name: &str,
+        element: &Element,
+        visit: &mut dyn FnMut(FieldSpan<'_>),
+    ) -> RosPeekResult<()> {
+        match element {
+            Element::Primitive(primitive) => {
+                self.reader.align_to(primitive.size())?;
+                let start = self.reader.position();
+                self.reader.skip_bytes(primitive.size())?;
+                visit(FieldSpan {
+                    name,
+                    kind: SpanKind::Primitive(*primitive),
+                    range: self.span(start),
+                });
+            }
+            Element::String => {
+                let len = self.reader.read_u32()? as usize;
+                let start = self.reader.position();
+                self.reader.skip_bytes(len)?;
+                visit(FieldSpan {
+                    name,
+                    kind: SpanKind::String,
+                    range: self.span(start),
+                });
+            }
+            Element::Time => {
+                self.reader.read_i32()?;
+                self.reader.read_u32()?;
+            }
+            Element::Struct(plan) => self.visit_plan(plan, visit)?,
         }
-        Ok(self.cache.get(type_name).unwrap().clone())
+        Ok(())
     }
 
-    // === Decode methods for each primitive ===
+    /// Returns the range of the data from a payload position up to the current one.
+    fn span(&self, start: usize) -> Range<usize> {
+        // positions are relative to the payload following the encapsulation header
+        start + CDR_HEADER_SIZE..self.reader.position() + CDR_HEADER_SIZE
+    }
 
-    fn align_to(&mut self, align: usize) -> RosPeekResult<()> {
-        let pos = self.cursor.position() as usize;
-        let padding = (align - (pos % align)) % align;
-        if padding > 0 {
-            let mut buf = vec![0u8; padding];
-            self.cursor.read_exact(&mut buf)?;
+    fn decode_element(&mut self, element: &Element) -> RosPeekResult<serde_json::Value> {
+        match element {
+            Element::Primitive(primitive) => self.decode_scalar(*primitive),
+            Element::String => Ok(json!(self.reader.read_str()?)),
+            Element::Time => {
+                let sec = self.reader.read_i32()?;
+                let nanosec = self.reader.read_u32()?;
+                Ok(json!({"sec": sec, "nanosec": nanosec}))
+            }
+            Element::Struct(plan) => self.decode_plan(plan),
         }
-        Ok(())
     }
 
-    fn decode_bytes<const N: usize>(&mut self) -> RosPeekResult<[u8; N]> {
-        let mut buf = [0u8; N];
-        self.cursor.read_exact(&mut buf)?;
-        Ok(buf)
+    /// Decodes an array of primitives in host byte order by copying it in bulk.
+    fn decode_native_array(
+        &mut self,
+        primitive: Primitive,
+        length: usize,
+    ) -> RosPeekResult<serde_json::Value> {
+        if length == 0 {
+            return Ok(json!([]));
+        }
+        self.reader.align_to(primitive.size())?;
+        let bytes = self.reader.take_bytes(primitive.size() * length)?;
+        // `pod_collect_to_vec` copies into an aligned buffer since payloads may be unaligned
+        Ok(match primitive {
+            Primitive::Bool => json!(bytes.iter().map(|b| *b != 0).collect::<Vec<_>>()),
+            Primitive::Octet | Primitive::Uint8 => json!(bytes),
+            Primitive::Char => json!(bytes.iter().map(|b| *b as char).collect::<Vec<_>>()),
+            Primitive::Int8 => json!(bytemuck::cast_slice::<u8, i8>(bytes)),
+            Primitive::Int16 => json!(bytemuck::pod_collect_to_vec::<u8, i16>(bytes)),
+            Primitive::Uint16 => json!(bytemuck::pod_collect_to_vec::<u8, u16>(bytes)),
+            Primitive::Int32 => json!(bytemuck::pod_collect_to_vec::<u8, i32>(bytes)),
+            Primitive::Uint32 => json!(bytemuck::pod_collect_to_vec::<u8, u32>(bytes)),
+            Primitive::Int64 => json!(bytemuck::pod_collect_to_vec::<u8, i64>(bytes)),
+            Primitive::Uint64 => json!(bytemuck::pod_collect_to_vec::<u8, u64>(bytes)),
+            Primitive::Float => json!(bytemuck::pod_collect_to_vec::<u8, f32>(bytes)),
+            Primitive::Double => json!(bytemuck::pod_collect_to_vec::<u8, f64>(bytes)),
+        })
     }
 
-    fn decode_bool(&mut self) -> RosPeekResult<bool> {
-        let b = self.decode_u8()?;
-        Ok(b != 0)
+    fn decode_scalar(&mut self, primitive: Primitive) -> RosPeekResult<serde_json::Value> {
+        Ok(match primitive {
+            Primitive::Bool => json!(self.reader.read_bool()?),
+            Primitive::Octet | Primitive::Uint8 => json!(self.reader.read_u8()?),
+            Primitive::Char => json!(self.reader.read_char()?),
+            Primitive::Int8 => json!(self.reader.read_i8()?),
+            Primitive::Int16 => json!(self.reader.read_i16()?),
+            Primitive::Uint16 => json!(self.reader.read_u16()?),
+            Primitive::Int32 => json!(self.reader.read_i32()?),
+            Primitive::Uint32 => json!(self.reader.read_u32()?),
+            Primitive::Int64 => json!(self.reader.read_i64()?),
+            Primitive::Uint64 => json!(self.reader.read_u64()?),
+            Primitive::Float => json!(self.reader.read_f32()?),
+            Primitive::Double => json!(self.reader.read_f64()?),
+        })
     }
 
-    fn decode_u8(&mut self) -> RosPeekResult<u8> {
-        let mut buf = [0u8; 1];
-        self.cursor.read_exact(&mut buf)?;
-        Ok(buf[0])
-    }
+    /// Decodes only the field at a path, skipping all the others without materializing them.
+    ///
+    /// # Arguments
+    /// * `schema` - ROS message schema
+    /// * `path` - Segments of the field path, where numeric segments index into iterables
+    ///
+    /// # Returns
+    /// The decoded value, or `None` if the path does not exist in the schema.
+    pub fn decode_path(
+        &mut self,
+        schema: &MessageSchema,
+        path: &[&str],
+    ) -> RosPeekResult<Option<serde_json::Value>> {
+        let Some((name, rest)) = path.split_first() else {
+            return self.decode(schema).map(Some);
+        };
 
-    fn decode_i8(&mut self) -> RosPeekResult<i8> {
-        self.decode_u8().map(|v| v as i8)
+        for field in schema.fields.iter() {
+            if field.name != *name {
+                self.skip_field(field)?;
+                continue;
+            }
+            return if field.is_iterable() {
+                self.decode_iterable_path(field, rest)
+            } else {
+                self.decode_primitive_path(field.type_name(), rest)
+            };
+        }
+        Ok(None)
     }
 
-    fn decode_u16(&mut self) -> RosPeekResult<u16> {
-        self.align_to(2)?;
-        let buf = self.decode_bytes::<2>()?;
-        Ok(match self.endianness {
-            Endianness::Big => u16::from_be_bytes(buf),
-            Endianness::Little => u16::from_le_bytes(buf),
-        })
+    fn decode_primitive_path(
+        &mut self,
+        type_name: &str,
+        rest: &[&str],
+    ) -> RosPeekResult<Option<serde_json::Value>> {
+        if rest.is_empty() {
+            return self.decode_primitive(type_name).map(Some);
+        }
+        match type_name {
+            "builtin_interfaces/msg/Time" | "builtin_interfaces/msg/Duration" => {
+                let sec = self.reader.read_i32()?;
+                let nanosec = self.reader.read_u32()?;
+                Ok(match rest {
+                    ["sec"] => Some(json!(sec)),
+                    ["nanosec"] => Some(json!(nanosec)),
+                    _ => None,
+                })
+            }
+            "string" => Ok(None),
+            _ if primitive_size(type_name).is_some() => Ok(None),
+            _ => {
+                let nested_schema = self.get_schema(type_name)?;
+                self.decode_path(nested_schema.as_ref(), rest)
+            }
+        }
     }
 
-    fn decode_i16(&mut self) -> RosPeekResult<i16> {
-        self.align_to(2)?;
-        let buf = self.decode_bytes::<2>()?;
-        Ok(match self.endianness {
-            Endianness::Big => i16::from_be_bytes(buf),
-            Endianness::Little => i16::from_le_bytes(buf),
-        })
-    }
+    fn decode_iterable_path(
+        &mut self,
+        field: &MessageField,
+        rest: &[&str],
+    ) -> RosPeekResult<Option<serde_json::Value>> {
+        let Some((index, rest)) = rest.split_first() else {
+            return self.decode_iterable(field).map(Some);
+        };
+        let Ok(index) = index.parse::<usize>() else {
+            return Ok(None);
+        };
 
-    fn decode_u32(&mut self) -> RosPeekResult<u32> {
-        self.align_to(4)?;
-        let buf = self.decode_bytes::<4>()?;
-        Ok(match self.endianness {
-            Endianness::Big => u32::from_be_bytes(buf),
-            Endianness::Little => u32::from_le_bytes(buf),
-        })
+        let length = self.decode_length(field)?;
+        if index >= length {
+            return Ok(None);
+        }
+        self.skip_elements(field.type_name(), index)?;
+        self.decode_primitive_path(field.type_name(), rest)
     }
 
-    fn decode_i32(&mut self) -> RosPeekResult<i32> {
-        self.align_to(4)?;
-        let buf = self.decode_bytes::<4>()?;
-        Ok(match self.endianness {
-            Endianness::Big => i32::from_be_bytes(buf),
-            Endianness::Little => i32::from_le_bytes(buf),
-        })
+    fn decode_primitive(&mut self, type_name: &str) -> RosPeekResult<serde_json::Value> {
+        match type_name {
+            // === primitive types ===
+            // NOTE: https://design.ros2.org/articles/idl_interface_definition.html
+            // TODO(ktro2828): [wchar, wstring] is not supported yet
+            "boolean" => Ok(json!(self.reader.read_bool()?)),
+            "octet" => Ok(json!(self.reader.read_u8()?)),
+            "char" => Ok(json!(self.reader.read_char()?)),
+            "float" => Ok(json!(self.reader.read_f32()?)),
+            "double" => Ok(json!(self.reader.read_f64()?)),
+            "int8" => Ok(json!(self.reader.read_i8()?)),
+            "uint8" => Ok(json!(self.reader.read_u8()?)),
+            "int16" => Ok(json!(self.reader.read_i16()?)),
+            "uint16" => Ok(json!(self.reader.read_u16()?)),
+            "int32" => Ok(json!(self.reader.read_i32()?)),
+            "uint32" => Ok(json!(self.reader.read_u32()?)),
+            "int64" => Ok(json!(self.reader.read_i64()?)),
+            "uint64" => Ok(json!(self.reader.read_u64()?)),
+            "string" => Ok(json!(self.reader.read_str()?)),
+            // === special ROS 2 types ===
+            "builtin_interfaces/msg/Time" | "builtin_interfaces/msg/Duration" => {
+                let sec = self.reader.read_i32()?;
+                let nanosec = self.reader.read_u32()?;
+                Ok(json!({"sec": sec, "nanosec": nanosec}))
+            }
+            // === nested structures ===
+            _ => {
+                let nested_schema = self.get_schema(type_name)?;
+                self.decode(nested_schema.as_ref())
+            }
+        }
     }
 
-    fn decode_u64(&mut self) -> RosPeekResult<u64> {
-        self.align_to(8)?;
-        let buf = self.decode_bytes::<8>()?;
-        Ok(match self.endianness {
-            Endianness::Big => u64::from_be_bytes(buf),
-            Endianness::Little => u64::from_le_bytes(buf),
-        })
+    fn decode_iterable(&mut self, field: &MessageField) -> RosPeekResult<serde_json::Value> {
+        let length = self.decode_length(field)?;
+
+        let mut items = Vec::with_capacity(length.min(self.reader.remaining()));
+        for _ in 0..length {
+            items.push(self.decode_primitive(field.type_name())?);
+        }
+        Ok(json!(items))
     }
 
-    fn decode_i64(&mut self) -> RosPeekResult<i64> {
-        self.align_to(8)?;
-        let buf = self.decode_bytes::<8>()?;
-        Ok(match self.endianness {
-            Endianness::Big => i64::from_be_bytes(buf),
-            Endianness::Little => i64::from_le_bytes(buf),
+    /// Returns the number of elements of an iterable field, reading the length of sequences.
+    fn decode_length(&mut self, field: &MessageField) -> RosPeekResult<usize> {
+        Ok(match field.field_type {
+            FieldType::Array(_, n) => n,
+            FieldType::Sequence(_) => self.reader.read_u32()? as usize,
+            _ => 0,
         })
     }
 
-    fn decode_f32(&mut self) -> RosPeekResult<f32> {
-        self.align_to(4)?;
-        let buf = self.decode_bytes::<4>()?;
-        Ok(match self.endianness {
-            Endianness::Big => f32::from_be_bytes(buf),
-            Endianness::Little => f32::from_le_bytes(buf),
-        })
+    // === Skip methods for fields that are not decoded ===
+
+    fn skip_field(&mut self, field: &MessageField) -> RosPeekResult<()> {
+        if field.is_iterable() {
+            let length = self.decode_length(field)?;
+            self.skip_elements(field.type_name(), length)
+        } else {
+            self.skip_type(field.type_name())
+        }
     }
 
-    fn decode_f64(&mut self) -> RosPeekResult<f64> {
-        self.align_to(8)?;
-        let buf = self.decode_bytes::<8>()?;
-        Ok(match self.endianness {
-            Endianness::Big => f64::from_be_bytes(buf),
-            Endianness::Little => f64::from_le_bytes(buf),
-        })
+    fn skip_elements(&mut self, type_name: &str, count: usize) -> RosPeekResult<()> {
+        match primitive_size(type_name) {
+            // fixed-size elements are contiguous after the first alignment
+            Some(size) if count > 0 => {
+                self.reader.align_to(size)?;
+                Ok(self.reader.skip_bytes(size * count)?)
+            }
+            Some(_) => Ok(()),
+            None => (0..count).try_for_each(|_| self.skip_type(type_name)),
+        }
     }
 
-    fn decode_char(&mut self) -> RosPeekResult<char> {
-        let code = self.decode_u8()?; // char in IDL is ASCII
-        Ok(code as char)
+    fn skip_type(&mut self, type_name: &str) -> RosPeekResult<()> {
+        if let Some(size) = primitive_size(type_name) {
+            self.reader.align_to(size)?;
+            return Ok(self.reader.skip_bytes(size)?);
+        }
+        match type_name {
+            "string" => {
+                let len = self.reader.read_u32()? as usize;
+                Ok(self.reader.skip_bytes(len)?)
+            }
+            "builtin_interfaces/msg/Time" | "builtin_interfaces/msg/Duration" => {
+                self.reader.align_to(4)?;
+                Ok(self.reader.skip_bytes(8)?)
+            }
+            _ => {
+                let nested_schema = self.get_schema(type_name)?;
+                nested_schema
+                    .fields
+                    .iter()
+                    .try_for_each(|field| self.skip_field(field))
+            }
+        }
     }
 
-    fn decode_string(&mut self) -> RosPeekResult<String> {
-        let len = self.decode_u32()? as usize;
-        let mut buf = vec![0u8; len];
-        self.cursor.read_exact(&mut buf)?;
-        if buf.last() == Some(&0) {
-            buf.pop(); // null terminator (optional in ROS 2)
+    fn get_schema(&mut self, type_name: &str) -> RosPeekResult<Arc<MessageSchema>> {
+        if !self.cache.contains_key(type_name) {
+            let schema = Arc::new(MessageSchema::try_from(type_name)?);
+            self.cache.insert(type_name.to_string(), schema);
         }
-        Ok(String::from_utf8(buf)?)
+        Ok(self.cache.get(type_name).unwrap().clone())
     }
 }
 
+/// Returns the serialized size of a fixed-size primitive type, which is also its alignment.
+fn primitive_size(type_name: &str) -> Option<usize> {
+    Primitive::from_type_name(type_name).map(Primitive::size)
+}
+
+/// Decodes a single field of a CDR-encoded message, skipping the other fields.
+///
+/// # Arguments
+/// * `schema` - ROS message schema
+/// * `path` - Dot-separated field path, such as `header.frame_id` or `poses.0.position.x`
+/// * `data` - CDR-encoded data
+///
+/// # Returns
+/// The decoded value, or `None` if the path does not exist in the schema.
+pub fn decode_field(
+    schema: &MessageSchema,
+    path: &str,
+    data: &[u8],
+) -> RosPeekResult<Option<serde_json::Value>> {
+    let segments: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+    CdrDecoder::from_schema(schema)
+        .reset(data)
+        .decode_path(schema, &segments)
+}
+
 /// Decodes messages for a given topic into JSON parallel.
 ///
+/// Values are returned in the order of the messages in the bag, so that the output is the same
+/// for identical inputs regardless of the number of threads. Rules of the [`DecoderRegistry`]
+/// matching the topic are honored.
+///
 /// # Arguments
 /// * `reader` - The bag reader to read messages from.
 /// * `topic` - The topic to decode messages for.
@@ -282,25 +556,283 @@ pub fn try_decode_json(
     limit: Option<usize>,
     offset: Option<usize>,
 ) -> RosPeekResult<Vec<serde_json::Value>> {
+    let runs = decode_runs(reader.as_ref(), topic, since, until, limit, offset)?;
+    Ok(runs.into_iter().flat_map(|run| run.values).collect())
+}
+
+/// Messages of a topic recorded with the same type version, decoded into JSON.
+#[cfg_attr(not(feature = "arrow"), allow(dead_code))]
+pub(crate) struct DecodedRun {
+    /// Schema the messages were decoded with, or `None` if their rule does not decode CDR.
+    pub schema: Option<MessageSchema>,
+    /// UNIX epoch nanoseconds of each message.
+    pub timestamps: Vec<u64>,
+    /// Decoded value of each message.
+    pub values: Vec<Value>,
+}
+
+/// Decodes messages for a given topic into JSON parallel, split into runs of type versions.
+pub(crate) fn decode_runs(
+    reader: &dyn BagReader,
+    topic: &str,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> RosPeekResult<Vec<DecodedRun>> {
     let topic_info = reader
         .topics()?
         .into_iter()
         .find(|t| t.name == topic)
         .ok_or_else(|| anyhow::anyhow!("Topic not found: {topic}"))?;
+    let versions = reader.type_versions(&topic_info)?;
+    let messages = reader.read_messages_range(topic, since, until, limit, offset)?;
+
+    let mut runs = Vec::new();
+    for (version, messages) in version_runs(&versions, &messages) {
+        let rule = DecoderRegistry::global().rule_for(topic, &version.type_name);
+        let (schema, plan) = if rule.as_ref().is_none_or(DecodeRule::needs_schema) {
+            let schema = MessageSchema::try_from(version.type_name.as_ref())?;
+            let plan = DecodePlan::compile(&schema)?;
+            (Some(schema), Some(plan))
+        } else {
+            (None, None)
+        };
+        let values = decode_messages(topic, messages, schema.as_ref(), |decoder, msg| {
+            decode_with_rule(rule.as_ref(), decoder, plan.as_ref(), &msg.data)
+        })?;
+        runs.push(DecodedRun {
+            schema,
+            timestamps: messages.iter().map(|msg| msg.timestamp).collect(),
+            values,
+        });
+    }
+    Ok(runs)
+}
+
+/// Decodes only the given fields of messages for a given topic into JSON parallel.
+///
+/// Fields which are not requested are skipped without being decoded. For topics whose rule in
+/// the [`DecoderRegistry`] does not decode CDR, fields are looked up in the value the rule
+/// produces instead.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topic` - The topic to decode messages for.
+/// * `fields` - Dot-separated paths of the fields to decode.
+/// * `since` - The start time to decode messages from.
+/// * `until` - The end time to decode messages to.
+/// * `limit` - Optional maximum number of messages to decode.
+/// * `offset` - Optional number of messages to skip after filtering.
+///
+/// # Returns
+/// A vector of JSON objects mapping each field path to its value, or null if it does not exist.
+pub fn try_decode_fields(
+    reader: Box<dyn BagReader>,
+    topic: &str,
+    fields: &[String],
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> RosPeekResult<Vec<serde_json::Value>> {
+    let runs = decode_field_runs(reader.as_ref(), topic, fields, since, until, limit, offset)?;
+    Ok(runs.into_iter().flat_map(|run| run.values).collect())
+}
+
+/// Decodes messages for a given topic into JSON parallel along with their timestamps, such as
+/// to split a dump by time.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topic` - The topic to decode messages for.
+/// * `fields` - Dot-separated paths of the fields to decode, or empty to decode whole messages
+///   as [`try_decode_json`] does.
+/// * `since` - The start time to decode messages from.
+/// * `until` - The end time to decode messages to.
+/// * `limit` - Optional maximum number of messages to decode.
+/// * `offset` - Optional number of messages to skip after filtering.
+///
+/// # Returns
+/// Pairs of the UNIX epoch nanoseconds and the decoded value of each message.
+pub fn try_decode_timestamped(
+    reader: Box<dyn BagReader>,
+    topic: &str,
+    fields: &[String],
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> RosPeekResult<Vec<(u64, serde_json::Value)>> {
+    let runs = match fields.is_empty() {
+        true => decode_runs(reader.as_ref(), topic, since, until, limit, offset)?,
+        false => decode_field_runs(reader.as_ref(), topic, fields, since, until, limit, offset)?,
+    };
+    Ok(runs
+        .into_iter()
+        .flat_map(|run| run.timestamps.into_iter().zip(run.values))
+        .collect())
+}
 
-    let schema = Arc::new(MessageSchema::try_from(topic_info.type_name.as_ref())?);
+/// Decodes only the given fields of messages for a given topic into JSON parallel, split into
+/// runs of type versions.
+fn decode_field_runs(
+    reader: &dyn BagReader,
+    topic: &str,
+    fields: &[String],
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> RosPeekResult<Vec<DecodedRun>> {
+    let topic_info = reader
+        .topics()?
+        .into_iter()
+        .find(|t| t.name == topic)
+        .ok_or_else(|| anyhow::anyhow!("Topic not found: {topic}"))?;
+    let versions = reader.type_versions(&topic_info)?;
+    let paths: Vec<Vec<&str>> = fields
+        .iter()
+        .map(|f| f.split('.').filter(|s| !s.is_empty()).collect())
+        .collect();
 
     let messages = reader.read_messages_range(topic, since, until, limit, offset)?;
 
-    let values = messages
-        .par_iter()
-        .map_init(
-            || CdrDecoder::from_schema(&schema),
-            |decoder, msg| decoder.reset(&msg.data).decode(&schema),
-        )
-        .collect::<RosPeekResult<Vec<_>>>()?;
+    let mut runs = Vec::new();
+    for (version, messages) in version_runs(&versions, &messages) {
+        let timestamps = messages.iter().map(|msg| msg.timestamp).collect();
+        let rule = DecoderRegistry::global().rule_for(topic, &version.type_name);
+        if let Some(rule) = rule.filter(|rule| !rule.needs_schema()) {
+            let values = decode_messages(topic, messages, None, |decoder, msg| {
+                let value = decode_with_rule(Some(&rule), decoder, None, &msg.data)?;
+                Ok(Value::Object(
+                    fields
+                        .iter()
+                        .map(|f| {
+                            (
+                                f.clone(),
+                                lookup_field(&value, f).cloned().unwrap_or_default(),
+                            )
+                        })
+                        .collect(),
+                ))
+            })?;
+            runs.push(DecodedRun {
+                schema: None,
+                timestamps,
+                values,
+            });
+            continue;
+        }
+
+        let schema = Arc::new(MessageSchema::try_from(version.type_name.as_ref())?);
+        let values = decode_messages(topic, messages, Some(&schema), |decoder, msg| {
+            let mut object = serde_json::Map::new();
+            for (field, path) in fields.iter().zip(paths.iter()) {
+                let value = decoder.reset(&msg.data).decode_path(&schema, path)?;
+                object.insert(field.clone(), value.unwrap_or(Value::Null));
+            }
+            Ok(Value::Object(object))
+        })?;
+        runs.push(DecodedRun {
+            // values hold the requested fields only, which the schema does not describe
+            schema: None,
+            timestamps,
+            values,
+        });
+    }
+    Ok(runs)
+}
+
+/// Splits messages ordered by time into runs recorded with the same type version.
+fn version_runs<'v, 'm>(
+    versions: &'v [TypeVersion],
+    messages: &'m [RawMessage],
+) -> Vec<(&'v TypeVersion, &'m [RawMessage])> {
+    let mut runs: Vec<(&TypeVersion, &[RawMessage])> = Vec::new();
+    let mut start = 0;
+    for (i, message) in messages.iter().enumerate() {
+        let Some(version) = TypeVersion::at(versions, message.timestamp) else {
+            continue;
+        };
+        match runs.last_mut() {
+            Some((last, run)) if std::ptr::eq(*last, version) => {
+                *run = &messages[start..=i];
+            }
+            _ => {
+                start = i;
+                runs.push((version, &messages[i..=i]));
+            }
+        }
+    }
+    runs
+}
+
+/// Decodes messages in parallel, giving each worker thread its own decoder.
+///
+/// Each result is written into the slot of its message index, so that values keep the order of
+/// the messages whatever the scheduling, and the error of the earliest failing message is
+/// returned, which makes the output reproducible for identical inputs.
+#[cfg(feature = "parallel")]
+fn decode_messages<'a, F>(
+    topic: &str,
+    messages: &'a [RawMessage],
+    schema: Option<&MessageSchema>,
+    decode: F,
+) -> RosPeekResult<Vec<Value>>
+where
+    F: Fn(&mut CdrDecoder<'a>, &'a RawMessage) -> RosPeekResult<Value> + Send + Sync,
+{
+    let progress = DecodeMonitor::global().start(topic, messages.len());
+    let mut slots: Vec<Option<RosPeekResult<Value>>> = Vec::with_capacity(messages.len());
+    slots.resize_with(messages.len(), || None);
+    slots.par_iter_mut().zip(messages.par_iter()).for_each_init(
+        || schema.map_or_else(|| CdrDecoder::new(&[]), CdrDecoder::from_schema),
+        |decoder, (slot, msg)| {
+            *slot = Some(monitored(progress.as_deref(), msg, || decode(decoder, msg)));
+        },
+    );
+    slots
+        .into_iter()
+        .map(|slot| slot.unwrap_or_else(|| Err(anyhow::anyhow!("Message was not decoded"))))
+        .collect()
+}
+
+/// Decodes messages one by one with a single decoder.
+#[cfg(not(feature = "parallel"))]
+fn decode_messages<'a, F>(
+    topic: &str,
+    messages: &'a [RawMessage],
+    schema: Option<&MessageSchema>,
+    decode: F,
+) -> RosPeekResult<Vec<Value>>
+where
+    F: Fn(&mut CdrDecoder<'a>, &'a RawMessage) -> RosPeekResult<Value>,
+{
+    let progress = DecodeMonitor::global().start(topic, messages.len());
+    let mut decoder = schema.map_or_else(|| CdrDecoder::new(&[]), CdrDecoder::from_schema);
+    messages
+        .iter()
+        .map(|msg| monitored(progress.as_deref(), msg, || decode(&mut decoder, msg)))
+        .collect()
+}
 
-    Ok(values)
+/// Decodes a message, recording it into the progress of its topic while monitoring.
+fn monitored<F>(
+    progress: Option<&DecodeProgress>,
+    msg: &RawMessage,
+    decode: F,
+) -> RosPeekResult<Value>
+where
+    F: FnOnce() -> RosPeekResult<Value>,
+{
+    let Some(progress) = progress else {
+        return decode();
+    };
+    let started = Instant::now();
+    let value = decode();
+    progress.record(msg.data.len(), started.elapsed());
+    value
 }
 
 /// Decode a topic into a CSV format.
@@ -315,6 +847,7 @@ pub fn try_decode_json(
 ///
 /// # Returns
 /// A tuple containing the column names and rows of the decoded CSV.
+#[cfg(feature = "csv")]
 pub fn try_decode_csv(
     reader: Box<dyn BagReader>,
     topic: &str,
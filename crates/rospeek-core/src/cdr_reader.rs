@@ -0,0 +1,183 @@
+//! Low-level reading of CDR primitives.
+//!
+//! This module only depends on `core`, so that it can be used by consumers without std
+//! (e.g. embedded or wasm targets) to decode messages into their own types.
+
+use core::fmt::{Display, Formatter};
+
+/// Errors raised while reading CDR-encoded data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CdrError {
+    /// The data ended before the value was fully read.
+    UnexpectedEof,
+    /// A string is not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl Display for CdrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CdrError::UnexpectedEof => write!(f, "unexpected end of CDR data"),
+            CdrError::InvalidUtf8 => write!(f, "CDR string is not valid UTF-8"),
+        }
+    }
+}
+
+impl core::error::Error for CdrError {}
+
+/// Byte order of CDR-encoded data, given by its encapsulation header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little, // Little-endian 0x01
+    Big,    // Big-endian 0x00
+}
+
+impl From<&[u8]> for Endianness {
+    fn from(bytes: &[u8]) -> Self {
+        match bytes.get(1).copied().unwrap_or(0x01) {
+            0x00 => Endianness::Big,
+            0x01 => Endianness::Little,
+            _ => Endianness::Little,
+        }
+    }
+}
+
+impl Endianness {
+    /// Returns whether the byte order matches the host, so that values can be copied as they are.
+    pub fn is_native(&self) -> bool {
+        match self {
+            Endianness::Little => cfg!(target_endian = "little"),
+            Endianness::Big => cfg!(target_endian = "big"),
+        }
+    }
+}
+
+/// Reads primitives from a slice of CDR-encoded data, aligning them relative to the payload.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::CdrReader;
+///
+/// // header (little-endian), then `uint8` 1 followed by `uint32` 2 aligned to 4 bytes
+/// let data = [0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00];
+/// let mut reader = CdrReader::new(&data);
+/// assert_eq!(reader.read_u8(), Ok(1));
+/// assert_eq!(reader.read_u32(), Ok(2));
+/// assert_eq!(reader.remaining(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CdrReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    endianness: Endianness,
+}
+
+/// Generates a method reading a fixed-size number in the byte order of the data.
+macro_rules! read_number {
+    ($name:ident, $ty:ty) => {
+        pub fn $name(&mut self) -> Result<$ty, CdrError> {
+            const SIZE: usize = core::mem::size_of::<$ty>();
+            self.align_to(SIZE)?;
+            let bytes: [u8; SIZE] = self.take_array()?;
+            Ok(match self.endianness {
+                Endianness::Big => <$ty>::from_be_bytes(bytes),
+                Endianness::Little => <$ty>::from_le_bytes(bytes),
+            })
+        }
+    };
+}
+
+impl<'a> CdrReader<'a> {
+    /// Creates a reader from CDR-encoded data, starting with the 4-byte encapsulation header.
+    ///
+    /// # Arguments
+    /// * `data` - CDR-encoded data
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data: data.get(4..).unwrap_or_default(),
+            position: 0,
+            endianness: Endianness::from(data),
+        }
+    }
+
+    /// Returns the byte order of the data.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Returns the position in the payload following the header.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    /// Skips padding so that the position is a multiple of `align`.
+    pub fn align_to(&mut self, align: usize) -> Result<(), CdrError> {
+        let padding = (align - (self.position % align)) % align;
+        self.skip_bytes(padding)
+    }
+
+    /// Returns the next `n` bytes without copying them, advancing the position.
+    pub fn take_bytes(&mut self, n: usize) -> Result<&'a [u8], CdrError> {
+        let end = self
+            .position
+            .checked_add(n)
+            .ok_or(CdrError::UnexpectedEof)?;
+        let bytes = self
+            .data
+            .get(self.position..end)
+            .ok_or(CdrError::UnexpectedEof)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Skips the next `n` bytes.
+    pub fn skip_bytes(&mut self, n: usize) -> Result<(), CdrError> {
+        self.take_bytes(n).map(|_| ())
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], CdrError> {
+        let bytes = self.take_bytes(N)?;
+        Ok(bytes.try_into().unwrap_or([0; N]))
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, CdrError> {
+        Ok(self.take_array::<1>()?[0])
+    }
+
+    pub fn read_i8(&mut self) -> Result<i8, CdrError> {
+        self.read_u8().map(|v| v as i8)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, CdrError> {
+        self.read_u8().map(|v| v != 0)
+    }
+
+    /// Reads a `char`, which is ASCII in IDL.
+    pub fn read_char(&mut self) -> Result<char, CdrError> {
+        self.read_u8().map(|v| v as char)
+    }
+
+    read_number!(read_u16, u16);
+    read_number!(read_i16, i16);
+    read_number!(read_u32, u32);
+    read_number!(read_i32, i32);
+    read_number!(read_u64, u64);
+    read_number!(read_i64, i64);
+    read_number!(read_f32, f32);
+    read_number!(read_f64, f64);
+
+    /// Reads a string without copying it, dropping the null terminator if present.
+    pub fn read_str(&mut self) -> Result<&'a str, CdrError> {
+        let len = self.read_u32()? as usize;
+        let mut bytes = self.take_bytes(len)?;
+        if let [rest @ .., 0] = bytes {
+            bytes = rest; // null terminator (optional in ROS 2)
+        }
+        core::str::from_utf8(bytes).map_err(|_| CdrError::InvalidUtf8)
+    }
+}
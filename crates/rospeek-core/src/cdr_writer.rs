@@ -0,0 +1,114 @@
+//! Low-level writing of CDR primitives, the counterpart of [`crate::CdrReader`].
+
+use alloc::{vec, vec::Vec};
+
+use crate::Endianness;
+
+/// Writes primitives into CDR-encoded data, aligning them relative to the payload.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{CdrReader, CdrWriter, Endianness};
+///
+/// let mut writer = CdrWriter::new(Endianness::Little);
+/// writer.write_u8(1);
+/// writer.write_u32(2);
+/// let data = writer.into_bytes();
+///
+/// let mut reader = CdrReader::new(&data);
+/// assert_eq!(reader.read_u8(), Ok(1));
+/// assert_eq!(reader.read_u32(), Ok(2));
+/// assert_eq!(reader.remaining(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CdrWriter {
+    data: Vec<u8>,
+    endianness: Endianness,
+}
+
+/// Size of the encapsulation header preceding the payload.
+const HEADER_SIZE: usize = 4;
+
+/// Generates a method writing a fixed-size number in the byte order of the data.
+macro_rules! write_number {
+    ($name:ident, $ty:ty) => {
+        pub fn $name(&mut self, value: $ty) {
+            self.align_to(core::mem::size_of::<$ty>());
+            match self.endianness {
+                Endianness::Big => self.data.extend_from_slice(&value.to_be_bytes()),
+                Endianness::Little => self.data.extend_from_slice(&value.to_le_bytes()),
+            }
+        }
+    };
+}
+
+impl CdrWriter {
+    /// Creates a writer starting with the 4-byte encapsulation header of plain CDR.
+    ///
+    /// # Arguments
+    /// * `endianness` - Byte order of the written data.
+    pub fn new(endianness: Endianness) -> Self {
+        let kind = match endianness {
+            Endianness::Big => 0x00,
+            Endianness::Little => 0x01,
+        };
+        Self {
+            data: vec![0x00, kind, 0x00, 0x00],
+            endianness,
+        }
+    }
+
+    /// Returns the position in the payload following the header.
+    pub fn position(&self) -> usize {
+        self.data.len() - HEADER_SIZE
+    }
+
+    /// Writes padding so that the position is a multiple of `align`.
+    pub fn align_to(&mut self, align: usize) {
+        let padding = (align - (self.position() % align)) % align;
+        self.data.resize(self.data.len() + padding, 0);
+    }
+
+    /// Writes bytes as they are, without alignment.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_i8(&mut self, value: i8) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    /// Writes a `char`, which is ASCII in IDL.
+    pub fn write_char(&mut self, value: char) {
+        self.write_u8(value as u8);
+    }
+
+    write_number!(write_u16, u16);
+    write_number!(write_i16, i16);
+    write_number!(write_u32, u32);
+    write_number!(write_i32, i32);
+    write_number!(write_u64, u64);
+    write_number!(write_i64, i64);
+    write_number!(write_f32, f32);
+    write_number!(write_f64, f64);
+
+    /// Writes a string with its length and a null terminator, as ROS 2 does.
+    pub fn write_str(&mut self, value: &str) {
+        self.write_u32(value.len() as u32 + 1);
+        self.write_bytes(value.as_bytes());
+        self.write_u8(0);
+    }
+
+    /// Returns the written data, including the header.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use crate::{BagReader, RawMessage, Record, RosPeekResult, Stage};
+
+/// Result of checking the messages of a topic.
+#[derive(Debug, Clone, Default)]
+pub struct TopicCheck {
+    /// Name of the topic.
+    pub name: String,
+    /// Number of messages in the topic.
+    pub messages: u64,
+    /// Number of exact duplicates, i.e. messages with the same timestamp and payload as an earlier one.
+    pub duplicates: u64,
+    /// Number of messages recorded after a message with a later timestamp.
+    pub out_of_order: u64,
+}
+
+impl TopicCheck {
+    /// Returns whether no problem was found.
+    pub fn is_ok(&self) -> bool {
+        self.duplicates == 0 && self.out_of_order == 0
+    }
+}
+
+/// Checks the messages of topics for recording glitches.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topics` - Topic names to check, or all topics if empty.
+///
+/// # Returns
+/// The result of the check for each topic.
+pub fn check_bag(reader: &dyn BagReader, topics: &[String]) -> RosPeekResult<Vec<TopicCheck>> {
+    let mut names: Vec<String> = reader
+        .topics()?
+        .into_iter()
+        .map(|t| t.name)
+        .filter(|name| topics.is_empty() || topics.contains(name))
+        .collect();
+    if let Some(missing) = topics.iter().find(|name| !names.contains(name)) {
+        anyhow::bail!("Topic not found: {missing}");
+    }
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let messages = reader.read_messages(&name)?;
+            Ok(TopicCheck {
+                messages: messages.len() as u64,
+                duplicates: duplicate_flags(&messages).filter(|d| *d).count() as u64,
+                out_of_order: count_out_of_order(&messages),
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Removes exact duplicates of messages, keeping their first occurrence.
+///
+/// # Arguments
+/// * `messages` - Messages of a single topic.
+///
+/// # Returns
+/// The number of removed messages.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{RawMessage, dedup_messages};
+///
+/// let msg = |timestamp, data: &[u8]| RawMessage { timestamp, topic_id: 1, data: data.to_vec() };
+/// let mut messages = vec![msg(1, b"a"), msg(1, b"a"), msg(1, b"b"), msg(2, b"a")];
+///
+/// assert_eq!(dedup_messages(&mut messages), 1);
+/// assert_eq!(messages.len(), 3);
+/// ```
+pub fn dedup_messages(messages: &mut Vec<RawMessage>) -> u64 {
+    let flags: Vec<bool> = duplicate_flags(messages).collect();
+    let mut flags = flags.into_iter();
+    let before = messages.len();
+    messages.retain(|_| !flags.next().unwrap_or(false));
+    (before - messages.len()) as u64
+}
+
+/// Pipeline stage dropping exact duplicates of messages, keeping their first occurrence.
+///
+/// Payloads are compared by their hash, so that seen messages don't have to be kept in memory.
+#[derive(Debug, Default)]
+pub struct Dedup {
+    /// Pairs of timestamp and payload hash of the seen messages for each topic
+    seen: HashMap<String, HashSet<(u64, u64)>>,
+}
+
+impl Stage for Dedup {
+    fn process(&mut self, record: Record) -> RosPeekResult<Option<Record>> {
+        let mut hasher = DefaultHasher::new();
+        record.message.data.hash(&mut hasher);
+        let key = (record.message.timestamp, hasher.finish());
+
+        if !self.seen.contains_key(&record.topic.name) {
+            self.seen.insert(record.topic.name.clone(), HashSet::new());
+        }
+        let seen = self.seen.get_mut(&record.topic.name).unwrap();
+        Ok(seen.insert(key).then_some(record))
+    }
+
+    fn name(&self) -> &str {
+        "Deduplication"
+    }
+}
+
+/// Counts messages recorded after a message with a later timestamp.
+///
+/// # Arguments
+/// * `messages` - Messages of a single topic in recorded order.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{RawMessage, count_out_of_order};
+///
+/// let msg = |timestamp| RawMessage { timestamp, topic_id: 1, data: Vec::new() };
+/// let messages = vec![msg(1), msg(3), msg(2), msg(2), msg(4)];
+///
+/// assert_eq!(count_out_of_order(&messages), 2);
+/// ```
+pub fn count_out_of_order(messages: &[RawMessage]) -> u64 {
+    let mut latest = 0;
+    messages
+        .iter()
+        .filter(|m| {
+            let reordered = m.timestamp < latest;
+            latest = latest.max(m.timestamp);
+            reordered
+        })
+        .count() as u64
+}
+
+/// Yields whether each message duplicates an earlier one.
+fn duplicate_flags(messages: &[RawMessage]) -> impl Iterator<Item = bool> + '_ {
+    let mut seen = HashSet::new();
+    messages
+        .iter()
+        .map(move |m| !seen.insert((m.timestamp, m.data.as_slice())))
+}
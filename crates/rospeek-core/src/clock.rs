@@ -0,0 +1,164 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    BagReader, CdrDecoder, FieldType, MessageField, MessageSchema, RawMessage, RosPeekResult,
+    ns_to_iso,
+};
+
+/// Default name of the topic publishing simulated time.
+pub const CLOCK_TOPIC: &str = "/clock";
+
+/// Message type of the topic publishing simulated time.
+pub const CLOCK_TYPE: &str = "rosgraph_msgs/msg/Clock";
+
+/// Mapping between bag timestamps and the simulated time published on `/clock`.
+///
+/// Times between two clock samples are interpolated linearly, and times outside of the samples
+/// are extrapolated from the nearest one at real-time rate.
+#[derive(Debug, Clone)]
+pub struct ClockMap {
+    /// Pairs of bag timestamp and simulated time in nanoseconds, ordered by bag timestamp
+    samples: Vec<(u64, u64)>,
+}
+
+impl ClockMap {
+    /// Builds a mapping from `rosgraph_msgs/msg/Clock` messages.
+    ///
+    /// # Arguments
+    /// * `messages` - Messages of the clock topic.
+    ///
+    /// # Returns
+    /// The mapping, or `None` if there is no clock message.
+    pub fn from_messages(messages: &[RawMessage]) -> RosPeekResult<Option<Self>> {
+        // `builtin_interfaces/msg/Time` is decoded without looking up its IDL
+        let schema = MessageSchema {
+            type_name: CLOCK_TYPE.to_string(),
+            fields: vec![MessageField {
+                name: "clock".to_string(),
+                field_type: FieldType::Object("builtin_interfaces/msg/Time".to_string()),
+            }],
+        };
+
+        let mut decoder = CdrDecoder::from_schema(&schema);
+        let mut samples = Vec::with_capacity(messages.len());
+        for message in messages.iter() {
+            let value = decoder.reset(&message.data).decode(&schema)?;
+            let sec = value["clock"]["sec"].as_i64().unwrap_or_default().max(0) as u64;
+            let nanosec = value["clock"]["nanosec"].as_u64().unwrap_or_default();
+            samples.push((message.timestamp, sec * 1_000_000_000 + nanosec));
+        }
+        samples.sort_by_key(|(bag_ns, _)| *bag_ns);
+
+        Ok((!samples.is_empty()).then_some(Self { samples }))
+    }
+
+    /// Builds a mapping from the clock topic of a bag.
+    ///
+    /// # Arguments
+    /// * `reader` - The bag reader to read clock messages from.
+    ///
+    /// # Returns
+    /// The mapping, or `None` if the bag has no clock topic or message.
+    pub fn from_reader(reader: &dyn BagReader) -> RosPeekResult<Option<Self>> {
+        let has_clock = reader
+            .topics()?
+            .iter()
+            .any(|t| t.name == CLOCK_TOPIC && t.type_name == CLOCK_TYPE);
+        if !has_clock {
+            return Ok(None);
+        }
+        Self::from_messages(&reader.read_messages(CLOCK_TOPIC)?)
+    }
+
+    /// Returns the number of clock samples.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns whether there is no clock sample, which never holds for a built mapping.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the simulated time of the first and last clock samples.
+    pub fn sim_range(&self) -> (u64, u64) {
+        let first = self.samples.first().map_or(0, |(_, sim)| *sim);
+        let last = self.samples.last().map_or(0, |(_, sim)| *sim);
+        (first, last)
+    }
+
+    /// Converts a bag timestamp into simulated time.
+    pub fn to_sim(&self, bag_ns: u64) -> u64 {
+        interpolate(&self.samples, bag_ns)
+    }
+
+    /// Converts simulated time into a bag timestamp.
+    ///
+    /// Simulated time is assumed not to go backwards, as is the case unless the simulation was reset.
+    pub fn to_bag(&self, sim_ns: u64) -> u64 {
+        let inverse: Vec<(u64, u64)> = self.samples.iter().map(|(b, s)| (*s, *b)).collect();
+        interpolate(&inverse, sim_ns)
+    }
+}
+
+/// Maps `x` through piecewise-linear samples `(x, y)` ordered by `x`.
+fn interpolate(samples: &[(u64, u64)], x: u64) -> u64 {
+    let index = samples.partition_point(|(sx, _)| *sx <= x);
+    match (
+        index.checked_sub(1).map(|i| samples[i]),
+        samples.get(index).copied(),
+    ) {
+        (Some((x0, y0)), Some((x1, y1))) if x1 > x0 => {
+            let ratio = (x - x0) as f64 / (x1 - x0) as f64;
+            (y0 as f64 + ratio * (y1 as f64 - y0 as f64)).round() as u64
+        }
+        (Some((x0, y0)), _) => y0.saturating_add(x - x0),
+        (None, Some((x1, y1))) => y1.saturating_sub(x1 - x),
+        (None, None) => x,
+    }
+}
+
+/// Time basis the timestamps of a bag are recorded in.
+#[derive(Debug, Clone)]
+pub enum TimeBasis {
+    /// Wall-clock time
+    Wall,
+    /// Simulated time published on `/clock`
+    SimClock(ClockMap),
+}
+
+impl TimeBasis {
+    /// Detects the time basis of a bag from the presence of a clock topic.
+    pub fn detect(reader: &dyn BagReader) -> RosPeekResult<Self> {
+        Ok(match ClockMap::from_reader(reader)? {
+            Some(clock) => TimeBasis::SimClock(clock),
+            None => TimeBasis::Wall,
+        })
+    }
+
+    /// Returns the clock mapping if the bag was recorded under simulated time.
+    pub fn clock(&self) -> Option<&ClockMap> {
+        match self {
+            TimeBasis::Wall => None,
+            TimeBasis::SimClock(clock) => Some(clock),
+        }
+    }
+}
+
+impl Display for TimeBasis {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TimeBasis::Wall => write!(f, "wall time"),
+            TimeBasis::SimClock(clock) => {
+                let (start, end) = clock.sim_range();
+                write!(
+                    f,
+                    "sim time ({CLOCK_TOPIC}, {} samples, {} → {})",
+                    clock.len(),
+                    ns_to_iso(start),
+                    ns_to_iso(end)
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::RosPeekResult;
+
+/// Difference between a decoded message and the message of a golden dump.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DumpDifference {
+    /// Index of the message in the dump.
+    pub index: usize,
+    /// Dot-separated path of the field, such as `objects.0.pose.x`, empty for the whole message.
+    pub path: String,
+    /// Value of the golden dump, or `None` if it has no such field or message.
+    pub expected: Option<Value>,
+    /// Value decoded from the bag, or `None` if it has no such field or message.
+    pub actual: Option<Value>,
+}
+
+/// Reads a dump, either a JSON array as written by `dump` or JSON Lines with a message per line.
+///
+/// # Arguments
+/// * `path` - Path to the dump file.
+pub fn load_dump<P: AsRef<Path>>(path: P) -> RosPeekResult<Vec<Value>> {
+    let path = path.as_ref();
+    let mut reader = BufReader::new(
+        File::open(path).map_err(|e| anyhow::anyhow!("Failed to open {}: {e}", path.display()))?,
+    );
+
+    let starts_with_array = reader
+        .fill_buf()?
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b'[');
+    if starts_with_array {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        return Ok(serde_json::from_str(&text)?);
+    }
+
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(l) if l.trim().is_empty()))
+        .map(|(i, line)| {
+            serde_json::from_str(&line?)
+                .map_err(|e| anyhow::anyhow!("Invalid JSON at {}:{}: {e}", path.display(), i + 1))
+        })
+        .collect()
+}
+
+/// Compares decoded messages against a golden dump, message by message.
+///
+/// # Arguments
+/// * `expected` - Messages of the golden dump.
+/// * `actual` - Messages decoded from the bag.
+/// * `tolerance` - Largest absolute difference between numbers considered equal.
+///
+/// # Returns
+/// Every difference, ordered by message then by field.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::compare_dumps;
+/// use serde_json::json;
+///
+/// let expected = [json!({"x": 1.0, "label": "car"})];
+/// let actual = [json!({"x": 1.0000001, "label": "bus"})];
+///
+/// let differences = compare_dumps(&expected, &actual, 1e-6);
+/// assert_eq!(differences.len(), 1);
+/// assert_eq!(differences[0].path, "label");
+/// ```
+pub fn compare_dumps(expected: &[Value], actual: &[Value], tolerance: f64) -> Vec<DumpDifference> {
+    let mut differences = Vec::new();
+    for index in 0..expected.len().max(actual.len()) {
+        match (expected.get(index), actual.get(index)) {
+            (Some(expected), Some(actual)) => {
+                diff_value(index, "", expected, actual, tolerance, &mut differences)
+            }
+            (expected, actual) => differences.push(DumpDifference {
+                index,
+                path: String::new(),
+                expected: expected.cloned(),
+                actual: actual.cloned(),
+            }),
+        }
+    }
+    differences
+}
+
+fn diff_value(
+    index: usize,
+    path: &str,
+    expected: &Value,
+    actual: &Value,
+    tolerance: f64,
+    differences: &mut Vec<DumpDifference>,
+) {
+    let join = |segment: &str| {
+        if path.is_empty() {
+            segment.to_string()
+        } else {
+            format!("{path}.{segment}")
+        }
+    };
+    let differ = |path: String, expected: Option<&Value>, actual: Option<&Value>| DumpDifference {
+        index,
+        path,
+        expected: expected.cloned(),
+        actual: actual.cloned(),
+    };
+
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected.iter() {
+                match actual.get(key) {
+                    Some(other) => {
+                        diff_value(index, &join(key), value, other, tolerance, differences)
+                    }
+                    None => differences.push(differ(join(key), Some(value), None)),
+                }
+            }
+            for (key, value) in actual
+                .iter()
+                .filter(|(key, _)| !expected.contains_key(*key))
+            {
+                differences.push(differ(join(key), None, Some(value)));
+            }
+        }
+        (Value::Array(expected_items), Value::Array(actual_items))
+            if expected_items.len() == actual_items.len() =>
+        {
+            for (i, (expected, actual)) in expected_items.iter().zip(actual_items).enumerate() {
+                diff_value(
+                    index,
+                    &join(&i.to_string()),
+                    expected,
+                    actual,
+                    tolerance,
+                    differences,
+                );
+            }
+        }
+        (Value::Number(a), Value::Number(b)) => {
+            let equal = match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => (a - b).abs() <= tolerance,
+                _ => a == b,
+            };
+            if !equal {
+                differences.push(differ(path.to_string(), Some(expected), Some(actual)));
+            }
+        }
+        _ if expected != actual => {
+            differences.push(differ(path.to_string(), Some(expected), Some(actual)))
+        }
+        _ => {}
+    }
+}
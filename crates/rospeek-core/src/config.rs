@@ -0,0 +1,301 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{CdrDecoder, DecodePlan, FieldUnits, RosPeekResult, TopicAliases};
+
+/// Environment variable with the path to the config file.
+pub const CONFIG_ENV: &str = "ROSPEEK_CONFIG";
+
+/// Handling of the messages of a topic, overriding the default decoding.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RawDecodeRule")]
+pub enum DecodeRule {
+    /// Decodes messages, replacing byte arrays such as image data with their length.
+    SkipBlobs,
+    /// Leaves messages undecoded, showing their raw bytes.
+    Bytes,
+    /// Decodes messages with the decoder registered under this name in the [`DecoderRegistry`].
+    Decoder(String),
+}
+
+/// A rule as written in the config, either a name or a `decoder: <name>` map.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDecodeRule {
+    Name(String),
+    Decoder { decoder: String },
+}
+
+impl TryFrom<RawDecodeRule> for DecodeRule {
+    type Error = String;
+
+    fn try_from(raw: RawDecodeRule) -> Result<Self, Self::Error> {
+        match raw {
+            RawDecodeRule::Name(name) => match name.as_str() {
+                "skip_blobs" => Ok(DecodeRule::SkipBlobs),
+                "bytes" => Ok(DecodeRule::Bytes),
+                _ => Err(format!(
+                    "unknown rule `{name}`, expected `skip_blobs`, `bytes` or `decoder: <name>`"
+                )),
+            },
+            RawDecodeRule::Decoder { decoder } => Ok(DecodeRule::Decoder(decoder)),
+        }
+    }
+}
+
+impl DecodeRule {
+    /// Returns whether the rule decodes messages with the schema of their type.
+    pub fn needs_schema(&self) -> bool {
+        matches!(self, DecodeRule::SkipBlobs)
+    }
+}
+
+/// Rule applied to the messages of a topic or of a message type.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DecoderOverride {
+    /// Name of the topic the rule applies to, such as `/camera/image_raw`.
+    #[serde(default)]
+    pub topic: Option<String>,
+    /// Name of the message type the rule applies to, such as `sensor_msgs/msg/PointCloud2`.
+    #[serde(default, rename = "type")]
+    pub type_name: Option<String>,
+    /// How the matching messages are handled.
+    pub rule: DecodeRule,
+}
+
+/// Settings read from the config file.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{Config, DecodeRule};
+///
+/// let config = Config::from_yaml(
+///     r#"
+/// overrides:
+///   - topic: /camera/image_raw
+///     rule: skip_blobs
+///   - type: my_msgs/msg/Packed
+///     rule:
+///       decoder: packed
+/// aliases:
+///   /sensing/radar/front_center/objects_raw: front_radar
+/// units:
+///   my_msgs/msg/VehicleStatus:
+///     speed: km/h
+/// "#,
+/// )
+/// .unwrap();
+/// assert_eq!(config.overrides[0].rule, DecodeRule::SkipBlobs);
+/// assert_eq!(config.aliases.alias("/sensing/radar/front_center/objects_raw"), Some("front_radar"));
+/// assert_eq!(config.overrides[1].rule, DecodeRule::Decoder("packed".to_string()));
+/// assert_eq!(config.units.unit("", "my_msgs/msg/VehicleStatus", "speed"), Some("km/h"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Per-topic and per-type decoding rules, where the first matching entry wins.
+    #[serde(default)]
+    pub overrides: Vec<DecoderOverride>,
+    /// Friendly names of topics, shown next to topic names and accepted in their place.
+    #[serde(default)]
+    pub aliases: TopicAliases,
+    /// Units of fields per topic or message type, labelling plots and summaries.
+    #[serde(default)]
+    pub units: FieldUnits,
+}
+
+impl Config {
+    /// Parses a config from YAML.
+    pub fn from_yaml(yaml: &str) -> RosPeekResult<Self> {
+        let config: Config = serde_yaml::from_str(yaml)?;
+        if let Some(entry) = config
+            .overrides
+            .iter()
+            .find(|o| o.topic.is_none() && o.type_name.is_none())
+        {
+            anyhow::bail!("Override needs a topic or a type: {:?}", entry.rule);
+        }
+        Ok(config)
+    }
+
+    /// Reads a config file.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the YAML config file.
+    pub fn load<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read config {}: {e}", path.display()))?;
+        Self::from_yaml(&yaml)
+            .map_err(|e| anyhow::anyhow!("Invalid config {}: {e}", path.display()))
+    }
+
+    /// Reads the config file named by [`CONFIG_ENV`], or else `rospeek/config.yaml` in the
+    /// user config directory if it exists.
+    ///
+    /// # Returns
+    /// The config read, or the default config if there is no config file.
+    pub fn discover() -> RosPeekResult<Self> {
+        if let Ok(path) = std::env::var(CONFIG_ENV) {
+            return Self::load(path);
+        }
+        match default_config_path() {
+            Some(path) if path.is_file() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Returns the rule applied to the messages of a topic, if any.
+    ///
+    /// # Arguments
+    /// * `topic` - Name of the topic.
+    /// * `type_name` - Name of the message type of the topic.
+    pub fn rule_for(&self, topic: &str, type_name: &str) -> Option<&DecodeRule> {
+        self.overrides
+            .iter()
+            .find(|o| {
+                o.topic.as_deref().is_none_or(|t| t == topic)
+                    && o.type_name.as_deref().is_none_or(|t| t == type_name)
+            })
+            .map(|o| &o.rule)
+    }
+}
+
+/// Returns `$XDG_CONFIG_HOME/rospeek/config.yaml`, falling back to `~/.config`.
+fn default_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("rospeek").join("config.yaml"))
+}
+
+/// Decoder of messages which are not CDR-encoded ROS messages, or need custom handling.
+pub trait MessageDecoder: Send + Sync {
+    /// Decodes the serialized data of a message into JSON.
+    fn decode(&self, data: &[u8]) -> RosPeekResult<Value>;
+}
+
+impl<F> MessageDecoder for F
+where
+    F: Fn(&[u8]) -> RosPeekResult<Value> + Send + Sync,
+{
+    fn decode(&self, data: &[u8]) -> RosPeekResult<Value> {
+        self(data)
+    }
+}
+
+/// Process-wide decoding overrides, holding the rules of the config and the custom decoders
+/// they refer to.
+///
+/// Decoding functions such as [`crate::try_decode_json`] honor the rules of the registry, so
+/// that they apply the same way to every command.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{DecodeRule, DecoderOverride, DecoderRegistry, RosPeekResult};
+/// use serde_json::{Value, json};
+///
+/// let registry = DecoderRegistry::global();
+/// registry.register("length", |data: &[u8]| -> RosPeekResult<Value> {
+///     Ok(json!({ "length": data.len() }))
+/// });
+/// registry.set_overrides(vec![DecoderOverride {
+///     topic: Some("/packed".to_string()),
+///     type_name: None,
+///     rule: DecodeRule::Decoder("length".to_string()),
+/// }]);
+/// assert_eq!(
+///     registry.rule_for("/packed", "my_msgs/msg/Packed"),
+///     Some(DecodeRule::Decoder("length".to_string()))
+/// );
+/// ```
+#[derive(Default)]
+pub struct DecoderRegistry {
+    config: RwLock<Config>,
+    decoders: RwLock<HashMap<String, Arc<dyn MessageDecoder>>>,
+}
+
+impl DecoderRegistry {
+    /// Returns the registry of the process.
+    pub fn global() -> &'static DecoderRegistry {
+        static REGISTRY: OnceLock<DecoderRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(DecoderRegistry::default)
+    }
+
+    /// Registers a custom decoder, which rules refer to by name.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the decoder, replacing any decoder registered under it.
+    /// * `decoder` - The decoder.
+    pub fn register<D: MessageDecoder + 'static>(&self, name: &str, decoder: D) {
+        self.decoders
+            .write()
+            .unwrap()
+            .insert(name.to_string(), Arc::new(decoder));
+    }
+
+    /// Returns the custom decoder registered under a name.
+    pub fn decoder(&self, name: &str) -> Option<Arc<dyn MessageDecoder>> {
+        self.decoders.read().unwrap().get(name).cloned()
+    }
+
+    /// Replaces the rules applied to topics, usually with the overrides of the config file.
+    pub fn set_overrides(&self, overrides: Vec<DecoderOverride>) {
+        self.config.write().unwrap().overrides = overrides;
+    }
+
+    /// Returns the rule applied to the messages of a topic, if any.
+    ///
+    /// # Arguments
+    /// * `topic` - Name of the topic.
+    /// * `type_name` - Name of the message type of the topic.
+    pub fn rule_for(&self, topic: &str, type_name: &str) -> Option<DecodeRule> {
+        self.config
+            .read()
+            .unwrap()
+            .rule_for(topic, type_name)
+            .cloned()
+    }
+}
+
+/// Decodes a message following a rule, or with a plan if there is no rule.
+///
+/// # Arguments
+/// * `rule` - The rule applied to the topic of the message, if any.
+/// * `decoder` - The decoder to reuse for CDR-encoded messages.
+/// * `plan` - Plan of the message type, which only rules decoding CDR need.
+/// * `data` - Serialized data of the message.
+pub fn decode_with_rule<'a>(
+    rule: Option<&DecodeRule>,
+    decoder: &mut CdrDecoder<'a>,
+    plan: Option<&DecodePlan>,
+    data: &'a [u8],
+) -> RosPeekResult<Value> {
+    match rule {
+        Some(DecodeRule::Bytes) => Ok(bytes_value(data)),
+        Some(DecodeRule::Decoder(name)) => DecoderRegistry::global()
+            .decoder(name)
+            .ok_or_else(|| anyhow::anyhow!("Decoder not registered: {name}"))?
+            .decode(data),
+        rule => {
+            let plan = plan.ok_or_else(|| anyhow::anyhow!("Failed to decode binary: no schema"))?;
+            decoder
+                .reset(data)
+                .skip_blobs(rule == Some(&DecodeRule::SkipBlobs))
+                .decode_plan(plan)
+        }
+    }
+}
+
+/// Represents undecoded message data as JSON, with its size and its bytes in hex.
+fn bytes_value(data: &[u8]) -> Value {
+    let hex: String = data.iter().map(|b| format!("{b:02x}")).collect();
+    json!({ "size": data.len(), "hex": hex })
+}
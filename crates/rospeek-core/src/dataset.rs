@@ -0,0 +1,153 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{RosPeekResult, SampleOptions};
+
+/// Folder structure of an exported dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetLayout {
+    /// Numbered frames per sensor directory with a calibration file per frame, as KITTI.
+    Kitti,
+    /// Sensor data under `samples/` described by JSON tables, as nuScenes.
+    Nuscenes,
+}
+
+/// Lidar of a dataset, whose frames set the timestamps of the samples.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LidarConfig {
+    /// Topic of `sensor_msgs/msg/PointCloud2` messages.
+    pub topic: String,
+    /// Directory or channel name, `velodyne` for KITTI and `LIDAR_TOP` for nuScenes by default.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Camera of a dataset.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CameraConfig {
+    /// Directory or channel name, such as `image_2` for KITTI or `CAM_FRONT` for nuScenes.
+    pub name: String,
+    /// Topic of `sensor_msgs/msg/Image` or `sensor_msgs/msg/CompressedImage` messages.
+    pub topic: String,
+    /// Topic of `sensor_msgs/msg/CameraInfo` messages calibrating the camera.
+    #[serde(default)]
+    pub camera_info: Option<String>,
+    /// Name of an MCAP attachment holding a `camera_calibration` file, used when the bag has
+    /// no camera info topic.
+    #[serde(default)]
+    pub calibration: Option<String>,
+}
+
+/// Mapping of the topics of a bag into a dataset, read from YAML.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{DatasetConfig, DatasetLayout};
+///
+/// let config = DatasetConfig::from_yaml(
+///     r#"
+/// layout: kitti
+/// lidar:
+///   topic: /sensing/lidar/points
+/// cameras:
+///   - name: image_2
+///     topic: /sensing/camera/front/image_raw
+///     camera_info: /sensing/camera/front/camera_info
+/// pose: /localization/pose
+/// stride: 5
+/// "#,
+/// )
+/// .unwrap();
+/// assert_eq!(config.layout, DatasetLayout::Kitti);
+/// assert_eq!(config.lidar_name(), "velodyne");
+/// assert_eq!(config.topics().len(), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DatasetConfig {
+    pub layout: DatasetLayout,
+    pub lidar: LidarConfig,
+    #[serde(default)]
+    pub cameras: Vec<CameraConfig>,
+    /// Topic of the ego pose, such as `geometry_msgs/msg/PoseStamped` or `nav_msgs/msg/Odometry`.
+    #[serde(default)]
+    pub pose: Option<String>,
+    /// Takes every n-th lidar frame.
+    #[serde(default = "default_stride")]
+    pub stride: usize,
+    /// Maximum time difference in seconds between a lidar frame and the messages of its sample.
+    #[serde(default)]
+    pub max_offset: Option<f64>,
+    /// Transform from the lidar to the first camera as a row-major 3x4 matrix, written as
+    /// `Tr_velo_to_cam` in KITTI calibration files.
+    #[serde(default)]
+    pub lidar_to_camera: Option<[f64; 12]>,
+    /// Leaf size in meters of the voxel grid downsampling the lidar frames.
+    #[serde(default)]
+    pub voxel_size: Option<f64>,
+    /// Undistorts the camera images with their calibration, so that they are described by the
+    /// projection matrix alone.
+    #[serde(default)]
+    pub undistort: bool,
+}
+
+fn default_stride() -> usize {
+    1
+}
+
+impl DatasetConfig {
+    /// Parses a configuration from YAML.
+    pub fn from_yaml(yaml: &str) -> RosPeekResult<Self> {
+        let config: Self = serde_yaml::from_str(yaml)?;
+        if let Some(camera) = config
+            .cameras
+            .iter()
+            .find(|c| c.camera_info.is_none() && c.calibration.is_none())
+        {
+            anyhow::bail!(
+                "Camera {} needs a camera_info topic or a calibration attachment",
+                camera.name
+            );
+        }
+        Ok(config)
+    }
+
+    /// Reads a configuration file.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the YAML configuration file.
+    pub fn load<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("Failed to read dataset config {}: {e}", path.display())
+        })?;
+        Self::from_yaml(&yaml)
+            .map_err(|e| anyhow::anyhow!("Invalid dataset config {}: {e}", path.display()))
+    }
+
+    /// Returns the directory or channel name of the lidar.
+    pub fn lidar_name(&self) -> &str {
+        match (&self.lidar.name, self.layout) {
+            (Some(name), _) => name,
+            (None, DatasetLayout::Kitti) => "velodyne",
+            (None, DatasetLayout::Nuscenes) => "LIDAR_TOP",
+        }
+    }
+
+    /// Returns the topics of a sample: the lidar, the cameras and then the pose if any.
+    pub fn topics(&self) -> Vec<String> {
+        std::iter::once(self.lidar.topic.clone())
+            .chain(self.cameras.iter().map(|c| c.topic.clone()))
+            .chain(self.pose.clone())
+            .collect()
+    }
+
+    /// Returns the options sampling the topics at the lidar frames.
+    pub fn sample_options(&self) -> SampleOptions {
+        SampleOptions {
+            stride: self.stride,
+            max_offset_ns: self.max_offset.map(|sec| (sec * 1e9) as u64),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Context, anyhow, bail};
+use serde_json::Value;
+
+use crate::{CdrWriter, Endianness, FieldType, MessageSchema, RosPeekResult};
+
+/// Encodes JSON values into CDR, the inverse of [`crate::CdrDecoder::decode`].
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{CdrDecoder, CdrEncoder, Endianness, parse_idl_str};
+/// use serde_json::json;
+///
+/// let idl = "module foo_msgs {\n  module msg {\n    struct Foo {\n      uint8 id;\n      double x;\n    };\n  };\n};";
+/// let schema = parse_idl_str(idl, "foo_msgs/msg/Foo");
+/// let value = json!({"id": 1, "x": 2.5});
+///
+/// let data = CdrEncoder::new(Endianness::Little).encode(&schema, &value).unwrap();
+/// assert_eq!(CdrDecoder::new(&data).decode(&schema).unwrap(), value);
+/// ```
+pub struct CdrEncoder {
+    writer: CdrWriter,
+    cache: HashMap<String, Arc<MessageSchema>>,
+}
+
+impl CdrEncoder {
+    /// Creates a new encoder.
+    ///
+    /// # Arguments
+    /// * `endianness` - Byte order of the encoded data.
+    pub fn new(endianness: Endianness) -> Self {
+        Self {
+            writer: CdrWriter::new(endianness),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Provides the schema of a nested type, so that it is not looked up.
+    ///
+    /// # Arguments
+    /// * `schema` - ROS message schema
+    pub fn with_schema(mut self, schema: &MessageSchema) -> Self {
+        self.cache
+            .insert(schema.type_name.clone(), Arc::new(schema.clone()));
+        self
+    }
+
+    /// Encodes a message.
+    ///
+    /// # Arguments
+    /// * `schema` - ROS message schema
+    /// * `value` - JSON object with a value for every field of the schema
+    ///
+    /// # Returns
+    /// The CDR-encoded data, including the encapsulation header.
+    pub fn encode(mut self, schema: &MessageSchema, value: &Value) -> RosPeekResult<Vec<u8>> {
+        self.encode_struct(schema, value)?;
+        Ok(self.writer.into_bytes())
+    }
+
+    fn encode_struct(&mut self, schema: &MessageSchema, value: &Value) -> RosPeekResult<()> {
+        let object = value
+            .as_object()
+            .with_context(|| format!("Expected an object for {}", schema.type_name))?;
+        for field in schema.fields.iter() {
+            let item = object
+                .get(&field.name)
+                .with_context(|| format!("Missing field {} of {}", field.name, schema.type_name))?;
+            match &field.field_type {
+                FieldType::Object(type_name) => self.encode_value(type_name, item)?,
+                FieldType::Array(type_name, length) => {
+                    let items = as_array(item, &field.name)?;
+                    if items.len() != *length {
+                        bail!(
+                            "Expected {length} items in {}, found {}",
+                            field.name,
+                            items.len()
+                        );
+                    }
+                    for item in items {
+                        self.encode_value(type_name, item)?;
+                    }
+                }
+                FieldType::Sequence(type_name) => {
+                    let items = as_array(item, &field.name)?;
+                    self.writer.write_u32(items.len() as u32);
+                    for item in items {
+                        self.encode_value(type_name, item)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_value(&mut self, type_name: &str, value: &Value) -> RosPeekResult<()> {
+        let writer = &mut self.writer;
+        match type_name {
+            "boolean" => writer.write_bool(value.as_bool().context("Expected a boolean")?),
+            "octet" | "uint8" => writer.write_u8(unsigned(value)?.try_into()?),
+            "char" => writer.write_char(
+                value
+                    .as_str()
+                    .and_then(|s| s.chars().next())
+                    .context("Expected a character")?,
+            ),
+            "float" => writer.write_f32(float(value)? as f32),
+            "double" => writer.write_f64(float(value)?),
+            "int8" => writer.write_i8(signed(value)?.try_into()?),
+            "int16" => writer.write_i16(signed(value)?.try_into()?),
+            "uint16" => writer.write_u16(unsigned(value)?.try_into()?),
+            "int32" => writer.write_i32(signed(value)?.try_into()?),
+            "uint32" => writer.write_u32(unsigned(value)?.try_into()?),
+            "int64" => writer.write_i64(signed(value)?),
+            "uint64" => writer.write_u64(unsigned(value)?),
+            "string" => writer.write_str(value.as_str().context("Expected a string")?),
+            "builtin_interfaces/msg/Time" | "builtin_interfaces/msg/Duration" => {
+                writer.write_i32(signed(&value["sec"])?.try_into()?);
+                writer.write_u32(unsigned(&value["nanosec"])?.try_into()?);
+            }
+            _ => {
+                let schema = self.get_schema(type_name)?;
+                self.encode_struct(&schema, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_schema(&mut self, type_name: &str) -> RosPeekResult<Arc<MessageSchema>> {
+        if !self.cache.contains_key(type_name) {
+            let schema = Arc::new(MessageSchema::try_from(type_name)?);
+            self.cache.insert(type_name.to_string(), schema);
+        }
+        Ok(self.cache.get(type_name).unwrap().clone())
+    }
+}
+
+fn as_array<'v>(value: &'v Value, name: &str) -> RosPeekResult<&'v Vec<Value>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("Expected an array for {name}"))
+}
+
+fn signed(value: &Value) -> RosPeekResult<i64> {
+    value
+        .as_i64()
+        .ok_or_else(|| anyhow!("Expected a signed integer, found {value}"))
+}
+
+fn unsigned(value: &Value) -> RosPeekResult<u64> {
+    value
+        .as_u64()
+        .ok_or_else(|| anyhow!("Expected an unsigned integer, found {value}"))
+}
+
+fn float(value: &Value) -> RosPeekResult<f64> {
+    value
+        .as_f64()
+        .ok_or_else(|| anyhow!("Expected a number, found {value}"))
+}
@@ -0,0 +1,262 @@
+use serde_json::Value;
+
+#[cfg(feature = "csv")]
+use crate::flatten_json;
+use crate::{
+    BagReader, CdrDecoder, DecodePlan, DecodeRule, DecoderRegistry, MessageSchema, RawMessage,
+    RosPeekResult, Topic, decode_with_rule, lookup_field,
+};
+
+/// Number of messages read from each topic to estimate its output.
+const ESTIMATE_SAMPLES: u64 = 32;
+
+/// Format of an output whose size is estimated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EstimateFormat {
+    /// Messages copied as recorded into a bag.
+    Bag,
+    /// Decoded messages in a pretty-printed JSON array.
+    Json,
+    /// Decoded messages flattened into CSV rows.
+    #[cfg(feature = "csv")]
+    Csv,
+}
+
+/// Estimated output of a topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicEstimate {
+    pub name: String,
+    pub type_name: String,
+    /// Number of messages in the selected time range.
+    pub messages: u64,
+    /// Size in bytes of the messages as recorded.
+    pub raw_bytes: u64,
+    /// Size in bytes of the messages in the output format.
+    pub output_bytes: u64,
+}
+
+impl TopicEstimate {
+    /// Returns how many times larger the output is than the messages as recorded.
+    pub fn expansion(&self) -> Option<f64> {
+        (self.raw_bytes > 0).then(|| self.output_bytes as f64 / self.raw_bytes as f64)
+    }
+
+    /// Keeps the messages of a page, such as those of a dump with an offset and a limit,
+    /// scaling the sizes along.
+    ///
+    /// # Arguments
+    /// * `offset` - Number of messages skipped.
+    /// * `limit` - Maximum number of messages kept.
+    pub fn page(&mut self, offset: Option<usize>, limit: Option<usize>) {
+        let kept = self
+            .messages
+            .saturating_sub(offset.unwrap_or_default() as u64)
+            .min(limit.map_or(u64::MAX, |limit| limit as u64));
+        if self.messages > 0 {
+            let ratio = kept as f64 / self.messages as f64;
+            self.raw_bytes = (self.raw_bytes as f64 * ratio).round() as u64;
+            self.output_bytes = (self.output_bytes as f64 * ratio).round() as u64;
+        }
+        self.messages = kept;
+    }
+}
+
+/// Estimated output of an export, topic by topic.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportEstimate {
+    pub topics: Vec<TopicEstimate>,
+}
+
+impl ExportEstimate {
+    /// Returns the total number of messages.
+    pub fn total_messages(&self) -> u64 {
+        self.topics.iter().map(|t| t.messages).sum()
+    }
+
+    /// Returns the total size in bytes of the output.
+    pub fn total_bytes(&self) -> u64 {
+        self.topics.iter().map(|t| t.output_bytes).sum()
+    }
+}
+
+/// Estimates the number of messages and the output size of an export without reading the
+/// topics in full, such as to check disk space before a long export.
+///
+/// A few messages spread over each topic are read by index. Their timestamps give the share of
+/// the topic within the time range, and their sizes the average size of a record, once decoded
+/// with the schema of the topic for JSON and CSV outputs.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topics` - Topic names to export, or all topics if empty.
+/// * `since` - Timestamp in nanoseconds since which messages are exported (inclusive).
+/// * `until` - Timestamp in nanoseconds until which messages are exported (inclusive).
+/// * `format` - Format of the output.
+/// * `fields` - Dot-separated paths of the fields kept in JSON and CSV records, or empty to keep
+///   whole messages.
+pub fn estimate_export(
+    reader: &dyn BagReader,
+    topics: &[String],
+    since: Option<u64>,
+    until: Option<u64>,
+    format: EstimateFormat,
+    fields: &[String],
+) -> RosPeekResult<ExportEstimate> {
+    let all = reader.topics()?;
+    if let Some(missing) = topics
+        .iter()
+        .find(|name| !all.iter().any(|t| &t.name == *name))
+    {
+        anyhow::bail!("Topic not found: {missing}");
+    }
+
+    let mut estimates = Vec::new();
+    for topic in all
+        .iter()
+        .filter(|t| topics.is_empty() || topics.contains(&t.name))
+    {
+        let samples = sample_indices(topic.count)
+            .into_iter()
+            .map(|index| {
+                let message = reader.read_messages_by_index(&topic.name, index as usize, 1)?;
+                Ok(message.into_iter().next().map(|message| (index, message)))
+            })
+            .filter_map(|sample| sample.transpose())
+            .collect::<RosPeekResult<Vec<_>>>()?;
+        if samples.is_empty() {
+            estimates.push(TopicEstimate {
+                name: topic.name.clone(),
+                type_name: topic.type_name.clone(),
+                messages: 0,
+                raw_bytes: 0,
+                output_bytes: 0,
+            });
+            continue;
+        }
+
+        let start = since.map_or(0.0, |since| index_at(&samples, topic.count, since));
+        let end = until.map_or(topic.count as f64, |until| {
+            index_at(&samples, topic.count, until.saturating_add(1))
+        });
+        let messages = (end - start).max(0.0).round() as u64;
+
+        // messages within the range are sized, or the nearest ones if none was sampled
+        let in_range: Vec<&RawMessage> = samples
+            .iter()
+            .map(|(_, message)| message)
+            .filter(|m| {
+                since.is_none_or(|s| m.timestamp >= s) && until.is_none_or(|u| m.timestamp <= u)
+            })
+            .collect();
+        let sized = match in_range.is_empty() {
+            true => samples.iter().map(|(_, message)| message).collect(),
+            false => in_range,
+        };
+        let raw_size =
+            sized.iter().map(|m| m.data.len()).sum::<usize>() as f64 / sized.len() as f64;
+        let sizes = record_sizes(topic, &sized, format, fields)?;
+        let output_size = sizes.iter().sum::<usize>() as f64 / sizes.len() as f64;
+        estimates.push(TopicEstimate {
+            name: topic.name.clone(),
+            type_name: topic.type_name.clone(),
+            messages,
+            raw_bytes: (raw_size * messages as f64).round() as u64,
+            output_bytes: (output_size * messages as f64).round() as u64,
+        });
+    }
+    Ok(ExportEstimate { topics: estimates })
+}
+
+/// Returns the indices of the sampled messages of a topic, spread evenly from the first message
+/// to the last one.
+fn sample_indices(count: u64) -> Vec<u64> {
+    match ESTIMATE_SAMPLES.min(count) {
+        0 => Vec::new(),
+        1 => vec![0],
+        n => (0..n).map(|i| i * (count - 1) / (n - 1)).collect(),
+    }
+}
+
+/// Estimates the index of the first message at or after a timestamp, interpolating linearly
+/// between the sampled messages.
+fn index_at(samples: &[(u64, RawMessage)], count: u64, timestamp: u64) -> f64 {
+    if samples
+        .first()
+        .is_none_or(|(_, m)| timestamp <= m.timestamp)
+    {
+        return 0.0;
+    }
+    for pair in samples.windows(2) {
+        let ((i0, m0), (i1, m1)) = (&pair[0], &pair[1]);
+        if m0.timestamp < timestamp && timestamp <= m1.timestamp {
+            let ratio = (timestamp - m0.timestamp) as f64 / (m1.timestamp - m0.timestamp) as f64;
+            // the sample at i0 is before the timestamp, so the message is one of those after it
+            return (*i0 as f64 + ratio * (i1 - i0) as f64).max(*i0 as f64 + 1.0);
+        }
+    }
+    count as f64
+}
+
+/// Returns the size in bytes of each message once decoded into a record of the format.
+fn record_sizes(
+    topic: &Topic,
+    messages: &[&RawMessage],
+    format: EstimateFormat,
+    fields: &[String],
+) -> RosPeekResult<Vec<usize>> {
+    if format == EstimateFormat::Bag {
+        return Ok(messages.iter().map(|m| m.data.len()).collect());
+    }
+    let rule = DecoderRegistry::global().rule_for(&topic.name, &topic.type_name);
+    let schema = match rule.as_ref().is_none_or(DecodeRule::needs_schema) {
+        true => Some(MessageSchema::try_from(topic.type_name.as_str())?),
+        false => None,
+    };
+    let plan = schema.as_ref().map(DecodePlan::compile).transpose()?;
+
+    messages
+        .iter()
+        .map(|message| {
+            let mut decoder = schema
+                .as_ref()
+                .map_or_else(|| CdrDecoder::new(&[]), CdrDecoder::from_schema);
+            let mut value =
+                decode_with_rule(rule.as_ref(), &mut decoder, plan.as_ref(), &message.data)?;
+            if !fields.is_empty() {
+                value = Value::Object(
+                    fields
+                        .iter()
+                        .map(|f| {
+                            (
+                                f.clone(),
+                                lookup_field(&value, f).cloned().unwrap_or_default(),
+                            )
+                        })
+                        .collect(),
+                );
+            }
+            record_size(&value, format)
+        })
+        .collect()
+}
+
+/// Returns the size in bytes of a record written in a format.
+fn record_size(value: &Value, format: EstimateFormat) -> RosPeekResult<usize> {
+    Ok(match format {
+        EstimateFormat::Bag => unreachable!("messages are copied into bags without being decoded"),
+        EstimateFormat::Json => {
+            // items of a pretty-printed array are indented by two spaces and separated by ",\n"
+            let json = serde_json::to_string_pretty(value)?;
+            json.len() + 2 * (json.matches('\n').count() + 1) + 2
+        }
+        #[cfg(feature = "csv")]
+        EstimateFormat::Csv => {
+            let row = match value {
+                Value::Object(object) => flatten_json(object)?,
+                other => serde_json::Map::from_iter([("value".to_string(), other.clone())]),
+            };
+            // a comma or a newline after each value
+            row.values().map(|v| v.to_string().len() + 1).sum()
+        }
+    })
+}
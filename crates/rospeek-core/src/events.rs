@@ -0,0 +1,327 @@
+use std::{fs::File, path::Path, str::FromStr};
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{BagReader, CdrDecoder, MessageSchema, RosPeekResult};
+
+/// Comparison of a field value against the threshold of a [`Condition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    /// Operators in the order they are looked up, two-character ones first.
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        (">=", Comparison::GreaterEqual),
+        ("<=", Comparison::LessEqual),
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+    ];
+
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterEqual => value >= threshold,
+            Comparison::Less => value < threshold,
+            Comparison::LessEqual => value <= threshold,
+            Comparison::Equal => value == threshold,
+            Comparison::NotEqual => value != threshold,
+        }
+    }
+}
+
+/// Condition on a field of a topic, written as `<topic>.<field> <operator> <threshold>`, where
+/// the operator is one of `>`, `>=`, `<`, `<=`, `==` and `!=`, and booleans compare as `1` and
+/// `0`.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{Comparison, Condition};
+/// use serde_json::json;
+///
+/// let condition: Condition = "/brake.data > 0.9".parse().unwrap();
+/// assert_eq!(condition.topic, "/brake");
+/// assert_eq!(condition.field, "data");
+/// assert_eq!(condition.comparison, Comparison::Greater);
+/// assert!(condition.holds(&json!(0.95)));
+/// assert!(!condition.holds(&json!("0.95")));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    /// Name of the topic.
+    pub topic: String,
+    /// Dot-separated path of the field, such as `twist.linear.x`.
+    pub field: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl Condition {
+    /// Returns whether a value of the field satisfies the condition, `false` if it is neither a
+    /// number nor a boolean.
+    pub fn holds(&self, value: &Value) -> bool {
+        to_number(value).is_some_and(|value| self.comparison.holds(value, self.threshold))
+    }
+}
+
+impl FromStr for Condition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, operator, comparison) = Comparison::OPERATORS
+            .iter()
+            .filter_map(|(operator, comparison)| {
+                s.find(operator)
+                    .map(|position| (position, *operator, *comparison))
+            })
+            .min_by_key(|(position, _, _)| *position)
+            .ok_or_else(|| anyhow!("Missing comparison operator in condition: {s}"))?;
+        let path = s[..position].trim();
+        let threshold = s[position + operator.len()..].trim();
+
+        // topic names contain slashes, so the field starts at the first dot after the last one
+        let field_start = path
+            .rfind('/')
+            .and_then(|slash| path[slash..].find('.').map(|dot| slash + dot))
+            .ok_or_else(|| anyhow!("Expected `<topic>.<field>` in condition: {s}"))?;
+        let threshold = match threshold {
+            "true" => 1.0,
+            "false" => 0.0,
+            threshold => threshold
+                .parse()
+                .with_context(|| format!("Invalid threshold in condition: {s}"))?,
+        };
+
+        Ok(Self {
+            topic: path[..field_start].to_string(),
+            field: path[field_start + 1..].to_string(),
+            comparison,
+            threshold,
+        })
+    }
+}
+
+/// [`Condition`] on a field of a topic, labeling the intervals where it holds.
+///
+/// Rules are written as `<label>: <topic>.<field> <operator> <threshold>`.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{Comparison, EventRule};
+///
+/// let rule: EventRule = "brake: /vehicle/brake.data > 0.8".parse().unwrap();
+/// assert_eq!(rule.label, "brake");
+/// assert_eq!(rule.topic, "/vehicle/brake");
+/// assert_eq!(rule.field, "data");
+/// assert_eq!(rule.comparison, Comparison::Greater);
+/// assert_eq!(rule.threshold, 0.8);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRule {
+    /// Label of the intervals where the condition holds.
+    pub label: String,
+    /// Name of the topic.
+    pub topic: String,
+    /// Dot-separated path of the field, such as `twist.linear.x`.
+    pub field: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl FromStr for EventRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (label, condition) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Expected `<label>: <topic>.<field> <op> <value>`: {s}"))?;
+        let label = label.trim();
+        if label.is_empty() {
+            anyhow::bail!("Missing label in event rule: {s}");
+        }
+
+        let Condition {
+            topic,
+            field,
+            comparison,
+            threshold,
+        } = condition
+            .parse()
+            .with_context(|| format!("Invalid event rule: {s}"))?;
+        Ok(Self {
+            label: label.to_string(),
+            topic,
+            field,
+            comparison,
+            threshold,
+        })
+    }
+}
+
+/// Options of the event extraction.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EventOptions {
+    /// Margin by which a value must fall back past the threshold to end an event, so that
+    /// values jittering around the threshold do not split it. Ignored by `==` and `!=`.
+    pub hysteresis: f64,
+    /// Minimum duration of an event in nanoseconds, shorter ones being dropped.
+    pub min_duration_ns: u64,
+}
+
+/// Interval where the condition of an [`EventRule`] holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabeledEvent {
+    /// Label of the rule.
+    pub label: String,
+    /// Topic the condition is evaluated on.
+    pub topic: String,
+    /// Timestamp of the first message satisfying the condition in nanoseconds.
+    pub start_ns: u64,
+    /// Timestamp of the last message satisfying the condition in nanoseconds.
+    pub end_ns: u64,
+}
+
+/// Finds the intervals where a condition holds over samples of a field.
+///
+/// An event starts at the first sample satisfying the condition, and lasts until a sample does
+/// not satisfy it anymore with the threshold moved back by the hysteresis.
+///
+/// # Arguments
+/// * `samples` - Pairs of timestamp in nanoseconds and value, sorted by timestamp.
+/// * `comparison` - Comparison of the values against the threshold.
+/// * `threshold` - Threshold of the condition.
+/// * `options` - Hysteresis and minimum duration of the events.
+///
+/// # Returns
+/// The start and end timestamps of the events, in time order.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{Comparison, EventOptions, find_events};
+///
+/// let samples = [(0, 0.0), (10, 0.9), (20, 0.75), (30, 0.5), (40, 0.9)];
+/// let options = EventOptions { hysteresis: 0.1, ..Default::default() };
+/// assert_eq!(
+///     find_events(&samples, Comparison::Greater, 0.8, &options),
+///     vec![(10, 20), (40, 40)]
+/// );
+/// ```
+pub fn find_events(
+    samples: &[(u64, f64)],
+    comparison: Comparison,
+    threshold: f64,
+    options: &EventOptions,
+) -> Vec<(u64, u64)> {
+    let release = match comparison {
+        Comparison::Greater | Comparison::GreaterEqual => threshold - options.hysteresis,
+        Comparison::Less | Comparison::LessEqual => threshold + options.hysteresis,
+        Comparison::Equal | Comparison::NotEqual => threshold,
+    };
+
+    let mut events = Vec::new();
+    let mut active: Option<(u64, u64)> = None;
+    for &(timestamp, value) in samples {
+        active = match active {
+            Some((start, _)) if comparison.holds(value, release) => Some((start, timestamp)),
+            Some(event) => {
+                events.push(event);
+                None
+            }
+            None if comparison.holds(value, threshold) => Some((timestamp, timestamp)),
+            None => None,
+        };
+    }
+    events.extend(active);
+    events.retain(|(start, end)| end - start >= options.min_duration_ns);
+    events
+}
+
+/// Extracts the labeled intervals where the conditions of rules hold in a bag.
+///
+/// Messages whose field is missing or not a number are skipped.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `rules` - Rules labeling the intervals.
+/// * `options` - Hysteresis and minimum duration of the events.
+///
+/// # Returns
+/// The events of all rules ordered by start time.
+pub fn extract_events(
+    reader: &dyn BagReader,
+    rules: &[EventRule],
+    options: &EventOptions,
+) -> RosPeekResult<Vec<LabeledEvent>> {
+    let topics = reader.topics()?;
+    let mut events = Vec::new();
+    for rule in rules {
+        let topic = topics
+            .iter()
+            .find(|t| t.name == rule.topic)
+            .ok_or_else(|| anyhow!("Topic not found: {}", rule.topic))?;
+        let schema = MessageSchema::try_from(topic.type_name.as_ref())?;
+        let path: Vec<&str> = rule.field.split('.').filter(|s| !s.is_empty()).collect();
+
+        let messages = reader.read_messages(&rule.topic)?;
+        let mut decoder = CdrDecoder::from_schema(&schema);
+        let mut samples = Vec::new();
+        for message in messages.iter() {
+            let value = decoder.reset(&message.data).decode_path(&schema, &path)?;
+            if let Some(value) = value.as_ref().and_then(to_number) {
+                samples.push((message.timestamp, value));
+            }
+        }
+
+        events.extend(
+            find_events(&samples, rule.comparison, rule.threshold, options)
+                .into_iter()
+                .map(|(start_ns, end_ns)| LabeledEvent {
+                    label: rule.label.clone(),
+                    topic: rule.topic.clone(),
+                    start_ns,
+                    end_ns,
+                }),
+        );
+    }
+    events.sort_by_key(|e| (e.start_ns, e.end_ns));
+    Ok(events)
+}
+
+/// Loads events written as a JSON array by [`save_events`].
+///
+/// # Arguments
+/// * `path` - Path to the JSON file.
+pub fn load_events<P: AsRef<Path>>(path: P) -> RosPeekResult<Vec<LabeledEvent>> {
+    let file = File::open(path.as_ref())
+        .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Writes events as a pretty-printed JSON array, e.g. to be used as dataset annotations.
+///
+/// # Arguments
+/// * `events` - The events to write.
+/// * `path` - Path to the JSON file.
+pub fn save_events<P: AsRef<Path>>(events: &[LabeledEvent], path: P) -> RosPeekResult<()> {
+    let file = File::create(path.as_ref())
+        .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
+    serde_json::to_writer_pretty(file, events)?;
+    Ok(())
+}
+
+fn to_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        value => value.as_f64(),
+    }
+}
@@ -0,0 +1,164 @@
+use serde::Serialize;
+
+use crate::TopicStats;
+
+/// Interval without messages on a topic, much longer than the usual period of the topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Gap {
+    /// Timestamp of the message before the gap in nanoseconds.
+    pub start_ns: u64,
+    /// Timestamp of the message after the gap in nanoseconds.
+    pub end_ns: u64,
+}
+
+impl Gap {
+    /// Returns the length of the gap in seconds.
+    pub fn duration_sec(&self) -> f64 {
+        (self.end_ns - self.start_ns) as f64 / 1_000_000_000.0
+    }
+}
+
+/// Finds the gaps of a topic, i.e. intervals between consecutive messages longer than a factor of
+/// the median interval.
+///
+/// # Arguments
+/// * `timestamps` - Timestamps of the messages of the topic in nanoseconds, sorted.
+/// * `factor` - How many times the median interval an interval must exceed to be a gap.
+///
+/// # Returns
+/// The gaps in time order, empty with less than three messages.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{Gap, find_gaps};
+///
+/// let timestamps = [0, 100, 200, 300, 1300, 1400];
+/// assert_eq!(find_gaps(&timestamps, 3.0), vec![Gap { start_ns: 300, end_ns: 1300 }]);
+/// ```
+pub fn find_gaps(timestamps: &[u64], factor: f64) -> Vec<Gap> {
+    let intervals: Vec<u64> = timestamps.windows(2).map(|w| w[1] - w[0]).collect();
+    if intervals.len() < 2 {
+        return Vec::new();
+    }
+    let mut sorted = intervals.clone();
+    sorted.sort_unstable();
+    let median = sorted[sorted.len() / 2];
+    if median == 0 {
+        return Vec::new();
+    }
+
+    let threshold = median as f64 * factor;
+    timestamps
+        .windows(2)
+        .zip(intervals)
+        .filter(|(_, interval)| *interval as f64 > threshold)
+        .map(|(w, _)| Gap {
+            start_ns: w[0],
+            end_ns: w[1],
+        })
+        .collect()
+}
+
+/// Counts messages in equal time bins, e.g. to plot the rate of a topic over a recording.
+///
+/// # Arguments
+/// * `timestamps` - Timestamps of the messages in nanoseconds.
+/// * `start_ns` - Start of the first bin.
+/// * `end_ns` - End of the last bin, inclusive.
+/// * `bins` - Number of bins.
+///
+/// # Returns
+/// The number of messages of each bin, ignoring messages out of the range.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::count_in_bins;
+///
+/// assert_eq!(count_in_bins(&[0, 10, 60, 100], 0, 100, 2), vec![2, 2]);
+/// ```
+pub fn count_in_bins(timestamps: &[u64], start_ns: u64, end_ns: u64, bins: usize) -> Vec<u64> {
+    let mut counts = vec![0; bins];
+    if bins == 0 || end_ns < start_ns {
+        return counts;
+    }
+    let span = (end_ns - start_ns) as u128 + 1;
+    for &timestamp in timestamps {
+        if timestamp < start_ns || timestamp > end_ns {
+            continue;
+        }
+        let bin = (timestamp - start_ns) as u128 * bins as u128 / span;
+        counts[bin as usize] += 1;
+    }
+    counts
+}
+
+/// How many times its mean interval a topic may stop before the end of the bag or pause before
+/// being flagged.
+const HEALTH_FACTOR: f64 = 5.0;
+
+/// Shortest silence in nanoseconds flagged, so that jitter of fast topics is not.
+const MIN_SILENCE_NS: u64 = 1_000_000_000;
+
+/// Health of a topic in a recording, from heuristics on its message timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopicHealth {
+    /// Messages are published throughout the recording.
+    Healthy,
+    /// The topic has no messages.
+    Empty,
+    /// Messages stop well before the end of the bag.
+    Stale,
+    /// Messages pause much longer than their mean interval somewhere in the recording.
+    Sparse,
+}
+
+impl TopicHealth {
+    /// Assesses the health of a topic.
+    ///
+    /// Topics with a single message, such as latched `/tf_static`, are healthy.
+    ///
+    /// # Arguments
+    /// * `stats` - Statistics of the messages of the topic.
+    /// * `max_interval_ns` - Longest interval between consecutive messages of the topic.
+    /// * `bag_end_ns` - Timestamp of the last message of the bag.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_core::{RawMessage, TopicHealth, TopicStats};
+    ///
+    /// let mut stats = TopicStats::default();
+    /// for i in 0..10 {
+    ///     stats.accumulate(&RawMessage { timestamp: i * 100_000_000, topic_id: 1, data: vec![] });
+    /// }
+    /// assert_eq!(TopicHealth::assess(&stats, 100_000_000, 900_000_000), TopicHealth::Healthy);
+    /// assert_eq!(TopicHealth::assess(&stats, 100_000_000, 60_000_000_000), TopicHealth::Stale);
+    /// ```
+    pub fn assess(stats: &TopicStats, max_interval_ns: u64, bag_end_ns: u64) -> Self {
+        if stats.count == 0 {
+            return TopicHealth::Empty;
+        }
+        if stats.count < 2 || stats.last_ns <= stats.first_ns {
+            return TopicHealth::Healthy;
+        }
+        let mean_interval = (stats.last_ns - stats.first_ns) as f64 / (stats.count - 1) as f64;
+        let threshold = (mean_interval * HEALTH_FACTOR).max(MIN_SILENCE_NS as f64);
+        if bag_end_ns.saturating_sub(stats.last_ns) as f64 > threshold {
+            TopicHealth::Stale
+        } else if max_interval_ns as f64 > threshold {
+            TopicHealth::Sparse
+        } else {
+            TopicHealth::Healthy
+        }
+    }
+
+    /// Returns a short description of the health.
+    pub fn description(&self) -> &'static str {
+        match self {
+            TopicHealth::Healthy => "Published throughout the recording",
+            TopicHealth::Empty => "No messages",
+            TopicHealth::Stale => "Stops publishing before the end of the bag",
+            TopicHealth::Sparse => "Pauses much longer than its usual interval",
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt::{Display, Formatter};
+
+use crate::METADATA_FILE;
+
+/// Sign that a recording was interrupted or is still running, so that the bag may lack its last
+/// messages and report a shorter duration than was recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IncompleteSign {
+    /// An MCAP file ends without its footer, as when the recorder is still writing it or was
+    /// killed before closing it.
+    MissingFooter { file: String },
+    /// A `.db3` file has a rollback journal or an uncheckpointed write-ahead log next to it, as
+    /// when the recorder is still writing it or crashed mid-transaction.
+    PendingJournal { file: String, journal: String },
+    /// A storage file of a bag directory is not listed in its `metadata.yaml`, as the final
+    /// split of a recording which stopped before updating it.
+    UnlistedFile { file: String },
+    /// The duration in `metadata.yaml` differs from the one of the messages in the files.
+    DurationMismatch { metadata_sec: f64, files_sec: f64 },
+}
+
+impl Display for IncompleteSign {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IncompleteSign::MissingFooter { file } => {
+                write!(f, "{file} has no MCAP footer")
+            }
+            IncompleteSign::PendingJournal { file, journal } => {
+                write!(f, "{file} has a pending journal {journal}")
+            }
+            IncompleteSign::UnlistedFile { file } => {
+                write!(f, "{file} is not listed in {METADATA_FILE}")
+            }
+            IncompleteSign::DurationMismatch {
+                metadata_sec,
+                files_sec,
+            } => write!(
+                f,
+                "Duration is {metadata_sec:.3} s in {METADATA_FILE} but {files_sec:.3} s in files"
+            ),
+        }
+    }
+}
@@ -1,13 +1,156 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod aliases;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "std")]
+pub mod assertion;
+#[cfg(feature = "std")]
+pub mod bag_metadata;
+#[cfg(feature = "std")]
+pub mod camera;
+#[cfg(feature = "std")]
 pub mod cdr;
+pub mod cdr_reader;
+pub mod cdr_writer;
+#[cfg(feature = "std")]
+pub mod check;
+#[cfg(feature = "std")]
+pub mod clock;
+#[cfg(feature = "std")]
+pub mod compare;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod dataset;
+#[cfg(feature = "std")]
+pub mod encoder;
+#[cfg(feature = "std")]
+pub mod estimate;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+pub mod gaps;
+#[cfg(feature = "std")]
+pub mod incomplete;
+#[cfg(feature = "std")]
 pub mod model;
+#[cfg(feature = "std")]
+pub mod monitor;
+#[cfg(feature = "std")]
+pub mod orientation;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod plan;
+#[cfg(feature = "std")]
+pub mod pointcloud;
+#[cfg(feature = "std")]
+pub mod pose;
+#[cfg(feature = "std")]
+pub mod qos;
+#[cfg(feature = "std")]
 pub mod reader;
+#[cfg(feature = "std")]
+pub mod redact;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod samples;
+#[cfg(feature = "std")]
 pub mod schema;
+#[cfg(feature = "std")]
+pub mod sort;
+#[cfg(feature = "std")]
+pub mod spectrum;
+#[cfg(feature = "std")]
+pub mod split;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod units;
+#[cfg(feature = "std")]
 pub mod utility;
+#[cfg(feature = "std")]
+pub mod writer;
 
+#[cfg(feature = "std")]
+pub use aliases::*;
+#[cfg(feature = "arrow")]
+pub use arrow::*;
+#[cfg(feature = "std")]
+pub use assertion::*;
+#[cfg(feature = "std")]
+pub use bag_metadata::*;
+#[cfg(feature = "std")]
+pub use camera::*;
+#[cfg(feature = "std")]
 pub use cdr::*;
+pub use cdr_reader::*;
+pub use cdr_writer::*;
+#[cfg(feature = "std")]
+pub use check::*;
+#[cfg(feature = "std")]
+pub use clock::*;
+#[cfg(feature = "std")]
+pub use compare::*;
+#[cfg(feature = "std")]
+pub use config::*;
+#[cfg(feature = "std")]
+pub use dataset::*;
+#[cfg(feature = "std")]
+pub use encoder::*;
+#[cfg(feature = "std")]
+pub use estimate::*;
+#[cfg(feature = "std")]
+pub use events::*;
+#[cfg(feature = "std")]
+pub use gaps::*;
+#[cfg(feature = "std")]
+pub use incomplete::*;
+#[cfg(feature = "std")]
 pub use model::*;
+#[cfg(feature = "std")]
+pub use monitor::*;
+#[cfg(feature = "std")]
+pub use orientation::*;
+#[cfg(feature = "std")]
+pub use pipeline::*;
+#[cfg(feature = "std")]
+pub use plan::*;
+#[cfg(feature = "std")]
+pub use pointcloud::*;
+#[cfg(feature = "std")]
+pub use pose::*;
+#[cfg(feature = "std")]
+pub use qos::*;
+#[cfg(feature = "std")]
 pub use reader::*;
+#[cfg(feature = "std")]
+pub use redact::*;
+#[cfg(feature = "std")]
+pub use report::*;
+#[cfg(feature = "std")]
+pub use samples::*;
+#[cfg(feature = "std")]
 pub use schema::*;
+#[cfg(feature = "std")]
+pub use sort::*;
+#[cfg(feature = "std")]
+pub use spectrum::*;
+#[cfg(feature = "std")]
+pub use split::*;
+#[cfg(feature = "std")]
+pub use stats::*;
+#[cfg(feature = "std")]
+pub use units::*;
+#[cfg(feature = "std")]
 pub use utility::*;
+#[cfg(feature = "std")]
+pub use writer::*;
 
+#[cfg(feature = "std")]
 pub type RosPeekResult<T> = anyhow::Result<T>;
@@ -1,3 +1,7 @@
+use std::sync::Arc;
+
+use crate::TypeName;
+
 #[derive(Debug, Clone)]
 pub struct Topic {
     pub id: u16,
@@ -8,6 +12,81 @@ pub struct Topic {
     pub offered_qos_profiles: Option<String>,
 }
 
+impl Topic {
+    /// Returns the package of the message type, such as `sensor_msgs` for
+    /// `sensor_msgs/msg/Image`.
+    pub fn package(&self) -> Option<&str> {
+        TypeName::parse(&self.type_name).map(|t| t.package)
+    }
+
+    /// Returns the name of the message type within its package, such as `Image` for
+    /// `sensor_msgs/msg/Image`.
+    pub fn message_name(&self) -> Option<&str> {
+        TypeName::parse(&self.type_name).map(|t| t.name)
+    }
+
+    /// Returns the namespace of the topic, such as `/sensing/lidar` for
+    /// `/sensing/lidar/points`, or `/` for a topic at the root.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_core::Topic;
+    ///
+    /// let mut topic = Topic {
+    ///     id: 1,
+    ///     name: "/sensing/lidar/points".to_string(),
+    ///     type_name: "sensor_msgs/msg/PointCloud2".to_string(),
+    ///     count: 0,
+    ///     serialization_format: "cdr".to_string(),
+    ///     offered_qos_profiles: None,
+    /// };
+    /// assert_eq!(topic.namespace(), "/sensing/lidar");
+    /// assert_eq!(topic.package(), Some("sensor_msgs"));
+    /// assert_eq!(topic.message_name(), Some("PointCloud2"));
+    ///
+    /// topic.name = "/points".to_string();
+    /// assert_eq!(topic.namespace(), "/");
+    /// ```
+    pub fn namespace(&self) -> &str {
+        match self.name.rsplit_once('/') {
+            Some((namespace, _)) if !namespace.is_empty() => namespace,
+            _ => "/",
+        }
+    }
+
+    /// Returns the namespace of the topic cut to its first `depth` levels, such as `/sensing` at
+    /// depth 1 for `/sensing/lidar/points`.
+    ///
+    /// # Arguments
+    /// * `depth` - Number of levels kept, where 0 keeps only the root namespace `/`.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_core::Topic;
+    ///
+    /// let topic = Topic {
+    ///     id: 1,
+    ///     name: "/sensing/lidar/top/points".to_string(),
+    ///     type_name: "sensor_msgs/msg/PointCloud2".to_string(),
+    ///     count: 0,
+    ///     serialization_format: "cdr".to_string(),
+    ///     offered_qos_profiles: None,
+    /// };
+    /// assert_eq!(topic.namespace_at(0), "/");
+    /// assert_eq!(topic.namespace_at(1), "/sensing");
+    /// assert_eq!(topic.namespace_at(2), "/sensing/lidar");
+    /// assert_eq!(topic.namespace_at(5), "/sensing/lidar/top");
+    /// ```
+    pub fn namespace_at(&self, depth: usize) -> &str {
+        let namespace = self.namespace();
+        match namespace.match_indices('/').nth(depth) {
+            Some((0, _)) => "/",
+            Some((end, _)) => &namespace[..end],
+            None => namespace,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RawMessage {
     /// UNIX epoch nanoseconds
@@ -17,3 +96,57 @@ pub struct RawMessage {
     /// CDR-encoded message
     pub data: Vec<u8>,
 }
+
+/// Message of a stream of several topics, tagged with the name of its topic.
+#[derive(Debug, Clone)]
+pub struct TaggedMessage {
+    /// Name of the topic, shared by the messages of the topic.
+    pub topic: Arc<str>,
+    pub message: RawMessage,
+}
+
+/// Message definition embedded in a bag file.
+#[derive(Debug, Clone)]
+pub struct EmbeddedSchema {
+    /// Name of ROS message type, such as `foo_msgs/msg/Foo`.
+    pub name: String,
+    /// Encoding of the definition, such as `ros2idl` or `ros2msg`.
+    pub encoding: String,
+    /// Definition data.
+    pub data: Vec<u8>,
+}
+
+/// Type of a topic over a range of the files of a bag.
+#[derive(Debug, Clone)]
+pub struct TypeVersion {
+    /// Name of ROS message type, such as `foo_msgs/msg/Foo`.
+    pub type_name: String,
+    /// Message definition embedded in the files, if any.
+    pub definition: Option<EmbeddedSchema>,
+    /// Paths of the files recorded with this version.
+    pub files: Vec<String>,
+    /// Number of messages recorded with this version.
+    pub count: u64,
+    /// UNIX epoch nanoseconds of the first message of the files.
+    pub start_ns: u64,
+    /// UNIX epoch nanoseconds of the last message of the files.
+    pub end_ns: u64,
+}
+
+impl TypeVersion {
+    /// Returns the version a message recorded at a given timestamp was recorded with.
+    ///
+    /// # Arguments
+    /// * `versions` - Type versions of a topic, ordered by time.
+    /// * `timestamp` - UNIX epoch nanoseconds of the message.
+    ///
+    /// # Returns
+    /// The latest version starting at or before the timestamp, or the first one otherwise.
+    pub fn at(versions: &[TypeVersion], timestamp: u64) -> Option<&TypeVersion> {
+        versions
+            .iter()
+            .rev()
+            .find(|v| v.start_ns <= timestamp)
+            .or_else(|| versions.first())
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::RosPeekResult;
+
+/// Counters of the decoding of a topic, updated by decoding workers.
+#[derive(Debug)]
+pub struct DecodeProgress {
+    /// Name of the topic being decoded.
+    pub topic: String,
+    queued: AtomicU64,
+    decoded: AtomicU64,
+    bytes: AtomicU64,
+    busy_ns: AtomicU64,
+    started: Instant,
+}
+
+impl DecodeProgress {
+    fn new(topic: &str) -> Self {
+        Self {
+            topic: topic.to_string(),
+            queued: AtomicU64::new(0),
+            decoded: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            busy_ns: AtomicU64::new(0),
+            started: Instant::now(),
+        }
+    }
+
+    /// Records a decoded message.
+    ///
+    /// # Arguments
+    /// * `bytes` - Size of the message in bytes.
+    /// * `busy` - Time a worker spent decoding it.
+    pub fn record(&self, bytes: usize, busy: Duration) {
+        self.decoded.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.busy_ns
+            .fetch_add(busy.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Returns a consistent-enough copy of the counters for display.
+    pub fn snapshot(&self) -> DecodeSnapshot {
+        DecodeSnapshot {
+            topic: self.topic.clone(),
+            queued: self.queued.load(Ordering::Relaxed),
+            decoded: self.decoded.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            busy: Duration::from_nanos(self.busy_ns.load(Ordering::Relaxed)),
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
+
+/// Counters of the decoding of a topic at a point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeSnapshot {
+    /// Name of the topic being decoded.
+    pub topic: String,
+    /// Number of messages handed to the decoding workers.
+    pub queued: u64,
+    /// Number of decoded messages.
+    pub decoded: u64,
+    /// Total size of the decoded messages in bytes.
+    pub bytes: u64,
+    /// Total time workers spent decoding.
+    pub busy: Duration,
+    /// Time since decoding of the topic started.
+    pub elapsed: Duration,
+}
+
+impl DecodeSnapshot {
+    /// Returns the number of messages waiting to be decoded.
+    pub fn pending(&self) -> u64 {
+        self.queued.saturating_sub(self.decoded)
+    }
+
+    /// Returns the number of decoded messages per second.
+    pub fn messages_per_sec(&self) -> f64 {
+        per_sec(self.decoded as f64, self.elapsed)
+    }
+
+    /// Returns the number of decoded bytes per second.
+    pub fn bytes_per_sec(&self) -> f64 {
+        per_sec(self.bytes as f64, self.elapsed)
+    }
+
+    /// Returns the average number of workers busy decoding since the topic started.
+    pub fn busy_workers(&self) -> f64 {
+        per_sec(self.busy.as_secs_f64(), self.elapsed)
+    }
+}
+
+fn per_sec(value: f64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 { value / secs } else { 0.0 }
+}
+
+/// Process-wide registry of decode progress, which diagnostic views poll while decoding runs.
+///
+/// Monitoring is disabled by default, so that decoding pays no bookkeeping cost.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::DecodeMonitor;
+///
+/// let monitor = DecodeMonitor::global();
+/// monitor.enable();
+/// for snapshot in monitor.snapshots() {
+///     println!("{}: {:.0} msg/s", snapshot.topic, snapshot.messages_per_sec());
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct DecodeMonitor {
+    enabled: AtomicBool,
+    topics: Mutex<Vec<Arc<DecodeProgress>>>,
+}
+
+impl DecodeMonitor {
+    /// Returns the monitor of the process.
+    pub fn global() -> &'static DecodeMonitor {
+        static MONITOR: OnceLock<DecodeMonitor> = OnceLock::new();
+        MONITOR.get_or_init(DecodeMonitor::default)
+    }
+
+    /// Starts recording decode progress.
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether decode progress is recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Registers messages of a topic about to be decoded.
+    ///
+    /// # Arguments
+    /// * `topic` - Name of the topic.
+    /// * `count` - Number of messages handed to the decoding workers.
+    ///
+    /// # Returns
+    /// The progress of the topic to record decoded messages into, or `None` while disabled.
+    pub fn start(&self, topic: &str, count: usize) -> Option<Arc<DecodeProgress>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut topics = self.topics.lock().unwrap();
+        let progress = match topics.iter().find(|p| p.topic == topic) {
+            Some(progress) => progress.clone(),
+            None => {
+                let progress = Arc::new(DecodeProgress::new(topic));
+                topics.push(progress.clone());
+                progress
+            }
+        };
+        progress.queued.fetch_add(count as u64, Ordering::Relaxed);
+        Some(progress)
+    }
+
+    /// Returns the progress of every topic decoded so far, in the order they started.
+    pub fn snapshots(&self) -> Vec<DecodeSnapshot> {
+        self.topics
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|progress| progress.snapshot())
+            .collect()
+    }
+}
+
+/// Returns the number of workers decoding messages in parallel.
+pub fn decode_workers() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        rayon::current_num_threads()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
+
+/// Bounds the number of workers decoding messages in parallel, which defaults to the number
+/// of CPUs.
+///
+/// This configures the global rayon pool, so it must be called before anything is decoded, and
+/// also bounds the other parallel work of the process such as reading MCAP chunks.
+///
+/// # Arguments
+/// * `jobs` - Number of workers, at least 1.
+#[cfg(feature = "parallel")]
+pub fn set_decode_jobs(jobs: usize) -> RosPeekResult<()> {
+    if jobs == 0 {
+        anyhow::bail!("Number of jobs must be at least 1");
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build_global()
+        .map_err(|e| anyhow::anyhow!("Failed to configure {jobs} decode jobs: {e}"))
+}
+
+/// Bounds the number of workers decoding messages, which is always 1 without parallel decoding.
+#[cfg(not(feature = "parallel"))]
+pub fn set_decode_jobs(jobs: usize) -> RosPeekResult<()> {
+    if jobs == 0 {
+        anyhow::bail!("Number of jobs must be at least 1");
+    }
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use serde_json::Value;
+
+/// Names of the fields holding a quaternion, as in `geometry_msgs/msg/Pose` and
+/// `geometry_msgs/msg/Transform`.
+pub const QUATERNION_FIELDS: [&str; 2] = ["orientation", "rotation"];
+
+/// Names of the fields [`add_euler_angles`] adds next to the components of a quaternion.
+pub const EULER_FIELDS: [&str; 3] = ["roll_deg", "pitch_deg", "yaw_deg"];
+
+/// Converts a quaternion into roll, pitch and yaw in radians, rotating about the fixed x, y
+/// and z axes in that order as `tf2::Matrix3x3::getRPY` does.
+///
+/// The quaternion is normalized first, and the pitch is clamped to ±90° at the gimbal lock.
+///
+/// # Arguments
+/// * `quaternion` - Quaternion `[x, y, z, w]`.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::quaternion_to_euler;
+///
+/// // a quarter turn around z
+/// let half = std::f64::consts::FRAC_PI_4;
+/// let [roll, pitch, yaw] = quaternion_to_euler([0.0, 0.0, half.sin(), half.cos()]);
+/// assert!(roll.abs() < 1e-12 && pitch.abs() < 1e-12);
+/// assert!((yaw - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+/// ```
+pub fn quaternion_to_euler(quaternion: [f64; 4]) -> [f64; 3] {
+    let [x, y, z, w] = quaternion;
+    let norm = (x * x + y * y + z * z + w * w).sqrt();
+    if norm == 0.0 {
+        return [0.0; 3];
+    }
+    let (x, y, z, w) = (x / norm, y / norm, z / norm, w / norm);
+    let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+    let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+    let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+    [roll, pitch, yaw]
+}
+
+/// Finds the quaternions of a decoded message, which are objects of numbers `x`, `y`, `z` and
+/// `w` in fields named as one of the [`QUATERNION_FIELDS`].
+///
+/// Fields decoded by themselves, whose keys are dot-separated paths such as
+/// `pose.pose.orientation`, are recognized by the last segment of the path.
+///
+/// # Returns
+/// The dot-separated paths of the fields paired with their quaternions `[x, y, z, w]`.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::find_quaternions;
+/// use serde_json::json;
+///
+/// let value = json!({
+///     "transforms": [
+///         {"transform": {"rotation": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}}},
+///     ],
+/// });
+/// assert_eq!(
+///     find_quaternions(&value),
+///     vec![("transforms.0.transform.rotation".to_string(), [0.0, 0.0, 0.0, 1.0])]
+/// );
+/// ```
+pub fn find_quaternions(value: &Value) -> Vec<(String, [f64; 4])> {
+    let mut quaternions = Vec::new();
+    collect_quaternions(value, "", &mut quaternions);
+    quaternions
+}
+
+fn collect_quaternions(value: &Value, prefix: &str, quaternions: &mut Vec<(String, [f64; 4])>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = format!("{prefix}{key}");
+                if let Some(quaternion) = as_quaternion(key, value) {
+                    quaternions.push((path, quaternion));
+                } else {
+                    collect_quaternions(value, &format!("{path}."), quaternions);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_quaternions(item, &format!("{prefix}{i}."), quaternions);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Adds the roll, pitch and yaw in degrees of every quaternion of a decoded message next to its
+/// components, as the [`EULER_FIELDS`].
+///
+/// # Arguments
+/// * `value` - The decoded JSON value, modified in place.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::add_euler_angles;
+/// use serde_json::json;
+///
+/// let mut value = json!({"pose": {"orientation": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0}}});
+/// add_euler_angles(&mut value);
+/// assert_eq!(value["pose"]["orientation"]["yaw_deg"], json!(0.0));
+/// ```
+pub fn add_euler_angles(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match as_quaternion(key, value) {
+                    Some(quaternion) => insert_euler_angles(value, quaternion),
+                    None => add_euler_angles(value),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(add_euler_angles),
+        _ => {}
+    }
+}
+
+fn insert_euler_angles(value: &mut Value, quaternion: [f64; 4]) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    for (name, angle) in EULER_FIELDS.iter().zip(quaternion_to_euler(quaternion)) {
+        object.insert(name.to_string(), Value::from(angle.to_degrees()));
+    }
+}
+
+/// Returns the quaternion held by a field, if it is named as one and holds one.
+fn as_quaternion(key: &str, value: &Value) -> Option<[f64; 4]> {
+    let name = key.rsplit('.').next().unwrap_or(key);
+    if !QUATERNION_FIELDS.contains(&name) {
+        return None;
+    }
+    let object = value.as_object()?;
+    let component = |axis: &str| object.get(axis).and_then(Value::as_f64);
+    Some([
+        component("x")?,
+        component("y")?,
+        component("z")?,
+        component("w")?,
+    ])
+}
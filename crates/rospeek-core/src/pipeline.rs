@@ -0,0 +1,512 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, HashMap},
+    sync::Arc,
+};
+
+use serde_json::Value;
+
+use crate::{
+    BagReader, CdrDecoder, DecodePlan, ExternalSorter, MessageSchema, RawMessage, RosPeekResult,
+    SortKey, Topic,
+};
+
+/// A message flowing through a [`Pipeline`].
+#[derive(Debug, Clone)]
+pub struct Record {
+    /// Topic the message belongs to.
+    pub topic: Arc<Topic>,
+    /// The raw message.
+    pub message: RawMessage,
+    /// The decoded message, filled by [`Pipeline::decode`].
+    pub value: Option<Value>,
+}
+
+/// Order in which a pipeline reads messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageOrder {
+    /// Topic by topic, each in recorded order, without buffering messages.
+    Topic,
+    /// Interleaved across topics by timestamp, each topic keeping its recorded order.
+    #[default]
+    Interleaved,
+    /// Sorted by timestamp.
+    Timestamp,
+}
+
+/// A processing step of a [`Pipeline`].
+pub trait Stage {
+    /// Processes a record.
+    ///
+    /// # Arguments
+    /// * `record` - The record to process.
+    ///
+    /// # Returns
+    /// The processed record, or `None` to drop it.
+    fn process(&mut self, record: Record) -> RosPeekResult<Option<Record>>;
+
+    /// Returns the name the records dropped by the stage are reported under, such as `Script`.
+    fn name(&self) -> &str {
+        "Stage"
+    }
+}
+
+/// Stages can be borrowed into a pipeline, to inspect their state once it has run.
+impl<S: Stage + ?Sized> Stage for &mut S {
+    fn process(&mut self, record: Record) -> RosPeekResult<Option<Record>> {
+        (**self).process(record)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+/// The final consumer of the records of a [`Pipeline`].
+pub trait Sink {
+    /// Registers a topic selected by the pipeline, called for every topic before any record.
+    fn add_topic(&mut self, _topic: &Topic) -> RosPeekResult<()> {
+        Ok(())
+    }
+
+    /// Consumes a record which went through all stages.
+    fn write(&mut self, record: Record) -> RosPeekResult<()>;
+
+    /// Finalizes the sink after the last record.
+    fn finish(&mut self) -> RosPeekResult<()> {
+        Ok(())
+    }
+}
+
+/// Number of records per topic which went through a [`Pipeline`].
+#[derive(Debug, Clone, Default)]
+pub struct PipelineSummary {
+    /// Number of records written to the sink for each topic.
+    pub counts: BTreeMap<String, u64>,
+    /// Number of records dropped by stages for each topic.
+    pub dropped: BTreeMap<String, u64>,
+    /// Name of each stage and number of records it dropped for each topic, in the order stages
+    /// were added.
+    pub dropped_by_stage: Vec<(String, BTreeMap<String, u64>)>,
+    /// Number of messages recorded out of timestamp order for each topic, only filled when
+    /// reading in [`MessageOrder::Timestamp`].
+    pub reordered: BTreeMap<String, u64>,
+}
+
+/// Approximate number of bytes of messages held in memory while ordering messages.
+const PIPELINE_SORT_BUDGET_BYTES: usize = 512 * 1024 * 1024;
+
+/// Number of messages of a topic read from the bag at a time.
+const PIPELINE_PAGE_SIZE: usize = 1024;
+
+/// Reads messages of a bag and passes them through stages into a sink.
+///
+/// Topics are read a page at a time and interleaved as they are read, so that bags larger than
+/// memory can be processed. Only [`MessageOrder::Timestamp`] sorts messages, spilling them to
+/// temporary files.
+///
+/// # Examples
+/// ```no_run
+/// use rospeek_core::{BagReader, Pipeline, Record, RosPeekResult, Sink};
+///
+/// struct Print;
+///
+/// impl Sink for Print {
+///     fn write(&mut self, record: Record) -> RosPeekResult<()> {
+///         println!("{}: {:?}", record.topic.name, record.value);
+///         Ok(())
+///     }
+/// }
+///
+/// fn print_large(reader: &dyn BagReader) -> RosPeekResult<()> {
+///     Pipeline::new(reader)
+///         .filter(|record| record.message.data.len() > 1024)
+///         .decode()
+///         .sink(&mut Print)?;
+///     Ok(())
+/// }
+/// ```
+pub struct Pipeline<'a> {
+    reader: &'a dyn BagReader,
+    topics: Vec<String>,
+    since: Option<u64>,
+    until: Option<u64>,
+    order: MessageOrder,
+    stages: Vec<Box<dyn Stage + 'a>>,
+}
+
+impl<'a> Pipeline<'a> {
+    /// Creates a pipeline reading all topics of a bag.
+    ///
+    /// # Arguments
+    /// * `reader` - The bag reader to read messages from.
+    pub fn new(reader: &'a dyn BagReader) -> Self {
+        Self {
+            reader,
+            topics: Vec::new(),
+            since: None,
+            until: None,
+            order: MessageOrder::default(),
+            stages: Vec::new(),
+        }
+    }
+
+    /// Selects topics to read, or all topics if empty.
+    pub fn topics(mut self, topics: &[String]) -> Self {
+        self.topics = topics.to_vec();
+        self
+    }
+
+    /// Selects the time range in nanoseconds to read messages from (inclusive).
+    pub fn range(mut self, since: Option<u64>, until: Option<u64>) -> Self {
+        self.since = since;
+        self.until = until;
+        self
+    }
+
+    /// Sets the order in which messages are read.
+    pub fn order(mut self, order: MessageOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Appends a stage.
+    pub fn stage(mut self, stage: impl Stage + 'a) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// Appends a stage keeping only records for which `predicate` returns `true`.
+    pub fn filter<F>(self, predicate: F) -> Self
+    where
+        F: FnMut(&Record) -> bool + 'a,
+    {
+        self.stage(Filter(predicate))
+    }
+
+    /// Appends a stage transforming records.
+    pub fn map<F>(self, f: F) -> Self
+    where
+        F: FnMut(Record) -> RosPeekResult<Record> + 'a,
+    {
+        self.stage(Map(f))
+    }
+
+    /// Appends a stage decoding records into [`Record::value`].
+    pub fn decode(self) -> Self {
+        self.stage(Decode::default())
+    }
+
+    /// Appends stages decoding records and transforming their decoded values.
+    pub fn map_value<F>(self, f: F) -> Self
+    where
+        F: FnMut(&Topic, Value) -> RosPeekResult<Value> + 'a,
+    {
+        self.decode().stage(MapValue(f))
+    }
+
+    /// Runs the pipeline, writing the records which went through all stages to a sink.
+    ///
+    /// # Arguments
+    /// * `sink` - The sink to write records to.
+    ///
+    /// # Returns
+    /// A summary of the processed records.
+    pub fn sink(mut self, sink: &mut dyn Sink) -> RosPeekResult<PipelineSummary> {
+        let topics: Vec<Arc<Topic>> = self
+            .reader
+            .topics()?
+            .into_iter()
+            .filter(|t| self.topics.is_empty() || self.topics.contains(&t.name))
+            .map(Arc::new)
+            .collect();
+        if let Some(missing) = self
+            .topics
+            .iter()
+            .find(|name| !topics.iter().any(|t| &t.name == *name))
+        {
+            anyhow::bail!("Topic not found: {missing}");
+        }
+        for topic in topics.iter() {
+            sink.add_topic(topic)?;
+        }
+        // records carry the type their message was recorded with, which may change between files
+        let versions = topics
+            .iter()
+            .map(|topic| self.versions(topic))
+            .collect::<RosPeekResult<Vec<_>>>()?;
+
+        let mut counts = vec![0; topics.len()];
+        // number of dropped records indexed by stage and topic
+        let mut dropped = vec![vec![0; topics.len()]; self.stages.len()];
+        let mut reordered = BTreeMap::new();
+        match self.order {
+            MessageOrder::Topic => {
+                for (index, topic) in topics.iter().enumerate() {
+                    let mut cursor = self.cursor(topic);
+                    while let Some(message) = cursor.next()? {
+                        let topic = version_at(&versions[index], topic, message.timestamp);
+                        match self.process(topic, message)? {
+                            Outcome::Kept(record) => {
+                                sink.write(record)?;
+                                counts[index] += 1;
+                            }
+                            Outcome::Dropped(stage) => dropped[stage][index] += 1,
+                        }
+                    }
+                }
+            }
+            MessageOrder::Interleaved => {
+                // merges the topics by the running maximum of their timestamps, which never
+                // decreases, so that each topic keeps its recorded order without sorting
+                let mut cursors: Vec<TopicCursor> =
+                    topics.iter().map(|topic| self.cursor(topic)).collect();
+                let mut heads = vec![None; topics.len()];
+                let mut latest = vec![0; topics.len()];
+                let mut heap = BinaryHeap::new();
+                for (index, cursor) in cursors.iter_mut().enumerate() {
+                    if let Some(message) = cursor.next()? {
+                        latest[index] = message.timestamp;
+                        heap.push(Reverse((message.timestamp, index)));
+                        heads[index] = Some(message);
+                    }
+                }
+                while let Some(Reverse((_, index))) = heap.pop() {
+                    let Some(message) = heads[index].take() else {
+                        continue;
+                    };
+                    if let Some(next) = cursors[index].next()? {
+                        latest[index] = latest[index].max(next.timestamp);
+                        heap.push(Reverse((latest[index], index)));
+                        heads[index] = Some(next);
+                    }
+                    let topic = version_at(&versions[index], &topics[index], message.timestamp);
+                    match self.process(topic, message)? {
+                        Outcome::Kept(record) => {
+                            sink.write(record)?;
+                            counts[index] += 1;
+                        }
+                        Outcome::Dropped(stage) => dropped[stage][index] += 1,
+                    }
+                }
+            }
+            MessageOrder::Timestamp => {
+                let mut sorter = ExternalSorter::new(PIPELINE_SORT_BUDGET_BYTES);
+                for (index, topic) in topics.iter().enumerate() {
+                    let mut cursor = self.cursor(topic);
+                    let mut latest = 0;
+                    let mut out_of_order = 0;
+                    let mut seq = 0;
+                    while let Some(message) = cursor.next()? {
+                        if message.timestamp < latest {
+                            out_of_order += 1;
+                        }
+                        latest = latest.max(message.timestamp);
+                        let key = SortKey {
+                            time: message.timestamp,
+                            topic: index,
+                            seq,
+                        };
+                        sorter.push(key, message)?;
+                        seq += 1;
+                    }
+                    reordered.insert(topic.name.clone(), out_of_order);
+                }
+
+                for entry in sorter.finish()? {
+                    let (key, message) = entry?;
+                    let topic =
+                        version_at(&versions[key.topic], &topics[key.topic], message.timestamp);
+                    match self.process(topic, message)? {
+                        Outcome::Kept(record) => {
+                            sink.write(record)?;
+                            counts[key.topic] += 1;
+                        }
+                        Outcome::Dropped(stage) => dropped[stage][key.topic] += 1,
+                    }
+                }
+            }
+        }
+        sink.finish()?;
+
+        let names = || topics.iter().map(|t| t.name.clone());
+        let total_dropped = (0..topics.len()).map(|topic| dropped.iter().map(|d| d[topic]).sum());
+        Ok(PipelineSummary {
+            counts: names().zip(counts).collect(),
+            dropped: names().zip(total_dropped).collect(),
+            dropped_by_stage: self
+                .stages
+                .iter()
+                .zip(dropped)
+                .map(|(stage, d)| (stage.name().to_string(), names().zip(d).collect()))
+                .collect(),
+            reordered,
+        })
+    }
+
+    /// Returns a cursor reading the messages of a topic within the range of the pipeline.
+    fn cursor(&self, topic: &Topic) -> TopicCursor<'a> {
+        TopicCursor {
+            reader: self.reader,
+            topic: topic.name.clone(),
+            since: self.since,
+            until: self.until,
+            offset: 0,
+            page: Vec::new().into_iter(),
+            exhausted: false,
+        }
+    }
+
+    /// Returns the start time and topic of each type version of a topic which has several.
+    fn versions(&self, topic: &Topic) -> RosPeekResult<Vec<(u64, Arc<Topic>)>> {
+        let versions = self.reader.type_versions(topic)?;
+        if versions.len() < 2 {
+            return Ok(Vec::new());
+        }
+        Ok(versions
+            .into_iter()
+            .map(|version| {
+                let topic = Topic {
+                    type_name: version.type_name,
+                    ..topic.clone()
+                };
+                (version.start_ns, Arc::new(topic))
+            })
+            .collect())
+    }
+
+    /// Passes a message through all stages, stopping once a stage drops it.
+    fn process(&mut self, topic: &Arc<Topic>, message: RawMessage) -> RosPeekResult<Outcome> {
+        let mut record = Record {
+            topic: topic.clone(),
+            message,
+            value: None,
+        };
+        for (index, stage) in self.stages.iter_mut().enumerate() {
+            match stage.process(record)? {
+                Some(next) => record = next,
+                None => return Ok(Outcome::Dropped(index)),
+            }
+        }
+        Ok(Outcome::Kept(record))
+    }
+}
+
+/// Reads the messages of a topic a page at a time.
+struct TopicCursor<'a> {
+    reader: &'a dyn BagReader,
+    topic: String,
+    since: Option<u64>,
+    until: Option<u64>,
+    /// Number of messages read so far.
+    offset: usize,
+    page: std::vec::IntoIter<RawMessage>,
+    /// Whether the last page has been read.
+    exhausted: bool,
+}
+
+impl TopicCursor<'_> {
+    /// Returns the next message, reading the next page once the current one is consumed.
+    fn next(&mut self) -> RosPeekResult<Option<RawMessage>> {
+        if let Some(message) = self.page.next() {
+            return Ok(Some(message));
+        }
+        if self.exhausted {
+            return Ok(None);
+        }
+        let page = self.reader.read_messages_range(
+            &self.topic,
+            self.since,
+            self.until,
+            Some(PIPELINE_PAGE_SIZE),
+            Some(self.offset),
+        )?;
+        self.offset += page.len();
+        self.exhausted = page.len() < PIPELINE_PAGE_SIZE;
+        self.page = page.into_iter();
+        Ok(self.page.next())
+    }
+}
+
+/// Returns the topic with the type version a message was recorded with.
+fn version_at<'t>(
+    versions: &'t [(u64, Arc<Topic>)],
+    topic: &'t Arc<Topic>,
+    timestamp: u64,
+) -> &'t Arc<Topic> {
+    versions
+        .iter()
+        .rev()
+        .find(|(start_ns, _)| *start_ns <= timestamp)
+        .or_else(|| versions.first())
+        .map_or(topic, |(_, topic)| topic)
+}
+
+/// Result of passing a message through the stages of a pipeline.
+enum Outcome {
+    /// The record went through all stages.
+    Kept(Record),
+    /// The record was dropped by the stage at the index.
+    Dropped(usize),
+}
+
+struct Filter<F>(F);
+
+impl<F: FnMut(&Record) -> bool> Stage for Filter<F> {
+    fn process(&mut self, record: Record) -> RosPeekResult<Option<Record>> {
+        Ok((self.0)(&record).then_some(record))
+    }
+
+    fn name(&self) -> &str {
+        "Filter"
+    }
+}
+
+struct Map<F>(F);
+
+impl<F: FnMut(Record) -> RosPeekResult<Record>> Stage for Map<F> {
+    fn process(&mut self, record: Record) -> RosPeekResult<Option<Record>> {
+        (self.0)(record).map(Some)
+    }
+}
+
+struct MapValue<F>(F);
+
+impl<F: FnMut(&Topic, Value) -> RosPeekResult<Value>> Stage for MapValue<F> {
+    fn process(&mut self, mut record: Record) -> RosPeekResult<Option<Record>> {
+        if let Some(value) = record.value.take() {
+            record.value = Some((self.0)(&record.topic, value)?);
+        }
+        Ok(Some(record))
+    }
+}
+
+/// Stage decoding records, compiling a plan once per message type.
+#[derive(Default)]
+struct Decode {
+    plans: HashMap<String, Arc<DecodePlan>>,
+}
+
+impl Stage for Decode {
+    fn process(&mut self, mut record: Record) -> RosPeekResult<Option<Record>> {
+        if record.value.is_some() {
+            return Ok(Some(record));
+        }
+        let type_name = &record.topic.type_name;
+        let plan = match self.plans.get(type_name) {
+            Some(plan) => plan.clone(),
+            None => {
+                let schema = MessageSchema::try_from(type_name.as_str())?;
+                let plan = Arc::new(DecodePlan::compile(&schema)?);
+                self.plans.insert(type_name.clone(), plan.clone());
+                plan
+            }
+        };
+        record.value = Some(CdrDecoder::new(&record.message.data).decode_plan(&plan)?);
+        Ok(Some(record))
+    }
+
+    fn name(&self) -> &str {
+        "Decoding"
+    }
+}
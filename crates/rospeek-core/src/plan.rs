@@ -0,0 +1,229 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{FieldType, MessageSchema, RosPeekResult};
+
+/// Fixed-size primitive types, resolved from IDL type names once at compile time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Bool,
+    Octet,
+    Char,
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Int64,
+    Uint64,
+    Float,
+    Double,
+}
+
+impl Primitive {
+    /// Resolves a primitive from its IDL type name, such as `uint32`.
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        Some(match type_name {
+            "boolean" => Primitive::Bool,
+            "octet" => Primitive::Octet,
+            "char" => Primitive::Char,
+            "int8" => Primitive::Int8,
+            "uint8" => Primitive::Uint8,
+            "int16" => Primitive::Int16,
+            "uint16" => Primitive::Uint16,
+            "int32" => Primitive::Int32,
+            "uint32" => Primitive::Uint32,
+            "int64" => Primitive::Int64,
+            "uint64" => Primitive::Uint64,
+            "float" => Primitive::Float,
+            "double" => Primitive::Double,
+            _ => return None,
+        })
+    }
+
+    /// Returns the serialized size in bytes, which is also the alignment.
+    pub fn size(self) -> usize {
+        match self {
+            Primitive::Bool
+            | Primitive::Octet
+            | Primitive::Char
+            | Primitive::Int8
+            | Primitive::Uint8 => 1,
+            Primitive::Int16 | Primitive::Uint16 => 2,
+            Primitive::Int32 | Primitive::Uint32 | Primitive::Float => 4,
+            Primitive::Int64 | Primitive::Uint64 | Primitive::Double => 8,
+        }
+    }
+}
+
+/// Type of a single value or iterable element in a decode plan.
+#[derive(Debug, Clone)]
+pub(crate) enum Element {
+    Primitive(Primitive),
+    String,
+    /// `builtin_interfaces/msg/Time` or `builtin_interfaces/msg/Duration`
+    Time,
+    /// Nested structure, decoded with its own plan per element
+    Struct(Arc<DecodePlan>),
+}
+
+/// Number of elements of an iterable field.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Length {
+    /// Fixed-length array, `T[N]`
+    Fixed(usize),
+    /// Variable-length sequence prefixed by a `uint32` length
+    Sequence,
+}
+
+/// A single step of a decode plan.
+#[derive(Debug, Clone)]
+pub(crate) enum PlanOp {
+    /// Decodes a single value into a field of the current object.
+    Value { name: String, element: Element },
+    /// Decodes an array or a sequence into a field of the current object.
+    Iterable {
+        name: String,
+        element: Element,
+        length: Length,
+    },
+    /// Starts an inlined nested structure stored in a field of the current object.
+    Begin { name: String },
+    /// Ends the innermost inlined nested structure.
+    End,
+}
+
+/// A message schema compiled into a flat list of operations.
+///
+/// Compiling resolves type names and nested schemas once, so that executing the plan per message
+/// with [`crate::CdrDecoder::decode_plan`] needs neither string matching nor schema lookups.
+#[derive(Debug, Clone)]
+pub struct DecodePlan {
+    type_name: String,
+    pub(crate) ops: Vec<PlanOp>,
+}
+
+impl DecodePlan {
+    /// Compiles a schema into a decode plan, looking up the IDL files of nested types.
+    ///
+    /// # Arguments
+    /// * `schema` - ROS message schema
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_core::{DecodePlan, FieldType, MessageField, MessageSchema};
+    ///
+    /// let schema = MessageSchema {
+    ///     type_name: "std_msgs/msg/Header".to_string(),
+    ///     fields: vec![
+    ///         MessageField {
+    ///             name: "stamp".to_string(),
+    ///             field_type: FieldType::Object("builtin_interfaces/msg/Time".to_string()),
+    ///         },
+    ///         MessageField {
+    ///             name: "frame_id".to_string(),
+    ///             field_type: FieldType::Object("string".to_string()),
+    ///         },
+    ///     ],
+    /// };
+    /// let plan = DecodePlan::compile(&schema).unwrap();
+    /// assert_eq!(plan.type_name(), "std_msgs/msg/Header");
+    /// ```
+    pub fn compile(schema: &MessageSchema) -> RosPeekResult<Self> {
+        let mut compiler = Compiler::default();
+        let mut ops = Vec::new();
+        compiler.compile_fields(schema, &mut ops)?;
+        Ok(Self {
+            type_name: schema.type_name.clone(),
+            ops,
+        })
+    }
+
+    /// Returns the name of the compiled message type.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+}
+
+/// Compilation state shared across nested types.
+#[derive(Default)]
+struct Compiler {
+    schemas: HashMap<String, Arc<MessageSchema>>,
+    plans: HashMap<String, Arc<DecodePlan>>,
+}
+
+impl Compiler {
+    fn compile_fields(
+        &mut self,
+        schema: &MessageSchema,
+        ops: &mut Vec<PlanOp>,
+    ) -> RosPeekResult<()> {
+        for field in schema.fields.iter() {
+            let name = field.name.clone();
+            match field.field_type {
+                FieldType::Object(ref type_name) => match element(type_name) {
+                    Some(element) => ops.push(PlanOp::Value { name, element }),
+                    None => {
+                        // single nested structures are inlined to keep the plan flat
+                        let nested = self.schema(type_name)?;
+                        ops.push(PlanOp::Begin { name });
+                        self.compile_fields(&nested, ops)?;
+                        ops.push(PlanOp::End);
+                    }
+                },
+                FieldType::Sequence(ref type_name) | FieldType::Array(ref type_name, _) => {
+                    let length = match field.field_type {
+                        FieldType::Array(_, n) => Length::Fixed(n),
+                        _ => Length::Sequence,
+                    };
+                    let element = match element(type_name) {
+                        Some(element) => element,
+                        None => Element::Struct(self.plan(type_name)?),
+                    };
+                    ops.push(PlanOp::Iterable {
+                        name,
+                        element,
+                        length,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn plan(&mut self, type_name: &str) -> RosPeekResult<Arc<DecodePlan>> {
+        if let Some(plan) = self.plans.get(type_name) {
+            return Ok(plan.clone());
+        }
+        let schema = self.schema(type_name)?;
+        let mut ops = Vec::new();
+        self.compile_fields(&schema, &mut ops)?;
+        let plan = Arc::new(DecodePlan {
+            type_name: type_name.to_string(),
+            ops,
+        });
+        self.plans.insert(type_name.to_string(), plan.clone());
+        Ok(plan)
+    }
+
+    fn schema(&mut self, type_name: &str) -> RosPeekResult<Arc<MessageSchema>> {
+        if let Some(schema) = self.schemas.get(type_name) {
+            return Ok(schema.clone());
+        }
+        let schema = Arc::new(MessageSchema::try_from(type_name)?);
+        self.schemas.insert(type_name.to_string(), schema.clone());
+        Ok(schema)
+    }
+}
+
+/// Resolves the element type of a non-structure type, or `None` for nested structures.
+fn element(type_name: &str) -> Option<Element> {
+    if let Some(primitive) = Primitive::from_type_name(type_name) {
+        return Some(Element::Primitive(primitive));
+    }
+    match type_name {
+        "string" => Some(Element::String),
+        "builtin_interfaces/msg/Time" | "builtin_interfaces/msg/Duration" => Some(Element::Time),
+        _ => None,
+    }
+}
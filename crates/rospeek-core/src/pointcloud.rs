@@ -0,0 +1,379 @@
+use std::{collections::HashSet, io::Write};
+
+use anyhow::anyhow;
+
+use crate::{CdrReader, RosPeekResult};
+
+/// Name of the ROS message type of point clouds.
+pub const POINT_CLOUD_TYPE: &str = "sensor_msgs/msg/PointCloud2";
+
+/// Numeric type of a field of a point, as the `datatype` constants of `sensor_msgs/msg/PointField`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointFieldType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl PointFieldType {
+    /// Returns the type of a `datatype` constant, `None` if it is unknown.
+    pub fn from_datatype(datatype: u8) -> Option<Self> {
+        Some(match datatype {
+            1 => PointFieldType::Int8,
+            2 => PointFieldType::UInt8,
+            3 => PointFieldType::Int16,
+            4 => PointFieldType::UInt16,
+            5 => PointFieldType::Int32,
+            6 => PointFieldType::UInt32,
+            7 => PointFieldType::Float32,
+            8 => PointFieldType::Float64,
+            _ => return None,
+        })
+    }
+
+    /// Returns the size of a value in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            PointFieldType::Int8 | PointFieldType::UInt8 => 1,
+            PointFieldType::Int16 | PointFieldType::UInt16 => 2,
+            PointFieldType::Int32 | PointFieldType::UInt32 | PointFieldType::Float32 => 4,
+            PointFieldType::Float64 => 8,
+        }
+    }
+
+    /// Returns the name of the type, such as `float32`.
+    pub fn name(self) -> &'static str {
+        match self {
+            PointFieldType::Int8 => "int8",
+            PointFieldType::UInt8 => "uint8",
+            PointFieldType::Int16 => "int16",
+            PointFieldType::UInt16 => "uint16",
+            PointFieldType::Int32 => "int32",
+            PointFieldType::UInt32 => "uint32",
+            PointFieldType::Float32 => "float32",
+            PointFieldType::Float64 => "float64",
+        }
+    }
+
+    /// Returns the `TYPE` of the type in PCD headers.
+    fn pcd_type(self) -> char {
+        match self {
+            PointFieldType::Int8 | PointFieldType::Int16 | PointFieldType::Int32 => 'I',
+            PointFieldType::UInt8 | PointFieldType::UInt16 | PointFieldType::UInt32 => 'U',
+            PointFieldType::Float32 | PointFieldType::Float64 => 'F',
+        }
+    }
+}
+
+/// Field of the points of a [`PointCloud`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointField {
+    pub name: String,
+    /// Offset of the field from the start of a point in bytes.
+    pub offset: usize,
+    pub datatype: PointFieldType,
+    /// Number of values of the field.
+    pub count: usize,
+}
+
+/// A decoded `sensor_msgs/msg/PointCloud2`, keeping the points in their packed layout.
+#[derive(Debug, Clone)]
+pub struct PointCloud {
+    pub frame_id: String,
+    pub height: usize,
+    pub width: usize,
+    pub fields: Vec<PointField>,
+    pub is_bigendian: bool,
+    /// Length of a point in bytes.
+    pub point_step: usize,
+    /// Length of a row in bytes.
+    pub row_step: usize,
+    pub data: Vec<u8>,
+    pub is_dense: bool,
+}
+
+impl PointCloud {
+    /// Decodes a CDR-encoded `sensor_msgs/msg/PointCloud2`.
+    ///
+    /// # Arguments
+    /// * `data` - CDR-encoded message.
+    pub fn from_cdr(data: &[u8]) -> RosPeekResult<Self> {
+        let mut reader = CdrReader::new(data);
+        // std_msgs/msg/Header
+        reader.read_i32()?;
+        reader.read_u32()?;
+        let frame_id = reader.read_str()?.to_string();
+
+        let height = reader.read_u32()? as usize;
+        let width = reader.read_u32()? as usize;
+        let num_fields = reader.read_u32()? as usize;
+        let mut fields = Vec::with_capacity(num_fields.min(64));
+        for _ in 0..num_fields {
+            let name = reader.read_str()?.to_string();
+            let offset = reader.read_u32()? as usize;
+            let datatype = reader.read_u8()?;
+            let count = reader.read_u32()? as usize;
+            let datatype = PointFieldType::from_datatype(datatype)
+                .ok_or_else(|| anyhow!("Unknown datatype {datatype} of point field {name}"))?;
+            fields.push(PointField {
+                name,
+                offset,
+                datatype,
+                count,
+            });
+        }
+        let is_bigendian = reader.read_bool()?;
+        let point_step = reader.read_u32()? as usize;
+        let row_step = reader.read_u32()? as usize;
+        let len = reader.read_u32()? as usize;
+        let data = reader.take_bytes(len)?.to_vec();
+        let is_dense = reader.read_bool()?;
+
+        let cloud = Self {
+            frame_id,
+            height,
+            width,
+            fields,
+            is_bigendian,
+            point_step,
+            row_step,
+            data,
+            is_dense,
+        };
+        cloud.validate()?;
+        Ok(cloud)
+    }
+
+    /// Returns the number of points.
+    pub fn len(&self) -> usize {
+        self.height * self.width
+    }
+
+    /// Returns whether the cloud has no points.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the packed bytes of a point.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the point in row-major order.
+    pub fn point(&self, index: usize) -> &[u8] {
+        let start = index / self.width * self.row_step + index % self.width * self.point_step;
+        &self.data[start..start + self.point_step]
+    }
+
+    /// Returns the field of the points with a given name.
+    pub fn field(&self, name: &str) -> Option<&PointField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Returns the number of bytes of a point which belong to no field.
+    pub fn padding(&self) -> usize {
+        let used: usize = self
+            .fields
+            .iter()
+            .map(|f| f.datatype.size() * f.count)
+            .sum();
+        self.point_step.saturating_sub(used)
+    }
+
+    /// Returns a cloud with only some of the fields, in the given order, packed without padding.
+    ///
+    /// # Arguments
+    /// * `names` - Names of the fields to keep, such as `x`, `y`, `z` and `intensity`.
+    pub fn select_fields<S: AsRef<str>>(&self, names: &[S]) -> RosPeekResult<Self> {
+        let selected = names
+            .iter()
+            .map(|name| {
+                let name = name.as_ref();
+                self.field(name).ok_or_else(|| {
+                    let available: Vec<_> = self.fields.iter().map(|f| f.name.as_str()).collect();
+                    anyhow!(
+                        "Point field {name} not found, available: {}",
+                        available.join(", ")
+                    )
+                })
+            })
+            .collect::<RosPeekResult<Vec<_>>>()?;
+
+        let mut fields = Vec::with_capacity(selected.len());
+        let mut point_step = 0;
+        for field in selected.iter() {
+            fields.push(PointField {
+                offset: point_step,
+                ..(*field).clone()
+            });
+            point_step += field.datatype.size() * field.count;
+        }
+        let mut data = Vec::with_capacity(point_step * self.len());
+        for index in 0..self.len() {
+            let point = self.point(index);
+            for field in selected.iter() {
+                let size = field.datatype.size() * field.count;
+                data.extend_from_slice(&point[field.offset..field.offset + size]);
+            }
+        }
+        Ok(Self {
+            frame_id: self.frame_id.clone(),
+            height: self.height,
+            width: self.width,
+            fields,
+            is_bigendian: self.is_bigendian,
+            point_step,
+            row_step: point_step * self.width,
+            data,
+            is_dense: self.is_dense,
+        })
+    }
+
+    /// Returns a lighter cloud keeping the first point of each cube of a voxel grid, such as to
+    /// view or export a dense lidar scan. Points with a non-finite coordinate are dropped.
+    ///
+    /// # Arguments
+    /// * `leaf_size` - Edge length of the cubes, in the unit of the coordinates.
+    pub fn voxel_downsample(&self, leaf_size: f64) -> RosPeekResult<Self> {
+        if leaf_size.is_nan() || leaf_size <= 0.0 {
+            anyhow::bail!("Voxel leaf size must be positive, got {leaf_size}");
+        }
+        let (Some(x), Some(y), Some(z)) = (self.field("x"), self.field("y"), self.field("z"))
+        else {
+            anyhow::bail!("Point cloud has no x, y and z fields");
+        };
+
+        let mut voxels = HashSet::new();
+        let mut data = Vec::new();
+        for index in 0..self.len() {
+            let position = [x, y, z].map(|field| self.value(index, field));
+            if position.iter().any(|v| !v.is_finite()) {
+                continue;
+            }
+            if voxels.insert(position.map(|v| (v / leaf_size).floor() as i64)) {
+                data.extend_from_slice(self.point(index));
+            }
+        }
+        Ok(Self {
+            frame_id: self.frame_id.clone(),
+            height: 1,
+            width: voxels.len(),
+            fields: self.fields.clone(),
+            is_bigendian: self.is_bigendian,
+            point_step: self.point_step,
+            row_step: self.point_step * voxels.len(),
+            data,
+            is_dense: true,
+        })
+    }
+
+    /// Returns the first value of a field of a point.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the point in row-major order.
+    /// * `field` - Field of the points, as returned by [`PointCloud::field`].
+    pub fn value(&self, index: usize, field: &PointField) -> f64 {
+        let size = field.datatype.size();
+        let mut bytes = [0u8; 8];
+        bytes[..size].copy_from_slice(&self.point(index)[field.offset..field.offset + size]);
+        if self.is_bigendian {
+            bytes[..size].reverse();
+        }
+        let [b0, b1, b2, b3, ..] = bytes;
+        match field.datatype {
+            PointFieldType::Int8 => b0 as i8 as f64,
+            PointFieldType::UInt8 => b0 as f64,
+            PointFieldType::Int16 => i16::from_le_bytes([b0, b1]) as f64,
+            PointFieldType::UInt16 => u16::from_le_bytes([b0, b1]) as f64,
+            PointFieldType::Int32 => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            PointFieldType::UInt32 => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            PointFieldType::Float32 => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            PointFieldType::Float64 => f64::from_le_bytes(bytes),
+        }
+    }
+
+    /// Writes the cloud as a binary PCD file.
+    ///
+    /// Padding between the fields is dropped and values are written in little-endian, so the
+    /// file can be read by PCL and Open3D regardless of the layout of the message.
+    ///
+    /// # Arguments
+    /// * `writer` - The writer to write the file to.
+    pub fn write_pcd<W: Write>(&self, mut writer: W) -> RosPeekResult<()> {
+        let mut fields: Vec<&PointField> = self.fields.iter().collect();
+        fields.sort_by_key(|f| f.offset);
+        let column = |f: fn(&PointField) -> String| {
+            fields
+                .iter()
+                .map(|field| f(field))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        writeln!(writer, "# .PCD v0.7 - Point Cloud Data file format")?;
+        writeln!(writer, "VERSION 0.7")?;
+        writeln!(writer, "FIELDS {}", column(|f| f.name.clone()))?;
+        writeln!(writer, "SIZE {}", column(|f| f.datatype.size().to_string()))?;
+        writeln!(
+            writer,
+            "TYPE {}",
+            column(|f| f.datatype.pcd_type().to_string())
+        )?;
+        writeln!(writer, "COUNT {}", column(|f| f.count.to_string()))?;
+        writeln!(writer, "WIDTH {}", self.width)?;
+        writeln!(writer, "HEIGHT {}", self.height)?;
+        writeln!(writer, "VIEWPOINT 0 0 0 1 0 0 0")?;
+        writeln!(writer, "POINTS {}", self.len())?;
+        writeln!(writer, "DATA binary")?;
+
+        let mut packed = Vec::with_capacity(self.point_step);
+        for index in 0..self.len() {
+            let point = self.point(index);
+            packed.clear();
+            for field in fields.iter() {
+                let size = field.datatype.size();
+                let bytes = &point[field.offset..field.offset + size * field.count];
+                if self.is_bigendian {
+                    for value in bytes.chunks_exact(size) {
+                        packed.extend(value.iter().rev());
+                    }
+                } else {
+                    packed.extend_from_slice(bytes);
+                }
+            }
+            writer.write_all(&packed)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the fields fit in a point and the points fit in the data.
+    fn validate(&self) -> RosPeekResult<()> {
+        if let Some(field) = self
+            .fields
+            .iter()
+            .find(|f| f.offset + f.datatype.size() * f.count > self.point_step)
+        {
+            anyhow::bail!(
+                "Point field {} exceeds the point step of {} bytes",
+                field.name,
+                self.point_step
+            );
+        }
+        let rows_overlap = self.height > 1 && self.width * self.point_step > self.row_step;
+        let required = match self.height {
+            0 => 0,
+            height => (height - 1) * self.row_step + self.width * self.point_step,
+        };
+        if rows_overlap || required > self.data.len() {
+            anyhow::bail!(
+                "Point cloud data is shorter than {}x{} points",
+                self.width,
+                self.height
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{CdrReader, RosPeekResult};
+
+/// Message types whose pose is read by [`Pose::from_cdr`].
+pub const POSE_TYPES: [&str; 4] = [
+    "geometry_msgs/msg/Pose",
+    "geometry_msgs/msg/PoseStamped",
+    "geometry_msgs/msg/PoseWithCovarianceStamped",
+    "nav_msgs/msg/Odometry",
+];
+
+/// Position and orientation, as `geometry_msgs/msg/Pose`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    /// Position `[x, y, z]`.
+    pub position: [f64; 3],
+    /// Orientation as a quaternion `[x, y, z, w]`.
+    pub orientation: [f64; 4],
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            orientation: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+impl Pose {
+    /// Reads the pose of a CDR-encoded message of one of the [`POSE_TYPES`].
+    ///
+    /// # Arguments
+    /// * `type_name` - Name of the message type.
+    /// * `data` - CDR-encoded message.
+    pub fn from_cdr(type_name: &str, data: &[u8]) -> RosPeekResult<Self> {
+        let mut reader = CdrReader::new(data);
+        if type_name != "geometry_msgs/msg/Pose" {
+            // std_msgs/msg/Header
+            reader.read_i32()?;
+            reader.read_u32()?;
+            reader.read_str()?;
+        }
+        match type_name {
+            "geometry_msgs/msg/Pose"
+            | "geometry_msgs/msg/PoseStamped"
+            | "geometry_msgs/msg/PoseWithCovarianceStamped" => {}
+            "nav_msgs/msg/Odometry" => {
+                // child_frame_id
+                reader.read_str()?;
+            }
+            _ => anyhow::bail!("{type_name} is not a pose type"),
+        }
+
+        let mut position = [0.0; 3];
+        let mut orientation = [0.0; 4];
+        for value in position.iter_mut().chain(orientation.iter_mut()) {
+            *value = reader.read_f64()?;
+        }
+        Ok(Self {
+            position,
+            orientation,
+        })
+    }
+
+    /// Returns the rotation matrix of the orientation, normalizing the quaternion.
+    pub fn rotation_matrix(&self) -> [[f64; 3]; 3] {
+        let [x, y, z, w] = self.orientation;
+        let norm = (x * x + y * y + z * z + w * w).sqrt();
+        if norm == 0.0 {
+            return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        }
+        let (x, y, z, w) = (x / norm, y / norm, z / norm, w / norm);
+        [
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - z * w),
+                2.0 * (x * z + y * w),
+            ],
+            [
+                2.0 * (x * y + z * w),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - x * w),
+            ],
+            [
+                2.0 * (x * z - y * w),
+                2.0 * (y * z + x * w),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ]
+    }
+}
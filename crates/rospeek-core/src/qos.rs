@@ -0,0 +1,251 @@
+use serde_yaml::Value;
+
+use crate::Topic;
+
+/// History depth below which a reliable publisher of sensor data is likely to block or drop
+/// messages under load.
+const SHALLOW_DEPTH: u64 = 5;
+
+/// Messages of `sensor_msgs` published at a high rate, which are expected to use the sensor data
+/// QoS.
+const SENSOR_DATA_MESSAGES: &[&str] = &[
+    "CompressedImage",
+    "Image",
+    "Imu",
+    "LaserScan",
+    "MagneticField",
+    "MultiEchoLaserScan",
+    "NavSatFix",
+    "PointCloud",
+    "PointCloud2",
+    "Range",
+];
+
+/// Last segments of the names of topics whose few messages are published once, which are
+/// expected to be `TRANSIENT_LOCAL` so that late subscribers still receive them.
+const LATCHED_TOPICS: &[&str] = &["map", "robot_description", "tf_static"];
+
+/// History policy of a QoS profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum History {
+    KeepLast,
+    KeepAll,
+    /// System default or unknown, as recorded when the publisher does not advertise it.
+    Unknown,
+}
+
+/// Reliability policy of a QoS profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reliability {
+    Reliable,
+    BestEffort,
+    /// System default, best available or unknown.
+    Unknown,
+}
+
+/// Durability policy of a QoS profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Durability {
+    TransientLocal,
+    Volatile,
+    /// System default, best available or unknown.
+    Unknown,
+}
+
+/// QoS profile offered by a publisher of a topic, as recorded in `offered_qos_profiles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QosProfile {
+    pub history: History,
+    /// History depth, `0` if unknown.
+    pub depth: u64,
+    pub reliability: Reliability,
+    pub durability: Durability,
+}
+
+impl QosProfile {
+    /// Parses the `offered_qos_profiles` of a topic, a YAML list of one profile per publisher.
+    ///
+    /// Policies are written as numbers before Jazzy and as names since, both being accepted.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_core::{Durability, QosProfile, Reliability};
+    ///
+    /// let humble = "- history: 1\n  depth: 10\n  reliability: 2\n  durability: 2\n";
+    /// let jazzy = "- history: keep_last\n  depth: 10\n  reliability: best_effort\n  durability: volatile\n";
+    /// assert_eq!(QosProfile::parse_all(humble), QosProfile::parse_all(jazzy));
+    ///
+    /// let profile = QosProfile::parse_all(humble)[0];
+    /// assert_eq!(profile.reliability, Reliability::BestEffort);
+    /// assert_eq!(profile.durability, Durability::Volatile);
+    /// ```
+    pub fn parse_all(profiles: &str) -> Vec<QosProfile> {
+        let Ok(Value::Sequence(profiles)) = serde_yaml::from_str::<Value>(profiles) else {
+            return Vec::new();
+        };
+        profiles
+            .iter()
+            .map(|profile| {
+                let policy = |key: &str, number: u64, name: &str| {
+                    profile.get(key).is_some_and(|value| match value {
+                        Value::Number(n) => n.as_u64() == Some(number),
+                        Value::String(s) => s.eq_ignore_ascii_case(name),
+                        _ => false,
+                    })
+                };
+                QosProfile {
+                    history: match () {
+                        _ if policy("history", 1, "keep_last") => History::KeepLast,
+                        _ if policy("history", 2, "keep_all") => History::KeepAll,
+                        _ => History::Unknown,
+                    },
+                    depth: profile.get("depth").and_then(Value::as_u64).unwrap_or(0),
+                    reliability: match () {
+                        _ if policy("reliability", 1, "reliable") => Reliability::Reliable,
+                        _ if policy("reliability", 2, "best_effort") => Reliability::BestEffort,
+                        _ => Reliability::Unknown,
+                    },
+                    durability: match () {
+                        _ if policy("durability", 1, "transient_local") => {
+                            Durability::TransientLocal
+                        }
+                        _ if policy("durability", 2, "volatile") => Durability::Volatile,
+                        _ => Durability::Unknown,
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+/// Rule flagging a recorded QoS combination which is a known cause of missing messages.
+#[derive(Debug, Clone, Copy)]
+pub struct QosRule {
+    /// Identifier of the rule, such as `sensor-data-reliable-shallow`.
+    pub id: &'static str,
+    /// Recommended settings instead.
+    pub recommendation: &'static str,
+    /// Returns what is wrong with the profiles of a topic, if they break the rule.
+    violation: fn(&Topic, &[QosProfile]) -> Option<String>,
+}
+
+/// Rules checked by [`lint_qos`].
+pub const QOS_RULES: &[QosRule] = &[
+    QosRule {
+        id: "sensor-data-reliable-shallow",
+        recommendation: "Publish sensor data with the sensor data QoS (BEST_EFFORT, KEEP_LAST 5), \
+                         or deepen the history of reliable publishers",
+        violation: |topic, profiles| {
+            // a depth of 0 is recorded when the publisher does not advertise its history
+            let depth = profiles
+                .iter()
+                .filter(|p| p.reliability == Reliability::Reliable)
+                .filter(|p| p.history == History::KeepLast)
+                .map(|p| p.depth)
+                .filter(|depth| (1..SHALLOW_DEPTH).contains(depth))
+                .min()?;
+            is_sensor_data(topic)
+                .then(|| format!("sensor data published RELIABLE with a history depth of {depth}"))
+        },
+    },
+    QosRule {
+        id: "sensor-data-transient-local",
+        recommendation: "Publish sensor data VOLATILE, as TRANSIENT_LOCAL keeps stale messages \
+                         for late subscribers",
+        violation: |topic, profiles| {
+            (is_sensor_data(topic)
+                && profiles
+                    .iter()
+                    .any(|p| p.durability == Durability::TransientLocal))
+            .then(|| "sensor data published TRANSIENT_LOCAL".to_string())
+        },
+    },
+    QosRule {
+        id: "latched-volatile",
+        recommendation: "Publish latched topics TRANSIENT_LOCAL and record them with a matching \
+                         QoS override, or their messages are missed when recording starts late",
+        violation: |topic, profiles| {
+            (is_latched(topic)
+                && profiles
+                    .iter()
+                    .all(|p| p.durability == Durability::Volatile))
+            .then(|| "latched topic published VOLATILE".to_string())
+        },
+    },
+    QosRule {
+        id: "mixed-reliability",
+        recommendation: "Publish a topic with the same reliability from every publisher, as the \
+                         recorder falls back to BEST_EFFORT for all of them",
+        violation: |_, profiles| {
+            let count = |reliability| {
+                profiles
+                    .iter()
+                    .filter(|p| p.reliability == reliability)
+                    .count()
+            };
+            let (reliable, best_effort) =
+                (count(Reliability::Reliable), count(Reliability::BestEffort));
+            (reliable > 0 && best_effort > 0).then(|| {
+                format!("{reliable} publishers are RELIABLE and {best_effort} are BEST_EFFORT")
+            })
+        },
+    },
+];
+
+/// Problematic QoS combination found on a topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QosFinding {
+    pub topic: String,
+    /// Identifier of the broken rule.
+    pub rule: &'static str,
+    /// What is wrong with the recorded profiles.
+    pub problem: String,
+    pub recommendation: &'static str,
+}
+
+/// Checks the recorded QoS profiles of topics against [`QOS_RULES`].
+///
+/// Topics without recorded profiles, such as those of MCAP files written by other tools, are
+/// skipped.
+///
+/// # Returns
+/// The findings ordered by topic, then by rule.
+pub fn lint_qos(topics: &[Topic]) -> Vec<QosFinding> {
+    let mut findings = Vec::new();
+    for topic in topics {
+        let profiles = topic
+            .offered_qos_profiles
+            .as_deref()
+            .map(QosProfile::parse_all)
+            .unwrap_or_default();
+        if profiles.is_empty() {
+            continue;
+        }
+        findings.extend(QOS_RULES.iter().filter_map(|rule| {
+            (rule.violation)(topic, &profiles).map(|problem| QosFinding {
+                topic: topic.name.clone(),
+                rule: rule.id,
+                problem,
+                recommendation: rule.recommendation,
+            })
+        }));
+    }
+    // rules keep their order within a topic
+    findings.sort_by(|a, b| a.topic.cmp(&b.topic));
+    findings
+}
+
+fn is_sensor_data(topic: &Topic) -> bool {
+    topic.package() == Some("sensor_msgs")
+        && topic
+            .message_name()
+            .is_some_and(|name| SENSOR_DATA_MESSAGES.contains(&name))
+}
+
+fn is_latched(topic: &Topic) -> bool {
+    topic
+        .name
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| LATCHED_TOPICS.contains(&name))
+}
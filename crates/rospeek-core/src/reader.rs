@@ -1,10 +1,50 @@
-use chrono::DateTime;
 use std::{
+    collections::BTreeMap,
     fmt::{Display, Formatter},
+    fs::File,
+    io::Read,
     path::Path,
+    sync::Arc,
 };
 
-use crate::{RawMessage, RosPeekResult, Topic};
+use crate::{
+    EmbeddedSchema, IncompleteSign, RawMessage, RosPeekResult, TaggedMessage, Topic, TypeVersion,
+};
+
+/// Work a reader does when a bag is opened, rather than on first use.
+///
+/// Callers which only read the messages of a few topics, such as the GUI loading a topic, can
+/// skip it so that opening a large file does not scan it.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::OpenOptions;
+///
+/// let options = OpenOptions {
+///     compute_stats: false,
+///     ..Default::default()
+/// };
+/// assert!(options.build_index);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenOptions {
+    /// Whether to compute [`BagStats`] when opening, which may scan every message.
+    pub compute_stats: bool,
+    /// Whether to load the index of the file when opening, such as the MCAP summary section.
+    pub build_index: bool,
+    /// Storage to read files as, or `None` to detect it with [`StorageType::detect`].
+    pub storage: Option<StorageType>,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self {
+            compute_stats: true,
+            build_index: true,
+            storage: None,
+        }
+    }
+}
 
 pub trait BagReader: Send {
     /// Opens a bag file at the given path.
@@ -18,11 +58,30 @@ pub trait BagReader: Send {
     where
         Self: Sized;
 
+    /// Opens a bag file at the given path, choosing which work is done upfront.
+    ///
+    /// Readers which have nothing to defer open the bag as [`BagReader::open`] does.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the bag file.
+    /// * `options` - What to compute when opening rather than on first use.
+    ///
+    /// # Returns
+    /// A result containing the opened bag reader or an error.
+    fn open_with_options<P: AsRef<Path>>(path: P, options: &OpenOptions) -> RosPeekResult<Self>
+    where
+        Self: Sized,
+    {
+        let _ = options;
+        Self::open(path)
+    }
+
     /// Returns statistics about the bag file.
     ///
     /// # Returns
-    /// A reference to the statistics about the bag file.
-    fn stats(&self) -> &BagStats;
+    /// A result containing a reference to the statistics about the bag file, or an error if they
+    /// are computed on first use and the file cannot be read.
+    fn stats(&self) -> RosPeekResult<&BagStats>;
 
     /// Returns a list of topics in the bag file.
     ///
@@ -82,6 +141,163 @@ pub trait BagReader: Send {
         Ok(messages)
     }
 
+    /// Reads a page of messages of a topic by their position in the topic.
+    ///
+    /// Unlike [`BagReader::read_messages_range`], no timestamps are involved, so pages are
+    /// addressed by message index alone. Readers override this to seek to the first message
+    /// rather than reading the messages before it.
+    ///
+    /// # Arguments
+    /// * `topic_name` - The name of the topic to read messages from.
+    /// * `start_index` - Index of the first message to read, counting from zero.
+    /// * `count` - Maximum number of messages to read.
+    ///
+    /// # Returns
+    /// A result containing at most `count` messages, empty if `start_index` is past the last
+    /// message, or an error.
+    fn read_messages_by_index(
+        &self,
+        topic_name: &str,
+        start_index: usize,
+        count: usize,
+    ) -> RosPeekResult<Vec<RawMessage>> {
+        self.read_messages_range(topic_name, None, None, Some(count), Some(start_index))
+    }
+
+    /// Reads the messages of several topics as a single stream ordered by timestamp.
+    ///
+    /// Messages with the same timestamp keep the order they were recorded in. Readers override
+    /// this to read the bag once rather than once per topic.
+    ///
+    /// # Arguments
+    /// * `topic_names` - The names of the topics to read messages from.
+    ///
+    /// # Returns
+    /// A result containing the messages tagged with their topic, or an error if a topic is not
+    /// found.
+    fn read_messages_multi(&self, topic_names: &[&str]) -> RosPeekResult<Vec<TaggedMessage>> {
+        let mut messages = Vec::new();
+        for (i, topic_name) in topic_names.iter().enumerate() {
+            if topic_names[..i].contains(topic_name) {
+                continue;
+            }
+            let topic: Arc<str> = Arc::from(*topic_name);
+            messages.extend(self.read_messages(topic_name)?.into_iter().map(|message| {
+                TaggedMessage {
+                    topic: topic.clone(),
+                    message,
+                }
+            }));
+        }
+        messages.sort_by_key(|m| m.message.timestamp);
+        Ok(messages)
+    }
+
+    /// Returns the first message of a topic strictly after a given timestamp.
+    ///
+    /// # Arguments
+    /// * `topic_name` - The name of the topic to look up.
+    /// * `after_ns` - The exclusive lower bound timestamp.
+    ///
+    /// # Returns
+    /// A result containing the next message if any, or an error.
+    fn next_message(&self, topic_name: &str, after_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        let mut messages = self.read_messages_range(
+            topic_name,
+            Some(after_ns.saturating_add(1)),
+            None,
+            Some(1),
+            None,
+        )?;
+        Ok(messages.pop())
+    }
+
+    /// Returns the last message of a topic strictly before a given timestamp.
+    ///
+    /// # Arguments
+    /// * `topic_name` - The name of the topic to look up.
+    /// * `before_ns` - The exclusive upper bound timestamp.
+    ///
+    /// # Returns
+    /// A result containing the previous message if any, or an error.
+    fn prev_message(&self, topic_name: &str, before_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        if before_ns == 0 {
+            return Ok(None);
+        }
+        let mut messages =
+            self.read_messages_range(topic_name, None, Some(before_ns - 1), None, None)?;
+        Ok(messages.pop())
+    }
+
+    /// Returns the message of a topic closest in time to a given timestamp.
+    ///
+    /// The earlier message wins when two messages are equally close. Readers override this to
+    /// search their indexes rather than reading the topic, so that it is fast enough to follow
+    /// a scrubbed timeline.
+    ///
+    /// # Arguments
+    /// * `topic_name` - The name of the topic to look up.
+    /// * `timestamp` - The timestamp to look up in nanoseconds.
+    ///
+    /// # Returns
+    /// A result containing the nearest message, `None` if the topic has no messages, or an error.
+    fn find_nearest(&self, topic_name: &str, timestamp: u64) -> RosPeekResult<Option<RawMessage>> {
+        let before = self.prev_message(topic_name, timestamp.saturating_add(1))?;
+        let after = self.next_message(topic_name, timestamp)?;
+        Ok([before, after]
+            .into_iter()
+            .flatten()
+            .min_by_key(|m| m.timestamp.abs_diff(timestamp)))
+    }
+
+    /// Returns the message definition embedded in the bag file for a type, if any.
+    ///
+    /// # Arguments
+    /// * `type_name` - Name of ROS message type, such as `foo_msgs/msg/Foo`.
+    ///
+    /// # Returns
+    /// A result containing the embedded schema if the bag carries one, or an error.
+    fn embedded_schema(&self, _type_name: &str) -> RosPeekResult<Option<EmbeddedSchema>> {
+        Ok(None)
+    }
+
+    /// Returns the versions of the type of a topic, ordered by time.
+    ///
+    /// A topic has several versions when its type or message definition changes between the
+    /// files of a split bag, such as after a message definition update mid-recording.
+    ///
+    /// # Arguments
+    /// * `topic` - The topic to look up, as returned by [`BagReader::topics`].
+    ///
+    /// # Returns
+    /// A result containing at least one type version, or an error.
+    fn type_versions(&self, topic: &Topic) -> RosPeekResult<Vec<TypeVersion>> {
+        let stats = self.stats()?;
+        Ok(vec![TypeVersion {
+            type_name: topic.type_name.clone(),
+            definition: self.embedded_schema(&topic.type_name)?,
+            files: vec![stats.path.clone()],
+            count: topic.count,
+            start_ns: stats.start_ns,
+            end_ns: stats.end_ns,
+        }])
+    }
+
+    /// Returns the total size in bytes of the messages of each topic, if the storage knows it
+    /// without reading the messages.
+    ///
+    /// # Returns
+    /// A result containing the sizes keyed by topic name, or `None` if the storage does not
+    /// know them.
+    fn topic_sizes(&self) -> RosPeekResult<Option<BTreeMap<String, u64>>> {
+        Ok(None)
+    }
+
+    /// Returns the signs found when opening the bag that its recording is incomplete.
+    fn incomplete_signs(&self) -> Vec<IncompleteSign> {
+        Vec::new()
+    }
+
     /// Reads messages from the bag file since a given timestamp.
     ///
     /// # Note
@@ -134,24 +350,82 @@ pub trait BagReader: Send {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BagStats {
     pub path: String,
     pub size_bytes: f64,
     pub storage_type: StorageType,
     pub duration_sec: f64,
+    /// UNIX epoch nanoseconds of the first message
+    pub start_ns: u64,
+    /// UNIX epoch nanoseconds of the last message
+    pub end_ns: u64,
     pub start_time: String,
     pub end_time: String,
+    /// Number of messages in the bag
+    pub message_count: u64,
+}
+
+impl BagStats {
+    /// Creates the statistics of a bag from the range of its message timestamps.
+    ///
+    /// Bags without messages have zero timestamps and `-` as start and end times.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the bag file.
+    /// * `storage_type` - The storage type of the bag.
+    /// * `message_count` - The number of messages in the bag.
+    /// * `start_ns` - UNIX epoch nanoseconds of the first message.
+    /// * `end_ns` - UNIX epoch nanoseconds of the last message.
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        storage_type: StorageType,
+        message_count: u64,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> Self {
+        let (start_ns, end_ns) = if message_count == 0 {
+            (0, 0)
+        } else {
+            (start_ns, end_ns)
+        };
+        let format_time = |ns| {
+            if message_count == 0 {
+                "-".to_string()
+            } else {
+                ns_to_iso(ns)
+            }
+        };
+
+        Self {
+            path: path.as_ref().display().to_string(),
+            size_bytes: size_gb(path.as_ref()),
+            storage_type,
+            duration_sec: to_duration_sec(start_ns, end_ns),
+            start_ns,
+            end_ns,
+            start_time: format_time(start_ns),
+            end_time: format_time(end_ns),
+            message_count,
+        }
+    }
+
+    /// Returns whether the bag has no messages.
+    pub fn is_empty(&self) -> bool {
+        self.message_count == 0
+    }
 }
 
 impl Display for BagStats {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(
             f,
-            "File:             {}\nBag size:         {:.3} GiB\nStorage type:     {}\nDuration:         {} s\nStart:            {}\nEnd:              {}",
+            "File:             {}\nBag size:         {:.3} GiB\nStorage type:     {}\nMessages:         {}{}\nDuration:         {} s\nStart:            {}\nEnd:              {}",
             self.path,
             self.size_bytes,
             self.storage_type,
+            self.message_count,
+            if self.is_empty() { " (empty)" } else { "" },
             self.duration_sec,
             self.start_time,
             self.end_time
@@ -159,12 +433,68 @@ impl Display for BagStats {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StorageType {
     Sqlite3,
     Mcap,
 }
 
+/// Leading bytes of MCAP files.
+const MCAP_MAGIC: &[u8] = b"\x89MCAP0\r\n";
+
+/// Leading bytes of SQLite database files.
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+
+impl StorageType {
+    /// Detects the storage of a file from its leading bytes, so that misnamed files such as a
+    /// `.bag` file containing MCAP still open, falling back to its extension.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the bag file.
+    ///
+    /// # Returns
+    /// The storage of the file, or `None` if it is neither a `.db3` nor an `.mcap` file.
+    pub fn detect<P: AsRef<Path>>(path: P) -> Option<StorageType> {
+        let path = path.as_ref();
+        let mut head = [0u8; 16];
+        let read = File::open(path)
+            .and_then(|mut file| {
+                let mut read = 0;
+                while read < head.len() {
+                    match file.read(&mut head[read..])? {
+                        0 => break,
+                        n => read += n,
+                    }
+                }
+                Ok(read)
+            })
+            .unwrap_or(0);
+        if let Some(storage) = Self::detect_bytes(&head[..read]) {
+            return Some(storage);
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("db3") => Some(StorageType::Sqlite3),
+            Some("mcap") => Some(StorageType::Mcap),
+            _ => None,
+        }
+    }
+
+    /// Detects the storage of a bag from its leading bytes, such as those of a bag piped
+    /// through the standard input.
+    ///
+    /// # Arguments
+    /// * `head` - The leading bytes of the bag.
+    pub fn detect_bytes(head: &[u8]) -> Option<StorageType> {
+        if head.starts_with(MCAP_MAGIC) {
+            Some(StorageType::Mcap)
+        } else if head.starts_with(SQLITE_MAGIC) {
+            Some(StorageType::Sqlite3)
+        } else {
+            None
+        }
+    }
+}
+
 impl Display for StorageType {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(f, "{self:?}")
@@ -179,7 +509,8 @@ impl Display for StorageType {
 /// # Returns
 /// The size of the file in GB.
 pub fn size_gb<P: AsRef<Path>>(path: P) -> f64 {
-    let size_bytes = path.as_ref().metadata().unwrap().len() as f64;
+    // bags read from a stream have no file to measure
+    let size_bytes = path.as_ref().metadata().map_or(0, |m| m.len()) as f64;
     size_bytes / (1024.0 * 1024.0 * 1024.0)
 }
 
@@ -195,10 +526,105 @@ pub fn size_gb<P: AsRef<Path>>(path: P) -> f64 {
 /// assert_eq!(ns_to_iso(1630456800000000000), "2021-09-01 00:40:00");
 /// ```
 pub fn ns_to_iso(ns: u64) -> String {
-    let secs = (ns / 1_000_000_000) as i64;
-    let nsecs = (ns % 1_000_000_000) as u32;
-    let date = DateTime::from_timestamp(secs, nsecs).unwrap();
-    date.format("%Y-%m-%d %H:%M:%S").to_string()
+    let secs = ns / 1_000_000_000;
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Parses a UTC date, optionally with a time, into nanoseconds since the Unix epoch.
+///
+/// # Arguments
+/// * `text` - A date such as `2024-01-01`, or a date and time such as `2024-01-01 12:30:00`
+///   or `2024-01-01T12:30:00`, as printed by [`ns_to_iso`].
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{iso_to_ns, ns_to_iso};
+///
+/// assert_eq!(iso_to_ns("2021-09-01 00:40:00").unwrap(), 1630456800000000000);
+/// assert_eq!(ns_to_iso(iso_to_ns("2024-02-29").unwrap()), "2024-02-29 00:00:00");
+/// assert!(iso_to_ns("2024-13-01").is_err());
+/// assert!(iso_to_ns("2023-02-29").is_err());
+/// assert!(iso_to_ns("2024-04-31").is_err());
+/// // beyond the range of nanoseconds in a u64
+/// assert!(iso_to_ns("2600-01-01").is_err());
+/// ```
+pub fn iso_to_ns(text: &str) -> RosPeekResult<u64> {
+    let invalid = || anyhow::anyhow!("Invalid date, expected YYYY-MM-DD[ HH:MM:SS]: {text}");
+    let (date, time) = text
+        .trim()
+        .split_once([' ', 'T'])
+        .unwrap_or((text.trim(), "00:00:00"));
+
+    let date: Vec<u32> = date
+        .split('-')
+        .map(|part| part.parse().map_err(|_| invalid()))
+        .collect::<RosPeekResult<_>>()?;
+    let time: Vec<u32> = time
+        .trim_end_matches('Z')
+        .split(':')
+        .map(|part| part.parse().map_err(|_| invalid()))
+        .collect::<RosPeekResult<_>>()?;
+    let (&[year, month, day], &[hour, minute, second]) = (date.as_slice(), time.as_slice()) else {
+        return Err(invalid());
+    };
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return Err(invalid());
+    }
+
+    let days = days_from_civil(year as i64, month, day);
+    let secs = days * 86_400 + (hour * 3600 + minute * 60 + second) as i64;
+    u64::try_from(secs)
+        .ok()
+        .and_then(|secs| secs.checked_mul(1_000_000_000))
+        .ok_or_else(invalid)
+}
+
+/// Returns the number of days of a month of the proleptic Gregorian calendar.
+fn days_in_month(year: u32, month: u32) -> u32 {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Converts a proleptic Gregorian date into days since the Unix epoch.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Converts days since the Unix epoch into a proleptic Gregorian `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
 }
 
 /// Converts nanoseconds to duration in seconds.
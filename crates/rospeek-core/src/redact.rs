@@ -0,0 +1,303 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Formatter},
+    str::FromStr,
+    sync::Arc,
+};
+
+use crate::{
+    BagReader, CdrDecoder, DecodePlan, Endianness, MessageSchema, Primitive, RawMessage, Record,
+    RosPeekResult, SpanKind, Stage, Topic,
+};
+
+/// Built-in anonymization preset applied by a [`Redactor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactPreset {
+    /// Drops camera topics, truncates GPS coordinates to 3 decimal places (about 100 m) and
+    /// masks strings which look like vehicle identification numbers.
+    GdprBasic,
+}
+
+impl RedactPreset {
+    /// All presets, in the order they are listed to users.
+    pub const ALL: &[RedactPreset] = &[RedactPreset::GdprBasic];
+}
+
+impl Display for RedactPreset {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            RedactPreset::GdprBasic => write!(f, "gdpr-basic"),
+        }
+    }
+}
+
+impl FromStr for RedactPreset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RedactPreset::ALL
+            .iter()
+            .find(|preset| preset.to_string() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = RedactPreset::ALL.iter().map(|p| p.to_string()).collect();
+                anyhow::anyhow!(
+                    "Unknown redaction preset: {s} (available: {})",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Message types of camera topics, which are dropped as a whole.
+const CAMERA_TYPES: &[&str] = &["sensor_msgs/msg/Image", "sensor_msgs/msg/CompressedImage"];
+
+/// Names of the fields holding GPS coordinates in degrees.
+const COORDINATE_FIELDS: &[&str] = &["latitude", "longitude"];
+
+/// Number of decimal places GPS coordinates are truncated to.
+const COORDINATE_DECIMALS: i32 = 3;
+
+/// Length of a vehicle identification number.
+const VIN_LENGTH: usize = 17;
+
+/// What a [`Redactor`] modified, per topic.
+#[derive(Debug, Clone, Default)]
+pub struct RedactionReport {
+    /// Number of messages of each topic left out as a whole.
+    pub dropped_topics: BTreeMap<String, u64>,
+    /// Number of messages of each topic whose coordinates were truncated.
+    pub coordinates: BTreeMap<String, u64>,
+    /// Number of messages of each topic whose strings were masked.
+    pub strings: BTreeMap<String, u64>,
+}
+
+impl RedactionReport {
+    /// Returns whether nothing was modified.
+    pub fn is_empty(&self) -> bool {
+        self.dropped_topics.is_empty() && self.coordinates.is_empty() && self.strings.is_empty()
+    }
+}
+
+/// Pipeline stage anonymizing messages with a [`RedactPreset`].
+///
+/// Values are patched in the raw messages without changing their size, so that redacted
+/// messages are exported without being encoded again. Messages whose type cannot be resolved
+/// fail the pipeline rather than being exported unredacted.
+///
+/// # Examples
+/// ```no_run
+/// use rospeek_core::{BagReader, BagWriter, ExportOptions, RosPeekResult, Redactor, export_bag_with};
+///
+/// fn export_redacted(reader: &dyn BagReader, writer: &mut dyn BagWriter) -> RosPeekResult<()> {
+///     let mut redactor = Redactor::new("gdpr-basic".parse()?);
+///     let options = ExportOptions {
+///         topics: redactor.select_topics(reader, &[])?,
+///         ..Default::default()
+///     };
+///     export_bag_with(reader, writer, &options, |pipeline| pipeline.stage(&mut redactor))?;
+///     println!("{:?}", redactor.report);
+///     Ok(())
+/// }
+/// ```
+pub struct Redactor {
+    preset: RedactPreset,
+    plans: HashMap<String, Arc<DecodePlan>>,
+    /// What was modified so far.
+    pub report: RedactionReport,
+}
+
+impl Redactor {
+    /// Creates a redactor applying a preset.
+    pub fn new(preset: RedactPreset) -> Self {
+        Self {
+            preset,
+            plans: HashMap::new(),
+            report: RedactionReport::default(),
+        }
+    }
+
+    /// Returns the preset applied by the redactor.
+    pub fn preset(&self) -> RedactPreset {
+        self.preset
+    }
+
+    /// Returns whether the preset leaves out all messages of a topic.
+    pub fn drops_topic(&self, topic: &Topic) -> bool {
+        match self.preset {
+            RedactPreset::GdprBasic => CAMERA_TYPES.contains(&topic.type_name.as_str()),
+        }
+    }
+
+    /// Selects the topics to export, leaving out the ones the preset drops.
+    ///
+    /// # Arguments
+    /// * `reader` - The bag reader to list topics from.
+    /// * `topics` - Topic names requested to export, or all topics if empty.
+    ///
+    /// # Returns
+    /// A result containing the names of the topics to export, which is never empty.
+    pub fn select_topics(
+        &mut self,
+        reader: &dyn BagReader,
+        topics: &[String],
+    ) -> RosPeekResult<Vec<String>> {
+        let mut selected = Vec::new();
+        for topic in reader.topics()? {
+            if !topics.is_empty() && !topics.contains(&topic.name) {
+                continue;
+            }
+            if self.drops_topic(&topic) {
+                self.report.dropped_topics.insert(topic.name, topic.count);
+            } else {
+                selected.push(topic.name);
+            }
+        }
+        if selected.is_empty() {
+            anyhow::bail!(
+                "All selected topics are dropped by redaction preset {}",
+                self.preset
+            );
+        }
+        Ok(selected)
+    }
+
+    /// Redacts a message in place.
+    ///
+    /// # Arguments
+    /// * `topic` - The topic the message belongs to.
+    /// * `message` - The message to redact.
+    pub fn redact(&mut self, topic: &Topic, message: &mut RawMessage) -> RosPeekResult<()> {
+        let plan = self.plan(&topic.type_name)?;
+
+        let mut coordinates = Vec::new();
+        let mut strings = Vec::new();
+        CdrDecoder::new(&message.data).visit_plan(&plan, &mut |span| match span.kind {
+            SpanKind::Primitive(Primitive::Double) if COORDINATE_FIELDS.contains(&span.name) => {
+                coordinates.push(span.range)
+            }
+            SpanKind::String if span.range.len() >= VIN_LENGTH => strings.push(span.range),
+            _ => {}
+        })?;
+
+        let endianness = Endianness::from(message.data.as_slice());
+        let mut truncated = false;
+        for range in coordinates {
+            truncated |= truncate_coordinate(&mut message.data[range], endianness);
+        }
+        let mut masked = false;
+        for range in strings {
+            masked |= mask_vins(&mut message.data[range]) > 0;
+        }
+
+        if truncated {
+            *self
+                .report
+                .coordinates
+                .entry(topic.name.clone())
+                .or_default() += 1;
+        }
+        if masked {
+            *self.report.strings.entry(topic.name.clone()).or_default() += 1;
+        }
+        Ok(())
+    }
+
+    fn plan(&mut self, type_name: &str) -> RosPeekResult<Arc<DecodePlan>> {
+        if let Some(plan) = self.plans.get(type_name) {
+            return Ok(plan.clone());
+        }
+        let plan = MessageSchema::try_from(type_name)
+            .and_then(|schema| DecodePlan::compile(&schema))
+            .map_err(|e| anyhow::anyhow!("Cannot redact messages of {type_name}: {e}"))?;
+        let plan = Arc::new(plan);
+        self.plans.insert(type_name.to_string(), plan.clone());
+        Ok(plan)
+    }
+}
+
+impl Stage for Redactor {
+    fn process(&mut self, mut record: Record) -> RosPeekResult<Option<Record>> {
+        if self.drops_topic(&record.topic) {
+            return Ok(None);
+        }
+        self.redact(&record.topic, &mut record.message)?;
+        // a value decoded by an earlier stage would still hold the original data
+        record.value = None;
+        Ok(Some(record))
+    }
+
+    fn name(&self) -> &str {
+        "Redaction"
+    }
+}
+
+/// Truncates a `double` coordinate in place, returning whether it changed.
+fn truncate_coordinate(bytes: &mut [u8], endianness: Endianness) -> bool {
+    let Ok(array) = <[u8; 8]>::try_from(&*bytes) else {
+        return false;
+    };
+    let value = match endianness {
+        Endianness::Big => f64::from_be_bytes(array),
+        Endianness::Little => f64::from_le_bytes(array),
+    };
+    let scale = 10f64.powi(COORDINATE_DECIMALS);
+    let truncated = (value * scale).trunc() / scale;
+    if !value.is_finite() || truncated == value {
+        return false;
+    }
+    bytes.copy_from_slice(&match endianness {
+        Endianness::Big => truncated.to_be_bytes(),
+        Endianness::Little => truncated.to_le_bytes(),
+    });
+    true
+}
+
+/// Masks vehicle identification numbers in a string in place, keeping its length.
+///
+/// A VIN is a standalone word of 17 digits and capital letters other than `I`, `O` and `Q`,
+/// containing both digits and letters.
+///
+/// # Arguments
+/// * `text` - Bytes of the string to mask.
+///
+/// # Returns
+/// The number of masked VINs.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::mask_vins;
+///
+/// let mut text = b"vin=1HGCM82633A004352, id=42".to_vec();
+/// assert_eq!(mask_vins(&mut text), 1);
+/// assert_eq!(text, b"vin=*****************, id=42");
+/// ```
+pub fn mask_vins(text: &mut [u8]) -> usize {
+    let is_vin_char =
+        |b: u8| b.is_ascii_digit() || (b.is_ascii_uppercase() && !b"IOQ".contains(&b));
+
+    let mut masked = 0;
+    let mut start = 0;
+    while start < text.len() {
+        if !text[start].is_ascii_alphanumeric() {
+            start += 1;
+            continue;
+        }
+        let end = start
+            + text[start..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric())
+                .count();
+        let word = &text[start..end];
+        if word.len() == VIN_LENGTH
+            && word.iter().all(|b| is_vin_char(*b))
+            && word.iter().any(u8::is_ascii_digit)
+            && word.iter().any(u8::is_ascii_uppercase)
+        {
+            text[start..end].fill(b'*');
+            masked += 1;
+        }
+        start = end;
+    }
+    masked
+}
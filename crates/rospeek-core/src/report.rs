@@ -0,0 +1,243 @@
+use std::{collections::HashMap, fs::File, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    BagReader, CdrDecoder, CdrError, DecodePlan, DecodeRule, DecoderRegistry, MessageSchema,
+    RawMessage, RosPeekResult, TypeVersion, decode_with_rule,
+};
+
+/// Number of leading bytes of a message kept in a [`DecodeFailure`].
+pub const FAILURE_HEAD_BYTES: usize = 64;
+
+/// Category of the reason a message failed to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// The definition of the message type was not found.
+    MissingSchema,
+    /// The message is not plain CDR, such as XCDR2.
+    UnsupportedEncapsulation,
+    /// The data ended before the message was fully decoded.
+    Truncated,
+    /// A string is not valid UTF-8.
+    InvalidUtf8,
+    /// A decoder of the [`DecoderRegistry`] failed.
+    Decoder,
+    /// Any other error.
+    Other,
+}
+
+impl FailureKind {
+    /// Classifies an error raised while decoding a message.
+    ///
+    /// # Arguments
+    /// * `error` - Error returned by the decoder.
+    /// * `rule` - Rule the message was decoded with, if any.
+    pub fn of(error: &anyhow::Error, rule: Option<&DecodeRule>) -> Self {
+        match (error.downcast_ref::<CdrError>(), rule) {
+            (Some(CdrError::UnexpectedEof), _) => FailureKind::Truncated,
+            (Some(CdrError::InvalidUtf8), _) => FailureKind::InvalidUtf8,
+            (None, Some(DecodeRule::Decoder(_))) => FailureKind::Decoder,
+            (None, _) => FailureKind::Other,
+        }
+    }
+}
+
+/// A message which failed to decode, with enough context to reproduce it in a bug report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodeFailure {
+    /// Name of the topic.
+    pub topic: String,
+    /// Name of ROS message type the message was decoded as.
+    pub type_name: String,
+    /// UNIX epoch nanoseconds of the message.
+    pub timestamp: u64,
+    /// Index of the message in the topic, as given to `--offset`.
+    pub offset: usize,
+    /// Category of the failure.
+    pub kind: FailureKind,
+    /// Message of the error.
+    pub error: String,
+    /// Size of the message in bytes.
+    pub size: usize,
+    /// First [`FAILURE_HEAD_BYTES`] bytes of the message in hex, including the CDR header.
+    pub head: String,
+}
+
+impl DecodeFailure {
+    fn new(
+        topic: &str,
+        type_name: &str,
+        offset: usize,
+        msg: &RawMessage,
+        kind: FailureKind,
+        error: String,
+    ) -> Self {
+        Self {
+            topic: topic.to_string(),
+            type_name: type_name.to_string(),
+            timestamp: msg.timestamp,
+            offset,
+            kind,
+            error,
+            size: msg.data.len(),
+            head: msg
+                .data
+                .iter()
+                .take(FAILURE_HEAD_BYTES)
+                .map(|b| format!("{b:02x}"))
+                .collect(),
+        }
+    }
+}
+
+/// Messages of a bag which failed to decode, written as JSON to be attached to bug reports.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DecodeReport {
+    /// Version of `rospeek` which decoded the messages.
+    pub version: String,
+    /// Failures ordered by topic, then by message.
+    pub failures: Vec<DecodeFailure>,
+}
+
+impl DecodeReport {
+    /// Returns whether every message was decoded.
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Writes the report as pretty-printed JSON.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the report file.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> RosPeekResult<()> {
+        let file = File::create(path.as_ref())?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+/// Decodes every message of topics, collecting the messages which fail to decode.
+///
+/// Messages are decoded one by one with the rules of the [`DecoderRegistry`], so that every
+/// failure is reported rather than only the first one.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topics` - Topic names to decode, or all topics if empty.
+/// * `since` - The start time to decode messages from.
+/// * `until` - The end time to decode messages to.
+///
+/// # Returns
+/// The report of the failures.
+pub fn collect_decode_failures(
+    reader: &dyn BagReader,
+    topics: &[String],
+    since: Option<u64>,
+    until: Option<u64>,
+) -> RosPeekResult<DecodeReport> {
+    let mut infos: Vec<_> = reader
+        .topics()?
+        .into_iter()
+        .filter(|t| topics.is_empty() || topics.contains(&t.name))
+        .collect();
+    if let Some(missing) = topics
+        .iter()
+        .find(|name| !infos.iter().any(|t| &t.name == *name))
+    {
+        anyhow::bail!("Topic not found: {missing}");
+    }
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut failures = Vec::new();
+    for info in infos {
+        let versions = reader.type_versions(&info)?;
+        let messages = reader.read_messages_range(&info.name, since, until, None, None)?;
+        failures.extend(find_decode_failures(&info.name, &versions, &messages));
+    }
+    Ok(DecodeReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        failures,
+    })
+}
+
+/// Decodes messages of a topic, returning the messages which fail to decode.
+///
+/// # Arguments
+/// * `topic` - Name of the topic.
+/// * `versions` - Type versions of the topic, ordered by time.
+/// * `messages` - Messages of the topic, whose index is reported as their offset.
+///
+/// # Returns
+/// The failures in the order of the messages.
+pub fn find_decode_failures(
+    topic: &str,
+    versions: &[TypeVersion],
+    messages: &[RawMessage],
+) -> Vec<DecodeFailure> {
+    let mut plans: HashMap<&str, Result<Option<DecodePlan>, String>> = HashMap::new();
+    let mut decoder = CdrDecoder::new(&[]);
+    let mut failures = Vec::new();
+    for (offset, msg) in messages.iter().enumerate() {
+        let Some(version) = TypeVersion::at(versions, msg.timestamp) else {
+            continue;
+        };
+        let type_name = version.type_name.as_str();
+        let rule = DecoderRegistry::global().rule_for(topic, type_name);
+        let needs_schema = rule.as_ref().is_none_or(DecodeRule::needs_schema);
+        let failure = |kind, error| DecodeFailure::new(topic, type_name, offset, msg, kind, error);
+
+        if needs_schema && let Some(name) = unsupported_encapsulation(&msg.data) {
+            failures.push(failure(
+                FailureKind::UnsupportedEncapsulation,
+                format!("Unsupported encapsulation: {name}"),
+            ));
+            continue;
+        }
+        let plan = plans.entry(type_name).or_insert_with(|| {
+            if !needs_schema {
+                return Ok(None);
+            }
+            MessageSchema::try_from(type_name)
+                .and_then(|schema| DecodePlan::compile(&schema))
+                .map(Some)
+                .map_err(|e| e.to_string())
+        });
+        match plan {
+            Ok(plan) => {
+                if let Err(e) =
+                    decode_with_rule(rule.as_ref(), &mut decoder, plan.as_ref(), &msg.data)
+                {
+                    failures.push(failure(FailureKind::of(&e, rule.as_ref()), e.to_string()));
+                }
+            }
+            Err(error) => failures.push(failure(FailureKind::MissingSchema, error.clone())),
+        }
+    }
+    failures
+}
+
+/// Returns the name of the encapsulation of CDR-encoded data if it is not plain CDR, which is
+/// the only one decoded.
+///
+/// # Arguments
+/// * `data` - CDR-encoded data including the encapsulation header.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::unsupported_encapsulation;
+///
+/// assert_eq!(unsupported_encapsulation(&[0x00, 0x01, 0x00, 0x00]), None);
+/// assert_eq!(unsupported_encapsulation(&[0x00, 0x07, 0x00, 0x00]), Some("XCDR2"));
+/// ```
+pub fn unsupported_encapsulation(data: &[u8]) -> Option<&'static str> {
+    match data.get(1)? {
+        0x00 | 0x01 => None,
+        0x02 | 0x03 => Some("PL_CDR"),
+        0x06 | 0x07 => Some("XCDR2"),
+        0x08 | 0x09 => Some("XCDR2 delimited"),
+        0x0a | 0x0b => Some("XCDR2 parameter list"),
+        _ => Some("unknown encapsulation"),
+    }
+}
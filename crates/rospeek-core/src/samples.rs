@@ -0,0 +1,116 @@
+use crate::{BagReader, RawMessage, RosPeekResult};
+
+/// Number of trigger messages read at once, so that large triggers such as point clouds are not
+/// all held in memory.
+const TRIGGER_PAGE_SIZE: usize = 64;
+
+/// Options of the sampling at the timestamps of a trigger topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleOptions {
+    /// Takes every n-th trigger message, to sub-sample a high-rate trigger.
+    pub stride: usize,
+    /// Maximum time difference in nanoseconds between a trigger and the message of a topic
+    /// sampled with it, further messages being left out.
+    pub max_offset_ns: Option<u64>,
+}
+
+impl Default for SampleOptions {
+    fn default() -> Self {
+        Self {
+            stride: 1,
+            max_offset_ns: None,
+        }
+    }
+}
+
+/// Messages of several topics nearest in time to a trigger message.
+#[derive(Debug, Clone)]
+pub struct TriggerSample {
+    /// Index of the trigger message in the trigger topic.
+    pub index: usize,
+    /// Timestamp of the trigger message in nanoseconds.
+    pub timestamp: u64,
+    /// Nearest message of each topic in the order of the topics, `None` if the topic has no
+    /// message within the maximum offset.
+    pub messages: Vec<Option<RawMessage>>,
+}
+
+impl TriggerSample {
+    /// Returns whether every topic has a message in the sample.
+    pub fn is_complete(&self) -> bool {
+        self.messages.iter().all(Option::is_some)
+    }
+}
+
+/// Samples topics at the timestamps of the messages of a trigger topic, such as every lidar
+/// frame, to build an aligned dataset.
+///
+/// Samples are passed to a callback one at a time, so that exporting a long bag does not hold
+/// all of its messages in memory.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `trigger` - Name of the trigger topic.
+/// * `topics` - Names of the topics to sample, which may include the trigger topic.
+/// * `options` - Stride and maximum time offset of the sampling.
+/// * `f` - Callback receiving each sample in time order.
+///
+/// # Returns
+/// The number of samples passed to the callback.
+pub fn sample_at_triggers<F>(
+    reader: &dyn BagReader,
+    trigger: &str,
+    topics: &[String],
+    options: &SampleOptions,
+    mut f: F,
+) -> RosPeekResult<usize>
+where
+    F: FnMut(TriggerSample) -> RosPeekResult<()>,
+{
+    let available = reader.topics()?;
+    if let Some(missing) = std::iter::once(trigger)
+        .chain(topics.iter().map(String::as_str))
+        .find(|name| !available.iter().any(|t| t.name == *name))
+    {
+        anyhow::bail!("Topic not found: {missing}");
+    }
+
+    let stride = options.stride.max(1);
+    let mut count = 0;
+    let mut start = 0;
+    loop {
+        let page = reader.read_messages_by_index(trigger, start, TRIGGER_PAGE_SIZE)?;
+        if page.is_empty() {
+            break;
+        }
+        for (index, message) in (start..).zip(page.iter()) {
+            if index % stride != 0 {
+                continue;
+            }
+            let timestamp = message.timestamp;
+            let messages = topics
+                .iter()
+                .map(|topic| {
+                    let nearest = if topic == trigger {
+                        Some(message.clone())
+                    } else {
+                        reader.find_nearest(topic, timestamp)?
+                    };
+                    Ok(nearest.filter(|m| {
+                        options
+                            .max_offset_ns
+                            .is_none_or(|max| m.timestamp.abs_diff(timestamp) <= max)
+                    }))
+                })
+                .collect::<RosPeekResult<Vec<_>>>()?;
+            f(TriggerSample {
+                index,
+                timestamp,
+                messages,
+            })?;
+            count += 1;
+        }
+        start += page.len();
+    }
+    Ok(count)
+}
@@ -1,14 +1,68 @@
+use std::collections::HashMap;
+#[cfg(feature = "schema-fs")]
 use std::{
     env,
     fs::read_to_string,
     path::{Path, PathBuf},
+    sync::{OnceLock, RwLock},
 };
 
-use anyhow::{Context, anyhow};
-use regex::Regex;
+#[cfg(feature = "schema-fs")]
+use anyhow::anyhow;
 
+#[cfg(feature = "schema-fs")]
 use crate::RosPeekResult;
 
+/// Name of a ROS interface type, such as `foo_msgs/msg/Foo`, split into its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypeName<'a> {
+    /// Package of the type, such as `foo_msgs`.
+    pub package: &'a str,
+    /// Kind of the interface, `msg` for messages.
+    pub kind: &'a str,
+    /// Name of the type within its package, such as `Foo`.
+    pub name: &'a str,
+}
+
+impl<'a> TypeName<'a> {
+    /// Parses a type name written as `<package>/<kind>/<name>`.
+    ///
+    /// # Arguments
+    /// * `type_name` - Name of ROS type, such as `foo_msgs/msg/Foo`.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_core::TypeName;
+    ///
+    /// let type_name = TypeName::parse("sensor_msgs/msg/Image").unwrap();
+    /// assert_eq!(type_name.package, "sensor_msgs");
+    /// assert_eq!(type_name.name, "Image");
+    /// assert!(type_name.is_message());
+    /// assert!(TypeName::parse("sensor_msgs/Image").is_none());
+    /// ```
+    pub fn parse(type_name: &'a str) -> Option<Self> {
+        let mut parts = type_name.split('/');
+        let (package, kind, name) = (parts.next()?, parts.next()?, parts.next()?);
+        let valid = parts.next().is_none() && [package, kind, name].iter().all(|p| !p.is_empty());
+        valid.then_some(Self {
+            package,
+            kind,
+            name,
+        })
+    }
+
+    /// Returns whether the type is a message rather than a service or an action.
+    pub fn is_message(&self) -> bool {
+        self.kind == "msg"
+    }
+}
+
+impl std::fmt::Display for TypeName<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}/{}", self.package, self.kind, self.name)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MessageSchema {
     /// Name of ROS message type, such ash `foo_msgs/msg/Foo`.
@@ -22,6 +76,8 @@ impl TryFrom<&str> for MessageSchema {
 
     /// Performs to try converting `type_name` into `MessageSchema` by looking up the corresponding IDL file.
     ///
+    /// Without the `schema-fs` feature, the lookup always fails.
+    ///
     /// # Arguments
     /// * `type_name` - Name of ROS message, such as `foo_msgs/msg/Foo`.
     ///
@@ -29,13 +85,35 @@ impl TryFrom<&str> for MessageSchema {
     /// ```
     /// let schema = rospeek_core::MessageSchema::try_from("std_msgs/msg/Float64").unwrap();
     /// ```
+    #[cfg(feature = "schema-fs")]
     fn try_from(type_name: &str) -> Result<Self, Self::Error> {
-        let idl = find_ros_idl_path(type_name)
-            .with_context(|| format!("IDL file not found for {type_name}"))?;
+        let idl = find_ros_idl_path(type_name).ok_or_else(|| IdlNotFound {
+            type_name: type_name.to_string(),
+        })?;
         parse_idl_to_schema(idl, type_name)
     }
+
+    #[cfg(not(feature = "schema-fs"))]
+    fn try_from(type_name: &str) -> Result<Self, Self::Error> {
+        anyhow::bail!("IDL lookup for {type_name} requires the `schema-fs` feature")
+    }
 }
 
+/// Error of a message type whose IDL file is not found among the installed ROS packages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlNotFound {
+    /// Name of ROS message type, such as `foo_msgs/msg/Foo`.
+    pub type_name: String,
+}
+
+impl std::fmt::Display for IdlNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IDL file not found for {}", self.type_name)
+    }
+}
+
+impl std::error::Error for IdlNotFound {}
+
 #[derive(Debug, Clone)]
 pub struct MessageField {
     /// Name of field.
@@ -78,8 +156,12 @@ impl FieldType {
     }
 }
 
+#[cfg(feature = "schema-fs")]
 /// Performs to try looking up the corresponding IDL file.
 ///
+/// Directories set with [`set_msg_paths`] are searched first, then the `share` directories of the
+/// installed ROS packages.
+///
 /// # Arguments
 /// * `type_name` - Name of ROS message type, such as `foo_msgs/msg/Foo`.
 ///
@@ -91,29 +173,42 @@ impl FieldType {
 /// assert_eq!(path, expect);
 /// ```
 pub fn find_ros_idl_path(type_name: &str) -> Option<PathBuf> {
-    let mut type_name_parts = type_name.split('/');
-    let package = type_name_parts.next()?;
-    let msg_or_srv = type_name_parts.next()?;
-    let msg_name = type_name_parts.next()?;
+    let type_name = TypeName::parse(type_name).filter(TypeName::is_message)?;
+    let relative = PathBuf::from(type_name.package)
+        .join(type_name.kind)
+        .join(format!("{}.idl", type_name.name));
 
-    if msg_or_srv != "msg" {
-        return None;
-    }
+    let msg_paths = global_msg_paths().read().unwrap().clone();
+    let ament_paths = env::var("AMENT_PREFIX_PATH").unwrap_or_default();
+    msg_paths
+        .into_iter()
+        .map(|dir| dir.join(&relative))
+        .chain(
+            ament_paths
+                .split(':')
+                .filter(|base_path| !base_path.is_empty())
+                .map(|base_path| PathBuf::from(base_path).join("share").join(&relative)),
+        )
+        .find(|candidate| candidate.exists())
+}
 
-    let ament_paths = env::var("AMENT_PREFIX_PATH").ok()?;
-    for base_path in ament_paths.split(':') {
-        let candidate = PathBuf::from(base_path)
-            .join("share")
-            .join(package)
-            .join(msg_or_srv)
-            .join(format!("{msg_name}.idl"));
-        if candidate.exists() {
-            return Some(candidate);
-        }
-    }
-    None
+#[cfg(feature = "schema-fs")]
+/// Sets directories searched for IDL files before the installed ROS packages, laid out as
+/// `<DIR>/<package>/msg/<Name>.idl`, such as those written by `rospeek schemas export`.
+///
+/// # Arguments
+/// * `paths` - Directories to search, in order.
+pub fn set_msg_paths(paths: Vec<PathBuf>) {
+    *global_msg_paths().write().unwrap() = paths;
+}
+
+#[cfg(feature = "schema-fs")]
+fn global_msg_paths() -> &'static RwLock<Vec<PathBuf>> {
+    static MSG_PATHS: OnceLock<RwLock<Vec<PathBuf>>> = OnceLock::new();
+    MSG_PATHS.get_or_init(RwLock::default)
 }
 
+#[cfg(feature = "schema-fs")]
 /// Performs to try parsing IDL and convert to `MessageSchema`.
 ///
 /// # Arguments
@@ -135,7 +230,24 @@ pub fn parse_idl_to_schema<P: AsRef<Path>>(
 ) -> RosPeekResult<MessageSchema> {
     let idl_path = read_to_filepath(idl.as_ref())?;
     let idl_str = read_to_string(&idl_path)?;
+    Ok(parse_idl_str(&idl_str, type_name))
+}
 
+/// Parses an IDL definition given as a string into `MessageSchema`.
+///
+/// # Arguments
+/// * `idl_str` - IDL definition in string.
+/// * `type_name` - Name of ROS message type, such as `foo_msgs/msg/Foo`.
+///
+/// # Examples
+/// ```
+/// let idl = "module std_msgs {\n  module msg {\n    struct Float64 {\n      double data;\n    };\n  };\n};";
+/// let schema = rospeek_core::parse_idl_str(idl, "std_msgs/msg/Float64");
+///
+/// assert_eq!(schema.fields.len(), 1);
+/// assert_eq!(schema.fields[0].name, "data".to_string());
+/// ```
+pub fn parse_idl_str(idl_str: &str, type_name: &str) -> MessageSchema {
     let lines = idl_str
         .lines()
         .map(|l| l.trim())
@@ -143,7 +255,9 @@ pub fn parse_idl_to_schema<P: AsRef<Path>>(
 
     let mut fields = Vec::new();
     for line in lines {
-        // e.g. "float64 x;" / "int32[3] values;" / "string[] names;"
+        // e.g. "double x;" / "double__3 values;" / "sequence<string, 5> names;"
+        // bounds of sequences are joined to their type, so that they do not split tokens
+        let line = line.replace(", ", ",");
         let tokens: Vec<_> = line.trim_end_matches(';').split_whitespace().collect();
         if tokens.len() != 2 {
             continue;
@@ -159,12 +273,53 @@ pub fn parse_idl_to_schema<P: AsRef<Path>>(
         });
     }
 
-    Ok(MessageSchema {
+    MessageSchema {
         type_name: type_name.to_string(),
         fields,
-    })
+    }
 }
 
+/// Splits a `ros2idl` schema embedded in a bag into the IDL definition of each type.
+///
+/// The definition of the type itself comes first, followed by the definitions of its
+/// dependencies, each separated by a line of `=` and starting with `IDL: <type_name>`.
+///
+/// # Arguments
+/// * `type_name` - Name of the ROS message type the schema describes.
+/// * `text` - Embedded schema data.
+///
+/// # Returns
+/// A map from type names to their IDL definitions.
+///
+/// # Examples
+/// ```
+/// let text = "float64 x;\n=====\nIDL: foo_msgs/msg/Bar\nint32 y;\n";
+/// let idls = rospeek_core::split_ros2idl("foo_msgs/msg/Foo", text);
+///
+/// assert_eq!(idls["foo_msgs/msg/Foo"].trim(), "float64 x;");
+/// assert_eq!(idls["foo_msgs/msg/Bar"].trim(), "int32 y;");
+/// ```
+pub fn split_ros2idl(type_name: &str, text: &str) -> HashMap<String, String> {
+    let mut idls = HashMap::new();
+    let mut name = type_name.to_string();
+    let mut body = String::new();
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        if !line.is_empty() && line.chars().all(|c| c == '=') {
+            idls.insert(std::mem::take(&mut name), std::mem::take(&mut body));
+            if let Some(header) = lines.next_if(|l| l.starts_with("IDL:")) {
+                name = header.trim_start_matches("IDL:").trim().to_string();
+            }
+            continue;
+        }
+        body.push_str(line);
+        body.push('\n');
+    }
+    idls.insert(name, body);
+    idls
+}
+
+#[cfg(feature = "schema-fs")]
 /// Reads a file path from a string, expanding any shell variables.
 ///
 /// # Arguments
@@ -188,20 +343,30 @@ pub fn read_to_filepath<P: AsRef<Path>>(path: P) -> RosPeekResult<PathBuf> {
 }
 
 fn to_field_type(s: &str) -> FieldType {
-    let re = Regex::new(r"^(?P<type>.+)__(?P<num>\d+)$").unwrap();
-    if let Some(capture) = re.captures(s) {
-        let type_name = capture.name("type").unwrap().as_str().replace("::", "/");
-        let num = capture
-            .name("num")
-            .unwrap()
-            .as_str()
-            .parse::<usize>()
-            .unwrap_or(0);
-        FieldType::Array(type_name, num)
+    // arrays are declared as `T__N`
+    if let Some((type_name, num)) = s.rsplit_once("__")
+        && !type_name.is_empty()
+        && !num.is_empty()
+        && num.bytes().all(|b| b.is_ascii_digit())
+    {
+        FieldType::Array(to_type_name(type_name), num.parse().unwrap_or(0))
     } else if s.starts_with("sequence<") && s.ends_with(">") {
         let type_name = &s["sequence<".len()..s.len() - 1];
-        FieldType::Sequence(type_name.replace("::", "/"))
+        // bounded sequences are declared as `sequence<T, N>`
+        let type_name = match type_name.rsplit_once(',') {
+            Some((type_name, bound)) if bound.bytes().all(|b| b.is_ascii_digit()) => type_name,
+            _ => type_name,
+        };
+        FieldType::Sequence(to_type_name(type_name))
     } else {
-        FieldType::Object(s.replace("::", "/"))
+        FieldType::Object(to_type_name(s))
+    }
+}
+
+/// Converts an IDL type name into a ROS type name, dropping the bound of bounded strings.
+fn to_type_name(s: &str) -> String {
+    match s.split_once('<') {
+        Some((base @ ("string" | "wstring"), _)) => base.to_string(),
+        _ => s.replace("::", "/"),
     }
 }
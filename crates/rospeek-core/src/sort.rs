@@ -0,0 +1,229 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{RawMessage, RosPeekResult};
+
+/// Key messages are ordered by, compared field by field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SortKey {
+    /// Timestamp in nanoseconds to order messages by.
+    pub time: u64,
+    /// Index of the topic, breaking ties between topics.
+    pub topic: usize,
+    /// Position of the message within its topic, breaking ties within a topic.
+    pub seq: u64,
+}
+
+/// Sorts messages which may not fit in memory.
+///
+/// Messages are buffered up to a memory budget, then sorted and spilled to a temporary file as a run.
+/// Finishing merges the runs, so only one message per run is held in memory at a time.
+pub struct ExternalSorter {
+    budget_bytes: usize,
+    buffer: Vec<(SortKey, RawMessage)>,
+    buffer_bytes: usize,
+    runs: Vec<PathBuf>,
+}
+
+impl ExternalSorter {
+    /// Creates a sorter spilling to temporary files once buffered messages exceed the budget.
+    ///
+    /// # Arguments
+    /// * `budget_bytes` - Approximate number of bytes of messages to buffer in memory.
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            budget_bytes,
+            buffer: Vec::new(),
+            buffer_bytes: 0,
+            runs: Vec::new(),
+        }
+    }
+
+    /// Adds a message to sort.
+    pub fn push(&mut self, key: SortKey, message: RawMessage) -> RosPeekResult<()> {
+        self.buffer_bytes += size_of::<(SortKey, RawMessage)>() + message.data.len();
+        self.buffer.push((key, message));
+        if self.buffer_bytes > self.budget_bytes {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of runs spilled to temporary files.
+    pub fn spilled_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Returns all messages in key order.
+    pub fn finish(mut self) -> RosPeekResult<SortedMessages> {
+        self.buffer.sort_unstable_by_key(|(key, _)| *key);
+        let buffer = std::mem::take(&mut self.buffer);
+        if self.runs.is_empty() {
+            return Ok(SortedMessages::Memory(buffer.into_iter()));
+        }
+
+        // the remaining buffer takes part in the merge as an in-memory run
+        let mut readers = Vec::with_capacity(self.runs.len());
+        for path in self.runs.iter() {
+            readers.push(BufReader::new(File::open(path)?));
+        }
+        let mut merge = Merge {
+            readers,
+            memory: buffer.into_iter(),
+            heads: Vec::new(),
+            heap: BinaryHeap::new(),
+            paths: std::mem::take(&mut self.runs),
+        };
+        merge.init()?;
+        Ok(SortedMessages::Merge(Box::new(merge)))
+    }
+
+    fn spill(&mut self) -> RosPeekResult<()> {
+        static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+
+        self.buffer.sort_unstable_by_key(|(key, _)| *key);
+        let path = std::env::temp_dir().join(format!(
+            "rospeek-sort-{}-{}.run",
+            std::process::id(),
+            NEXT_RUN.fetch_add(1, Ordering::Relaxed)
+        ));
+        self.runs.push(path.clone());
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for (key, message) in self.buffer.drain(..) {
+            write_entry(&mut writer, &key, &message)?;
+        }
+        writer.flush()?;
+        self.buffer_bytes = 0;
+        Ok(())
+    }
+}
+
+impl Drop for ExternalSorter {
+    fn drop(&mut self) {
+        for path in self.runs.iter() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Messages returned by [`ExternalSorter::finish`] in key order.
+pub enum SortedMessages {
+    Memory(std::vec::IntoIter<(SortKey, RawMessage)>),
+    Merge(Box<Merge>),
+}
+
+impl Iterator for SortedMessages {
+    type Item = RosPeekResult<(SortKey, RawMessage)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            SortedMessages::Memory(iter) => iter.next().map(Ok),
+            SortedMessages::Merge(merge) => merge.next(),
+        }
+    }
+}
+
+/// K-way merge of sorted runs.
+pub struct Merge {
+    readers: Vec<BufReader<File>>,
+    /// In-memory run, which has the index `readers.len()`
+    memory: std::vec::IntoIter<(SortKey, RawMessage)>,
+    /// Next message of each run
+    heads: Vec<Option<RawMessage>>,
+    heap: BinaryHeap<Reverse<(SortKey, usize)>>,
+    paths: Vec<PathBuf>,
+}
+
+impl Merge {
+    fn init(&mut self) -> RosPeekResult<()> {
+        self.heads = vec![None; self.readers.len() + 1];
+        for run in 0..=self.readers.len() {
+            self.advance(run)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the next message of a run into its head.
+    fn advance(&mut self, run: usize) -> RosPeekResult<()> {
+        let entry = match self.readers.get_mut(run) {
+            Some(reader) => read_entry(reader)?,
+            None => self.memory.next(),
+        };
+        if let Some((key, message)) = entry {
+            self.heads[run] = Some(message);
+            self.heap.push(Reverse((key, run)));
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Option<RosPeekResult<(SortKey, RawMessage)>> {
+        let Reverse((key, run)) = self.heap.pop()?;
+        let message = self.heads[run].take()?;
+        if let Err(e) = self.advance(run) {
+            return Some(Err(e));
+        }
+        Some(Ok((key, message)))
+    }
+}
+
+impl Drop for Merge {
+    fn drop(&mut self) {
+        for path in self.paths.iter() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn write_entry(writer: &mut impl Write, key: &SortKey, message: &RawMessage) -> RosPeekResult<()> {
+    writer.write_all(&key.time.to_le_bytes())?;
+    writer.write_all(&(key.topic as u64).to_le_bytes())?;
+    writer.write_all(&key.seq.to_le_bytes())?;
+    writer.write_all(&message.timestamp.to_le_bytes())?;
+    writer.write_all(&message.topic_id.to_le_bytes())?;
+    writer.write_all(&(message.data.len() as u64).to_le_bytes())?;
+    writer.write_all(&message.data)?;
+    Ok(())
+}
+
+fn read_entry(reader: &mut impl Read) -> RosPeekResult<Option<(SortKey, RawMessage)>> {
+    let mut time = [0u8; 8];
+    match reader.read_exact(&mut time) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let mut u64_buf = [0u8; 8];
+    let mut u16_buf = [0u8; 2];
+
+    reader.read_exact(&mut u64_buf)?;
+    let topic = u64::from_le_bytes(u64_buf) as usize;
+    reader.read_exact(&mut u64_buf)?;
+    let seq = u64::from_le_bytes(u64_buf);
+    reader.read_exact(&mut u64_buf)?;
+    let timestamp = u64::from_le_bytes(u64_buf);
+    reader.read_exact(&mut u16_buf)?;
+    let topic_id = u16::from_le_bytes(u16_buf);
+    reader.read_exact(&mut u64_buf)?;
+    let mut data = vec![0u8; u64::from_le_bytes(u64_buf) as usize];
+    reader.read_exact(&mut data)?;
+
+    Ok(Some((
+        SortKey {
+            time: u64::from_le_bytes(time),
+            topic,
+            seq,
+        },
+        RawMessage {
+            timestamp,
+            topic_id,
+            data,
+        },
+    )))
+}
@@ -0,0 +1,191 @@
+use std::f64::consts::PI;
+
+/// Window applied to the samples before the FFT, trading frequency resolution for less leakage
+/// of a peak into the neighboring frequencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpectrumWindow {
+    Rectangular,
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl SpectrumWindow {
+    pub const ALL: [SpectrumWindow; 4] = [
+        SpectrumWindow::Rectangular,
+        SpectrumWindow::Hann,
+        SpectrumWindow::Hamming,
+        SpectrumWindow::Blackman,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SpectrumWindow::Rectangular => "Rectangular",
+            SpectrumWindow::Hann => "Hann",
+            SpectrumWindow::Hamming => "Hamming",
+            SpectrumWindow::Blackman => "Blackman",
+        }
+    }
+
+    /// Returns the weight of the `i`-th of `n` samples.
+    fn weight(self, i: usize, n: usize) -> f64 {
+        if n < 2 {
+            return 1.0;
+        }
+        let phase = 2.0 * PI * i as f64 / (n - 1) as f64;
+        match self {
+            SpectrumWindow::Rectangular => 1.0,
+            SpectrumWindow::Hann => 0.5 - 0.5 * phase.cos(),
+            SpectrumWindow::Hamming => 0.54 - 0.46 * phase.cos(),
+            SpectrumWindow::Blackman => 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+        }
+    }
+}
+
+/// Single-sided amplitude spectrum of a series, from 0 Hz to the Nyquist frequency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    /// Rate the series was resampled at in Hz.
+    pub sample_rate_hz: f64,
+    /// Width of a frequency bin in Hz.
+    pub resolution_hz: f64,
+    /// Amplitude of each frequency bin, in the unit of the series.
+    pub amplitudes: Vec<f64>,
+}
+
+impl Spectrum {
+    /// Returns the center frequency of a bin in Hz.
+    pub fn frequency_hz(&self, bin: usize) -> f64 {
+        bin as f64 * self.resolution_hz
+    }
+
+    /// Returns the frequency in Hz and amplitude of the highest peak, ignoring the 0 Hz bin.
+    pub fn peak(&self) -> Option<(f64, f64)> {
+        self.amplitudes
+            .iter()
+            .enumerate()
+            .skip(1)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(bin, amplitude)| (self.frequency_hz(bin), *amplitude))
+    }
+}
+
+/// Computes the amplitude spectrum of a series, such as the acceleration of an IMU, to find the
+/// frequencies of vibrations and oscillations.
+///
+/// As messages are rarely evenly spaced, the series is first resampled at its mean rate by
+/// linear interpolation. Its mean is removed so that the offset does not hide the peaks, and it
+/// is zero-padded to a power of two.
+///
+/// # Arguments
+/// * `samples` - Pairs of timestamp in nanoseconds and value, sorted by timestamp.
+/// * `window` - Window applied to the samples.
+///
+/// # Returns
+/// The spectrum, or `None` with fewer than 4 samples or a series without duration.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{SpectrumWindow, spectrum};
+///
+/// // 2.0 * sin(2π * 10 Hz * t) sampled at 100 Hz
+/// let samples: Vec<(u64, f64)> = (0..512)
+///     .map(|i| {
+///         let t = i as f64 / 100.0;
+///         ((t * 1e9) as u64, 2.0 * (2.0 * std::f64::consts::PI * 10.0 * t).sin())
+///     })
+///     .collect();
+/// let spectrum = spectrum(&samples, SpectrumWindow::Hann).unwrap();
+/// let (frequency_hz, amplitude) = spectrum.peak().unwrap();
+/// assert!((frequency_hz - 10.0).abs() < spectrum.resolution_hz);
+/// assert!((amplitude - 2.0).abs() < 0.3);
+/// ```
+pub fn spectrum(samples: &[(u64, f64)], window: SpectrumWindow) -> Option<Spectrum> {
+    let n = samples.len();
+    let (first, last) = (samples.first()?.0, samples.last()?.0);
+    if n < 4 || last <= first {
+        return None;
+    }
+    let period_ns = (last - first) as f64 / (n - 1) as f64;
+    let sample_rate_hz = 1e9 / period_ns;
+
+    // resample on an even grid
+    let mut values = Vec::with_capacity(n);
+    let mut j = 0;
+    for i in 0..n {
+        let t = first as f64 + i as f64 * period_ns;
+        while j + 2 < n && (samples[j + 1].0 as f64) < t {
+            j += 1;
+        }
+        let ((t0, v0), (t1, v1)) = (samples[j], samples[j + 1]);
+        let ratio = if t1 > t0 {
+            ((t - t0 as f64) / (t1 - t0) as f64).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        values.push(v0 + (v1 - v0) * ratio);
+    }
+
+    let mean = values.iter().sum::<f64>() / n as f64;
+    let weights: Vec<f64> = (0..n).map(|i| window.weight(i, n)).collect();
+    let gain: f64 = weights.iter().sum();
+    let size = n.next_power_of_two();
+    let mut buffer = vec![(0.0, 0.0); size];
+    for (i, (value, weight)) in values.iter().zip(&weights).enumerate() {
+        buffer[i].0 = (value - mean) * weight;
+    }
+    fft(&mut buffer);
+
+    let amplitudes = buffer[..=size / 2]
+        .iter()
+        .enumerate()
+        .map(|(bin, (re, im))| {
+            // energy of the negative frequencies is folded into the positive ones
+            let scale = if bin == 0 || bin == size / 2 {
+                1.0
+            } else {
+                2.0
+            };
+            scale * re.hypot(*im) / gain
+        })
+        .collect();
+    Some(Spectrum {
+        sample_rate_hz,
+        resolution_hz: sample_rate_hz / size as f64,
+        amplitudes,
+    })
+}
+
+/// Transforms complex values in place with an iterative radix-2 FFT.
+///
+/// The length of `buffer` must be a power of two.
+fn fft(buffer: &mut [(f64, f64)]) {
+    let n = buffer.len();
+    if n < 2 {
+        return;
+    }
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            buffer.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (re, im) = buffer[start + k + len / 2];
+                let twiddled = (re * cos - im * sin, re * sin + im * cos);
+                let even = buffer[start + k];
+                buffer[start + k] = (even.0 + twiddled.0, even.1 + twiddled.1);
+                buffer[start + k + len / 2] = (even.0 - twiddled.0, even.1 - twiddled.1);
+            }
+        }
+        len *= 2;
+    }
+}
@@ -0,0 +1,354 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    BagReader, BagStats, EmbeddedSchema, IncompleteSign, RawMessage, RosPeekResult, TaggedMessage,
+    Topic, TypeVersion,
+};
+
+/// A file of a split bag along with its reader.
+struct Split {
+    path: PathBuf,
+    reader: Box<dyn BagReader>,
+    topics: Vec<Topic>,
+    stats: BagStats,
+}
+
+impl Split {
+    fn topic(&self, topic_name: &str) -> Option<&Topic> {
+        self.topics.iter().find(|t| t.name == topic_name)
+    }
+}
+
+/// Reads a bag recorded into several files as a single bag.
+///
+/// The type of a topic may change between files when a message definition is updated
+/// mid-recording, so each topic has one or more [`TypeVersion`]s.
+///
+/// # Examples
+/// ```no_run
+/// use rospeek_core::{BagReader, RosPeekResult, SplitReader};
+///
+/// fn print_type_changes(splits: Vec<(std::path::PathBuf, Box<dyn BagReader>)>) -> RosPeekResult<()> {
+///     let reader = SplitReader::new("my_bag", splits)?;
+///     for (topic, versions) in reader.type_changes()? {
+///         for version in versions {
+///             println!("{topic}: {} in {}", version.type_name, version.files.join(", "));
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct SplitReader {
+    splits: Vec<Split>,
+    stats: BagStats,
+}
+
+impl SplitReader {
+    /// Creates a reader over the files of a split bag.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the bag directory.
+    /// * `splits` - Pairs of the path and the reader of each file, in any order.
+    ///
+    /// # Returns
+    /// A result containing the reader, with files ordered by their first message.
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        splits: Vec<(PathBuf, Box<dyn BagReader>)>,
+    ) -> RosPeekResult<Self> {
+        if splits.is_empty() {
+            anyhow::bail!("No bag files found in {}", path.as_ref().display());
+        }
+
+        let mut splits = splits
+            .into_iter()
+            .map(|(path, reader)| {
+                let topics = reader.topics()?;
+                let stats = reader.stats()?.clone();
+                Ok(Split {
+                    path,
+                    reader,
+                    topics,
+                    stats,
+                })
+            })
+            .collect::<RosPeekResult<Vec<_>>>()?;
+        // empty files have zero timestamps, so keep them after the files they follow
+        splits.sort_by_key(|split| (split.stats.is_empty(), split.stats.start_ns));
+
+        let recorded = || splits.iter().map(|s| &s.stats).filter(|s| !s.is_empty());
+        let mut stats = BagStats::new(
+            path.as_ref(),
+            splits[0].stats.storage_type,
+            recorded().map(|s| s.message_count).sum(),
+            recorded().map(|s| s.start_ns).min().unwrap_or_default(),
+            recorded().map(|s| s.end_ns).max().unwrap_or_default(),
+        );
+        stats.size_bytes = splits.iter().map(|s| s.stats.size_bytes).sum();
+
+        Ok(Self { splits, stats })
+    }
+
+    /// Returns the paths of the files, ordered by their first message.
+    pub fn files(&self) -> Vec<&Path> {
+        self.splits.iter().map(|s| s.path.as_path()).collect()
+    }
+
+    /// Returns the time range of each file with messages, ordered by their first message.
+    ///
+    /// # Returns
+    /// Triples of the path, first and last message timestamps in nanoseconds of each file.
+    pub fn file_ranges(&self) -> Vec<(&Path, u64, u64)> {
+        self.splits
+            .iter()
+            .filter(|s| !s.stats.is_empty())
+            .map(|s| (s.path.as_path(), s.stats.start_ns, s.stats.end_ns))
+            .collect()
+    }
+
+    /// Returns the topics whose type changes between files, along with their type versions.
+    ///
+    /// # Returns
+    /// Pairs of topic name and type versions, sorted by topic name.
+    pub fn type_changes(&self) -> RosPeekResult<Vec<(String, Vec<TypeVersion>)>> {
+        let mut changes = Vec::new();
+        for topic in self.topics()? {
+            let versions = self.type_versions(&topic)?;
+            if versions.len() > 1 {
+                changes.push((topic.name, versions));
+            }
+        }
+        Ok(changes)
+    }
+}
+
+impl BagReader for SplitReader {
+    fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!(
+            "Split bags are opened from the readers of their files: {}",
+            path.as_ref().display()
+        )
+    }
+
+    fn stats(&self) -> RosPeekResult<&BagStats> {
+        Ok(&self.stats)
+    }
+
+    fn topics(&self) -> RosPeekResult<Vec<Topic>> {
+        let mut merged: BTreeMap<String, Topic> = BTreeMap::new();
+        for topic in self.splits.iter().flat_map(|s| s.topics.iter()) {
+            merged
+                .entry(topic.name.clone())
+                .and_modify(|merged| {
+                    merged.count += topic.count;
+                    // report the type of the latest recording
+                    merged.type_name = topic.type_name.clone();
+                })
+                .or_insert_with(|| topic.clone());
+        }
+        Ok(merged.into_values().collect())
+    }
+
+    fn read_messages(&self, topic_name: &str) -> RosPeekResult<Vec<RawMessage>> {
+        self.read_messages_range(topic_name, None, None, None, None)
+    }
+
+    fn read_messages_range(
+        &self,
+        topic_name: &str,
+        start_ns: Option<u64>,
+        end_ns: Option<u64>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> RosPeekResult<Vec<RawMessage>> {
+        let mut results = Vec::new();
+        let mut found = false;
+        let mut skip = offset.unwrap_or_default();
+
+        for split in self.splits.iter() {
+            if split.topic(topic_name).is_none() {
+                continue;
+            }
+            found = true;
+            if limit.is_some_and(|limit| results.len() >= limit) {
+                break;
+            }
+
+            // messages skipped by the offset may be in this file, so read them as well
+            let wanted = limit.map(|limit| limit - results.len() + skip);
+            let mut messages = split
+                .reader
+                .read_messages_range(topic_name, start_ns, end_ns, wanted, None)?;
+            let skipped = skip.min(messages.len());
+            messages.drain(..skipped);
+            skip -= skipped;
+            results.extend(messages);
+        }
+        if !found {
+            anyhow::bail!("Topic not found: {topic_name}");
+        }
+        if let Some(limit) = limit {
+            results.truncate(limit);
+        }
+
+        Ok(results)
+    }
+
+    fn read_messages_by_index(
+        &self,
+        topic_name: &str,
+        start_index: usize,
+        count: usize,
+    ) -> RosPeekResult<Vec<RawMessage>> {
+        let mut results = Vec::new();
+        let mut found = false;
+        let mut skip = start_index;
+
+        for split in self.splits.iter() {
+            let Some(topic) = split.topic(topic_name) else {
+                continue;
+            };
+            found = true;
+            if results.len() >= count {
+                break;
+            }
+
+            // the counts of the files locate the first message without reading the files before it
+            let in_file = topic.count as usize;
+            if skip >= in_file {
+                skip -= in_file;
+                continue;
+            }
+            let messages =
+                split
+                    .reader
+                    .read_messages_by_index(topic_name, skip, count - results.len())?;
+            skip = 0;
+            results.extend(messages);
+        }
+        if !found {
+            anyhow::bail!("Topic not found: {topic_name}");
+        }
+
+        Ok(results)
+    }
+
+    fn read_messages_multi(&self, topic_names: &[&str]) -> RosPeekResult<Vec<TaggedMessage>> {
+        if let Some(missing) = topic_names
+            .iter()
+            .find(|name| !self.splits.iter().any(|s| s.topic(name).is_some()))
+        {
+            anyhow::bail!("Topic not found: {missing}");
+        }
+
+        let mut results = Vec::new();
+        for split in self.splits.iter() {
+            let names: Vec<&str> = topic_names
+                .iter()
+                .copied()
+                .filter(|name| split.topic(name).is_some())
+                .collect();
+            if !names.is_empty() {
+                results.extend(split.reader.read_messages_multi(&names)?);
+            }
+        }
+        // files are ordered by their start, but their time ranges may overlap
+        results.sort_by_key(|m| m.message.timestamp);
+
+        Ok(results)
+    }
+
+    fn next_message(&self, topic_name: &str, after_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        for split in self.splits.iter().filter(|s| s.topic(topic_name).is_some()) {
+            if let Some(message) = split.reader.next_message(topic_name, after_ns)? {
+                return Ok(Some(message));
+            }
+        }
+        Ok(None)
+    }
+
+    fn prev_message(&self, topic_name: &str, before_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        for split in self.splits.iter().rev() {
+            if split.topic(topic_name).is_none() {
+                continue;
+            }
+            if let Some(message) = split.reader.prev_message(topic_name, before_ns)? {
+                return Ok(Some(message));
+            }
+        }
+        Ok(None)
+    }
+
+    fn embedded_schema(&self, type_name: &str) -> RosPeekResult<Option<EmbeddedSchema>> {
+        // prefer the definition of the latest recording, like the merged topic type
+        for split in self.splits.iter().rev() {
+            if let Some(schema) = split.reader.embedded_schema(type_name)? {
+                return Ok(Some(schema));
+            }
+        }
+        Ok(None)
+    }
+
+    fn type_versions(&self, topic: &Topic) -> RosPeekResult<Vec<TypeVersion>> {
+        let mut versions: Vec<TypeVersion> = Vec::new();
+        for split in self.splits.iter() {
+            let Some(recorded) = split.topic(&topic.name) else {
+                continue;
+            };
+            let definition = split.reader.embedded_schema(&recorded.type_name)?;
+            let stats = &split.stats;
+            let file = split.path.display().to_string();
+
+            if let Some(last) = versions.last_mut()
+                && last.type_name == recorded.type_name
+                && last.definition.as_ref().map(|d| &d.data) == definition.as_ref().map(|d| &d.data)
+            {
+                last.files.push(file);
+                last.count += recorded.count;
+                if !stats.is_empty() {
+                    last.end_ns = last.end_ns.max(stats.end_ns);
+                }
+                continue;
+            }
+            versions.push(TypeVersion {
+                type_name: recorded.type_name.clone(),
+                definition,
+                files: vec![file],
+                count: recorded.count,
+                start_ns: stats.start_ns,
+                end_ns: stats.end_ns,
+            });
+        }
+        if versions.is_empty() {
+            anyhow::bail!("Topic not found: {}", topic.name);
+        }
+        Ok(versions)
+    }
+
+    fn incomplete_signs(&self) -> Vec<IncompleteSign> {
+        self.splits
+            .iter()
+            .flat_map(|split| split.reader.incomplete_signs())
+            .collect()
+    }
+
+    fn topic_sizes(&self) -> RosPeekResult<Option<BTreeMap<String, u64>>> {
+        let mut merged: BTreeMap<String, u64> = BTreeMap::new();
+        for split in self.splits.iter() {
+            // sizes are only known if every file knows them
+            let Some(sizes) = split.reader.topic_sizes()? else {
+                return Ok(None);
+            };
+            for (name, bytes) in sizes {
+                *merged.entry(name).or_default() += bytes;
+            }
+        }
+        Ok(Some(merged))
+    }
+}
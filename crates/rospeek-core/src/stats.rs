@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+
+use crate::{BagReader, MessageOrder, Pipeline, RawMessage, Record, RosPeekResult, Sink, Topic};
+
+/// Frequency and size statistics of a topic, accumulated message by message.
+///
+/// Statistics accumulated separately, e.g. on different threads or over different ranges of
+/// messages, can be combined with [`TopicStats::merge`].
+///
+/// # Examples
+/// ```
+/// use rospeek_core::{RawMessage, TopicStats};
+///
+/// let message = |timestamp: u64| RawMessage { timestamp, topic_id: 1, data: vec![0; 100] };
+///
+/// let mut first = TopicStats::default();
+/// first.accumulate(&message(0));
+/// first.accumulate(&message(100_000_000));
+///
+/// let mut second = TopicStats::default();
+/// second.accumulate(&message(200_000_000));
+///
+/// first.merge(&second);
+/// assert_eq!(first.count, 3);
+/// assert_eq!(first.frequency_hz(), Some(10.0));
+/// assert_eq!(first.bandwidth_bps(), Some(1500.0));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TopicStats {
+    /// Number of messages.
+    pub count: u64,
+    /// Total size of messages in bytes.
+    pub bytes: u64,
+    /// Size of the smallest message in bytes.
+    pub min_size: u64,
+    /// Size of the largest message in bytes.
+    pub max_size: u64,
+    /// Timestamp of the earliest message in nanoseconds.
+    pub first_ns: u64,
+    /// Timestamp of the latest message in nanoseconds.
+    pub last_ns: u64,
+}
+
+impl TopicStats {
+    /// Adds a message to the statistics.
+    pub fn accumulate(&mut self, message: &RawMessage) {
+        let size = message.data.len() as u64;
+        if self.count == 0 {
+            self.min_size = size;
+            self.max_size = size;
+            self.first_ns = message.timestamp;
+            self.last_ns = message.timestamp;
+        } else {
+            self.min_size = self.min_size.min(size);
+            self.max_size = self.max_size.max(size);
+            self.first_ns = self.first_ns.min(message.timestamp);
+            self.last_ns = self.last_ns.max(message.timestamp);
+        }
+        self.count += 1;
+        self.bytes += size;
+    }
+
+    /// Combines statistics accumulated separately into these.
+    pub fn merge(&mut self, other: &TopicStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        self.count += other.count;
+        self.bytes += other.bytes;
+        self.min_size = self.min_size.min(other.min_size);
+        self.max_size = self.max_size.max(other.max_size);
+        self.first_ns = self.first_ns.min(other.first_ns);
+        self.last_ns = self.last_ns.max(other.last_ns);
+    }
+
+    /// Returns the time between the earliest and latest messages in seconds.
+    pub fn duration_sec(&self) -> f64 {
+        (self.last_ns - self.first_ns) as f64 / 1_000_000_000.0
+    }
+
+    /// Returns the average number of messages per second, or `None` with less than two messages.
+    pub fn frequency_hz(&self) -> Option<f64> {
+        let duration = self.duration_sec();
+        (self.count > 1 && duration > 0.0).then(|| (self.count - 1) as f64 / duration)
+    }
+
+    /// Returns the average number of bytes per second, or `None` with less than two messages.
+    pub fn bandwidth_bps(&self) -> Option<f64> {
+        let duration = self.duration_sec();
+        (self.count > 1 && duration > 0.0).then(|| self.bytes as f64 / duration)
+    }
+
+    /// Returns the average message size in bytes, or `None` without messages.
+    pub fn mean_size(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.bytes as f64 / self.count as f64)
+    }
+}
+
+/// Range of message sizes of a [`SizeHistogram`] and the number of messages in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeBucket {
+    /// Smallest size of the bucket in bytes.
+    pub min_size: u64,
+    /// Largest size of the bucket in bytes, inclusive.
+    pub max_size: u64,
+    /// Number of messages in the bucket.
+    pub count: u64,
+}
+
+/// Distribution of the message sizes of a topic in power-of-two buckets.
+///
+/// Streams mixing resolutions or compression levels show several peaks.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::SizeHistogram;
+///
+/// let mut histogram = SizeHistogram::default();
+/// [100, 120, 5000].into_iter().for_each(|size| histogram.add(size));
+///
+/// let buckets = histogram.buckets();
+/// assert_eq!((buckets[0].min_size, buckets[0].max_size, buckets[0].count), (64, 127, 2));
+/// assert_eq!(buckets.last().unwrap().count, 1);
+/// assert_eq!(histogram.total(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeHistogram {
+    /// Number of messages per bit length of their size.
+    counts: Vec<u64>,
+}
+
+impl SizeHistogram {
+    /// Adds a message size in bytes.
+    pub fn add(&mut self, size: u64) {
+        let bucket = (u64::BITS - size.leading_zeros()) as usize;
+        if self.counts.len() <= bucket {
+            self.counts.resize(bucket + 1, 0);
+        }
+        self.counts[bucket] += 1;
+    }
+
+    /// Adds a message.
+    pub fn accumulate(&mut self, message: &RawMessage) {
+        self.add(message.data.len() as u64);
+    }
+
+    /// Combines a histogram accumulated separately into this one.
+    pub fn merge(&mut self, other: &SizeHistogram) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+    }
+
+    /// Returns the number of messages.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the buckets from the smallest to the largest non-empty one, including empty
+    /// buckets in between.
+    pub fn buckets(&self) -> Vec<SizeBucket> {
+        let Some(first) = self.counts.iter().position(|count| *count > 0) else {
+            return Vec::new();
+        };
+        let last = self
+            .counts
+            .iter()
+            .rposition(|count| *count > 0)
+            .unwrap_or(first);
+        (first..=last)
+            .map(|bucket| {
+                let (min_size, max_size) = match bucket {
+                    0 => (0, 0),
+                    64 => (1 << 63, u64::MAX),
+                    _ => (1 << (bucket - 1), (1 << bucket) - 1),
+                };
+                SizeBucket {
+                    min_size,
+                    max_size,
+                    count: self.counts[bucket],
+                }
+            })
+            .collect()
+    }
+}
+
+/// Computes the message size distribution of topics.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topics` - Topic names to compute distributions of, or all topics if empty.
+///
+/// # Returns
+/// Pairs of topic name and size distribution, sorted by topic name.
+pub fn size_histograms(
+    reader: &dyn BagReader,
+    topics: &[String],
+) -> RosPeekResult<Vec<(String, SizeHistogram)>> {
+    let mut sink = SizeHistogramSink::default();
+    Pipeline::new(reader)
+        .topics(topics)
+        .order(MessageOrder::Topic)
+        .sink(&mut sink)?;
+    Ok(sink.histograms.into_iter().collect())
+}
+
+/// Pipeline sink accumulating the message size distribution of each topic.
+#[derive(Debug, Default)]
+struct SizeHistogramSink {
+    histograms: BTreeMap<String, SizeHistogram>,
+}
+
+impl Sink for SizeHistogramSink {
+    fn add_topic(&mut self, topic: &Topic) -> RosPeekResult<()> {
+        self.histograms
+            .insert(topic.name.clone(), SizeHistogram::default());
+        Ok(())
+    }
+
+    fn write(&mut self, record: Record) -> RosPeekResult<()> {
+        if let Some(histogram) = self.histograms.get_mut(&record.topic.name) {
+            histogram.accumulate(&record.message);
+        }
+        Ok(())
+    }
+}
+
+/// Computes the statistics of topics.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `topics` - Topic names to compute statistics of, or all topics if empty.
+///
+/// # Returns
+/// Pairs of topic name and statistics, sorted by topic name.
+pub fn topic_stats(
+    reader: &dyn BagReader,
+    topics: &[String],
+) -> RosPeekResult<Vec<(String, TopicStats)>> {
+    let mut sink = StatsSink::default();
+    Pipeline::new(reader)
+        .topics(topics)
+        .order(MessageOrder::Topic)
+        .sink(&mut sink)?;
+    Ok(sink.stats.into_iter().collect())
+}
+
+/// Pipeline sink accumulating the statistics of each topic.
+#[derive(Debug, Clone, Default)]
+pub struct StatsSink {
+    /// Statistics of each topic, including topics without messages.
+    pub stats: BTreeMap<String, TopicStats>,
+}
+
+impl Sink for StatsSink {
+    fn add_topic(&mut self, topic: &Topic) -> RosPeekResult<()> {
+        self.stats.insert(topic.name.clone(), TopicStats::default());
+        Ok(())
+    }
+
+    fn write(&mut self, record: Record) -> RosPeekResult<()> {
+        if let Some(stats) = self.stats.get_mut(&record.topic.name) {
+            stats.accumulate(&record.message);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    collections::BTreeMap,
+    sync::{OnceLock, RwLock},
+};
+
+use serde::Deserialize;
+
+use crate::RosPeekResult;
+
+/// Units of the fields of well-known message types, as `(type, field, unit)`.
+///
+/// A field stands for itself and the fields nested under it, so that `linear_acceleration`
+/// covers `linear_acceleration.x`.
+const BUILTIN_UNITS: &[(&str, &str, &str)] = &[
+    ("sensor_msgs/msg/Imu", "linear_acceleration", "m/s²"),
+    ("sensor_msgs/msg/Imu", "angular_velocity", "rad/s"),
+    ("sensor_msgs/msg/Temperature", "temperature", "°C"),
+    ("sensor_msgs/msg/Temperature", "variance", "°C²"),
+    ("sensor_msgs/msg/FluidPressure", "fluid_pressure", "Pa"),
+    ("sensor_msgs/msg/FluidPressure", "variance", "Pa²"),
+    ("sensor_msgs/msg/Illuminance", "illuminance", "lx"),
+    ("sensor_msgs/msg/MagneticField", "magnetic_field", "T"),
+    ("sensor_msgs/msg/BatteryState", "voltage", "V"),
+    ("sensor_msgs/msg/BatteryState", "temperature", "°C"),
+    ("sensor_msgs/msg/BatteryState", "current", "A"),
+    ("sensor_msgs/msg/BatteryState", "charge", "Ah"),
+    ("sensor_msgs/msg/BatteryState", "capacity", "Ah"),
+    ("sensor_msgs/msg/BatteryState", "design_capacity", "Ah"),
+    ("sensor_msgs/msg/BatteryState", "cell_voltage", "V"),
+    ("sensor_msgs/msg/BatteryState", "cell_temperature", "°C"),
+    ("sensor_msgs/msg/NavSatFix", "latitude", "°"),
+    ("sensor_msgs/msg/NavSatFix", "longitude", "°"),
+    ("sensor_msgs/msg/NavSatFix", "altitude", "m"),
+    ("sensor_msgs/msg/NavSatFix", "position_covariance", "m²"),
+    ("sensor_msgs/msg/Range", "field_of_view", "rad"),
+    ("sensor_msgs/msg/Range", "min_range", "m"),
+    ("sensor_msgs/msg/Range", "max_range", "m"),
+    ("sensor_msgs/msg/Range", "range", "m"),
+    ("sensor_msgs/msg/LaserScan", "angle_min", "rad"),
+    ("sensor_msgs/msg/LaserScan", "angle_max", "rad"),
+    ("sensor_msgs/msg/LaserScan", "angle_increment", "rad"),
+    ("sensor_msgs/msg/LaserScan", "time_increment", "s"),
+    ("sensor_msgs/msg/LaserScan", "scan_time", "s"),
+    ("sensor_msgs/msg/LaserScan", "range_min", "m"),
+    ("sensor_msgs/msg/LaserScan", "range_max", "m"),
+    ("sensor_msgs/msg/LaserScan", "ranges", "m"),
+    ("sensor_msgs/msg/JointState", "position", "rad"),
+    ("sensor_msgs/msg/JointState", "velocity", "rad/s"),
+    ("sensor_msgs/msg/JointState", "effort", "N·m"),
+    ("nav_msgs/msg/Odometry", "pose.pose.position", "m"),
+    ("nav_msgs/msg/Odometry", "twist.twist.linear", "m/s"),
+    ("nav_msgs/msg/Odometry", "twist.twist.angular", "rad/s"),
+    ("geometry_msgs/msg/Twist", "linear", "m/s"),
+    ("geometry_msgs/msg/Twist", "angular", "rad/s"),
+    ("geometry_msgs/msg/TwistStamped", "twist.linear", "m/s"),
+    ("geometry_msgs/msg/TwistStamped", "twist.angular", "rad/s"),
+    ("geometry_msgs/msg/Accel", "linear", "m/s²"),
+    ("geometry_msgs/msg/Accel", "angular", "rad/s²"),
+    ("geometry_msgs/msg/AccelStamped", "accel.linear", "m/s²"),
+    ("geometry_msgs/msg/AccelStamped", "accel.angular", "rad/s²"),
+    ("geometry_msgs/msg/Wrench", "force", "N"),
+    ("geometry_msgs/msg/Wrench", "torque", "N·m"),
+    ("geometry_msgs/msg/WrenchStamped", "wrench.force", "N"),
+    ("geometry_msgs/msg/WrenchStamped", "wrench.torque", "N·m"),
+    ("geometry_msgs/msg/Pose", "position", "m"),
+    ("geometry_msgs/msg/PoseStamped", "pose.position", "m"),
+    (
+        "geometry_msgs/msg/PoseWithCovarianceStamped",
+        "pose.pose.position",
+        "m",
+    ),
+    ("geometry_msgs/msg/Transform", "translation", "m"),
+    (
+        "geometry_msgs/msg/TransformStamped",
+        "transform.translation",
+        "m",
+    ),
+    (
+        "tf2_msgs/msg/TFMessage",
+        "transforms.transform.translation",
+        "m",
+    ),
+];
+
+/// Units of the fields of messages, labelling the values of plots and summaries.
+///
+/// Units come from the `units` section of the config file, as a map from topic name or message
+/// type to a map from field path to unit. A field path stands for the field and the fields nested
+/// under it, and the longest matching path wins. Fields the config leaves out fall back to a
+/// built-in table of common ROS message types, then to a guess from the name of the field.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::FieldUnits;
+///
+/// let units = FieldUnits::from_yaml(
+///     r#"
+/// my_msgs/msg/VehicleStatus:
+///   speed: km/h
+/// /vehicle/steering:
+///   angle: deg
+/// "#,
+/// )
+/// .unwrap();
+/// assert_eq!(units.unit("/vehicle/status", "my_msgs/msg/VehicleStatus", "speed"), Some("km/h"));
+/// assert_eq!(units.unit("/vehicle/steering", "my_msgs/msg/Steering", "angle"), Some("deg"));
+/// assert_eq!(
+///     units.unit("/imu", "sensor_msgs/msg/Imu", "angular_velocity.z"),
+///     Some("rad/s")
+/// );
+/// assert_eq!(units.unit("/imu", "sensor_msgs/msg/Imu", "header.frame_id"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct FieldUnits {
+    /// Unit of each field path, per topic name or message type.
+    units: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl FieldUnits {
+    /// Parses units from a YAML map of topic name or message type to a map of field path to
+    /// unit.
+    pub fn from_yaml(yaml: &str) -> RosPeekResult<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Returns whether there are no units configured.
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Returns the unit of a field, looking up the units of the topic, then those of the message
+    /// type, then the built-in table and finally guessing from the name of the field.
+    ///
+    /// # Arguments
+    /// * `topic` - Name of the topic, or an empty string if there is none.
+    /// * `type_name` - Name of the message type, such as `sensor_msgs/msg/Imu`.
+    /// * `field` - Dot-separated path of the field, such as `linear_acceleration.x`. Indices of
+    ///   array elements, such as in `transforms.0.transform`, are ignored.
+    pub fn unit<'a>(&'a self, topic: &str, type_name: &str, field: &str) -> Option<&'a str> {
+        let field = strip_indices(field);
+        [topic, type_name]
+            .into_iter()
+            .filter_map(|key| self.units.get(key))
+            .find_map(|units| {
+                longest_match(
+                    units
+                        .iter()
+                        .map(|(path, unit)| (path.as_str(), unit.as_str())),
+                    &field,
+                )
+            })
+            .or_else(|| builtin_unit(type_name, &field))
+            .or_else(|| guess_unit(&field))
+    }
+
+    /// Returns the units of the process, usually those of the config file.
+    pub fn global() -> FieldUnits {
+        global_units().read().unwrap().clone()
+    }
+
+    /// Replaces the units of the process.
+    pub fn set_global(units: FieldUnits) {
+        *global_units().write().unwrap() = units;
+    }
+}
+
+fn global_units() -> &'static RwLock<FieldUnits> {
+    static UNITS: OnceLock<RwLock<FieldUnits>> = OnceLock::new();
+    UNITS.get_or_init(RwLock::default)
+}
+
+/// Returns the unit of a field of a common ROS message type, from a built-in table.
+///
+/// # Arguments
+/// * `type_name` - Name of the message type, such as `sensor_msgs/msg/Imu`.
+/// * `field` - Dot-separated path of the field, without indices of array elements.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::builtin_unit;
+///
+/// assert_eq!(builtin_unit("sensor_msgs/msg/Imu", "linear_acceleration.x"), Some("m/s²"));
+/// assert_eq!(builtin_unit("sensor_msgs/msg/Temperature", "temperature"), Some("°C"));
+/// assert_eq!(builtin_unit("sensor_msgs/msg/Imu", "orientation.w"), None);
+/// ```
+pub fn builtin_unit(type_name: &str, field: &str) -> Option<&'static str> {
+    longest_match(
+        BUILTIN_UNITS
+            .iter()
+            .filter(|(t, _, _)| *t == type_name)
+            .map(|(_, path, unit)| (*path, *unit)),
+        field,
+    )
+}
+
+/// Guesses the unit of a field from its name, assuming SI units as ROS messages do
+/// ([REP 103](https://www.ros.org/reps/rep-0103.html)).
+///
+/// # Arguments
+/// * `field` - Dot-separated path of the field, without indices of array elements.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::guess_unit;
+///
+/// assert_eq!(guess_unit("twist.twist.linear.x"), Some("m/s"));
+/// assert_eq!(guess_unit("pose.pose.position.y"), Some("m"));
+/// assert_eq!(guess_unit("pose.orientation.yaw_deg"), Some("°"));
+/// assert_eq!(guess_unit("header.stamp.nanosec"), Some("ns"));
+/// assert_eq!(guess_unit("header.frame_id"), None);
+/// ```
+pub fn guess_unit(field: &str) -> Option<&'static str> {
+    let segments: Vec<&str> = field.split('.').collect();
+    let name = *segments.last()?;
+    let parent = segments.len().checked_sub(2).map(|i| segments[i]);
+    let within = |names: &[&str]| segments.iter().any(|s| names.contains(s));
+
+    if name.ends_with("_deg") {
+        return Some("°");
+    }
+    match name {
+        "sec" => return Some("s"),
+        "nanosec" => return Some("ns"),
+        _ => {}
+    }
+    match parent {
+        Some("position" | "translation") => return Some("m"),
+        Some("linear") if within(&["twist", "velocity"]) => return Some("m/s"),
+        Some("angular") if within(&["twist", "velocity"]) => return Some("rad/s"),
+        Some("linear") if within(&["accel", "acceleration"]) => return Some("m/s²"),
+        Some("angular") if within(&["accel", "acceleration"]) => return Some("rad/s²"),
+        Some("linear_acceleration") => return Some("m/s²"),
+        Some("angular_velocity") => return Some("rad/s"),
+        _ => {}
+    }
+
+    let words: Vec<&str> = name.split('_').collect();
+    let has = |names: &[&str]| words.iter().any(|w| names.contains(w));
+    // quantities come first, so that `current_speed` is a speed rather than a current
+    if has(&["temperature", "temp"]) {
+        Some("°C")
+    } else if has(&["pressure"]) {
+        Some("Pa")
+    } else if has(&["acceleration", "accel"]) {
+        Some("m/s²")
+    } else if has(&["speed", "velocity"]) {
+        Some("m/s")
+    } else if has(&["yaw", "roll", "pitch", "heading", "angle"]) {
+        Some("rad")
+    } else if has(&["latitude", "longitude", "lat", "lon"]) {
+        Some("°")
+    } else if has(&["altitude", "range", "distance"]) {
+        Some("m")
+    } else if has(&["voltage"]) {
+        Some("V")
+    } else if has(&["current"]) {
+        Some("A")
+    } else {
+        None
+    }
+}
+
+/// Returns the unit of the longest path equal to the field or a parent of it.
+fn longest_match<'a>(
+    units: impl Iterator<Item = (&'a str, &'a str)>,
+    field: &str,
+) -> Option<&'a str> {
+    units
+        .filter(|(path, _)| {
+            field == *path
+                || field
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .max_by_key(|(path, _)| path.len())
+        .map(|(_, unit)| unit)
+}
+
+/// Removes the indices of array elements from a field path.
+fn strip_indices(field: &str) -> String {
+    field
+        .split('.')
+        .filter(|segment| segment.parse::<usize>().is_err())
+        .collect::<Vec<_>>()
+        .join(".")
+}
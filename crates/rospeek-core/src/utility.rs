@@ -1,13 +1,84 @@
-use serde_json::{Map, Value, json};
+use serde_json::Value;
+#[cfg(feature = "csv")]
+use serde_json::{Map, json};
 
+#[cfg(feature = "csv")]
 use crate::RosPeekResult;
 
+#[cfg(feature = "csv")]
 pub fn flatten_json(json: &Map<String, Value>) -> RosPeekResult<Map<String, Value>> {
     let mut output = Map::new();
     insert_object(&mut output, None, json);
     Ok(output)
 }
 
+/// Looks up a nested value by a dot-separated field path.
+///
+/// Numeric segments index into arrays.
+///
+/// # Arguments
+/// * `value` - The decoded JSON value.
+/// * `path` - Field path, such as `header.frame_id` or `transforms.0.header.stamp`.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::lookup_field;
+/// use serde_json::json;
+///
+/// let value = json!({"header": {"frame_id": "map"}, "points": [{"x": 1.0}]});
+/// assert_eq!(lookup_field(&value, "header.frame_id"), Some(&json!("map")));
+/// assert_eq!(lookup_field(&value, "points.0.x"), Some(&json!(1.0)));
+/// assert_eq!(lookup_field(&value, "header.seq"), None);
+/// ```
+pub fn lookup_field<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|s| !s.is_empty())
+        .try_fold(value, |value, segment| match value {
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            Value::Object(object) => object.get(segment),
+            _ => None,
+        })
+}
+
+/// Largest integer which a double, as used by JavaScript and most JSON tools, holds exactly.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Replaces integers which do not fit in a double without losing precision with strings.
+///
+/// Nanosecond timestamps and IDs near `2^63` are rounded when JSON is post-processed with
+/// doubles, while their string representation keeps every digit.
+///
+/// # Arguments
+/// * `value` - The decoded JSON value, modified in place.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::stringify_large_integers;
+/// use serde_json::json;
+///
+/// let mut value = json!({"stamp": 1700000000123456789u64, "ids": [42, -9007199254740993i64]});
+/// stringify_large_integers(&mut value);
+/// assert_eq!(value, json!({"stamp": "1700000000123456789", "ids": [42, "-9007199254740993"]}));
+/// ```
+pub fn stringify_large_integers(value: &mut Value) {
+    match value {
+        Value::Number(number) => {
+            let large = match (number.as_u64(), number.as_i64()) {
+                (Some(n), _) => n > MAX_SAFE_INTEGER,
+                (None, Some(n)) => n.unsigned_abs() > MAX_SAFE_INTEGER,
+                (None, None) => false,
+            };
+            if large {
+                *value = Value::String(number.to_string());
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(stringify_large_integers),
+        Value::Object(object) => object.values_mut().for_each(stringify_large_integers),
+        _ => {}
+    }
+}
+
+#[cfg(feature = "csv")]
 fn insert_object(
     base_json: &mut Map<String, Value>,
     base_key: Option<&str>,
@@ -26,6 +97,7 @@ fn insert_object(
     });
 }
 
+#[cfg(feature = "csv")]
 fn insert_array(base_json: &mut Map<String, Value>, base_key: &str, array: &[Value]) {
     array.iter().for_each(|value| {
         if let Some(object) = value.as_object() {
@@ -38,6 +110,7 @@ fn insert_array(base_json: &mut Map<String, Value>, base_key: &str, array: &[Val
     });
 }
 
+#[cfg(feature = "csv")]
 fn insert_value(base_json: &mut Map<String, Value>, key: &str, to_insert: &Value) {
     if let Some(value) = base_json.get_mut(key) {
         if let Some(array) = value.as_array_mut() {
@@ -50,7 +123,7 @@ fn insert_value(base_json: &mut Map<String, Value>, key: &str, to_insert: &Value
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "csv"))]
 mod tests {
     use super::*;
 
@@ -0,0 +1,390 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::File,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BagReader, Dedup, EmbeddedSchema, MessageOrder, Pipeline, RawMessage, Record, RosPeekResult,
+    Sink, Stage, Topic,
+};
+
+/// Extension replacing that of an output bag to name its checkpoint file.
+const CHECKPOINT_EXTENSION: &str = "progress.json";
+
+/// Interval at which an export commits the written messages and saves its checkpoint.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+
+pub trait BagWriter: Send {
+    /// Creates a new bag file at the given path.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the bag file to create.
+    ///
+    /// # Returns
+    /// A result containing the bag writer or an error.
+    fn create<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
+    where
+        Self: Sized;
+
+    /// Opens an existing bag file to write more messages into it, such as to resume an
+    /// interrupted export.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the bag file to append to.
+    ///
+    /// # Returns
+    /// A result containing the bag writer or an error if the format cannot be appended to.
+    fn append<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
+    where
+        Self: Sized,
+    {
+        anyhow::bail!("Cannot append to bag: {}", path.as_ref().display())
+    }
+
+    /// Embeds the message definition of a type into the bag file, in place of the installed one,
+    /// such as the definition embedded in the bag being exported.
+    ///
+    /// Called before the topics of the type are registered. Formats which cannot carry
+    /// definitions ignore it.
+    ///
+    /// # Arguments
+    /// * `schema` - The message definition to embed.
+    fn add_schema(&mut self, _schema: &EmbeddedSchema) -> RosPeekResult<()> {
+        Ok(())
+    }
+
+    /// Registers a topic to the bag file.
+    ///
+    /// # Arguments
+    /// * `topic` - The topic to register.
+    ///
+    /// # Returns
+    /// A result containing the ID of the topic in the written bag or an error.
+    fn add_topic(&mut self, topic: &Topic) -> RosPeekResult<u16>;
+
+    /// Writes a message to a registered topic.
+    ///
+    /// # Arguments
+    /// * `topic_id` - The ID returned by [`BagWriter::add_topic`].
+    /// * `message` - The message to write.
+    fn write_message(&mut self, topic_id: u16, message: &RawMessage) -> RosPeekResult<()>;
+
+    /// Makes the messages written so far durable, so that they are kept if the process dies
+    /// before [`BagWriter::finish`].
+    fn commit(&mut self) -> RosPeekResult<()> {
+        Ok(())
+    }
+
+    /// Flushes all pending data and finalizes the bag file.
+    fn finish(&mut self) -> RosPeekResult<()>;
+}
+
+/// Options selecting what to export from a bag.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Topic names to export, or all topics if empty.
+    pub topics: Vec<String>,
+    /// Timestamp in nanoseconds since which to export messages (inclusive).
+    pub since: Option<u64>,
+    /// Timestamp in nanoseconds until which to export messages (inclusive).
+    pub until: Option<u64>,
+    /// Whether to drop exact duplicates of messages.
+    pub dedup: bool,
+    /// Whether to re-sort messages by timestamp, otherwise each topic keeps its recorded order.
+    pub sort_by_time: bool,
+    /// Path of the checkpoint file saved periodically while exporting and removed once the
+    /// export completes, such as [`ExportCheckpoint::path`].
+    pub checkpoint: Option<PathBuf>,
+    /// Progress of an interrupted export to resume, whose messages are not exported again.
+    pub resume: Option<ExportCheckpoint>,
+}
+
+/// Progress of an export, saved alongside its output so that an interrupted export can resume
+/// from where it stopped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportCheckpoint {
+    /// Number of messages of each topic written into the output, which are the first ones of
+    /// the topic in the order the export writes them.
+    pub written: BTreeMap<String, u64>,
+}
+
+impl ExportCheckpoint {
+    /// Returns the path of the checkpoint file of an output bag, such as `export.progress.json`
+    /// for `export.db3`.
+    ///
+    /// # Arguments
+    /// * `output` - The path to the output bag file.
+    pub fn path<P: AsRef<Path>>(output: P) -> PathBuf {
+        output.as_ref().with_extension(CHECKPOINT_EXTENSION)
+    }
+
+    /// Reads a checkpoint file.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the checkpoint file.
+    pub fn load<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open checkpoint {}", path.display()))?;
+        serde_json::from_reader(file)
+            .with_context(|| format!("Invalid checkpoint {}", path.display()))
+    }
+
+    /// Writes a checkpoint file, replacing the previous one only once fully written.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the checkpoint file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> RosPeekResult<()> {
+        let path = path.as_ref();
+        let partial = path.with_extension("partial");
+        let file = File::create(&partial)
+            .with_context(|| format!("Failed to create {}", partial.display()))?;
+        serde_json::to_writer_pretty(file, self)?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+}
+
+/// Number of exported messages for each topic.
+#[derive(Debug, Clone, Default)]
+pub struct ExportSummary {
+    pub counts: BTreeMap<String, u64>,
+    /// Number of dropped duplicates for each topic, only filled when deduplicating.
+    pub duplicates: BTreeMap<String, u64>,
+    /// Number of reordered messages for each topic, only filled when sorting by time.
+    pub reordered: BTreeMap<String, u64>,
+    /// Name of each additional stage of [`export_bag_with`] and number of messages it dropped
+    /// for each topic, in the order stages were added.
+    pub dropped_by_stage: Vec<(String, BTreeMap<String, u64>)>,
+    /// Number of messages skipped because the resumed export had written them, only filled when
+    /// resuming.
+    pub resumed: BTreeMap<String, u64>,
+}
+
+impl ExportSummary {
+    /// Returns the total number of exported messages.
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+}
+
+/// Copies the selected topics and time range of a bag into another bag.
+///
+/// Messages are interleaved across topics by timestamp as topics are read page by page, so that
+/// exports larger than memory are possible. Each topic keeps its recorded order unless
+/// [`ExportOptions::sort_by_time`] is set, which sorts messages spilling to temporary files.
+///
+/// With [`ExportOptions::checkpoint`], the written messages are committed and the number of
+/// written messages of each topic saved every few seconds. Exporting again into the writer
+/// appending to the same bag with [`ExportOptions::resume`] set to the saved checkpoint skips
+/// as many messages of each topic and continues where the export stopped. Messages are skipped
+/// by position rather than by timestamp, so that topics recorded out of order resume without
+/// losing messages, as long as the options are the same.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `writer` - The bag writer to write messages to.
+/// * `options` - The selection of topics and time range.
+///
+/// # Returns
+/// A summary of the exported messages.
+pub fn export_bag(
+    reader: &dyn BagReader,
+    writer: &mut dyn BagWriter,
+    options: &ExportOptions,
+) -> RosPeekResult<ExportSummary> {
+    export_bag_with(reader, writer, options, |pipeline| pipeline)
+}
+
+/// Copies the selected topics and time range of a bag into another bag, passing messages
+/// through additional pipeline stages.
+///
+/// The message definitions embedded in the bag are embedded into the other bag as well, so that
+/// it can be decoded where the message packages are not installed.
+///
+/// # Arguments
+/// * `reader` - The bag reader to read messages from.
+/// * `writer` - The bag writer to write messages to.
+/// * `options` - The selection of topics and time range.
+/// * `stages` - Appends stages to the pipeline, which run after deduplication.
+///
+/// # Returns
+/// A summary of the exported messages.
+pub fn export_bag_with<'a, F>(
+    reader: &'a dyn BagReader,
+    writer: &mut dyn BagWriter,
+    options: &ExportOptions,
+    stages: F,
+) -> RosPeekResult<ExportSummary>
+where
+    F: FnOnce(Pipeline<'a>) -> Pipeline<'a>,
+{
+    let order = if options.sort_by_time {
+        MessageOrder::Timestamp
+    } else {
+        MessageOrder::Interleaved
+    };
+    let mut pipeline = Pipeline::new(reader)
+        .topics(&options.topics)
+        .range(options.since, options.until)
+        .order(order);
+    if options.dedup {
+        pipeline = pipeline.stage(Dedup::default());
+    }
+
+    let types: BTreeSet<String> = reader
+        .topics()?
+        .into_iter()
+        .filter(|t| options.topics.is_empty() || options.topics.contains(&t.name))
+        .map(|t| t.type_name)
+        .collect();
+    for type_name in types {
+        if let Some(schema) = reader.embedded_schema(&type_name)? {
+            writer.add_schema(&schema)?;
+        }
+    }
+
+    let mut sink = BagSink::new(writer);
+    if let Some(path) = &options.checkpoint {
+        let progress = options.resume.clone().unwrap_or_default();
+        sink = sink.with_checkpoint(path.clone(), progress);
+    }
+    let mut pipeline = stages(pipeline);
+    if let Some(resume) = &options.resume {
+        // last, so that the skipped messages are the ones which reached the output
+        pipeline = pipeline.stage(Resume {
+            remaining: resume.written.clone(),
+        });
+    }
+    let summary = pipeline.sink(&mut sink)?;
+
+    let mut dropped_by_stage = summary.dropped_by_stage;
+    let resumed = match options.resume {
+        Some(_) => dropped_by_stage.pop().unwrap_or_default().1,
+        None => BTreeMap::new(),
+    };
+    let duplicates = if options.dedup && !dropped_by_stage.is_empty() {
+        dropped_by_stage.remove(0).1
+    } else {
+        BTreeMap::new()
+    };
+    Ok(ExportSummary {
+        counts: summary.counts,
+        duplicates,
+        reordered: summary.reordered,
+        dropped_by_stage,
+        resumed,
+    })
+}
+
+/// Stage dropping the first messages of each topic, which a resumed export had written.
+struct Resume {
+    /// Number of messages still to skip for each topic
+    remaining: BTreeMap<String, u64>,
+}
+
+impl Stage for Resume {
+    fn process(&mut self, record: Record) -> RosPeekResult<Option<Record>> {
+        match self.remaining.get_mut(&record.topic.name) {
+            Some(remaining) if *remaining > 0 => {
+                *remaining -= 1;
+                Ok(None)
+            }
+            _ => Ok(Some(record)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Resume"
+    }
+}
+
+/// Pipeline sink writing records into a bag.
+pub struct BagSink<'a> {
+    writer: &'a mut dyn BagWriter,
+    /// IDs of the registered topics in the written bag
+    ids: HashMap<String, u16>,
+    checkpoint: Option<Checkpointing>,
+}
+
+/// Progress of a [`BagSink`] saved into a checkpoint file.
+struct Checkpointing {
+    path: PathBuf,
+    progress: ExportCheckpoint,
+    saved_at: Instant,
+}
+
+impl<'a> BagSink<'a> {
+    /// Creates a sink writing into a bag.
+    ///
+    /// # Arguments
+    /// * `writer` - The bag writer to write messages to, which is finished with the pipeline.
+    pub fn new(writer: &'a mut dyn BagWriter) -> Self {
+        Self {
+            writer,
+            ids: HashMap::new(),
+            checkpoint: None,
+        }
+    }
+
+    /// Saves the progress into a checkpoint file every few seconds, after committing the written
+    /// messages, and removes the file once finished.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the checkpoint file.
+    /// * `progress` - The progress so far, such as that of a resumed export.
+    pub fn with_checkpoint(mut self, path: PathBuf, progress: ExportCheckpoint) -> Self {
+        self.checkpoint = Some(Checkpointing {
+            path,
+            progress,
+            saved_at: Instant::now(),
+        });
+        self
+    }
+}
+
+impl Sink for BagSink<'_> {
+    fn add_topic(&mut self, topic: &Topic) -> RosPeekResult<()> {
+        let id = self.writer.add_topic(topic)?;
+        self.ids.insert(topic.name.clone(), id);
+        Ok(())
+    }
+
+    fn write(&mut self, record: Record) -> RosPeekResult<()> {
+        let id = self
+            .ids
+            .get(&record.topic.name)
+            .ok_or_else(|| anyhow::anyhow!("Topic not registered: {}", record.topic.name))?;
+        self.writer.write_message(*id, &record.message)?;
+
+        if let Some(checkpoint) = self.checkpoint.as_mut() {
+            *checkpoint
+                .progress
+                .written
+                .entry(record.topic.name.clone())
+                .or_default() += 1;
+            if checkpoint.saved_at.elapsed() >= CHECKPOINT_INTERVAL {
+                // committed first, so that the checkpoint never claims messages which were lost
+                self.writer.commit()?;
+                checkpoint.progress.save(&checkpoint.path)?;
+                checkpoint.saved_at = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> RosPeekResult<()> {
+        self.writer.finish()?;
+        if let Some(checkpoint) = &self.checkpoint
+            && checkpoint.path.exists()
+        {
+            std::fs::remove_file(&checkpoint.path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use rospeek_core::{ALIASES_FILE, Config, TopicAliases};
+
+#[test]
+fn test_aliases_with_sidecar() {
+    let dir = std::env::temp_dir().join(format!("rospeek-aliases-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let config = Config::from_yaml(
+        r#"
+aliases:
+  /sensing/radar/front_center/objects_raw: front_radar
+  /sensing/lidar/top/pointcloud_raw_ex: top_lidar
+"#,
+    )
+    .unwrap();
+
+    // bag directories have an aliases.yaml overriding the config
+    std::fs::write(
+        dir.join(ALIASES_FILE),
+        "/sensing/lidar/top/pointcloud_raw_ex: lidar\n",
+    )
+    .unwrap();
+    let aliases = config.aliases.with_sidecar(&dir).unwrap();
+    assert_eq!(
+        aliases.resolve("front_radar"),
+        "/sensing/radar/front_center/objects_raw"
+    );
+    assert_eq!(
+        aliases.resolve("lidar"),
+        "/sensing/lidar/top/pointcloud_raw_ex"
+    );
+    assert_eq!(aliases.resolve("top_lidar"), "top_lidar");
+    assert_eq!(aliases.label("/tf"), "/tf");
+
+    // bag files have a <name>.aliases.yaml next to them
+    let bag = dir.join("drive.mcap");
+    assert_eq!(
+        TopicAliases::sidecar_path(&bag),
+        dir.join("drive.aliases.yaml")
+    );
+    assert_eq!(TopicAliases::load_sidecar(&bag).unwrap(), None);
+    assert_eq!(config.aliases.with_sidecar(&bag).unwrap(), config.aliases);
+
+    std::fs::write(dir.join("drive.aliases.yaml"), "- not a map\n").unwrap();
+    assert!(TopicAliases::load_sidecar(&bag).is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
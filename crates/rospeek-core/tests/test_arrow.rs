@@ -0,0 +1,146 @@
+#![cfg(feature = "arrow")]
+
+use arrow_array::{
+    Array, Float64Array, Int32Array, ListArray, StringArray, UInt32Array, UInt64Array,
+    types::Float32Type,
+};
+use arrow_ipc::reader::FileReader;
+use arrow_schema::DataType;
+use rospeek_core::{
+    FieldType, MessageField, MessageSchema, TIMESTAMP_COLUMN, record_batch_from_values,
+    record_batch_schema, write_arrow_ipc,
+};
+use serde_json::json;
+
+fn schema() -> MessageSchema {
+    let field = |name: &str, field_type| MessageField {
+        name: name.into(),
+        field_type,
+    };
+    MessageSchema {
+        type_name: "foo_msgs/msg/Foo".into(),
+        fields: vec![
+            field(
+                "stamp",
+                FieldType::Object("builtin_interfaces/msg/Time".into()),
+            ),
+            field("frame_id", FieldType::Object("string".into())),
+            field("speed", FieldType::Object("double".into())),
+            field("ranges", FieldType::Sequence("float".into())),
+            field("labels", FieldType::Sequence("string".into())),
+        ],
+    }
+}
+
+#[test]
+fn test_record_batch_schema() {
+    let schema = record_batch_schema(&schema()).unwrap();
+
+    let columns: Vec<_> = schema
+        .fields()
+        .iter()
+        .map(|f| (f.name().as_str(), f.data_type().clone()))
+        .collect();
+    assert_eq!(columns[0], (TIMESTAMP_COLUMN, DataType::UInt64));
+    assert_eq!(columns[1], ("stamp.sec", DataType::Int32));
+    assert_eq!(columns[2], ("stamp.nanosec", DataType::UInt32));
+    assert_eq!(columns[3], ("frame_id", DataType::Utf8));
+    assert_eq!(columns[4], ("speed", DataType::Float64));
+    assert!(
+        matches!(columns[5], ("ranges", DataType::List(ref item)) if item.data_type() == &DataType::Float32)
+    );
+    // sequences of non-primitives are stored as JSON
+    assert_eq!(columns[6], ("labels", DataType::Utf8));
+}
+
+#[test]
+fn test_record_batch_from_values() {
+    let values = [
+        json!({
+            "stamp": {"sec": 1, "nanosec": 500},
+            "frame_id": "map",
+            "speed": 1.5,
+            "ranges": [0.5, 1.0],
+            "labels": ["a", "b"],
+        }),
+        // missing and mistyped values become nulls
+        json!({
+            "stamp": {"sec": 2, "nanosec": 0},
+            "frame_id": 3,
+            "ranges": [],
+            "labels": [],
+        }),
+    ];
+    let batch = record_batch_from_values(&schema(), &[10, 20], &values).unwrap();
+    assert_eq!(batch.num_rows(), 2);
+
+    let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+    let timestamps = column(TIMESTAMP_COLUMN);
+    let timestamps = timestamps.as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!(timestamps.values(), &[10, 20]);
+
+    let sec = column("stamp.sec");
+    assert_eq!(
+        sec.as_any().downcast_ref::<Int32Array>().unwrap().values(),
+        &[1, 2]
+    );
+    let nanosec = column("stamp.nanosec");
+    assert_eq!(
+        nanosec
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .unwrap()
+            .value(0),
+        500
+    );
+
+    let frame_id = column("frame_id");
+    let frame_id = frame_id.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(frame_id.value(0), "map");
+    assert!(frame_id.is_null(1));
+
+    let speed = column("speed");
+    let speed = speed.as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(speed.value(0), 1.5);
+    assert!(speed.is_null(1));
+
+    let ranges = column("ranges");
+    let ranges = ranges.as_any().downcast_ref::<ListArray>().unwrap();
+    let first = ranges.value(0);
+    assert_eq!(
+        first
+            .as_any()
+            .downcast_ref::<arrow_array::PrimitiveArray<Float32Type>>()
+            .unwrap()
+            .values(),
+        &[0.5, 1.0]
+    );
+    assert_eq!(ranges.value(1).len(), 0);
+
+    let labels = column("labels");
+    let labels = labels.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(labels.value(0), r#"["a","b"]"#);
+}
+
+#[test]
+fn test_record_batch_from_values_mismatched_timestamps() {
+    assert!(record_batch_from_values(&schema(), &[10], &[]).is_err());
+}
+
+#[test]
+fn test_write_arrow_ipc() {
+    let values = [json!({"stamp": {"sec": 7, "nanosec": 0}, "speed": 2.5})];
+    let batch = record_batch_from_values(&schema(), &[10], &values).unwrap();
+    let schema = batch.schema();
+    let file = write_arrow_ipc(Vec::new(), &schema, &[batch.clone(), batch]).unwrap();
+
+    let reader = FileReader::try_new(std::io::Cursor::new(file), None).unwrap();
+    assert_eq!(reader.schema(), schema);
+    let batches: Vec<_> = reader.map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 2);
+    let sec = batches[1].column_by_name("stamp.sec").unwrap();
+    assert_eq!(
+        sec.as_any().downcast_ref::<Int32Array>().unwrap().values(),
+        &[7]
+    );
+}
@@ -0,0 +1,65 @@
+use rospeek_core::{AssertRules, Topic, TopicRule};
+
+fn topic(name: &str) -> Topic {
+    Topic {
+        id: 1,
+        name: name.to_string(),
+        type_name: "sensor_msgs/msg/Imu".to_string(),
+        count: 0,
+        serialization_format: "cdr".to_string(),
+        offered_qos_profiles: None,
+    }
+}
+
+#[test]
+fn test_evaluate_topic_rule() {
+    let rule = TopicRule {
+        name: "/imu".to_string(),
+        type_name: Some("sensor_msgs/msg/Imu".to_string()),
+        min_count: Some(5),
+        min_frequency: Some(5.0),
+        max_gap: Some(0.3),
+        min_coverage: Some(0.9),
+    };
+    // 10 Hz over the first half of a 2 s bag, with a 0.5 s dropout
+    let timestamps = [0, 100, 200, 700, 800, 900, 1000].map(|ms| ms * 1_000_000);
+
+    let results = rule.evaluate(Some(&topic("/imu")), &timestamps, 0, 2_000_000_000);
+    let outcomes: Vec<_> = results
+        .iter()
+        .map(|r| (r.rule.as_str(), r.actual.as_str(), r.passed))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("type", "sensor_msgs/msg/Imu", true),
+            ("min_count", "7", true),
+            ("min_frequency", "6.00 Hz", true),
+            ("max_gap", "0.500 s", false),
+            ("min_coverage", "50.0%", false),
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_missing_topic() {
+    let rule = TopicRule {
+        name: "/radar".to_string(),
+        min_frequency: Some(10.0),
+        ..Default::default()
+    };
+
+    let results = rule.evaluate(None, &[], 0, 1);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].rule, "exists");
+    assert!(!results[0].passed);
+}
+
+#[test]
+fn test_parse_assert_rules() {
+    let rules = AssertRules::from_yaml("topics:\n  - name: /tf\n    min_count: 1\n").unwrap();
+    assert_eq!(rules.min_duration, None);
+    assert_eq!(rules.topics[0].min_count, Some(1));
+
+    assert!(AssertRules::from_yaml("topics:\n  - name: /tf\n    min_hz: 1\n").is_err());
+}
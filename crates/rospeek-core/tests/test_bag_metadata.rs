@@ -0,0 +1,116 @@
+use rospeek_core::{BagMetadata, BagStats, IncompleteSign, StorageType};
+
+/// `metadata.yaml` as written by Jazzy, with QoS profiles as a list.
+const JAZZY_METADATA: &str = r#"
+rosbag2_bagfile_information:
+  version: 9
+  storage_identifier: mcap
+  duration:
+    nanoseconds: 1500000000
+  starting_time:
+    nanoseconds_since_epoch: 1700000000000000000
+  message_count: 30
+  topics_with_message_count:
+    - topic_metadata:
+        name: /tf
+        type: tf2_msgs/msg/TFMessage
+        serialization_format: cdr
+        offered_qos_profiles:
+          - history: keep_last
+            depth: 100
+        type_description_hash: RIHS01_e5e0b1b7
+      message_count: 20
+    - topic_metadata:
+        name: /odom
+        type: nav_msgs/msg/Odometry
+        serialization_format: cdr
+        offered_qos_profiles: ""
+        type_description_hash: RIHS01_3cc9a5b2
+      message_count: 10
+  compression_format: ""
+  compression_mode: ""
+  relative_file_paths:
+    - bag_0.mcap
+  files:
+    - path: bag_0.mcap
+      message_count: 30
+"#;
+
+#[test]
+fn test_bag_metadata_from_yaml() {
+    let metadata = BagMetadata::from_yaml(JAZZY_METADATA).expect("Failed to parse metadata");
+
+    assert_eq!(metadata.message_count, 30);
+    assert_eq!(metadata.start_ns(), 1_700_000_000_000_000_000);
+    assert_eq!(metadata.end_ns(), 1_700_000_001_500_000_000);
+    assert_eq!(metadata.relative_file_paths, vec!["bag_0.mcap"]);
+
+    let topics = metadata.topics();
+    let summary: Vec<_> = topics
+        .iter()
+        .map(|t| (t.id, t.name.as_str(), t.type_name.as_str(), t.count))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (1, "/tf", "tf2_msgs/msg/TFMessage", 20),
+            (2, "/odom", "nav_msgs/msg/Odometry", 10),
+        ]
+    );
+    assert!(
+        topics[0]
+            .offered_qos_profiles
+            .as_deref()
+            .is_some_and(|qos| qos.contains("keep_last"))
+    );
+
+    let stats = metadata
+        .stats(std::env::temp_dir())
+        .expect("Failed to build stats");
+    assert_eq!(stats.storage_type, StorageType::Mcap);
+    assert_eq!(stats.message_count, 30);
+
+    assert!(BagMetadata::from_yaml("rosbag2_bagfile_information: {}").is_err());
+}
+
+#[test]
+fn test_bag_metadata_incomplete_signs() {
+    let metadata = BagMetadata::from_yaml(JAZZY_METADATA).expect("Failed to parse metadata");
+    let dir = std::env::temp_dir().join(format!("rospeek-incomplete-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create bag directory");
+    for file in ["bag_0.mcap", "bag_1.mcap", "notes.txt"] {
+        std::fs::write(dir.join(file), b"").expect("Failed to create file");
+    }
+
+    let signs = metadata
+        .incomplete_signs(&dir, None)
+        .expect("Failed to check bag directory");
+    assert_eq!(
+        signs,
+        vec![IncompleteSign::UnlistedFile {
+            file: dir.join("bag_1.mcap").display().to_string()
+        }]
+    );
+
+    // the files end 0.5 s before the end in metadata.yaml
+    let stats = BagStats::new(
+        &dir,
+        StorageType::Mcap,
+        30,
+        metadata.start_ns(),
+        metadata.end_ns() - 500_000_000,
+    );
+    let signs = metadata
+        .incomplete_signs(&dir, Some(&stats))
+        .expect("Failed to check bag directory");
+    assert_eq!(
+        signs.last(),
+        Some(&IncompleteSign::DurationMismatch {
+            metadata_sec: 1.5,
+            files_sec: 1.0
+        })
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,143 @@
+use rospeek_core::{
+    CAMERA_INFO_TYPE, CameraInfo, CdrWriter, Endianness, Topic, find_camera_info_topic,
+};
+
+#[test]
+fn test_camera_info_from_cdr() {
+    let mut writer = CdrWriter::new(Endianness::Little);
+    writer.write_i32(0);
+    writer.write_u32(0);
+    writer.write_str("camera");
+    writer.write_u32(480);
+    writer.write_u32(640);
+    writer.write_str("plumb_bob");
+    writer.write_u32(5);
+    for value in [-0.1, 0.01, 0.0, 0.0, 0.0] {
+        writer.write_f64(value);
+    }
+    let k = [500.0, 0.0, 320.0, 0.0, 500.0, 240.0, 0.0, 0.0, 1.0];
+    let r = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+    let p = [
+        500.0, 0.0, 320.0, 0.0, 0.0, 500.0, 240.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+    ];
+    for value in k.iter().chain(r.iter()).chain(p.iter()) {
+        writer.write_f64(*value);
+    }
+    // binning and region of interest
+    for _ in 0..6 {
+        writer.write_u32(0);
+    }
+    writer.write_bool(false);
+
+    let info = CameraInfo::from_cdr(&writer.into_bytes()).unwrap();
+    assert_eq!(info.frame_id, "camera");
+    assert_eq!((info.width, info.height), (640, 480));
+    assert_eq!(info.distortion_model, "plumb_bob");
+    assert_eq!(info.d, vec![-0.1, 0.01, 0.0, 0.0, 0.0]);
+    assert_eq!(info.k, k);
+    assert_eq!(info.r, r);
+    assert_eq!(info.p, p);
+}
+
+#[test]
+fn test_camera_info_from_calibration_yaml() {
+    let yaml = r#"
+image_width: 640
+image_height: 480
+camera_name: front
+camera_matrix:
+  rows: 3
+  cols: 3
+  data: [500, 0, 320, 0, 500, 240, 0, 0, 1]
+distortion_model: plumb_bob
+distortion_coefficients:
+  rows: 1
+  cols: 5
+  data: [-0.1, 0.01, 0, 0, 0]
+"#;
+    let info = CameraInfo::from_calibration_yaml(yaml).unwrap();
+    assert_eq!(info.frame_id, "front");
+    assert_eq!(info.d.len(), 5);
+    assert_eq!(info.k[2], 320.0);
+    // the projection defaults to the intrinsics without translation
+    assert_eq!(
+        info.p,
+        [
+            500.0, 0.0, 320.0, 0.0, 0.0, 500.0, 240.0, 0.0, 0.0, 0.0, 1.0, 0.0
+        ]
+    );
+    assert_eq!(info.r[0], 1.0);
+
+    let invalid = yaml.replace("data: [500, 0, 320, 0, 500, 240, 0, 0, 1]", "data: [500]");
+    assert!(CameraInfo::from_calibration_yaml(&invalid).is_err());
+}
+
+fn camera(distortion_model: &str, d: Vec<f64>) -> CameraInfo {
+    let k = [100.0, 0.0, 50.0, 0.0, 100.0, 40.0, 0.0, 0.0, 1.0];
+    CameraInfo {
+        frame_id: "camera".to_string(),
+        width: 100,
+        height: 80,
+        distortion_model: distortion_model.to_string(),
+        d,
+        k,
+        r: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+        p: [
+            k[0], k[1], k[2], 0.0, k[3], k[4], k[5], 0.0, k[6], k[7], k[8], 0.0,
+        ],
+    }
+}
+
+#[test]
+fn test_undistort_map() {
+    let info = camera("plumb_bob", vec![0.0; 5]);
+    assert!(!info.is_distorted());
+    let map = info.undistort_map().unwrap();
+    assert_eq!((map.width, map.height), (100, 80));
+    for (x, y) in [(0, 0), (50, 40), (99, 79)] {
+        let [u, v] = map.source(x, y);
+        assert!((u - x as f32).abs() < 1e-4 && (v - y as f32).abs() < 1e-4);
+    }
+
+    // barrel distortion pulls the corners of the undistorted image towards the center
+    let info = camera("plumb_bob", vec![-0.2, 0.0, 0.0, 0.0, 0.0]);
+    assert!(info.is_distorted());
+    let map = info.undistort_map().unwrap();
+    assert_eq!(map.source(50, 40), [50.0, 40.0]);
+    let [u, v] = map.source(0, 0);
+    assert!(u > 0.0 && v > 0.0);
+
+    let map = camera("equidistant", vec![0.0; 4]).undistort_map().unwrap();
+    let [u, v] = map.source(0, 0);
+    // a fisheye lens maps the angle rather than its tangent
+    let r = (0.5f64 * 0.5 + 0.4 * 0.4).sqrt();
+    let scale = r.atan() / r;
+    assert!((u as f64 - (50.0 - 50.0 * scale)).abs() < 1e-3);
+    assert!((v as f64 - (40.0 - 40.0 * scale)).abs() < 1e-3);
+
+    assert!(camera("unknown", vec![]).undistort_map().is_err());
+}
+
+#[test]
+fn test_find_camera_info_topic() {
+    let topic = |id, name: &str, type_name: &str| Topic {
+        id,
+        name: name.to_string(),
+        type_name: type_name.to_string(),
+        count: 1,
+        serialization_format: "cdr".to_string(),
+        offered_qos_profiles: None,
+    };
+    let topics = vec![
+        topic(
+            0,
+            "/cam/image_raw/compressed",
+            "sensor_msgs/msg/CompressedImage",
+        ),
+        topic(1, "/cam/camera_info", CAMERA_INFO_TYPE),
+        topic(2, "/other/camera_info", "std_msgs/msg/String"),
+    ];
+    let found = find_camera_info_topic("/cam/image_raw/compressed", &topics).unwrap();
+    assert_eq!(found.name, "/cam/camera_info");
+    assert!(find_camera_info_topic("/other/image_raw", &topics).is_none());
+}
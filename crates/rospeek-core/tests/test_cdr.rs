@@ -1,4 +1,7 @@
-use rospeek_core::{CdrDecoder, FieldType, MessageField, MessageSchema};
+use rospeek_core::{
+    CdrDecoder, CdrError, CdrReader, DecodePlan, Endianness, FieldType, MessageField,
+    MessageSchema, decode_field,
+};
 
 #[test]
 fn test_decode_time() {
@@ -93,3 +96,137 @@ fn test_decode_duration() {
 //     assert_eq!(result["values"][0], "hello");
 //     assert_eq!(result["values"][1], "world");
 // }
+
+fn projection_schema() -> MessageSchema {
+    MessageSchema {
+        type_name: "test_msgs/msg/Projection".into(),
+        fields: vec![
+            MessageField {
+                name: "label".into(),
+                field_type: FieldType::Object("string".into()),
+            },
+            MessageField {
+                name: "values".into(),
+                field_type: FieldType::Sequence("double".into()),
+            },
+            MessageField {
+                name: "count".into(),
+                field_type: FieldType::Object("uint32".into()),
+            },
+        ],
+    }
+}
+
+fn projection_data() -> Vec<u8> {
+    let mut data = vec![0x00, 0x01, 0x00, 0x00]; // CDR header
+    data.extend(3u32.to_le_bytes()); // label length
+    data.extend(b"ab\0"); // label
+    data.push(0x00); // padding
+    data.extend(2u32.to_le_bytes()); // values length
+    data.extend([0x00; 4]); // padding
+    data.extend(1.5f64.to_le_bytes());
+    data.extend(2.5f64.to_le_bytes());
+    data.extend(7u32.to_le_bytes()); // count
+    data
+}
+
+#[test]
+fn test_decode_field_skips_variable_length_fields() {
+    let schema = projection_schema();
+    let data = projection_data();
+
+    let count = decode_field(&schema, "count", &data).expect("Failed to decode count");
+    assert_eq!(count, Some(serde_json::json!(7)));
+
+    let value = decode_field(&schema, "values.1", &data).expect("Failed to decode values.1");
+    assert_eq!(value, Some(serde_json::json!(2.5)));
+
+    let label = decode_field(&schema, "label", &data).expect("Failed to decode label");
+    assert_eq!(label, Some(serde_json::json!("ab")));
+}
+
+#[test]
+fn test_decode_field_missing_path() {
+    let schema = projection_schema();
+    let data = projection_data();
+
+    assert_eq!(decode_field(&schema, "missing", &data).unwrap(), None);
+    assert_eq!(decode_field(&schema, "values.2", &data).unwrap(), None);
+    assert_eq!(decode_field(&schema, "count.x", &data).unwrap(), None);
+}
+
+#[test]
+fn test_decode_plan_matches_decode() {
+    let schema = projection_schema();
+    let data = projection_data();
+
+    let plan = DecodePlan::compile(&schema).expect("Failed to compile plan");
+    assert_eq!(plan.type_name(), "test_msgs/msg/Projection");
+
+    let expected = CdrDecoder::new(&data)
+        .decode(&schema)
+        .expect("Failed to decode");
+    let result = CdrDecoder::new(&data)
+        .decode_plan(&plan)
+        .expect("Failed to decode with plan");
+    assert_eq!(result, expected);
+    assert_eq!(result["values"], serde_json::json!([1.5, 2.5]));
+}
+
+#[test]
+fn test_decode_plan_array_endianness() {
+    let schema = MessageSchema {
+        type_name: "test_msgs/msg/Samples".into(),
+        fields: vec![MessageField {
+            name: "samples".into(),
+            field_type: FieldType::Array("int16".into(), 3),
+        }],
+    };
+    let plan = DecodePlan::compile(&schema).expect("Failed to compile plan");
+
+    let mut little = vec![0x00, 0x01, 0x00, 0x00]; // CDR header (little-endian)
+    let mut big = vec![0x00, 0x00, 0x00, 0x00]; // CDR header (big-endian)
+    for sample in [-2i16, 300, 7] {
+        little.extend(sample.to_le_bytes());
+        big.extend(sample.to_be_bytes());
+    }
+
+    for data in [little, big] {
+        let result = CdrDecoder::new(&data)
+            .decode_plan(&plan)
+            .expect("Failed to decode with plan");
+        assert_eq!(result["samples"], serde_json::json!([-2, 300, 7]));
+    }
+}
+
+#[test]
+fn test_cdr_reader_primitives() {
+    let data = [
+        0x00, 0x00, 0x00, 0x00, // CDR header (big-endian)
+        0x01, // bool = true
+        0x00, // padding
+        0x01, 0x2C, // int16 = 300
+        0x00, 0x00, 0x00, 0x03, // string length = 3
+        b'a', b'b', 0x00, // "ab" with null terminator
+    ];
+
+    let mut reader = CdrReader::new(&data);
+    assert_eq!(reader.endianness(), Endianness::Big);
+    assert_eq!(reader.read_bool(), Ok(true));
+    assert_eq!(reader.read_i16(), Ok(300));
+    assert_eq!(reader.read_str(), Ok("ab"));
+    assert_eq!(reader.remaining(), 0);
+    assert_eq!(reader.read_u8(), Err(CdrError::UnexpectedEof));
+}
+
+#[test]
+fn test_cdr_reader_truncated() {
+    let data = [
+        0x00, 0x01, 0x00, 0x00, // CDR header
+        0x01, 0x00, // truncated uint32
+    ];
+
+    let mut reader = CdrReader::new(&data);
+    assert_eq!(reader.read_u32(), Err(CdrError::UnexpectedEof));
+    assert_eq!(CdrReader::new(&[]).read_u8(), Err(CdrError::UnexpectedEof));
+}
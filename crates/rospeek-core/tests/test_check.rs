@@ -0,0 +1,43 @@
+use rospeek_core::{RawMessage, dedup_messages};
+
+fn message(timestamp: u64, data: &[u8]) -> RawMessage {
+    RawMessage {
+        timestamp,
+        topic_id: 1,
+        data: data.to_vec(),
+    }
+}
+
+#[test]
+fn test_dedup_messages() {
+    let mut messages = vec![
+        message(1, b"a"),
+        message(1, b"a"),
+        message(2, b"a"),
+        message(1, b"a"),
+        message(2, b"b"),
+    ];
+
+    assert_eq!(dedup_messages(&mut messages), 2);
+
+    let remaining: Vec<_> = messages
+        .iter()
+        .map(|m| (m.timestamp, m.data.as_slice()))
+        .collect();
+    assert_eq!(
+        remaining,
+        vec![
+            (1, b"a".as_slice()),
+            (2, b"a".as_slice()),
+            (2, b"b".as_slice())
+        ]
+    );
+}
+
+#[test]
+fn test_dedup_messages_without_duplicates() {
+    let mut messages = vec![message(1, b"a"), message(2, b"a"), message(2, b"b")];
+
+    assert_eq!(dedup_messages(&mut messages), 0);
+    assert_eq!(messages.len(), 3);
+}
@@ -0,0 +1,39 @@
+use rospeek_core::{ClockMap, RawMessage};
+
+fn clock_message(bag_ns: u64, sec: i32, nanosec: u32) -> RawMessage {
+    let mut data = vec![0x00, 0x01, 0x00, 0x00]; // CDR header
+    data.extend(sec.to_le_bytes());
+    data.extend(nanosec.to_le_bytes());
+    RawMessage {
+        timestamp: bag_ns,
+        topic_id: 1,
+        data,
+    }
+}
+
+#[test]
+fn test_clock_map_conversion() {
+    // simulation running at half of real-time rate
+    let messages = vec![
+        clock_message(1_000_000_000, 10, 0),
+        clock_message(3_000_000_000, 11, 0),
+    ];
+    let clock = ClockMap::from_messages(&messages)
+        .expect("Failed to decode clock messages")
+        .expect("Clock map should not be empty");
+
+    assert_eq!(clock.len(), 2);
+    assert_eq!(clock.sim_range(), (10_000_000_000, 11_000_000_000));
+
+    assert_eq!(clock.to_sim(2_000_000_000), 10_500_000_000);
+    assert_eq!(clock.to_sim(500_000_000), 9_500_000_000);
+    assert_eq!(clock.to_sim(4_000_000_000), 12_000_000_000);
+
+    assert_eq!(clock.to_bag(10_500_000_000), 2_000_000_000);
+    assert_eq!(clock.to_bag(clock.to_sim(2_500_000_000)), 2_500_000_000);
+}
+
+#[test]
+fn test_clock_map_empty() {
+    assert!(ClockMap::from_messages(&[]).unwrap().is_none());
+}
@@ -0,0 +1,57 @@
+use rospeek_core::{compare_dumps, load_dump};
+use serde_json::json;
+
+#[test]
+fn test_compare_dumps() {
+    let expected = vec![
+        json!({"objects": [{"x": 1.0, "label": "car"}], "frame_id": "map"}),
+        json!({"objects": [], "frame_id": "map"}),
+    ];
+    let actual = vec![
+        json!({"objects": [{"x": 1.1, "label": "car"}], "frame_id": "map", "extra": 1}),
+        json!({"objects": [], "frame_id": "map"}),
+        json!({"objects": [], "frame_id": "base_link"}),
+    ];
+
+    let differences = compare_dumps(&expected, &actual, 1e-6);
+    let summary: Vec<_> = differences
+        .iter()
+        .map(|d| {
+            (
+                d.index,
+                d.path.as_str(),
+                d.expected.is_some(),
+                d.actual.is_some(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0, "objects.0.x", true, true),
+            (0, "extra", false, true),
+            (2, "", false, true),
+        ]
+    );
+
+    assert_eq!(compare_dumps(&expected, &actual[..2], 0.2).len(), 1);
+}
+
+#[test]
+fn test_load_dump() {
+    let dir = std::env::temp_dir();
+    let array = dir.join(format!("rospeek-dump-{}.json", std::process::id()));
+    let lines = dir.join(format!("rospeek-dump-{}.jsonl", std::process::id()));
+    std::fs::write(&array, "\n[{\"a\": 1}, {\"a\": 2}]").unwrap();
+    std::fs::write(&lines, "{\"a\": 1}\n\n{\"a\": 2}\n").unwrap();
+
+    let expected = vec![json!({"a": 1}), json!({"a": 2})];
+    assert_eq!(load_dump(&array).unwrap(), expected);
+    assert_eq!(load_dump(&lines).unwrap(), expected);
+
+    std::fs::write(&lines, "{\"a\": 1}\n{").unwrap();
+    assert!(load_dump(&lines).is_err());
+
+    let _ = std::fs::remove_file(array);
+    let _ = std::fs::remove_file(lines);
+}
@@ -0,0 +1,117 @@
+use rospeek_core::{
+    CdrDecoder, Config, DecodePlan, DecodeRule, DecoderRegistry, FieldType, MessageField,
+    MessageSchema, RosPeekResult, decode_with_rule,
+};
+use serde_json::{Value, json};
+
+/// CDR-encoded message with `uint32 width` and `uint8[] data = [1, 2, 3]`.
+static BLOB_DATA: [u8; 15] = [
+    0x00, 0x01, 0x00, 0x00, // CDR header
+    0x02, 0x00, 0x00, 0x00, // width = 2
+    0x03, 0x00, 0x00, 0x00, // data length = 3
+    0x01, 0x02, 0x03, // data
+];
+
+fn blob_schema() -> MessageSchema {
+    MessageSchema {
+        type_name: "test_msgs/msg/Blob".into(),
+        fields: vec![
+            MessageField {
+                name: "width".into(),
+                field_type: FieldType::Object("uint32".into()),
+            },
+            MessageField {
+                name: "data".into(),
+                field_type: FieldType::Sequence("uint8".into()),
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_config_rule_for() {
+    let config = Config::from_yaml(
+        r#"
+overrides:
+  - topic: /camera/image_raw
+    rule: bytes
+  - type: sensor_msgs/msg/Image
+    rule: skip_blobs
+"#,
+    )
+    .expect("Failed to parse config");
+
+    // the first matching entry wins
+    assert_eq!(
+        config.rule_for("/camera/image_raw", "sensor_msgs/msg/Image"),
+        Some(&DecodeRule::Bytes)
+    );
+    assert_eq!(
+        config.rule_for("/camera/depth", "sensor_msgs/msg/Image"),
+        Some(&DecodeRule::SkipBlobs)
+    );
+    assert_eq!(config.rule_for("/odom", "nav_msgs/msg/Odometry"), None);
+
+    assert!(Config::from_yaml("overrides:\n  - rule: bytes\n").is_err());
+    assert!(Config::from_yaml("overrides:\n  - topic: /a\n    rule: unknown\n").is_err());
+}
+
+#[test]
+fn test_config_decoder_rule() {
+    let config = Config::from_yaml(
+        r#"
+overrides:
+  - topic: /sensor/packed
+    rule:
+      decoder: packed
+"#,
+    )
+    .expect("Failed to parse config");
+    assert_eq!(
+        config.rule_for("/sensor/packed", "vendor_msgs/msg/Packed"),
+        Some(&DecodeRule::Decoder("packed".into()))
+    );
+
+    assert!(Config::from_yaml("overrides:\n  - topic: /a\n    rule:\n      other: x\n").is_err());
+}
+
+#[test]
+fn test_decode_with_rule() {
+    let schema = blob_schema();
+    let plan = DecodePlan::compile(&schema).expect("Failed to compile plan");
+    let mut decoder = CdrDecoder::new(&[]);
+
+    let value = decode_with_rule(None, &mut decoder, Some(&plan), &BLOB_DATA)
+        .expect("Failed to decode message");
+    assert_eq!(value, json!({"width": 2, "data": [1, 2, 3]}));
+
+    let value = decode_with_rule(
+        Some(&DecodeRule::SkipBlobs),
+        &mut decoder,
+        Some(&plan),
+        &BLOB_DATA,
+    )
+    .expect("Failed to decode message");
+    assert_eq!(value, json!({"width": 2, "data": {"bytes": 3}}));
+
+    let value = decode_with_rule(
+        Some(&DecodeRule::Bytes),
+        &mut decoder,
+        None,
+        &BLOB_DATA[..6],
+    )
+    .expect("Failed to decode message");
+    assert_eq!(value, json!({"size": 6, "hex": "000100000200"}));
+
+    DecoderRegistry::global().register("test-length", |data: &[u8]| -> RosPeekResult<Value> {
+        Ok(json!({ "length": data.len() }))
+    });
+    let rule = DecodeRule::Decoder("test-length".into());
+    let value = decode_with_rule(Some(&rule), &mut decoder, None, &BLOB_DATA)
+        .expect("Failed to decode message");
+    assert_eq!(value, json!({"length": 15}));
+
+    let rule = DecodeRule::Decoder("missing".into());
+    assert!(decode_with_rule(Some(&rule), &mut decoder, None, &BLOB_DATA).is_err());
+    assert!(decode_with_rule(None, &mut decoder, None, &BLOB_DATA).is_err());
+}
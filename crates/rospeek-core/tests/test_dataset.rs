@@ -0,0 +1,44 @@
+use rospeek_core::{DatasetConfig, DatasetLayout};
+
+#[test]
+fn test_dataset_config() {
+    let config = DatasetConfig::from_yaml(
+        r#"
+layout: nuscenes
+lidar:
+  topic: /lidar
+cameras:
+  - name: CAM_FRONT
+    topic: /camera/front/image/compressed
+    calibration: front.yaml
+max_offset: 0.05
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.layout, DatasetLayout::Nuscenes);
+    assert_eq!(config.lidar_name(), "LIDAR_TOP");
+    assert_eq!(
+        config.topics(),
+        vec![
+            "/lidar".to_string(),
+            "/camera/front/image/compressed".to_string()
+        ]
+    );
+    let options = config.sample_options();
+    assert_eq!(options.stride, 1);
+    assert_eq!(options.max_offset_ns, Some(50_000_000));
+}
+
+#[test]
+fn test_dataset_config_requires_calibration() {
+    let yaml = r#"
+layout: kitti
+lidar:
+  topic: /lidar
+cameras:
+  - name: image_2
+    topic: /camera/image
+"#;
+    assert!(DatasetConfig::from_yaml(yaml).is_err());
+    assert!(DatasetConfig::from_yaml("layout: waymo\nlidar:\n  topic: /lidar\n").is_err());
+}
@@ -0,0 +1,89 @@
+use rospeek_core::{Comparison, Condition, EventOptions, EventRule, find_events};
+use serde_json::json;
+
+#[test]
+fn test_parse_event_rule() {
+    let rule: EventRule = "hard brake : /vehicle/status/brake.report.value >= 0.5"
+        .parse()
+        .unwrap();
+    assert_eq!(rule.label, "hard brake");
+    assert_eq!(rule.topic, "/vehicle/status/brake");
+    assert_eq!(rule.field, "report.value");
+    assert_eq!(rule.comparison, Comparison::GreaterEqual);
+    assert_eq!(rule.threshold, 0.5);
+
+    let rule: EventRule = "stopped: /odom.twist.twist.linear.x<0.1".parse().unwrap();
+    assert_eq!(rule.comparison, Comparison::Less);
+    assert_eq!(rule.field, "twist.twist.linear.x");
+
+    let rule: EventRule = "engaged: /autoware/engage.engage == true".parse().unwrap();
+    assert_eq!(rule.comparison, Comparison::Equal);
+    assert_eq!(rule.threshold, 1.0);
+}
+
+#[test]
+fn test_parse_invalid_event_rule() {
+    for rule in [
+        "/vehicle/brake.data > 0.8",
+        ": /vehicle/brake.data > 0.8",
+        "brake: /vehicle/brake > 0.8",
+        "brake: /vehicle/brake.data 0.8",
+        "brake: /vehicle/brake.data > high",
+    ] {
+        assert!(rule.parse::<EventRule>().is_err(), "{rule}");
+    }
+}
+
+#[test]
+fn test_condition_holds() {
+    let condition: Condition = "/autoware/engage.engage == true".parse().unwrap();
+    assert!(condition.holds(&json!(true)));
+    assert!(!condition.holds(&json!(false)));
+    assert!(!condition.holds(&json!(null)));
+
+    let condition: Condition = "/vehicle/brake.data>=0.9".parse().unwrap();
+    assert_eq!(condition.topic, "/vehicle/brake");
+    assert!(condition.holds(&json!(0.9)));
+    assert!(!condition.holds(&json!(0.5)));
+
+    assert!("/vehicle/brake.data".parse::<Condition>().is_err());
+}
+
+#[test]
+fn test_find_events_min_duration() {
+    let samples = [
+        (0, 1.0),
+        (10, 1.0),
+        (20, 0.0),
+        (30, 1.0),
+        (40, 0.0),
+        (50, 1.0),
+    ];
+    let options = EventOptions {
+        min_duration_ns: 10,
+        ..Default::default()
+    };
+    // the single-message event and the one still active at the end are too short
+    assert_eq!(
+        find_events(&samples, Comparison::Equal, 1.0, &options),
+        vec![(0, 10)]
+    );
+}
+
+#[test]
+fn test_find_events_hysteresis_below() {
+    let samples = [(0, 5.0), (10, 0.5), (20, 1.2), (30, 0.8), (40, 2.0)];
+    let options = EventOptions {
+        hysteresis: 0.5,
+        ..Default::default()
+    };
+    assert_eq!(
+        find_events(&samples, Comparison::Less, 1.0, &options),
+        vec![(10, 30)]
+    );
+    // without hysteresis, crossing back above the threshold ends the event
+    assert_eq!(
+        find_events(&samples, Comparison::Less, 1.0, &EventOptions::default()),
+        vec![(10, 10), (30, 30)]
+    );
+}
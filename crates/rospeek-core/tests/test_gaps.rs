@@ -0,0 +1,71 @@
+use rospeek_core::{Gap, RawMessage, TopicHealth, TopicStats, count_in_bins, find_gaps};
+
+#[test]
+fn test_find_gaps() {
+    // 10 Hz with a dropout of one second and a shorter hiccup
+    let mut timestamps: Vec<u64> = (0..10).map(|i| i * 100_000_000).collect();
+    timestamps.extend((20..30).map(|i| i * 100_000_000));
+    timestamps.push(3_200_000_000);
+
+    let gaps = find_gaps(&timestamps, 3.0);
+    assert_eq!(
+        gaps,
+        vec![Gap {
+            start_ns: 900_000_000,
+            end_ns: 2_000_000_000,
+        }]
+    );
+    assert_eq!(gaps[0].duration_sec(), 1.1);
+    assert_eq!(find_gaps(&timestamps, 1.5).len(), 2);
+
+    assert!(find_gaps(&[0, 100], 3.0).is_empty());
+    assert!(find_gaps(&[5, 5, 5, 100], 3.0).is_empty());
+}
+
+#[test]
+fn test_count_in_bins() {
+    let timestamps = [0, 1, 2, 3, 9, 10, 11];
+    assert_eq!(count_in_bins(&timestamps, 0, 9, 2), vec![4, 1]);
+    assert_eq!(count_in_bins(&timestamps, 0, 9, 0), Vec::<u64>::new());
+    assert_eq!(count_in_bins(&timestamps, 10, 0, 3), vec![0, 0, 0]);
+}
+
+#[test]
+fn test_topic_health() {
+    let stats = |timestamps: &[u64]| {
+        let mut stats = TopicStats::default();
+        for &timestamp in timestamps {
+            stats.accumulate(&RawMessage {
+                timestamp,
+                topic_id: 1,
+                data: Vec::new(),
+            });
+        }
+        stats
+    };
+    let second = 1_000_000_000;
+
+    assert_eq!(
+        TopicHealth::assess(&stats(&[]), 0, 10 * second),
+        TopicHealth::Empty
+    );
+    // latched topics publish once
+    assert_eq!(
+        TopicHealth::assess(&stats(&[0]), 0, 10 * second),
+        TopicHealth::Healthy
+    );
+
+    let steady: Vec<u64> = (0..=100).map(|i| i * second / 10).collect();
+    assert_eq!(
+        TopicHealth::assess(&stats(&steady), second / 10, 10 * second),
+        TopicHealth::Healthy
+    );
+    assert_eq!(
+        TopicHealth::assess(&stats(&steady), second / 10, 20 * second),
+        TopicHealth::Stale
+    );
+    assert_eq!(
+        TopicHealth::assess(&stats(&steady), 3 * second, 10 * second),
+        TopicHealth::Sparse
+    );
+}
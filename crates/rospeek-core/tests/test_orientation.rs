@@ -0,0 +1,71 @@
+use std::f64::consts::FRAC_PI_4;
+
+use rospeek_core::{add_euler_angles, find_quaternions, quaternion_to_euler};
+use serde_json::json;
+
+#[test]
+fn test_quaternion_to_euler() {
+    // roll 30°, pitch 20°, yaw 10° composed as yaw * pitch * roll
+    let (roll, pitch, yaw) = (30f64.to_radians(), 20f64.to_radians(), 10f64.to_radians());
+    let (cr, sr) = ((roll / 2.0).cos(), (roll / 2.0).sin());
+    let (cp, sp) = ((pitch / 2.0).cos(), (pitch / 2.0).sin());
+    let (cy, sy) = ((yaw / 2.0).cos(), (yaw / 2.0).sin());
+    let quaternion = [
+        sr * cp * cy - cr * sp * sy,
+        cr * sp * cy + sr * cp * sy,
+        cr * cp * sy - sr * sp * cy,
+        cr * cp * cy + sr * sp * sy,
+    ];
+    // scaling does not change the rotation
+    let euler = quaternion_to_euler(quaternion.map(|v| v * 3.0));
+    for (value, expected) in euler.iter().zip([roll, pitch, yaw]) {
+        assert!((value - expected).abs() < 1e-12);
+    }
+
+    // gimbal lock
+    let [_, pitch, _] = quaternion_to_euler([0.0, FRAC_PI_4.sin(), 0.0, FRAC_PI_4.cos()]);
+    assert!((pitch - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+
+    assert_eq!(quaternion_to_euler([0.0; 4]), [0.0; 3]);
+}
+
+#[test]
+fn test_find_quaternions() {
+    let value = json!({
+        "pose": {
+            "position": {"x": 1.0, "y": 2.0, "z": 3.0},
+            "orientation": {"x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0},
+        },
+        // not a quaternion
+        "rotation": {"x": 0.0, "y": 0.0, "z": 0.0},
+        // decoded with --fields
+        "pose.pose.orientation": {"x": 1.0, "y": 0.0, "z": 0.0, "w": 0.0},
+    });
+    let mut quaternions = find_quaternions(&value);
+    quaternions.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        quaternions,
+        vec![
+            ("pose.orientation".to_string(), [0.0, 0.0, 0.0, 1.0]),
+            ("pose.pose.orientation".to_string(), [1.0, 0.0, 0.0, 0.0]),
+        ]
+    );
+}
+
+#[test]
+fn test_add_euler_angles() {
+    let half = FRAC_PI_4;
+    let mut value = json!({
+        "transforms": [
+            {"transform": {"rotation": {"x": 0.0, "y": 0.0, "z": half.sin(), "w": half.cos()}}},
+        ],
+        "orientation_covariance": vec![0.0; 9],
+    });
+    add_euler_angles(&mut value);
+
+    let rotation = &value["transforms"][0]["transform"]["rotation"];
+    assert!(rotation["roll_deg"].as_f64().unwrap().abs() < 1e-9);
+    assert!(rotation["pitch_deg"].as_f64().unwrap().abs() < 1e-9);
+    assert!((rotation["yaw_deg"].as_f64().unwrap() - 90.0).abs() < 1e-9);
+    assert_eq!(value["orientation_covariance"], json!(vec![0.0; 9]));
+}
@@ -0,0 +1,139 @@
+use rospeek_core::{CdrWriter, Endianness, PointCloud, PointFieldType};
+
+/// Encodes a cloud of `(x, intensity)` points with 3 bytes of padding between the fields.
+fn encode_cloud(endianness: Endianness, points: &[(f32, u8)]) -> Vec<u8> {
+    let mut writer = CdrWriter::new(endianness);
+    writer.write_i32(0);
+    writer.write_u32(0);
+    writer.write_str("lidar");
+    writer.write_u32(1);
+    writer.write_u32(points.len() as u32);
+    // fields listed out of offset order
+    writer.write_u32(2);
+    writer.write_str("intensity");
+    writer.write_u32(4);
+    writer.write_u8(2);
+    writer.write_u32(1);
+    writer.write_str("x");
+    writer.write_u32(0);
+    writer.write_u8(7);
+    writer.write_u32(1);
+    writer.write_bool(endianness == Endianness::Big);
+    writer.write_u32(8);
+    writer.write_u32(8 * points.len() as u32);
+    writer.write_u32(8 * points.len() as u32);
+    for &(x, intensity) in points {
+        match endianness {
+            Endianness::Big => writer.write_bytes(&x.to_be_bytes()),
+            Endianness::Little => writer.write_bytes(&x.to_le_bytes()),
+        }
+        writer.write_bytes(&[intensity, 0, 0, 0]);
+    }
+    writer.write_bool(true);
+    writer.into_bytes()
+}
+
+#[test]
+fn test_point_cloud_from_cdr() {
+    let cloud = PointCloud::from_cdr(&encode_cloud(Endianness::Little, &[(1.5, 7)])).unwrap();
+    assert_eq!(cloud.frame_id, "lidar");
+    assert_eq!(cloud.len(), 1);
+    assert_eq!(cloud.fields[0].name, "intensity");
+    assert_eq!(cloud.fields[0].datatype, PointFieldType::UInt8);
+    assert_eq!(cloud.fields[1].datatype, PointFieldType::Float32);
+    assert_eq!(cloud.point(0)[4], 7);
+    assert!(cloud.is_dense);
+
+    // data shorter than the declared points
+    let mut data = encode_cloud(Endianness::Little, &[(1.5, 7)]);
+    data[28] = 2; // width, after the stamp, frame ID and height
+    assert!(PointCloud::from_cdr(&data).is_err());
+}
+
+#[test]
+fn test_point_cloud_write_pcd() {
+    for endianness in [Endianness::Little, Endianness::Big] {
+        let cloud =
+            PointCloud::from_cdr(&encode_cloud(endianness, &[(1.5, 7), (-2.0, 9)])).unwrap();
+        let mut pcd = Vec::new();
+        cloud.write_pcd(&mut pcd).unwrap();
+
+        let header_end = pcd.windows(12).position(|w| w == b"DATA binary\n").unwrap() + 12;
+        let header = std::str::from_utf8(&pcd[..header_end]).unwrap();
+        assert!(header.contains("FIELDS x intensity\n"));
+        assert!(header.contains("SIZE 4 1\n"));
+        assert!(header.contains("TYPE F U\n"));
+        assert!(header.contains("POINTS 2\n"));
+
+        // packed little-endian points without padding
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&1.5f32.to_le_bytes());
+        expected.push(7);
+        expected.extend_from_slice(&(-2.0f32).to_le_bytes());
+        expected.push(9);
+        assert_eq!(&pcd[header_end..], expected.as_slice());
+    }
+}
+
+#[test]
+fn test_point_cloud_value() {
+    for endianness in [Endianness::Little, Endianness::Big] {
+        let cloud =
+            PointCloud::from_cdr(&encode_cloud(endianness, &[(1.5, 7), (-2.0, 9)])).unwrap();
+        let x = cloud.field("x").unwrap();
+        let intensity = cloud.field("intensity").unwrap();
+        assert_eq!(cloud.value(1, x), -2.0);
+        assert_eq!(cloud.value(1, intensity), 9.0);
+        assert!(cloud.field("ring").is_none());
+    }
+}
+
+#[test]
+fn test_point_cloud_select_fields() {
+    for endianness in [Endianness::Little, Endianness::Big] {
+        let cloud =
+            PointCloud::from_cdr(&encode_cloud(endianness, &[(1.5, 7), (-2.0, 9)])).unwrap();
+        assert_eq!(cloud.padding(), 3);
+
+        let selected = cloud.select_fields(&["x", "intensity"]).unwrap();
+        assert_eq!(selected.point_step, 5);
+        assert_eq!(selected.row_step, 10);
+        assert_eq!(selected.padding(), 0);
+        assert_eq!(selected.fields[0].name, "x");
+        assert_eq!(selected.fields[1].offset, 4);
+        let x = selected.field("x").unwrap();
+        let intensity = selected.field("intensity").unwrap();
+        assert_eq!(selected.value(1, x), -2.0);
+        assert_eq!(selected.value(1, intensity), 9.0);
+
+        let only_x = cloud.select_fields(&["x"]).unwrap();
+        assert_eq!(only_x.data.len(), 8);
+        assert!(cloud.select_fields(&["ring"]).is_err());
+    }
+}
+
+#[test]
+fn test_point_cloud_voxel_downsample() {
+    let points = [(0.1, 1), (0.4, 2), (1.2, 3), (f32::NAN, 4), (-0.1, 5)];
+    let cloud = PointCloud::from_cdr(&encode_cloud(Endianness::Little, &points)).unwrap();
+    // the y and z fields are required
+    assert!(cloud.voxel_downsample(1.0).is_err());
+
+    // x repeated as y and z
+    let mut cloud = cloud.select_fields(&["x", "x", "x", "intensity"]).unwrap();
+    for (field, name) in cloud.fields.iter_mut().zip(["x", "y", "z"]) {
+        field.name = name.to_string();
+    }
+    let downsampled = cloud.voxel_downsample(0.5).unwrap();
+    assert_eq!(downsampled.len(), 3);
+    let intensity = downsampled.field("intensity").unwrap();
+    let kept: Vec<f64> = (0..downsampled.len())
+        .map(|i| downsampled.value(i, intensity))
+        .collect();
+    // the first point of each voxel is kept and the NaN point is dropped
+    assert_eq!(kept, vec![1.0, 3.0, 5.0]);
+    assert!(downsampled.is_dense);
+
+    assert_eq!(cloud.voxel_downsample(10.0).unwrap().len(), 2);
+    assert!(cloud.voxel_downsample(0.0).is_err());
+}
@@ -0,0 +1,43 @@
+use rospeek_core::{CdrWriter, Endianness, Pose};
+
+fn encode_odometry(position: [f64; 3], orientation: [f64; 4]) -> Vec<u8> {
+    let mut writer = CdrWriter::new(Endianness::Little);
+    writer.write_i32(0);
+    writer.write_u32(0);
+    writer.write_str("map");
+    writer.write_str("base_link");
+    for value in position.iter().chain(orientation.iter()) {
+        writer.write_f64(*value);
+    }
+    writer.into_bytes()
+}
+
+#[test]
+fn test_pose_from_cdr() {
+    let data = encode_odometry([1.0, 2.0, 3.0], [0.0, 0.0, 0.0, 1.0]);
+    let pose = Pose::from_cdr("nav_msgs/msg/Odometry", &data).unwrap();
+    assert_eq!(pose.position, [1.0, 2.0, 3.0]);
+    assert_eq!(pose.orientation, [0.0, 0.0, 0.0, 1.0]);
+
+    assert!(Pose::from_cdr("std_msgs/msg/String", &data).is_err());
+}
+
+#[test]
+fn test_pose_rotation_matrix() {
+    // a quarter turn around z, not normalized
+    let half = std::f64::consts::FRAC_PI_4;
+    let pose = Pose {
+        position: [0.0; 3],
+        orientation: [0.0, 0.0, 2.0 * half.sin(), 2.0 * half.cos()],
+    };
+    let expected = [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+    for (row, expected) in pose.rotation_matrix().iter().zip(expected.iter()) {
+        for (value, expected) in row.iter().zip(expected.iter()) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+    }
+    assert_eq!(
+        Pose::default().rotation_matrix(),
+        [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+    );
+}
@@ -0,0 +1,90 @@
+use rospeek_core::{History, QosProfile, Topic, lint_qos};
+
+/// Profiles as recorded by Humble, where the history of a discovered publisher is unknown.
+const HUMBLE_RELIABLE: &str = r#"- history: 3
+  depth: 0
+  reliability: 1
+  durability: 2
+  deadline:
+    sec: 9223372036
+    nsec: 854775807
+  lifespan:
+    sec: 9223372036
+    nsec: 854775807
+  liveliness: 1
+  liveliness_lease_duration:
+    sec: 9223372036
+    nsec: 854775807
+  avoid_ros_namespace_conventions: false
+"#;
+
+fn topic(name: &str, type_name: &str, profiles: Option<&str>) -> Topic {
+    Topic {
+        id: 1,
+        name: name.to_string(),
+        type_name: type_name.to_string(),
+        count: 0,
+        serialization_format: "cdr".to_string(),
+        offered_qos_profiles: profiles.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_qos_profile_parse_all() {
+    let profiles = QosProfile::parse_all(HUMBLE_RELIABLE);
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[0].history, History::Unknown);
+    assert_eq!(profiles[0].depth, 0);
+
+    assert!(QosProfile::parse_all("").is_empty());
+    assert!(QosProfile::parse_all("not: a list").is_empty());
+}
+
+#[test]
+fn test_lint_qos() {
+    let shallow = "- {history: keep_last, depth: 1, reliability: reliable, durability: volatile}\n";
+    let latched =
+        "- {history: keep_last, depth: 1, reliability: reliable, durability: transient_local}\n";
+    let best_effort =
+        "- {history: keep_last, depth: 5, reliability: best_effort, durability: volatile}\n";
+    let topics = vec![
+        topic("/sensing/imu", "sensor_msgs/msg/Imu", Some(shallow)),
+        topic(
+            "/sensing/points",
+            "sensor_msgs/msg/PointCloud2",
+            Some(latched),
+        ),
+        topic("/tf_static", "tf2_msgs/msg/TFMessage", Some(shallow)),
+        topic("/map", "nav_msgs/msg/OccupancyGrid", Some(latched)),
+        topic(
+            "/odom",
+            "nav_msgs/msg/Odometry",
+            Some(&format!("{shallow}{best_effort}")),
+        ),
+        // unknown depth and missing profiles are not flagged
+        topic(
+            "/sensing/camera",
+            "sensor_msgs/msg/Image",
+            Some(HUMBLE_RELIABLE),
+        ),
+        topic("/sensing/scan", "sensor_msgs/msg/LaserScan", None),
+    ];
+
+    let findings: Vec<_> = lint_qos(&topics)
+        .into_iter()
+        .map(|f| (f.topic, f.rule))
+        .collect();
+    assert_eq!(
+        findings,
+        vec![
+            ("/odom".to_string(), "mixed-reliability"),
+            ("/sensing/imu".to_string(), "sensor-data-reliable-shallow"),
+            (
+                "/sensing/points".to_string(),
+                "sensor-data-reliable-shallow"
+            ),
+            ("/sensing/points".to_string(), "sensor-data-transient-local"),
+            ("/tf_static".to_string(), "latched-volatile"),
+        ]
+    );
+}
@@ -0,0 +1,69 @@
+use rospeek_core::{
+    CdrDecoder, DecodeReport, FailureKind, FieldType, MessageField, MessageSchema, RawMessage,
+    TypeVersion, find_decode_failures,
+};
+
+fn message(timestamp: u64, data: &[u8]) -> RawMessage {
+    RawMessage {
+        timestamp,
+        topic_id: 1,
+        data: data.to_vec(),
+    }
+}
+
+fn version(type_name: &str) -> TypeVersion {
+    TypeVersion {
+        type_name: type_name.into(),
+        definition: None,
+        files: vec![],
+        count: 0,
+        start_ns: 0,
+        end_ns: 0,
+    }
+}
+
+#[test]
+fn test_failure_kind_of() {
+    let schema = MessageSchema {
+        type_name: "test_msgs/msg/Text".into(),
+        fields: vec![MessageField {
+            name: "text".into(),
+            field_type: FieldType::Object("string".into()),
+        }],
+    };
+    let truncated = [0x00, 0x01, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, b'a'];
+    let error = CdrDecoder::new(&truncated).decode(&schema).unwrap_err();
+    assert_eq!(FailureKind::of(&error, None), FailureKind::Truncated);
+
+    let invalid = [0x00, 0x01, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0xff, 0x00];
+    let error = CdrDecoder::new(&invalid).decode(&schema).unwrap_err();
+    assert_eq!(FailureKind::of(&error, None), FailureKind::InvalidUtf8);
+}
+
+#[test]
+fn test_find_decode_failures() {
+    let versions = [version("missing_msgs/msg/Missing")];
+    let messages = [
+        message(1, &[0x00, 0x01, 0x00, 0x00, 0x2a]),
+        message(2, &[0x00, 0x07, 0x00, 0x00]),
+    ];
+
+    let failures = find_decode_failures("/missing", &versions, &messages);
+
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].kind, FailureKind::MissingSchema);
+    assert_eq!(failures[0].offset, 0);
+    assert_eq!(failures[0].timestamp, 1);
+    assert_eq!(failures[0].size, 5);
+    assert_eq!(failures[0].head, "000100002a");
+    assert_eq!(failures[1].kind, FailureKind::UnsupportedEncapsulation);
+    assert_eq!(failures[1].offset, 1);
+
+    let report = DecodeReport {
+        version: "0.0.0".into(),
+        failures,
+    };
+    let json = serde_json::to_value(&report).expect("Failed to serialize report");
+    assert_eq!(json["failures"][0]["kind"], "missing_schema");
+    assert_eq!(json["failures"][1]["kind"], "unsupported_encapsulation");
+}
@@ -0,0 +1,174 @@
+use proptest::{collection::vec, prelude::*};
+use rospeek_core::{
+    CdrDecoder, CdrEncoder, DecodePlan, Endianness, FieldType, MessageSchema, parse_idl_str,
+};
+use serde_json::{Value, json};
+
+const TYPE_NAME: &str = "test_msgs/msg/Random";
+
+const TYPES: &[&str] = &[
+    "boolean", "octet", "char", "int8", "uint8", "int16", "uint16", "int32", "uint32", "int64",
+    "uint64", "float", "double", "string",
+];
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Object,
+    Array(usize),
+    Sequence(Option<usize>),
+}
+
+/// Definition of a generated field, named after its index.
+#[derive(Debug, Clone)]
+struct FieldDef {
+    type_name: &'static str,
+    /// Bound of a bounded string, such as `string<5>`
+    string_bound: Option<usize>,
+    kind: Kind,
+}
+
+impl FieldDef {
+    /// Returns the IDL type of the field, as written in `.idl` files generated by ROS 2.
+    fn idl_type(&self) -> String {
+        match self.string_bound {
+            Some(bound) => format!("string<{bound}>"),
+            None => self.type_name.to_string(),
+        }
+    }
+
+    fn field_type(&self) -> FieldType {
+        let type_name = self.type_name.to_string();
+        match self.kind {
+            Kind::Object => FieldType::Object(type_name),
+            Kind::Array(n) => FieldType::Array(type_name, n),
+            Kind::Sequence(_) => FieldType::Sequence(type_name),
+        }
+    }
+}
+
+fn field_def() -> impl Strategy<Value = FieldDef> {
+    let kind = prop_oneof![
+        Just(Kind::Object),
+        (1usize..5).prop_map(Kind::Array),
+        proptest::option::of(1usize..5).prop_map(Kind::Sequence),
+    ];
+    (
+        proptest::sample::select(TYPES),
+        proptest::option::of(1usize..16),
+        kind,
+    )
+        .prop_map(|(type_name, bound, kind)| FieldDef {
+            type_name,
+            string_bound: bound.filter(|_| type_name == "string"),
+            kind,
+        })
+}
+
+/// Renders field definitions into an IDL file, declaring arrays with typedefs as ROS 2 does.
+fn render_idl(defs: &[FieldDef]) -> String {
+    let mut typedefs = String::new();
+    let mut members = String::new();
+    for (i, def) in defs.iter().enumerate() {
+        let decl = match def.kind {
+            Kind::Object => def.idl_type(),
+            Kind::Array(n) => {
+                let alias = format!("{}__{n}", def.type_name);
+                typedefs.push_str(&format!("    typedef {} {alias};\n", def.idl_type()));
+                alias
+            }
+            Kind::Sequence(None) => format!("sequence<{}>", def.idl_type()),
+            Kind::Sequence(Some(bound)) => format!("sequence<{}, {bound}>", def.idl_type()),
+        };
+        members.push_str(&format!("      {decl} f{i};\n"));
+    }
+    format!(
+        "module test_msgs {{\n  module msg {{\n{typedefs}    struct Random {{\n{members}    }};\n  }};\n}};\n"
+    )
+}
+
+fn scalar(type_name: &str) -> BoxedStrategy<Value> {
+    match type_name {
+        "boolean" => any::<bool>().prop_map(|v| json!(v)).boxed(),
+        "octet" | "uint8" => any::<u8>().prop_map(|v| json!(v)).boxed(),
+        "char" => proptest::char::range('\x01', '\x7f')
+            .prop_map(|v| json!(v))
+            .boxed(),
+        "int8" => any::<i8>().prop_map(|v| json!(v)).boxed(),
+        "int16" => any::<i16>().prop_map(|v| json!(v)).boxed(),
+        "uint16" => any::<u16>().prop_map(|v| json!(v)).boxed(),
+        "int32" => any::<i32>().prop_map(|v| json!(v)).boxed(),
+        "uint32" => any::<u32>().prop_map(|v| json!(v)).boxed(),
+        "int64" => any::<i64>().prop_map(|v| json!(v)).boxed(),
+        "uint64" => any::<u64>().prop_map(|v| json!(v)).boxed(),
+        "float" => (-1e6f32..1e6f32).prop_map(|v| json!(v)).boxed(),
+        "double" => (-1e12f64..1e12f64).prop_map(|v| json!(v)).boxed(),
+        _ => "[a-zA-Z0-9 äö]{0,12}".prop_map(|v| json!(v)).boxed(),
+    }
+}
+
+fn field_value(def: &FieldDef) -> BoxedStrategy<Value> {
+    let item = scalar(def.type_name);
+    match def.kind {
+        Kind::Object => item,
+        Kind::Array(n) => vec(item, n).prop_map(Value::from).boxed(),
+        Kind::Sequence(bound) => vec(item, 0..=bound.unwrap_or(8))
+            .prop_map(Value::from)
+            .boxed(),
+    }
+}
+
+/// Generates field definitions along with a message of values for them.
+fn message() -> impl Strategy<Value = (Vec<FieldDef>, Value)> {
+    vec(field_def(), 1..8).prop_flat_map(|defs| {
+        let values: Vec<_> = defs.iter().map(field_value).collect();
+        (Just(defs), values).prop_map(|(defs, values)| {
+            let object = values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| (format!("f{i}"), value))
+                .collect();
+            (defs, Value::Object(object))
+        })
+    })
+}
+
+fn endianness() -> impl Strategy<Value = Endianness> {
+    prop_oneof![Just(Endianness::Little), Just(Endianness::Big)]
+}
+
+proptest! {
+    #[test]
+    fn test_parse_encode_decode_roundtrip((defs, value) in message(), endianness in endianness()) {
+        let idl = render_idl(&defs);
+        let schema = parse_idl_str(&idl, TYPE_NAME);
+
+        prop_assert_eq!(schema.fields.len(), defs.len(), "{}", idl);
+        for (i, (field, def)) in schema.fields.iter().zip(defs.iter()).enumerate() {
+            prop_assert_eq!(&field.name, &format!("f{i}"));
+            prop_assert_eq!(format!("{:?}", field.field_type), format!("{:?}", def.field_type()));
+        }
+
+        let data = CdrEncoder::new(endianness)
+            .encode(&schema, &value)
+            .expect("Failed to encode message");
+        let decoded = CdrDecoder::new(&data)
+            .decode(&schema)
+            .expect("Failed to decode message");
+        prop_assert_eq!(&decoded, &value);
+
+        let plan = DecodePlan::compile(&schema).expect("Failed to compile plan");
+        let decoded = CdrDecoder::new(&data)
+            .decode_plan(&plan)
+            .expect("Failed to decode message with plan");
+        prop_assert_eq!(&decoded, &value);
+    }
+
+    #[test]
+    fn test_decode_arbitrary_data((defs, _) in message(), data in vec(any::<u8>(), 0..64)) {
+        // malformed data must be reported as an error rather than panic or exhaust memory
+        let schema: MessageSchema = parse_idl_str(&render_idl(&defs), TYPE_NAME);
+        let _ = CdrDecoder::new(&data).decode(&schema);
+        let plan = DecodePlan::compile(&schema).expect("Failed to compile plan");
+        let _ = CdrDecoder::new(&data).decode_plan(&plan);
+    }
+}
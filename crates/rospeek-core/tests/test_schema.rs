@@ -0,0 +1,31 @@
+#![cfg(feature = "schema-fs")]
+
+use rospeek_core::{MessageSchema, find_ros_idl_path, set_msg_paths};
+
+#[test]
+fn test_set_msg_paths() {
+    let dir = std::env::temp_dir().join(format!("rospeek-msg-path-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("foo_msgs/msg")).unwrap();
+    let idl = "module foo_msgs {\n  module msg {\n    struct Foo {\n      int32 x;\n      sequence<double> y;\n    };\n  };\n};\n";
+    std::fs::write(dir.join("foo_msgs/msg/Foo.idl"), idl).unwrap();
+
+    assert!(find_ros_idl_path("foo_msgs/msg/Foo").is_none());
+
+    set_msg_paths(vec![dir.clone()]);
+    assert_eq!(
+        find_ros_idl_path("foo_msgs/msg/Foo"),
+        Some(dir.join("foo_msgs/msg/Foo.idl"))
+    );
+    let schema = MessageSchema::try_from("foo_msgs/msg/Foo").expect("Failed to resolve schema");
+    let fields: Vec<_> = schema
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.type_name()))
+        .collect();
+    assert_eq!(fields, vec![("x", "int32"), ("y", "double")]);
+    assert!(find_ros_idl_path("foo_msgs/msg/Bar").is_none());
+
+    set_msg_paths(Vec::new());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
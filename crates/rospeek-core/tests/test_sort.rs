@@ -0,0 +1,56 @@
+use rospeek_core::{ExternalSorter, RawMessage, SortKey};
+
+fn entry(time: u64, seq: u64) -> (SortKey, RawMessage) {
+    (
+        SortKey {
+            time,
+            topic: 0,
+            seq,
+        },
+        RawMessage {
+            timestamp: time,
+            topic_id: 1,
+            data: vec![seq as u8; 16],
+        },
+    )
+}
+
+#[test]
+fn test_external_sorter_in_memory() {
+    let mut sorter = ExternalSorter::new(usize::MAX);
+    for (time, seq) in [(3, 0), (1, 1), (2, 2)] {
+        let (key, message) = entry(time, seq);
+        sorter.push(key, message).unwrap();
+    }
+    assert_eq!(sorter.spilled_runs(), 0);
+
+    let times: Vec<_> = sorter
+        .finish()
+        .unwrap()
+        .map(|e| e.unwrap().1.timestamp)
+        .collect();
+    assert_eq!(times, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_external_sorter_spills_runs() {
+    // a budget of zero spills every message into its own run
+    let mut sorter = ExternalSorter::new(0);
+    let times = [5, 3, 9, 1, 3, 7];
+    for (seq, time) in times.iter().enumerate() {
+        let (key, message) = entry(*time, seq as u64);
+        sorter.push(key, message).unwrap();
+    }
+    assert_eq!(sorter.spilled_runs(), times.len());
+
+    let sorted: Vec<_> = sorter
+        .finish()
+        .unwrap()
+        .map(|e| {
+            let (key, message) = e.unwrap();
+            assert_eq!(message.data, vec![key.seq as u8; 16]);
+            (message.timestamp, key.seq)
+        })
+        .collect();
+    assert_eq!(sorted, vec![(1, 3), (3, 1), (3, 4), (5, 0), (7, 5), (9, 2)]);
+}
@@ -0,0 +1,44 @@
+use std::f64::consts::PI;
+
+use rospeek_core::{SpectrumWindow, spectrum};
+
+/// Samples `offset + amplitude * sin(2π * frequency_hz * t)` at jittered times around a rate.
+fn sine(frequency_hz: f64, amplitude: f64, offset: f64, rate_hz: f64, n: usize) -> Vec<(u64, f64)> {
+    (0..n)
+        .map(|i| {
+            let jitter = if i % 2 == 0 { 0.1 } else { -0.1 };
+            let t = (i as f64 + jitter) / rate_hz;
+            let t = t.max(0.0);
+            (
+                (t * 1e9) as u64,
+                offset + amplitude * (2.0 * PI * frequency_hz * t).sin(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_spectrum_peak() {
+    let samples = sine(12.5, 0.5, 9.8, 200.0, 1000);
+    for window in SpectrumWindow::ALL {
+        let spectrum = spectrum(&samples, window).unwrap();
+        assert_eq!(spectrum.amplitudes.len(), 1024 / 2 + 1);
+        assert!((spectrum.sample_rate_hz - 200.0).abs() < 1.0);
+
+        let (frequency_hz, amplitude) = spectrum.peak().unwrap();
+        assert!(
+            (frequency_hz - 12.5).abs() <= spectrum.resolution_hz,
+            "{window:?}: {frequency_hz}"
+        );
+        assert!((amplitude - 0.5).abs() < 0.15, "{window:?}: {amplitude}");
+        // the offset of gravity is removed
+        assert!(spectrum.amplitudes[0] < 0.01, "{window:?}");
+    }
+}
+
+#[test]
+fn test_spectrum_too_short() {
+    assert!(spectrum(&[], SpectrumWindow::Hann).is_none());
+    assert!(spectrum(&[(0, 1.0), (1, 2.0), (2, 3.0)], SpectrumWindow::Hann).is_none());
+    assert!(spectrum(&[(5, 1.0); 8], SpectrumWindow::Hann).is_none());
+}
@@ -0,0 +1,91 @@
+use rospeek_core::{RawMessage, SizeBucket, SizeHistogram, TopicStats};
+
+fn message(timestamp: u64, size: usize) -> RawMessage {
+    RawMessage {
+        timestamp,
+        topic_id: 1,
+        data: vec![0; size],
+    }
+}
+
+#[test]
+fn test_accumulate_topic_stats() {
+    let mut stats = TopicStats::default();
+    assert_eq!(stats.frequency_hz(), None);
+    assert_eq!(stats.mean_size(), None);
+
+    for (i, size) in [10, 30, 20].into_iter().enumerate() {
+        stats.accumulate(&message(i as u64 * 500_000_000, size));
+    }
+
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.bytes, 60);
+    assert_eq!((stats.min_size, stats.max_size), (10, 30));
+    assert_eq!(stats.duration_sec(), 1.0);
+    assert_eq!(stats.frequency_hz(), Some(2.0));
+    assert_eq!(stats.bandwidth_bps(), Some(60.0));
+    assert_eq!(stats.mean_size(), Some(20.0));
+}
+
+#[test]
+fn test_merge_topic_stats() {
+    let messages: Vec<_> = (0..10).map(|i| message(i * 100, 1 + i as usize)).collect();
+
+    let mut expected = TopicStats::default();
+    messages.iter().for_each(|m| expected.accumulate(m));
+
+    // accumulate interleaved halves separately, as parallel workers would
+    let mut even = TopicStats::default();
+    let mut odd = TopicStats::default();
+    for (i, m) in messages.iter().enumerate() {
+        if i.is_multiple_of(2) {
+            even.accumulate(m);
+        } else {
+            odd.accumulate(m);
+        }
+    }
+    even.merge(&odd);
+    assert_eq!(even, expected);
+
+    let mut empty = TopicStats::default();
+    empty.merge(&expected);
+    assert_eq!(empty, expected);
+    expected.merge(&TopicStats::default());
+    assert_eq!(empty, expected);
+}
+
+#[test]
+fn test_size_histogram() {
+    let mut histogram = SizeHistogram::default();
+    assert!(histogram.buckets().is_empty());
+
+    // two resolutions of a camera stream
+    for _ in 0..3 {
+        histogram.accumulate(&message(0, 640 * 480 * 3));
+    }
+    let mut large = SizeHistogram::default();
+    large.add(1920 * 1080 * 3);
+    large.add(0);
+    histogram.merge(&large);
+
+    let buckets = histogram.buckets();
+    assert_eq!(histogram.total(), 5);
+    assert_eq!(buckets.len(), 24);
+    assert_eq!(
+        buckets[0],
+        SizeBucket {
+            min_size: 0,
+            max_size: 0,
+            count: 1
+        }
+    );
+    let peaks: Vec<_> = buckets.iter().filter(|b| b.count > 0).skip(1).collect();
+    assert_eq!(peaks.len(), 2);
+    assert_eq!(
+        (peaks[0].min_size, peaks[0].max_size),
+        (1 << 19, (1 << 20) - 1)
+    );
+    assert_eq!(peaks[0].count, 3);
+    assert_eq!(peaks[1].min_size, 1 << 22);
+    assert_eq!(peaks[1].count, 1);
+}
@@ -0,0 +1,61 @@
+use rospeek_core::{Config, FieldUnits, guess_unit};
+
+#[test]
+fn test_units_precedence() {
+    let config = Config::from_yaml(
+        r#"
+units:
+  /vehicle/twist:
+    twist.linear: km/h
+  geometry_msgs/msg/TwistStamped:
+    twist: m/s
+    twist.angular.z: deg/s
+"#,
+    )
+    .unwrap();
+    let units = &config.units;
+
+    // the topic comes before the type, and the longest path wins
+    let unit = |topic, field| units.unit(topic, "geometry_msgs/msg/TwistStamped", field);
+    assert_eq!(unit("/vehicle/twist", "twist.linear.x"), Some("km/h"));
+    assert_eq!(unit("/vehicle/twist", "twist.angular.z"), Some("deg/s"));
+    assert_eq!(unit("/cmd_vel", "twist.linear.x"), Some("m/s"));
+    // a path is not a prefix of a sibling with a longer name
+    assert_eq!(unit("/cmd_vel", "twist_covariance"), None);
+
+    // the built-in table and the heuristics fill in the rest
+    assert_eq!(
+        units.unit("/gnss/fix", "sensor_msgs/msg/NavSatFix", "altitude"),
+        Some("m")
+    );
+    assert_eq!(
+        units.unit(
+            "/tf",
+            "tf2_msgs/msg/TFMessage",
+            "transforms.0.transform.translation.x"
+        ),
+        Some("m")
+    );
+    assert_eq!(
+        units.unit("/vehicle/status", "my_msgs/msg/Status", "battery_voltage"),
+        Some("V")
+    );
+}
+
+#[test]
+fn test_guess_unit() {
+    assert_eq!(guess_unit("twist.twist.angular.z"), Some("rad/s"));
+    assert_eq!(guess_unit("accel.accel.linear.x"), Some("m/s²"));
+    assert_eq!(guess_unit("current_speed"), Some("m/s"));
+    assert_eq!(guess_unit("motor_current"), Some("A"));
+    assert_eq!(guess_unit("steering_tire_angle"), Some("rad"));
+    assert_eq!(guess_unit("cpu_temperature"), Some("°C"));
+    assert_eq!(guess_unit("header.stamp.sec"), Some("s"));
+    assert_eq!(guess_unit("pose.orientation.x"), None);
+    assert_eq!(guess_unit("status.level"), None);
+}
+
+#[test]
+fn test_units_invalid() {
+    assert!(FieldUnits::from_yaml("speed: km/h").is_err());
+}
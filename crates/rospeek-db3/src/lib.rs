@@ -1,3 +1,5 @@
 mod reader;
+mod writer;
 
 pub use reader::Db3Reader;
+pub use writer::Db3Writer;
@@ -1,16 +1,76 @@
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::anyhow;
 use rospeek_core::{
-    BagReader, RawMessage, RosPeekResult, Topic, ns_to_iso,
+    BagReader, EmbeddedSchema, IncompleteSign, RawMessage, RosPeekResult, TaggedMessage, Topic,
     reader::{BagStats, StorageType},
-    size_gb, to_duration_sec,
 };
-use rusqlite::{Connection, params_from_iter, types::Value as SqlValue};
+use rusqlite::{
+    Connection, ErrorCode, OpenFlags, OptionalExtension, params, params_from_iter,
+    types::Value as SqlValue,
+};
+
+/// Time to wait for a lock held by a writer, such as a recorder committing messages.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(1);
 
 pub struct Db3Reader {
     connection: rusqlite::Connection,
     stats: BagStats,
+    /// Journal left next to the file before it was opened, if any
+    journal: Option<PathBuf>,
+}
+
+impl Db3Reader {
+    fn topic_id(&self, topic_name: &str) -> RosPeekResult<u16> {
+        self.connection
+            .query_row(
+                "SELECT id FROM topics WHERE name = ?1",
+                [topic_name],
+                |row| row.get(0),
+            )
+            .map_err(|_| anyhow!("Topic not found: {topic_name}"))
+    }
+
+    /// Reads the messages written after a message row, in the order they were written, so that
+    /// a bag still being recorded can be followed without reading it again.
+    ///
+    /// # Arguments
+    /// * `after_id` - ID of the last message row already read, `0` to read from the first one.
+    ///
+    /// # Returns
+    /// A result containing the ID of the row of each message along with the message, or an error.
+    pub fn read_messages_after_id(&self, after_id: i64) -> RosPeekResult<Vec<(i64, RawMessage)>> {
+        let mut statement = self.connection.prepare(
+            "SELECT id, topic_id, timestamp, data FROM messages WHERE id > ?1 ORDER BY id ASC",
+        )?;
+
+        let rows = statement.query_map([after_id], |row| {
+            Ok((
+                row.get(0)?,
+                RawMessage {
+                    timestamp: row.get(2)?,
+                    topic_id: row.get(1)?,
+                    data: row.get(3)?,
+                },
+            ))
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Returns the ID of the row of the last message written, `0` if there is none.
+    pub fn last_message_id(&self) -> RosPeekResult<i64> {
+        Ok(self
+            .connection
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM messages", [], |row| {
+                row.get(0)
+            })?)
+    }
 }
 
 impl BagReader for Db3Reader {
@@ -18,32 +78,42 @@ impl BagReader for Db3Reader {
     where
         Self: Sized,
     {
-        let connection = Connection::open(path.as_ref())?;
+        // the connection may create its own journal, so look for a leftover one first
+        let journal = pending_journal(path.as_ref());
+        let connection = open_read_only(path.as_ref())?;
 
-        let (start_ns, end_ns) = connection.query_row(
-            "SELECT COALESCE(MIN(timestamp), 0), COALESCE(MAX(timestamp), 0) FROM messages",
+        let (message_count, start_ns, end_ns) = connection.query_row(
+            "SELECT COUNT(*), COALESCE(MIN(timestamp), 0), COALESCE(MAX(timestamp), 0) FROM messages",
             [],
             |r| {
-                let start_ns: u64 = r.get(0)?;
-                let end_ns: u64 = r.get(1)?;
-                Ok((start_ns, end_ns))
+                let message_count: u64 = r.get(0)?;
+                let start_ns: u64 = r.get(1)?;
+                let end_ns: u64 = r.get(2)?;
+                Ok((message_count, start_ns, end_ns))
             },
         )?;
 
-        let stats = BagStats {
-            path: path.as_ref().display().to_string(),
-            size_bytes: size_gb(path.as_ref()),
-            storage_type: StorageType::Sqlite3,
-            duration_sec: to_duration_sec(start_ns, end_ns),
-            start_time: ns_to_iso(start_ns),
-            end_time: ns_to_iso(end_ns),
-        };
+        let stats = BagStats::new(path, StorageType::Sqlite3, message_count, start_ns, end_ns);
 
-        Ok(Self { connection, stats })
+        Ok(Self {
+            connection,
+            stats,
+            journal,
+        })
     }
 
-    fn stats(&self) -> &BagStats {
-        &self.stats
+    fn stats(&self) -> RosPeekResult<&BagStats> {
+        Ok(&self.stats)
+    }
+
+    fn incomplete_signs(&self) -> Vec<IncompleteSign> {
+        self.journal
+            .iter()
+            .map(|journal| IncompleteSign::PendingJournal {
+                file: self.stats.path.clone(),
+                journal: journal.display().to_string(),
+            })
+            .collect()
     }
 
     fn topics(&self) -> RosPeekResult<Vec<rospeek_core::Topic>> {
@@ -71,10 +141,106 @@ impl BagReader for Db3Reader {
         Ok(rows.collect::<Result<_, _>>()?)
     }
 
+    fn embedded_schema(&self, type_name: &str) -> RosPeekResult<Option<EmbeddedSchema>> {
+        // bags recorded before Jazzy have no message definitions
+        let has_definitions: bool = self.connection.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'message_definitions')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_definitions {
+            return Ok(None);
+        }
+        let definition = self
+            .connection
+            .query_row(
+                "SELECT encoding, encoded_message_definition FROM message_definitions WHERE topic_type = ?1",
+                [type_name],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?;
+
+        // types without a definition, such as those of unknown packages, are left empty
+        Ok(definition
+            .filter(|(_, data)| !data.is_empty())
+            .map(|(encoding, data)| EmbeddedSchema {
+                name: type_name.to_string(),
+                encoding,
+                data: data.into_bytes(),
+            }))
+    }
+
+    fn topic_sizes(&self) -> RosPeekResult<Option<BTreeMap<String, u64>>> {
+        // LENGTH of a BLOB is read from the record header, without loading the data
+        let mut statement = self.connection.prepare(
+            r#"SELECT t.name, COALESCE(SUM(LENGTH(m.data)), 0)
+                    FROM topics t
+                    LEFT JOIN messages m ON t.id = m.topic_id
+                    GROUP BY t.id"#,
+        )?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        Ok(Some(rows.collect::<Result<_, _>>()?))
+    }
+
     fn read_messages(&self, topic_name: &str) -> RosPeekResult<Vec<rospeek_core::RawMessage>> {
         self.read_messages_range(topic_name, None, None, None, None)
     }
 
+    fn prev_message(
+        &self,
+        topic_name: &str,
+        before_ns: u64,
+    ) -> RosPeekResult<Option<rospeek_core::RawMessage>> {
+        let topic_id = self.topic_id(topic_name)?;
+        let mut statement = self.connection.prepare(
+            "SELECT timestamp, data FROM messages WHERE topic_id = ?1 AND timestamp < ?2 ORDER BY timestamp DESC LIMIT 1",
+        )?;
+        let mut rows = statement.query_map(params![topic_id, before_ns as i64], |row| {
+            Ok(RawMessage {
+                timestamp: row.get(0)?,
+                topic_id,
+                data: row.get(1)?,
+            })
+        })?;
+
+        Ok(rows.next().transpose()?)
+    }
+
+    fn read_messages_multi(&self, topic_names: &[&str]) -> RosPeekResult<Vec<TaggedMessage>> {
+        let mut topics = HashMap::<u16, Arc<str>>::new();
+        for topic_name in topic_names {
+            topics.insert(self.topic_id(topic_name)?, Arc::from(*topic_name));
+        }
+        if topics.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; topics.len()].join(", ");
+        let sql = format!(
+            "SELECT topic_id, timestamp, data FROM messages WHERE topic_id IN ({placeholders}) ORDER BY timestamp ASC, id ASC"
+        );
+        let mut statement = self.connection.prepare(&sql)?;
+
+        let rows = statement.query_map(params_from_iter(topics.keys()), |row| {
+            let topic_id: u16 = row.get(0)?;
+            Ok(RawMessage {
+                timestamp: row.get(1)?,
+                topic_id,
+                data: row.get(2)?,
+            })
+        })?;
+
+        rows.map(|row| -> RosPeekResult<TaggedMessage> {
+            let message = row?;
+            Ok(TaggedMessage {
+                topic: topics[&message.topic_id].clone(),
+                message,
+            })
+        })
+        .collect()
+    }
+
     fn read_messages_range(
         &self,
         topic_name: &str,
@@ -83,14 +249,7 @@ impl BagReader for Db3Reader {
         limit: Option<usize>,
         offset: Option<usize>,
     ) -> RosPeekResult<Vec<rospeek_core::RawMessage>> {
-        let topic_id: u16 = self
-            .connection
-            .query_row(
-                "SELECT id FROM topics WHERE name = ?1",
-                [topic_name],
-                |row| row.get(0),
-            )
-            .map_err(|_| anyhow!("Topic not found: {topic_name}"))?;
+        let topic_id = self.topic_id(topic_name)?;
 
         let mut sql = String::from("SELECT timestamp, data FROM messages WHERE topic_id = ?");
         let mut params: Vec<SqlValue> = vec![SqlValue::from(topic_id as i64)];
@@ -104,7 +263,7 @@ impl BagReader for Db3Reader {
             params.push(SqlValue::from(end as i64));
         }
 
-        sql.push_str(" ORDER BY timestamp ASC");
+        sql.push_str(" ORDER BY timestamp ASC, id ASC");
 
         let mut has_limit = false;
         if let Some(limit) = limit {
@@ -132,4 +291,105 @@ impl BagReader for Db3Reader {
 
         Ok(rows.collect::<Result<_, _>>()?)
     }
+
+    fn read_messages_by_index(
+        &self,
+        topic_name: &str,
+        start_index: usize,
+        count: usize,
+    ) -> RosPeekResult<Vec<rospeek_core::RawMessage>> {
+        let topic_id = self.topic_id(topic_name)?;
+        // break timestamp ties by insertion order, so that consecutive pages never overlap
+        let mut statement = self.connection.prepare(
+            "SELECT timestamp, data FROM messages WHERE topic_id = ?1 ORDER BY timestamp ASC, id ASC LIMIT ?2 OFFSET ?3",
+        )?;
+
+        let rows =
+            statement.query_map(params![topic_id, count as i64, start_index as i64], |row| {
+                Ok(RawMessage {
+                    timestamp: row.get(0)?,
+                    topic_id,
+                    data: row.get(1)?,
+                })
+            })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+/// Opens a db3 file read-only, so that bags on read-only media or still being recorded can be read.
+///
+/// Bags are opened with `mode=ro`, which reads the contents of a `-wal` sidecar and respects
+/// the locks of a writer. If the location is read-only and there is no `-wal` sidecar, the
+/// bag is opened with `immutable=1`, which does not need to create lock files.
+fn open_read_only(path: &Path) -> RosPeekResult<Connection> {
+    if !path.exists() {
+        anyhow::bail!("Bag file not found: {}", path.display());
+    }
+
+    let error = match open_uri(path, "mode=ro") {
+        Ok(connection) => return Ok(connection),
+        Err(e) => e,
+    };
+    match error.sqlite_error_code() {
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked) => Err(anyhow!(
+            "{} is locked by another process, such as an active recorder using exclusive locking; \
+             try again once the recording has stopped",
+            path.display()
+        )),
+        Some(ErrorCode::CannotOpen | ErrorCode::ReadOnly) if sidecar(path, "-wal").exists() => {
+            Err(anyhow!(
+                "{} has a -wal file which cannot be read from a read-only location; \
+                 copy the bag together with its -wal and -shm files to a writable directory",
+                path.display()
+            ))
+        }
+        Some(ErrorCode::CannotOpen | ErrorCode::ReadOnly) => Ok(open_uri(path, "immutable=1")?),
+        _ => Err(error.into()),
+    }
+}
+
+/// Opens a connection with URI parameters, checking that the database can be read.
+fn open_uri(path: &Path, parameters: &str) -> rusqlite::Result<Connection> {
+    let flags = OpenFlags::SQLITE_OPEN_READ_ONLY
+        | OpenFlags::SQLITE_OPEN_URI
+        | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    let connection = Connection::open_with_flags(to_uri(path, parameters), flags)?;
+    connection.busy_timeout(BUSY_TIMEOUT)?;
+    // opening is lazy, so touch the schema to surface locks and missing sidecars here
+    connection.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))?;
+    Ok(connection)
+}
+
+/// Returns the rollback journal or the non-empty write-ahead log next to a database, which a
+/// writer leaves behind while it is still writing or once it crashed.
+fn pending_journal(path: &Path) -> Option<PathBuf> {
+    ["-journal", "-wal"]
+        .into_iter()
+        .map(|suffix| sidecar(path, suffix))
+        .find(|journal| journal.metadata().is_ok_and(|m| m.len() > 0))
+}
+
+/// Returns the path of a sidecar file, such as `bag.db3-wal`.
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Converts a path into an SQLite URI, escaping the characters which have a meaning in URIs.
+fn to_uri(path: &Path, parameters: &str) -> String {
+    let mut uri = String::from("file:");
+    for c in path.to_string_lossy().chars() {
+        match c {
+            '%' => uri.push_str("%25"),
+            '?' => uri.push_str("%3f"),
+            '#' => uri.push_str("%23"),
+            '\\' => uri.push('/'),
+            c => uri.push(c),
+        }
+    }
+    uri.push('?');
+    uri.push_str(parameters);
+    uri
 }
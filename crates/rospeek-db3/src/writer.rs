@@ -0,0 +1,151 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::bail;
+use rospeek_core::{BagWriter, EmbeddedSchema, RawMessage, RosPeekResult, Topic};
+use rusqlite::{Connection, params};
+
+pub struct Db3Writer {
+    connection: Connection,
+    next_topic_id: u16,
+    /// IDs of the topics already in the bag
+    topic_ids: HashMap<String, u16>,
+}
+
+impl BagWriter for Db3Writer {
+    fn create<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
+    where
+        Self: Sized,
+    {
+        if path.as_ref().exists() {
+            bail!("Output bag already exists: {}", path.as_ref().display());
+        }
+
+        let connection = Connection::open(path.as_ref())?;
+        connection.execute_batch(
+            r#"
+            CREATE TABLE topics (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                type TEXT NOT NULL,
+                serialization_format TEXT NOT NULL,
+                offered_qos_profiles TEXT NOT NULL
+            );
+
+            CREATE TABLE messages (
+                id INTEGER PRIMARY KEY,
+                topic_id INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL,
+                data BLOB NOT NULL
+            );
+
+            CREATE INDEX timestamp_idx ON messages (timestamp ASC);
+
+            BEGIN;
+            "#,
+        )?;
+        create_message_definitions(&connection)?;
+
+        Ok(Self {
+            connection,
+            next_topic_id: 1,
+            topic_ids: HashMap::new(),
+        })
+    }
+
+    fn append<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
+    where
+        Self: Sized,
+    {
+        if !path.as_ref().is_file() {
+            bail!("Output bag not found: {}", path.as_ref().display());
+        }
+
+        let connection = Connection::open(path.as_ref())?;
+        let topic_ids = connection
+            .prepare("SELECT name, id FROM topics")?
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u16>(1)?))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let next_topic_id = topic_ids.values().max().map_or(1, |id| id + 1);
+        connection.execute_batch("BEGIN;")?;
+        create_message_definitions(&connection)?;
+
+        Ok(Self {
+            connection,
+            next_topic_id,
+            topic_ids,
+        })
+    }
+
+    fn add_schema(&mut self, schema: &EmbeddedSchema) -> RosPeekResult<()> {
+        self.connection.execute(
+            "INSERT INTO message_definitions (topic_type, encoding, encoded_message_definition, type_description_hash)
+                SELECT ?1, ?2, ?3, '' WHERE NOT EXISTS (SELECT 1 FROM message_definitions WHERE topic_type = ?1)",
+            params![
+                schema.name,
+                schema.encoding,
+                String::from_utf8_lossy(&schema.data)
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn add_topic(&mut self, topic: &Topic) -> RosPeekResult<u16> {
+        if let Some(id) = self.topic_ids.get(&topic.name) {
+            return Ok(*id);
+        }
+        let id = self.next_topic_id;
+        self.connection.execute(
+            "INSERT INTO topics (id, name, type, serialization_format, offered_qos_profiles) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                topic.name,
+                topic.type_name,
+                topic.serialization_format,
+                topic.offered_qos_profiles.clone().unwrap_or_default()
+            ],
+        )?;
+        self.next_topic_id += 1;
+        self.topic_ids.insert(topic.name.clone(), id);
+        Ok(id)
+    }
+
+    fn write_message(&mut self, topic_id: u16, message: &RawMessage) -> RosPeekResult<()> {
+        let mut statement = self.connection.prepare_cached(
+            "INSERT INTO messages (topic_id, timestamp, data) VALUES (?1, ?2, ?3)",
+        )?;
+        statement.execute(params![topic_id, message.timestamp as i64, message.data])?;
+        Ok(())
+    }
+
+    fn commit(&mut self) -> RosPeekResult<()> {
+        if !self.connection.is_autocommit() {
+            self.connection.execute_batch("COMMIT; BEGIN;")?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> RosPeekResult<()> {
+        if !self.connection.is_autocommit() {
+            self.connection.execute_batch("COMMIT;")?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates the table of message definitions, as rosbag2 has since Jazzy, unless the bag has it.
+fn create_message_definitions(connection: &Connection) -> RosPeekResult<()> {
+    connection.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS message_definitions (
+            id INTEGER PRIMARY KEY,
+            topic_type TEXT NOT NULL,
+            encoding TEXT NOT NULL,
+            encoded_message_definition TEXT NOT NULL,
+            type_description_hash TEXT NOT NULL
+        );
+        "#,
+    )?;
+    Ok(())
+}
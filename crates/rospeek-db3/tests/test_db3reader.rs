@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use rospeek_core::BagReader;
+use rospeek_core::{BagReader, IncompleteSign, StorageType};
 use rospeek_db3::Db3Reader;
 
 mod generate_db3;
@@ -15,6 +15,7 @@ fn test_open_and_read_topics() {
 
     assert_eq!(topics.len(), 1);
     assert_eq!(topics[0].name, "/test_topic");
+    assert!(reader.incomplete_signs().is_empty());
 }
 
 #[test]
@@ -38,3 +39,285 @@ fn test_read_messages() {
         ]
     );
 }
+
+#[test]
+fn test_read_messages_multi() {
+    let path = std::env::temp_dir().join(format!("rospeek-multi-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+    rusqlite::Connection::open(&path)
+        .and_then(|conn| {
+            conn.execute_batch(
+                "INSERT INTO topics (id, name, type, serialization_format) VALUES (2, '/other', 'std_msgs/msg/String', 'cdr');
+                 INSERT INTO messages (topic_id, timestamp, data) VALUES (2, 1234567800, x'00');
+                 INSERT INTO messages (topic_id, timestamp, data) VALUES (2, 1234567990, x'00');",
+            )
+        })
+        .expect("Failed to add topic");
+
+    let reader = Db3Reader::open(&path).expect("Failed to open bag");
+    let messages = reader
+        .read_messages_multi(&["/test_topic", "/other"])
+        .expect("Failed to read messages");
+    let order: Vec<_> = messages
+        .iter()
+        .map(|m| (&*m.topic, m.message.timestamp))
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            ("/other", 1234567800),
+            ("/test_topic", 1234567890),
+            ("/other", 1234567990),
+        ]
+    );
+    assert!(reader.read_messages_multi(&["/missing"]).is_err());
+
+    drop(reader);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_topic_sizes() {
+    let path = std::env::temp_dir().join(format!("rospeek-sizes-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+    rusqlite::Connection::open(&path)
+        .and_then(|conn| {
+            conn.execute_batch(
+                "INSERT INTO topics (id, name, type, serialization_format) VALUES (2, '/other', 'std_msgs/msg/String', 'cdr');
+                 INSERT INTO topics (id, name, type, serialization_format) VALUES (3, '/empty', 'std_msgs/msg/String', 'cdr');
+                 INSERT INTO messages (topic_id, timestamp, data) VALUES (2, 1234567800, x'0001');
+                 INSERT INTO messages (topic_id, timestamp, data) VALUES (2, 1234567990, x'000102');",
+            )
+        })
+        .expect("Failed to add topic");
+
+    let reader = Db3Reader::open(&path).expect("Failed to open bag");
+    let sizes = reader
+        .topic_sizes()
+        .expect("Failed to read sizes")
+        .expect("Sizes are known");
+    assert_eq!(sizes["/test_topic"], 14);
+    assert_eq!(sizes["/other"], 5);
+    assert_eq!(sizes["/empty"], 0);
+
+    drop(reader);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_read_messages_by_index() {
+    let path = std::env::temp_dir().join(format!("rospeek-index-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+    rusqlite::Connection::open(&path)
+        .and_then(|conn| {
+            conn.execute_batch(
+                "INSERT INTO messages (topic_id, timestamp, data) VALUES (1, 1234567800, x'01');
+                 INSERT INTO messages (topic_id, timestamp, data) VALUES (1, 1234567890, x'02');
+                 INSERT INTO messages (topic_id, timestamp, data) VALUES (1, 1234567990, x'03');",
+            )
+        })
+        .expect("Failed to add messages");
+
+    let reader = Db3Reader::open(&path).expect("Failed to open bag");
+    let page = |start, count| -> Vec<(u64, usize)> {
+        reader
+            .read_messages_by_index("/test_topic", start, count)
+            .expect("Failed to read messages")
+            .iter()
+            .map(|m| (m.timestamp, m.data.len()))
+            .collect()
+    };
+    // messages with the same timestamp keep their recorded order across pages
+    assert_eq!(page(0, 2), vec![(1234567800, 1), (1234567890, 14)]);
+    assert_eq!(page(2, 2), vec![(1234567890, 1), (1234567990, 1)]);
+    assert!(page(4, 2).is_empty());
+    assert!(reader.read_messages_by_index("/missing", 0, 1).is_err());
+
+    drop(reader);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_find_nearest() {
+    let path = std::env::temp_dir().join(format!("rospeek-nearest-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+    rusqlite::Connection::open(&path)
+        .and_then(|conn| {
+            conn.execute_batch(
+                "INSERT INTO messages (topic_id, timestamp, data) VALUES (1, 1234567800, x'01');
+                 INSERT INTO messages (topic_id, timestamp, data) VALUES (1, 1234567990, x'03');",
+            )
+        })
+        .expect("Failed to add messages");
+
+    let reader = Db3Reader::open(&path).expect("Failed to open bag");
+    let nearest = |timestamp| {
+        reader
+            .find_nearest("/test_topic", timestamp)
+            .expect("Failed to find nearest message")
+            .map(|m| m.timestamp)
+    };
+    assert_eq!(nearest(1234567850), Some(1234567890));
+    // the earlier message wins on ties
+    assert_eq!(nearest(1234567845), Some(1234567800));
+    assert_eq!(nearest(1234567890), Some(1234567890));
+    assert_eq!(nearest(0), Some(1234567800));
+    assert_eq!(nearest(2234567890), Some(1234567990));
+    assert!(reader.find_nearest("/missing", 0).is_err());
+
+    drop(reader);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_read_uncheckpointed_wal() {
+    let path = std::env::temp_dir().join(format!("rospeek-wal-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+
+    // keep a writer open so that the inserted message stays in the -wal file
+    let writer = rusqlite::Connection::open(&path).expect("Failed to open writer");
+    writer
+        .execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA wal_autocheckpoint = 0;
+             INSERT INTO messages (topic_id, timestamp, data) SELECT topic_id, timestamp + 1, data FROM messages;",
+        )
+        .expect("Failed to write into WAL");
+
+    let reader = Db3Reader::open(&path).expect("Failed to open bag in WAL mode");
+    let messages = reader
+        .read_messages("/test_topic")
+        .expect("Failed to read messages");
+    assert_eq!(messages.len(), 2);
+    assert!(matches!(
+        reader.incomplete_signs().as_slice(),
+        [IncompleteSign::PendingJournal { journal, .. }] if journal.ends_with("-wal")
+    ));
+
+    drop(reader);
+    drop(writer);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_read_messages_after_id() {
+    let path = std::env::temp_dir().join(format!("rospeek-follow-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+
+    let reader = Db3Reader::open(&path).expect("Failed to open bag");
+    let messages = reader
+        .read_messages_after_id(0)
+        .expect("Failed to read messages");
+    assert_eq!(messages.len(), 1);
+    let last_id = reader.last_message_id().expect("Failed to read last ID");
+    assert_eq!(messages[0].0, last_id);
+
+    // the open reader sees the messages a recorder writes afterwards, recorded out of order
+    rusqlite::Connection::open(&path)
+        .and_then(|conn| {
+            conn.execute_batch(
+                "INSERT INTO messages (topic_id, timestamp, data) SELECT topic_id, timestamp + 2, data FROM messages;
+                 INSERT INTO messages (topic_id, timestamp, data) SELECT topic_id, timestamp + 1, data FROM messages WHERE id = 1;",
+            )
+        })
+        .expect("Failed to write messages");
+    let messages = reader
+        .read_messages_after_id(last_id)
+        .expect("Failed to read messages");
+    let timestamps: Vec<u64> = messages.iter().map(|(_, m)| m.timestamp).collect();
+    let first = reader.read_messages("/test_topic").expect("Failed to read")[0].timestamp;
+    assert_eq!(timestamps, vec![first + 2, first + 1]);
+    assert_eq!(
+        reader.last_message_id().expect("Failed to read last ID"),
+        messages[1].0
+    );
+    assert!(
+        reader
+            .read_messages_after_id(messages[1].0)
+            .expect("Failed to read messages")
+            .is_empty()
+    );
+
+    drop(reader);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_open_locked_bag() {
+    let path = std::env::temp_dir().join(format!("rospeek-locked-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+
+    let writer = rusqlite::Connection::open(&path).expect("Failed to open writer");
+    writer
+        .execute_batch("PRAGMA locking_mode = EXCLUSIVE; BEGIN EXCLUSIVE;")
+        .expect("Failed to lock bag");
+
+    let error = Db3Reader::open(&path)
+        .err()
+        .expect("Opening a locked bag should fail");
+    assert!(error.to_string().contains("locked by another process"));
+
+    drop(writer);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_open_missing_bag() {
+    let error = Db3Reader::open("tests/data/missing.db3")
+        .err()
+        .expect("Opening a missing bag should fail");
+    assert!(error.to_string().contains("not found"));
+}
+
+#[test]
+fn test_open_empty_bag() {
+    let path = std::env::temp_dir().join(format!("rospeek-empty-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+    rusqlite::Connection::open(&path)
+        .and_then(|conn| conn.execute("DELETE FROM messages", []))
+        .expect("Failed to empty bag");
+
+    let reader = Db3Reader::open(&path).expect("Failed to open empty bag");
+    let stats = reader.stats().expect("Failed to read stats");
+    assert!(stats.is_empty());
+    assert_eq!(stats.start_ns, 0);
+    assert_eq!(stats.duration_sec, 0.0);
+    assert_eq!(stats.start_time, "-");
+
+    let topics = reader.topics().expect("Failed to read topics");
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].count, 0);
+
+    let messages = reader
+        .read_messages("/test_topic")
+        .expect("Reading an empty topic should succeed");
+    assert!(messages.is_empty());
+    assert!(reader.read_messages("/missing").is_err());
+
+    drop(reader);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_detect_misnamed_bag() {
+    let path = std::env::temp_dir().join(format!("rospeek-misnamed-{}.bag", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+
+    assert_eq!(StorageType::detect(&path), Some(StorageType::Sqlite3));
+    assert_eq!(StorageType::detect("tests/data/missing.bag"), None);
+    assert_eq!(
+        StorageType::detect("tests/data/missing.mcap"),
+        Some(StorageType::Mcap)
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,314 @@
+use std::path::Path;
+
+use rospeek_core::{
+    BagReader, BagWriter, EmbeddedSchema, ExportCheckpoint, ExportOptions, RawMessage, Topic,
+    export_bag, export_bag_with,
+};
+use rospeek_db3::{Db3Reader, Db3Writer};
+
+mod generate_db3;
+
+#[test]
+fn test_export_roundtrip() {
+    let path = Path::new("tests/data/test.db3");
+    generate_db3::generate_test_db(path);
+
+    let output = std::env::temp_dir().join(format!("rospeek-export-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&output);
+
+    let reader = Db3Reader::open(path).expect("Failed to open test.db3");
+    let mut writer = Db3Writer::create(&output).expect("Failed to create output bag");
+    let summary =
+        export_bag(&reader, &mut writer, &ExportOptions::default()).expect("Failed to export bag");
+    assert_eq!(summary.total(), 1);
+
+    let exported = Db3Reader::open(&output).expect("Failed to open exported bag");
+    let topics = exported.topics().expect("Failed to read topics");
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].name, "/test_topic");
+    assert_eq!(topics[0].type_name, "std_msgs/msg/String");
+
+    let messages = exported
+        .read_messages("/test_topic")
+        .expect("Failed to read messages");
+    let expected = reader
+        .read_messages("/test_topic")
+        .expect("Failed to read messages");
+    assert_eq!(messages.len(), expected.len());
+    assert_eq!(messages[0].timestamp, expected[0].timestamp);
+    assert_eq!(messages[0].data, expected[0].data);
+
+    std::fs::remove_file(&output).expect("Failed to remove exported bag");
+}
+
+#[test]
+fn test_export_dropped_by_stage() {
+    let path = Path::new("tests/data/test.db3");
+    generate_db3::generate_test_db(path);
+
+    let output = std::env::temp_dir().join(format!("rospeek-stages-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&output);
+
+    let reader = Db3Reader::open(path).expect("Failed to open test.db3");
+    let mut writer = Db3Writer::create(&output).expect("Failed to create output bag");
+    let options = ExportOptions {
+        dedup: true,
+        ..Default::default()
+    };
+    let summary = export_bag_with(&reader, &mut writer, &options, |pipeline| {
+        pipeline.map(Ok).filter(|_| false)
+    })
+    .expect("Failed to export bag");
+    assert_eq!(summary.total(), 0);
+    // deduplication is reported apart from the additional stages, each under its own name
+    assert_eq!(summary.duplicates["/test_topic"], 0);
+    let stages: Vec<(&str, u64)> = summary
+        .dropped_by_stage
+        .iter()
+        .map(|(stage, dropped)| (stage.as_str(), dropped["/test_topic"]))
+        .collect();
+    assert_eq!(stages, vec![("Stage", 0), ("Filter", 1)]);
+
+    drop(writer);
+    std::fs::remove_file(&output).expect("Failed to remove exported bag");
+}
+
+#[test]
+fn test_export_unknown_topic() {
+    let path = Path::new("tests/data/test.db3");
+    generate_db3::generate_test_db(path);
+
+    let output = std::env::temp_dir().join(format!("rospeek-unknown-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&output);
+
+    let reader = Db3Reader::open(path).expect("Failed to open test.db3");
+    let mut writer = Db3Writer::create(&output).expect("Failed to create output bag");
+    let options = ExportOptions {
+        topics: vec!["/missing".to_string()],
+        ..Default::default()
+    };
+    assert!(export_bag(&reader, &mut writer, &options).is_err());
+
+    let _ = std::fs::remove_file(&output);
+}
+
+#[test]
+fn test_export_resume() {
+    let dir = std::env::temp_dir().join(format!("rospeek-resume-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    let topic = Topic {
+        id: 1,
+        name: "/counter".to_string(),
+        type_name: "std_msgs/msg/UInt8".to_string(),
+        count: 3,
+        serialization_format: "cdr".to_string(),
+        offered_qos_profiles: None,
+    };
+    let message = |timestamp: u64| RawMessage {
+        timestamp,
+        topic_id: 1,
+        data: vec![0x00, 0x01, 0x00, 0x00, timestamp as u8],
+    };
+    let input = dir.join("input.db3");
+    let mut writer = Db3Writer::create(&input).expect("Failed to create input bag");
+    let id = writer.add_topic(&topic).expect("Failed to add topic");
+    for timestamp in 1..=3 {
+        writer
+            .write_message(id, &message(timestamp))
+            .expect("Failed to write message");
+    }
+    writer.finish().expect("Failed to finish input bag");
+    drop(writer);
+
+    // an export which stopped after committing the first message
+    let output = dir.join("output.db3");
+    let checkpoint = ExportCheckpoint::path(&output);
+    let mut writer = Db3Writer::create(&output).expect("Failed to create output bag");
+    let id = writer.add_topic(&topic).expect("Failed to add topic");
+    writer
+        .write_message(id, &message(1))
+        .expect("Failed to write message");
+    writer.commit().expect("Failed to commit");
+    writer
+        .write_message(id, &message(2))
+        .expect("Failed to write message");
+    drop(writer);
+    let progress = ExportCheckpoint {
+        written: [("/counter".to_string(), 1)].into(),
+    };
+    progress
+        .save(&checkpoint)
+        .expect("Failed to save checkpoint");
+
+    let reader = Db3Reader::open(&input).expect("Failed to open input bag");
+    let mut writer = Db3Writer::append(&output).expect("Failed to append to output bag");
+    let options = ExportOptions {
+        checkpoint: Some(checkpoint.clone()),
+        resume: Some(ExportCheckpoint::load(&checkpoint).expect("Failed to load checkpoint")),
+        ..Default::default()
+    };
+    let summary = export_bag(&reader, &mut writer, &options).expect("Failed to resume export");
+    drop(writer);
+    assert_eq!(summary.total(), 2);
+    assert_eq!(summary.resumed["/counter"], 1);
+    assert!(!checkpoint.exists());
+
+    let exported = Db3Reader::open(&output).expect("Failed to open exported bag");
+    assert_eq!(exported.topics().expect("Failed to read topics").len(), 1);
+    let timestamps: Vec<u64> = exported
+        .read_messages("/counter")
+        .expect("Failed to read messages")
+        .iter()
+        .map(|m| m.timestamp)
+        .collect();
+    assert_eq!(timestamps, vec![1, 2, 3]);
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
+
+#[test]
+fn test_export_resume_out_of_order() {
+    let dir = std::env::temp_dir().join(format!("rospeek-resume-order-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    let topic = Topic {
+        id: 1,
+        name: "/counter".to_string(),
+        type_name: "std_msgs/msg/UInt8".to_string(),
+        count: 5,
+        serialization_format: "cdr".to_string(),
+        offered_qos_profiles: None,
+    };
+    // recorded out of order and with a repeated timestamp, each message holding its position
+    let message = |timestamp: u64, position: u8| RawMessage {
+        timestamp,
+        topic_id: 1,
+        data: vec![0x00, 0x01, 0x00, 0x00, position],
+    };
+    let recorded = [3, 1, 2, 2, 5];
+    let input = dir.join("input.db3");
+    let mut writer = Db3Writer::create(&input).expect("Failed to create input bag");
+    let id = writer.add_topic(&topic).expect("Failed to add topic");
+    for (position, timestamp) in recorded.into_iter().enumerate() {
+        writer
+            .write_message(id, &message(timestamp, position as u8))
+            .expect("Failed to write message");
+    }
+    writer.finish().expect("Failed to finish input bag");
+    drop(writer);
+
+    // an export which stopped after committing the first two messages it read, the second one
+    // sharing its timestamp with the next one
+    let reader = Db3Reader::open(&input).expect("Failed to open input bag");
+    let read = reader
+        .read_messages("/counter")
+        .expect("Failed to read messages");
+    let output = dir.join("output.db3");
+    let checkpoint = ExportCheckpoint::path(&output);
+    let mut writer = Db3Writer::create(&output).expect("Failed to create output bag");
+    let id = writer.add_topic(&topic).expect("Failed to add topic");
+    for message in &read[..2] {
+        writer
+            .write_message(id, message)
+            .expect("Failed to write message");
+    }
+    writer.commit().expect("Failed to commit");
+    drop(writer);
+    ExportCheckpoint {
+        written: [("/counter".to_string(), 2)].into(),
+    }
+    .save(&checkpoint)
+    .expect("Failed to save checkpoint");
+
+    let mut writer = Db3Writer::append(&output).expect("Failed to append to output bag");
+    let options = ExportOptions {
+        checkpoint: Some(checkpoint.clone()),
+        resume: Some(ExportCheckpoint::load(&checkpoint).expect("Failed to load checkpoint")),
+        ..Default::default()
+    };
+    let summary = export_bag(&reader, &mut writer, &options).expect("Failed to resume export");
+    drop(writer);
+    assert_eq!(summary.total(), 3);
+    assert_eq!(summary.resumed["/counter"], 2);
+
+    let exported = Db3Reader::open(&output).expect("Failed to open exported bag");
+    let mut positions: Vec<u8> = exported
+        .read_messages("/counter")
+        .expect("Failed to read messages")
+        .iter()
+        .map(|m| m.data[4])
+        .collect();
+    positions.sort();
+    assert_eq!(positions, vec![0, 1, 2, 3, 4]);
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
+
+#[test]
+fn test_export_schemas() {
+    let dir = std::env::temp_dir().join(format!("rospeek-schemas-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    let topic = Topic {
+        id: 1,
+        name: "/status".to_string(),
+        type_name: "my_msgs/msg/Status".to_string(),
+        count: 1,
+        serialization_format: "cdr".to_string(),
+        offered_qos_profiles: Some("- history: 3\n  depth: 0\n".to_string()),
+    };
+    let schema = EmbeddedSchema {
+        name: "my_msgs/msg/Status".to_string(),
+        encoding: "ros2idl".to_string(),
+        data: b"module my_msgs { module msg { struct Status { uint8 level; }; }; };".to_vec(),
+    };
+    let input = dir.join("input.db3");
+    let mut writer = Db3Writer::create(&input).expect("Failed to create input bag");
+    writer.add_schema(&schema).expect("Failed to add schema");
+    // a definition is only kept once per type
+    writer.add_schema(&schema).expect("Failed to add schema");
+    let id = writer.add_topic(&topic).expect("Failed to add topic");
+    let message = RawMessage {
+        timestamp: 42,
+        topic_id: id,
+        data: vec![0x00, 0x01, 0x00, 0x00, 0x02],
+    };
+    writer
+        .write_message(id, &message)
+        .expect("Failed to write message");
+    writer.finish().expect("Failed to finish input bag");
+    drop(writer);
+
+    let reader = Db3Reader::open(&input).expect("Failed to open input bag");
+    let output = dir.join("output.db3");
+    let mut writer = Db3Writer::create(&output).expect("Failed to create output bag");
+    export_bag(&reader, &mut writer, &ExportOptions::default()).expect("Failed to export bag");
+    drop(writer);
+
+    let exported = Db3Reader::open(&output).expect("Failed to open exported bag");
+    let embedded = exported
+        .embedded_schema("my_msgs/msg/Status")
+        .expect("Failed to read schema")
+        .expect("Schema not embedded");
+    assert_eq!(embedded.encoding, schema.encoding);
+    assert_eq!(embedded.data, schema.data);
+    let topics = exported.topics().expect("Failed to read topics");
+    assert_eq!(topics[0].offered_qos_profiles, topic.offered_qos_profiles);
+
+    // bags recorded before definitions were stored have none
+    let path = Path::new("tests/data/test.db3");
+    generate_db3::generate_test_db(path);
+    let reader = Db3Reader::open(path).expect("Failed to open test.db3");
+    assert!(
+        reader
+            .embedded_schema("std_msgs/msg/String")
+            .expect("Failed to read schema")
+            .is_none()
+    );
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
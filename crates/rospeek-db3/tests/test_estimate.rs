@@ -0,0 +1,55 @@
+use rospeek_core::{BagReader, EstimateFormat, estimate_export};
+use rospeek_db3::Db3Reader;
+
+mod generate_db3;
+
+#[test]
+fn test_estimate_export() {
+    let path = std::env::temp_dir().join(format!("rospeek-estimate-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+    // 100 messages of 10 bytes at 100 Hz from 1000
+    let mut sql = String::from(
+        "INSERT INTO topics (id, name, type, serialization_format) VALUES (2, '/scan', 'std_msgs/msg/String', 'cdr');",
+    );
+    for i in 0..100 {
+        sql += &format!(
+            "INSERT INTO messages (topic_id, timestamp, data) VALUES (2, {}, zeroblob(10));",
+            1000 + i * 10
+        );
+    }
+    rusqlite::Connection::open(&path)
+        .and_then(|conn| conn.execute_batch(&sql))
+        .expect("Failed to add messages");
+    let reader = Db3Reader::open(&path).expect("Failed to open bag");
+    let topics = ["/scan".to_string()];
+
+    let estimate = estimate_export(&reader, &topics, None, None, EstimateFormat::Bag, &[])
+        .expect("Failed to estimate");
+    assert_eq!(estimate.topics.len(), 1);
+    assert_eq!(estimate.total_messages(), 100);
+    assert_eq!(estimate.total_bytes(), 1000);
+    assert_eq!(estimate.topics[0].expansion(), Some(1.0));
+
+    // 30 messages from 1200 to 1490
+    let estimate = estimate_export(
+        &reader,
+        &topics,
+        Some(1200),
+        Some(1499),
+        EstimateFormat::Bag,
+        &[],
+    )
+    .expect("Failed to estimate");
+    assert!(estimate.total_messages().abs_diff(30) <= 2);
+    assert_eq!(estimate.total_bytes(), estimate.total_messages() * 10);
+
+    let estimate = estimate_export(&reader, &topics, Some(5000), None, EstimateFormat::Bag, &[])
+        .expect("Failed to estimate");
+    assert_eq!(estimate.total_messages(), 0);
+
+    let missing = ["/missing".to_string()];
+    assert!(estimate_export(&reader, &missing, None, None, EstimateFormat::Bag, &[]).is_err());
+
+    std::fs::remove_file(&path).expect("Failed to remove bag");
+}
@@ -0,0 +1,138 @@
+use std::path::Path;
+
+use rospeek_core::{
+    BagReader, BagWriter, MessageOrder, Pipeline, RawMessage, Record, RosPeekResult, Sink,
+    StatsSink, Topic,
+};
+use rospeek_db3::{Db3Reader, Db3Writer};
+
+mod generate_db3;
+
+#[derive(Default)]
+struct Collect {
+    topics: Vec<String>,
+    records: Vec<Record>,
+    finished: bool,
+}
+
+impl Sink for Collect {
+    fn add_topic(&mut self, topic: &Topic) -> RosPeekResult<()> {
+        self.topics.push(topic.name.clone());
+        Ok(())
+    }
+
+    fn write(&mut self, record: Record) -> RosPeekResult<()> {
+        self.records.push(record);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> RosPeekResult<()> {
+        self.finished = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pipeline_stages() {
+    let path = Path::new("tests/data/test.db3");
+    generate_db3::generate_test_db(path);
+    let reader = Db3Reader::open(path).expect("Failed to open test.db3");
+
+    let mut sink = Collect::default();
+    let summary = Pipeline::new(&reader)
+        .map(|mut record| {
+            record.message.timestamp += 1;
+            Ok(record)
+        })
+        .filter(|record| !record.message.data.is_empty())
+        .sink(&mut sink)
+        .expect("Failed to run pipeline");
+
+    let expected = reader
+        .read_messages("/test_topic")
+        .expect("Failed to read messages");
+    assert!(sink.finished);
+    assert_eq!(sink.topics, vec!["/test_topic".to_string()]);
+    assert_eq!(sink.records.len(), expected.len());
+    assert_eq!(sink.records[0].topic.name, "/test_topic");
+    assert_eq!(sink.records[0].message.timestamp, expected[0].timestamp + 1);
+    assert_eq!(summary.counts["/test_topic"], expected.len() as u64);
+    assert_eq!(summary.dropped["/test_topic"], 0);
+
+    let mut sink = Collect::default();
+    let summary = Pipeline::new(&reader)
+        .filter(|_| false)
+        .sink(&mut sink)
+        .expect("Failed to run pipeline");
+    assert!(sink.records.is_empty());
+    assert_eq!(summary.dropped["/test_topic"], expected.len() as u64);
+}
+
+#[test]
+fn test_pipeline_stats_sink() {
+    let path = Path::new("tests/data/test.db3");
+    generate_db3::generate_test_db(path);
+    let reader = Db3Reader::open(path).expect("Failed to open test.db3");
+
+    let mut sink = StatsSink::default();
+    Pipeline::new(&reader)
+        .topics(&["/test_topic".to_string()])
+        .sink(&mut sink)
+        .expect("Failed to run pipeline");
+    assert_eq!(sink.stats["/test_topic"].count, 1);
+
+    let missing = Pipeline::new(&reader)
+        .topics(&["/missing".to_string()])
+        .sink(&mut StatsSink::default());
+    assert!(missing.is_err());
+}
+
+#[test]
+fn test_pipeline_interleaves_pages() {
+    let dir = std::env::temp_dir().join(format!("rospeek-pipeline-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    // two topics of several pages each, with alternating timestamps
+    let input = dir.join("input.db3");
+    let mut writer = Db3Writer::create(&input).expect("Failed to create input bag");
+    for (offset, name) in ["/even", "/odd"].into_iter().enumerate() {
+        let topic = Topic {
+            id: offset as u16 + 1,
+            name: name.to_string(),
+            type_name: "std_msgs/msg/UInt8".to_string(),
+            count: 3000,
+            serialization_format: "cdr".to_string(),
+            offered_qos_profiles: None,
+        };
+        let id = writer.add_topic(&topic).expect("Failed to add topic");
+        for i in 0..3000u64 {
+            let message = RawMessage {
+                timestamp: 2 * i + offset as u64,
+                topic_id: id,
+                data: vec![0x00, 0x01, 0x00, 0x00, 0x00],
+            };
+            writer
+                .write_message(id, &message)
+                .expect("Failed to write message");
+        }
+    }
+    writer.finish().expect("Failed to finish input bag");
+    drop(writer);
+
+    let reader = Db3Reader::open(&input).expect("Failed to open input bag");
+    for order in [MessageOrder::Interleaved, MessageOrder::Timestamp] {
+        let mut sink = Collect::default();
+        let summary = Pipeline::new(&reader)
+            .range(Some(100), Some(5099))
+            .order(order)
+            .sink(&mut sink)
+            .expect("Failed to run pipeline");
+        let timestamps: Vec<u64> = sink.records.iter().map(|r| r.message.timestamp).collect();
+        assert_eq!(timestamps, (100..5100).collect::<Vec<_>>());
+        assert_eq!(summary.counts["/even"], 2500);
+        assert_eq!(summary.counts["/odd"], 2500);
+    }
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
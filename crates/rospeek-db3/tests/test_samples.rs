@@ -0,0 +1,81 @@
+use rospeek_core::{BagReader, SampleOptions, TriggerSample, sample_at_triggers};
+use rospeek_db3::Db3Reader;
+
+mod generate_db3;
+
+#[test]
+fn test_sample_at_triggers() {
+    let path = std::env::temp_dir().join(format!("rospeek-samples-{}.db3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    generate_db3::generate_test_db(&path);
+    // a trigger at 10 Hz from 1000 and a topic at 25 Hz from 1030
+    let mut sql = String::from(
+        "INSERT INTO topics (id, name, type, serialization_format) VALUES (2, '/trigger', 'std_msgs/msg/String', 'cdr');
+         INSERT INTO topics (id, name, type, serialization_format) VALUES (3, '/pose', 'std_msgs/msg/String', 'cdr');",
+    );
+    for i in 0..4 {
+        sql += &format!(
+            "INSERT INTO messages (topic_id, timestamp, data) VALUES (2, {}, x'0{i}');",
+            1000 + i * 100
+        );
+    }
+    for i in 0..8 {
+        sql += &format!(
+            "INSERT INTO messages (topic_id, timestamp, data) VALUES (3, {}, x'00');",
+            1030 + i * 40
+        );
+    }
+    rusqlite::Connection::open(&path)
+        .and_then(|conn| conn.execute_batch(&sql))
+        .expect("Failed to add messages");
+
+    let reader = Db3Reader::open(&path).expect("Failed to open bag");
+    let topics = ["/trigger".to_string(), "/pose".to_string()];
+    let sample = |options: &SampleOptions| {
+        let mut samples: Vec<TriggerSample> = Vec::new();
+        let count = sample_at_triggers(&reader, "/trigger", &topics, options, |sample| {
+            samples.push(sample);
+            Ok(())
+        })
+        .expect("Failed to sample");
+        assert_eq!(count, samples.len());
+        samples
+    };
+
+    let samples = sample(&SampleOptions::default());
+    assert_eq!(samples.len(), 4);
+    assert_eq!(samples[1].index, 1);
+    assert_eq!(samples[1].timestamp, 1100);
+    // the trigger message itself and the nearest pose
+    assert_eq!(samples[1].messages[0].as_ref().unwrap().data, vec![0x01]);
+    assert_eq!(samples[1].messages[1].as_ref().unwrap().timestamp, 1110);
+    assert_eq!(samples[3].messages[1].as_ref().unwrap().timestamp, 1310);
+    assert!(samples.iter().all(TriggerSample::is_complete));
+
+    let samples = sample(&SampleOptions {
+        stride: 2,
+        max_offset_ns: Some(20),
+    });
+    assert_eq!(
+        samples.iter().map(|s| s.timestamp).collect::<Vec<_>>(),
+        vec![1000, 1200]
+    );
+    // the nearest pose of the first trigger is 30 ns away
+    assert!(samples[0].messages[1].is_none());
+    assert!(!samples[0].is_complete());
+    assert_eq!(samples[1].messages[1].as_ref().unwrap().timestamp, 1190);
+
+    assert!(
+        sample_at_triggers(
+            &reader,
+            "/missing",
+            &topics,
+            &SampleOptions::default(),
+            |_| Ok(())
+        )
+        .is_err()
+    );
+
+    drop(reader);
+    let _ = std::fs::remove_file(&path);
+}
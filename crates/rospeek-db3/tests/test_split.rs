@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use rospeek_core::{BagReader, Pipeline, Record, RosPeekResult, Sink, SplitReader};
+use rospeek_db3::Db3Reader;
+
+mod generate_db3;
+
+/// Generates a bag split into two files, the second one recorded with a new type.
+fn generate_split_bag(name: &str) -> (PathBuf, Vec<PathBuf>) {
+    let dir = std::env::temp_dir().join(format!("rospeek-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let files = vec![dir.join("bag_0.db3"), dir.join("bag_1.db3")];
+    for file in files.iter() {
+        generate_db3::generate_test_db(file);
+    }
+    rusqlite::Connection::open(&files[1])
+        .and_then(|conn| {
+            conn.execute_batch(
+                "UPDATE topics SET type = 'std_msgs/msg/Int32';
+                 UPDATE messages SET timestamp = 2234567890;",
+            )
+        })
+        .expect("Failed to update second file");
+
+    (dir, files)
+}
+
+fn open_split(dir: &PathBuf, files: &[PathBuf]) -> SplitReader {
+    // pass the files in reverse order, which the reader sorts by time
+    let splits = files
+        .iter()
+        .rev()
+        .map(|path| {
+            let reader: Box<dyn BagReader> =
+                Box::new(Db3Reader::open(path).expect("Failed to open split"));
+            (path.clone(), reader)
+        })
+        .collect();
+    SplitReader::new(dir, splits).expect("Failed to open split bag")
+}
+
+#[derive(Default)]
+struct Collect(Vec<Record>);
+
+impl Sink for Collect {
+    fn write(&mut self, record: Record) -> RosPeekResult<()> {
+        self.0.push(record);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_split_type_versions() {
+    let (dir, files) = generate_split_bag("split-versions");
+    let reader = open_split(&dir, &files);
+
+    assert_eq!(reader.files(), vec![files[0].as_path(), files[1].as_path()]);
+    assert_eq!(
+        reader.stats().expect("Failed to read stats").message_count,
+        2
+    );
+    assert_eq!(
+        reader.stats().expect("Failed to read stats").start_ns,
+        1234567890
+    );
+    assert_eq!(
+        reader.stats().expect("Failed to read stats").end_ns,
+        2234567890
+    );
+
+    let topics = reader.topics().expect("Failed to read topics");
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].count, 2);
+    assert_eq!(topics[0].type_name, "std_msgs/msg/Int32");
+
+    let versions = reader
+        .type_versions(&topics[0])
+        .expect("Failed to read type versions");
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0].type_name, "std_msgs/msg/String");
+    assert_eq!(versions[0].files, vec![files[0].display().to_string()]);
+    assert_eq!(versions[1].type_name, "std_msgs/msg/Int32");
+    assert_eq!(versions[1].start_ns, 2234567890);
+
+    assert_eq!(
+        reader.file_ranges(),
+        vec![
+            (files[0].as_path(), 1234567890, 1234567890),
+            (files[1].as_path(), 2234567890, 2234567890)
+        ]
+    );
+
+    let changes = reader.type_changes().expect("Failed to read type changes");
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].0, "/test_topic");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_split_read_messages() {
+    let (dir, files) = generate_split_bag("split-read");
+    let reader = open_split(&dir, &files);
+
+    let messages = reader
+        .read_messages("/test_topic")
+        .expect("Failed to read messages");
+    let timestamps: Vec<_> = messages.iter().map(|m| m.timestamp).collect();
+    assert_eq!(timestamps, vec![1234567890, 2234567890]);
+
+    let messages = reader
+        .read_messages_range("/test_topic", None, None, Some(1), Some(1))
+        .expect("Failed to read messages");
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].timestamp, 2234567890);
+
+    let next = reader
+        .next_message("/test_topic", 1234567890)
+        .expect("Failed to read next message");
+    assert_eq!(next.map(|m| m.timestamp), Some(2234567890));
+    assert!(reader.read_messages("/missing").is_err());
+
+    // records carry the type their message was recorded with
+    let mut sink = Collect::default();
+    Pipeline::new(&reader)
+        .sink(&mut sink)
+        .expect("Failed to run pipeline");
+    let types: Vec<_> = sink.0.iter().map(|r| r.topic.type_name.as_str()).collect();
+    assert_eq!(types, vec!["std_msgs/msg/String", "std_msgs/msg/Int32"]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_split_read_messages_by_index() {
+    let (dir, files) = generate_split_bag("split-index");
+    let reader = open_split(&dir, &files);
+
+    let timestamps = |start, count| -> Vec<u64> {
+        reader
+            .read_messages_by_index("/test_topic", start, count)
+            .expect("Failed to read messages")
+            .iter()
+            .map(|m| m.timestamp)
+            .collect()
+    };
+    assert_eq!(timestamps(0, 10), vec![1234567890, 2234567890]);
+    assert_eq!(timestamps(1, 10), vec![2234567890]);
+    assert_eq!(timestamps(0, 1), vec![1234567890]);
+    assert!(timestamps(2, 10).is_empty());
+    assert!(reader.read_messages_by_index("/missing", 0, 1).is_err());
+
+    drop(reader);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -1,10 +1,47 @@
 use crossbeam_channel as channel;
-use egui::{RichText, collapsing_header::CollapsingState};
+use egui::RichText;
 use rfd::FileDialog;
-use rospeek_core::{CdrDecoder, MessageSchema, RawMessage, Topic, ns_to_iso, try_decode_binary};
-use std::{f32, path::PathBuf, sync::Arc};
+use rospeek_core::{
+    CLOCK_TOPIC, CLOCK_TYPE, CdrDecoder, ClockMap, Condition, DecodePlan, DecodeRule,
+    DecoderRegistry, ExportOptions, ExportSummary, FieldUnits, IncompleteSign, LabeledEvent,
+    MessageSchema, RawMessage, RosPeekResult, SizeHistogram, Topic, TopicAliases, TopicHealth,
+    TopicStats, decode_field, decode_with_rule, find_quaternions, load_events, ns_to_iso,
+    quaternion_to_euler, try_decode_binary,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    f32,
+    path::PathBuf,
+    sync::Arc,
+};
 
-use crate::backend::Backend;
+use crate::{
+    attachments::AttachmentsPanel,
+    backend::{Backend, BackendFactory, ReaderBackendFactory, Segment},
+    cache::{Page, PageCache, value_bytes},
+    covariance::{Covariance, find_covariances, show_covariance},
+    errors::ErrorDialog,
+    export::ExportDialog,
+    fonts::{install_fallback_font, printable},
+    histogram::size_chart,
+    i18n::Language,
+    images::is_image_type,
+    playback::Playback,
+    plot::PlotPanel,
+    prefetch::{PrefetchConfig, Prefetcher},
+    preview::{is_blob_type, preview_blob},
+    remote::RemoteBackend,
+    snapshot::Snapshots,
+    start::{RECENT_KEY, StartScreen},
+    thumbnails::{ThumbnailStrip, thumbnail_strip},
+    timeline::timeline_bar,
+    trigger::Trigger,
+    visualizer::VisualizerRegistry,
+    watch::{WatchExpression, Watcher},
+    zoom::{ZOOM_KEY, Zoom},
+};
+
+const MIB: usize = 1024 * 1024;
 
 #[derive(Debug)]
 enum Command {
@@ -13,6 +50,16 @@ enum Command {
         offset: usize,
         limit: usize,
     },
+    Step {
+        topic: String,
+        from_ns: u64,
+        forward: bool,
+    },
+    Export {
+        output: PathBuf,
+        options: ExportOptions,
+    },
+    SetMemoryCap(usize),
 }
 
 #[derive(Debug)]
@@ -21,170 +68,531 @@ enum Event {
     Page {
         topic: String,
         offset: usize,
-        msgs: Vec<RawMessage>,
+        msgs: Page,
+    },
+    /// Approximate bytes held by the worker page cache
+    Memory(usize),
+    /// Statistics of a topic accumulated so far
+    Stats {
+        topic: String,
+        stats: TopicStats,
+        sizes: SizeHistogram,
+    },
+    /// Health of a topic, once all its messages are scanned
+    Health {
+        topic: String,
+        health: TopicHealth,
     },
-    Error(String),
+    Seek(u64),
+    Exported(ExportSummary),
+    Error(ErrorDialog),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ViewMode {
     Auto,
     Bytes,
     Json,
 }
 
-pub struct App<B: Backend + 'static> {
-    backend: Option<Arc<B>>,
-    bag_path: Option<PathBuf>,
+/// Message at the playback cursor decoded for the visualizer of its type and the panels of the
+/// inspector.
+struct Visualized {
+    topic: String,
+    timestamp: u64,
+    value: Result<serde_json::Value, String>,
+    /// Covariance matrices found in the value
+    covariances: Vec<Covariance>,
+    /// Quaternions found in the value, by path
+    quaternions: Vec<(String, [f64; 4])>,
+    /// Approximate number of bytes held by the value
+    bytes: usize,
+}
+
+pub struct App {
+    factory: Box<dyn BackendFactory>,
+    backend: Option<Arc<dyn Backend>>,
+    source: Option<String>,
+    remote_url: String,
     topics: Vec<Topic>,
+    /// Statistics of the topics, filled in while messages are scanned in the background
+    topic_stats: HashMap<String, TopicStats>,
+    /// Message size distributions of the topics, filled in along with their statistics
+    topic_sizes: HashMap<String, SizeHistogram>,
+    /// Health of the topics, assessed once their messages are scanned
+    topic_health: HashMap<String, TopicHealth>,
     topic_filter: String,
+    /// Friendly names of the topics of the opened bag
+    aliases: TopicAliases,
+    /// Units labelling the values of fields in plots and watches
+    units: FieldUnits,
     current_schema: Option<MessageSchema>,
     current_topic: Option<String>,
     page_offset: usize,
     page_size: usize,
-    page: Vec<RawMessage>,
+    page: Page,
+    /// Memory cap of the page cache in MiB
+    memory_cap_mb: usize,
+    cached_bytes: usize,
     view_mode: ViewMode,
+    /// Whether the Auto view shows blob-bearing messages, such as images, in full
+    show_full_blobs: bool,
+    /// Whether the inspector shows the quaternions of the message at the cursor as Euler angles
+    show_euler: bool,
+    /// Indexes of the expanded messages in the page
+    expanded: BTreeSet<usize>,
+    /// Rendered bodies of the expanded messages
+    decoded: HashMap<usize, String>,
+    /// Message shown by the visualizer of the focused topic, decoded once per message
+    visualized: Option<Visualized>,
+    /// Field paths shown as columns of the message list
+    columns: Vec<String>,
+    column_input: String,
+    /// Extracted column values of the messages in the page
+    column_values: HashMap<usize, Vec<String>>,
+    playback: Playback,
+    /// Files of the opened bag when several files were opened as one
+    segments: Vec<Segment>,
+    /// Mapping to simulated time if the bag has a clock topic
+    clock: Option<ClockMap>,
+    /// Whether to show times in simulated time
+    use_sim_time: bool,
+    prefetcher: Option<Prefetcher>,
+    /// Image topic whose thumbnails are shown along the timeline
+    thumbnail_topic: Option<String>,
+    /// Thumbnails of the image topic, created once the topic is selected
+    thumbnails: Option<ThumbnailStrip>,
+    /// Labeled events shown as bands on the timeline
+    events: Vec<LabeledEvent>,
+    trigger_input: String,
+    /// Condition pausing playback when satisfied
+    trigger_condition: Option<Condition>,
+    /// Evaluator of the condition, created once a condition is armed
+    trigger: Option<Trigger>,
+    /// Plot of a field of the focused topic, created once shown
+    plot: Option<PlotPanel>,
+    /// Whether to show the watch sidebar
+    show_watch: bool,
+    /// Fields watched during playback, kept across bags
+    watches: Vec<WatchExpression>,
+    watch_input: String,
+    /// Reader of the watches, created once the sidebar is shown
+    watcher: Option<Watcher>,
+    export_dialog: Option<ExportDialog>,
+    attachments_panel: Option<AttachmentsPanel>,
+    /// Language of the labels
+    language: Language,
+    /// Scale of the UI, persisted across sessions
+    zoom: Zoom,
+    snapshots: Snapshots,
+    /// Recent bags and catalog shown until a bag is opened
+    start: StartScreen,
+    /// Result of the last view exported as PNG
+    snapshot_status: Option<String>,
+    /// Last failure, shown until the dialog is closed
+    error: Option<ErrorDialog>,
+    /// Signs that the recording of the opened bag is incomplete
+    incomplete: Vec<IncompleteSign>,
+    /// Title of the window, sent to the viewport when it changes
+    title: String,
     // backend workers
     tx: channel::Sender<Command>,
     rx: channel::Receiver<Event>,
 }
 
-impl<B: Backend + 'static> App<B> {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+impl App {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self::with_factory(cc, Box::new(ReaderBackendFactory))
+    }
+
+    /// Creates the app with a custom backend factory used to open bags.
+    ///
+    /// # Arguments
+    /// * `factory` - Factory opening the backend for a selected path.
+    pub fn with_factory(
+        cc: &eframe::CreationContext<'_>,
+        factory: Box<dyn BackendFactory>,
+    ) -> Self {
+        install_fallback_font(&cc.egui_ctx);
+        let zoom = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<f32>(storage, ZOOM_KEY))
+            .map_or_else(Zoom::default, Zoom::new);
+        // zoom shortcuts are handled by the app, so that the factor is bounded and persisted
+        cc.egui_ctx
+            .options_mut(|options| options.zoom_with_keyboard = false);
+        cc.egui_ctx.set_zoom_factor(zoom.factor());
+        let recent = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Vec<String>>(storage, RECENT_KEY))
+            .unwrap_or_default();
         let (txc, rxc) = channel::unbounded::<Command>();
         let (txe, rxe) = channel::unbounded::<Event>();
 
         // worker thread starts empty; will be (re)created when a bag is opened
         std::thread::spawn(move || {
             // idle loop; wait for commands until a real backend is provided after open
-            while let Ok(_cmd) = rxc.recv() {
-                let _ = txe.send(Event::Error("No bag opened".into()));
+            while let Ok(cmd) = rxc.recv() {
+                if !matches!(cmd, Command::SetMemoryCap(_)) {
+                    let _ = txe.send(Event::Error(ErrorDialog::new(&anyhow::anyhow!(
+                        "No bag opened"
+                    ))));
+                }
             }
         });
 
         Self {
+            factory,
             backend: None,
-            bag_path: None,
+            source: None,
+            remote_url: String::new(),
             topics: Vec::new(),
+            topic_stats: HashMap::new(),
+            topic_sizes: HashMap::new(),
+            topic_health: HashMap::new(),
             topic_filter: String::new(),
+            aliases: TopicAliases::global(),
+            units: FieldUnits::global(),
             current_schema: None,
             current_topic: None,
             page_offset: 0,
             page_size: 200,
-            page: Vec::new(),
+            page: Page::default(),
+            memory_cap_mb: 1024,
+            cached_bytes: 0,
             view_mode: ViewMode::Auto,
+            show_full_blobs: false,
+            show_euler: false,
+            expanded: BTreeSet::new(),
+            decoded: HashMap::new(),
+            visualized: None,
+            columns: Vec::new(),
+            column_input: String::new(),
+            column_values: HashMap::new(),
+            playback: Playback::new(0, 0),
+            segments: Vec::new(),
+            clock: None,
+            use_sim_time: false,
+            prefetcher: None,
+            thumbnail_topic: None,
+            thumbnails: None,
+            events: Vec::new(),
+            trigger_input: String::new(),
+            trigger_condition: None,
+            trigger: None,
+            plot: None,
+            show_watch: false,
+            watches: Vec::new(),
+            watch_input: String::new(),
+            watcher: None,
+            export_dialog: None,
+            attachments_panel: None,
+            language: Language::from_env(),
+            zoom,
+            snapshots: Snapshots::default(),
+            start: StartScreen::new(recent),
+            snapshot_status: None,
+            error: None,
+            incomplete: Vec::new(),
+            title: String::new(),
             tx: txc,
             rx: rxe,
         }
     }
 
-    /// Open a ROS 2 bag file.
+    /// Open ROS 2 bag files, several files being concatenated into one timeline.
     fn open(&mut self) {
-        if let Some(path) = FileDialog::new()
+        let Some(mut paths) = FileDialog::new()
             .add_filter("ROS 2 bag", &["db3", "mcap"])
-            .pick_file()
-        {
-            match B::open(&path) {
-                Ok(backend) => {
-                    let backend = Arc::new(backend);
-                    let topics = backend.topics().unwrap_or_default();
-
-                    // Rebuild worker bound to this backend
-                    let (txc, rxc) = channel::unbounded::<Command>();
-                    let (txe, rxe) = channel::unbounded::<Event>();
-
-                    // let worker bound to this backend
-                    let bend = backend.clone();
-                    let tmp_topics = topics.clone().into_iter().collect::<Vec<_>>();
-                    std::thread::spawn(move || {
-                        let _ = txe.send(Event::Topics(tmp_topics));
-                        while let Ok(cmd) = rxc.recv() {
-                            match cmd {
-                                Command::LoadTopic {
-                                    name,
-                                    offset,
-                                    limit,
-                                } => match bend.read_messages(&name, None, limit, Some(offset)) {
+            .pick_files()
+        else {
+            return;
+        };
+        if paths.len() == 1 {
+            self.open_path(paths.remove(0));
+            return;
+        }
+        paths.sort();
+        let result = self.factory.open_many(&paths);
+        let source = format!("{} (+{} files)", paths[0].display(), paths.len() - 1);
+        self.attach(result, source, TopicAliases::global());
+    }
+
+    /// Open a ROS 2 bag at a path, remembering it as recently opened.
+    fn open_path(&mut self, path: PathBuf) {
+        let result = self.factory.open(&path);
+        if result.is_ok() {
+            self.start.push_recent(&path);
+        }
+        let aliases = TopicAliases::global();
+        let aliases = aliases.with_sidecar(&path).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            aliases
+        });
+        self.attach(result, path.display().to_string(), aliases);
+    }
+
+    /// Connect to a bag served by a remote rospeek server.
+    fn connect_remote(&mut self) {
+        let url = self.remote_url.trim().to_string();
+        if url.is_empty() {
+            return;
+        }
+        let result = RemoteBackend::connect(&url).map(|b| Arc::new(b) as Arc<dyn Backend>);
+        self.attach(result, url, TopicAliases::global());
+    }
+
+    /// Binds the app and a fresh worker to an opened backend.
+    ///
+    /// # Arguments
+    /// * `result` - The result of opening the backend.
+    /// * `source` - Human readable location of the bag (path or URL).
+    /// * `aliases` - Friendly names of the topics of the bag.
+    fn attach(
+        &mut self,
+        result: RosPeekResult<Arc<dyn Backend>>,
+        source: String,
+        aliases: TopicAliases,
+    ) {
+        match result {
+            Ok(backend) => {
+                let topics = backend.topics().unwrap_or_default();
+                let (start_ns, end_ns) = backend.time_range().unwrap_or_default();
+                self.incomplete = backend.incomplete_signs();
+
+                // Rebuild worker bound to this backend
+                let (txc, rxc) = channel::unbounded::<Command>();
+                let (txe, rxe) = channel::unbounded::<Event>();
+
+                // let worker bound to this backend
+                let bend = backend.clone();
+                let tmp_topics = topics.clone().into_iter().collect::<Vec<_>>();
+                let txe_stats = txe.clone();
+                let mut cache = PageCache::new(self.memory_cap_mb * MIB);
+                std::thread::spawn(move || {
+                    let _ = txe.send(Event::Topics(tmp_topics));
+                    while let Ok(cmd) = rxc.recv() {
+                        match cmd {
+                            Command::LoadTopic {
+                                name,
+                                offset,
+                                limit,
+                            } => {
+                                let msgs = match cache.get(&name, offset) {
+                                    Some(page) => Ok(page),
+                                    None => bend
+                                        .read_messages(&name, None, limit, Some(offset))
+                                        .map(|msgs| {
+                                            let page = Page::new(msgs);
+                                            cache.insert(name.clone(), offset, page.clone());
+                                            page
+                                        }),
+                                };
+                                match msgs {
                                     Ok(msgs) => {
                                         let _ = txe.send(Event::Page {
                                             topic: name,
                                             offset,
                                             msgs,
                                         });
+                                        let _ = txe.send(Event::Memory(cache.bytes()));
+                                    }
+                                    Err(e) => {
+                                        let _ = txe.send(Event::Error(ErrorDialog::new(&e)));
+                                    }
+                                }
+                            }
+                            Command::Step {
+                                topic,
+                                from_ns,
+                                forward,
+                            } => {
+                                let result = if forward {
+                                    bend.next_message(&topic, from_ns)
+                                } else {
+                                    bend.prev_message(&topic, from_ns)
+                                };
+                                match result {
+                                    Ok(Some(msg)) => {
+                                        let _ = txe.send(Event::Seek(msg.timestamp));
                                     }
+                                    Ok(None) => {}
                                     Err(e) => {
-                                        let _ = txe.send(Event::Error(e.to_string()));
+                                        let _ = txe.send(Event::Error(ErrorDialog::new(&e)));
                                     }
-                                },
+                                }
+                            }
+                            Command::SetMemoryCap(capacity_bytes) => {
+                                cache.set_capacity(capacity_bytes);
+                                let _ = txe.send(Event::Memory(cache.bytes()));
+                            }
+                            Command::Export { output, options } => {
+                                match bend.export(&output, &options) {
+                                    Ok(summary) => {
+                                        let _ = txe.send(Event::Exported(summary));
+                                    }
+                                    Err(e) => {
+                                        let _ = txe.send(Event::Error(ErrorDialog::new(&e)));
+                                    }
+                                }
                             }
                         }
-                    });
+                    }
+                });
 
-                    self.backend = Some(backend);
-                    self.bag_path = Some(path);
-                    self.topics = topics;
-                    self.current_schema = None;
-                    self.current_topic = None;
-                    self.page_offset = 0;
-                    self.page.clear();
-                    self.tx = txc;
-                    self.rx = rxe;
-                }
-                Err(e) => {
-                    self.backend = None;
-                    self.bag_path = None;
-                    self.topics.clear();
-                    self.current_schema = None;
-                    self.current_topic = None;
-                    self.page_offset = 0;
-                    egui::PopupCloseBehavior::default();
-                    eprintln!("Open failed: {e:?}")
-                }
+                spawn_stats_scan(backend.clone(), &topics, end_ns, txe_stats);
+
+                self.prefetcher = Some(Prefetcher::new(backend.clone(), PrefetchConfig::default()));
+                self.thumbnail_topic = None;
+                self.thumbnails = None;
+                self.watcher = None;
+                self.trigger_condition = None;
+                self.trigger = None;
+                self.plot = None;
+                self.events.clear();
+                self.playback = Playback::new(start_ns, end_ns);
+                self.segments = backend.segments();
+                self.clock = load_clock(backend.as_ref(), &topics);
+                self.use_sim_time = self.clock.is_some();
+                self.backend = Some(backend);
+                self.source = Some(source);
+                self.aliases = aliases;
+                self.topics = topics;
+                self.topic_stats.clear();
+                self.topic_sizes.clear();
+                self.topic_health.clear();
+                self.current_schema = None;
+                self.current_topic = None;
+                self.page_offset = 0;
+                self.page = Page::default();
+                self.cached_bytes = 0;
+                self.expanded.clear();
+                self.decoded.clear();
+                self.column_values.clear();
+                self.tx = txc;
+                self.rx = rxe;
+            }
+            Err(e) => {
+                self.backend = None;
+                self.prefetcher = None;
+                self.thumbnail_topic = None;
+                self.thumbnails = None;
+                self.watcher = None;
+                self.trigger_condition = None;
+                self.trigger = None;
+                self.plot = None;
+                self.events.clear();
+                self.playback = Playback::new(0, 0);
+                self.segments.clear();
+                self.incomplete.clear();
+                self.source = None;
+                self.topics.clear();
+                self.topic_stats.clear();
+                self.topic_sizes.clear();
+                self.topic_health.clear();
+                self.current_schema = None;
+                self.current_topic = None;
+                self.page_offset = 0;
+                egui::PopupCloseBehavior::default();
+                self.error = Some(ErrorDialog::new(&e));
             }
         }
     }
 
     /// Performs UI operations related to topics.
     fn ui_topics(&mut self, ui: &mut egui::Ui) {
+        let lang = self.language;
         ui.horizontal(|ui| {
-            ui.label("Topic Filter");
+            ui.label(lang.tr("Topic Filter"));
             ui.text_edit_singleline(&mut self.topic_filter);
         });
         ui.separator();
 
         let filter = self.topic_filter.to_lowercase();
+        let mut clicked = None;
         egui::ScrollArea::vertical().show(ui, |ui| {
             for topic in self.topics.iter() {
-                if !filter.is_empty() && !topic.name.to_lowercase().contains(&filter) {
+                let label = self.aliases.label(&topic.name);
+                if !filter.is_empty() && !label.to_lowercase().contains(&filter) {
                     continue;
                 }
                 let select = Some(topic.name.clone()) == self.current_topic;
-                if ui
-                    .selectable_label(
-                        select,
-                        to_rich_text(&format!("{} [{}]", topic.name, &topic.type_name)),
-                    )
-                    .clicked()
+                let rate = self
+                    .topic_stats
+                    .get(&topic.name)
+                    .and_then(TopicStats::frequency_hz)
+                    .map(|hz| format!(" {hz:.1} Hz"))
+                    .unwrap_or_default();
+                let text = printable(
+                    ui.ctx(),
+                    &egui::TextStyle::Body.resolve(ui.style()),
+                    &format!("{label} [{}]{rate}", &topic.type_name),
+                );
+                let health = self.topic_health.get(&topic.name);
+                let text = match health.and_then(health_color) {
+                    Some(color) => RichText::new(text).color(color),
+                    None => to_rich_text(&text),
+                };
+                let mut response = ui.selectable_label(select, text);
+                if let Some(health) = health {
+                    response = response.on_hover_text(lang.tr(health.description()));
+                }
+                if response.clicked() {
+                    clicked = Some(topic.clone());
+                }
+            }
+        });
+        if let Some(topic) = clicked {
+            self.focus_topic(&topic);
+        }
+    }
+
+    /// Shows the messages of a topic in the inspector.
+    fn focus_topic(&mut self, topic: &Topic) {
+        self.current_schema = match MessageSchema::try_from(topic.type_name.as_ref()) {
+            Ok(schema) => Some(schema),
+            Err(e) => {
+                // messages are shown as bytes, unless the config decodes them as is
+                if DecoderRegistry::global()
+                    .rule_for(&topic.name, &topic.type_name)
+                    .is_none_or(|rule| rule.needs_schema())
                 {
-                    self.current_schema = MessageSchema::try_from(topic.type_name.as_ref()).ok();
-                    self.current_topic = Some(topic.name.clone());
-                    self.page_offset = 0;
-                    let _ = self.tx.send(Command::LoadTopic {
-                        name: topic.name.clone(),
-                        offset: 0,
-                        limit: self.page_size,
-                    });
+                    self.error = Some(ErrorDialog::new(&e));
                 }
+                None
             }
+        };
+        self.current_topic = Some(topic.name.clone());
+        self.page_offset = 0;
+        let _ = self.tx.send(Command::LoadTopic {
+            name: topic.name.clone(),
+            offset: 0,
+            limit: self.page_size,
         });
+        if let Some(prefetcher) = &self.prefetcher {
+            prefetcher.follow(vec![topic.clone()]);
+            prefetcher.seek(self.playback.cursor_ns);
+        }
     }
 
     fn ui_center(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Message Inspector");
+        let lang = self.language;
+        let rect = ui.max_rect();
+        ui.horizontal(|ui| {
+            ui.heading(lang.tr("Message Inspector"));
+            if ui
+                .button("📷")
+                .on_hover_text(lang.tr("Export view as PNG"))
+                .clicked()
+            {
+                self.snapshots.request(ui.ctx(), rect, "inspector");
+            }
+        });
         ui.separator();
 
+        let (view_mode, show_full_blobs) = (self.view_mode, self.show_full_blobs);
         ui.horizontal(|ui| {
-            egui::ComboBox::from_label("View Mode")
+            egui::ComboBox::from_label(lang.tr("View Mode"))
                 .selected_text(match self.view_mode {
                     ViewMode::Auto => "Auto",
                     ViewMode::Bytes => "Bytes",
@@ -195,58 +603,614 @@ impl<B: Backend + 'static> App<B> {
                     ui.selectable_value(&mut self.view_mode, ViewMode::Bytes, "Bytes");
                     ui.selectable_value(&mut self.view_mode, ViewMode::Json, "Json");
                 });
+            if self.view_mode == ViewMode::Auto {
+                ui.checkbox(&mut self.show_full_blobs, lang.tr("Show full"))
+                    .on_hover_text(
+                        lang.tr("Show images and point clouds in full instead of a summary"),
+                    );
+            }
+            if self.view_mode != ViewMode::Bytes {
+                ui.checkbox(&mut self.show_euler, lang.tr("Euler angles"))
+                    .on_hover_text(lang.tr(
+                        "Show the roll, pitch and yaw of orientations and rotations in degrees",
+                    ));
+            }
         });
+        if self.view_mode != view_mode || self.show_full_blobs != show_full_blobs {
+            self.decoded.clear();
+        }
 
-        if let (Some(topic), Some(schema)) = (&self.current_topic, &self.current_schema) {
-            ui.monospace(to_rich_text(&format!("Topic: {topic}")).strong());
+        if let Some(topic) = self.current_topic.clone() {
+            ui.monospace(to_rich_text(&format!("Topic: {}", self.aliases.label(&topic))).strong());
             ui.add_space(4.0);
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                let mut decoder = CdrDecoder::from_schema(schema);
-                for (idx, msg) in self.page.iter().enumerate() {
-                    let id = ui.make_persistent_id(("msg_row", msg.topic_id, msg.timestamp, idx));
-                    let header = CollapsingState::load_with_default_open(ui.ctx(), id, false)
-                        .show_header(ui, |ui| {
-                            ui.label(format!(
-                                "[#{idx}] @{} ({} bytes)",
-                                ns_to_iso(msg.timestamp),
-                                msg.data.len()
-                            ))
-                        });
-
-                    // display decoded message if the header is unindented
-                    header.body_unindented(|ui| {
-                        let mut body = self.display_message(&mut decoder, msg);
+            if let Some(current) = self
+                .prefetcher
+                .as_ref()
+                .and_then(|p| p.latest(&topic, self.playback.cursor_ns))
+            {
+                ui.collapsing(
+                    format!(
+                        "At playback cursor @{} ({} bytes)",
+                        ns_to_iso(current.message.timestamp),
+                        current.message.data.len()
+                    ),
+                    |ui| {
+                        let mut body = match (self.view_mode, self.current_rule()) {
+                            (ViewMode::Bytes, _) | (_, Some(DecodeRule::Bytes)) => {
+                                dump_bytes(&current.message.data, 64)
+                            }
+                            _ => current.decoded,
+                        };
                         egui::TextEdit::multiline(&mut body)
                             .code_editor()
                             .interactive(false)
                             .desired_width(f32::INFINITY)
                             .show(ui);
-                    });
+                    },
+                );
+                self.ui_visualizer(ui, &topic, &current.message);
+                self.ui_covariances(ui, &topic, &current.message);
+                if self.show_euler {
+                    self.ui_euler_angles(ui, &topic, &current.message);
                 }
-            });
+                ui.separator();
+            }
+            if let Some(sizes) = self.topic_sizes.get(&topic) {
+                ui.collapsing(lang.tr("Message sizes"), |ui| size_chart(ui, sizes));
+            }
+            self.ui_columns(ui);
+            if let Some(backend) = &self.backend
+                && let Some(focused) = self.topics.iter().find(|t| t.name == topic)
+            {
+                let plot = self
+                    .plot
+                    .get_or_insert_with(|| PlotPanel::new(backend.clone(), ui.ctx().clone()));
+                let moved = ui
+                    .collapsing(lang.tr("Plot"), |ui| {
+                        plot.show(ui, lang, focused, &self.units, &mut self.playback)
+                    })
+                    .body_returned
+                    .unwrap_or(false);
+                if moved {
+                    self.follow_cursor();
+                }
+            }
+            ui.separator();
+
+            if self.topics.iter().any(|t| t.name == topic && t.count == 0) {
+                ui.label(format!("{topic} has no messages in this bag."));
+                return;
+            }
+
+            // rows are virtualized: only those intersecting the viewport are laid out
+            let page = self.page.clone();
+            let row_spacing = ui.spacing().item_spacing.y;
+            let header_height = ui.spacing().interact_size.y + row_spacing;
+            let line_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            let heights: Vec<f32> = (0..page.len())
+                .map(|idx| {
+                    if !self.expanded.contains(&idx) {
+                        return header_height;
+                    }
+                    let lines = self
+                        .decoded
+                        .get(&idx)
+                        .map_or(1, |body| body.lines().count().max(1));
+                    header_height + lines as f32 * line_height + row_spacing
+                })
+                .collect();
+            let total_height: f32 = heights.iter().sum();
+
+            egui::ScrollArea::vertical()
+                .auto_shrink(false)
+                .show_viewport(ui, |ui, viewport| {
+                    let origin = ui.max_rect().min;
+                    let width = ui.available_width();
+                    ui.set_height(total_height);
+
+                    let mut top = 0.0;
+                    for (idx, height) in heights.iter().enumerate() {
+                        let bottom = top + height;
+                        if bottom < viewport.min.y {
+                            top = bottom;
+                            continue;
+                        }
+                        if top > viewport.max.y {
+                            break;
+                        }
+                        let rect = egui::Rect::from_min_size(
+                            origin + egui::vec2(0.0, top),
+                            egui::vec2(width, *height),
+                        );
+                        ui.scope_builder(egui::UiBuilder::new().max_rect(rect), |ui| {
+                            self.ui_message_row(ui, idx, &page[idx]);
+                        });
+                        top = bottom;
+                    }
+                });
+        } else if !self.topics.is_empty() && self.topics.iter().all(|t| t.count == 0) {
+            ui.label(lang.tr("This bag has no messages."));
         } else {
-            ui.label("Select a topic on the left.");
+            ui.label(lang.tr("Select a topic on the left."));
+        }
+    }
+
+    /// Decodes a message for the panels of the inspector, unless it is already decoded.
+    ///
+    /// # Returns
+    /// Whether the message is decoded, which it is not in the Bytes view or without a schema.
+    fn decode_visualized(&mut self, topic: &str, msg: &RawMessage) -> bool {
+        let rule = self.current_rule();
+        if self.view_mode == ViewMode::Bytes || rule == Some(DecodeRule::Bytes) {
+            return false;
+        }
+        let Some(schema) = self.current_schema.as_ref() else {
+            return false;
+        };
+
+        let stale = self
+            .visualized
+            .as_ref()
+            .is_none_or(|v| v.topic != topic || v.timestamp != msg.timestamp);
+        if stale {
+            let plan = DecodePlan::compile(schema).ok();
+            let value = decode_with_rule(
+                rule.as_ref(),
+                &mut CdrDecoder::new(&[]),
+                plan.as_ref(),
+                &msg.data,
+            )
+            .map_err(|e| e.to_string());
+            let covariances = value.as_ref().map_or_else(|_| Vec::new(), find_covariances);
+            let quaternions = value.as_ref().map_or_else(|_| Vec::new(), find_quaternions);
+            let bytes = value.as_ref().map_or(0, value_bytes);
+            self.visualized = Some(Visualized {
+                topic: topic.to_string(),
+                timestamp: msg.timestamp,
+                value,
+                covariances,
+                quaternions,
+                bytes,
+            });
+        }
+        true
+    }
+
+    /// Shows the registered visualizer of the focused topic type for a message, if any.
+    fn ui_visualizer(&mut self, ui: &mut egui::Ui, topic: &str, msg: &RawMessage) {
+        let Some(visualizer) = self
+            .current_schema
+            .as_ref()
+            .and_then(|schema| VisualizerRegistry::global().find(&schema.type_name))
+        else {
+            return;
+        };
+        if !self.decode_visualized(topic, msg) {
+            return;
+        }
+
+        egui::CollapsingHeader::new(visualizer.name())
+            .default_open(true)
+            .show(ui, |ui| match self.visualized.as_ref().map(|v| &v.value) {
+                Some(Ok(value)) => visualizer.show(ui, value),
+                Some(Err(e)) => {
+                    ui.label(e.as_str());
+                }
+                None => {}
+            });
+    }
+
+    /// Decodes a message for the panels of the inspector which search its fields, skipping
+    /// blob-bearing messages.
+    fn inspected(&mut self, topic: &str, msg: &RawMessage) -> Option<&Visualized> {
+        // decoding whole images and point clouds at every move of the cursor would stall playback
+        let blob = self
+            .current_schema
+            .as_ref()
+            .is_some_and(|schema| is_blob_type(&schema.type_name));
+        if blob || !self.decode_visualized(topic, msg) {
+            return None;
+        }
+        self.visualized.as_ref()
+    }
+
+    /// Shows the covariance matrices of a message as grids.
+    fn ui_covariances(&mut self, ui: &mut egui::Ui, topic: &str, msg: &RawMessage) {
+        let lang = self.language;
+        let Some(visualized) = self.inspected(topic, msg) else {
+            return;
+        };
+        for covariance in visualized.covariances.iter() {
+            egui::CollapsingHeader::new(covariance.path.as_str())
+                .id_salt(("covariance", &covariance.path))
+                .default_open(true)
+                .show(ui, |ui| show_covariance(ui, lang, covariance));
+        }
+    }
+
+    /// Shows the roll, pitch and yaw of the quaternions of a message.
+    fn ui_euler_angles(&mut self, ui: &mut egui::Ui, topic: &str, msg: &RawMessage) {
+        let lang = self.language;
+        let Some(visualized) = self.inspected(topic, msg) else {
+            return;
+        };
+        if visualized.quaternions.is_empty() {
+            return;
+        }
+        egui::Grid::new("euler_angles")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong(lang.tr("Quaternion"));
+                for axis in [lang.tr("Roll"), lang.tr("Pitch"), lang.tr("Yaw")] {
+                    ui.strong(format!("{axis} [°]"));
+                }
+                ui.end_row();
+                for (path, quaternion) in visualized.quaternions.iter() {
+                    ui.monospace(path.as_str());
+                    for angle in quaternion_to_euler(*quaternion) {
+                        ui.monospace(format!("{:8.3}", angle.to_degrees()));
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// Shows the editor of the field columns of the message list.
+    fn ui_columns(&mut self, ui: &mut egui::Ui) {
+        let lang = self.language;
+        ui.horizontal_wrapped(|ui| {
+            ui.label(lang.tr("Columns"));
+            let mut removed = None;
+            for (i, column) in self.columns.iter().enumerate() {
+                if ui
+                    .button(format!("{column} ✖"))
+                    .on_hover_text(lang.tr("Remove column"))
+                    .clicked()
+                {
+                    removed = Some(i);
+                }
+            }
+            if let Some(i) = removed {
+                self.columns.remove(i);
+                self.column_values.clear();
+            }
+
+            let input = ui.add(
+                egui::TextEdit::singleline(&mut self.column_input)
+                    .hint_text("header.frame_id")
+                    .desired_width(160.0),
+            );
+            let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui.button("➕").clicked() || submitted) && !self.column_input.trim().is_empty() {
+                self.columns.push(self.column_input.trim().to_string());
+                self.column_input.clear();
+                self.column_values.clear();
+            }
+        });
+    }
+
+    /// Shows the watch expressions with their values at the playback cursor and the extremes
+    /// seen so far.
+    fn ui_watch(&mut self, ui: &mut egui::Ui) {
+        let lang = self.language;
+        let Some(backend) = &self.backend else {
+            return;
+        };
+        let watcher = self.watcher.get_or_insert_with(|| {
+            let watcher = Watcher::new(backend.clone(), ui.ctx().clone());
+            watcher.watch(&self.watches, &self.topics, &self.aliases);
+            watcher
+        });
+        watcher.seek(self.playback.cursor_ns);
+        let watcher = &*watcher;
+
+        ui.heading(lang.tr("Watch"));
+        ui.horizontal(|ui| {
+            let input = ui.add(
+                egui::TextEdit::singleline(&mut self.watch_input)
+                    .hint_text("/vehicle/status.velocity")
+                    .desired_width(ui.available_width() - 32.0),
+            );
+            let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui
+                .button("➕")
+                .on_hover_text(lang.tr("Add watch"))
+                .clicked()
+                || submitted)
+                && let Some(expression) = WatchExpression::parse(&self.watch_input)
+            {
+                if !self.watches.contains(&expression) {
+                    self.watches.push(expression);
+                    watcher.watch(&self.watches, &self.topics, &self.aliases);
+                }
+                self.watch_input.clear();
+            }
+        });
+        if ui.button(lang.tr("Reset min/max")).clicked() {
+            watcher.reset_extremes();
+        }
+        ui.separator();
+
+        let mut removed = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("watch_grid")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(lang.tr("Expression"));
+                    ui.strong(lang.tr("Value"));
+                    ui.strong(lang.tr("Min / Max"));
+                    ui.label("");
+                    ui.end_row();
+
+                    for (i, expression) in self.watches.iter().enumerate() {
+                        ui.monospace(expression.to_string());
+                        let topic = self.aliases.resolve(&expression.topic);
+                        let unit = self
+                            .topics
+                            .iter()
+                            .find(|t| t.name == topic)
+                            .and_then(|t| self.units.unit(&t.name, &t.type_name, &expression.field))
+                            .map_or(String::new(), |unit| format!(" {unit}"));
+                        let value = self
+                            .watcher
+                            .as_ref()
+                            .and_then(|watcher| watcher.value(expression))
+                            .unwrap_or_default();
+                        match (&value.error, &value.value) {
+                            (Some(error), _) => {
+                                ui.colored_label(egui::Color32::ORANGE, "⚠")
+                                    .on_hover_text(error);
+                            }
+                            (None, Some(v)) => {
+                                let text = match v {
+                                    serde_json::Value::String(s) => s.clone(),
+                                    serde_json::Value::Number(n) => format!("{n}{unit}"),
+                                    v => v.to_string(),
+                                };
+                                let label = ui.add(
+                                    egui::Label::new(RichText::new(text).monospace()).truncate(),
+                                );
+                                if let Some(ns) = value.timestamp {
+                                    label.on_hover_text(self.format_time(ns));
+                                }
+                            }
+                            (None, None) => {
+                                ui.label(to_rich_text("-"));
+                            }
+                        }
+                        match (value.min, value.max) {
+                            (Some(min), Some(max)) => {
+                                ui.monospace(format!("{min:.6} … {max:.6}{unit}"));
+                            }
+                            _ => {
+                                ui.label(to_rich_text("-"));
+                            }
+                        }
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text(lang.tr("Remove watch"))
+                            .clicked()
+                        {
+                            removed = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+        if let Some(i) = removed
+            && let Some(watcher) = &self.watcher
+        {
+            self.watches.remove(i);
+            watcher.watch(&self.watches, &self.topics, &self.aliases);
+        }
+    }
+
+    /// Shows a message header, and its body if expanded, decoding it on first display.
+    fn ui_message_row(&mut self, ui: &mut egui::Ui, idx: usize, msg: &RawMessage) {
+        let expanded = self.expanded.contains(&idx);
+        let marker = if expanded { "▼" } else { "▶" };
+        let mut header = format!(
+            "{marker} [#{idx}] @{} ({} bytes)",
+            ns_to_iso(msg.timestamp),
+            msg.data.len()
+        );
+        if !self.columns.is_empty() {
+            let (columns, schema) = (&self.columns, self.current_schema.as_ref());
+            let values = self
+                .column_values
+                .entry(idx)
+                .or_insert_with(|| extract_columns(columns, schema, msg));
+            for (column, value) in columns.iter().zip(values.iter()) {
+                header.push_str(&format!(" | {column}={value}"));
+            }
+        }
+        if ui.selectable_label(expanded, header).clicked() {
+            if expanded {
+                self.expanded.remove(&idx);
+            } else {
+                self.expanded.insert(idx);
+            }
+        }
+
+        if expanded {
+            let rule = self.current_rule();
+            let (view_mode, schema) = (&self.view_mode, self.current_schema.as_ref());
+            let summarize = *view_mode == ViewMode::Auto && !self.show_full_blobs;
+            let body = self.decoded.entry(idx).or_insert_with(|| {
+                let body = display_message(view_mode, rule.as_ref(), summarize, schema, msg);
+                printable(
+                    ui.ctx(),
+                    &egui::TextStyle::Monospace.resolve(ui.style()),
+                    &body,
+                )
+            });
+            ui.add(egui::Label::new(RichText::new(body.as_str()).monospace()).extend());
+        }
+    }
+
+    /// Returns the title of the window, naming the opened bag and warning if its recording is
+    /// incomplete.
+    fn window_title(&self) -> String {
+        let mut title = String::from("rospeek-app");
+        if let Some(source) = &self.source {
+            title.push_str(&format!(" - {source}"));
+        }
+        if !self.incomplete.is_empty() {
+            title.push_str(&format!(" ⚠ {}", self.language.tr("Incomplete recording")));
+        }
+        title
+    }
+
+    /// Returns the rule of the config overriding how the focused topic is decoded, if any.
+    fn current_rule(&self) -> Option<DecodeRule> {
+        let topic = self.current_topic.as_ref()?;
+        let topic = self.topics.iter().find(|t| &t.name == topic)?;
+        DecoderRegistry::global().rule_for(&topic.name, &topic.type_name)
+    }
+
+    /// Arms a condition pausing playback when satisfied, or disarms it with `None`.
+    fn arm_trigger(&mut self, ctx: &egui::Context, condition: Option<Condition>) {
+        let Some(backend) = &self.backend else {
+            return;
+        };
+        let armed = match condition {
+            Some(mut condition) => {
+                condition.topic = self.aliases.resolve(&condition.topic).to_string();
+                let Some(topic) = self.topics.iter().find(|t| t.name == condition.topic) else {
+                    let e = anyhow::anyhow!("Topic not found: {}", condition.topic);
+                    self.error = Some(ErrorDialog::new(&e));
+                    return;
+                };
+                Some((condition, topic.clone()))
+            }
+            None => None,
+        };
+        let trigger = self
+            .trigger
+            .get_or_insert_with(|| Trigger::new(backend.clone(), ctx.clone()));
+        trigger.update(self.playback.cursor_ns, false);
+        self.trigger_condition = armed.as_ref().map(|(condition, _)| condition.clone());
+        trigger.arm(armed);
+    }
+
+    /// Moves the workers following the playback cursor to where it jumped.
+    fn follow_cursor(&mut self) {
+        if let Some(prefetcher) = &self.prefetcher {
+            prefetcher.seek(self.playback.cursor_ns);
+        }
+        // the messages jumped over must not trigger, even while playing
+        if let Some(trigger) = self.trigger.as_mut() {
+            trigger.update(self.playback.cursor_ns, false);
         }
     }
 
-    fn display_message<'a>(&self, decoder: &mut CdrDecoder<'a>, msg: &'a RawMessage) -> String {
-        match self.view_mode {
-            ViewMode::Bytes => dump_bytes(&msg.data, 64),
-            _ => self.current_schema.as_ref().map_or_else(
-                || "Failed to decode binary: no schema".to_string(),
-                |schema| {
-                    try_decode_binary(decoder, schema, &msg.data).unwrap_or_else(|e| e.to_string())
-                },
-            ),
+    /// Moves the playback cursor to the adjacent message of the focused topic.
+    fn step(&mut self, forward: bool) {
+        if let Some(topic) = self.current_topic.clone() {
+            self.playback.pause();
+            let _ = self.tx.send(Command::Step {
+                topic,
+                from_ns: self.playback.cursor_ns,
+                forward,
+            });
         }
     }
 
+    /// Returns the approximate number of bytes held by the messages decoded for display, which
+    /// are not covered by the cap of the page cache.
+    fn decoded_bytes(&self) -> usize {
+        let bodies: usize = self.decoded.values().map(String::len).sum();
+        let columns: usize = self.column_values.values().flatten().map(String::len).sum();
+        bodies + columns + self.visualized.as_ref().map_or(0, |v| v.bytes)
+    }
+
+    /// Performs UI operations related to the status bar.
+    fn ui_status(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let prefetched = self
+                .prefetcher
+                .as_ref()
+                .map_or(0, |prefetcher| prefetcher.buffered_bytes());
+            ui.label(to_rich_text(&format!(
+                "Memory: pages {:.1} MiB | decoded {:.1} MiB | prefetch {:.1} MiB",
+                self.cached_bytes as f64 / MIB as f64,
+                self.decoded_bytes() as f64 / MIB as f64,
+                prefetched as f64 / MIB as f64
+            )));
+            ui.label(to_rich_text(self.language.tr("Page cache cap (MiB)")));
+            if ui
+                .add(egui::DragValue::new(&mut self.memory_cap_mb).range(16..=65536))
+                .changed()
+            {
+                let _ = self
+                    .tx
+                    .send(Command::SetMemoryCap(self.memory_cap_mb * MIB));
+            }
+            ui.separator();
+            ui.label(to_rich_text(self.language.tr("Zoom")));
+            if self.zoom.show(ui, self.language) {
+                ui.ctx().set_zoom_factor(self.zoom.factor());
+            }
+            if let Some(status) = &self.snapshot_status {
+                ui.separator();
+                ui.label(to_rich_text(status));
+            }
+        });
+    }
+
     /// Performs UI operations related to the timeline.
+    /// Formats a bag timestamp in the selected time basis.
+    fn format_time(&self, ns: u64) -> String {
+        match &self.clock {
+            Some(clock) if self.use_sim_time => format!("{} (sim)", ns_to_iso(clock.to_sim(ns))),
+            _ => ns_to_iso(ns),
+        }
+    }
+
     fn ui_timeline(&mut self, ui: &mut egui::Ui) {
+        let lang = self.language;
+        let rect = ui.max_rect();
         ui.horizontal(|ui| {
-            if ui.button("▶").clicked() { /* TODO(ktro2828): Implement timeline playback */ }
-            if ui.button("⏸").clicked() { /* TODO(ktro2828): Implement timeline pause */ }
-            if ui.button("⏹").clicked() { /* TODO(ktro2828): Implement timeline stop */ }
+            if ui
+                .button("📷")
+                .on_hover_text(lang.tr("Export view as PNG"))
+                .clicked()
+            {
+                self.snapshots.request(ui.ctx(), rect, "timeline");
+            }
+            if ui.button("▶").clicked() {
+                self.playback.play();
+            }
+            if ui.button("⏸").clicked() {
+                self.playback.pause();
+            }
+            if ui.button("⏹").clicked() {
+                self.playback.stop();
+            }
+            let (step_back, step_forward) = ui.input(|i| {
+                (
+                    i.key_pressed(egui::Key::Comma),
+                    i.key_pressed(egui::Key::Period),
+                )
+            });
+            let typing = ui.ctx().wants_keyboard_input();
+            if ui
+                .button("|◀")
+                .on_hover_text(lang.tr("Previous message (,)"))
+                .clicked()
+                || (step_back && !typing)
+            {
+                self.step(false);
+            }
+            if ui
+                .button("▶|")
+                .on_hover_text(lang.tr("Next message (.)"))
+                .clicked()
+                || (step_forward && !typing)
+            {
+                self.step(true);
+            }
             if ui.button("⏭").clicked()
                 && let Some(topic) = self.current_topic.clone()
             {
@@ -258,13 +1222,165 @@ impl<B: Backend + 'static> App<B> {
                 });
             }
             ui.add_space(8.0);
-            ui.label(to_rich_text("Timeline"));
+            ui.label(to_rich_text(lang.tr("Timeline")));
+            ui.label(to_rich_text(&self.format_time(self.playback.cursor_ns)));
+            if self.clock.is_some() {
+                ui.checkbox(&mut self.use_sim_time, lang.tr("Sim time"))
+                    .on_hover_text(lang.tr("Show times in simulated time published on /clock"));
+            }
+
+            ui.separator();
+            if ui
+                .button("A")
+                .on_hover_text(lang.tr("Set region start"))
+                .clicked()
+            {
+                let (_, b_ns) = self.playback.selected_range();
+                self.playback.set_region(self.playback.cursor_ns, b_ns);
+            }
+            if ui
+                .button("B")
+                .on_hover_text(lang.tr("Set region end"))
+                .clicked()
+            {
+                let (a_ns, _) = self.playback.selected_range();
+                self.playback.set_region(a_ns, self.playback.cursor_ns);
+            }
+            if let Some((a_ns, b_ns)) = self.playback.region() {
+                ui.label(to_rich_text(&format!(
+                    "{} → {}",
+                    self.format_time(a_ns),
+                    self.format_time(b_ns)
+                )));
+                if ui
+                    .button("✖")
+                    .on_hover_text(lang.tr("Clear region"))
+                    .clicked()
+                {
+                    self.playback.clear_region();
+                }
+            }
+            ui.checkbox(&mut self.playback.looping, lang.tr("Loop"));
+
+            ui.separator();
+            ui.label(to_rich_text(lang.tr("Pause when")));
+            let armed = self.trigger_condition.is_some();
+            let input = ui.add_enabled(
+                !armed,
+                egui::TextEdit::singleline(&mut self.trigger_input)
+                    .hint_text("/brake.data > 0.9")
+                    .desired_width(180.0),
+            );
+            if armed {
+                if ui
+                    .button("✖")
+                    .on_hover_text(lang.tr("Disarm trigger"))
+                    .clicked()
+                {
+                    self.arm_trigger(ui.ctx(), None);
+                }
+            } else {
+                let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui
+                    .button(lang.tr("Arm"))
+                    .on_hover_text(
+                        lang.tr("Pause playback at the first message satisfying the condition"),
+                    )
+                    .clicked()
+                    || submitted)
+                    && !self.trigger_input.trim().is_empty()
+                {
+                    match self.trigger_input.parse::<Condition>() {
+                        Ok(condition) => self.arm_trigger(ui.ctx(), Some(condition)),
+                        Err(e) => self.error = Some(ErrorDialog::new(&e)),
+                    }
+                }
+            }
+
+            let image_topics: Vec<&Topic> = self
+                .topics
+                .iter()
+                .filter(|t| is_image_type(&t.type_name))
+                .collect();
+            if !image_topics.is_empty() {
+                ui.separator();
+                egui::ComboBox::from_label(lang.tr("Thumbnails"))
+                    .selected_text(self.thumbnail_topic.as_deref().unwrap_or(lang.tr("None")))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.thumbnail_topic, None, lang.tr("None"));
+                        for topic in image_topics {
+                            ui.selectable_value(
+                                &mut self.thumbnail_topic,
+                                Some(topic.name.clone()),
+                                &topic.name,
+                            );
+                        }
+                    });
+            }
         });
+
+        let cursor_ns = self.playback.cursor_ns;
+        if let Some(topic) = &self.thumbnail_topic
+            && let Some(topic) = self.topics.iter().find(|t| &t.name == topic)
+            && let Some(backend) = &self.backend
+        {
+            let strip = self
+                .thumbnails
+                .get_or_insert_with(|| ThumbnailStrip::new(backend.clone(), ui.ctx().clone()));
+            thumbnail_strip(ui, strip, &mut self.playback, &topic.name, &topic.type_name);
+        }
+        timeline_bar(ui, &mut self.playback, &self.segments, &self.events);
+        if self.playback.cursor_ns != cursor_ns {
+            self.follow_cursor();
+        }
     }
 }
 
-impl<B: Backend + 'static> eframe::App for App<B> {
+impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let title = self.window_title();
+        if title != self.title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+        if self.zoom.handle_keys(ctx) {
+            ctx.set_zoom_factor(self.zoom.factor());
+        }
+        match self.snapshots.poll(ctx) {
+            Some(Ok(path)) => {
+                self.snapshot_status = Some(format!("✨Saved view to {}", path.display()));
+            }
+            Some(Err(e)) => self.snapshot_status = Some(e.to_string()),
+            None => {}
+        }
+        if self.playback.tick() {
+            if let Some(prefetcher) = &self.prefetcher {
+                prefetcher.seek(self.playback.cursor_ns);
+            }
+            if let Some(trigger) = self.trigger.as_mut() {
+                trigger.update(self.playback.cursor_ns, true);
+            }
+        }
+        match self.trigger.as_ref().and_then(Trigger::poll) {
+            Some(Ok(hit)) => {
+                self.playback.pause();
+                self.playback.seek(hit.timestamp);
+                self.follow_cursor();
+                if let Some(topic) = self.topics.iter().find(|t| t.name == hit.topic).cloned() {
+                    // also moves the prefetcher to the message
+                    self.focus_topic(&topic);
+                }
+            }
+            Some(Err(e)) => {
+                self.error = Some(ErrorDialog::new(&e));
+                self.trigger_condition = None;
+            }
+            None => {}
+        }
+        if self.playback.is_playing() {
+            ctx.request_repaint();
+        }
+
         while let Ok(ev) = self.rx.try_recv() {
             match ev {
                 Event::Topics(ts) => {
@@ -278,26 +1394,133 @@ impl<B: Backend + 'static> eframe::App for App<B> {
                     if Some(topic.clone()) == self.current_topic {
                         self.page_offset = offset;
                         self.page = msgs;
+                        self.expanded.clear();
+                        self.decoded.clear();
+                        self.column_values.clear();
                     }
                 }
-                Event::Error(e) => {
-                    egui::Window::new("Error").show(ctx, |ui| {
-                        ui.label(format!("Error: {e}"));
-                    });
+                Event::Memory(bytes) => {
+                    self.cached_bytes = bytes;
+                }
+                Event::Stats {
+                    topic,
+                    stats,
+                    sizes,
+                } => {
+                    self.topic_stats.insert(topic.clone(), stats);
+                    self.topic_sizes.insert(topic, sizes);
+                }
+                Event::Health { topic, health } => {
+                    self.topic_health.insert(topic, health);
                 }
+                Event::Seek(timestamp) => {
+                    self.playback.seek(timestamp);
+                    self.follow_cursor();
+                }
+                Event::Exported(summary) => {
+                    if let Some(dialog) = self.export_dialog.as_mut() {
+                        let dropped: u64 = summary.duplicates.values().sum();
+                        dialog.set_status(format!(
+                            "✨Exported {} messages of {} topics ({dropped} duplicates dropped)",
+                            summary.total(),
+                            summary.counts.len()
+                        ));
+                    }
+                }
+                Event::Error(dialog) => self.error = Some(dialog),
             }
         }
 
+        let lang = self.language;
+        if self
+            .error
+            .as_ref()
+            .is_some_and(|dialog| !dialog.show(ctx, lang))
+        {
+            self.error = None;
+        }
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                if ui.button("Open bag...").clicked() {
+                if ui.button(lang.tr("Open bag...")).clicked() {
                     self.open();
                 }
-                if let Some(p) = &self.bag_path {
-                    ui.label(to_rich_text(&p.display().to_string()));
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.remote_url)
+                        .hint_text("http://host:port")
+                        .desired_width(180.0),
+                );
+                if ui.button(lang.tr("Connect")).clicked() {
+                    self.connect_remote();
+                }
+                if ui
+                    .add_enabled(
+                        self.backend.is_some(),
+                        egui::Button::new(lang.tr("Export selection as new bag…")),
+                    )
+                    .clicked()
+                {
+                    self.export_dialog = Some(ExportDialog::new(&self.topics));
+                }
+                if ui
+                    .add_enabled(
+                        self.backend.is_some(),
+                        egui::Button::new(lang.tr("Attachments")),
+                    )
+                    .clicked()
+                    && let Some(backend) = &self.backend
+                {
+                    self.attachments_panel = Some(AttachmentsPanel::new(
+                        backend.attachments().unwrap_or_default(),
+                        backend.metadata().unwrap_or_default(),
+                    ));
+                }
+                ui.add_enabled_ui(self.backend.is_some(), |ui| {
+                    ui.toggle_value(&mut self.show_watch, lang.tr("Watch"))
+                        .on_hover_text(lang.tr("Show fields updated live during playback"));
+                });
+                if ui
+                    .add_enabled(
+                        self.backend.is_some(),
+                        egui::Button::new(lang.tr("Load events…")),
+                    )
+                    .on_hover_text(
+                        lang.tr("Show events extracted by `rospeek events` on the timeline"),
+                    )
+                    .clicked()
+                    && let Some(path) = FileDialog::new().add_filter("JSON", &["json"]).pick_file()
+                {
+                    match load_events(&path) {
+                        Ok(events) => self.events = events,
+                        Err(e) => eprintln!("Failed to load events: {e:?}"),
+                    }
+                }
+                if let Some(source) = &self.source {
+                    ui.label(to_rich_text(source));
+                }
+                if !self.incomplete.is_empty() {
+                    let signs: Vec<String> =
+                        self.incomplete.iter().map(ToString::to_string).collect();
+                    ui.colored_label(
+                        egui::Color32::ORANGE,
+                        format!("⚠ {}", lang.tr("Incomplete recording")),
+                    )
+                    .on_hover_text(format!(
+                        "{}\n{}",
+                        lang.tr(
+                            "The bag may lack its last messages and report a shorter duration:"
+                        ),
+                        signs.join("\n")
+                    ));
                 }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.label(to_rich_text("rospeek-gui"));
+                    egui::ComboBox::from_label(lang.tr("Language"))
+                        .selected_text(self.language.name())
+                        .show_ui(ui, |ui| {
+                            for language in Language::ALL {
+                                ui.selectable_value(&mut self.language, language, language.name());
+                            }
+                        });
                 });
             })
         });
@@ -309,13 +1532,65 @@ impl<B: Backend + 'static> eframe::App for App<B> {
                 self.ui_topics(ui);
             });
 
+        if self.show_watch && self.backend.is_some() {
+            egui::SidePanel::right("watch")
+                .resizable(true)
+                .default_width(260.0)
+                .show(ctx, |ui| {
+                    self.ui_watch(ui);
+                });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.ui_center(ui);
+            if self.backend.is_some() {
+                self.ui_center(ui);
+            } else if let Some(path) = self.start.show(ui, lang) {
+                self.open_path(path);
+            }
+        });
+
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+            self.ui_status(ui);
         });
 
         egui::TopBottomPanel::bottom("bottom").show(ctx, |ui| {
             self.ui_timeline(ui);
         });
+
+        if let Some(dialog) = self.export_dialog.as_mut() {
+            let mut open = true;
+            if let Some((output, options)) =
+                dialog.show(ctx, &self.topics, self.playback.selected_range(), &mut open)
+            {
+                let _ = self.tx.send(Command::Export { output, options });
+            }
+            if !open {
+                self.export_dialog = None;
+            }
+        }
+
+        if let Some(panel) = self.attachments_panel.as_mut() {
+            let mut open = true;
+            panel.show(ctx, &mut open);
+            if !open {
+                self.attachments_panel = None;
+            }
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, ZOOM_KEY, &self.zoom.factor());
+        eframe::set_value(storage, RECENT_KEY, &self.start.recent());
+    }
+}
+
+/// Returns the color of the topics of a health, or `None` for healthy topics.
+fn health_color(health: &TopicHealth) -> Option<egui::Color32> {
+    match health {
+        TopicHealth::Healthy => None,
+        TopicHealth::Empty => Some(egui::Color32::from_rgb(220, 80, 70)),
+        TopicHealth::Stale => Some(egui::Color32::from_rgb(230, 150, 50)),
+        TopicHealth::Sparse => Some(egui::Color32::from_rgb(210, 190, 60)),
     }
 }
 
@@ -324,6 +1599,134 @@ fn to_rich_text(s: &str) -> egui::RichText {
     RichText::new(s).color(egui::Color32::from_gray(150))
 }
 
+/// Loads the mapping to simulated time from the clock topic, if any.
+/// Scans the messages of topics page by page on a background thread, sending their statistics
+/// as they accumulate.
+///
+/// The scan stops once the app is bound to another backend and drops the receiver.
+fn spawn_stats_scan(
+    backend: Arc<dyn Backend>,
+    topics: &[Topic],
+    end_ns: u64,
+    tx: channel::Sender<Event>,
+) {
+    const SCAN_PAGE_SIZE: usize = 1000;
+
+    let names: Vec<String> = topics.iter().map(|t| t.name.clone()).collect();
+    std::thread::spawn(move || {
+        for name in names {
+            let mut stats = TopicStats::default();
+            let mut sizes = SizeHistogram::default();
+            let mut max_interval_ns = 0;
+            let mut previous_ns = None;
+            let mut offset = 0;
+            loop {
+                let Ok(msgs) = backend.read_messages(&name, None, SCAN_PAGE_SIZE, Some(offset))
+                else {
+                    break;
+                };
+                for msg in msgs.iter() {
+                    stats.accumulate(msg);
+                    sizes.accumulate(msg);
+                    if let Some(previous_ns) = previous_ns {
+                        max_interval_ns =
+                            max_interval_ns.max(msg.timestamp.saturating_sub(previous_ns));
+                    }
+                    previous_ns = Some(msg.timestamp);
+                }
+                offset += msgs.len();
+                let event = Event::Stats {
+                    topic: name.clone(),
+                    stats,
+                    sizes: sizes.clone(),
+                };
+                if tx.send(event).is_err() {
+                    return;
+                }
+                if msgs.len() < SCAN_PAGE_SIZE {
+                    let event = Event::Health {
+                        topic: name.clone(),
+                        health: TopicHealth::assess(&stats, max_interval_ns, end_ns),
+                    };
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                    break;
+                }
+            }
+        }
+    });
+}
+
+fn load_clock(backend: &dyn Backend, topics: &[Topic]) -> Option<ClockMap> {
+    if !topics
+        .iter()
+        .any(|t| t.name == CLOCK_TOPIC && t.type_name == CLOCK_TYPE)
+    {
+        return None;
+    }
+    let messages = backend
+        .read_messages(CLOCK_TOPIC, None, usize::MAX, None)
+        .ok()?;
+    ClockMap::from_messages(&messages).ok().flatten()
+}
+
+/// Extracts the values of field columns from a message.
+fn extract_columns(
+    columns: &[String],
+    schema: Option<&MessageSchema>,
+    msg: &RawMessage,
+) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| {
+            schema
+                .and_then(|schema| decode_field(schema, column, &msg.data).ok().flatten())
+                .map_or_else(|| "-".to_string(), |value| value.to_string())
+        })
+        .collect()
+}
+
+/// Renders a message body for the given view mode.
+///
+/// The rule of the topic, if any, takes precedence over the view mode except for bytes. With
+/// `summarize`, blob-bearing messages are rendered as a preview.
+fn display_message(
+    view_mode: &ViewMode,
+    rule: Option<&DecodeRule>,
+    summarize: bool,
+    schema: Option<&MessageSchema>,
+    msg: &RawMessage,
+) -> String {
+    match (view_mode, rule, schema) {
+        (ViewMode::Bytes, _, _) | (_, Some(DecodeRule::Bytes), _) => dump_bytes(&msg.data, 64),
+        (_, Some(rule), schema) => {
+            let plan = schema.and_then(|schema| DecodePlan::compile(schema).ok());
+            decode_with_rule(
+                Some(rule),
+                &mut CdrDecoder::new(&[]),
+                plan.as_ref(),
+                &msg.data,
+            )
+            .and_then(|value| Ok(serde_json::to_string_pretty(&value)?))
+            .unwrap_or_else(|e| e.to_string())
+        }
+        (_, None, Some(schema)) if summarize && is_blob_type(&schema.type_name) => {
+            CdrDecoder::new(&msg.data).decode(schema).map_or_else(
+                |e| e.to_string(),
+                |value| preview_blob(&schema.type_name, value),
+            )
+        }
+        (_, None, schema) => schema.map_or_else(
+            || "Failed to decode binary: no schema".to_string(),
+            |schema| {
+                let mut decoder = CdrDecoder::from_schema(schema);
+                try_decode_binary(&mut decoder, schema, &msg.data).unwrap_or_else(|e| e.to_string())
+            },
+        ),
+    }
+}
+
 /// Converts bytes to string representation.
 fn dump_bytes(bytes: &[u8], max_line: usize) -> String {
     const CHUNK_SIZE: usize = 16;
@@ -0,0 +1,84 @@
+use rfd::FileDialog;
+use rospeek_core::ns_to_iso;
+use rospeek_mcap::{McapAttachment, McapMetadata};
+
+/// Panel listing the attachments and metadata records of the opened bag.
+#[derive(Debug, Default)]
+pub struct AttachmentsPanel {
+    attachments: Vec<McapAttachment>,
+    metadata: Vec<McapMetadata>,
+    status: Option<String>,
+}
+
+impl AttachmentsPanel {
+    pub fn new(attachments: Vec<McapAttachment>, metadata: Vec<McapMetadata>) -> Self {
+        Self {
+            attachments,
+            metadata,
+            status: None,
+        }
+    }
+
+    /// Shows the panel.
+    ///
+    /// # Arguments
+    /// * `ctx` - The egui context.
+    /// * `open` - Whether the panel stays open.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("Attachments & Metadata")
+            .open(open)
+            .show(ctx, |ui| {
+                ui.heading("Metadata");
+                if self.metadata.is_empty() {
+                    ui.label("No metadata records");
+                }
+                for (i, metadata) in self.metadata.iter().enumerate() {
+                    egui::CollapsingHeader::new(&metadata.name)
+                        .id_salt(("metadata", i))
+                        .show(ui, |ui| {
+                            egui::Grid::new(("metadata_grid", i))
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (key, value) in metadata.entries.iter() {
+                                        ui.monospace(key);
+                                        ui.label(value);
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
+                ui.separator();
+
+                ui.heading("Attachments");
+                if self.attachments.is_empty() {
+                    ui.label("No attachments");
+                }
+                egui::Grid::new("attachments_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for attachment in self.attachments.iter() {
+                            ui.monospace(&attachment.name);
+                            ui.label(&attachment.media_type);
+                            ui.label(format!("{} bytes", attachment.data.len()));
+                            ui.label(ns_to_iso(attachment.log_time));
+                            if ui.button("Save…").clicked()
+                                && let Some(path) = FileDialog::new()
+                                    .set_file_name(&attachment.name)
+                                    .save_file()
+                            {
+                                self.status = Some(match std::fs::write(&path, &attachment.data) {
+                                    Ok(()) => format!("✨Saved to {}", path.display()),
+                                    Err(e) => format!("Failed to save attachment: {e}"),
+                                });
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(status) = &self.status {
+                    ui.separator();
+                    ui.label(status);
+                }
+            });
+    }
+}
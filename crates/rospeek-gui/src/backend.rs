@@ -1,17 +1,29 @@
-use std::{path::Path, sync::Mutex};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
 use anyhow::bail;
-use rospeek_core::{BagReader, RawMessage, RosPeekResult, Topic};
-use rospeek_db3::Db3Reader;
-use rospeek_mcap::McapReader;
+use rospeek_core::{
+    BagMetadata, BagReader, BagWriter, ExportOptions, ExportSummary, IncompleteSign, OpenOptions,
+    RawMessage, RosPeekResult, SplitReader, StorageType, Topic, export_bag,
+};
+use rospeek_db3::{Db3Reader, Db3Writer};
+use rospeek_mcap::{McapAttachment, McapMetadata, McapReader, McapStreamSummary, McapWriter};
 
-pub trait Backend: Send + Sync {
-    fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
-    where
-        Self: Sized;
+use crate::encryption::{is_encrypted, open_encrypted};
 
+/// Object-safe interface the GUI uses to browse a bag.
+///
+/// Backends are opened through a [`BackendFactory`] and shared as `Arc<dyn Backend>`,
+/// so alternative implementations can be swapped in at runtime.
+pub trait Backend: Send + Sync {
     fn topics(&self) -> RosPeekResult<Vec<Topic>>;
 
+    /// Returns the first and last message timestamps of the bag in nanoseconds.
+    fn time_range(&self) -> RosPeekResult<(u64, u64)>;
+
     fn read_messages(
         &self,
         topic: &str,
@@ -19,28 +31,197 @@ pub trait Backend: Send + Sync {
         limit: usize,
         offset: Option<usize>,
     ) -> RosPeekResult<Vec<RawMessage>>;
+
+    /// Returns the first message of a topic strictly after `after_ns`.
+    fn next_message(&self, topic: &str, after_ns: u64) -> RosPeekResult<Option<RawMessage>>;
+
+    /// Returns the last message of a topic strictly before `before_ns`.
+    fn prev_message(&self, topic: &str, before_ns: u64) -> RosPeekResult<Option<RawMessage>>;
+
+    /// Returns the message of a topic closest in time to `timestamp`, the earlier one on ties.
+    fn find_nearest(&self, topic: &str, timestamp: u64) -> RosPeekResult<Option<RawMessage>> {
+        let before = self.prev_message(topic, timestamp.saturating_add(1))?;
+        let after = self.next_message(topic, timestamp)?;
+        Ok([before, after]
+            .into_iter()
+            .flatten()
+            .min_by_key(|m| m.timestamp.abs_diff(timestamp)))
+    }
+
+    /// Exports the selected topics and time range into a new bag file.
+    ///
+    /// # Arguments
+    /// * `output` - The path of the bag file to create.
+    /// * `options` - The selection of topics and time range.
+    fn export(&self, _output: &Path, _options: &ExportOptions) -> RosPeekResult<ExportSummary> {
+        bail!("Export is not supported by this backend")
+    }
+
+    /// Returns the attachments of the bag, which only MCAP files can carry.
+    fn attachments(&self) -> RosPeekResult<Vec<McapAttachment>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the metadata records of the bag, which only MCAP files can carry.
+    fn metadata(&self) -> RosPeekResult<Vec<McapMetadata>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the files of a bag concatenated from several files, empty for a single bag.
+    fn segments(&self) -> Vec<Segment> {
+        Vec::new()
+    }
+
+    /// Returns the signs found when opening the bag that its recording is incomplete.
+    fn incomplete_signs(&self) -> Vec<IncompleteSign> {
+        Vec::new()
+    }
+}
+
+/// File of a bag concatenated from several files, such as consecutive drives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// Name of the file.
+    pub name: String,
+    /// Timestamp of the first message of the file in nanoseconds.
+    pub start_ns: u64,
+    /// Timestamp of the last message of the file in nanoseconds.
+    pub end_ns: u64,
+}
+
+/// Opens a [`Backend`] for a given location.
+pub trait BackendFactory: Send + Sync {
+    /// Opens a backend for the given path.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the bag file.
+    ///
+    /// # Returns
+    /// A result containing the opened backend or an error.
+    fn open(&self, path: &Path) -> RosPeekResult<Arc<dyn Backend>>;
+
+    /// Opens a backend presenting several bag files as one concatenated bag.
+    ///
+    /// # Arguments
+    /// * `paths` - The paths to the bag files, in any order.
+    fn open_many(&self, paths: &[PathBuf]) -> RosPeekResult<Arc<dyn Backend>> {
+        match paths {
+            [path] => self.open(path),
+            _ => bail!("Opening several bags at once is not supported by this backend"),
+        }
+    }
 }
 
 pub struct ReaderBackend {
     inner: Mutex<Box<dyn BagReader>>,
+    attachments: Vec<McapAttachment>,
+    metadata: Vec<McapMetadata>,
+    segments: Vec<Segment>,
+    incomplete: Vec<IncompleteSign>,
 }
 
-impl Backend for ReaderBackend {
-    fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
-    where
-        Self: Sized,
-    {
-        let reader = create_reader(path)?;
+impl ReaderBackend {
+    /// Opens a local bag file with the reader matching its extension.
+    ///
+    /// Statistics are computed on first use, so that indexed files open without being scanned.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the bag file.
+    pub fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
+        let options = OpenOptions {
+            compute_stats: false,
+            ..Default::default()
+        };
+        // MCAP files are opened directly to also load their attachments and metadata
+        if path.as_ref().is_file()
+            && !is_encrypted(&path)
+            && StorageType::detect(&path) == Some(StorageType::Mcap)
+        {
+            let reader = McapReader::open_with_options(path, &options)?;
+            let attachments = reader.attachments()?;
+            let metadata = reader.metadata()?;
+            let incomplete = reader.incomplete_signs();
+            let reader: Box<dyn BagReader> = Box::new(reader);
+            return Ok(Self {
+                inner: Mutex::new(reader),
+                attachments,
+                metadata,
+                segments: Vec::new(),
+                incomplete,
+            });
+        }
+
+        let reader = create_reader_with_options(&path, &options)?;
+        let mut incomplete = reader.incomplete_signs();
+        if let Some(metadata) = BagMetadata::load(&path)? {
+            incomplete.extend(metadata.incomplete_signs(&path, Some(reader.stats()?))?);
+        }
 
         Ok(Self {
             inner: Mutex::new(reader),
+            attachments: Vec::new(),
+            metadata: Vec::new(),
+            segments: Vec::new(),
+            incomplete,
+        })
+    }
+
+    /// Opens several bag files as one bag, concatenating them in the order of their first
+    /// message without merging them into a new file.
+    ///
+    /// # Arguments
+    /// * `paths` - The paths to the bag files, in any order.
+    pub fn open_many(paths: &[PathBuf]) -> RosPeekResult<Self> {
+        let options = OpenOptions {
+            compute_stats: false,
+            ..Default::default()
+        };
+        let splits = paths
+            .iter()
+            .map(|path| {
+                create_reader_with_options(path, &options).map(|reader| (path.clone(), reader))
+            })
+            .collect::<RosPeekResult<Vec<_>>>()?;
+        let parent = paths
+            .first()
+            .and_then(|path| path.parent())
+            .unwrap_or(Path::new(""));
+        let reader = SplitReader::new(parent, splits)?;
+        let segments = reader
+            .file_ranges()
+            .into_iter()
+            .map(|(path, start_ns, end_ns)| Segment {
+                name: path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                ),
+                start_ns,
+                end_ns,
+            })
+            .collect();
+        let incomplete = reader.incomplete_signs();
+
+        Ok(Self {
+            inner: Mutex::new(Box::new(reader)),
+            attachments: Vec::new(),
+            metadata: Vec::new(),
+            segments,
+            incomplete,
         })
     }
+}
 
+impl Backend for ReaderBackend {
     fn topics(&self) -> RosPeekResult<Vec<Topic>> {
         self.inner.lock().unwrap().topics()
     }
 
+    fn time_range(&self) -> RosPeekResult<(u64, u64)> {
+        let reader = self.inner.lock().unwrap();
+        let stats = reader.stats()?;
+        Ok((stats.start_ns, stats.end_ns))
+    }
+
     fn read_messages(
         &self,
         topic: &str,
@@ -48,19 +229,220 @@ impl Backend for ReaderBackend {
         limit: usize,
         offset: Option<usize>,
     ) -> RosPeekResult<Vec<RawMessage>> {
-        self.inner
-            .lock()
-            .unwrap()
-            .read_messages_range(topic, start_ns, None, Some(limit), offset)
+        let reader = self.inner.lock().unwrap();
+        match start_ns {
+            // pages are addressed by index, so the reader can seek to them
+            None => reader.read_messages_by_index(topic, offset.unwrap_or_default(), limit),
+            Some(start_ns) => {
+                reader.read_messages_range(topic, Some(start_ns), None, Some(limit), offset)
+            }
+        }
+    }
+
+    fn next_message(&self, topic: &str, after_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        self.inner.lock().unwrap().next_message(topic, after_ns)
+    }
+
+    fn prev_message(&self, topic: &str, before_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        self.inner.lock().unwrap().prev_message(topic, before_ns)
+    }
+
+    fn find_nearest(&self, topic: &str, timestamp: u64) -> RosPeekResult<Option<RawMessage>> {
+        self.inner.lock().unwrap().find_nearest(topic, timestamp)
+    }
+
+    fn export(&self, output: &Path, options: &ExportOptions) -> RosPeekResult<ExportSummary> {
+        let mut writer = create_writer(output)?;
+        let reader = self.inner.lock().unwrap();
+        export_bag(reader.as_ref(), writer.as_mut(), options)
+    }
+
+    fn attachments(&self) -> RosPeekResult<Vec<McapAttachment>> {
+        Ok(self.attachments.clone())
+    }
+
+    fn metadata(&self) -> RosPeekResult<Vec<McapMetadata>> {
+        Ok(self.metadata.clone())
+    }
+
+    fn segments(&self) -> Vec<Segment> {
+        self.segments.clone()
+    }
+
+    fn incomplete_signs(&self) -> Vec<IncompleteSign> {
+        self.incomplete.clone()
+    }
+}
+
+/// Factory opening local bag files as [`ReaderBackend`].
+#[derive(Debug, Default)]
+pub struct ReaderBackendFactory;
+
+impl BackendFactory for ReaderBackendFactory {
+    fn open(&self, path: &Path) -> RosPeekResult<Arc<dyn Backend>> {
+        Ok(Arc::new(ReaderBackend::open(path)?))
+    }
+
+    fn open_many(&self, paths: &[PathBuf]) -> RosPeekResult<Arc<dyn Backend>> {
+        match paths {
+            [path] => self.open(path),
+            _ => Ok(Arc::new(ReaderBackend::open_many(paths)?)),
+        }
     }
 }
 
+/// Path reading a bag from the standard input, as in `cat bag.mcap | rospeek info -`.
+pub const STDIN: &str = "-";
+
+/// Error of a bag whose storage is neither SQLite nor MCAP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedFormat {
+    /// The path to the bag.
+    pub path: PathBuf,
+}
+
+impl UnsupportedFormat {
+    pub(crate) fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl std::fmt::Display for UnsupportedFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unsupported bag format: {}", self.path.display())
+    }
+}
+
+impl std::error::Error for UnsupportedFormat {}
+
 pub fn create_reader<P: AsRef<Path>>(bag: P) -> RosPeekResult<Box<dyn BagReader>> {
-    let reader: Box<dyn BagReader> = match bag.as_ref().extension().and_then(|ext| ext.to_str()) {
-        Some("db3") => Box::new(Db3Reader::open(bag)?),
-        Some("mcap") => Box::new(McapReader::open(bag)?),
-        _ => bail!("Unsupported bag format: {}", bag.as_ref().display()),
+    create_reader_with_options(bag, &OpenOptions::default())
+}
+
+/// Opens a bag file or directory with the reader of its storage.
+///
+/// The storage of files is taken from `options`, or else detected from their leading bytes and
+/// then their extension, so that misnamed files still open. The path [`STDIN`] reads an MCAP
+/// bag from the standard input.
+///
+/// # Arguments
+/// * `bag` - The path to the bag file or directory.
+/// * `options` - What to compute when opening rather than on first use.
+pub fn create_reader_with_options<P: AsRef<Path>>(
+    bag: P,
+    options: &OpenOptions,
+) -> RosPeekResult<Box<dyn BagReader>> {
+    if bag.as_ref() == Path::new(STDIN) {
+        return read_stdin(options);
+    }
+    if bag.as_ref().is_dir() {
+        return create_split_reader(bag.as_ref(), options);
+    }
+    if is_encrypted(&bag) {
+        return open_encrypted(bag.as_ref(), options);
+    }
+
+    let storage = options
+        .storage
+        .or_else(|| StorageType::detect(&bag))
+        .ok_or_else(|| UnsupportedFormat::new(&bag))?;
+    let reader: Box<dyn BagReader> = match storage {
+        StorageType::Sqlite3 => Box::new(Db3Reader::open_with_options(bag, options)?),
+        StorageType::Mcap => Box::new(McapReader::open_with_options(bag, options)?),
     };
 
     Ok(reader)
 }
+
+/// Opens a bag to summarize its statistics, topics and sizes, such as for `info` and `topics`.
+///
+/// Unlike [`create_reader_with_options`], an MCAP bag piped through the standard input is read
+/// in a single pass without holding it in memory, keeping only the messages of `keep`. Other
+/// bags are opened as usual.
+///
+/// # Arguments
+/// * `bag` - The path to the bag file or directory, or [`STDIN`].
+/// * `options` - What to compute when opening rather than on first use.
+/// * `keep` - The topics whose messages can be read from a piped bag, such as `/clock`.
+pub fn create_summary_reader<P: AsRef<Path>>(
+    bag: P,
+    options: &OpenOptions,
+    keep: &[&str],
+) -> RosPeekResult<Box<dyn BagReader>> {
+    if bag.as_ref() != Path::new(STDIN) {
+        return create_reader_with_options(bag, options);
+    }
+    if options.storage == Some(StorageType::Sqlite3) {
+        bail!("Only MCAP bags can be read from the standard input, save .db3 bags into a file");
+    }
+    let stdin = std::io::stdin().lock();
+    Ok(Box::new(McapStreamSummary::read(stdin, STDIN, keep)?))
+}
+
+/// Reads an MCAP bag piped through the standard input into memory, for commands which read
+/// messages in any order. The whole bag is held in memory, see [`create_summary_reader`] for
+/// commands which only summarize it.
+///
+/// SQLite needs a file to open, so `.db3` bags are refused rather than spilled to disk.
+fn read_stdin(options: &OpenOptions) -> RosPeekResult<Box<dyn BagReader>> {
+    let mut bytes = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut bytes)?;
+    match options
+        .storage
+        .or_else(|| StorageType::detect_bytes(&bytes))
+    {
+        Some(StorageType::Mcap) => Ok(Box::new(McapReader::from_bytes(bytes, STDIN, options)?)),
+        Some(StorageType::Sqlite3) => {
+            bail!("Only MCAP bags can be read from the standard input, save .db3 bags into a file")
+        }
+        None => Err(UnsupportedFormat::new(STDIN).into()),
+    }
+}
+
+/// Opens a bag directory, reading its files as a single bag if it was split while recording.
+fn create_split_reader(dir: &Path, options: &OpenOptions) -> RosPeekResult<Box<dyn BagReader>> {
+    let mut files = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    files.retain(|path| {
+        matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("db3" | "mcap")
+        )
+    });
+    files.sort();
+
+    match files.len() {
+        0 => bail!("No bag files found in {}", dir.display()),
+        1 => create_reader_with_options(&files[0], options),
+        _ => {
+            let splits = files
+                .into_iter()
+                .map(|path| create_reader_with_options(&path, options).map(|reader| (path, reader)))
+                .collect::<RosPeekResult<Vec<_>>>()?;
+            Ok(Box::new(SplitReader::new(dir, splits)?))
+        }
+    }
+}
+
+pub fn create_writer<P: AsRef<Path>>(bag: P) -> RosPeekResult<Box<dyn BagWriter>> {
+    let writer: Box<dyn BagWriter> = match bag.as_ref().extension().and_then(|ext| ext.to_str()) {
+        Some("db3") => Box::new(Db3Writer::create(bag)?),
+        Some("mcap") => Box::new(McapWriter::create(bag)?),
+        _ => return Err(UnsupportedFormat::new(&bag).into()),
+    };
+
+    Ok(writer)
+}
+
+pub fn append_writer<P: AsRef<Path>>(bag: P) -> RosPeekResult<Box<dyn BagWriter>> {
+    let writer: Box<dyn BagWriter> = match bag.as_ref().extension().and_then(|ext| ext.to_str()) {
+        Some("db3") => Box::new(Db3Writer::append(bag)?),
+        Some("mcap") => Box::new(McapWriter::append(bag)?),
+        _ => return Err(UnsupportedFormat::new(&bag).into()),
+    };
+
+    Ok(writer)
+}
@@ -0,0 +1,85 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use rospeek_core::RawMessage;
+use serde_json::Value;
+
+/// A page of messages shared between the worker cache and the UI.
+pub type Page = Arc<Vec<RawMessage>>;
+
+/// Returns the approximate number of bytes held by a page of raw messages, before decoding.
+pub fn page_bytes(page: &[RawMessage]) -> usize {
+    page.iter()
+        .map(|m| size_of::<RawMessage>() + m.data.len())
+        .sum()
+}
+
+/// Returns the approximate number of bytes held by a decoded message, which is often several
+/// times its raw size, such as for point clouds.
+pub fn value_bytes(value: &Value) -> usize {
+    size_of::<Value>()
+        + match value {
+            Value::String(s) => s.len(),
+            Value::Array(items) => items.iter().map(value_bytes).sum(),
+            Value::Object(fields) => fields.iter().map(|(k, v)| k.len() + value_bytes(v)).sum(),
+            _ => 0,
+        }
+}
+
+/// Least-recently-used cache of loaded pages bounded by an approximate memory cap.
+#[derive(Debug)]
+pub struct PageCache {
+    /// Pages ordered from least to most recently used
+    pages: VecDeque<(String, usize, Page)>,
+    bytes: usize,
+    capacity_bytes: usize,
+}
+
+impl PageCache {
+    pub fn new(capacity_bytes: usize) -> Self {
+        Self {
+            pages: VecDeque::new(),
+            bytes: 0,
+            capacity_bytes,
+        }
+    }
+
+    /// Returns the cached page of a topic at an offset, marking it as recently used.
+    pub fn get(&mut self, topic: &str, offset: usize) -> Option<Page> {
+        let index = self
+            .pages
+            .iter()
+            .position(|(t, o, _)| t == topic && *o == offset)?;
+        let entry = self.pages.remove(index)?;
+        let page = entry.2.clone();
+        self.pages.push_back(entry);
+        Some(page)
+    }
+
+    pub fn insert(&mut self, topic: String, offset: usize, page: Page) {
+        self.bytes += page_bytes(&page);
+        self.pages.push_back((topic, offset, page));
+        self.trim();
+    }
+
+    /// Changes the memory cap, trimming pages if necessary.
+    pub fn set_capacity(&mut self, capacity_bytes: usize) {
+        self.capacity_bytes = capacity_bytes;
+        self.trim();
+    }
+
+    /// Returns the approximate number of cached bytes.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Drops least recently used pages until the cache fits the cap.
+    ///
+    /// The most recent page is always kept since it is the one displayed.
+    fn trim(&mut self) {
+        while self.bytes > self.capacity_bytes && self.pages.len() > 1 {
+            if let Some((_, _, page)) = self.pages.pop_front() {
+                self.bytes -= page_bytes(&page);
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+
+use rospeek_core::{BagMetadata, BagStats, OpenOptions, RosPeekResult, Topic};
+use rusqlite::{Connection, params, params_from_iter, types::Value as SqlValue};
+
+use crate::create_reader_with_options;
+
+/// Environment variable naming the catalog file.
+pub const CATALOG_ENV: &str = "ROSPEEK_CATALOG";
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS bags (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    storage_type TEXT NOT NULL,
+    size_gib REAL NOT NULL,
+    message_count INTEGER NOT NULL,
+    start_ns INTEGER NOT NULL,
+    end_ns INTEGER NOT NULL,
+    duration_sec REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS topics (
+    bag_id INTEGER NOT NULL REFERENCES bags(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    type TEXT NOT NULL,
+    count INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS topics_name ON topics(name);
+CREATE INDEX IF NOT EXISTS topics_type ON topics(type);
+"#;
+
+/// Bag recorded in the catalog.
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    pub path: String,
+    pub storage_type: String,
+    pub size_gib: f64,
+    pub message_count: u64,
+    pub start_ns: u64,
+    pub duration_sec: f64,
+}
+
+/// Conditions on the bags to find, all of which must hold.
+#[derive(Debug, Clone, Default)]
+pub struct CatalogQuery {
+    /// Topic name the bag must contain, with `*` and `?` wildcards.
+    pub topic: Option<String>,
+    /// Message type the bag must contain, with `*` and `?` wildcards.
+    pub type_name: Option<String>,
+    /// UNIX epoch nanoseconds at or after which the recording must start.
+    pub after: Option<u64>,
+    /// UNIX epoch nanoseconds before which the recording must start.
+    pub before: Option<u64>,
+}
+
+/// SQLite index of the stats and topics of the bags of a directory tree, to find recordings
+/// without opening them.
+pub struct Catalog {
+    connection: Connection,
+}
+
+impl Catalog {
+    /// Opens a catalog, creating it if it does not exist.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the catalog file.
+    pub fn open(path: &Path) -> RosPeekResult<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(path)?;
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// Indexes every bag found under a directory, replacing the entries of bags indexed before.
+    ///
+    /// # Arguments
+    /// * `dir` - Root of the directory tree to scan.
+    ///
+    /// # Returns
+    /// The result of indexing each bag found.
+    pub fn add_dir(&mut self, dir: &Path) -> RosPeekResult<Vec<(PathBuf, RosPeekResult<usize>)>> {
+        let mut bags = Vec::new();
+        find_bags(dir, &mut bags)?;
+        bags.sort();
+
+        let mut results = Vec::with_capacity(bags.len());
+        for bag in bags {
+            let indexed = self.add_bag(&bag);
+            results.push((bag, indexed));
+        }
+        Ok(results)
+    }
+
+    /// Indexes a bag, replacing its entry if it was indexed before.
+    ///
+    /// # Returns
+    /// The number of topics of the bag.
+    fn add_bag(&mut self, bag: &Path) -> RosPeekResult<usize> {
+        let (stats, topics) = read_bag(bag)?;
+        let path = std::fs::canonicalize(bag)?.display().to_string();
+
+        let transaction = self.connection.transaction()?;
+        transaction.execute("DELETE FROM bags WHERE path = ?1", [&path])?;
+        transaction.execute(
+            "INSERT INTO bags (path, storage_type, size_gib, message_count, start_ns, end_ns, duration_sec)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                path,
+                stats.storage_type.to_string(),
+                stats.size_bytes,
+                stats.message_count as i64,
+                stats.start_ns as i64,
+                stats.end_ns as i64,
+                stats.duration_sec,
+            ],
+        )?;
+        let bag_id = transaction.last_insert_rowid();
+        for topic in topics.iter() {
+            transaction.execute(
+                "INSERT INTO topics (bag_id, name, type, count) VALUES (?1, ?2, ?3, ?4)",
+                params![bag_id, topic.name, topic.type_name, topic.count as i64],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(topics.len())
+    }
+
+    /// Finds the bags matching a query, ordered by start time.
+    pub fn search(&self, query: &CatalogQuery) -> RosPeekResult<Vec<CatalogEntry>> {
+        let mut sql = String::from(
+            "SELECT b.path, b.storage_type, b.size_gib, b.message_count, b.start_ns, b.duration_sec
+             FROM bags b WHERE 1 = 1",
+        );
+        let mut params: Vec<SqlValue> = Vec::new();
+        for (column, pattern) in [("name", &query.topic), ("type", &query.type_name)] {
+            if let Some(pattern) = pattern {
+                sql.push_str(&format!(
+                    " AND EXISTS (SELECT 1 FROM topics t WHERE t.bag_id = b.id AND t.{column} GLOB ?)"
+                ));
+                params.push(SqlValue::from(pattern.clone()));
+            }
+        }
+        if let Some(after) = query.after {
+            sql.push_str(" AND b.start_ns >= ?");
+            params.push(SqlValue::from(after as i64));
+        }
+        if let Some(before) = query.before {
+            sql.push_str(" AND b.start_ns < ?");
+            params.push(SqlValue::from(before as i64));
+        }
+        sql.push_str(" ORDER BY b.start_ns, b.path");
+
+        let mut statement = self.connection.prepare(&sql)?;
+        let rows = statement.query_map(params_from_iter(params), |row| {
+            Ok(CatalogEntry {
+                path: row.get(0)?,
+                storage_type: row.get(1)?,
+                size_gib: row.get(2)?,
+                message_count: row.get(3)?,
+                start_ns: row.get(4)?,
+                duration_sec: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+/// Returns the path of the catalog from [`CATALOG_ENV`], or else `rospeek/catalog.db3` in the
+/// user data directory.
+pub fn default_catalog_path() -> RosPeekResult<PathBuf> {
+    if let Some(path) = std::env::var_os(CATALOG_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!("No data directory found, pass --catalog or set {CATALOG_ENV}")
+        })?;
+    Ok(base.join("rospeek").join("catalog.db3"))
+}
+
+/// Collects the bags under a directory: directories with a `metadata.yaml`, and `.db3` and
+/// `.mcap` files outside of them.
+fn find_bags(dir: &Path, bags: &mut Vec<PathBuf>) -> RosPeekResult<()> {
+    if BagMetadata::load(dir).ok().flatten().is_some() {
+        bags.push(dir.to_path_buf());
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_bags(&path, bags)?;
+        } else if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("db3" | "mcap")
+        ) {
+            bags.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the stats and topics of a bag, from its `metadata.yaml` if it has one.
+fn read_bag(bag: &Path) -> RosPeekResult<(BagStats, Vec<Topic>)> {
+    if let Some(metadata) = BagMetadata::load(bag)? {
+        return Ok((metadata.stats(bag)?, metadata.topics()));
+    }
+    let reader = create_reader_with_options(bag, &OpenOptions::default())?;
+    let topics = reader.topics()?;
+    let stats = reader.stats()?;
+    let stats = BagStats::new(
+        bag,
+        stats.storage_type,
+        stats.message_count,
+        stats.start_ns,
+        stats.end_ns,
+    );
+    Ok((stats, topics))
+}
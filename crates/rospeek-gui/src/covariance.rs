@@ -0,0 +1,207 @@
+use egui::{Color32, RichText};
+use serde_json::Value;
+
+use crate::i18n::Language;
+
+/// Labels of the rows and columns of a 6×6 covariance, as in `PoseWithCovariance` and
+/// `TwistWithCovariance`.
+const POSE_AXES: [&str; 6] = ["x", "y", "z", "roll", "pitch", "yaw"];
+
+const POSITIVE_COLOR: Color32 = Color32::from_rgb(76, 120, 168);
+const NEGATIVE_COLOR: Color32 = Color32::from_rgb(220, 80, 70);
+
+/// Covariance matrix found in a decoded message, such as the pose covariance of an odometry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Covariance {
+    /// Dot-separated path of the field, such as `pose.covariance`.
+    pub path: String,
+    /// Number of rows and columns.
+    pub size: usize,
+    /// Elements in row-major order.
+    pub values: Vec<f64>,
+}
+
+impl Covariance {
+    /// Returns the element at a row and a column.
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        self.values[row * self.size + column]
+    }
+
+    /// Returns whether the publisher left the covariance unknown, which ROS messages mark with
+    /// zeros or with -1 as the first element.
+    pub fn is_unknown(&self) -> bool {
+        self.values.first() == Some(&-1.0) || self.values.iter().all(|v| *v == 0.0)
+    }
+
+    /// Returns whether the matrix is symmetric and positive-definite, as covariances of
+    /// well-conditioned estimates are.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_gui::covariance::Covariance;
+    ///
+    /// let covariance = |values: Vec<f64>| Covariance {
+    ///     path: "covariance".to_string(),
+    ///     size: 3,
+    ///     values,
+    /// };
+    /// assert!(covariance(vec![2.0, 0.5, 0.0, 0.5, 1.0, 0.0, 0.0, 0.0, 3.0]).is_positive_definite());
+    /// // a variance of zero
+    /// assert!(!covariance(vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).is_positive_definite());
+    /// // correlations stronger than the variances allow
+    /// assert!(!covariance(vec![1.0, 2.0, 0.0, 2.0, 1.0, 0.0, 0.0, 0.0, 1.0]).is_positive_definite());
+    /// // not symmetric
+    /// assert!(!covariance(vec![1.0, 0.5, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]).is_positive_definite());
+    /// ```
+    pub fn is_positive_definite(&self) -> bool {
+        let n = self.size;
+        let scale = self.values.iter().fold(0.0f64, |max, v| max.max(v.abs()));
+        let tolerance = scale * 1e-9;
+        let symmetric =
+            (0..n).all(|i| (0..i).all(|j| (self.get(i, j) - self.get(j, i)).abs() <= tolerance));
+        if !symmetric || scale == 0.0 {
+            return false;
+        }
+
+        // Cholesky decomposition, which only succeeds for positive-definite matrices
+        let mut lower = vec![0.0f64; n * n];
+        for j in 0..n {
+            let diagonal = self.get(j, j) - (0..j).map(|k| lower[j * n + k].powi(2)).sum::<f64>();
+            if diagonal <= tolerance {
+                return false;
+            }
+            lower[j * n + j] = diagonal.sqrt();
+            for i in j + 1..n {
+                let dot: f64 = (0..j).map(|k| lower[i * n + k] * lower[j * n + k]).sum();
+                lower[i * n + j] = (self.get(i, j) - dot) / lower[j * n + j];
+            }
+        }
+        true
+    }
+}
+
+/// Finds the covariance matrices of a decoded message, which are arrays of 9 or 36 numbers in
+/// fields named `covariance` or ending with `_covariance`, such as `orientation_covariance`.
+///
+/// # Examples
+/// ```
+/// use rospeek_gui::covariance::find_covariances;
+/// use serde_json::json;
+///
+/// let odometry = json!({
+///     "pose": { "pose": {}, "covariance": vec![0.0; 36] },
+///     "twist": { "twist": {}, "covariance": vec![0.0; 36] },
+/// });
+/// let covariances = find_covariances(&odometry);
+/// assert_eq!(covariances.len(), 2);
+/// assert_eq!(covariances[0].path, "pose.covariance");
+/// assert_eq!(covariances[0].size, 6);
+///
+/// let objects = json!({ "objects": [{ "position_covariance": vec![1.0; 9] }] });
+/// assert_eq!(find_covariances(&objects)[0].path, "objects.0.position_covariance");
+/// assert!(find_covariances(&json!({ "covariance": [1.0, 2.0] })).is_empty());
+/// ```
+pub fn find_covariances(value: &Value) -> Vec<Covariance> {
+    let mut covariances = Vec::new();
+    collect_covariances(value, "", &mut covariances);
+    covariances
+}
+
+fn collect_covariances(value: &Value, prefix: &str, covariances: &mut Vec<Covariance>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = format!("{prefix}{key}");
+                if (key == "covariance" || key.ends_with("_covariance"))
+                    && let Some(covariance) = to_covariance(&path, value)
+                {
+                    covariances.push(covariance);
+                    continue;
+                }
+                collect_covariances(value, &format!("{path}."), covariances);
+            }
+        }
+        // arrays of numbers, such as the data of images, hold no covariance
+        Value::Array(items) if items.first().is_some_and(|v| v.is_object() || v.is_array()) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_covariances(item, &format!("{prefix}{i}."), covariances);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn to_covariance(path: &str, value: &Value) -> Option<Covariance> {
+    let values: Vec<f64> = value
+        .as_array()?
+        .iter()
+        .map(Value::as_f64)
+        .collect::<Option<_>>()?;
+    let size = match values.len() {
+        9 => 3,
+        36 => 6,
+        _ => return None,
+    };
+    Some(Covariance {
+        path: path.to_string(),
+        size,
+        values,
+    })
+}
+
+/// Shows a covariance as a grid whose cells are colored by the magnitude of their element,
+/// warning if it is not positive-definite.
+pub fn show_covariance(ui: &mut egui::Ui, lang: Language, covariance: &Covariance) {
+    if covariance.is_unknown() {
+        ui.weak(lang.tr("Unknown covariance"));
+        return;
+    }
+    if !covariance.is_positive_definite() {
+        ui.colored_label(
+            Color32::ORANGE,
+            format!("⚠ {}", lang.tr("Not positive-definite")),
+        );
+    }
+
+    let scale = covariance
+        .values
+        .iter()
+        .fold(0.0f64, |max, v| max.max(v.abs()));
+    let axes = (covariance.size == 6).then_some(POSE_AXES);
+    egui::Grid::new(&covariance.path)
+        .spacing([2.0, 2.0])
+        .show(ui, |ui| {
+            if let Some(axes) = axes {
+                ui.label("");
+                for axis in axes {
+                    ui.weak(axis);
+                }
+                ui.end_row();
+            }
+            for row in 0..covariance.size {
+                if let Some(axes) = axes {
+                    ui.weak(axes[row]);
+                }
+                for column in 0..covariance.size {
+                    let value = covariance.get(row, column);
+                    let color = if value < 0.0 {
+                        NEGATIVE_COLOR
+                    } else {
+                        POSITIVE_COLOR
+                    };
+                    let alpha = if scale > 0.0 {
+                        (value.abs() / scale) as f32
+                    } else {
+                        0.0
+                    };
+                    ui.label(
+                        RichText::new(format!("{value:>10.3e}"))
+                            .monospace()
+                            .background_color(color.gamma_multiply(alpha)),
+                    )
+                    .on_hover_text(format!("[{row}, {column}] = {value}"));
+                }
+                ui.end_row();
+            }
+        });
+}
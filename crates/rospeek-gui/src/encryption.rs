@@ -0,0 +1,303 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use age::stream::StreamWriter;
+use anyhow::{Context, anyhow, bail};
+use rospeek_core::{
+    BagReader, BagStats, EmbeddedSchema, IncompleteSign, OpenOptions, RawMessage, RosPeekResult,
+    StorageType, TaggedMessage, Topic, TypeVersion,
+};
+use rospeek_db3::Db3Reader;
+use rospeek_mcap::McapReader;
+use tempfile::TempDir;
+
+use crate::backend::UnsupportedFormat;
+
+/// Extension appended to the name of files encrypted with age.
+pub const ENCRYPTED_EXTENSION: &str = "age";
+
+/// Environment variable with the path to the age identity file used to decrypt bags.
+pub const IDENTITY_ENV: &str = "ROSPEEK_AGE_IDENTITY";
+
+/// Returns the path of the encrypted copy of a file, such as `out.db3.age` for `out.db3`.
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut output = path.as_os_str().to_owned();
+    output.push(".");
+    output.push(ENCRYPTED_EXTENSION);
+    PathBuf::from(output)
+}
+
+/// Wraps a writer into one encrypting what is written to it for age recipients.
+///
+/// The returned writer must be finished with [`StreamWriter::finish`] to write the last chunk.
+///
+/// # Arguments
+/// * `output` - The writer receiving the encrypted data.
+/// * `recipients` - age public keys, such as `age1...`, which can decrypt the data.
+///
+/// # Examples
+/// ```
+/// use std::io::Write;
+///
+/// use rospeek_gui::encryption::{decrypt_to_vec, encrypt_writer};
+///
+/// let identity = age::x25519::Identity::generate();
+/// let recipient = identity.to_public().to_string();
+///
+/// let mut encrypted = Vec::new();
+/// let mut writer = encrypt_writer(&mut encrypted, &[recipient]).unwrap();
+/// writer.write_all(b"secret bag").unwrap();
+/// writer.finish().unwrap();
+/// assert!(!encrypted.windows(10).any(|w| w == b"secret bag"));
+///
+/// let identities: Vec<Box<dyn age::Identity>> = vec![Box::new(identity)];
+/// assert_eq!(decrypt_to_vec(&encrypted[..], &identities).unwrap(), b"secret bag");
+/// ```
+pub fn encrypt_writer<W: Write>(
+    output: W,
+    recipients: &[String],
+) -> RosPeekResult<StreamWriter<W>> {
+    if recipients.is_empty() {
+        bail!("At least one --recipient is required to encrypt");
+    }
+    let recipients = recipients
+        .iter()
+        .map(|r| {
+            r.parse::<age::x25519::Recipient>()
+                .map_err(|e| anyhow!("Invalid recipient {r}: {e}"))
+        })
+        .collect::<RosPeekResult<Vec<_>>>()?;
+    let encryptor =
+        age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))?;
+    Ok(encryptor.wrap_output(output)?)
+}
+
+/// Decrypts data encrypted with age into memory.
+///
+/// # Arguments
+/// * `input` - The encrypted data.
+/// * `identities` - age identities, one of which the data was encrypted for.
+pub fn decrypt_to_vec<R: Read>(
+    input: R,
+    identities: &[Box<dyn age::Identity>],
+) -> RosPeekResult<Vec<u8>> {
+    let decryptor = age::Decryptor::new(BufReader::new(input))?;
+    let mut reader =
+        decryptor.decrypt(identities.iter().map(|i| i.as_ref() as &dyn age::Identity))?;
+    let mut plain = Vec::new();
+    reader.read_to_end(&mut plain)?;
+    Ok(plain)
+}
+
+/// Output file, encrypted on the fly for age recipients if any, so that no plaintext copy of
+/// an encrypted output ever reaches the disk.
+pub enum OutputFile {
+    Plain(BufWriter<File>),
+    Encrypted(StreamWriter<BufWriter<File>>),
+}
+
+impl OutputFile {
+    /// Creates a file.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the file, including [`ENCRYPTED_EXTENSION`] when encrypting.
+    /// * `recipients` - age public keys to encrypt the file for, `None` to write it as is.
+    pub fn create(path: &Path, recipients: Option<&[String]>) -> RosPeekResult<Self> {
+        let file = BufWriter::new(
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+        Ok(match recipients {
+            None => OutputFile::Plain(file),
+            Some(recipients) => OutputFile::Encrypted(encrypt_writer(file, recipients)?),
+        })
+    }
+
+    /// Writes the last chunk of an encrypted file and flushes the file.
+    pub fn finish(self) -> RosPeekResult<()> {
+        let mut file = match self {
+            OutputFile::Plain(file) => file,
+            OutputFile::Encrypted(writer) => writer.finish()?,
+        };
+        file.flush()?;
+        Ok(())
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputFile::Plain(file) => file.write(buf),
+            OutputFile::Encrypted(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputFile::Plain(file) => file.flush(),
+            OutputFile::Encrypted(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Creates a directory only the current user can access, under a random name, which is removed
+/// with its contents once dropped.
+///
+/// Bags which have to be written as plain files before being encrypted, or read as plain files
+/// once decrypted, such as `.db3` bags whose database needs a file, are kept there.
+///
+/// # Arguments
+/// * `parent` - The directory to create it in, the temporary directory if `None`.
+pub fn private_dir(parent: Option<&Path>) -> RosPeekResult<TempDir> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".rospeek-");
+    let dir = match parent {
+        Some(parent) => builder.tempdir_in(parent)?,
+        None => builder.tempdir()?,
+    };
+    Ok(dir)
+}
+
+/// Encrypts a file for age recipients, streaming it into the encrypted output.
+///
+/// # Arguments
+/// * `input` - The path to the file to encrypt, left as is.
+/// * `output` - The path to the encrypted file, such as [`encrypted_path`] of the input.
+/// * `recipients` - age public keys, such as `age1...`, which can decrypt the file.
+pub fn encrypt_file(input: &Path, output: &Path, recipients: &[String]) -> RosPeekResult<()> {
+    let mut input = BufReader::new(File::open(input)?);
+    let mut writer = OutputFile::create(output, Some(recipients))?;
+    io::copy(&mut input, &mut writer)?;
+    writer.finish()
+}
+
+/// Returns whether a file is encrypted with age, judging from its extension.
+pub fn is_encrypted<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().extension().and_then(|ext| ext.to_str()) == Some(ENCRYPTED_EXTENSION)
+}
+
+/// Opens an encrypted bag by decrypting it into memory.
+///
+/// MCAP bags are read from memory. `.db3` bags are written into a [`private_dir`] for SQLite to
+/// open, which is removed once the reader is dropped. The identity is read from the file named
+/// by [`IDENTITY_ENV`].
+pub(crate) fn open_encrypted(
+    path: &Path,
+    options: &OpenOptions,
+) -> RosPeekResult<Box<dyn BagReader>> {
+    let Ok(identity) = std::env::var(IDENTITY_ENV) else {
+        bail!(
+            "{} is encrypted, set {IDENTITY_ENV} to the path of an age identity file to open it",
+            path.display()
+        );
+    };
+    let identities = age::IdentityFile::from_file(identity.clone())
+        .map_err(|e| anyhow!("Failed to read identity {identity}: {e}"))?
+        .into_identities()?;
+    let plain = decrypt_to_vec(File::open(path)?, &identities)
+        .with_context(|| format!("Failed to decrypt {}", path.display()))?;
+
+    match options
+        .storage
+        .or_else(|| StorageType::detect_bytes(&plain))
+    {
+        Some(StorageType::Mcap) => Ok(Box::new(McapReader::from_bytes(plain, path, options)?)),
+        Some(StorageType::Sqlite3) => {
+            let dir = private_dir(None)?;
+            // the decrypted copy keeps the inner name, such as `foo.db3`
+            let name = path.file_stem().unwrap_or(path.as_os_str());
+            let copy = dir.path().join(name);
+            std::fs::write(&copy, plain)?;
+            let inner = Box::new(Db3Reader::open_with_options(&copy, options)?);
+            Ok(Box::new(DecryptedReader { inner, _plain: dir }))
+        }
+        None => Err(UnsupportedFormat::new(path).into()),
+    }
+}
+
+/// Reader of a decrypted copy of a bag, removing the copy once dropped.
+struct DecryptedReader {
+    inner: Box<dyn BagReader>,
+    _plain: TempDir,
+}
+
+impl BagReader for DecryptedReader {
+    fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
+    where
+        Self: Sized,
+    {
+        bail!(
+            "Encrypted bags are opened with create_reader: {}",
+            path.as_ref().display()
+        )
+    }
+
+    fn stats(&self) -> RosPeekResult<&BagStats> {
+        self.inner.stats()
+    }
+
+    fn topics(&self) -> RosPeekResult<Vec<Topic>> {
+        self.inner.topics()
+    }
+
+    fn topic_sizes(&self) -> RosPeekResult<Option<BTreeMap<String, u64>>> {
+        self.inner.topic_sizes()
+    }
+
+    fn incomplete_signs(&self) -> Vec<IncompleteSign> {
+        self.inner.incomplete_signs()
+    }
+
+    fn read_messages(&self, topic_name: &str) -> RosPeekResult<Vec<RawMessage>> {
+        self.inner.read_messages(topic_name)
+    }
+
+    fn read_messages_range(
+        &self,
+        topic_name: &str,
+        start_ns: Option<u64>,
+        end_ns: Option<u64>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> RosPeekResult<Vec<RawMessage>> {
+        self.inner
+            .read_messages_range(topic_name, start_ns, end_ns, limit, offset)
+    }
+
+    fn read_messages_by_index(
+        &self,
+        topic_name: &str,
+        start_index: usize,
+        count: usize,
+    ) -> RosPeekResult<Vec<RawMessage>> {
+        self.inner
+            .read_messages_by_index(topic_name, start_index, count)
+    }
+
+    fn read_messages_multi(&self, topic_names: &[&str]) -> RosPeekResult<Vec<TaggedMessage>> {
+        self.inner.read_messages_multi(topic_names)
+    }
+
+    fn next_message(&self, topic_name: &str, after_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        self.inner.next_message(topic_name, after_ns)
+    }
+
+    fn prev_message(&self, topic_name: &str, before_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        self.inner.prev_message(topic_name, before_ns)
+    }
+
+    fn find_nearest(&self, topic_name: &str, timestamp: u64) -> RosPeekResult<Option<RawMessage>> {
+        self.inner.find_nearest(topic_name, timestamp)
+    }
+
+    fn embedded_schema(&self, type_name: &str) -> RosPeekResult<Option<EmbeddedSchema>> {
+        self.inner.embedded_schema(type_name)
+    }
+
+    fn type_versions(&self, topic: &Topic) -> RosPeekResult<Vec<TypeVersion>> {
+        self.inner.type_versions(topic)
+    }
+}
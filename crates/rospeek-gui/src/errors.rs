@@ -0,0 +1,145 @@
+use rospeek_core::IdlNotFound;
+use rusqlite::ErrorCode;
+
+use crate::{backend::UnsupportedFormat, i18n::Language};
+
+/// Common failure the GUI knows how to fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The IDL file of a message type is not found, so its messages cannot be decoded.
+    IdlNotFound,
+    /// The bag is neither a `.db3` nor an `.mcap` file.
+    UnsupportedFormat,
+    /// The `.db3` file is locked by another process.
+    DatabaseLocked,
+    /// Any other failure, shown as is.
+    Other,
+}
+
+impl ErrorKind {
+    /// Returns the kind of the first error in the chain of `error` that is a known failure.
+    pub fn of(error: &anyhow::Error) -> Self {
+        error
+            .chain()
+            .find_map(|cause| {
+                if cause.is::<IdlNotFound>() {
+                    Some(ErrorKind::IdlNotFound)
+                } else if cause.is::<UnsupportedFormat>() {
+                    Some(ErrorKind::UnsupportedFormat)
+                } else {
+                    cause
+                        .downcast_ref::<rusqlite::Error>()
+                        .and_then(rusqlite::Error::sqlite_error_code)
+                        .filter(|code| {
+                            matches!(code, ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+                        })
+                        .map(|_| ErrorKind::DatabaseLocked)
+                }
+            })
+            .unwrap_or(ErrorKind::Other)
+    }
+
+    /// Returns the title of the dialog in English.
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorKind::IdlNotFound => "Message definition not found",
+            ErrorKind::UnsupportedFormat => "Unsupported bag format",
+            ErrorKind::DatabaseLocked => "Bag is in use",
+            ErrorKind::Other => "Error",
+        }
+    }
+
+    /// Returns what to do about the failure in English, if known.
+    pub fn suggestion(self) -> Option<&'static str> {
+        match self {
+            ErrorKind::IdlNotFound => Some(
+                "Source your ROS 2 environment, such as /opt/ros/<distro>/setup.bash, and the \
+                 workspace of the message package before starting rospeek, so that \
+                 AMENT_PREFIX_PATH includes the package.",
+            ),
+            ErrorKind::UnsupportedFormat => Some(
+                "Open a ROS 2 bag stored as .db3 or .mcap, or the bag directory holding them. \
+                 ROS 1 .bag files need to be converted first, such as with rosbags-convert.",
+            ),
+            ErrorKind::DatabaseLocked => Some(
+                "Another process, such as ros2 bag record, is writing this .db3 file. Wait \
+                 until it finishes, or open a copy of the file.",
+            ),
+            ErrorKind::Other => None,
+        }
+    }
+}
+
+/// Failure shown in a dialog until it is closed, with a suggested fix for common failures.
+///
+/// # Examples
+/// ```
+/// use rospeek_core::IdlNotFound;
+/// use rospeek_gui::errors::{ErrorDialog, ErrorKind};
+///
+/// let error = anyhow::Error::new(IdlNotFound {
+///     type_name: "foo_msgs/msg/Foo".to_string(),
+/// })
+/// .context("Failed to decode /foo");
+/// let dialog = ErrorDialog::new(&error);
+/// assert_eq!(dialog.kind, ErrorKind::IdlNotFound);
+/// assert_eq!(
+///     dialog.detail,
+///     "Failed to decode /foo: IDL file not found for foo_msgs/msg/Foo"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorDialog {
+    pub kind: ErrorKind,
+    /// The error with its causes, as reported.
+    pub detail: String,
+}
+
+impl ErrorDialog {
+    pub fn new(error: &anyhow::Error) -> Self {
+        Self {
+            kind: ErrorKind::of(error),
+            detail: format!("{error:#}"),
+        }
+    }
+
+    /// Shows the dialog.
+    ///
+    /// # Returns
+    /// `false` once the dialog is closed.
+    pub fn show(&self, ctx: &egui::Context, lang: Language) -> bool {
+        let mut open = true;
+        let mut closed = false;
+        egui::Window::new(lang.tr(self.kind.title()))
+            .id(egui::Id::new("error_dialog"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.set_max_width(420.0);
+                match self.kind.suggestion() {
+                    Some(suggestion) => {
+                        ui.label(lang.tr(suggestion));
+                        ui.add_space(4.0);
+                        ui.collapsing(lang.tr("Details"), |ui| {
+                            ui.monospace(&self.detail);
+                        });
+                    }
+                    None => {
+                        ui.label(&self.detail);
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(lang.tr("Copy details")).clicked() {
+                        ui.ctx().copy_text(self.detail.clone());
+                    }
+                    if ui.button(lang.tr("Close")).clicked() {
+                        closed = true;
+                    }
+                });
+            });
+        open && !closed
+    }
+}
@@ -0,0 +1,113 @@
+use std::{collections::BTreeSet, path::PathBuf};
+
+use rfd::FileDialog;
+use rospeek_core::{ExportOptions, Topic, ns_to_iso};
+
+/// Dialog exporting the selected time range of checked topics into a new bag.
+#[derive(Debug, Default)]
+pub struct ExportDialog {
+    checked: BTreeSet<String>,
+    dedup: bool,
+    sort_by_time: bool,
+    status: Option<String>,
+}
+
+impl ExportDialog {
+    /// Creates a dialog with all topics checked.
+    pub fn new(topics: &[Topic]) -> Self {
+        Self {
+            checked: topics.iter().map(|t| t.name.clone()).collect(),
+            dedup: false,
+            sort_by_time: false,
+            status: None,
+        }
+    }
+
+    /// Sets the message shown at the bottom of the dialog.
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = Some(status.into());
+    }
+
+    /// Shows the dialog.
+    ///
+    /// # Arguments
+    /// * `ctx` - The egui context.
+    /// * `topics` - All topics of the opened bag.
+    /// * `range` - The selected time range in nanoseconds.
+    /// * `open` - Whether the dialog stays open.
+    ///
+    /// # Returns
+    /// The output path and export options once the user confirmed the export.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        topics: &[Topic],
+        range: (u64, u64),
+        open: &mut bool,
+    ) -> Option<(PathBuf, ExportOptions)> {
+        let mut request = None;
+        egui::Window::new("Export selection as new bag")
+            .open(open)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Range: {} → {}",
+                    ns_to_iso(range.0),
+                    ns_to_iso(range.1)
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("All").clicked() {
+                        self.checked = topics.iter().map(|t| t.name.clone()).collect();
+                    }
+                    if ui.button("None").clicked() {
+                        self.checked.clear();
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        for topic in topics {
+                            let mut checked = self.checked.contains(&topic.name);
+                            if ui.checkbox(&mut checked, topic.name.as_str()).changed() {
+                                if checked {
+                                    self.checked.insert(topic.name.clone());
+                                } else {
+                                    self.checked.remove(&topic.name);
+                                }
+                            }
+                        }
+                    });
+                ui.separator();
+                ui.checkbox(&mut self.dedup, "Drop duplicate messages");
+                ui.checkbox(&mut self.sort_by_time, "Sort messages by time");
+
+                let enabled = !self.checked.is_empty();
+                if ui
+                    .add_enabled(enabled, egui::Button::new("Export…"))
+                    .clicked()
+                    && let Some(path) = FileDialog::new()
+                        .add_filter("ROS 2 bag", &["mcap", "db3"])
+                        .set_file_name("export.mcap")
+                        .save_file()
+                {
+                    self.status = Some(format!("Exporting to {}…", path.display()));
+                    request = Some((
+                        path,
+                        ExportOptions {
+                            topics: self.checked.iter().cloned().collect(),
+                            since: Some(range.0),
+                            until: Some(range.1),
+                            dedup: self.dedup,
+                            sort_by_time: self.sort_by_time,
+                            ..Default::default()
+                        },
+                    ));
+                }
+                if let Some(status) = &self.status {
+                    ui.label(status);
+                }
+            });
+        request
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use egui::{FontData, FontDefinitions, FontFamily, FontId};
+
+/// Environment variable pointing to a font file used as a fallback, such as a `.ttf` or `.ttc`.
+pub const FONT_ENV: &str = "ROSPEEK_FONT";
+
+/// Name of the fallback font in the font definitions.
+const FALLBACK_FONT: &str = "rospeek-fallback";
+
+/// CJK-capable fonts shipped with common Linux distributions, macOS and Windows, in order of
+/// preference.
+const SYSTEM_FONTS: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJKjp-Regular.otf",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/truetype/takao-gothic/TakaoPGothic.ttf",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "C:\\Windows\\Fonts\\YuGothM.ttc",
+    "C:\\Windows\\Fonts\\meiryo.ttc",
+    "C:\\Windows\\Fonts\\msgothic.ttc",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+];
+
+/// Returns the path of the fallback font, given by [`FONT_ENV`] or found among system fonts.
+pub fn find_fallback_font() -> Option<PathBuf> {
+    std::env::var_os(FONT_ENV)
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+        .or_else(|| {
+            SYSTEM_FONTS
+                .iter()
+                .map(Path::new)
+                .find(|path| path.is_file())
+                .map(Path::to_path_buf)
+        })
+}
+
+/// Installs a CJK-capable font as the fallback of the default fonts, so that Japanese and
+/// Chinese characters do not render as tofu.
+///
+/// # Arguments
+/// * `ctx` - The egui context to install the font into.
+///
+/// # Returns
+/// The path of the installed font, or `None` if no font was found.
+pub(crate) fn install_fallback_font(ctx: &egui::Context) -> Option<PathBuf> {
+    let path = find_fallback_font()?;
+    let data = match std::fs::read(&path) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Failed to read font {}: {e}", path.display());
+            return None;
+        }
+    };
+
+    let mut fonts = FontDefinitions::default();
+    fonts
+        .font_data
+        .insert(FALLBACK_FONT.to_string(), FontData::from_owned(data).into());
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .push(FALLBACK_FONT.to_string());
+    }
+    ctx.set_fonts(fonts);
+    Some(path)
+}
+
+/// Replaces characters the fonts cannot render, as well as control characters other than line
+/// breaks and tabs, with their escaped code points, so that they are readable instead of tofu.
+///
+/// # Arguments
+/// * `ctx` - The egui context holding the fonts.
+/// * `font_id` - Font the text is rendered with.
+/// * `text` - Text to render.
+pub(crate) fn printable(ctx: &egui::Context, font_id: &FontId, text: &str) -> String {
+    if text
+        .chars()
+        .all(|c| c.is_ascii_graphic() || c == ' ' || c == '\n')
+    {
+        return text.to_string();
+    }
+    ctx.fonts(|fonts| {
+        text.chars()
+            .map(|c| match c {
+                '\n' | '\t' => c.to_string(),
+                c if c.is_control() || !fonts.has_glyph(font_id, c) => {
+                    c.escape_unicode().to_string()
+                }
+                c => c.to_string(),
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,78 @@
+use egui::{Align2, Color32, FontId, Rect, Sense, pos2, vec2};
+use rospeek_core::SizeHistogram;
+
+const CHART_HEIGHT: f32 = 120.0;
+const LABEL_HEIGHT: f32 = 14.0;
+
+/// Draws the message size distribution of a topic as a bar chart, a bar per power of two.
+///
+/// Hovering a bar shows its size range and number of messages.
+pub fn size_chart(ui: &mut egui::Ui, histogram: &SizeHistogram) {
+    let buckets = histogram.buckets();
+    if buckets.is_empty() {
+        ui.label("No messages.");
+        return;
+    }
+
+    let width = ui.available_width().max(100.0);
+    let (rect, response) =
+        ui.allocate_exact_size(vec2(width, CHART_HEIGHT + LABEL_HEIGHT), Sense::hover());
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(rect);
+    let plot = Rect::from_min_size(rect.min, vec2(rect.width(), CHART_HEIGHT));
+    painter.rect_filled(plot, 2.0, visuals.extreme_bg_color);
+
+    let max = buckets.iter().map(|b| b.count).max().unwrap_or(1) as f32;
+    let bar_width = plot.width() / buckets.len() as f32;
+    let hovered = response
+        .hover_pos()
+        .map(|pos| ((pos.x - plot.left()) / bar_width) as usize);
+    for (i, bucket) in buckets.iter().enumerate() {
+        let left = plot.left() + i as f32 * bar_width;
+        let height = bucket.count as f32 / max * (CHART_HEIGHT - 4.0);
+        let bar = Rect::from_min_max(
+            pos2(left + 1.0, plot.bottom() - height),
+            pos2(left + bar_width - 1.0, plot.bottom()),
+        );
+        let color = if hovered == Some(i) {
+            visuals.selection.bg_fill
+        } else {
+            Color32::from_rgb(76, 120, 168)
+        };
+        painter.rect_filled(bar, 0.0, color);
+        if i == 0 || i + 1 == buckets.len() {
+            painter.text(
+                pos2(left + bar_width / 2.0, plot.bottom() + 1.0),
+                Align2::CENTER_TOP,
+                format_size(bucket.min_size),
+                FontId::monospace(10.0),
+                visuals.weak_text_color(),
+            );
+        }
+    }
+
+    if let Some(bucket) = hovered.and_then(|i| buckets.get(i)) {
+        response.on_hover_text(format!(
+            "{} - {}: {} messages",
+            format_size(bucket.min_size),
+            format_size(bucket.max_size),
+            bucket.count
+        ));
+    }
+}
+
+/// Formats a size in bytes with a binary unit.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
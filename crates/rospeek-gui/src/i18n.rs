@@ -0,0 +1,208 @@
+/// Language of the labels of the GUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Japanese,
+}
+
+/// Japanese translations of the labels, keyed by their English text.
+const JAPANESE: &[(&str, &str)] = &[
+    ("Open bag...", "bag を開く..."),
+    ("Connect", "接続"),
+    (
+        "Export selection as new bag…",
+        "選択範囲を新しい bag に書き出す…",
+    ),
+    ("Attachments", "添付ファイル"),
+    ("Language", "言語"),
+    ("Topic Filter", "トピックの絞り込み"),
+    ("Message Inspector", "メッセージインスペクタ"),
+    ("View Mode", "表示モード"),
+    ("Show full", "すべて表示"),
+    (
+        "Show images and point clouds in full instead of a summary",
+        "画像と点群を要約せずにすべて表示します",
+    ),
+    ("Columns", "列"),
+    ("Remove column", "列を削除"),
+    (
+        "This bag has no messages.",
+        "この bag にはメッセージがありません。",
+    ),
+    (
+        "Select a topic on the left.",
+        "左のトピックを選択してください。",
+    ),
+    ("Page cache cap (MiB)", "ページキャッシュの上限 (MiB)"),
+    ("Previous message (,)", "前のメッセージ (,)"),
+    ("Next message (.)", "次のメッセージ (.)"),
+    ("Timeline", "タイムライン"),
+    ("Sim time", "シミュレーション時刻"),
+    (
+        "Show times in simulated time published on /clock",
+        "/clock のシミュレーション時刻で表示します",
+    ),
+    ("Set region start", "区間の開始を設定"),
+    ("Set region end", "区間の終了を設定"),
+    ("Clear region", "区間を解除"),
+    ("Loop", "ループ"),
+    ("Export view as PNG", "表示を PNG で保存"),
+    ("Zoom", "拡大率"),
+    ("Zoom out (Ctrl+-)", "縮小 (Ctrl+-)"),
+    ("Reset zoom (Ctrl+0)", "拡大率をリセット (Ctrl+0)"),
+    ("Zoom in (Ctrl+=)", "拡大 (Ctrl+=)"),
+    ("Message sizes", "メッセージサイズの分布"),
+    (
+        "Published throughout the recording",
+        "記録の最後まで配信されています",
+    ),
+    ("No messages", "メッセージがありません"),
+    (
+        "Stops publishing before the end of the bag",
+        "bag の終わりより前に配信が止まっています",
+    ),
+    (
+        "Pauses much longer than its usual interval",
+        "通常の周期よりずっと長く途切れています",
+    ),
+    ("Recent bags", "最近開いた bag"),
+    (
+        "No bags opened yet. Use \"Open bag...\" above.",
+        "まだ bag を開いていません。上の「bag を開く...」を使ってください。",
+    ),
+    ("Catalog", "カタログ"),
+    ("Path", "パス"),
+    ("Start", "開始"),
+    ("Duration (s)", "長さ (秒)"),
+    ("Messages", "メッセージ数"),
+    ("Size (GiB)", "サイズ (GiB)"),
+    ("Thumbnails", "サムネイル"),
+    ("None", "なし"),
+    ("Load events…", "イベントを読み込む…"),
+    (
+        "Show events extracted by `rospeek events` on the timeline",
+        "`rospeek events` で抽出したイベントをタイムラインに表示します",
+    ),
+    (
+        "Message definition not found",
+        "メッセージ定義が見つかりません",
+    ),
+    ("Unsupported bag format", "対応していない bag の形式です"),
+    ("Bag is in use", "bag が使用中です"),
+    ("Error", "エラー"),
+    (
+        "Source your ROS 2 environment, such as /opt/ros/<distro>/setup.bash, and the \
+         workspace of the message package before starting rospeek, so that \
+         AMENT_PREFIX_PATH includes the package.",
+        "rospeek を起動する前に /opt/ros/<distro>/setup.bash などの ROS 2 環境と\
+         メッセージのパッケージのワークスペースを source して、\
+         AMENT_PREFIX_PATH にパッケージが含まれるようにしてください。",
+    ),
+    (
+        "Open a ROS 2 bag stored as .db3 or .mcap, or the bag directory holding them. \
+         ROS 1 .bag files need to be converted first, such as with rosbags-convert.",
+        ".db3 または .mcap で保存された ROS 2 の bag か、それを含む bag ディレクトリを\
+         開いてください。ROS 1 の .bag ファイルは rosbags-convert などで先に変換が必要です。",
+    ),
+    (
+        "Another process, such as ros2 bag record, is writing this .db3 file. Wait \
+         until it finishes, or open a copy of the file.",
+        "ros2 bag record などの別のプロセスがこの .db3 ファイルに書き込んでいます。\
+         終わるまで待つか、ファイルのコピーを開いてください。",
+    ),
+    ("Details", "詳細"),
+    ("Copy details", "詳細をコピー"),
+    ("Incomplete recording", "不完全な記録"),
+    (
+        "The bag may lack its last messages and report a shorter duration:",
+        "bag の最後のメッセージが欠け、長さが短く表示されている可能性があります:",
+    ),
+    ("Close", "閉じる"),
+    ("Watch", "ウォッチ"),
+    (
+        "Show fields updated live during playback",
+        "再生中にフィールドの値を随時表示します",
+    ),
+    ("Add watch", "ウォッチを追加"),
+    ("Remove watch", "ウォッチを削除"),
+    ("Reset min/max", "最小/最大をリセット"),
+    ("Expression", "式"),
+    ("Value", "値"),
+    ("Min / Max", "最小 / 最大"),
+    ("Plot", "プロット"),
+    ("Time series", "時系列"),
+    ("Histogram", "ヒストグラム"),
+    ("Bins: ", "ビン数: "),
+    ("Spectrum", "スペクトル"),
+    ("Window", "窓関数"),
+    (
+        "Too few samples for a spectrum.",
+        "スペクトルを求めるにはサンプルが少なすぎます。",
+    ),
+    ("Export series as CSV", "系列を CSV に書き出す"),
+    ("Pause when", "一時停止の条件"),
+    ("Arm", "有効化"),
+    ("Disarm trigger", "条件を解除"),
+    (
+        "Pause playback at the first message satisfying the condition",
+        "条件を満たす最初のメッセージで再生を一時停止します",
+    ),
+    ("Unknown covariance", "共分散は未知です"),
+    ("Not positive-definite", "正定値ではありません"),
+    ("Euler angles", "オイラー角"),
+    (
+        "Show the roll, pitch and yaw of orientations and rotations in degrees",
+        "姿勢と回転のロール・ピッチ・ヨーを度単位で表示します",
+    ),
+    ("Quaternion", "クォータニオン"),
+    ("Roll", "ロール"),
+    ("Pitch", "ピッチ"),
+    ("Yaw", "ヨー"),
+];
+
+impl Language {
+    /// Every supported language, in the order they are listed in the settings.
+    pub const ALL: [Language; 2] = [Language::English, Language::Japanese];
+
+    /// Returns the language of the locale given by `LC_ALL`, `LC_MESSAGES` or `LANG`.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+            .filter(|locale| locale.starts_with("ja"))
+            .map_or(Language::English, |_| Language::Japanese)
+    }
+
+    /// Returns the name of the language in itself.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Japanese => "日本語",
+        }
+    }
+
+    /// Translates a label, falling back to English if it has no translation.
+    ///
+    /// # Arguments
+    /// * `text` - The English label.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_gui::i18n::Language;
+    ///
+    /// assert_eq!(Language::Japanese.tr("Loop"), "ループ");
+    /// assert_eq!(Language::English.tr("Loop"), "Loop");
+    /// assert_eq!(Language::Japanese.tr("Untranslated"), "Untranslated");
+    /// ```
+    pub fn tr(self, text: &'static str) -> &'static str {
+        let table = match self {
+            Language::English => return text,
+            Language::Japanese => JAPANESE,
+        };
+        table
+            .iter()
+            .find(|(english, _)| *english == text)
+            .map_or(text, |(_, translated)| translated)
+    }
+}
@@ -0,0 +1,198 @@
+use anyhow::anyhow;
+use image::{
+    DynamicImage, GrayImage, ImageBuffer, Luma, Pixel, Primitive, RgbImage, RgbaImage, imageops,
+};
+use rospeek_core::{CdrReader, RosPeekResult, UndistortMap};
+
+pub const IMAGE_TYPE: &str = "sensor_msgs/msg/Image";
+pub const COMPRESSED_IMAGE_TYPE: &str = "sensor_msgs/msg/CompressedImage";
+
+/// Returns whether messages of a type are images.
+pub fn is_image_type(type_name: &str) -> bool {
+    type_name == IMAGE_TYPE || type_name == COMPRESSED_IMAGE_TYPE
+}
+
+/// An image message read straight from the CDR data without decoding the pixels into JSON.
+pub enum ImageMessage<'a> {
+    Raw(RawImage<'a>),
+    Compressed {
+        /// Format of the data, such as `jpeg` or `png`.
+        format: &'a str,
+        data: &'a [u8],
+    },
+}
+
+impl<'a> ImageMessage<'a> {
+    /// Reads an image message.
+    ///
+    /// # Arguments
+    /// * `type_name` - `sensor_msgs/msg/Image` or `sensor_msgs/msg/CompressedImage`.
+    /// * `data` - CDR-encoded message.
+    pub fn from_cdr(type_name: &str, data: &'a [u8]) -> RosPeekResult<Self> {
+        let mut reader = CdrReader::new(data);
+        // std_msgs/msg/Header
+        reader.read_i32()?;
+        reader.read_u32()?;
+        reader.read_str()?;
+        match type_name {
+            IMAGE_TYPE => {
+                let height = reader.read_u32()? as usize;
+                let width = reader.read_u32()? as usize;
+                let encoding = reader.read_str()?;
+                let big_endian = reader.read_u8()? != 0;
+                let step = reader.read_u32()? as usize;
+                let len = reader.read_u32()? as usize;
+                let pixels = reader.take_bytes(len)?;
+                Ok(ImageMessage::Raw(RawImage {
+                    width,
+                    height,
+                    step,
+                    encoding,
+                    big_endian,
+                    pixels,
+                }))
+            }
+            COMPRESSED_IMAGE_TYPE => {
+                let format = reader.read_str()?;
+                let len = reader.read_u32()? as usize;
+                let data = reader.take_bytes(len)?;
+                Ok(ImageMessage::Compressed { format, data })
+            }
+            _ => anyhow::bail!("{type_name} is not an image type"),
+        }
+    }
+
+    /// Decodes the pixels of the image.
+    pub fn decode(&self) -> RosPeekResult<DynamicImage> {
+        match self {
+            ImageMessage::Raw(image) => image.to_image(),
+            ImageMessage::Compressed { data, .. } => Ok(image::load_from_memory(data)?),
+        }
+    }
+}
+
+/// Pixels of a `sensor_msgs/msg/Image`.
+pub struct RawImage<'a> {
+    pub width: usize,
+    pub height: usize,
+    /// Length of a row in bytes.
+    pub step: usize,
+    pub encoding: &'a str,
+    pub big_endian: bool,
+    pub pixels: &'a [u8],
+}
+
+impl RawImage<'_> {
+    /// Returns the size of a pixel in bytes, checking that the pixels fit in the data.
+    pub fn bytes_per_pixel(&self) -> RosPeekResult<usize> {
+        if self.width == 0 || self.height == 0 {
+            anyhow::bail!("Image is empty");
+        }
+        let bytes_per_pixel = match self.encoding {
+            "mono8" | "8UC1" => 1,
+            "mono16" | "16UC1" => 2,
+            "rgb8" | "bgr8" => 3,
+            "rgba8" | "bgra8" => 4,
+            encoding => anyhow::bail!("Unsupported image encoding: {encoding}"),
+        };
+        if self.step * (self.height - 1) + self.width * bytes_per_pixel > self.pixels.len() {
+            anyhow::bail!("Image data is shorter than {}x{}", self.width, self.height);
+        }
+        Ok(bytes_per_pixel)
+    }
+
+    /// Returns the color of a pixel as RGBA, 16-bit values being cut to their most significant
+    /// byte.
+    ///
+    /// # Arguments
+    /// * `x` - Column of the pixel.
+    /// * `y` - Row of the pixel.
+    /// * `bytes_per_pixel` - Size of a pixel as returned by [`RawImage::bytes_per_pixel`].
+    pub fn rgba(&self, x: usize, y: usize, bytes_per_pixel: usize) -> [u8; 4] {
+        let offset = y * self.step + x * bytes_per_pixel;
+        let p = &self.pixels[offset..offset + bytes_per_pixel];
+        match self.encoding {
+            "mono8" | "8UC1" => [p[0], p[0], p[0], 255],
+            "mono16" | "16UC1" => {
+                let v = if self.big_endian { p[0] } else { p[1] };
+                [v, v, v, 255]
+            }
+            "rgb8" => [p[0], p[1], p[2], 255],
+            "bgr8" => [p[2], p[1], p[0], 255],
+            "rgba8" => [p[0], p[1], p[2], p[3]],
+            _ => [p[2], p[1], p[0], p[3]],
+        }
+    }
+
+    /// Converts the pixels into an image, keeping the full depth of 16-bit images.
+    pub fn to_image(&self) -> RosPeekResult<DynamicImage> {
+        let bytes_per_pixel = self.bytes_per_pixel()?;
+        let row_len = self.width * bytes_per_pixel;
+        let mut packed = Vec::with_capacity(row_len * self.height);
+        for y in 0..self.height {
+            packed.extend_from_slice(&self.pixels[y * self.step..y * self.step + row_len]);
+        }
+        if self.encoding.starts_with("bgr") {
+            for pixel in packed.chunks_exact_mut(bytes_per_pixel) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        let (width, height) = (self.width as u32, self.height as u32);
+        let image = match self.encoding {
+            "mono8" | "8UC1" => GrayImage::from_raw(width, height, packed).map(DynamicImage::from),
+            "mono16" | "16UC1" => {
+                let values = packed
+                    .chunks_exact(2)
+                    .map(|p| match self.big_endian {
+                        true => u16::from_be_bytes([p[0], p[1]]),
+                        false => u16::from_le_bytes([p[0], p[1]]),
+                    })
+                    .collect();
+                ImageBuffer::<Luma<u16>, Vec<u16>>::from_raw(width, height, values)
+                    .map(DynamicImage::from)
+            }
+            "rgb8" | "bgr8" => RgbImage::from_raw(width, height, packed).map(DynamicImage::from),
+            _ => RgbaImage::from_raw(width, height, packed).map(DynamicImage::from),
+        };
+        image.ok_or_else(|| anyhow!("Image data does not match {}x{}", self.width, self.height))
+    }
+}
+
+/// Undistorts an image by sampling it bilinearly at the positions of a map, leaving black the
+/// pixels that fall outside of it.
+///
+/// # Arguments
+/// * `image` - The distorted image.
+/// * `map` - Map computed by [`rospeek_core::CameraInfo::undistort_map`] for the size of the image.
+pub fn undistort(image: &DynamicImage, map: &UndistortMap) -> RosPeekResult<DynamicImage> {
+    if (image.width(), image.height()) != (map.width, map.height) {
+        anyhow::bail!(
+            "Image of {}x{} does not match the camera info of {}x{}",
+            image.width(),
+            image.height(),
+            map.width,
+            map.height
+        );
+    }
+    Ok(match image {
+        DynamicImage::ImageLuma8(image) => remap(image, map).into(),
+        DynamicImage::ImageLuma16(image) => remap(image, map).into(),
+        DynamicImage::ImageRgb8(image) => remap(image, map).into(),
+        image => remap(&image.to_rgba8(), map).into(),
+    })
+}
+
+fn remap<P: Pixel>(
+    image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    map: &UndistortMap,
+) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    let black = *P::from_slice(&vec![
+        P::Subpixel::DEFAULT_MIN_VALUE;
+        P::CHANNEL_COUNT as usize
+    ]);
+    ImageBuffer::from_fn(map.width, map.height, |x, y| {
+        let [u, v] = map.source(x, y);
+        imageops::interpolate_bilinear(image, u, v).unwrap_or(black)
+    })
+}
@@ -1,11 +1,38 @@
 use app::App;
-use backend::ReaderBackend;
 
 pub mod app;
+pub mod attachments;
 pub mod backend;
+pub mod cache;
+pub mod catalog;
+pub mod covariance;
+pub mod encryption;
+pub mod errors;
+pub mod export;
+pub mod fonts;
+pub mod histogram;
+pub mod i18n;
+pub mod images;
+pub mod playback;
+pub mod plot;
+pub mod prefetch;
+pub mod preview;
+pub mod remote;
+pub mod snapshot;
+pub mod start;
+pub mod thumbnails;
+pub mod timeline;
+pub mod trigger;
+pub mod visualizer;
+pub mod watch;
+pub mod zoom;
 
-pub use backend::create_reader;
+pub use backend::{
+    append_writer, create_reader, create_reader_with_options, create_summary_reader, create_writer,
+};
+pub use encryption::{OutputFile, encrypt_file, encrypted_path, is_encrypted, private_dir};
 use rospeek_core::RosPeekResult;
+pub use visualizer::{Visualizer, VisualizerRegistry};
 
 pub fn spawn_app() -> RosPeekResult<()> {
     let native_options = eframe::NativeOptions {
@@ -16,7 +43,7 @@ pub fn spawn_app() -> RosPeekResult<()> {
     eframe::run_native(
         "rospeek-app",
         native_options,
-        Box::new(|cc| Ok(Box::new(App::<ReaderBackend>::new(cc)))),
+        Box::new(|cc| Ok(Box::new(App::new(cc)))),
     )
     .map_err(|e| anyhow::anyhow!("GUI error: {e}"))
 }
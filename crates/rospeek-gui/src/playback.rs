@@ -0,0 +1,109 @@
+use std::time::Instant;
+
+/// Playback state of the timeline.
+#[derive(Debug)]
+pub struct Playback {
+    /// UNIX epoch nanoseconds of the beginning of the bag
+    pub start_ns: u64,
+    /// UNIX epoch nanoseconds of the end of the bag
+    pub end_ns: u64,
+    /// UNIX epoch nanoseconds of the current playback position
+    pub cursor_ns: u64,
+    /// Playback rate relative to wall time
+    pub speed: f64,
+    /// Whether to restart from the beginning of the selected range when reaching its end
+    pub looping: bool,
+    region: Option<(u64, u64)>,
+    playing: bool,
+    last_tick: Option<Instant>,
+}
+
+impl Playback {
+    pub fn new(start_ns: u64, end_ns: u64) -> Self {
+        Self {
+            start_ns,
+            end_ns,
+            cursor_ns: start_ns,
+            speed: 1.0,
+            looping: false,
+            region: None,
+            playing: false,
+            last_tick: None,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Returns the selected sub-range of the timeline, if any.
+    pub fn region(&self) -> Option<(u64, u64)> {
+        self.region
+    }
+
+    /// Selects a sub-range of the timeline, clamped to the bag range.
+    pub fn set_region(&mut self, a_ns: u64, b_ns: u64) {
+        let clamp = |ns: u64| ns.max(self.start_ns).min(self.end_ns);
+        let (a_ns, b_ns) = (clamp(a_ns), clamp(b_ns));
+        self.region = Some((a_ns.min(b_ns), a_ns.max(b_ns)));
+    }
+
+    pub fn clear_region(&mut self) {
+        self.region = None;
+    }
+
+    /// Returns the selected range, or the whole bag if nothing is selected.
+    ///
+    /// This is the range that playback loops over and that exports and plots are scoped to.
+    pub fn selected_range(&self) -> (u64, u64) {
+        self.region.unwrap_or((self.start_ns, self.end_ns))
+    }
+
+    pub fn play(&mut self) {
+        let (from, to) = self.selected_range();
+        if self.cursor_ns >= to || (self.region.is_some() && self.cursor_ns < from) {
+            self.cursor_ns = from;
+        }
+        self.playing = true;
+        self.last_tick = Some(Instant::now());
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+        self.last_tick = None;
+    }
+
+    pub fn stop(&mut self) {
+        self.pause();
+        self.cursor_ns = self.start_ns;
+    }
+
+    /// Moves the cursor to the given timestamp, clamped to the bag range.
+    pub fn seek(&mut self, cursor_ns: u64) {
+        self.cursor_ns = cursor_ns.max(self.start_ns).min(self.end_ns);
+    }
+
+    /// Advances the cursor by the wall time elapsed since the previous tick.
+    ///
+    /// # Returns
+    /// `true` if the cursor moved.
+    pub fn tick(&mut self) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let now = Instant::now();
+        let elapsed = self.last_tick.map_or(0, |t| (now - t).as_nanos() as u64);
+        self.last_tick = Some(now);
+
+        let step = (elapsed as f64 * self.speed) as u64;
+        self.seek(self.cursor_ns.saturating_add(step));
+
+        let (from, to) = self.selected_range();
+        if self.looping && self.cursor_ns >= to {
+            self.cursor_ns = from;
+        } else if self.cursor_ns >= self.end_ns {
+            self.pause();
+        }
+        step > 0
+    }
+}
@@ -0,0 +1,788 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use crossbeam_channel as channel;
+use egui::{Align2, Color32, FontId, Pos2, Rect, Sense, Stroke, pos2, vec2};
+use rfd::FileDialog;
+use rospeek_core::{
+    FieldUnits, MessageSchema, RosPeekResult, Spectrum, SpectrumWindow, Topic, decode_field,
+    ns_to_iso, spectrum,
+};
+use serde_json::Value;
+
+use crate::{backend::Backend, i18n::Language, playback::Playback};
+
+const CHART_HEIGHT: f32 = 160.0;
+const LABEL_HEIGHT: f32 = 14.0;
+const SERIES_COLOR: Color32 = Color32::from_rgb(76, 120, 168);
+
+/// Number of messages read from the backend per request.
+const BATCH_SIZE: usize = 1_000;
+
+/// Number of bins of the histogram until changed.
+const DEFAULT_BINS: usize = 40;
+
+/// Number of samples below which a series is not downsampled any further.
+const MIN_LEVEL_SAMPLES: usize = 1_000;
+
+/// Ratio of the number of samples of a level of a series to that of the next coarser level.
+const LEVEL_FACTOR: usize = 4;
+
+/// Field of a topic, whose values over the whole bag make a series.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SeriesKey {
+    pub topic: String,
+    pub type_name: String,
+    /// Dot-separated field path, such as `twist.linear.x`.
+    pub field: String,
+}
+
+/// Numeric values of a field with the timestamps of their messages, in time order, along with
+/// coarser copies of them, so that a time range of any length is drawn from a bounded number of
+/// points.
+///
+/// Coarser levels keep the minimum and the maximum of every few samples rather than every few
+/// samples, so that spikes show at every zoom.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Series {
+    /// Levels of resolution, from every sample to the coarsest, each having about
+    /// [`LEVEL_FACTOR`] times fewer samples than the previous one.
+    levels: Vec<Vec<(u64, f64)>>,
+}
+
+impl Series {
+    /// Builds the levels of a series from its samples, down to [`MIN_LEVEL_SAMPLES`].
+    pub fn new(samples: Vec<(u64, f64)>) -> Self {
+        let mut levels = vec![samples];
+        while let Some(coarsest) = levels.last()
+            && coarsest.len() > MIN_LEVEL_SAMPLES
+        {
+            let mut coarser = Vec::with_capacity(coarsest.len() / LEVEL_FACTOR + 2);
+            for chunk in coarsest.chunks(2 * LEVEL_FACTOR) {
+                push_min_max(&mut coarser, chunk);
+            }
+            levels.push(coarser);
+        }
+        Self { levels }
+    }
+
+    /// Returns the number of samples.
+    pub fn len(&self) -> usize {
+        self.levels.first().map_or(0, Vec::len)
+    }
+
+    /// Returns whether the series has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns every sample within a time range, for statistics over it.
+    ///
+    /// # Arguments
+    /// * `start_ns` - Timestamp in nanoseconds of the start of the range (inclusive).
+    /// * `end_ns` - Timestamp in nanoseconds of the end of the range (inclusive).
+    pub fn range(&self, start_ns: u64, end_ns: u64) -> &[(u64, f64)] {
+        self.levels
+            .first()
+            .map_or(&[], |level| within(level, start_ns, end_ns))
+    }
+
+    /// Returns the samples within a time range at the finest level having at most a number of
+    /// them, for drawing.
+    ///
+    /// # Arguments
+    /// * `start_ns` - Timestamp in nanoseconds of the start of the range (inclusive).
+    /// * `end_ns` - Timestamp in nanoseconds of the end of the range (inclusive).
+    /// * `max_samples` - Number of samples above which a coarser level is used, such as a few
+    ///   per pixel of the chart.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_gui::plot::Series;
+    ///
+    /// let series = Series::new((0..10_000u64).map(|t| (t, t as f64)).collect());
+    /// assert_eq!(series.len(), 10_000);
+    /// // a narrow range is drawn from every sample
+    /// assert_eq!(series.view(0, 99, 500).len(), 100);
+    /// // a wide one from a coarser level
+    /// let view = series.view(0, 9_999, 1_000);
+    /// assert!(!view.is_empty() && view.len() <= 1_000);
+    /// assert_eq!(series.range(0, 9_999).len(), 10_000);
+    /// ```
+    pub fn view(&self, start_ns: u64, end_ns: u64, max_samples: usize) -> &[(u64, f64)] {
+        let mut view: &[(u64, f64)] = &[];
+        for level in &self.levels {
+            view = within(level, start_ns, end_ns);
+            if view.len() <= max_samples {
+                break;
+            }
+        }
+        view
+    }
+
+    /// Returns the minimum and the maximum of the samples within each of a number of buckets of
+    /// equal duration over a time range, such as one bucket per pixel of a chart.
+    ///
+    /// # Arguments
+    /// * `start_ns` - Timestamp in nanoseconds of the start of the range (inclusive).
+    /// * `end_ns` - Timestamp in nanoseconds of the end of the range (inclusive).
+    /// * `buckets` - Number of buckets to split the range into.
+    ///
+    /// # Examples
+    /// ```
+    /// use rospeek_gui::plot::Series;
+    ///
+    /// // a single spike among a million samples
+    /// let series = Series::new(
+    ///     (0..1_000_000u64)
+    ///         .map(|t| (t, if t == 123_457 { 100.0 } else { 0.0 }))
+    ///         .collect(),
+    /// );
+    /// let decimated = series.decimated(0, 999_999, 500);
+    /// assert!(decimated.len() <= 2 * 500);
+    /// assert!(decimated.iter().any(|(_, v)| *v == 100.0));
+    /// ```
+    pub fn decimated(&self, start_ns: u64, end_ns: u64, buckets: usize) -> Vec<(u64, f64)> {
+        let buckets = buckets.max(1);
+        let samples = self.view(start_ns, end_ns, 2 * LEVEL_FACTOR * buckets);
+        let span = end_ns.saturating_sub(start_ns) as u128 + 1;
+        let bucket =
+            |t: u64| (t.saturating_sub(start_ns) as u128 * buckets as u128 / span) as usize;
+
+        let mut decimated = Vec::with_capacity(2 * buckets);
+        let mut rest = samples;
+        while let Some((first, _)) = rest.first() {
+            let index = bucket(*first);
+            let len = rest.partition_point(|(t, _)| bucket(*t) == index);
+            push_min_max(&mut decimated, &rest[..len]);
+            rest = &rest[len..];
+        }
+        decimated
+    }
+}
+
+/// Pushes the minimum and the maximum of some samples in time order, or the sample itself if
+/// there is only one.
+fn push_min_max(samples: &mut Vec<(u64, f64)>, chunk: &[(u64, f64)]) {
+    if chunk.is_empty() {
+        return;
+    }
+    let (mut min, mut max) = (0, 0);
+    for (i, (_, value)) in chunk.iter().enumerate() {
+        if *value < chunk[min].1 {
+            min = i;
+        }
+        if *value > chunk[max].1 {
+            max = i;
+        }
+    }
+    samples.push(chunk[min.min(max)]);
+    if min != max {
+        samples.push(chunk[min.max(max)]);
+    }
+}
+
+/// Returns the samples of a level within a time range.
+fn within(samples: &[(u64, f64)], start_ns: u64, end_ns: u64) -> &[(u64, f64)] {
+    let start = samples.partition_point(|(t, _)| *t < start_ns);
+    let end = samples.partition_point(|(t, _)| *t <= end_ns);
+    &samples[start..end.max(start)]
+}
+
+/// Loading state of a series.
+#[derive(Debug, Clone)]
+pub enum SeriesState {
+    Loading,
+    Ready(Arc<Series>),
+    Failed(String),
+}
+
+/// Series decoded on a worker thread, kept for the session of a bag so that every plot mode,
+/// every time range and every redraw shares them, and dropped along with the bag.
+pub struct SeriesCache {
+    series: Arc<Mutex<HashMap<SeriesKey, SeriesState>>>,
+    tx: channel::Sender<SeriesKey>,
+}
+
+impl SeriesCache {
+    /// Creates a cache whose worker reads messages from a backend.
+    ///
+    /// # Arguments
+    /// * `backend` - The backend to read messages from.
+    /// * `ctx` - The context to repaint as series are loaded.
+    pub fn new(backend: Arc<dyn Backend>, ctx: egui::Context) -> Self {
+        let series = Arc::new(Mutex::new(HashMap::new()));
+        let (tx, rx) = channel::unbounded::<SeriesKey>();
+
+        let shared = series.clone();
+        std::thread::spawn(move || {
+            while let Ok(key) = rx.recv() {
+                let state = match load_series(backend.as_ref(), &key) {
+                    Ok(samples) => SeriesState::Ready(Arc::new(Series::new(samples))),
+                    Err(e) => SeriesState::Failed(format!("{e:#}")),
+                };
+                shared.lock().unwrap().insert(key, state);
+                ctx.request_repaint();
+            }
+        });
+
+        Self { series, tx }
+    }
+
+    /// Returns the state of a series, requesting it on first use.
+    pub fn get(&self, key: &SeriesKey) -> SeriesState {
+        let mut series = self.series.lock().unwrap();
+        match series.get(key) {
+            Some(state) => state.clone(),
+            None => {
+                series.insert(key.clone(), SeriesState::Loading);
+                let _ = self.tx.send(key.clone());
+                SeriesState::Loading
+            }
+        }
+    }
+}
+
+/// Decodes the values of a field from every message of a topic.
+///
+/// Values which are neither numbers nor booleans, such as those of a string field, are skipped.
+fn load_series(backend: &dyn Backend, key: &SeriesKey) -> RosPeekResult<Vec<(u64, f64)>> {
+    let schema = MessageSchema::try_from(key.type_name.as_ref())?;
+    let mut samples = Vec::new();
+    let mut from = 0;
+    loop {
+        let messages = backend.read_messages(&key.topic, Some(from), BATCH_SIZE, None)?;
+        for message in &messages {
+            let value = decode_field(&schema, &key.field, &message.data)?
+                .with_context(|| format!("Field not found: {}", key.field))?;
+            if let Some(value) = to_number(&value) {
+                samples.push((message.timestamp, value));
+            }
+        }
+        match messages.last() {
+            Some(last) if messages.len() == BATCH_SIZE => from = last.timestamp + 1,
+            _ => return Ok(samples),
+        }
+    }
+}
+
+fn to_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        value => value.as_f64(),
+    }
+}
+
+/// Range of values and the number of samples within it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueBin {
+    pub min: f64,
+    pub max: f64,
+    pub count: usize,
+}
+
+/// Splits the range of values into bins of equal width, counting the values within each.
+///
+/// Values which are not finite are skipped, and values all equal fall into a single bin.
+///
+/// # Examples
+/// ```
+/// use rospeek_gui::plot::value_histogram;
+///
+/// let bins = value_histogram([0.0, 1.0, 1.5, 4.0], 4);
+/// let counts: Vec<usize> = bins.iter().map(|bin| bin.count).collect();
+/// assert_eq!(counts, vec![1, 2, 0, 1]);
+/// assert_eq!((bins[0].min, bins[3].max), (0.0, 4.0));
+///
+/// assert_eq!(value_histogram([2.0, 2.0], 4).len(), 1);
+/// assert!(value_histogram([f64::NAN], 4).is_empty());
+/// ```
+pub fn value_histogram(values: impl IntoIterator<Item = f64>, bins: usize) -> Vec<ValueBin> {
+    let values: Vec<f64> = values.into_iter().filter(|v| v.is_finite()).collect();
+    let Some((min, max)) = extremes(values.iter().copied()) else {
+        return Vec::new();
+    };
+    let bins = if max > min { bins.max(1) } else { 1 };
+    let width = (max - min) / bins as f64;
+    let mut histogram: Vec<ValueBin> = (0..bins)
+        .map(|i| ValueBin {
+            min: min + i as f64 * width,
+            max: if i + 1 == bins {
+                max
+            } else {
+                min + (i + 1) as f64 * width
+            },
+            count: 0,
+        })
+        .collect();
+    for value in values {
+        let i = if width > 0.0 {
+            (((value - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        histogram[i].count += 1;
+    }
+    histogram
+}
+
+fn extremes(values: impl Iterator<Item = f64>) -> Option<(f64, f64)> {
+    values.fold(None, |extremes, v| match extremes {
+        Some((min, max)) => Some((v.min(min), v.max(max))),
+        None => Some((v, v)),
+    })
+}
+
+/// How a series is plotted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotMode {
+    /// Values over time.
+    TimeSeries,
+    /// Distribution of the values.
+    Histogram,
+    /// Amplitudes of the frequencies of the values.
+    Spectrum,
+}
+
+/// Spectrum of a series over a time range with a window, if it has enough samples.
+struct CachedSpectrum {
+    key: SeriesKey,
+    range: (u64, u64),
+    window: SpectrumWindow,
+    spectrum: Option<Spectrum>,
+}
+
+/// Plot of a field of the focused topic over the selected range of the playback.
+pub struct PlotPanel {
+    cache: SeriesCache,
+    input: String,
+    field: Option<String>,
+    mode: PlotMode,
+    bins: usize,
+    window: SpectrumWindow,
+    /// Spectrum of the last plotted series, computed once per series, time range and window
+    spectrum: Option<CachedSpectrum>,
+    /// Result of the last series exported as CSV
+    export_status: Option<String>,
+}
+
+impl PlotPanel {
+    pub fn new(backend: Arc<dyn Backend>, ctx: egui::Context) -> Self {
+        Self {
+            cache: SeriesCache::new(backend, ctx),
+            input: String::new(),
+            field: None,
+            mode: PlotMode::TimeSeries,
+            bins: DEFAULT_BINS,
+            window: SpectrumWindow::default(),
+            spectrum: None,
+            export_status: None,
+        }
+    }
+
+    /// Shows the field selection and the plot of the field.
+    ///
+    /// # Arguments
+    /// * `ui` - The UI to draw the panel in.
+    /// * `lang` - The language of the labels.
+    /// * `topic` - The focused topic.
+    /// * `units` - Units the values of the field are labelled with.
+    /// * `playback` - The playback whose selected range is plotted and whose cursor is shown.
+    ///
+    /// # Returns
+    /// `true` if the cursor was moved by the user.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        lang: Language,
+        topic: &Topic,
+        units: &FieldUnits,
+        playback: &mut Playback,
+    ) -> bool {
+        ui.horizontal(|ui| {
+            let input = ui.add(
+                egui::TextEdit::singleline(&mut self.input)
+                    .hint_text("twist.linear.x")
+                    .desired_width(160.0),
+            );
+            let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if (ui.button(lang.tr("Plot")).clicked() || submitted) && !self.input.trim().is_empty()
+            {
+                self.field = Some(self.input.trim().to_string());
+            }
+            ui.separator();
+            ui.selectable_value(&mut self.mode, PlotMode::TimeSeries, lang.tr("Time series"));
+            ui.selectable_value(&mut self.mode, PlotMode::Histogram, lang.tr("Histogram"));
+            ui.selectable_value(&mut self.mode, PlotMode::Spectrum, lang.tr("Spectrum"));
+            match self.mode {
+                PlotMode::TimeSeries => {}
+                PlotMode::Histogram => {
+                    ui.add(
+                        egui::DragValue::new(&mut self.bins)
+                            .range(2..=200)
+                            .prefix(lang.tr("Bins: ")),
+                    );
+                }
+                PlotMode::Spectrum => {
+                    egui::ComboBox::from_label(lang.tr("Window"))
+                        .selected_text(self.window.name())
+                        .show_ui(ui, |ui| {
+                            for window in SpectrumWindow::ALL {
+                                ui.selectable_value(&mut self.window, window, window.name());
+                            }
+                        });
+                }
+            }
+        });
+
+        let Some(field) = &self.field else {
+            return false;
+        };
+        let unit = units.unit(&topic.name, &topic.type_name, field);
+        let range = playback.selected_range();
+        let key = SeriesKey {
+            topic: topic.name.clone(),
+            type_name: topic.type_name.clone(),
+            field: field.clone(),
+        };
+        match self.cache.get(&key) {
+            SeriesState::Loading => {
+                ui.spinner();
+                false
+            }
+            SeriesState::Failed(e) => {
+                ui.colored_label(Color32::ORANGE, format!("⚠ {e}"));
+                false
+            }
+            SeriesState::Ready(series) => {
+                let samples = series.range(range.0, range.1);
+                ui.horizontal(|ui| {
+                    match unit {
+                        Some(unit) => {
+                            ui.label(format!("{field} [{unit}]: {} samples", samples.len()))
+                        }
+                        None => ui.label(format!("{field}: {} samples", samples.len())),
+                    };
+                    if ui
+                        .button("💾")
+                        .on_hover_text(lang.tr("Export series as CSV"))
+                        .clicked()
+                        && let Some(path) = FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name(format!("{}.{field}.csv", topic_stem(&topic.name)))
+                            .save_file()
+                    {
+                        let result = File::create(&path)
+                            .with_context(|| format!("Failed to create {}", path.display()))
+                            .and_then(|file| write_series_csv(file, field, samples));
+                        self.export_status = Some(match result {
+                            Ok(()) => format!("✨Saved series to {}", path.display()),
+                            Err(e) => format!("⚠ {e:#}"),
+                        });
+                    }
+                    if let Some(status) = &self.export_status {
+                        ui.weak(status);
+                    }
+                });
+                match self.mode {
+                    PlotMode::TimeSeries => time_series_chart(ui, &series, unit, range, playback),
+                    PlotMode::Histogram => {
+                        histogram_chart(ui, samples, unit, self.bins);
+                        false
+                    }
+                    PlotMode::Spectrum => {
+                        let cached = self.spectrum.as_ref().is_some_and(|cached| {
+                            cached.key == key
+                                && cached.range == range
+                                && cached.window == self.window
+                        });
+                        if !cached {
+                            self.spectrum = Some(CachedSpectrum {
+                                key,
+                                range,
+                                window: self.window,
+                                spectrum: spectrum(samples, self.window),
+                            });
+                        }
+                        match self.spectrum.as_ref().and_then(|c| c.spectrum.as_ref()) {
+                            Some(spectrum) => spectrum_chart(ui, spectrum, unit),
+                            None => {
+                                ui.label(lang.tr("Too few samples for a spectrum."));
+                            }
+                        }
+                        false
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Writes samples of a field as CSV with a `timestamp` column in nanoseconds and a column named
+/// by the field, as `dump --format csv --fields` does with the timestamp added.
+///
+/// # Examples
+/// ```
+/// use rospeek_gui::plot::write_series_csv;
+///
+/// let mut csv = Vec::new();
+/// write_series_csv(&mut csv, "twist.linear.x", &[(1_000, 0.5), (2_000, -1.25)]).unwrap();
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     "timestamp,twist.linear.x\n1000,0.5\n2000,-1.25\n"
+/// );
+/// ```
+pub fn write_series_csv<W: Write>(
+    writer: W,
+    field: &str,
+    samples: &[(u64, f64)],
+) -> RosPeekResult<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(["timestamp", field])?;
+    for (timestamp, value) in samples {
+        writer.write_record([timestamp.to_string(), value.to_string()])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the name of a topic usable in file names, such as `foo.bar` for `/foo/bar`.
+fn topic_stem(topic: &str) -> String {
+    topic.trim_start_matches('/').replace('/', ".")
+}
+
+/// Draws the values of a series over a time range as a line, with the playback cursor.
+///
+/// Long ranges are drawn from the minimum and the maximum of the samples of each pixel.
+/// Clicking the chart seeks the cursor, and hovering it shows the sample nearest in time.
+fn time_series_chart(
+    ui: &mut egui::Ui,
+    series: &Series,
+    unit: Option<&str>,
+    (start_ns, end_ns): (u64, u64),
+    playback: &mut Playback,
+) -> bool {
+    let width = ui.available_width().max(100.0);
+    let samples = series.decimated(start_ns, end_ns, width as usize);
+    let (rect, response) = ui.allocate_exact_size(vec2(width, CHART_HEIGHT), Sense::click());
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let Some((min, max)) = extremes(samples.iter().map(|(_, v)| *v)) else {
+        painter.text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            "No samples",
+            FontId::proportional(12.0),
+            visuals.weak_text_color(),
+        );
+        return false;
+    };
+    // a constant series is drawn across the middle
+    let (low, high) = if max > min {
+        (min, max)
+    } else {
+        (min - 1.0, max + 1.0)
+    };
+
+    let span = end_ns.saturating_sub(start_ns).max(1) as f64;
+    let to_x =
+        |ns: u64| rect.left() + (ns.saturating_sub(start_ns) as f64 / span) as f32 * rect.width();
+    let to_ns = |x: f32| {
+        let ratio = ((x - rect.left()) / rect.width()).clamp(0.0, 1.0) as f64;
+        start_ns + (ratio * span) as u64
+    };
+    let plot = rect.shrink2(vec2(0.0, 4.0));
+    let to_y = |v: f64| plot.bottom() - ((v - low) / (high - low)) as f32 * plot.height();
+
+    let points: Vec<Pos2> = samples
+        .iter()
+        .map(|(t, v)| pos2(to_x(*t), to_y(*v)))
+        .collect();
+    painter.add(egui::Shape::line(points, Stroke::new(1.5, SERIES_COLOR)));
+    for (value, anchor, y) in [
+        (max, Align2::LEFT_TOP, rect.top()),
+        (min, Align2::LEFT_BOTTOM, rect.bottom()),
+    ] {
+        painter.text(
+            pos2(rect.left() + 2.0, y),
+            anchor,
+            format_value(value, unit),
+            FontId::monospace(10.0),
+            visuals.weak_text_color(),
+        );
+    }
+    if (start_ns..=end_ns).contains(&playback.cursor_ns) {
+        let x = to_x(playback.cursor_ns);
+        painter.line_segment(
+            [pos2(x, rect.top()), pos2(x, rect.bottom())],
+            Stroke::new(1.0, visuals.selection.stroke.color),
+        );
+    }
+
+    if response.clicked()
+        && let Some(pos) = response.interact_pointer_pos()
+    {
+        playback.seek(to_ns(pos.x));
+        return true;
+    }
+    if let Some(pos) = response.hover_pos() {
+        let ns = to_ns(pos.x);
+        let i = samples.partition_point(|(t, _)| *t < ns);
+        let nearest = [i.checked_sub(1), Some(i)]
+            .into_iter()
+            .flatten()
+            .filter_map(|i| samples.get(i))
+            .min_by_key(|(t, _)| t.abs_diff(ns));
+        if let Some((t, v)) = nearest {
+            response.on_hover_text(format!("{}\n{}", ns_to_iso(*t), format_value(*v, unit)));
+        }
+    }
+    false
+}
+
+/// Draws the distribution of the values of a series as a bar chart.
+///
+/// Hovering a bar shows its range of values and number of samples.
+fn histogram_chart(ui: &mut egui::Ui, samples: &[(u64, f64)], unit: Option<&str>, bins: usize) {
+    let histogram = value_histogram(samples.iter().map(|(_, v)| *v), bins);
+    if histogram.is_empty() {
+        ui.label("No samples.");
+        return;
+    }
+
+    let width = ui.available_width().max(100.0);
+    let (rect, response) =
+        ui.allocate_exact_size(vec2(width, CHART_HEIGHT + LABEL_HEIGHT), Sense::hover());
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(rect);
+    let plot = Rect::from_min_size(rect.min, vec2(rect.width(), CHART_HEIGHT));
+    painter.rect_filled(plot, 2.0, visuals.extreme_bg_color);
+
+    let max = histogram.iter().map(|bin| bin.count).max().unwrap_or(1) as f32;
+    let bar_width = plot.width() / histogram.len() as f32;
+    let hovered = response
+        .hover_pos()
+        .map(|pos| ((pos.x - plot.left()) / bar_width) as usize);
+    for (i, bin) in histogram.iter().enumerate() {
+        let left = plot.left() + i as f32 * bar_width;
+        let height = bin.count as f32 / max * (CHART_HEIGHT - 4.0);
+        let bar = Rect::from_min_max(
+            pos2(left + 1.0, plot.bottom() - height),
+            pos2(left + bar_width - 1.0, plot.bottom()),
+        );
+        let color = if hovered == Some(i) {
+            visuals.selection.bg_fill
+        } else {
+            SERIES_COLOR
+        };
+        painter.rect_filled(bar, 0.0, color);
+    }
+    let (first, last) = (histogram[0], histogram[histogram.len() - 1]);
+    for (value, anchor, x) in [
+        (first.min, Align2::LEFT_TOP, plot.left()),
+        (last.max, Align2::RIGHT_TOP, plot.right()),
+    ] {
+        painter.text(
+            pos2(x, plot.bottom() + 1.0),
+            anchor,
+            format_value(value, unit),
+            FontId::monospace(10.0),
+            visuals.weak_text_color(),
+        );
+    }
+
+    if let Some(bin) = hovered.and_then(|i| histogram.get(i)) {
+        response.on_hover_text(format!(
+            "{} - {}: {} samples",
+            format_value(bin.min, unit),
+            format_value(bin.max, unit),
+            bin.count
+        ));
+    }
+}
+
+/// Draws the amplitudes of a spectrum over its frequencies up to the Nyquist frequency, marking
+/// its highest peak.
+///
+/// Hovering the chart shows the frequency and amplitude under the pointer.
+fn spectrum_chart(ui: &mut egui::Ui, spectrum: &Spectrum, unit: Option<&str>) {
+    let width = ui.available_width().max(100.0);
+    let (rect, response) =
+        ui.allocate_exact_size(vec2(width, CHART_HEIGHT + LABEL_HEIGHT), Sense::hover());
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(rect);
+    let plot = Rect::from_min_size(rect.min, vec2(rect.width(), CHART_HEIGHT));
+    painter.rect_filled(plot, 2.0, visuals.extreme_bg_color);
+
+    let bins = spectrum.amplitudes.len();
+    let max = spectrum
+        .amplitudes
+        .iter()
+        .copied()
+        .fold(0.0, f64::max)
+        .max(f64::MIN_POSITIVE);
+    let to_x = |bin: usize| plot.left() + bin as f32 / (bins - 1).max(1) as f32 * plot.width();
+    let to_y = |amplitude: f64| plot.bottom() - (amplitude / max) as f32 * (CHART_HEIGHT - 4.0);
+    let points: Vec<Pos2> = spectrum
+        .amplitudes
+        .iter()
+        .enumerate()
+        .map(|(bin, amplitude)| pos2(to_x(bin), to_y(*amplitude)))
+        .collect();
+    painter.add(egui::Shape::line(points, Stroke::new(1.5, SERIES_COLOR)));
+
+    if let Some((frequency_hz, amplitude)) = spectrum.peak() {
+        let x = to_x((frequency_hz / spectrum.resolution_hz).round() as usize);
+        painter.text(
+            pos2(x, to_y(amplitude)),
+            Align2::CENTER_BOTTOM,
+            format!("{frequency_hz:.2} Hz"),
+            FontId::monospace(10.0),
+            visuals.strong_text_color(),
+        );
+    }
+    for (text, anchor, x) in [
+        ("0 Hz".to_string(), Align2::LEFT_TOP, plot.left()),
+        (
+            format!("{:.1} Hz", spectrum.frequency_hz(bins - 1)),
+            Align2::RIGHT_TOP,
+            plot.right(),
+        ),
+    ] {
+        painter.text(
+            pos2(x, plot.bottom() + 1.0),
+            anchor,
+            text,
+            FontId::monospace(10.0),
+            visuals.weak_text_color(),
+        );
+    }
+
+    if let Some(pos) = response.hover_pos() {
+        let ratio = ((pos.x - plot.left()) / plot.width()).clamp(0.0, 1.0);
+        let bin = (ratio * (bins - 1) as f32).round() as usize;
+        if let Some(amplitude) = spectrum.amplitudes.get(bin) {
+            response.on_hover_text(format!(
+                "{:.3} Hz: {}",
+                spectrum.frequency_hz(bin),
+                format_value(*amplitude, unit)
+            ));
+        }
+    }
+}
+
+/// Formats a value with up to 6 decimals, without trailing zeros, followed by its unit if any.
+fn format_value(value: f64, unit: Option<&str>) -> String {
+    let text = format!("{value:.6}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match unit {
+        Some(unit) => format!("{text} {unit}"),
+        None => text.to_string(),
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crossbeam_channel as channel;
+use rospeek_core::{
+    CdrDecoder, DecodePlan, DecodeRule, DecoderRegistry, MessageSchema, RawMessage, RosPeekResult,
+    Topic, decode_with_rule,
+};
+
+use crate::{
+    backend::Backend,
+    preview::{is_blob_type, preview_blob},
+};
+
+/// A message decoded ahead of the playback cursor.
+#[derive(Debug, Clone)]
+pub struct PrefetchedMessage {
+    pub topic: String,
+    pub message: RawMessage,
+    pub decoded: String,
+}
+
+impl PrefetchedMessage {
+    /// Returns the approximate number of bytes held by this message.
+    pub fn footprint(&self) -> usize {
+        self.topic.len() + self.message.data.len() + self.decoded.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PrefetchConfig {
+    /// How far ahead of the cursor to decode, in nanoseconds.
+    pub window_ns: u64,
+    /// How much history behind the cursor to keep, in nanoseconds.
+    pub history_ns: u64,
+    /// Upper bound of the buffered bytes.
+    pub capacity_bytes: usize,
+    /// Number of messages read from the backend per request.
+    pub batch_size: usize,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self {
+            window_ns: 5_000_000_000,
+            history_ns: 1_000_000_000,
+            capacity_bytes: 256 * 1024 * 1024,
+            batch_size: 64,
+        }
+    }
+}
+
+impl PrefetchConfig {
+    /// Returns whether moving the cursor invalidates what was buffered around the previous one,
+    /// that is seeking backward or past the read-ahead window.
+    fn jumps(&self, from_ns: u64, to_ns: u64) -> bool {
+        to_ns < from_ns || to_ns > from_ns.saturating_add(self.window_ns)
+    }
+}
+
+enum Request {
+    Follow(Vec<Topic>),
+    /// Cursor to read ahead of, and the generation of the requests it was sent in.
+    Seek(u64, u64),
+}
+
+#[derive(Default)]
+struct Buffer {
+    /// Messages ordered by timestamp.
+    messages: VecDeque<PrefetchedMessage>,
+    bytes: usize,
+    cursor_ns: u64,
+    /// Timestamp up to which each followed topic has been fetched.
+    fetched_until: HashMap<String, u64>,
+}
+
+impl Buffer {
+    fn clear(&mut self) {
+        self.messages.clear();
+        self.bytes = 0;
+        self.fetched_until.clear();
+    }
+
+    /// Merges a batch of messages ordered by timestamp, such as a page of a single topic.
+    ///
+    /// Batches usually land at or near the back of the buffer, so only the messages after the
+    /// first one of the batch are merged with it, rather than sorting the whole buffer.
+    fn insert(&mut self, batch: Vec<PrefetchedMessage>) {
+        self.bytes += batch
+            .iter()
+            .map(PrefetchedMessage::footprint)
+            .sum::<usize>();
+        let Some(first) = batch.first().map(|m| m.message.timestamp) else {
+            return;
+        };
+        let split = self
+            .messages
+            .partition_point(|m| m.message.timestamp <= first);
+        let mut tail = self.messages.split_off(split).into_iter().peekable();
+        for message in batch {
+            while let Some(next) =
+                tail.next_if(|m| m.message.timestamp <= message.message.timestamp)
+            {
+                self.messages.push_back(next);
+            }
+            self.messages.push_back(message);
+        }
+        self.messages.extend(tail);
+    }
+
+    /// Drops messages behind the history window, then the furthest-ahead ones over capacity.
+    fn evict(&mut self, config: &PrefetchConfig) {
+        let oldest = self.cursor_ns.saturating_sub(config.history_ns);
+        while let Some(front) = self.messages.front()
+            && front.message.timestamp < oldest
+        {
+            self.bytes -= front.footprint();
+            self.messages.pop_front();
+        }
+        while self.bytes > config.capacity_bytes
+            && let Some(back) = self.messages.pop_back()
+        {
+            self.bytes -= back.footprint();
+            let until = back.message.timestamp.saturating_sub(1);
+            self.fetched_until
+                .entry(back.topic)
+                .and_modify(|t| *t = (*t).min(until));
+        }
+    }
+}
+
+/// Decodes the followed topics ahead of the playback cursor on worker threads.
+///
+/// Decoded messages are held in a timestamp-ordered buffer bounded by
+/// [`PrefetchConfig::capacity_bytes`], evicting history as the cursor moves forward.
+///
+/// Following other topics or jumping the cursor starts a new generation of requests, which
+/// cancels the fetches in flight between batches and drops the batches they were decoding.
+pub struct Prefetcher {
+    buffer: Arc<Mutex<Buffer>>,
+    tx: channel::Sender<Request>,
+    config: PrefetchConfig,
+    generation: Arc<AtomicU64>,
+    /// Cursor of the latest seek.
+    cursor_ns: AtomicU64,
+}
+
+impl Prefetcher {
+    pub fn new(backend: Arc<dyn Backend>, config: PrefetchConfig) -> Self {
+        let buffer = Arc::new(Mutex::new(Buffer::default()));
+        let (tx, rx) = channel::unbounded::<Request>();
+        let generation = Arc::new(AtomicU64::new(0));
+
+        let shared = buffer.clone();
+        let current = generation.clone();
+        std::thread::spawn(move || {
+            let mut topics: Vec<(Topic, Option<DecodePlan>)> = Vec::new();
+            while let Ok(request) = rx.recv() {
+                // only the latest cursor position matters
+                let mut seek = None;
+                for request in std::iter::once(request).chain(rx.try_iter()) {
+                    match request {
+                        Request::Follow(ts) => {
+                            topics = ts
+                                .into_iter()
+                                .map(|t| {
+                                    let plan = MessageSchema::try_from(t.type_name.as_ref())
+                                        .and_then(|schema| DecodePlan::compile(&schema))
+                                        .ok();
+                                    (t, plan)
+                                })
+                                .collect();
+                            shared.lock().unwrap().clear();
+                        }
+                        Request::Seek(cursor_ns, generation) => {
+                            seek = Some((cursor_ns, generation))
+                        }
+                    }
+                }
+                if let Some((cursor_ns, generation)) = seek {
+                    let cancelled = || current.load(Ordering::Acquire) != generation;
+                    fetch(
+                        backend.as_ref(),
+                        &topics,
+                        &shared,
+                        cursor_ns,
+                        &config,
+                        &cancelled,
+                    );
+                }
+            }
+        });
+
+        Self {
+            buffer,
+            tx,
+            config,
+            generation,
+            cursor_ns: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the topics to decode ahead, discarding the current buffer.
+    pub fn follow(&self, topics: Vec<Topic>) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        let _ = self.tx.send(Request::Follow(topics));
+    }
+
+    /// Moves the read-ahead window to the given cursor.
+    ///
+    /// Moving forward within the window keeps the fetches in flight, as what they decode is still
+    /// ahead of the cursor.
+    pub fn seek(&self, cursor_ns: u64) {
+        let previous = self.cursor_ns.swap(cursor_ns, Ordering::AcqRel);
+        let generation = if self.config.jumps(previous, cursor_ns) {
+            self.generation.fetch_add(1, Ordering::AcqRel) + 1
+        } else {
+            self.generation.load(Ordering::Acquire)
+        };
+        let _ = self.tx.send(Request::Seek(cursor_ns, generation));
+    }
+
+    /// Returns the latest buffered message of a topic at or before the cursor.
+    pub fn latest(&self, topic: &str, cursor_ns: u64) -> Option<PrefetchedMessage> {
+        let buffer = self.buffer.lock().unwrap();
+        let end = buffer
+            .messages
+            .partition_point(|m| m.message.timestamp <= cursor_ns);
+        buffer
+            .messages
+            .range(..end)
+            .rev()
+            .find(|m| m.topic == topic)
+            .cloned()
+    }
+
+    /// Returns the approximate number of buffered bytes.
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffer.lock().unwrap().bytes
+    }
+}
+
+fn fetch(
+    backend: &dyn Backend,
+    topics: &[(Topic, Option<DecodePlan>)],
+    shared: &Mutex<Buffer>,
+    cursor_ns: u64,
+    config: &PrefetchConfig,
+    cancelled: &(dyn Fn() -> bool + Sync),
+) {
+    let horizon = cursor_ns.saturating_add(config.window_ns);
+    let starts: Vec<u64> = {
+        let mut buffer = shared.lock().unwrap();
+        // seeking backward or past the window invalidates everything buffered
+        if config.jumps(buffer.cursor_ns, cursor_ns) {
+            buffer.clear();
+        }
+        buffer.cursor_ns = cursor_ns;
+        buffer.evict(config);
+        if buffer.bytes >= config.capacity_bytes {
+            return;
+        }
+        topics
+            .iter()
+            .map(|(t, _)| {
+                buffer
+                    .fetched_until
+                    .get(&t.name)
+                    .map_or(cursor_ns.saturating_sub(config.history_ns), |t| t + 1)
+            })
+            .collect()
+    };
+
+    std::thread::scope(|scope| {
+        for ((topic, plan), start) in topics.iter().zip(starts) {
+            if start > horizon {
+                continue;
+            }
+            scope.spawn(move || {
+                let mut from = start;
+                while from <= horizon && !cancelled() {
+                    let Ok(msgs) =
+                        backend.read_messages(&topic.name, Some(from), config.batch_size, None)
+                    else {
+                        return;
+                    };
+                    let exhausted = msgs.len() < config.batch_size;
+                    let msgs: Vec<_> = msgs
+                        .into_iter()
+                        .filter(|m| m.timestamp <= horizon)
+                        .collect();
+                    let reached = exhausted || msgs.last().is_none_or(|m| m.timestamp >= horizon);
+                    let until = if reached {
+                        horizon
+                    } else {
+                        msgs.last().map_or(horizon, |m| m.timestamp)
+                    };
+
+                    let rule = DecoderRegistry::global().rule_for(&topic.name, &topic.type_name);
+                    let decoded: Vec<String> = msgs
+                        .iter()
+                        .map(|m| {
+                            decode_pretty(&topic.type_name, rule.as_ref(), plan.as_ref(), &m.data)
+                                .unwrap_or_else(|e| e.to_string())
+                        })
+                        .collect();
+                    let batch = msgs
+                        .into_iter()
+                        .zip(decoded)
+                        .map(|(message, decoded)| PrefetchedMessage {
+                            topic: topic.name.clone(),
+                            message,
+                            decoded,
+                        })
+                        .collect();
+
+                    let mut buffer = shared.lock().unwrap();
+                    // a batch decoded for a previous generation is stale
+                    if cancelled() {
+                        return;
+                    }
+                    buffer.insert(batch);
+                    buffer.fetched_until.insert(topic.name.clone(), until);
+                    buffer.evict(config);
+                    if reached || buffer.bytes >= config.capacity_bytes {
+                        return;
+                    }
+                    from = until + 1;
+                }
+            });
+        }
+    });
+}
+
+/// Decodes a message with a precompiled plan, or the rule of its topic, into pretty-printed JSON.
+///
+/// Blob-bearing messages without a rule are kept as a preview, so that images do not fill the
+/// buffer with megabytes of JSON numbers.
+fn decode_pretty(
+    type_name: &str,
+    rule: Option<&DecodeRule>,
+    plan: Option<&DecodePlan>,
+    data: &[u8],
+) -> RosPeekResult<String> {
+    let value = decode_with_rule(rule, &mut CdrDecoder::new(&[]), plan, data)?;
+    if rule.is_none() && is_blob_type(type_name) {
+        return Ok(preview_blob(type_name, value));
+    }
+    Ok(serde_json::to_string_pretty(&value)?)
+}
@@ -0,0 +1,80 @@
+use rospeek_core::TypeName;
+use serde_json::{Value, json};
+
+/// Message types carrying large binary payloads, which the Auto view summarizes.
+const BLOB_TYPES: &[&str] = &[
+    "sensor_msgs/msg/Image",
+    "sensor_msgs/msg/CompressedImage",
+    "sensor_msgs/msg/PointCloud2",
+];
+
+/// Number of payload bytes kept in a preview.
+const PREVIEW_BYTES: usize = 32;
+
+/// Returns whether messages of a type are summarized rather than shown in full.
+pub(crate) fn is_blob_type(type_name: &str) -> bool {
+    BLOB_TYPES.contains(&type_name)
+}
+
+/// Renders a blob-bearing message as a summary line followed by its fields, with the payload
+/// truncated to its first bytes.
+///
+/// # Arguments
+/// * `type_name` - Name of the message type, such as `sensor_msgs/msg/Image`.
+/// * `value` - The decoded message.
+pub(crate) fn preview_blob(type_name: &str, mut value: Value) -> String {
+    let summary = summarize(type_name, &value);
+    if let Some(data) = value.get_mut("data")
+        && let Some(bytes) = data.as_array()
+    {
+        *data = json!(truncate_bytes(bytes));
+    }
+    let body = serde_json::to_string_pretty(&value).unwrap_or_default();
+    format!("{summary}\n{body}")
+}
+
+/// Describes the payload of a message, such as image dimensions or number of points.
+fn summarize(type_name: &str, value: &Value) -> String {
+    let number = |field: &str| value[field].as_u64().unwrap_or_default();
+    let text = |field: &str| value[field].as_str().unwrap_or("-").to_string();
+    let bytes = value["data"].as_array().map_or(0, Vec::len);
+
+    match TypeName::parse(type_name).map_or(type_name, |t| t.name) {
+        "Image" => format!(
+            "Image {}x{}, encoding {}, step {}, {bytes} bytes",
+            number("width"),
+            number("height"),
+            text("encoding"),
+            number("step"),
+        ),
+        "CompressedImage" => format!("CompressedImage format {}, {bytes} bytes", text("format")),
+        "PointCloud2" => {
+            let fields: Vec<_> = value["fields"]
+                .as_array()
+                .map(|fields| fields.iter().filter_map(|f| f["name"].as_str()).collect())
+                .unwrap_or_default();
+            format!(
+                "PointCloud2 {} points ({}x{}), fields {}, point step {}, {bytes} bytes",
+                number("width") * number("height"),
+                number("width"),
+                number("height"),
+                fields.join(","),
+                number("point_step"),
+            )
+        }
+        _ => format!("{type_name}, {bytes} bytes"),
+    }
+}
+
+/// Formats the first bytes of a payload as hex, noting how many were left out.
+fn truncate_bytes(bytes: &[Value]) -> String {
+    let mut preview: Vec<String> = bytes
+        .iter()
+        .take(PREVIEW_BYTES)
+        .map(|b| format!("{:02x}", b.as_u64().unwrap_or_default()))
+        .collect();
+    if bytes.len() > PREVIEW_BYTES {
+        preview.push(format!("... (+{} bytes)", bytes.len() - PREVIEW_BYTES));
+    }
+    preview.join(" ")
+}
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, bail};
+use rospeek_core::{RawMessage, RosPeekResult, Topic};
+use serde_json::Value;
+
+use crate::backend::Backend;
+
+/// Backend browsing a recorded bag hosted by a remote server over plain HTTP.
+///
+/// Every read is a blocking `GET` answered with JSON, so the bag is browsed like a local one but
+/// nothing is pushed: there is no streaming or WebSocket channel, and messages recorded after
+/// connecting only show up once the bag is reopened.
+///
+/// The server is expected to expose the following JSON endpoints:
+/// * `GET /topics` - An array of topics, with the same fields as [`Topic`].
+/// * `GET /stats` - An object with the `start_ns` and `end_ns` of the bag.
+/// * `GET /messages?topic=..&limit=..[&offset=..][&start_ns=..]` - An array of
+///   `{"timestamp", "topic_id", "data"}` objects, where `data` is the hex-encoded CDR payload.
+/// * `GET /messages/previous?topic=..&before_ns=..` - The last message strictly before
+///   `before_ns` in the same format, or `null`.
+pub struct RemoteBackend {
+    agent: ureq::Agent,
+    /// Base URL of the server, without a trailing `/`
+    base: String,
+}
+
+impl RemoteBackend {
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Connects to a remote server and checks that it answers.
+    ///
+    /// # Arguments
+    /// * `url` - Base URL of the server, such as `http://vehicle:8080/bags/latest`.
+    pub fn connect(url: &str) -> RosPeekResult<Self> {
+        if !url.starts_with("http://") {
+            bail!("Only http:// URLs are supported: {url}");
+        }
+
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Self::TIMEOUT))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        let backend = Self {
+            agent,
+            base: url.trim_end_matches('/').to_string(),
+        };
+        backend.topics()?;
+        Ok(backend)
+    }
+
+    fn get(&self, path_and_query: &str) -> RosPeekResult<Value> {
+        let mut response = self
+            .agent
+            .get(format!("{}{path_and_query}", self.base))
+            .header("Accept", "application/json")
+            .call()?;
+        let status = response.status().as_u16();
+        if status != 200 {
+            bail!("Remote request {path_and_query} failed with status {status}");
+        }
+
+        // payloads of large messages exceed the default limit of the body
+        Ok(serde_json::from_reader(response.body_mut().as_reader())?)
+    }
+}
+
+impl Backend for RemoteBackend {
+    fn topics(&self) -> RosPeekResult<Vec<Topic>> {
+        let value = self.get("/topics")?;
+        value
+            .as_array()
+            .ok_or_else(|| anyhow!("Expected an array of topics"))?
+            .iter()
+            .map(to_topic)
+            .collect()
+    }
+
+    fn time_range(&self) -> RosPeekResult<(u64, u64)> {
+        let value = self.get("/stats")?;
+        Ok((
+            value["start_ns"].as_u64().unwrap_or_default(),
+            value["end_ns"].as_u64().unwrap_or_default(),
+        ))
+    }
+
+    fn read_messages(
+        &self,
+        topic: &str,
+        start_ns: Option<u64>,
+        limit: usize,
+        offset: Option<usize>,
+    ) -> RosPeekResult<Vec<RawMessage>> {
+        let mut query = format!("/messages?topic={}&limit={limit}", encode_query(topic));
+        if let Some(offset) = offset {
+            query.push_str(&format!("&offset={offset}"));
+        }
+        if let Some(start) = start_ns {
+            query.push_str(&format!("&start_ns={start}"));
+        }
+
+        let value = self.get(&query)?;
+        value
+            .as_array()
+            .ok_or_else(|| anyhow!("Expected an array of messages"))?
+            .iter()
+            .map(to_message)
+            .collect()
+    }
+
+    fn next_message(&self, topic: &str, after_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        let mut messages = self.read_messages(topic, Some(after_ns.saturating_add(1)), 1, None)?;
+        Ok(messages.pop())
+    }
+
+    fn prev_message(&self, topic: &str, before_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        let value = self.get(&format!(
+            "/messages/previous?topic={}&before_ns={before_ns}",
+            encode_query(topic)
+        ))?;
+        if value.is_null() {
+            return Ok(None);
+        }
+        to_message(&value).map(Some)
+    }
+}
+
+fn to_topic(value: &Value) -> RosPeekResult<Topic> {
+    let text = |key: &str| value[key].as_str().map(str::to_string);
+    Ok(Topic {
+        id: value["id"].as_u64().unwrap_or_default() as u16,
+        name: text("name").ok_or_else(|| anyhow!("Topic without name"))?,
+        type_name: text("type_name").unwrap_or_default(),
+        count: value["count"].as_u64().unwrap_or_default(),
+        serialization_format: text("serialization_format").unwrap_or_default(),
+        offered_qos_profiles: text("offered_qos_profiles"),
+    })
+}
+
+fn to_message(value: &Value) -> RosPeekResult<RawMessage> {
+    let data = value["data"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Message without data"))?;
+    Ok(RawMessage {
+        timestamp: value["timestamp"].as_u64().unwrap_or_default(),
+        topic_id: value["topic_id"].as_u64().unwrap_or_default() as u16,
+        data: decode_hex(data)?,
+    })
+}
+
+fn decode_hex(s: &str) -> RosPeekResult<Vec<u8>> {
+    if !s.is_ascii() || !s.len().is_multiple_of(2) {
+        bail!("Malformed hex payload");
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(Into::into))
+        .collect()
+}
+
+/// Percent-encodes a query value, keeping unreserved characters and `/` as is.
+fn encode_query(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
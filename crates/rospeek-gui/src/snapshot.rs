@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use egui::{ColorImage, Rect, UserData, ViewportCommand};
+use rfd::FileDialog;
+use rospeek_core::RosPeekResult;
+
+/// Region of the window to save once the screenshot of the next frame arrives.
+#[derive(Debug, Clone)]
+struct Request {
+    rect: Rect,
+    path: PathBuf,
+}
+
+/// Exports views of the app as PNG images using the screenshot of the viewport.
+#[derive(Debug, Default)]
+pub(crate) struct Snapshots {
+    pending: Option<Request>,
+}
+
+impl Snapshots {
+    /// Asks where to save a view, then requests a screenshot of the next frame.
+    ///
+    /// # Arguments
+    /// * `ctx` - The egui context of the viewport.
+    /// * `rect` - Region of the view in points.
+    /// * `name` - Default file name without extension.
+    pub(crate) fn request(&mut self, ctx: &egui::Context, rect: Rect, name: &str) {
+        let Some(path) = FileDialog::new()
+            .add_filter("PNG image", &["png"])
+            .set_file_name(format!("{name}.png"))
+            .save_file()
+        else {
+            return;
+        };
+        self.pending = Some(Request { rect, path });
+        ctx.send_viewport_cmd(ViewportCommand::Screenshot(UserData::default()));
+    }
+
+    /// Saves the requested view if its screenshot arrived in this frame.
+    ///
+    /// # Arguments
+    /// * `ctx` - The egui context of the viewport.
+    ///
+    /// # Returns
+    /// The path of the saved image, or `None` while no screenshot arrived.
+    pub(crate) fn poll(&mut self, ctx: &egui::Context) -> Option<RosPeekResult<PathBuf>> {
+        self.pending.as_ref()?;
+        let image = ctx.input(|input| {
+            input.raw.events.iter().find_map(|event| match event {
+                egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                _ => None,
+            })
+        })?;
+        let request = self.pending.take()?;
+        let view = image.region(&request.rect, Some(ctx.pixels_per_point()));
+        Some(save_png(&view, &request.path).map(|_| request.path))
+    }
+}
+
+/// Saves an image as PNG.
+///
+/// # Arguments
+/// * `image` - The image to save.
+/// * `path` - Path to the PNG file.
+pub fn save_png<P: AsRef<Path>>(image: &ColorImage, path: P) -> RosPeekResult<()> {
+    let [width, height] = image.size;
+    let rgba: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+        .collect();
+    image::save_buffer_with_format(
+        path.as_ref(),
+        &rgba,
+        width as u32,
+        height as u32,
+        image::ExtendedColorType::Rgba8,
+        image::ImageFormat::Png,
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to save {}: {e}", path.as_ref().display()))
+}
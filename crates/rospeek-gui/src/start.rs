@@ -0,0 +1,157 @@
+use std::path::{Path, PathBuf};
+
+use rospeek_core::ns_to_iso;
+
+use crate::{
+    catalog::{Catalog, CatalogEntry, CatalogQuery, default_catalog_path},
+    i18n::Language,
+};
+
+/// Key of the recently opened bags in the storage of the app.
+pub(crate) const RECENT_KEY: &str = "recent_bags";
+
+/// Number of recently opened bags kept.
+const MAX_RECENT: usize = 10;
+
+/// Number of catalog entries listed at once.
+const MAX_ENTRIES: usize = 200;
+
+/// Screen shown until a bag is opened, listing recently opened bags and the bags of the catalog.
+#[derive(Default)]
+pub(crate) struct StartScreen {
+    /// Recently opened bags, the latest first
+    recent: Vec<PathBuf>,
+    /// Catalog written by `rospeek catalog add`, if there is one
+    catalog: Option<Catalog>,
+    topic_filter: String,
+    /// Catalog entries matching the filter
+    entries: Vec<CatalogEntry>,
+    /// Error of the last catalog search
+    error: Option<String>,
+}
+
+impl StartScreen {
+    /// Creates the start screen, opening the default catalog if it exists.
+    ///
+    /// # Arguments
+    /// * `recent` - Recently opened bags, the latest first.
+    pub(crate) fn new(recent: Vec<String>) -> Self {
+        let catalog = default_catalog_path()
+            .ok()
+            .filter(|path| path.exists())
+            .and_then(|path| Catalog::open(&path).ok());
+        let mut screen = Self {
+            recent: recent.into_iter().map(PathBuf::from).collect(),
+            catalog,
+            ..Default::default()
+        };
+        screen.search();
+        screen
+    }
+
+    /// Returns the recently opened bags to persist.
+    pub(crate) fn recent(&self) -> Vec<String> {
+        self.recent
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect()
+    }
+
+    /// Moves a bag to the top of the recently opened bags.
+    pub(crate) fn push_recent(&mut self, path: &Path) {
+        self.recent.retain(|p| p != path);
+        self.recent.insert(0, path.to_path_buf());
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    fn search(&mut self) {
+        let Some(catalog) = &self.catalog else {
+            return;
+        };
+        let filter = self.topic_filter.trim();
+        let query = CatalogQuery {
+            topic: (!filter.is_empty()).then(|| {
+                if filter.contains(['*', '?']) {
+                    filter.to_string()
+                } else {
+                    format!("*{filter}*")
+                }
+            }),
+            ..Default::default()
+        };
+        match catalog.search(&query) {
+            Ok(mut entries) => {
+                // latest recordings first
+                entries.reverse();
+                entries.truncate(MAX_ENTRIES);
+                self.entries = entries;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// Shows the recently opened bags and the catalog.
+    ///
+    /// # Arguments
+    /// * `ui` - The UI to show the screen in.
+    /// * `lang` - Language of the labels.
+    ///
+    /// # Returns
+    /// The bag the user chose to open.
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, lang: Language) -> Option<PathBuf> {
+        let mut selected = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.heading(lang.tr("Recent bags"));
+            if self.recent.is_empty() {
+                ui.label(lang.tr("No bags opened yet. Use \"Open bag...\" above."));
+            }
+            for path in self.recent.iter() {
+                if ui.link(path.display().to_string()).clicked() {
+                    selected = Some(path.clone());
+                }
+            }
+
+            if self.catalog.is_none() {
+                return;
+            }
+            ui.add_space(12.0);
+            ui.heading(lang.tr("Catalog"));
+            ui.horizontal(|ui| {
+                ui.label(lang.tr("Topic Filter"));
+                if ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.topic_filter)
+                            .hint_text("/sensing/radar/*"),
+                    )
+                    .changed()
+                {
+                    self.search();
+                }
+            });
+            if let Some(error) = &self.error {
+                ui.colored_label(egui::Color32::RED, error.as_str());
+            }
+            egui::Grid::new("catalog")
+                .striped(true)
+                .num_columns(5)
+                .show(ui, |ui| {
+                    for label in ["Path", "Start", "Duration (s)", "Messages", "Size (GiB)"] {
+                        ui.strong(lang.tr(label));
+                    }
+                    ui.end_row();
+                    for entry in self.entries.iter() {
+                        if ui.link(entry.path.as_str()).clicked() {
+                            selected = Some(PathBuf::from(&entry.path));
+                        }
+                        ui.label(ns_to_iso(entry.start_ns));
+                        ui.label(format!("{:.1}", entry.duration_sec));
+                        ui.label(entry.message_count.to_string());
+                        ui.label(format!("{:.3}", entry.size_gib));
+                        ui.end_row();
+                    }
+                });
+        });
+        selected
+    }
+}
@@ -0,0 +1,335 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use crossbeam_channel as channel;
+use egui::{Color32, ColorImage, Rect, Sense, TextureHandle, TextureOptions, pos2, vec2};
+use rospeek_core::{RosPeekResult, ns_to_iso};
+
+use crate::{
+    backend::Backend,
+    images::{ImageMessage, RawImage},
+    playback::Playback,
+};
+
+/// Height in pixels of the thumbnails.
+const THUMBNAIL_HEIGHT: usize = 48;
+/// Width of a slot of the strip, each slot showing the message nearest to its center.
+const SLOT_WIDTH: f32 = 80.0;
+/// Number of decoded thumbnails kept by the worker, so that resizing or going back to a topic
+/// does not decode them again.
+const CACHE_CAPACITY: usize = 512;
+
+/// A thumbnail of the message nearest to the center of a slot of the strip.
+#[derive(Clone)]
+struct Thumbnail {
+    /// Timestamp of the message the thumbnail was made from.
+    timestamp: u64,
+    image: Arc<ColorImage>,
+}
+
+/// Slots of the strip being filled by the worker.
+#[derive(Default)]
+struct Strip {
+    /// Topic, time range and number of slots the thumbnails were requested for.
+    key: Option<StripKey>,
+    slots: Vec<Option<Thumbnail>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StripKey {
+    topic: String,
+    type_name: String,
+    start_ns: u64,
+    end_ns: u64,
+    slots: usize,
+}
+
+impl StripKey {
+    /// Returns the timestamp at the center of a slot.
+    fn slot_ns(&self, slot: usize) -> u64 {
+        let span = (self.end_ns - self.start_ns) as f64;
+        self.start_ns + ((slot as f64 + 0.5) / self.slots as f64 * span) as u64
+    }
+}
+
+/// Least-recently-used cache of thumbnails keyed by topic and message timestamp.
+#[derive(Default)]
+struct ThumbnailCache {
+    entries: VecDeque<((String, u64), Arc<ColorImage>)>,
+}
+
+impl ThumbnailCache {
+    fn get(&mut self, topic: &str, timestamp: u64) -> Option<Arc<ColorImage>> {
+        let index = self
+            .entries
+            .iter()
+            .position(|((t, ts), _)| t == topic && *ts == timestamp)?;
+        let entry = self.entries.remove(index)?;
+        let image = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(image)
+    }
+
+    fn insert(&mut self, topic: String, timestamp: u64, image: Arc<ColorImage>) {
+        self.entries.push_back(((topic, timestamp), image));
+        while self.entries.len() > CACHE_CAPACITY {
+            self.entries.pop_front();
+        }
+    }
+}
+
+/// Renders sparse thumbnails of an image topic along the timeline.
+///
+/// Thumbnails are decoded lazily on a worker thread, one per slot of the strip, and cached so
+/// that resizing the window or going back to a topic only decodes the slots not seen before.
+pub struct ThumbnailStrip {
+    strip: Arc<Mutex<Strip>>,
+    tx: channel::Sender<StripKey>,
+    /// Textures of the thumbnails shown, keyed by message timestamp
+    textures: HashMap<u64, TextureHandle>,
+    requested: Option<StripKey>,
+}
+
+impl ThumbnailStrip {
+    /// Creates a strip whose worker reads messages from a backend.
+    ///
+    /// # Arguments
+    /// * `backend` - The backend to read messages from.
+    /// * `ctx` - The context to repaint as thumbnails arrive.
+    pub fn new(backend: Arc<dyn Backend>, ctx: egui::Context) -> Self {
+        let strip = Arc::new(Mutex::new(Strip::default()));
+        let (tx, rx) = channel::unbounded::<StripKey>();
+
+        let shared = strip.clone();
+        std::thread::spawn(move || {
+            let mut cache = ThumbnailCache::default();
+            while let Ok(key) = rx.recv() {
+                // only the latest request matters
+                let key = rx.try_iter().last().unwrap_or(key);
+                {
+                    let mut strip = shared.lock().unwrap();
+                    strip.slots = vec![None; key.slots];
+                    strip.key = Some(key.clone());
+                }
+                for slot in 0..key.slots {
+                    if !rx.is_empty() {
+                        break;
+                    }
+                    let thumbnail = thumbnail_at(backend.as_ref(), &mut cache, &key, slot);
+                    let mut strip = shared.lock().unwrap();
+                    if strip.key.as_ref() == Some(&key) {
+                        strip.slots[slot] = thumbnail;
+                    }
+                    ctx.request_repaint();
+                }
+            }
+        });
+
+        Self {
+            strip,
+            tx,
+            textures: HashMap::new(),
+            requested: None,
+        }
+    }
+
+    /// Requests the thumbnails of a topic over a time range, unless they are already requested.
+    fn request(&mut self, key: StripKey) {
+        if self.requested.as_ref() == Some(&key) {
+            return;
+        }
+        if self
+            .requested
+            .as_ref()
+            .is_none_or(|requested| requested.topic != key.topic)
+        {
+            self.textures.clear();
+        }
+        self.requested = Some(key.clone());
+        let _ = self.tx.send(key);
+    }
+
+    /// Returns the texture of a thumbnail, uploading it on first use.
+    fn texture(&mut self, ctx: &egui::Context, thumbnail: &Thumbnail) -> TextureHandle {
+        self.textures
+            .entry(thumbnail.timestamp)
+            .or_insert_with(|| {
+                ctx.load_texture(
+                    format!("thumbnail-{}", thumbnail.timestamp),
+                    (*thumbnail.image).clone(),
+                    TextureOptions::LINEAR,
+                )
+            })
+            .clone()
+    }
+}
+
+/// Draws the thumbnails of an image topic along the whole time range of the playback.
+///
+/// Clicking a thumbnail seeks the cursor to the message it was made from.
+///
+/// # Arguments
+/// * `ui` - The UI to draw the strip in.
+/// * `strip` - The strip holding the thumbnails.
+/// * `playback` - The playback whose time range is covered and whose cursor is moved.
+/// * `topic` - Name of the image topic.
+/// * `type_name` - Message type of the topic.
+///
+/// # Returns
+/// `true` if the cursor was moved by the user.
+pub fn thumbnail_strip(
+    ui: &mut egui::Ui,
+    strip: &mut ThumbnailStrip,
+    playback: &mut Playback,
+    topic: &str,
+    type_name: &str,
+) -> bool {
+    let width = ui.available_width().max(SLOT_WIDTH);
+    let height = THUMBNAIL_HEIGHT as f32 + 4.0;
+    let (rect, response) = ui.allocate_exact_size(vec2(width, height), Sense::click());
+    if playback.end_ns <= playback.start_ns {
+        return false;
+    }
+
+    let slots = (width / SLOT_WIDTH).floor().max(1.0) as usize;
+    strip.request(StripKey {
+        topic: topic.to_string(),
+        type_name: type_name.to_string(),
+        start_ns: playback.start_ns,
+        end_ns: playback.end_ns,
+        slots,
+    });
+
+    let thumbnails: Vec<Option<Thumbnail>> = {
+        let shared = strip.strip.lock().unwrap();
+        match shared.key.as_ref() {
+            Some(key) if Some(key) == strip.requested.as_ref() => shared.slots.clone(),
+            _ => Vec::new(),
+        }
+    };
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    let slot_width = rect.width() / slots as f32;
+    let hovered = response
+        .hover_pos()
+        .map(|pos| ((pos.x - rect.left()) / slot_width) as usize);
+    for (i, thumbnail) in thumbnails.iter().enumerate() {
+        let slot = Rect::from_min_size(
+            pos2(rect.left() + i as f32 * slot_width, rect.top()),
+            vec2(slot_width, rect.height()),
+        )
+        .shrink(2.0);
+        let Some(thumbnail) = thumbnail else {
+            continue;
+        };
+        let [w, h] = thumbnail.image.size;
+        let scale = (slot.width() / w as f32).min(slot.height() / h as f32);
+        let image = Rect::from_center_size(slot.center(), vec2(w as f32, h as f32) * scale);
+        let texture = strip.texture(ui.ctx(), thumbnail);
+        painter.image(
+            texture.id(),
+            image,
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        if hovered == Some(i) {
+            painter.rect_stroke(
+                image,
+                0.0,
+                (1.5, ui.visuals().selection.stroke.color),
+                egui::StrokeKind::Outside,
+            );
+        }
+    }
+
+    let hovered = hovered
+        .and_then(|i| thumbnails.get(i))
+        .and_then(Option::as_ref);
+    if response.clicked()
+        && let Some(thumbnail) = hovered
+    {
+        playback.seek(thumbnail.timestamp);
+        return true;
+    }
+    if let Some(thumbnail) = hovered {
+        response.on_hover_text(ns_to_iso(thumbnail.timestamp));
+    }
+    false
+}
+
+/// Makes the thumbnail of the message nearest to the center of a slot, if any.
+fn thumbnail_at(
+    backend: &dyn Backend,
+    cache: &mut ThumbnailCache,
+    key: &StripKey,
+    slot: usize,
+) -> Option<Thumbnail> {
+    let message = backend
+        .find_nearest(&key.topic, key.slot_ns(slot))
+        .ok()
+        .flatten()?;
+    let image = match cache.get(&key.topic, message.timestamp) {
+        Some(image) => image,
+        None => {
+            let image = decode_thumbnail(&key.type_name, &message.data, THUMBNAIL_HEIGHT)
+                .map_err(|e| eprintln!("Failed to decode a thumbnail of {}: {e}", key.topic))
+                .ok()?;
+            let image = Arc::new(image);
+            cache.insert(key.topic.clone(), message.timestamp, image.clone());
+            image
+        }
+    };
+    Some(Thumbnail {
+        timestamp: message.timestamp,
+        image,
+    })
+}
+
+/// Decodes an image message into a thumbnail no taller than a given height.
+///
+/// Raw images are read straight from the CDR data without decoding the pixels into JSON.
+///
+/// # Arguments
+/// * `type_name` - `sensor_msgs/msg/Image` or `sensor_msgs/msg/CompressedImage`.
+/// * `data` - CDR-encoded message.
+/// * `max_height` - Height of the thumbnail, smaller images keeping their size.
+pub fn decode_thumbnail(
+    type_name: &str,
+    data: &[u8],
+    max_height: usize,
+) -> RosPeekResult<ColorImage> {
+    match ImageMessage::from_cdr(type_name, data)? {
+        ImageMessage::Raw(image) => raw_thumbnail(&image, max_height),
+        ImageMessage::Compressed { data, .. } => {
+            let image = image::load_from_memory(data)?;
+            let height = (image.height() as usize).min(max_height).max(1);
+            let width = (image.width() as usize * height / image.height().max(1) as usize).max(1);
+            let thumbnail = image.thumbnail(width as u32, height as u32).to_rgba8();
+            Ok(ColorImage::from_rgba_unmultiplied(
+                [thumbnail.width() as usize, thumbnail.height() as usize],
+                thumbnail.as_raw(),
+            ))
+        }
+    }
+}
+
+/// Downscales a raw image by sampling the nearest pixels.
+fn raw_thumbnail(image: &RawImage, max_height: usize) -> RosPeekResult<ColorImage> {
+    let bytes_per_pixel = image.bytes_per_pixel()?;
+    let height = image.height.min(max_height);
+    let width = (image.width * height / image.height).max(1);
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        for x in 0..width {
+            rgba.extend_from_slice(&image.rgba(
+                x * image.width / width,
+                y * image.height / height,
+                bytes_per_pixel,
+            ));
+        }
+    }
+    Ok(ColorImage::from_rgba_unmultiplied([width, height], &rgba))
+}
@@ -0,0 +1,164 @@
+use egui::{Color32, CursorIcon, Pos2, Rect, Sense, Stroke, pos2, vec2};
+use rospeek_core::LabeledEvent;
+
+use crate::{backend::Segment, playback::Playback};
+
+const BAR_HEIGHT: f32 = 18.0;
+const HANDLE_WIDTH: f32 = 6.0;
+
+/// Colors of the event bands, assigned to labels in order of appearance.
+const EVENT_COLORS: [Color32; 6] = [
+    Color32::from_rgb(76, 120, 168),
+    Color32::from_rgb(245, 133, 24),
+    Color32::from_rgb(84, 162, 75),
+    Color32::from_rgb(178, 121, 162),
+    Color32::from_rgb(238, 202, 59),
+    Color32::from_rgb(157, 117, 93),
+];
+
+/// Draws the timeline bar of the playback.
+///
+/// Clicking or dragging on the bar seeks the cursor, and the handles of the selected
+/// region can be dragged to resize it. The boundaries between the files of a concatenated
+/// bag are marked, and hovering the bar shows the file under the pointer. Labeled events are
+/// drawn as colored bands, a color per label, and hovering one shows its label.
+///
+/// # Arguments
+/// * `ui` - The UI to draw the bar in.
+/// * `playback` - The playback whose cursor and region are shown.
+/// * `segments` - The files of a concatenated bag, empty for a single bag.
+/// * `events` - Labeled events shown as bands, such as those extracted by `rospeek events`.
+///
+/// # Returns
+/// `true` if the cursor was moved by the user.
+pub fn timeline_bar(
+    ui: &mut egui::Ui,
+    playback: &mut Playback,
+    segments: &[Segment],
+    events: &[LabeledEvent],
+) -> bool {
+    let width = ui.available_width().max(100.0);
+    let (rect, response) = ui.allocate_exact_size(vec2(width, BAR_HEIGHT), Sense::click_and_drag());
+
+    let (start_ns, end_ns) = (playback.start_ns, playback.end_ns);
+    if end_ns <= start_ns {
+        return false;
+    }
+    let span = (end_ns - start_ns) as f64;
+    let to_x =
+        |ns: u64| rect.left() + (ns.saturating_sub(start_ns) as f64 / span) as f32 * rect.width();
+    let to_ns = |x: f32| {
+        let ratio = ((x - rect.left()) / rect.width()).clamp(0.0, 1.0) as f64;
+        start_ns + (ratio * span) as u64
+    };
+
+    // handles are interacted before painting so that dragging them does not seek
+    let mut region = playback.region();
+    let mut dragging_handle = false;
+    if let Some((a_ns, b_ns)) = region.as_mut() {
+        for (name, ns) in [("a", a_ns), ("b", b_ns)] {
+            let x = to_x(*ns);
+            let handle =
+                Rect::from_center_size(pos2(x, rect.center().y), vec2(HANDLE_WIDTH, BAR_HEIGHT));
+            let handle = ui
+                .interact(handle, response.id.with(name), Sense::drag())
+                .on_hover_cursor(CursorIcon::ResizeHorizontal);
+            if handle.dragged()
+                && let Some(pos) = handle.interact_pointer_pos()
+            {
+                *ns = to_ns(pos.x);
+                dragging_handle = true;
+            }
+        }
+    }
+    if dragging_handle && let Some((a_ns, b_ns)) = region {
+        playback.set_region(a_ns, b_ns);
+    }
+
+    let mut seeked = false;
+    if !dragging_handle
+        && (response.clicked() || response.dragged())
+        && let Some(pos) = response.interact_pointer_pos()
+    {
+        playback.seek(to_ns(pos.x));
+        seeked = true;
+    }
+
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+    let mut labels: Vec<&str> = Vec::new();
+    for event in events {
+        let index = match labels.iter().position(|l| *l == event.label) {
+            Some(index) => index,
+            None => {
+                labels.push(&event.label);
+                labels.len() - 1
+            }
+        };
+        // events of a single message are widened to stay visible
+        let (start_x, end_x) = (
+            to_x(event.start_ns),
+            to_x(event.end_ns).max(to_x(event.start_ns) + 2.0),
+        );
+        painter.rect_filled(
+            Rect::from_x_y_ranges(start_x..=end_x, rect.y_range()),
+            0.0,
+            EVENT_COLORS[index % EVENT_COLORS.len()].gamma_multiply(0.6),
+        );
+    }
+    if let Some((a_ns, b_ns)) = playback.region() {
+        let (a_x, b_x) = (to_x(a_ns), to_x(b_ns));
+        let selection = Rect::from_x_y_ranges(a_x..=b_x, rect.y_range());
+        painter.rect_filled(
+            selection,
+            0.0,
+            visuals.selection.bg_fill.gamma_multiply(0.5),
+        );
+        for x in [a_x, b_x] {
+            painter.rect_filled(
+                Rect::from_center_size(pos2(x, rect.center().y), vec2(HANDLE_WIDTH, BAR_HEIGHT)),
+                1.0,
+                visuals.selection.bg_fill,
+            );
+        }
+    }
+    for segment in segments.iter().skip(1) {
+        let x = to_x(segment.start_ns);
+        painter.line_segment(
+            [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+            Stroke::new(1.5, visuals.weak_text_color()),
+        );
+    }
+    let cursor_x = to_x(playback.cursor_ns);
+    painter.line_segment(
+        [
+            Pos2::new(cursor_x, rect.top()),
+            Pos2::new(cursor_x, rect.bottom()),
+        ],
+        Stroke::new(2.0, Color32::from_rgb(230, 80, 60)),
+    );
+
+    if let Some(pos) = response.hover_pos() {
+        let ns = to_ns(pos.x);
+        let mut hover: Vec<&str> = segments
+            .iter()
+            .rev()
+            .find(|s| s.start_ns <= ns)
+            .or(segments.first())
+            .map(|segment| segment.name.as_str())
+            .into_iter()
+            .collect();
+        hover.extend(
+            events
+                .iter()
+                .filter(|e| (to_x(e.start_ns) - 1.0..=to_x(e.end_ns) + 1.0).contains(&pos.x))
+                .map(|e| e.label.as_str()),
+        );
+        if !hover.is_empty() {
+            response.on_hover_text(hover.join("\n"));
+        }
+    }
+
+    seeked
+}
@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use crossbeam_channel as channel;
+use rospeek_core::{Condition, MessageSchema, RawMessage, RosPeekResult, Topic, decode_field};
+use serde_json::Value;
+
+use crate::backend::Backend;
+
+/// Number of messages read from the backend per request.
+const BATCH_SIZE: usize = 1_000;
+
+/// Message at which the condition of a [`Trigger`] became satisfied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerHit {
+    pub topic: String,
+    pub timestamp: u64,
+    /// Value of the field satisfying the condition.
+    pub value: Value,
+}
+
+enum Request {
+    Arm(Option<(Condition, Topic)>),
+    /// The cursor jumped, as by a seek or a loop.
+    Seek(u64),
+    /// The cursor moved forward by playback.
+    Advance(u64),
+}
+
+/// Armed condition with what is needed to evaluate it.
+struct Armed {
+    condition: Condition,
+    topic: Topic,
+    schema: MessageSchema,
+    /// Whether the condition holds at the cursor, so that it only triggers again once it stopped
+    /// holding.
+    satisfied: bool,
+    cursor_ns: Option<u64>,
+}
+
+impl Armed {
+    fn evaluate(&self, message: &RawMessage) -> RosPeekResult<Option<Value>> {
+        let value = decode_field(&self.schema, &self.condition.field, &message.data)?
+            .with_context(|| format!("Field not found: {}", self.condition.field))?;
+        Ok(self.condition.holds(&value).then_some(value))
+    }
+
+    /// Moves to a cursor without triggering, taking the state of the condition from the latest
+    /// message at or before it.
+    fn seek(&mut self, backend: &dyn Backend, cursor_ns: u64) -> RosPeekResult<()> {
+        let latest = backend.prev_message(&self.topic.name, cursor_ns.saturating_add(1))?;
+        self.satisfied = match latest {
+            Some(message) => self.evaluate(&message)?.is_some(),
+            None => false,
+        };
+        self.cursor_ns = Some(cursor_ns);
+        Ok(())
+    }
+
+    /// Moves forward to a cursor, reading every message in between.
+    ///
+    /// # Returns
+    /// The first message at which the condition became satisfied, stopping there.
+    fn advance(
+        &mut self,
+        backend: &dyn Backend,
+        cursor_ns: u64,
+    ) -> RosPeekResult<Option<TriggerHit>> {
+        let Some(mut from) = self.cursor_ns.filter(|from| *from < cursor_ns) else {
+            return self.seek(backend, cursor_ns).map(|_| None);
+        };
+        loop {
+            let messages =
+                backend.read_messages(&self.topic.name, Some(from + 1), BATCH_SIZE, None)?;
+            let exhausted = messages.len() < BATCH_SIZE;
+            for message in messages {
+                if message.timestamp > cursor_ns {
+                    self.cursor_ns = Some(cursor_ns);
+                    return Ok(None);
+                }
+                from = message.timestamp;
+                let value = self.evaluate(&message)?;
+                let triggered = !self.satisfied;
+                self.satisfied = value.is_some();
+                if let Some(value) = value.filter(|_| triggered) {
+                    self.cursor_ns = Some(message.timestamp);
+                    return Ok(Some(TriggerHit {
+                        topic: self.topic.name.clone(),
+                        timestamp: message.timestamp,
+                        value,
+                    }));
+                }
+            }
+            if exhausted {
+                self.cursor_ns = Some(cursor_ns);
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// Condition pausing playback at the first message satisfying it, evaluated on a worker thread
+/// as the cursor moves.
+///
+/// Only messages played through trigger it, not those the cursor jumps over, and a condition
+/// which already holds only triggers again once it stopped holding.
+pub struct Trigger {
+    tx: channel::Sender<Request>,
+    hits: channel::Receiver<RosPeekResult<TriggerHit>>,
+    /// Cursor last sent to the worker.
+    cursor_ns: Option<u64>,
+}
+
+impl Trigger {
+    /// Starts the worker, requesting a repaint of `ctx` when the condition triggers.
+    pub fn new(backend: Arc<dyn Backend>, ctx: egui::Context) -> Self {
+        let (tx, rx) = channel::unbounded::<Request>();
+        let (txh, hits) = channel::unbounded();
+
+        std::thread::spawn(move || {
+            let mut armed: Option<Armed> = None;
+            let mut cursor_ns = None;
+            while let Ok(request) = rx.recv() {
+                // consecutive moves only matter by where they end
+                let (mut seek, mut advance) = (None, None);
+                for request in std::iter::once(request).chain(rx.try_iter()) {
+                    match request {
+                        Request::Arm(condition) => {
+                            armed = condition.and_then(|(condition, topic)| {
+                                let schema = MessageSchema::try_from(topic.type_name.as_ref());
+                                match schema {
+                                    Ok(schema) => Some(Armed {
+                                        condition,
+                                        topic,
+                                        schema,
+                                        satisfied: false,
+                                        cursor_ns: None,
+                                    }),
+                                    Err(e) => {
+                                        let _ = txh.send(Err(e));
+                                        None
+                                    }
+                                }
+                            });
+                            seek = advance.take().or(seek).or(cursor_ns);
+                        }
+                        Request::Seek(ns) => (seek, advance) = (Some(ns), None),
+                        Request::Advance(ns) => advance = Some(ns),
+                    }
+                }
+                cursor_ns = advance.or(seek).or(cursor_ns);
+                let Some(state) = armed.as_mut() else {
+                    continue;
+                };
+                let mut result = seek.map_or(Ok(()), |ns| state.seek(backend.as_ref(), ns));
+                if let (Ok(()), Some(ns)) = (&result, advance) {
+                    result = match state.advance(backend.as_ref(), ns) {
+                        Ok(Some(hit)) => {
+                            let _ = txh.send(Ok(hit));
+                            ctx.request_repaint();
+                            Ok(())
+                        }
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(e),
+                    };
+                }
+                if let Err(e) = result {
+                    // a condition which cannot be evaluated would fail at every move
+                    armed = None;
+                    let _ = txh.send(Err(e));
+                    ctx.request_repaint();
+                }
+            }
+        });
+
+        Self {
+            tx,
+            hits,
+            cursor_ns: None,
+        }
+    }
+
+    /// Arms a condition on a topic, or disarms the trigger with `None`.
+    pub fn arm(&self, condition: Option<(Condition, Topic)>) {
+        let _ = self.tx.send(Request::Arm(condition));
+    }
+
+    /// Moves the cursor the condition is evaluated up to.
+    ///
+    /// # Arguments
+    /// * `cursor_ns` - The playback cursor.
+    /// * `played` - Whether the cursor moved by playback, so that the messages played through
+    ///   are evaluated. Otherwise, or if the cursor moved backward, it jumped.
+    pub fn update(&mut self, cursor_ns: u64, played: bool) {
+        let request = match self.cursor_ns {
+            Some(previous) if previous == cursor_ns => return,
+            Some(previous) if played && previous < cursor_ns => Request::Advance(cursor_ns),
+            _ => Request::Seek(cursor_ns),
+        };
+        self.cursor_ns = Some(cursor_ns);
+        let _ = self.tx.send(request);
+    }
+
+    /// Returns the first message at which the condition triggered since the last call, or
+    /// why it could not be evaluated.
+    pub fn poll(&self) -> Option<RosPeekResult<TriggerHit>> {
+        let mut received = self.hits.try_iter();
+        let first = received.next()?;
+        // later hits were reached while the playback had not paused yet, but errors disarm it
+        Some(received.fold(first, |first, next| match next {
+            Ok(_) => first,
+            Err(e) => Err(e),
+        }))
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::{Arc, OnceLock, RwLock};
+
+use serde_json::Value;
+
+/// Panel rendering decoded messages of some types, such as the objects of a perception message
+/// as a table, shown in the inspector above the message list.
+pub trait Visualizer: Send + Sync {
+    /// Returns the name shown as the heading of the panel.
+    fn name(&self) -> &str;
+
+    /// Returns whether the visualizer renders messages of a type.
+    ///
+    /// # Arguments
+    /// * `type_name` - Name of ROS message type, such as `foo_msgs/msg/Foo`.
+    fn matches(&self, type_name: &str) -> bool;
+
+    /// Renders a decoded message.
+    ///
+    /// # Arguments
+    /// * `ui` - The UI to render into.
+    /// * `value` - The message decoded into JSON, as shown in the inspector.
+    fn show(&self, ui: &mut egui::Ui, value: &Value);
+}
+
+/// Process-wide list of the visualizers the GUI looks up by message type.
+///
+/// Applications embedding the GUI register their visualizers before calling
+/// [`crate::spawn_app`], so that proprietary message types get panels without forking the app.
+///
+/// # Examples
+/// ```
+/// use rospeek_gui::visualizer::{Visualizer, VisualizerRegistry};
+/// use serde_json::Value;
+///
+/// struct ObjectCount;
+///
+/// impl Visualizer for ObjectCount {
+///     fn name(&self) -> &str {
+///         "Objects"
+///     }
+///
+///     fn matches(&self, type_name: &str) -> bool {
+///         type_name == "autoware_perception_msgs/msg/DetectedObjects"
+///     }
+///
+///     fn show(&self, ui: &mut egui::Ui, value: &Value) {
+///         let count = value["objects"].as_array().map_or(0, Vec::len);
+///         ui.label(format!("{count} objects"));
+///     }
+/// }
+///
+/// let registry = VisualizerRegistry::global();
+/// registry.register(ObjectCount);
+/// assert!(registry.find("autoware_perception_msgs/msg/DetectedObjects").is_some());
+/// assert!(registry.find("std_msgs/msg/String").is_none());
+/// ```
+#[derive(Default)]
+pub struct VisualizerRegistry {
+    visualizers: RwLock<Vec<Arc<dyn Visualizer>>>,
+}
+
+impl VisualizerRegistry {
+    /// Returns the registry of the process.
+    pub fn global() -> &'static VisualizerRegistry {
+        static REGISTRY: OnceLock<VisualizerRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(VisualizerRegistry::default)
+    }
+
+    /// Registers a visualizer, which takes precedence over those registered before it.
+    ///
+    /// # Arguments
+    /// * `visualizer` - The visualizer.
+    pub fn register<V: Visualizer + 'static>(&self, visualizer: V) {
+        self.visualizers.write().unwrap().push(Arc::new(visualizer));
+    }
+
+    /// Returns the latest registered visualizer of a message type, if any.
+    ///
+    /// # Arguments
+    /// * `type_name` - Name of ROS message type, such as `foo_msgs/msg/Foo`.
+    pub fn find(&self, type_name: &str) -> Option<Arc<dyn Visualizer>> {
+        self.visualizers
+            .read()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|v| v.matches(type_name))
+            .cloned()
+    }
+}
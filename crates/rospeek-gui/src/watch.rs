@@ -0,0 +1,303 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, anyhow};
+use crossbeam_channel as channel;
+use rospeek_core::{MessageSchema, RosPeekResult, Topic, TopicAliases, decode_field};
+use serde_json::Value;
+
+use crate::backend::Backend;
+
+/// Longest forward step of the cursor over which every message is read, so that the extremes
+/// include the values skipped during playback. Longer steps only read the latest message, as
+/// seeks do.
+const MAX_SCAN_NS: u64 = 10_000_000_000;
+
+/// Maximum number of messages of a topic read per step of the cursor.
+const SCAN_LIMIT: usize = 10_000;
+
+/// Field of a topic watched during playback, written as the topic followed by a dot-separated
+/// field path, such as `/vehicle/status.velocity`.
+///
+/// # Examples
+/// ```
+/// use rospeek_gui::watch::WatchExpression;
+///
+/// let watch = WatchExpression::parse("/vehicle/status.velocity.linear.x").unwrap();
+/// assert_eq!(watch.topic, "/vehicle/status");
+/// assert_eq!(watch.field, "velocity.linear.x");
+/// assert_eq!(watch.to_string(), "/vehicle/status.velocity.linear.x");
+/// assert!(WatchExpression::parse("/vehicle/status").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WatchExpression {
+    /// Topic name or alias.
+    pub topic: String,
+    /// Field path, as accepted by [`decode_field`].
+    pub field: String,
+}
+
+impl WatchExpression {
+    pub fn parse(expression: &str) -> Option<Self> {
+        let (topic, field) = expression.trim().split_once('.')?;
+        (!topic.is_empty() && !field.is_empty()).then(|| Self {
+            topic: topic.to_string(),
+            field: field.to_string(),
+        })
+    }
+}
+
+impl Display for WatchExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.topic, self.field)
+    }
+}
+
+/// Value of a watch expression at the playback cursor, with the extremes of the numbers seen so
+/// far.
+///
+/// # Examples
+/// ```
+/// use rospeek_gui::watch::WatchValue;
+/// use serde_json::json;
+///
+/// let mut value = WatchValue::default();
+/// value.observe(json!(2.0), 10);
+/// value.observe(json!(-1.5), 20);
+/// value.observe(json!(0.5), 30);
+/// assert_eq!(value.value, Some(json!(0.5)));
+/// assert_eq!((value.min, value.max), (Some(-1.5), Some(2.0)));
+///
+/// value.reset_extremes();
+/// assert_eq!((value.min, value.max), (Some(0.5), Some(0.5)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchValue {
+    /// Value of the field in the latest message at or before the cursor.
+    pub value: Option<Value>,
+    /// Timestamp of that message.
+    pub timestamp: Option<u64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Why the field could not be read, if it could not.
+    pub error: Option<String>,
+}
+
+impl WatchValue {
+    /// Records the value of the field in a message, updating the extremes if it is a number.
+    pub fn observe(&mut self, value: Value, timestamp: u64) {
+        if let Some(number) = value.as_f64() {
+            self.min = Some(self.min.map_or(number, |min| min.min(number)));
+            self.max = Some(self.max.map_or(number, |max| max.max(number)));
+        }
+        self.value = Some(value);
+        self.timestamp = Some(timestamp);
+        self.error = None;
+    }
+
+    /// Restarts the extremes from the current value.
+    pub fn reset_extremes(&mut self) {
+        let number = self.value.as_ref().and_then(Value::as_f64);
+        self.min = number;
+        self.max = number;
+    }
+}
+
+enum Request {
+    Watch(Vec<(WatchExpression, Option<Topic>)>),
+    Seek(u64),
+    ResetExtremes,
+}
+
+/// Watch expression with what is needed to read it.
+struct Watched {
+    expression: WatchExpression,
+    topic: Option<Topic>,
+    schema: Option<MessageSchema>,
+    /// Cursor at which the expression was last read.
+    cursor_ns: Option<u64>,
+}
+
+impl Watched {
+    fn new(expression: WatchExpression, topic: Option<Topic>) -> Self {
+        let schema = topic
+            .as_ref()
+            .and_then(|t| MessageSchema::try_from(t.type_name.as_ref()).ok());
+        Self {
+            expression,
+            topic,
+            schema,
+            cursor_ns: None,
+        }
+    }
+}
+
+/// Reads watch expressions at the playback cursor on a worker thread, like the watch window of
+/// a debugger.
+///
+/// As the cursor moves forward, every message in between is read so that the extremes cover
+/// the values played through.
+pub struct Watcher {
+    values: Arc<Mutex<HashMap<WatchExpression, WatchValue>>>,
+    tx: channel::Sender<Request>,
+    /// Cursor last sent to the worker.
+    cursor_ns: Option<u64>,
+}
+
+impl Watcher {
+    /// Starts the worker, requesting a repaint of `ctx` whenever the values change.
+    pub fn new(backend: Arc<dyn Backend>, ctx: egui::Context) -> Self {
+        let values = Arc::new(Mutex::new(HashMap::new()));
+        let (tx, rx) = channel::unbounded::<Request>();
+
+        let shared = values.clone();
+        std::thread::spawn(move || {
+            let mut watched: Vec<Watched> = Vec::new();
+            let mut cursor_ns = None;
+            while let Ok(request) = rx.recv() {
+                // only the latest cursor position matters
+                let mut seek = None;
+                for request in std::iter::once(request).chain(rx.try_iter()) {
+                    match request {
+                        Request::Watch(expressions) => {
+                            let mut previous = std::mem::take(&mut watched);
+                            watched = expressions
+                                .into_iter()
+                                .map(|(expression, topic)| {
+                                    let name = topic.as_ref().map(|t| &t.name);
+                                    match previous.iter().position(|w| {
+                                        w.expression == expression
+                                            && w.topic.as_ref().map(|t| &t.name) == name
+                                    }) {
+                                        Some(i) => previous.swap_remove(i),
+                                        None => Watched::new(expression, topic),
+                                    }
+                                })
+                                .collect();
+                            shared.lock().unwrap().retain(|expression, _| {
+                                watched.iter().any(|w| w.expression == *expression)
+                            });
+                            // newly watched expressions are read at the current cursor
+                            seek = seek.or(cursor_ns);
+                        }
+                        Request::Seek(ns) => seek = Some(ns),
+                        Request::ResetExtremes => {
+                            shared
+                                .lock()
+                                .unwrap()
+                                .values_mut()
+                                .for_each(WatchValue::reset_extremes);
+                            ctx.request_repaint();
+                        }
+                    }
+                }
+                if let Some(ns) = seek {
+                    for w in watched.iter_mut().filter(|w| w.cursor_ns != Some(ns)) {
+                        let mut value = shared
+                            .lock()
+                            .unwrap()
+                            .get(&w.expression)
+                            .cloned()
+                            .unwrap_or_default();
+                        match read_values(backend.as_ref(), w, ns) {
+                            Ok(values) => values
+                                .into_iter()
+                                .for_each(|(timestamp, v)| value.observe(v, timestamp)),
+                            Err(e) => value.error = Some(format!("{e:#}")),
+                        }
+                        w.cursor_ns = Some(ns);
+                        shared.lock().unwrap().insert(w.expression.clone(), value);
+                    }
+                    cursor_ns = Some(ns);
+                    ctx.request_repaint();
+                }
+            }
+        });
+
+        Self {
+            values,
+            tx,
+            cursor_ns: None,
+        }
+    }
+
+    /// Sets the expressions to watch, keeping the values of those already watched.
+    ///
+    /// # Arguments
+    /// * `expressions` - The watch expressions, whose topics may be aliases.
+    /// * `topics` - The topics of the bag.
+    /// * `aliases` - The aliases of the topics.
+    pub fn watch(&self, expressions: &[WatchExpression], topics: &[Topic], aliases: &TopicAliases) {
+        let expressions = expressions
+            .iter()
+            .map(|expression| {
+                let name = aliases.resolve(&expression.topic);
+                let topic = topics.iter().find(|t| t.name == name).cloned();
+                (expression.clone(), topic)
+            })
+            .collect();
+        let _ = self.tx.send(Request::Watch(expressions));
+    }
+
+    /// Reads the expressions at the given cursor, unless they were already read there.
+    pub fn seek(&mut self, cursor_ns: u64) {
+        if self.cursor_ns != Some(cursor_ns) {
+            self.cursor_ns = Some(cursor_ns);
+            let _ = self.tx.send(Request::Seek(cursor_ns));
+        }
+    }
+
+    /// Restarts the extremes of every expression from its current value.
+    pub fn reset_extremes(&self) {
+        let _ = self.tx.send(Request::ResetExtremes);
+    }
+
+    /// Returns the value of an expression, or `None` until it is first read.
+    pub fn value(&self, expression: &WatchExpression) -> Option<WatchValue> {
+        self.values.lock().unwrap().get(expression).cloned()
+    }
+}
+
+/// Reads the values of a watched field from the messages since it was last read, or from the
+/// latest message at or before the cursor after a seek.
+///
+/// # Returns
+/// The values with the timestamps of their messages, in order.
+fn read_values(
+    backend: &dyn Backend,
+    watched: &Watched,
+    cursor_ns: u64,
+) -> RosPeekResult<Vec<(u64, Value)>> {
+    let WatchExpression { topic: name, field } = &watched.expression;
+    let topic = watched
+        .topic
+        .as_ref()
+        .with_context(|| format!("Topic not found: {name}"))?;
+    let schema = watched
+        .schema
+        .as_ref()
+        .with_context(|| format!("Message definition not found for {}", topic.type_name))?;
+
+    let messages = match watched.cursor_ns {
+        Some(previous) if previous < cursor_ns && cursor_ns - previous <= MAX_SCAN_NS => backend
+            .read_messages(&topic.name, Some(previous + 1), SCAN_LIMIT, None)?
+            .into_iter()
+            .filter(|m| m.timestamp <= cursor_ns)
+            .collect(),
+        _ => backend
+            .prev_message(&topic.name, cursor_ns.saturating_add(1))?
+            .into_iter()
+            .collect::<Vec<_>>(),
+    };
+    messages
+        .iter()
+        .map(|m| {
+            decode_field(schema, field, &m.data)?
+                .map(|value| (m.timestamp, value))
+                .ok_or_else(|| anyhow!("Field not found: {field}"))
+        })
+        .collect()
+}
@@ -0,0 +1,110 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+use crate::i18n::Language;
+
+/// Key of the zoom factor in the storage of the app.
+pub(crate) const ZOOM_KEY: &str = "zoom_factor";
+
+/// Smallest zoom factor.
+const MIN_ZOOM: f32 = 0.5;
+
+/// Largest zoom factor.
+const MAX_ZOOM: f32 = 3.0;
+
+/// Change of the zoom factor per step.
+const ZOOM_STEP: f32 = 0.1;
+
+const ZOOM_IN: [KeyboardShortcut; 2] = [
+    KeyboardShortcut::new(Modifiers::COMMAND, Key::Equals),
+    KeyboardShortcut::new(Modifiers::COMMAND, Key::Plus),
+];
+const ZOOM_OUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Minus);
+const ZOOM_RESET: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Num0);
+
+/// Scale of the UI on top of the scale of the display, which `pixels_per_point` is made of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Zoom {
+    factor: f32,
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self { factor: 1.0 }
+    }
+}
+
+impl Zoom {
+    /// Creates a zoom clamped into the supported range.
+    ///
+    /// # Arguments
+    /// * `factor` - The zoom factor, where 1.0 is the native scale of the display.
+    pub(crate) fn new(factor: f32) -> Self {
+        let factor = if factor.is_finite() { factor } else { 1.0 };
+        Self {
+            factor: factor.clamp(MIN_ZOOM, MAX_ZOOM),
+        }
+    }
+
+    pub(crate) fn factor(&self) -> f32 {
+        self.factor
+    }
+
+    fn step(&mut self, steps: f32) {
+        // round to steps so that zooming in and out returns to the same factor
+        *self = Self::new(((self.factor + steps * ZOOM_STEP) / ZOOM_STEP).round() * ZOOM_STEP);
+    }
+
+    /// Applies Ctrl+= / Ctrl+- / Ctrl+0 (Cmd on macOS) pressed since the last frame.
+    ///
+    /// # Returns
+    /// Whether the zoom factor changed.
+    pub(crate) fn handle_keys(&mut self, ctx: &egui::Context) -> bool {
+        let before = *self;
+        ctx.input_mut(|input| {
+            if ZOOM_IN.iter().any(|s| input.consume_shortcut(s)) {
+                self.step(1.0);
+            }
+            if input.consume_shortcut(&ZOOM_OUT) {
+                self.step(-1.0);
+            }
+            if input.consume_shortcut(&ZOOM_RESET) {
+                *self = Self::default();
+            }
+        });
+        *self != before
+    }
+
+    /// Shows buttons to zoom out, reset and zoom in.
+    ///
+    /// # Arguments
+    /// * `ui` - The UI to show the buttons in.
+    /// * `lang` - Language of the labels.
+    ///
+    /// # Returns
+    /// Whether the zoom factor changed.
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, lang: Language) -> bool {
+        let before = *self;
+        if ui
+            .button("−")
+            .on_hover_text(lang.tr("Zoom out (Ctrl+-)"))
+            .clicked()
+        {
+            self.step(-1.0);
+        }
+        if ui
+            .button(format!("{:.0}%", self.factor * 100.0))
+            .on_hover_text(lang.tr("Reset zoom (Ctrl+0)"))
+            .clicked()
+        {
+            *self = Self::default();
+        }
+        if ui
+            .button("+")
+            .on_hover_text(lang.tr("Zoom in (Ctrl+=)"))
+            .clicked()
+        {
+            self.step(1.0);
+        }
+        *self != before
+    }
+}
@@ -0,0 +1,60 @@
+use age::secrecy::ExposeSecret;
+use rospeek_core::{BagWriter, RawMessage, Topic};
+use rospeek_db3::Db3Writer;
+use rospeek_gui::{create_reader, encrypt_file, encrypted_path, encryption::IDENTITY_ENV};
+
+#[test]
+fn test_encrypted_bag_roundtrip() {
+    let dir = std::env::temp_dir().join(format!("rospeek-encryption-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    let topic = Topic {
+        id: 1,
+        name: "/secret".to_string(),
+        type_name: "std_msgs/msg/String".to_string(),
+        count: 1,
+        serialization_format: "cdr".to_string(),
+        offered_qos_profiles: None,
+    };
+    let message = RawMessage {
+        timestamp: 42,
+        topic_id: 1,
+        data: b"\x00\x01\x00\x00\x07\x00\x00\x00secret\x00".to_vec(),
+    };
+    let plain = dir.join("plain.db3");
+    let mut writer = Db3Writer::create(&plain).expect("Failed to create bag");
+    let id = writer.add_topic(&topic).expect("Failed to add topic");
+    writer
+        .write_message(id, &message)
+        .expect("Failed to write message");
+    writer.finish().expect("Failed to finish bag");
+    drop(writer);
+
+    let identity = age::x25519::Identity::generate();
+    let identity_file = dir.join("identity.txt");
+    std::fs::write(&identity_file, identity.to_string().expose_secret())
+        .expect("Failed to write identity");
+    let encrypted = encrypted_path(&plain);
+    encrypt_file(&plain, &encrypted, &[identity.to_public().to_string()])
+        .expect("Failed to encrypt bag");
+    std::fs::remove_file(&plain).expect("Failed to remove plain bag");
+    let bytes = std::fs::read(&encrypted).expect("Failed to read encrypted bag");
+    assert!(!bytes.windows(6).any(|w| w == b"secret"));
+
+    // SAFETY: no other test of this binary reads the environment
+    unsafe { std::env::set_var(IDENTITY_ENV, &identity_file) };
+    let reader = create_reader(&encrypted).expect("Failed to open encrypted bag");
+    let topics = reader.topics().expect("Failed to read topics");
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].name, "/secret");
+    let messages = reader
+        .read_messages("/secret")
+        .expect("Failed to read messages");
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].timestamp, 42);
+    assert_eq!(messages[0].data, message.data);
+    drop(reader);
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
@@ -0,0 +1,153 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use rospeek_core::{RawMessage, RosPeekResult, Topic};
+use rospeek_gui::{
+    backend::Backend,
+    prefetch::{PrefetchConfig, Prefetcher},
+};
+
+/// Backend of two topics interleaved in time, `/even` and `/odd`, with a message every
+/// nanosecond.
+struct InterleavedBackend {
+    end_ns: u64,
+    /// Time each read takes.
+    delay: Duration,
+}
+
+impl InterleavedBackend {
+    fn messages(&self, topic: &str) -> impl Iterator<Item = RawMessage> {
+        let first = if topic == "/even" { 0 } else { 1 };
+        (first..=self.end_ns)
+            .step_by(2)
+            .map(move |timestamp| RawMessage {
+                timestamp,
+                topic_id: first as u16,
+                data: vec![0x00, 0x01, 0x00, 0x00, timestamp as u8],
+            })
+    }
+}
+
+impl Backend for InterleavedBackend {
+    fn topics(&self) -> RosPeekResult<Vec<Topic>> {
+        Ok(["/even", "/odd"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| Topic {
+                id: id as u16,
+                name: name.to_string(),
+                type_name: "std_msgs/msg/UInt8".to_string(),
+                count: self.end_ns / 2,
+                serialization_format: "cdr".to_string(),
+                offered_qos_profiles: None,
+            })
+            .collect())
+    }
+
+    fn time_range(&self) -> RosPeekResult<(u64, u64)> {
+        Ok((0, self.end_ns))
+    }
+
+    fn read_messages(
+        &self,
+        topic: &str,
+        start_ns: Option<u64>,
+        limit: usize,
+        offset: Option<usize>,
+    ) -> RosPeekResult<Vec<RawMessage>> {
+        std::thread::sleep(self.delay);
+        Ok(self
+            .messages(topic)
+            .filter(|m| m.timestamp >= start_ns.unwrap_or_default())
+            .skip(offset.unwrap_or_default())
+            .take(limit)
+            .collect())
+    }
+
+    fn next_message(&self, topic: &str, after_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        Ok(self.messages(topic).find(|m| m.timestamp > after_ns))
+    }
+
+    fn prev_message(&self, topic: &str, before_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        Ok(self
+            .messages(topic)
+            .filter(|m| m.timestamp < before_ns)
+            .last())
+    }
+}
+
+#[test]
+fn test_prefetch_merges_topics_in_order() {
+    let backend = Arc::new(InterleavedBackend {
+        end_ns: 2000,
+        delay: Duration::ZERO,
+    });
+    let topics = backend.topics().expect("Failed to read topics");
+    let prefetcher = Prefetcher::new(
+        backend,
+        PrefetchConfig {
+            window_ns: 1000,
+            history_ns: 1000,
+            capacity_bytes: usize::MAX,
+            batch_size: 8,
+        },
+    );
+    prefetcher.follow(topics);
+    prefetcher.seek(0);
+
+    let latest = |topic: &str, cursor_ns: u64| {
+        prefetcher
+            .latest(topic, cursor_ns)
+            .map(|m| m.message.timestamp)
+    };
+    // topics are fetched in small batches on separate threads, landing in any order
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while latest("/odd", 1000) != Some(999) || latest("/even", 1000) != Some(1000) {
+        assert!(Instant::now() < deadline, "Prefetching timed out");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    for cursor_ns in 0..=1000 {
+        assert_eq!(latest("/even", cursor_ns), Some(cursor_ns - cursor_ns % 2));
+        let odd = (cursor_ns > 0).then(|| cursor_ns - (cursor_ns + 1) % 2);
+        assert_eq!(latest("/odd", cursor_ns), odd);
+    }
+}
+
+#[test]
+fn test_prefetch_cancels_on_seek() {
+    let backend = Arc::new(InterleavedBackend {
+        end_ns: 100_000,
+        delay: Duration::from_millis(20),
+    });
+    let topics = backend.topics().expect("Failed to read topics");
+    let prefetcher = Prefetcher::new(
+        backend,
+        PrefetchConfig {
+            window_ns: 10_000,
+            history_ns: 0,
+            capacity_bytes: usize::MAX,
+            batch_size: 1,
+        },
+    );
+    prefetcher.follow(topics);
+    // reading the whole window one message at a time would take well over a minute
+    prefetcher.seek(0);
+    std::thread::sleep(Duration::from_millis(100));
+    prefetcher.seek(50_000);
+
+    // the fetch for the previous cursor stops within a batch instead of reading its window
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while prefetcher
+        .latest("/even", 50_000)
+        .is_none_or(|m| m.message.timestamp != 50_000)
+    {
+        assert!(
+            Instant::now() < deadline,
+            "Seeking waited for the previous window"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+use rospeek_gui::{backend::Backend, remote::RemoteBackend};
+
+/// Serves canned responses on a local port, answering each request with the response of the
+/// first path prefix matching it.
+///
+/// # Returns
+/// The base URL of the server.
+fn serve(routes: Vec<(&'static str, String)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+    let port = listener.local_addr().expect("No local address").port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().expect("Failed to clone stream"));
+            let mut request = String::new();
+            reader
+                .read_line(&mut request)
+                .expect("Failed to read request");
+            let mut header = String::new();
+            while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+                header.clear();
+            }
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let response = routes
+                .iter()
+                .find(|(prefix, _)| path.starts_with(prefix))
+                .map_or(
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+                    |(_, r)| r,
+                );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    format!("http://127.0.0.1:{port}/bags/latest/")
+}
+
+/// Encodes a body in chunks of a few bytes, as servers streaming their responses do.
+fn chunked(body: &str) -> String {
+    let mut response =
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n"
+            .to_string();
+    for chunk in body.as_bytes().chunks(7) {
+        response.push_str(&format!("{:x}\r\n", chunk.len()));
+        response.push_str(std::str::from_utf8(chunk).expect("Non-ASCII body"));
+        response.push_str("\r\n");
+    }
+    response.push_str("0\r\n\r\n");
+    response
+}
+
+fn sized(body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[test]
+fn test_remote_chunked_responses() {
+    let url = serve(vec![
+        (
+            "/bags/latest/topics",
+            chunked(
+                r#"[{"id": 3, "name": "/odom", "type_name": "nav_msgs/msg/Odometry", "count": 2}]"#,
+            ),
+        ),
+        (
+            "/bags/latest/stats",
+            sized(r#"{"start_ns": 10, "end_ns": 20}"#),
+        ),
+        ("/bags/latest/messages/previous", sized("null")),
+        (
+            "/bags/latest/messages",
+            chunked(r#"[{"timestamp": 10, "topic_id": 3, "data": "00010000ff"}]"#),
+        ),
+    ]);
+
+    let backend = RemoteBackend::connect(&url).expect("Failed to connect");
+    let topics = backend.topics().expect("Failed to read topics");
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].name, "/odom");
+    assert_eq!(topics[0].id, 3);
+    assert_eq!(
+        backend.time_range().expect("Failed to read range"),
+        (10, 20)
+    );
+
+    let messages = backend
+        .read_messages("/odom", None, 10, None)
+        .expect("Failed to read messages");
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].timestamp, 10);
+    assert_eq!(messages[0].data, vec![0x00, 0x01, 0x00, 0x00, 0xff]);
+    assert!(
+        backend
+            .prev_message("/odom", 10)
+            .expect("Failed to read previous message")
+            .is_none()
+    );
+}
+
+#[test]
+fn test_remote_error_status() {
+    let url = serve(vec![("/bags/latest/topics", sized("[]"))]);
+
+    let backend = RemoteBackend::connect(&url).expect("Failed to connect");
+    let error = backend
+        .time_range()
+        .expect_err("Missing endpoint succeeded");
+    assert!(error.to_string().contains("404"));
+    assert!(RemoteBackend::connect("https://127.0.0.1/").is_err());
+}
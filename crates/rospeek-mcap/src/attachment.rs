@@ -0,0 +1,25 @@
+use std::collections::BTreeMap;
+
+/// A file attached to an MCAP file, such as a calibration file or a map.
+#[derive(Debug, Clone)]
+pub struct McapAttachment {
+    /// Name of the attachment, such as `calibration.yaml`.
+    pub name: String,
+    /// Media type of the attachment, such as `text/yaml`.
+    pub media_type: String,
+    /// Time at which the attachment was recorded in nanoseconds.
+    pub log_time: u64,
+    /// Time at which the attachment was created in nanoseconds.
+    pub create_time: u64,
+    /// Content of the attachment.
+    pub data: Vec<u8>,
+}
+
+/// A metadata record of an MCAP file.
+#[derive(Debug, Clone)]
+pub struct McapMetadata {
+    /// Name of the metadata record.
+    pub name: String,
+    /// Key-value pairs of the metadata record.
+    pub entries: BTreeMap<String, String>,
+}
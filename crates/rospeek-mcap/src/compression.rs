@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+/// Sizes of a chunk of an MCAP file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkCompression {
+    /// Compression of the chunk, such as `zstd` or `lz4`, empty if uncompressed.
+    pub compression: String,
+    /// Size of the chunk records in bytes.
+    pub compressed_size: u64,
+    /// Size of the chunk records after decompression in bytes.
+    pub uncompressed_size: u64,
+    /// Number of messages in the chunk.
+    pub message_count: u64,
+}
+
+impl ChunkCompression {
+    /// Returns how many times smaller the chunk is compressed, or `None` for empty chunks.
+    pub fn ratio(&self) -> Option<f64> {
+        ratio(self.uncompressed_size as f64, self.compressed_size as f64)
+    }
+}
+
+/// Sizes of the messages of a channel of an MCAP file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelCompression {
+    /// Topic of the channel.
+    pub topic: String,
+    /// Size of the message payloads in bytes.
+    pub message_bytes: u64,
+    /// Size the messages take in the compressed chunks in bytes, estimated by sharing the size of
+    /// each chunk among its channels by their message bytes.
+    pub compressed_bytes: f64,
+}
+
+impl ChannelCompression {
+    /// Returns how many times smaller the messages are compressed, or `None` without messages.
+    pub fn ratio(&self) -> Option<f64> {
+        ratio(self.message_bytes as f64, self.compressed_bytes)
+    }
+}
+
+/// Compressed and uncompressed sizes of the chunks and channels of an MCAP file, to tune the
+/// compression and chunk size of recorders.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompressionReport {
+    /// Every chunk, in file order.
+    pub chunks: Vec<ChunkCompression>,
+    /// Every channel with messages, sorted by topic.
+    pub channels: Vec<ChannelCompression>,
+}
+
+impl CompressionReport {
+    /// Returns the total compressed and uncompressed sizes of the chunks of each compression.
+    pub fn by_compression(&self) -> BTreeMap<&str, (u64, u64, usize)> {
+        let mut totals = BTreeMap::new();
+        for chunk in self.chunks.iter() {
+            let (compressed, uncompressed, count) = totals
+                .entry(chunk.compression.as_str())
+                .or_insert((0, 0, 0));
+            *compressed += chunk.compressed_size;
+            *uncompressed += chunk.uncompressed_size;
+            *count += 1;
+        }
+        totals
+    }
+
+    /// Returns the total compressed size of the chunks in bytes.
+    pub fn compressed_size(&self) -> u64 {
+        self.chunks.iter().map(|c| c.compressed_size).sum()
+    }
+
+    /// Returns the total uncompressed size of the chunks in bytes.
+    pub fn uncompressed_size(&self) -> u64 {
+        self.chunks.iter().map(|c| c.uncompressed_size).sum()
+    }
+
+    /// Returns how many times smaller the chunks are compressed, or `None` without chunks.
+    pub fn ratio(&self) -> Option<f64> {
+        ratio(
+            self.uncompressed_size() as f64,
+            self.compressed_size() as f64,
+        )
+    }
+}
+
+fn ratio(uncompressed: f64, compressed: f64) -> Option<f64> {
+    (compressed > 0.0).then(|| uncompressed / compressed)
+}
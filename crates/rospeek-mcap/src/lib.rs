@@ -1,3 +1,11 @@
+pub mod attachment;
+pub mod compression;
 pub mod reader;
+pub mod stream;
+pub mod writer;
 
+pub use attachment::{McapAttachment, McapMetadata};
+pub use compression::{ChannelCompression, ChunkCompression, CompressionReport};
 pub use reader::McapReader;
+pub use stream::{McapFollower, McapStreamSummary};
+pub use writer::McapWriter;
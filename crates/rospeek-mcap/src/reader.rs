@@ -1,69 +1,605 @@
-use std::{fs::File, path::Path};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fs::File,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
 
-use mcap::MessageStream;
+use mcap::{
+    MAGIC, Message, MessageStream,
+    read::{Summary, attachment, metadata},
+    records::ChunkIndex,
+};
 use memmap2::Mmap;
+use rayon::prelude::*;
 use rospeek_core::{
-    BagReader, BagStats, RawMessage, RosPeekResult, StorageType, Topic, ns_to_iso, size_gb,
-    to_duration_sec,
+    BagReader, BagStats, EmbeddedSchema, IncompleteSign, OpenOptions, RawMessage, RosPeekResult,
+    StorageType, TaggedMessage, Topic,
+};
+
+use crate::{
+    ChannelCompression, ChunkCompression, CompressionReport, McapAttachment, McapMetadata,
 };
 
+/// Contents of an MCAP file, mapped from disk or read from a stream.
+enum McapData {
+    Mapped(Mmap),
+    Buffered(Vec<u8>),
+}
+
+impl Deref for McapData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            McapData::Mapped(mmap) => mmap,
+            McapData::Buffered(bytes) => bytes,
+        }
+    }
+}
+
 pub struct McapReader {
-    mmap: Mmap,
-    stats: BagStats,
+    data: McapData,
+    path: PathBuf,
+    /// Statistics, computed when opening or on first use depending on [`OpenOptions`]
+    stats: OnceLock<BagStats>,
+    /// Summary section parsed once, when opening or on first use depending on [`OpenOptions`]
+    summary: OnceLock<Option<Summary>>,
+    /// Whether the file is still being written, i.e. it does not end with the closing magic yet
+    live: bool,
 }
 
 impl McapReader {
-    fn as_stream(&self) -> RosPeekResult<MessageStream<'_>> {
-        Ok(MessageStream::new(&self.mmap)?)
+    /// Iterates over the messages of the file in file order.
+    fn messages(&self) -> RosPeekResult<impl Iterator<Item = RosPeekResult<Message<'_>>>> {
+        stream_messages(&self.data, self.live)
     }
-}
 
-impl BagReader for McapReader {
-    fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
-        let fd = File::open(path.as_ref())?;
-        let mmap = unsafe { Mmap::map(&fd) }?;
+    /// Reads a file from its contents in memory instead of mapping it, such as a bag piped
+    /// through the standard input out of a container or an object storage.
+    ///
+    /// # Arguments
+    /// * `bytes` - The contents of the file.
+    /// * `name` - The name the file is reported with, such as `-` for the standard input.
+    /// * `options` - What to compute when opening rather than on first use.
+    pub fn from_bytes<P: AsRef<Path>>(
+        bytes: Vec<u8>,
+        name: P,
+        options: &OpenOptions,
+    ) -> RosPeekResult<Self> {
+        Self::with_data(McapData::Buffered(bytes), name.as_ref(), options)
+    }
 
-        // TODO(kto2828): Implement stats calculation
+    fn with_data(data: McapData, path: &Path, options: &OpenOptions) -> RosPeekResult<Self> {
+        let live = !data.ends_with(MAGIC);
+        let reader = Self {
+            data,
+            path: path.to_path_buf(),
+            stats: OnceLock::new(),
+            summary: OnceLock::new(),
+            live,
+        };
+        if options.build_index {
+            reader.summary()?;
+        }
+        if options.compute_stats {
+            let stats = reader.compute_stats()?;
+            reader.stats.get_or_init(|| stats);
+        }
+        Ok(reader)
+    }
+
+    /// Returns whether the file is still being written by a recorder.
+    ///
+    /// Such files have no summary section yet, so messages are read by scanning the file.
+    pub fn is_live(&self) -> bool {
+        self.live
+    }
+
+    /// Returns the summary section, which files still being written do not have.
+    fn summary(&self) -> RosPeekResult<Option<&Summary>> {
+        if self.live {
+            return Ok(None);
+        }
+        if let Some(summary) = self.summary.get() {
+            return Ok(summary.as_ref());
+        }
+        let summary = Summary::read(&self.data)?;
+        Ok(self.summary.get_or_init(|| summary).as_ref())
+    }
+
+    /// Computes the statistics of the file, sized by its contents since files read from a
+    /// stream are only in memory.
+    fn compute_stats(&self) -> RosPeekResult<BagStats> {
+        let mut stats = self.compute_counts()?;
+        stats.size_bytes = self.data.len() as f64 / (1024.0 * 1024.0 * 1024.0);
+        Ok(stats)
+    }
+
+    /// Computes the message count and time range of the file from its summary section, or by
+    /// scanning every message for files without one.
+    fn compute_counts(&self) -> RosPeekResult<BagStats> {
+        if let Some(summary) = self.summary()?
+            && let Some(stats) = &summary.stats
+        {
+            return Ok(BagStats::new(
+                &self.path,
+                StorageType::Mcap,
+                stats.message_count,
+                stats.message_start_time,
+                stats.message_end_time,
+            ));
+        }
+
+        let mut message_count = 0;
         let mut start_ns = u64::MAX;
         let mut end_ns = u64::MIN;
+        for message_result in self.messages()? {
+            let log_time = message_result?.log_time;
+            message_count += 1;
+            start_ns = start_ns.min(log_time);
+            end_ns = end_ns.max(log_time);
+        }
+        Ok(BagStats::new(
+            &self.path,
+            StorageType::Mcap,
+            message_count,
+            start_ns,
+            end_ns,
+        ))
+    }
 
-        let stream = MessageStream::new(&mmap)?;
+    /// Returns the attachments of the file.
+    ///
+    /// Attachments are looked up from the summary section, so files without one have none.
+    pub fn attachments(&self) -> RosPeekResult<Vec<McapAttachment>> {
+        let Some(summary) = self.summary()? else {
+            return Ok(Vec::new());
+        };
 
-        for message_result in stream.into_iter() {
-            let message = message_result?;
+        summary
+            .attachment_indexes
+            .iter()
+            .map(|index| {
+                let record = attachment(&self.data, index)?;
+                Ok(McapAttachment {
+                    name: record.name,
+                    media_type: record.media_type,
+                    log_time: record.log_time,
+                    create_time: record.create_time,
+                    data: record.data.into_owned(),
+                })
+            })
+            .collect()
+    }
 
-            let log_time = message.log_time;
+    /// Returns the metadata records of the file.
+    ///
+    /// Metadata records are looked up from the summary section, so files without one have none.
+    pub fn metadata(&self) -> RosPeekResult<Vec<McapMetadata>> {
+        let Some(summary) = self.summary()? else {
+            return Ok(Vec::new());
+        };
+
+        summary
+            .metadata_indexes
+            .iter()
+            .map(|index| {
+                let record = metadata(&self.data, index)?;
+                Ok(McapMetadata {
+                    name: record.name,
+                    entries: record.metadata,
+                })
+            })
+            .collect()
+    }
 
-            if log_time < start_ns {
-                start_ns = log_time;
+    /// Returns the compressed and uncompressed sizes of the chunks and channels of the file.
+    ///
+    /// Chunks are decompressed in parallel to measure the message bytes of each channel.
+    /// Returns `None` if the file has no chunk index, e.g. while it is being written.
+    pub fn compression_report(&self) -> RosPeekResult<Option<CompressionReport>> {
+        let Some(summary) = self.summary()? else {
+            return Ok(None);
+        };
+        if summary.chunk_indexes.is_empty() {
+            return Ok(None);
+        }
+
+        let mut indexes: Vec<_> = summary.chunk_indexes.iter().collect();
+        indexes.sort_by_key(|c| c.chunk_start_offset);
+        let measured = indexes
+            .par_iter()
+            .map(|index| -> RosPeekResult<(u64, HashMap<u16, u64>)> {
+                let mut count = 0;
+                let mut bytes = HashMap::new();
+                for message_result in summary.stream_chunk(&self.data, index)? {
+                    let message = message_result?;
+                    count += 1;
+                    *bytes.entry(message.channel.id).or_default() += message.data.len() as u64;
+                }
+                Ok((count, bytes))
+            })
+            .collect::<RosPeekResult<Vec<_>>>()?;
+
+        let mut chunks = Vec::with_capacity(indexes.len());
+        let mut channels: HashMap<u16, (u64, f64)> = HashMap::new();
+        for (index, (message_count, bytes)) in indexes.iter().zip(measured) {
+            let total: u64 = bytes.values().sum();
+            for (id, channel_bytes) in bytes {
+                let (message_bytes, compressed_bytes) = channels.entry(id).or_default();
+                *message_bytes += channel_bytes;
+                *compressed_bytes +=
+                    index.compressed_size as f64 * channel_bytes as f64 / total.max(1) as f64;
             }
-            if log_time > end_ns {
-                end_ns = log_time;
+            chunks.push(ChunkCompression {
+                compression: index.compression.clone(),
+                compressed_size: index.compressed_size,
+                uncompressed_size: index.uncompressed_size,
+                message_count,
+            });
+        }
+
+        let mut channels: Vec<_> = channels
+            .into_iter()
+            .filter_map(|(id, (message_bytes, compressed_bytes))| {
+                Some(ChannelCompression {
+                    topic: summary.channels.get(&id)?.topic.clone(),
+                    message_bytes,
+                    compressed_bytes,
+                })
+            })
+            .collect();
+        channels.sort_by(|a, b| a.topic.cmp(&b.topic));
+        Ok(Some(CompressionReport { chunks, channels }))
+    }
+
+    /// Returns the IDs of the channels of the summary section carrying any of the given topics.
+    fn channel_ids(summary: &Summary, topic_names: &[&str]) -> HashSet<u16> {
+        summary
+            .channels
+            .values()
+            .filter(|c| topic_names.contains(&c.topic.as_str()))
+            .map(|c| c.id)
+            .collect()
+    }
+
+    /// Reads messages of channels by decompressing chunks in parallel, keeping the file order.
+    ///
+    /// Chunks are decoded in batches of the thread pool size so that `limit` can stop early.
+    /// Returns `None` if the file has no chunk index to plan the reads from.
+    fn read_chunks_parallel(
+        &self,
+        channel_ids: &HashSet<u16>,
+        start_ns: Option<u64>,
+        end_ns: Option<u64>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> RosPeekResult<Option<Vec<RawMessage>>> {
+        let Some(summary) = self.summary()? else {
+            return Ok(None);
+        };
+        if summary.chunk_indexes.is_empty() {
+            return Ok(None);
+        }
+
+        // chunks without message indexes may contain any channel
+        let mut chunks: Vec<_> = summary
+            .chunk_indexes
+            .iter()
+            .filter(|c| {
+                c.message_index_offsets.is_empty()
+                    || c.message_index_offsets
+                        .keys()
+                        .any(|id| channel_ids.contains(id))
+            })
+            .collect();
+        chunks.sort_by_key(|c| c.chunk_start_offset);
+
+        let in_range = |ts: u64| start_ns.is_none_or(|s| ts >= s) && end_ns.is_none_or(|e| ts <= e);
+
+        let mut skipped = 0usize;
+        let mut results = Vec::new();
+        for batch in chunks.chunks(rayon::current_num_threads().max(1)) {
+            let decoded = batch
+                .par_iter()
+                .map(|index| -> RosPeekResult<Vec<RawMessage>> {
+                    let mut messages = Vec::new();
+                    for message_result in summary.stream_chunk(&self.data, index)? {
+                        let message = message_result?;
+                        if !channel_ids.contains(&message.channel.id)
+                            || !in_range(message.publish_time)
+                        {
+                            continue;
+                        }
+                        messages.push(RawMessage {
+                            timestamp: message.publish_time,
+                            topic_id: message.channel.id,
+                            data: message.data.into(),
+                        });
+                    }
+                    Ok(messages)
+                })
+                .collect::<RosPeekResult<Vec<_>>>()?;
+
+            for message in decoded.into_iter().flatten() {
+                if let Some(offset) = offset
+                    && skipped < offset
+                {
+                    skipped += 1;
+                    continue;
+                }
+
+                results.push(message);
+
+                if let Some(limit) = limit
+                    && results.len() >= limit
+                {
+                    return Ok(Some(results));
+                }
+            }
+        }
+
+        Ok(Some(results))
+    }
+
+    /// Returns the chunks holding messages of channels in file order, along with the summary
+    /// section they are indexed in.
+    ///
+    /// Returns `None` if a chunk has no message indexes, as messages of such files can only be
+    /// located by decompressing every chunk.
+    fn indexed_chunks(
+        &self,
+        channel_ids: &HashSet<u16>,
+    ) -> RosPeekResult<Option<(&Summary, Vec<&ChunkIndex>)>> {
+        let Some(summary) = self.summary()? else {
+            return Ok(None);
+        };
+        if summary.chunk_indexes.is_empty()
+            || summary
+                .chunk_indexes
+                .iter()
+                .any(|c| c.message_index_offsets.is_empty())
+        {
+            return Ok(None);
+        }
+
+        let mut chunks: Vec<_> = summary
+            .chunk_indexes
+            .iter()
+            .filter(|c| {
+                c.message_index_offsets
+                    .keys()
+                    .any(|id| channel_ids.contains(id))
+            })
+            .collect();
+        chunks.sort_by_key(|c| c.chunk_start_offset);
+        Ok(Some((summary, chunks)))
+    }
+
+    /// Picks a message of channels around a timestamp, decompressing only the chunks logged
+    /// around it.
+    ///
+    /// Chunks are indexed by log time, while messages are read with their publish time. So the
+    /// chunks logged over the timestamp are searched along with the last one logged before and
+    /// the first one logged after it, and messages are compared by publish time. The chunks
+    /// further away in log time are only skipped once a message is picked if every message
+    /// searched was logged at its publish time, as the log time tells nothing of the publish
+    /// time otherwise, such as for messages logged on a simulated clock or long after they were
+    /// published; then every chunk of the channels is searched.
+    /// Returns `None` if a chunk has no message indexes to select chunks from.
+    ///
+    /// # Arguments
+    /// * `channel_ids` - The channels to pick a message of.
+    /// * `timestamp` - The timestamp in nanoseconds to search around.
+    /// * `rank` - Ranks a message by its publish time, the lowest rank being picked and the
+    ///   first one in file order on ties, or `None` to leave the message out.
+    fn pick_indexed<K: Ord>(
+        &self,
+        channel_ids: &HashSet<u16>,
+        timestamp: u64,
+        rank: impl Fn(u64) -> Option<K>,
+    ) -> RosPeekResult<Option<Option<RawMessage>>> {
+        let Some((summary, chunks)) = self.indexed_chunks(channel_ids)? else {
+            return Ok(None);
+        };
+
+        // chunks may overlap in time, so search every chunk around the timestamp
+        let before = chunks
+            .iter()
+            .filter(|c| c.message_end_time < timestamp)
+            .max_by_key(|c| c.message_end_time);
+        let after = chunks
+            .iter()
+            .filter(|c| c.message_start_time > timestamp)
+            .min_by_key(|c| c.message_start_time);
+        let mut candidates: Vec<&ChunkIndex> = chunks
+            .iter()
+            .filter(|c| c.message_start_time <= timestamp && timestamp <= c.message_end_time)
+            .chain(before)
+            .chain(after)
+            .copied()
+            .collect();
+        candidates.sort_by_key(|c| c.chunk_start_offset);
+        let around = candidates.len();
+        let mut further: Vec<&ChunkIndex> = chunks
+            .iter()
+            .filter(|c| {
+                !candidates
+                    .iter()
+                    .any(|a| a.chunk_start_offset == c.chunk_start_offset)
+            })
+            .copied()
+            .collect();
+        further.sort_by_key(|c| {
+            c.message_start_time
+                .saturating_sub(timestamp)
+                .max(timestamp.saturating_sub(c.message_end_time))
+        });
+
+        let mut picked: Option<(K, Message)> = None;
+        let mut same_clock = true;
+        for (i, index) in candidates.into_iter().chain(further).enumerate() {
+            if i >= around && picked.is_some() && same_clock {
+                break;
+            }
+            for message_result in summary.stream_chunk(&self.data, index)? {
+                let message = message_result?;
+                if !channel_ids.contains(&message.channel.id) {
+                    continue;
+                }
+                same_clock &= message.log_time == message.publish_time;
+                let Some(key) = rank(message.publish_time) else {
+                    continue;
+                };
+                if picked.as_ref().is_none_or(|(best, _)| key < *best) {
+                    picked = Some((key, message));
+                }
             }
         }
 
-        let stats = BagStats {
-            path: path.as_ref().display().to_string(),
-            size_bytes: size_gb(path),
-            storage_type: StorageType::Mcap,
-            duration_sec: to_duration_sec(start_ns, end_ns),
-            start_time: ns_to_iso(start_ns),
-            end_time: ns_to_iso(end_ns),
+        Ok(Some(picked.map(|(_, message)| RawMessage {
+            timestamp: message.publish_time,
+            topic_id: message.channel.id,
+            data: message.data.into(),
+        })))
+    }
+
+    /// Returns the IDs of the channels of a topic if the file has a summary section, failing if
+    /// the topic is not in it.
+    fn indexed_channel_ids(&self, topic_name: &str) -> RosPeekResult<Option<HashSet<u16>>> {
+        let Some(summary) = self.summary()? else {
+            return Ok(None);
+        };
+        let channel_ids = Self::channel_ids(summary, &[topic_name]);
+        if channel_ids.is_empty() {
+            anyhow::bail!("Topic not found: {topic_name}");
+        }
+        Ok(Some(channel_ids))
+    }
+
+    /// Reads messages of channels by their position, counting them from the message indexes
+    /// so that only the chunks holding the requested messages are decompressed.
+    ///
+    /// Returns `None` if a chunk has no message indexes to count its messages from.
+    fn read_indexed(
+        &self,
+        channel_ids: &HashSet<u16>,
+        start_index: usize,
+        count: usize,
+    ) -> RosPeekResult<Option<Vec<RawMessage>>> {
+        let Some((summary, chunks)) = self.indexed_chunks(channel_ids)? else {
+            return Ok(None);
         };
 
-        Ok(Self { mmap, stats })
+        let mut skip = start_index;
+        let mut results = Vec::new();
+        for index in chunks {
+            if results.len() >= count {
+                break;
+            }
+            if skip > 0 {
+                let in_chunk: usize = summary
+                    .read_message_indexes(&self.data, index)?
+                    .iter()
+                    .filter(|(channel, _)| channel_ids.contains(&channel.id))
+                    .map(|(_, entries)| entries.len())
+                    .sum();
+                if skip >= in_chunk {
+                    skip -= in_chunk;
+                    continue;
+                }
+            }
+
+            for message_result in summary.stream_chunk(&self.data, index)? {
+                let message = message_result?;
+                if !channel_ids.contains(&message.channel.id) {
+                    continue;
+                }
+                if skip > 0 {
+                    skip -= 1;
+                    continue;
+                }
+                results.push(RawMessage {
+                    timestamp: message.publish_time,
+                    topic_id: message.channel.id,
+                    data: message.data.into(),
+                });
+                if results.len() >= count {
+                    break;
+                }
+            }
+        }
+
+        Ok(Some(results))
     }
+}
 
-    fn stats(&self) -> &BagStats {
-        &self.stats
+impl BagReader for McapReader {
+    fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
+        Self::open_with_options(path, &OpenOptions::default())
+    }
+
+    fn open_with_options<P: AsRef<Path>>(path: P, options: &OpenOptions) -> RosPeekResult<Self> {
+        let fd = File::open(path.as_ref())?;
+        let mmap = unsafe { Mmap::map(&fd) }?;
+        Self::with_data(McapData::Mapped(mmap), path.as_ref(), options)
+    }
+
+    fn incomplete_signs(&self) -> Vec<IncompleteSign> {
+        match self.live {
+            true => vec![IncompleteSign::MissingFooter {
+                file: self.path.display().to_string(),
+            }],
+            false => Vec::new(),
+        }
+    }
+
+    fn stats(&self) -> RosPeekResult<&BagStats> {
+        if let Some(stats) = self.stats.get() {
+            return Ok(stats);
+        }
+        let stats = self.compute_stats()?;
+        Ok(self.stats.get_or_init(|| stats))
     }
 
     fn topics(&self) -> RosPeekResult<Vec<Topic>> {
         use std::collections::HashMap;
 
-        let stream = self.as_stream()?;
+        // per-channel counts of the summary spare scanning messages, unless the writer omitted them
+        if let Some(summary) = self.summary()?
+            && let Some(stats) = &summary.stats
+            && stats.channel_message_counts.values().sum::<u64>() == stats.message_count
+        {
+            let mut topic_map = HashMap::<String, Topic>::new();
+            for channel in summary.channels.values() {
+                let count = stats
+                    .channel_message_counts
+                    .get(&channel.id)
+                    .copied()
+                    .unwrap_or_default();
+                topic_map
+                    .entry(channel.topic.clone())
+                    .and_modify(|topic| topic.count += count)
+                    .or_insert_with(|| Topic {
+                        id: channel.id,
+                        name: channel.topic.clone(),
+                        type_name: channel
+                            .schema
+                            .as_ref()
+                            .map(|s| s.name.clone())
+                            .unwrap_or_default(),
+                        count,
+                        serialization_format: channel.message_encoding.clone(),
+                        offered_qos_profiles: channel.metadata.get("offered_qos_profiles").cloned(),
+                    });
+            }
+            return Ok(topic_map.into_values().collect());
+        }
 
-        let topic_map: Result<HashMap<String, Topic>, anyhow::Error> = stream.into_iter().try_fold(
+        let topic_map: Result<HashMap<String, Topic>, anyhow::Error> = self.messages()?.try_fold(
             HashMap::<String, Topic>::new(),
             |mut acc, message_result| {
                 let message = message_result?;
@@ -82,20 +618,125 @@ impl BagReader for McapReader {
                             .unwrap_or_default(),
                         count: 1,
                         serialization_format: message.channel.message_encoding.clone(),
-                        offered_qos_profiles: None,
+                        offered_qos_profiles: message
+                            .channel
+                            .metadata
+                            .get("offered_qos_profiles")
+                            .cloned(),
                     });
 
                 Ok(acc)
             },
         );
 
-        topic_map.map(|map| map.into_values().collect())
+        let mut topic_map = topic_map?;
+
+        // channels without messages only appear in the summary section
+        if let Some(summary) = self.summary()? {
+            for channel in summary.channels.values() {
+                topic_map
+                    .entry(channel.topic.clone())
+                    .or_insert_with(|| Topic {
+                        id: channel.id,
+                        name: channel.topic.clone(),
+                        type_name: channel
+                            .schema
+                            .as_ref()
+                            .map(|s| s.name.clone())
+                            .unwrap_or_default(),
+                        count: 0,
+                        serialization_format: channel.message_encoding.clone(),
+                        offered_qos_profiles: channel.metadata.get("offered_qos_profiles").cloned(),
+                    });
+            }
+        }
+
+        Ok(topic_map.into_values().collect())
+    }
+
+    fn embedded_schema(&self, type_name: &str) -> RosPeekResult<Option<EmbeddedSchema>> {
+        let Some(summary) = self.summary()? else {
+            return Ok(None);
+        };
+
+        Ok(summary
+            .schemas
+            .values()
+            .find(|s| s.name == type_name)
+            .map(|s| EmbeddedSchema {
+                name: s.name.clone(),
+                encoding: s.encoding.clone(),
+                data: s.data.to_vec(),
+            }))
     }
 
     fn read_messages(&self, topic_name: &str) -> RosPeekResult<Vec<RawMessage>> {
         self.read_messages_range(topic_name, None, None, None, None)
     }
 
+    fn read_messages_multi(&self, topic_names: &[&str]) -> RosPeekResult<Vec<TaggedMessage>> {
+        let tags: HashMap<&str, Arc<str>> = topic_names
+            .iter()
+            .map(|name| (*name, Arc::from(*name)))
+            .collect();
+
+        let chunked = match self.summary()? {
+            Some(summary) => {
+                let channels: HashMap<u16, Arc<str>> = summary
+                    .channels
+                    .values()
+                    .filter_map(|c| Some((c.id, tags.get(c.topic.as_str())?.clone())))
+                    .collect();
+                if let Some(missing) = topic_names
+                    .iter()
+                    .find(|name| !channels.values().any(|tag| &**tag == **name))
+                {
+                    anyhow::bail!("Topic not found: {missing}");
+                }
+
+                let channel_ids: HashSet<u16> = channels.keys().copied().collect();
+                self.read_chunks_parallel(&channel_ids, None, None, None, None)?
+                    .map(|messages| {
+                        messages
+                            .into_iter()
+                            .map(|message| TaggedMessage {
+                                topic: channels[&message.topic_id].clone(),
+                                message,
+                            })
+                            .collect::<Vec<_>>()
+                    })
+            }
+            None => None,
+        };
+
+        let mut results = match chunked {
+            Some(messages) => messages,
+            None => {
+                // fall back to a sequential scan for files without a chunk index
+                let mut messages = Vec::new();
+                for message_result in self.messages()? {
+                    let message = message_result?;
+                    let Some(topic) = tags.get(message.channel.topic.as_str()) else {
+                        continue;
+                    };
+                    messages.push(TaggedMessage {
+                        topic: topic.clone(),
+                        message: RawMessage {
+                            timestamp: message.publish_time,
+                            topic_id: message.channel.id,
+                            data: message.data.into(),
+                        },
+                    });
+                }
+                messages
+            }
+        };
+        // chunks are in file order, which may interleave topics recorded with a delay
+        results.sort_by_key(|m| m.message.timestamp);
+
+        Ok(results)
+    }
+
     fn read_messages_range(
         &self,
         topic_name: &str,
@@ -104,15 +745,24 @@ impl BagReader for McapReader {
         limit: Option<usize>,
         offset: Option<usize>,
     ) -> RosPeekResult<Vec<RawMessage>> {
-        let stream = self.as_stream()?;
+        if let Some(summary) = self.summary()? {
+            let channel_ids = Self::channel_ids(summary, &[topic_name]);
+            if channel_ids.is_empty() {
+                anyhow::bail!("Topic not found: {topic_name}");
+            }
+            if let Some(results) =
+                self.read_chunks_parallel(&channel_ids, start_ns, end_ns, limit, offset)?
+            {
+                return Ok(results);
+            }
+        }
+
+        // fall back to a sequential scan for files without a summary
         let mut results = Vec::new();
         let mut skipped = 0usize;
 
-        for message_result in stream.into_iter() {
-            let message = match message_result {
-                Ok(message) => message,
-                Err(e) => return Err(anyhow::Error::from(e)),
-            };
+        for message_result in self.messages()? {
+            let message = message_result?;
 
             if message.channel.topic != topic_name {
                 continue;
@@ -152,4 +802,95 @@ impl BagReader for McapReader {
 
         Ok(results)
     }
+
+    fn read_messages_by_index(
+        &self,
+        topic_name: &str,
+        start_index: usize,
+        count: usize,
+    ) -> RosPeekResult<Vec<RawMessage>> {
+        if let Some(summary) = self.summary()? {
+            let channel_ids = Self::channel_ids(summary, &[topic_name]);
+            if channel_ids.is_empty() {
+                anyhow::bail!("Topic not found: {topic_name}");
+            }
+            if let Some(results) = self.read_indexed(&channel_ids, start_index, count)? {
+                return Ok(results);
+            }
+        }
+
+        // files without message indexes are scanned up to the requested messages
+        self.read_messages_range(topic_name, None, None, Some(count), Some(start_index))
+    }
+
+    fn next_message(&self, topic_name: &str, after_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        if let Some(channel_ids) = self.indexed_channel_ids(topic_name)?
+            && let Some(next) =
+                self.pick_indexed(&channel_ids, after_ns, |t| (t > after_ns).then_some(t))?
+        {
+            return Ok(next);
+        }
+
+        // files without message indexes are scanned up to the next message
+        let mut messages = self.read_messages_range(
+            topic_name,
+            Some(after_ns.saturating_add(1)),
+            None,
+            Some(1),
+            None,
+        )?;
+        Ok(messages.pop())
+    }
+
+    fn prev_message(&self, topic_name: &str, before_ns: u64) -> RosPeekResult<Option<RawMessage>> {
+        if before_ns == 0 {
+            return Ok(None);
+        }
+        if let Some(channel_ids) = self.indexed_channel_ids(topic_name)?
+            && let Some(prev) = self.pick_indexed(&channel_ids, before_ns, |t| {
+                (t < before_ns).then_some(Reverse(t))
+            })?
+        {
+            return Ok(prev);
+        }
+
+        // files without message indexes are scanned for the messages before the timestamp
+        let mut messages =
+            self.read_messages_range(topic_name, None, Some(before_ns - 1), None, None)?;
+        Ok(messages.pop())
+    }
+
+    fn find_nearest(&self, topic_name: &str, timestamp: u64) -> RosPeekResult<Option<RawMessage>> {
+        if let Some(channel_ids) = self.indexed_channel_ids(topic_name)?
+            && let Some(nearest) = self.pick_indexed(&channel_ids, timestamp, |t| {
+                // prefer the earlier message on ties, like a cursor resting on a message
+                Some((t.abs_diff(timestamp), t))
+            })?
+        {
+            return Ok(nearest);
+        }
+
+        // files without message indexes are scanned for the messages around the timestamp
+        let before = self.prev_message(topic_name, timestamp.saturating_add(1))?;
+        let after = self.next_message(topic_name, timestamp)?;
+        Ok([before, after]
+            .into_iter()
+            .flatten()
+            .min_by_key(|m| m.timestamp.abs_diff(timestamp)))
+    }
+}
+
+/// Iterates over the messages of an MCAP file in file order.
+///
+/// For files still being written, reading stops at the first incomplete record instead of failing.
+fn stream_messages(
+    mmap: &[u8],
+    live: bool,
+) -> RosPeekResult<impl Iterator<Item = RosPeekResult<Message<'_>>>> {
+    let stream = MessageStream::new(mmap)?;
+    Ok(stream.map_while(move |result| match result {
+        Ok(message) => Some(Ok(message)),
+        Err(_) if live => None,
+        Err(e) => Some(Err(e.into())),
+    }))
 }
@@ -0,0 +1,276 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use mcap::{
+    records::{Channel, Record},
+    sans_io::linear_reader::{LinearReadEvent, LinearReader},
+};
+use rospeek_core::{
+    BagReader, BagStats, EmbeddedSchema, IncompleteSign, RawMessage, RosPeekResult, StorageType,
+    Topic,
+};
+
+/// Summary of an MCAP bag read in a single pass over a stream, such as a bag piped through the
+/// standard input.
+///
+/// Only the statistics, topics and sizes of the bag are held in memory, along with the messages
+/// of the topics kept when reading it, so that streamed bags larger than memory can be
+/// summarized. Reading the messages of other topics fails.
+pub struct McapStreamSummary {
+    path: PathBuf,
+    stats: BagStats,
+    topics: Vec<Topic>,
+    sizes: BTreeMap<String, u64>,
+    schemas: Vec<EmbeddedSchema>,
+    /// Messages of the kept topics, in stream order
+    kept: HashMap<String, Vec<RawMessage>>,
+    /// Whether the stream ended without the footer, as when the bag is still being written
+    live: bool,
+}
+
+impl McapStreamSummary {
+    /// Reads a bag from a stream, keeping the messages of a few topics.
+    ///
+    /// # Arguments
+    /// * `input` - The stream to read the bag from.
+    /// * `name` - The name the bag is reported with, such as `-` for the standard input.
+    /// * `keep` - The topics whose messages are kept to be read back, such as `/clock`.
+    pub fn read<R: Read, P: AsRef<Path>>(
+        mut input: R,
+        name: P,
+        keep: &[&str],
+    ) -> RosPeekResult<Self> {
+        let mut reader = LinearReader::new();
+        let mut schemas = HashMap::<u16, EmbeddedSchema>::new();
+        let mut channels = HashMap::<u16, Channel>::new();
+        let mut counts = HashMap::<u16, u64>::new();
+        let mut sizes = HashMap::<u16, u64>::new();
+        let mut kept: HashMap<String, Vec<RawMessage>> = keep
+            .iter()
+            .map(|topic| (topic.to_string(), Vec::new()))
+            .collect();
+        let mut message_count = 0;
+        let mut start_ns = u64::MAX;
+        let mut end_ns = u64::MIN;
+        let mut size_bytes = 0u64;
+        let mut ended = false;
+        let mut complete = false;
+
+        while let Some(event) = reader.next_event() {
+            let event = match event {
+                Ok(event) => event,
+                // a bag still being written ends with an incomplete record
+                Err(_) if ended => break,
+                Err(e) => return Err(e.into()),
+            };
+            match event {
+                LinearReadEvent::ReadRequest(need) => {
+                    if ended {
+                        break;
+                    }
+                    let read = input.read(reader.insert(need))?;
+                    reader.notify_read(read);
+                    size_bytes += read as u64;
+                    ended = read == 0;
+                }
+                LinearReadEvent::Record { opcode, data } => {
+                    match mcap::parse_record(opcode, data)? {
+                        // the summary section repeats the schemas and channels
+                        Record::Schema { header, data } => {
+                            schemas.entry(header.id).or_insert_with(|| EmbeddedSchema {
+                                name: header.name,
+                                encoding: header.encoding,
+                                data: data.into_owned(),
+                            });
+                        }
+                        Record::Channel(channel) => {
+                            channels.entry(channel.id).or_insert(channel);
+                        }
+                        Record::Message { header, data } => {
+                            message_count += 1;
+                            start_ns = start_ns.min(header.log_time);
+                            end_ns = end_ns.max(header.log_time);
+                            *counts.entry(header.channel_id).or_default() += 1;
+                            *sizes.entry(header.channel_id).or_default() += data.len() as u64;
+                            if let Some(messages) = channels
+                                .get(&header.channel_id)
+                                .and_then(|channel| kept.get_mut(&channel.topic))
+                            {
+                                messages.push(RawMessage {
+                                    timestamp: header.publish_time,
+                                    topic_id: header.channel_id,
+                                    data: data.into_owned(),
+                                });
+                            }
+                        }
+                        Record::Footer(_) => complete = true,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let mut topic_map = HashMap::<String, Topic>::new();
+        let mut topic_sizes = BTreeMap::<String, u64>::new();
+        for channel in channels.values() {
+            let count = counts.get(&channel.id).copied().unwrap_or_default();
+            *topic_sizes.entry(channel.topic.clone()).or_default() +=
+                sizes.get(&channel.id).copied().unwrap_or_default();
+            topic_map
+                .entry(channel.topic.clone())
+                .and_modify(|topic| topic.count += count)
+                .or_insert_with(|| Topic {
+                    id: channel.id,
+                    name: channel.topic.clone(),
+                    type_name: schemas
+                        .get(&channel.schema_id)
+                        .map(|s| s.name.clone())
+                        .unwrap_or_default(),
+                    count,
+                    serialization_format: channel.message_encoding.clone(),
+                    offered_qos_profiles: channel.metadata.get("offered_qos_profiles").cloned(),
+                });
+        }
+
+        let path = name.as_ref().to_path_buf();
+        let mut stats = BagStats::new(&path, StorageType::Mcap, message_count, start_ns, end_ns);
+        stats.size_bytes = size_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+        Ok(Self {
+            path,
+            stats,
+            topics: topic_map.into_values().collect(),
+            sizes: topic_sizes,
+            schemas: schemas.into_values().collect(),
+            kept,
+            live: !complete,
+        })
+    }
+}
+
+impl BagReader for McapStreamSummary {
+    fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
+        let file = File::open(path.as_ref())?;
+        Self::read(BufReader::new(file), path, &[])
+    }
+
+    fn incomplete_signs(&self) -> Vec<IncompleteSign> {
+        match self.live {
+            true => vec![IncompleteSign::MissingFooter {
+                file: self.path.display().to_string(),
+            }],
+            false => Vec::new(),
+        }
+    }
+
+    fn stats(&self) -> RosPeekResult<&BagStats> {
+        Ok(&self.stats)
+    }
+
+    fn topics(&self) -> RosPeekResult<Vec<Topic>> {
+        Ok(self.topics.clone())
+    }
+
+    fn topic_sizes(&self) -> RosPeekResult<Option<BTreeMap<String, u64>>> {
+        Ok(Some(self.sizes.clone()))
+    }
+
+    fn embedded_schema(&self, type_name: &str) -> RosPeekResult<Option<EmbeddedSchema>> {
+        Ok(self.schemas.iter().find(|s| s.name == type_name).cloned())
+    }
+
+    fn read_messages(&self, topic_name: &str) -> RosPeekResult<Vec<RawMessage>> {
+        if !self.topics.iter().any(|t| t.name == topic_name) {
+            bail!("Topic not found: {topic_name}");
+        }
+        if let Some(messages) = self.kept.get(topic_name) {
+            return Ok(messages.clone());
+        }
+        bail!(
+            "Messages of {topic_name} are not kept when summarizing {}, save the bag into a file to read them",
+            self.path.display()
+        )
+    }
+}
+
+/// Follower of an MCAP bag being written, which reads the messages appended since the previous
+/// poll.
+///
+/// The file is kept open and read on from the byte offset the previous poll stopped at, so that
+/// a record cut short by the writer is completed on a later poll.
+pub struct McapFollower {
+    file: File,
+    reader: LinearReader,
+    schemas: HashMap<u16, String>,
+    channels: HashMap<u16, Channel>,
+}
+
+impl McapFollower {
+    /// Opens a bag to follow from its first message.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the MCAP file.
+    pub fn open<P: AsRef<Path>>(path: P) -> RosPeekResult<Self> {
+        Ok(Self {
+            file: File::open(path)?,
+            reader: LinearReader::new(),
+            schemas: HashMap::new(),
+            channels: HashMap::new(),
+        })
+    }
+
+    /// Reads the messages written since the previous poll, in file order.
+    ///
+    /// The timestamp of each message is its publish time and its topic ID is its channel ID,
+    /// which [`McapFollower::channel`] resolves.
+    pub fn poll(&mut self) -> RosPeekResult<Vec<RawMessage>> {
+        let mut messages = Vec::new();
+        while let Some(event) = self.reader.next_event() {
+            match event? {
+                LinearReadEvent::ReadRequest(need) => {
+                    let read = self.file.read(self.reader.insert(need))?;
+                    // the end of the file is not notified, the writer may append to it
+                    if read == 0 {
+                        break;
+                    }
+                    self.reader.notify_read(read);
+                }
+                LinearReadEvent::Record { opcode, data } => {
+                    match mcap::parse_record(opcode, data)? {
+                        Record::Schema { header, .. } => {
+                            self.schemas.entry(header.id).or_insert(header.name);
+                        }
+                        Record::Channel(channel) => {
+                            self.channels.entry(channel.id).or_insert(channel);
+                        }
+                        Record::Message { header, data } => messages.push(RawMessage {
+                            timestamp: header.publish_time,
+                            topic_id: header.channel_id,
+                            data: data.into_owned(),
+                        }),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Returns the topic and type names of a channel read so far.
+    ///
+    /// # Arguments
+    /// * `id` - The channel ID, as the topic ID of the messages returned by polling.
+    pub fn channel(&self, id: u16) -> Option<(&str, &str)> {
+        let channel = self.channels.get(&id)?;
+        let type_name = self
+            .schemas
+            .get(&channel.schema_id)
+            .map(String::as_str)
+            .unwrap_or_default();
+        Some((channel.topic.as_str(), type_name))
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, read_to_string},
+    io::BufWriter,
+    path::Path,
+};
+
+use anyhow::bail;
+use mcap::{Writer, records::MessageHeader};
+use rospeek_core::{
+    BagWriter, EmbeddedSchema, RawMessage, RosPeekResult, Topic, find_ros_idl_path,
+};
+
+pub struct McapWriter {
+    writer: Writer<BufWriter<File>>,
+    /// Schema ID for each message type name
+    schemas: HashMap<String, u16>,
+    /// Next sequence number for each channel
+    sequences: HashMap<u16, u32>,
+}
+
+impl McapWriter {
+    /// Registers the schema of a message type once, returning its ID.
+    ///
+    /// The definition given by [`BagWriter::add_schema`] is embedded if any, else the installed
+    /// IDL of the type when it can be found, otherwise the channel has no schema.
+    fn schema_id(&mut self, type_name: &str) -> RosPeekResult<u16> {
+        if let Some(id) = self.schemas.get(type_name) {
+            return Ok(*id);
+        }
+
+        let id = match find_ros_idl_path(type_name).and_then(|p| read_to_string(p).ok()) {
+            Some(idl) => self
+                .writer
+                .add_schema(type_name, "ros2idl", idl.as_bytes())?,
+            None => 0,
+        };
+        self.schemas.insert(type_name.to_string(), id);
+        Ok(id)
+    }
+}
+
+impl BagWriter for McapWriter {
+    fn create<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
+    where
+        Self: Sized,
+    {
+        if path.as_ref().exists() {
+            bail!("Output bag already exists: {}", path.as_ref().display());
+        }
+
+        let fd = File::create(path.as_ref())?;
+        let writer = Writer::new(BufWriter::new(fd))?;
+
+        Ok(Self {
+            writer,
+            schemas: HashMap::new(),
+            sequences: HashMap::new(),
+        })
+    }
+
+    fn append<P: AsRef<Path>>(path: P) -> RosPeekResult<Self>
+    where
+        Self: Sized,
+    {
+        // the summary at the end of the file would have to be rewritten
+        bail!(
+            "Cannot append to MCAP bag: {}, export into a .db3 bag to resume",
+            path.as_ref().display()
+        )
+    }
+
+    fn add_schema(&mut self, schema: &EmbeddedSchema) -> RosPeekResult<()> {
+        if !self.schemas.contains_key(&schema.name) {
+            let id = self
+                .writer
+                .add_schema(&schema.name, &schema.encoding, &schema.data)?;
+            self.schemas.insert(schema.name.clone(), id);
+        }
+        Ok(())
+    }
+
+    fn add_topic(&mut self, topic: &Topic) -> RosPeekResult<u16> {
+        let schema_id = self.schema_id(&topic.type_name)?;
+
+        let mut metadata = BTreeMap::new();
+        if let Some(qos) = &topic.offered_qos_profiles {
+            metadata.insert("offered_qos_profiles".to_string(), qos.clone());
+        }
+
+        let id = self.writer.add_channel(
+            schema_id,
+            &topic.name,
+            &topic.serialization_format,
+            &metadata,
+        )?;
+        Ok(id)
+    }
+
+    fn write_message(&mut self, topic_id: u16, message: &RawMessage) -> RosPeekResult<()> {
+        let sequence = self.sequences.entry(topic_id).or_default();
+        self.writer.write_to_known_channel(
+            &MessageHeader {
+                channel_id: topic_id,
+                sequence: *sequence,
+                log_time: message.timestamp,
+                publish_time: message.timestamp,
+            },
+            &message.data,
+        )?;
+        *sequence += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> RosPeekResult<()> {
+        self.writer.finish()?;
+        Ok(())
+    }
+}
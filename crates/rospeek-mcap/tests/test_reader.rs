@@ -0,0 +1,237 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use mcap::{WriteOptions, records::MessageHeader};
+use rospeek_core::BagReader;
+use rospeek_mcap::{McapFollower, McapReader, McapStreamSummary};
+
+/// Writes a bag of a single topic from the publish and log times of its messages.
+///
+/// # Arguments
+/// * `path` - The path to the bag to write.
+/// * `times` - The publish and log times of each message, in file order.
+/// * `chunk_size` - Size of the chunks, `None` to write all messages into a single chunk.
+fn write_bag(path: &Path, times: &[(u64, u64)], chunk_size: Option<u64>) {
+    let file = File::create(path).expect("Failed to create bag");
+    let mut writer = WriteOptions::new()
+        .chunk_size(chunk_size)
+        .create(BufWriter::new(file))
+        .expect("Failed to create writer");
+    let channel = writer
+        .add_channel(0, "/times", "cdr", &BTreeMap::new())
+        .expect("Failed to add channel");
+    for (sequence, (publish_time, log_time)) in times.iter().enumerate() {
+        writer
+            .write_to_known_channel(
+                &MessageHeader {
+                    channel_id: channel,
+                    sequence: sequence as u32,
+                    log_time: *log_time,
+                    publish_time: *publish_time,
+                },
+                &[0x00, 0x01, 0x00, 0x00, sequence as u8],
+            )
+            .expect("Failed to write message");
+    }
+    writer.finish().expect("Failed to finish bag");
+}
+
+#[test]
+fn test_find_nearest_publish_time() {
+    let dir = std::env::temp_dir().join(format!("rospeek-mcap-nearest-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    // the second message is logged long after it was published, closer to 26 than the third
+    let path = dir.join("nearest.mcap");
+    write_bag(&path, &[(10, 10), (20, 29), (30, 31)], None);
+    let reader = McapReader::open(&path).expect("Failed to open bag");
+
+    let nearest = reader
+        .find_nearest("/times", 26)
+        .expect("Failed to find nearest")
+        .expect("No nearest message");
+    assert_eq!(nearest.timestamp, 30);
+    assert_eq!(nearest.data[4], 2);
+    // equally close messages resolve to the earlier one
+    let nearest = reader
+        .find_nearest("/times", 25)
+        .expect("Failed to find nearest")
+        .expect("No nearest message");
+    assert_eq!(nearest.timestamp, 20);
+    assert!(reader.find_nearest("/missing", 25).is_err());
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
+
+#[test]
+fn test_next_prev_message() {
+    let dir = std::env::temp_dir().join(format!("rospeek-mcap-next-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    // a chunk per message, the second one logged long after it was published
+    let path = dir.join("next.mcap");
+    write_bag(&path, &[(10, 10), (20, 29), (30, 31), (40, 40)], Some(1));
+    let reader = McapReader::open(&path).expect("Failed to open bag");
+
+    let next = |after: u64| {
+        reader
+            .next_message("/times", after)
+            .expect("Failed to read next message")
+            .map(|m| m.timestamp)
+    };
+    assert_eq!(next(0), Some(10));
+    assert_eq!(next(10), Some(20));
+    assert_eq!(next(20), Some(30));
+    assert_eq!(next(35), Some(40));
+    assert_eq!(next(40), None);
+
+    let prev = |before: u64| {
+        reader
+            .prev_message("/times", before)
+            .expect("Failed to read previous message")
+            .map(|m| m.timestamp)
+    };
+    assert_eq!(prev(0), None);
+    assert_eq!(prev(10), None);
+    assert_eq!(prev(21), Some(20));
+    assert_eq!(prev(30), Some(20));
+    assert_eq!(prev(u64::MAX), Some(40));
+    assert!(reader.next_message("/missing", 0).is_err());
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
+
+#[test]
+fn test_pick_message_logged_late() {
+    let dir = std::env::temp_dir().join(format!("rospeek-mcap-late-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    // a chunk per message, the second one logged far from the chunks around its publish time
+    let path = dir.join("late.mcap");
+    write_bag(&path, &[(10, 10), (20, 1000), (30, 31), (40, 40)], Some(1));
+    let reader = McapReader::open(&path).expect("Failed to open bag");
+
+    let next = reader
+        .next_message("/times", 15)
+        .expect("Failed to read next message")
+        .map(|m| m.timestamp);
+    assert_eq!(next, Some(20));
+    let prev = reader
+        .prev_message("/times", 25)
+        .expect("Failed to read previous message")
+        .map(|m| m.timestamp);
+    assert_eq!(prev, Some(20));
+    let nearest = reader
+        .find_nearest("/times", 22)
+        .expect("Failed to find nearest")
+        .expect("No nearest message");
+    assert_eq!(nearest.timestamp, 20);
+    assert_eq!(nearest.data[4], 1);
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
+
+#[test]
+fn test_stream_summary() {
+    let dir = std::env::temp_dir().join(format!("rospeek-mcap-stream-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    let path = dir.join("stream.mcap");
+    write_bag(&path, &[(10, 10), (20, 29), (30, 31)], Some(1));
+    let bytes = std::fs::read(&path).expect("Failed to read bag");
+
+    let summary = McapStreamSummary::read(bytes.as_slice(), "-", &["/times"])
+        .expect("Failed to summarize bag");
+    assert_eq!(
+        summary.stats().expect("Failed to read stats").message_count,
+        3
+    );
+    assert_eq!(summary.stats().expect("Failed to read stats").start_ns, 10);
+    assert_eq!(summary.stats().expect("Failed to read stats").end_ns, 31);
+    let topics = summary.topics().expect("Failed to read topics");
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].name, "/times");
+    assert_eq!(topics[0].count, 3);
+    let sizes = summary
+        .topic_sizes()
+        .expect("Failed to read sizes")
+        .expect("No sizes");
+    assert_eq!(sizes["/times"], 15);
+    let timestamps: Vec<u64> = summary
+        .read_messages("/times")
+        .expect("Failed to read messages")
+        .iter()
+        .map(|m| m.timestamp)
+        .collect();
+    assert_eq!(timestamps, vec![10, 20, 30]);
+    assert!(summary.incomplete_signs().is_empty());
+
+    // messages of topics which are not kept are not held in memory
+    let summary =
+        McapStreamSummary::read(bytes.as_slice(), "-", &[]).expect("Failed to summarize bag");
+    assert!(summary.read_messages("/times").is_err());
+    assert!(summary.read_messages("/missing").is_err());
+
+    // a bag still being written ends without its footer
+    let summary = McapStreamSummary::read(&bytes[..bytes.len() - 16], "-", &[])
+        .expect("Failed to summarize bag");
+    assert_eq!(
+        summary.stats().expect("Failed to read stats").message_count,
+        3
+    );
+    assert_eq!(summary.incomplete_signs().len(), 1);
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}
+
+#[test]
+fn test_follow_appended_messages() {
+    let dir = std::env::temp_dir().join(format!("rospeek-mcap-follow-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+
+    let path = dir.join("full.mcap");
+    write_bag(&path, &[(10, 10), (20, 20), (30, 30)], Some(1));
+    let bytes = std::fs::read(&path).expect("Failed to read bag");
+
+    // the recorder has written the bag up to the middle of a record
+    let live = dir.join("live.mcap");
+    let cut = bytes.len() / 2;
+    std::fs::write(&live, &bytes[..cut]).expect("Failed to write bag");
+    let mut follower = McapFollower::open(&live).expect("Failed to open bag");
+    let first: Vec<u64> = follower
+        .poll()
+        .expect("Failed to poll")
+        .iter()
+        .map(|m| m.timestamp)
+        .collect();
+    assert!(first.len() < 3);
+    assert!(follower.poll().expect("Failed to poll").is_empty());
+
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&live)
+        .and_then(|mut file| file.write_all(&bytes[cut..]))
+        .expect("Failed to append to bag");
+    let rest = follower.poll().expect("Failed to poll");
+    let timestamps: Vec<u64> = first
+        .into_iter()
+        .chain(rest.iter().map(|m| m.timestamp))
+        .collect();
+    assert_eq!(timestamps, vec![10, 20, 30]);
+    assert_eq!(
+        follower.channel(rest[0].topic_id).map(|(topic, _)| topic),
+        Some("/times")
+    );
+    assert!(follower.channel(u16::MAX).is_none());
+
+    std::fs::remove_dir_all(&dir).expect("Failed to remove temp dir");
+}